@@ -7,6 +7,8 @@ mod pipeline;
 pub use pipeline::*;
 mod tile_map;
 pub use tile_map::*;
+// re-export tiled, so object layers can be read without depending on it directly
+pub use tiled;
 
 /// Adds support for GLTF file loading to Apps
 #[derive(Default)]
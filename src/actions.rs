@@ -0,0 +1,931 @@
+// actions module
+// the action queue each person works through, and the systems that run each action
+use bevy::prelude::*;
+use bevy::diagnostic::Diagnostics;
+use std::collections::{HashMap, HashSet};
+use std::time::Instant;
+use bevy_rapier2d::rapier::dynamics::RigidBodySet;
+use bevy_rapier2d::rapier::geometry::ColliderSet;
+use bevy_rapier2d::rapier::pipeline::QueryPipeline;
+use rand::Rng;
+
+use crate::{
+    ACTION_STAGE, BEHAVIOUR_STAGE, combat_query, FLANK_CRIT_BONUS, GameRng, GRENADE_FUSE,
+    HIGH_GROUND_ACCURACY, HIGH_GROUND_RANGE, Id, MOVE_EASE_DISTANCE, Position, SNEAK_SPEED,
+    Velocity, WADING_ACCURACY,
+};
+use crate::animation::{AnimationType, SpriteData};
+use crate::behaviour::Behaviour;
+use crate::bench::RUN_ACTION_SYSTEM_TIME;
+use crate::combat::{AttackEvent, DamageEvent, DamageType, Perks, StatusEffects, Suppression};
+use crate::config::GameConfig;
+use crate::control::MoveSpeed;
+use crate::elevation::{Elevation, has_high_ground};
+use crate::nerve::{Action, ActionType, Nerve, TargetGroup};
+use crate::perception::Perception;
+use crate::person::{Command, Controlled};
+use crate::stamina::Stamina;
+use crate::stance::Sneaking;
+use crate::tick::AddTickSystem;
+use crate::water::Wading;
+
+// actions plugin
+// responsible for implementing/managing an interface that allows for lower level control of entities
+pub struct ActionsPlugin;
+
+// boilerplate code for the plugin
+impl Plugin for ActionsPlugin {
+    fn build(&self, app: &mut AppBuilder){
+        // add in the interact and throw events, sent by interact and distract actions
+        app.add_tick_event::<InteractEvent>()
+        .add_tick_event::<ThrowEvent>()
+        // add in the action completed and failed events, sent for every action carried through to the end or given up on
+        .add_tick_event::<ActionCompletedEvent>()
+        .add_tick_event::<ActionFailedEvent>()
+        // add in the run action system
+        .add_tick_system_to_stage(ACTION_STAGE, run_action_system.system())
+        // add in the action completed system
+        .add_tick_system_to_stage(ACTION_STAGE, action_completed_system.system())
+        // add in the system clearing orders targeting anything that's gone, at the start of the tick
+        .add_tick_system_to_stage(BEHAVIOUR_STAGE, clear_missing_targets_system.system())
+        // add in the system interrupting passive actions when taking damage, before behaviours are picked
+        // damage events are kept for a tick, so none dealt the tick before is missed
+        .init_resource::<DamageInterruptState>()
+        .add_tick_system_to_stage(BEHAVIOUR_STAGE, damage_interrupt_system.system());
+    }
+}
+
+// action completed event
+// sent whenever an entity carries an action through to the end, rather than skipping it or giving up on it
+pub struct ActionCompletedEvent {
+    // id of the entity that carried out the action
+    pub id: String,
+    // the action carried out
+    pub action: Action,
+}
+
+// action failure
+// why an action couldn't be carried out
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ActionFailure {
+    // the action targets an entity that no longer exists, e.g. it has died or been despawned
+    TargetMissing,
+    // the action was given neither a point nor an entity to target
+    NoTarget,
+    // the action was put a stop to part way through, e.g. by taking damage during a passive action
+    Interrupted,
+}
+
+// action failed event
+// sent whenever an entity gives up on an action because it couldn't be carried out
+pub struct ActionFailedEvent {
+    // id of the entity that was carrying out the action
+    pub id: String,
+    // the action given up on
+    pub action: Action,
+    // why it was given up on
+    pub reason: ActionFailure,
+}
+
+// action completed system
+// sends out every action completed or failed since it last ran
+pub fn action_completed_system(mut events: ResMut<Events<ActionCompletedEvent>>, mut failed_events: ResMut<Events<ActionFailedEvent>>, mut query: Query<(&Id, &mut Nerve)>) {
+    for (id, mut actions) in &mut query.iter() {
+        for action in actions.completed.drain(..) {
+            events.send(ActionCompletedEvent {
+                id: id.id(),
+                action: action,
+            });
+        }
+        for (action, reason) in actions.failed.drain(..) {
+            failed_events.send(ActionFailedEvent {
+                id: id.id(),
+                action: action,
+                reason: reason,
+            });
+        }
+    }
+}
+
+// damage interrupt state holds an event reader for damage events
+#[derive(Default)]
+pub struct DamageInterruptState {
+    pub event_reader: EventReader<DamageEvent>,
+}
+
+// damage interrupt system
+// puts a stop to the passive actions of anyone taking damage, e.g. waiting, healing or patrolling,
+// and has anyone with a behaviour think again about what to do
+pub fn damage_interrupt_system(mut state: ResMut<DamageInterruptState>, damages: Res<Events<DamageEvent>>, mut query: Query<(&Id, &mut Nerve, Option<&mut Behaviour>)>) {
+    // the ids of everyone hurt since this last ran
+    let mut hurt = HashSet::new();
+    for damage in state.event_reader.iter(&damages) {
+        hurt.insert(damage.target_id.clone());
+    }
+    if hurt.is_empty() {
+        return;
+    }
+
+    for (id, mut actions, behav) in &mut query.iter() {
+        if !hurt.contains(&id.id()) {
+            continue;
+        }
+        if actions.interrupt_action() {
+            if let Some(mut behav) = behav {
+                behav.reevaluate = true;
+            }
+        }
+    }
+}
+
+// gives where an action's target is, from where everything is by id
+// a target entity takes precedence over a target point
+pub fn resolve_target(target: &(Option<(f32, f32)>, Option<String>), positions: &HashMap<String, (f32, f32)>) -> Result<(f32, f32), ActionFailure> {
+    match target {
+        (_, Some(tid)) => positions.get(tid).cloned().ok_or(ActionFailure::TargetMissing),
+        (Some(point), None) => Ok(*point),
+        (None, None) => Err(ActionFailure::NoTarget),
+    }
+}
+
+// resolved group
+// what a target group comes down to, from where the entity acting on it stands
+pub struct ResolvedGroup {
+    // the point to head for
+    pub position: (f32, f32),
+    // the entity picked out of the group, if the group picks one out
+    pub target_id: Option<String>,
+    // how far the group reaches out from the point, so ranges can be kept to all of it
+    pub spread: f32,
+}
+
+// gives what a target group comes down to from a given position, from where everything is by id
+// members of a group that no longer exist are left out, the group is only missing once all of them are gone
+pub fn resolve_group(group: &TargetGroup, from: (f32, f32), positions: &HashMap<String, (f32, f32)>) -> Result<ResolvedGroup, ActionFailure> {
+    let distance = |a: (f32, f32), b: (f32, f32)| Vec2::new(a.0 - b.0, a.1 - b.1).length();
+    match group {
+        TargetGroup::Area(centre, radius) => Ok(ResolvedGroup {
+            position: *centre,
+            target_id: None,
+            spread: *radius,
+        }),
+        TargetGroup::Any(ids) | TargetGroup::All(ids) => {
+            if ids.is_empty() {
+                return Err(ActionFailure::NoTarget);
+            }
+            let members: Vec<(&String, (f32, f32))> = ids.iter()
+                .filter_map(|id| positions.get(id).map(|&position| (id, position)))
+                .collect();
+            // the closest member is picked out
+            let (closest, closest_pos) = members.iter()
+                .min_by(|a, b| distance(a.1, from).partial_cmp(&distance(b.1, from)).unwrap_or(std::cmp::Ordering::Equal))
+                .cloned()
+                .ok_or(ActionFailure::TargetMissing)?;
+            match group {
+                // the whole group is covered from the middle of it
+                TargetGroup::All(_) => {
+                    let count = members.len() as f32;
+                    let centre = (
+                        members.iter().map(|member| (member.1).0).sum::<f32>() / count,
+                        members.iter().map(|member| (member.1).1).sum::<f32>() / count,
+                    );
+                    let spread = members.iter().map(|member| distance(member.1, centre)).fold(0.0, f32::max);
+                    Ok(ResolvedGroup {
+                        position: centre,
+                        target_id: Some(closest.clone()),
+                        spread: spread,
+                    })
+                },
+                _ => Ok(ResolvedGroup {
+                    position: closest_pos,
+                    target_id: Some(closest.clone()),
+                    spread: 0.0,
+                }),
+            }
+        },
+    }
+}
+
+// checks whether an order still makes sense, i.e. it doesn't target an entity that no longer exists
+pub fn command_target_exists(command: &Command, ids: &HashSet<String>) -> bool {
+    command.target_id.as_ref().map_or(true, |tid| ids.contains(tid))
+}
+
+// clear missing targets system
+// drops any orders, given or waiting, that target an entity that no longer exists
+pub fn clear_missing_targets_system(mut ids_query: Query<&Id>, mut query: Query<&mut Controlled>) {
+    let mut ids = HashSet::new();
+    for id in &mut ids_query.iter() {
+        ids.insert(id.id());
+    }
+    for mut state in &mut query.iter() {
+        if !command_target_exists(&state.current_command, &ids) {
+            state.current_command = Command::default();
+        }
+        state.command_queue.retain(|command| command_target_exists(command, &ids));
+    }
+}
+
+// throw event
+// sent whenever a distract or grenade action throws something
+pub struct ThrowEvent {
+    // id of the entity throwing
+    pub thrower_id: String,
+    // where the throw is from
+    pub from: (f32, f32),
+    // where the thrown object lands
+    pub to: (f32, f32),
+    // seconds the fuse burns for once it lands, if it's a grenade
+    pub fuse: Option<f32>,
+}
+
+// interact event
+// sent whenever an interact action finishes operating an object
+pub struct InteractEvent {
+    // id of the entity doing the interacting
+    pub actor_id: String,
+    // id of the object interacted with
+    pub target_id: String,
+}
+
+// gives the velocity to move towards a target point at, going at the given speed
+// and slowing down on the way in to the target
+pub fn get_straightline_velocity(target: (f32, f32), curr: (f32, f32), speed: f32) -> Vec2 {
+    // get the distance vector from the player to the move point
+    let dist_vector = Vec2::new(target.0 - curr.0, target.1 - curr.1);
+    // the length of the distance vector is the distance between the two points
+    let dist = dist_vector.length();
+    // if distance is 0 then the velocity vector is 0
+    let mut new_vel = Vec2::new(0.0, 0.0);
+    // otherwise if distance is greater than 0
+    if dist > 0.0 {
+        // divide the distance by the distance factor and cap at 1.0
+        let ease_input = (dist / MOVE_EASE_DISTANCE).min(1.0);
+
+        // new velocity vector is a rescaled exponential applied to the normalized distance vector
+        // the result is that speed is based on distance and varies according to an exponential curve
+        // and the velocity is always towards the move point
+        // if pathfinding is implemented for the player, then this will need to be changed
+        new_vel = ezing::expo_out( ease_input ) * speed * dist_vector.normalize();
+    }
+
+    // if the new x-velocity has insignificant magnitude,
+    // just set x-vel to the distance to target x
+    // this is to avoid sliding
+    if new_vel[0].abs() < 1.0 {
+        new_vel[0] = dist_vector[0];        
+    }
+    // if the new y-velocity has insignificant magnitude,
+    // just set y-vel to the distance to target y
+    // this is to avoid sliding
+    if new_vel[1].abs() < 1.0 {
+        new_vel[1] = dist_vector[1];
+    }
+    
+    new_vel
+}
+
+// close enough function
+// checks if two floats are within a certain threshold of each other
+// this function should be used whenever we want to measure distances and 
+// establish distance thresholds, due to the way that velocity and moving 
+// is implemented. otherwise, we have an achilles and the tortoise situation
+// where an entity will asymptotically approach a border but never actually 
+// reach it
+pub fn close_enough (x: f32, y: f32, enough: f32) -> bool {
+    if (x - y).abs() < enough {
+        true
+    }else{
+        false
+    }
+}
+
+// run action system
+// responsible for implementing the various actions used for lower level control of entities
+pub fn run_action_system(config: Res<GameConfig>, time: Res<Time>, mut diagnostics: ResMut<Diagnostics>, mut rng: ResMut<GameRng>, mut attack_events: ResMut<Events<AttackEvent>>,
+    mut interact_events: ResMut<Events<InteractEvent>>, mut throw_events: ResMut<Events<ThrowEvent>>, pipeline: Res<QueryPipeline>, bodies: Res<RigidBodySet>, colliders: Res<ColliderSet>, mut query: Query<(&mut Nerve, &Id, &Position, &mut Velocity, &mut SpriteData, Option<&MoveSpeed>, Option<&Sneaking>, Option<&StatusEffects>, Option<&Suppression>, Option<&Stamina>, Option<&Perks>, Option<&Wading>)>, mut ent_query: Query<(&Id, &Position, Option<&Elevation>)>,
+    mut perception_query: Query<(&Id, &Perception)>) {
+    // start timing this system
+    let timer = Instant::now();
+
+    // where everything is, by id, for finding the targets of actions, and how high up
+    let mut positions = HashMap::new();
+    let mut heights = HashMap::new();
+    for (eid, pos, elevation) in &mut ent_query.iter() {
+        positions.insert(eid.id(), (pos.0, pos.1));
+        heights.insert(eid.id(), elevation.map_or(0.0, |elevation| elevation.0));
+    }
+
+    // go through all entities with a brain, position, and velocity
+    for (mut actions, id, pos, mut vel, mut sprite, speed, sneaking, effects, suppression, stamina, perks, wading) in &mut query.iter() {
+        // stunned entities stand still, with their actions held until the stun wears off
+        if effects.map_or(false, |effects| effects.stunned()) {
+            vel.0 = 0.0;
+            vel.1 = 0.0;
+            sprite.animation_type = AnimationType::Idle;
+            continue;
+        }
+
+        // get the current action
+        let action = actions.current_action.clone();
+
+        // check the action type
+        match action.action_type {
+            // move actions will move the entity to a stationary point
+            ActionType::Move => {
+                
+                sprite.animation_type = AnimationType::Move;
+
+                // check if the targeted id is the same as this id
+                if action.target.1 == Some(id.id()) {
+                    // if this move action is self-targeted
+                    // skip it (it's pointless)
+                    // this is important to do in cases where
+                    // ordinarily the move isn't popped when at rest
+                    // e.g. follow commands
+
+                    // pop actions queue and ready next action
+                    actions.advance();
+                    continue;
+                }
+
+                // find where the target is, giving up on the move if it's gone or there never was one
+                // a target group is headed for as a whole, and spreads out around the point headed for
+                let resolved = match &action.group {
+                    Some(group) => resolve_group(group, (pos.0, pos.1), &positions).map(|group| (group.position, group.spread)),
+                    None => resolve_target(&action.target, &positions).map(|target| (target, 0.0)),
+                };
+                let (mut move_to, spread) = match resolved {
+                    Ok(resolved) => resolved,
+                    Err(reason) => {
+                        actions.fail_action(reason);
+                        continue;
+                    },
+                };
+
+                // keep to an offset from a target entity, e.g. a formation slot
+                if action.target.1.is_some() && action.group.is_none() {
+                    if let Some(params) = &action.params {
+                        if let (Some(&ox), Some(&oy)) = (params.get("offset_x"), params.get("offset_y")) {
+                            move_to = (move_to.0 + ox, move_to.1 + oy);
+                        }
+                    }
+                }
+
+                // these parameters are technically optional, however
+                // if not defined no movement will occur
+                // range defaults to None
+                let mut range = None;
+                // min_range defaults to None
+                let mut min_range = None;
+                // no_skip defaults to None
+                let mut no_skip = None;
+
+                // check if additional parameters were passed in with the action
+                if let Some(params) = &action.params {
+                    // get range parameter from hashmap
+                    range = params.get("range").cloned();
+                    // get min_range parameter from hashmap
+                    min_range = params.get("min_range").cloned();
+                    // get no_skip parameter from hashmap
+                    no_skip = params.get("no_skip").cloned();
+                }
+
+                // moves on a target group head for the point it comes down to if no range was given,
+                // and otherwise keep the range to the whole of the group, so it's narrowed by how far the group spreads out
+                if action.group.is_some() {
+                    let group_range = (range.unwrap_or(0.0) - spread).max(0.0);
+                    range = Some(group_range);
+                    // a group too spread out to stay far enough from all of it is kept as far away as the range allows
+                    min_range = min_range.map(|min_range| (min_range + spread).min(group_range));
+                }
+
+                // flag to check if move vector should be used
+                // i.e. if position needs to be adjusted
+                let mut use_move_vector = false;
+                
+                // check for parameter relational validity
+                if let Some(range) = range {
+                    if let Some(min_range) = min_range {
+                        // if minimum range to launch the attack is greater than the maximmum range
+                        // then give an error -> this needs to be handled and the action skipped
+                        // but the player must also get a notification that this is an invalid action
+                        if min_range > range {
+                            // current behaviour causes the program to panic
+                            panic!("min_range > range, need to implement warning system visible to players")
+                        }
+                    }
+                }
+
+                // get vector to target from current position
+                let target_vector = Vec2::new(move_to.0 - pos.0, move_to.1 - pos.1);    
+                // get distance between two points
+                let dist = target_vector.length();
+                // get normalized target vector
+                let target_dir = target_vector.normalize();
+                
+                // check if min_range was specified
+                if let Some(min_range) = min_range {
+                    // check if the entity is within the minimum range
+                    if dist < min_range {
+                        // if so
+                        // get new target vector to appropriate range
+                        let new_target_vector = target_dir * (dist - min_range);
+                        // add target vector to current position vector to get
+                        // the target coordinate
+                        let edge_vector = new_target_vector + Vec2::new(pos.0, pos.1);
+                        // update the target coordinates
+                        move_to.0 = edge_vector[0];
+                        move_to.1 = edge_vector[1];
+
+                        // position must be adjusted
+                        // set flag
+                        use_move_vector = true;
+                    }    
+                }
+                
+                // check if range was specified
+                if let Some(range) = range {
+                    // check if the entity is beyond the maximum range
+                    if dist > range {
+                        // if so
+                        // get new target vector to appropriate range
+                        let new_target_vector = target_dir * (dist - range);
+                        // add target vector to current position vector to get 
+                        // the target coordinate
+                        let edge_vector = new_target_vector + Vec2::new(pos.0, pos.1);
+                        // update the target coordinates
+                        move_to.0 = edge_vector[0];
+                        move_to.1 = edge_vector[1];
+                        
+                        // position must be adjusted
+                        // set flag
+                        use_move_vector = true;
+                    }
+                }
+
+                if use_move_vector {
+                    // only calculate velocity if velocity needs to be adjusted
+
+                    // retrieve new straightline velocity to position
+                    // at however fast this entity moves right now, sped up by perks and slowed down while sneaking
+                    let move_speed = speed.cloned().unwrap_or_default().current(stamina)
+                        * perks.map_or(1.0, |perks| perks.speed_multiplier())
+                        * if sneaking.is_some() { SNEAK_SPEED } else { 1.0 };
+                    let new_vel = get_straightline_velocity(move_to, (pos.0, pos.1), move_speed);
+                
+                    // set the velocity vector to use the new velocity vector
+                    vel.0 = new_vel[0];
+                    vel.1 = new_vel[1];
+                }
+
+                // check if this move can be skipped
+                let mut can_skip = true;
+                // check no_skip parameter to see if this move can be skipped
+                if let Some(no_skip) = no_skip {
+                    // if no_skip is positive
+                    if no_skip > 0.0 {
+                        // set can_skip to false
+                        can_skip = false;
+                    }
+                }
+
+                // if no longer moving
+                if vel.0.abs() < 1.0 && vel.1.abs() < 1.0 && can_skip {
+                    // pop actions queue and ready next action
+                    actions.complete_action();
+                }else{
+                    
+                }
+            },
+            // attack actions will attack a targeted entity
+            ActionType::Attack => {
+                // set to use attack animation
+                sprite.animation_type = AnimationType::Attack;
+
+                // find where the target is, giving up on the attack if it's gone
+                // attacks are only ever made on entities, never on points,
+                // so a target group has to pick one out, the closest of whoever is left
+                let resolved = match &action.group {
+                    Some(group) => resolve_group(group, (pos.0, pos.1), &positions)
+                        .and_then(|group| group.target_id.ok_or(ActionFailure::NoTarget)),
+                    None => action.target.1.clone().ok_or(ActionFailure::NoTarget),
+                }.and_then(|target_id| resolve_target(&(None, Some(target_id.clone())), &positions).map(|target| (target_id, target)));
+                let (target_id, target_pos) = match resolved {
+                    Ok(resolved) => resolved,
+                    Err(reason) => {
+                        actions.fail_action(reason);
+                        continue;
+                    },
+                };
+
+                // the attack is also over once the target has got further away than
+                // the attacker is willing to chase, if a max_chase parameter was given
+                let max_chase = action.params.as_ref().and_then(|params| params.get("max_chase").cloned());
+                let target_dist = Vec2::new(target_pos.0 - pos.0, target_pos.1 - pos.1).length();
+                if max_chase.map_or(false, |max_chase| target_dist > max_chase) {
+                    // pop actions queue and ready next action
+                    actions.advance();
+                    continue;
+                }
+
+                // attacks from the high ground reach further and hit more often
+                let height = |id: &String| heights.get(id).cloned().unwrap_or(0.0);
+                let high_ground = has_high_ground(height(&id.id()), height(&target_id));
+
+                // these parameters are technically optional, however
+                // if not defined the attack will never go out of range
+                // range defaults to None
+                let mut range = None;
+                // min_range defaults to None
+                let mut min_range = None;
+
+                // check if additional parameters were passed in with the action
+                if let Some(params) = &action.params {
+                    // get range parameter from hashmap
+                    range = params.get("range");
+                    // get min_range parameter from hashmap
+                    min_range = params.get("min_range");
+                }
+
+                // reattach flag, indicates whether or not
+                // the attacker needs to enter optimal range again
+                let mut reattach = false;
+
+                // check if range was specified
+                if let Some(&range) = range {
+                    let range = if high_ground { range * HIGH_GROUND_RANGE } else { range };
+                    // get the distance vector from the player to the move point
+                    let dist_vector = Vec2::new(target_pos.0 - pos.0, target_pos.1 - pos.1);
+                    // the length of the distance vector is the distance between the two points
+                    let dist = dist_vector.length();
+                    // check if min_range was specified
+                    if let Some(&min_range) = min_range {
+                        // if minimum range to launch the attack is greater than the maximmum range
+                        // then give an error -> this needs to be handled and the action skipped
+                        // but the player must also get a notification that this is an invalid action
+                        if min_range > range {
+                            // current behaviour causes the program to panic
+                            panic!("min_range > range, need to implement warning system visible to players")
+                        }
+                        // check if the entity is within the minimum range to launch the attack
+                        // additional check to see if entity is barely on the border for minimum
+                        // range - this is here because of the way that velocity is implemented
+                        // we have an achilles and the tortoise type situation that makes it 
+                        // difficult to actually get the entity exactly at the target point
+                        if dist < min_range && !close_enough(dist, min_range, 1.0) {
+                            // if so
+                            // flag for reattachment
+                            reattach = true;
+                        }    
+                    }
+                    // check if the entity is beyond the maximum range to launch the attack
+                    // additional check to see if entity is barely on the border for maximum
+                    // range - this is here because of the way that velocity is implemented
+                    // we have an achilles and the tortoise type situation that makes it 
+                    // difficult to actually get the entity exactly at the target point
+                    if dist > range && !close_enough(dist, range, 1.0) {
+                        // if so
+                        // flag for reattachment
+                        reattach = true;
+                    }
+                }
+
+                // check if flagged for reattachment
+                if reattach {
+                    // if so
+                    // check the front of the action queue
+                    match actions.peek() {
+                        // if action queue is empty
+                        None => {
+                            // retrack target
+                            actions.action_queue.push_back(Action {
+                                action_type: ActionType::Move,
+                                target: action.target.clone(),
+                                params: action.params.clone(),
+                                group: action.group.clone(),
+                            });
+                            // attack target once target is tracked
+                            actions.action_queue.push_back(Action {
+                                action_type: ActionType::Attack,
+                                target: action.target.clone(),
+                                params: action.params.clone(),
+                                group: action.group.clone(),
+                            });
+
+                            // pop current action and move to next
+                            actions.advance();
+                        },
+                        // otherwise
+                        _ => {
+                            // if there are still actions in the queue
+                            // assume that they override pressing the attack
+                        }
+                    }
+                }else{
+                    // still in range, so the attack can be launched
+                    // the action timer is used as the cooldown between attacks
+                    let ready = match &mut actions.action_timer {
+                        Some(timer) => {
+                            timer.tick(time.delta_seconds);
+                            timer.finished
+                        },
+                        None => true,
+                    };
+
+                    // attacks can only be launched with a clear line of sight to the target
+                    let in_sight = combat_query::line_of_sight(&config, &pipeline, &bodies, &colliders, (pos.0, pos.1), target_pos);
+
+                    if ready && in_sight {
+                        // cooldown defaults to one second
+                        let mut cooldown = 1.0;
+                        // knockback defaults to no knockback
+                        let mut knockback = 0.0;
+                        // damage defaults to no damage
+                        let mut damage = 0.0;
+                        // accuracy defaults to always hitting
+                        let mut accuracy = 1.0;
+                        // damage defaults to never varying, and attacks default to never being critical
+                        let mut spread = 0.0;
+                        let mut crit_chance = 0.0;
+                        let mut crit_multiplier = 1.0;
+                        // damage defaults to ballistic
+                        let mut damage_type = DamageType::default();
+                        if let Some(params) = &action.params {
+                            if let Some(&c) = params.get("cooldown") {
+                                cooldown = c;
+                            }
+                            if let Some(&k) = params.get("knockback") {
+                                knockback = k;
+                            }
+                            if let Some(&d) = params.get("damage") {
+                                damage = d;
+                            }
+                            if let Some(&a) = params.get("accuracy") {
+                                accuracy = a;
+                            }
+                            if let Some(&s) = params.get("spread") {
+                                spread = s;
+                            }
+                            if let Some(&c) = params.get("crit_chance") {
+                                crit_chance = c;
+                            }
+                            if let Some(&m) = params.get("crit_multiplier") {
+                                crit_multiplier = m;
+                            }
+                            if let Some(&t) = params.get("damage_type") {
+                                damage_type = DamageType::from_index(t as usize);
+                            }
+                        }
+                        // suppressed, exhausted and wading attackers have a harder time aiming,
+                        // and attackers on the high ground an easier one
+                        accuracy *= suppression.map_or(1.0, |suppression| suppression.accuracy_multiplier())
+                            * stamina.map_or(1.0, |stamina| stamina.accuracy_multiplier())
+                            * wading.map_or(1.0, |_wading| WADING_ACCURACY)
+                            * if high_ground { HIGH_GROUND_ACCURACY } else { 1.0 };
+
+                        // direction from the attacker to the victim
+                        let dir = Vec2::new(target_pos.0 - pos.0, target_pos.1 - pos.1);
+                        let dir = if dir.length() > 0.0 { dir.normalize() } else { dir };
+
+                        // roll through the shared random number generator, so seeded runs play out the same way
+                        let hit = accuracy >= 1.0 || rng.0.gen::<f32>() < accuracy;
+
+                        // attacks from outside the target's vision cone are flanking, and more likely to be critical
+                        let mut flanking = false;
+                        for (pid, perception) in &mut perception_query.iter() {
+                            if pid.id() == target_id {
+                                flanking = !perception.in_vision_cone(target_pos, (pos.0, pos.1), 1.0);
+                                break;
+                            }
+                        }
+                        crit_chance += if flanking { FLANK_CRIT_BONUS } else { 0.0 }
+                            + perks.map_or(0.0, |perks| perks.crit_chance_bonus(flanking));
+                        let critical = hit && rng.0.gen::<f32>() < crit_chance;
+
+                        // damage varies either way by up to the spread, and critical attacks multiply it
+                        let mut damage = damage * (1.0 + spread * (rng.0.gen::<f32>() * 2.0 - 1.0));
+                        if critical {
+                            damage *= crit_multiplier + perks.map_or(0.0, |perks| perks.crit_multiplier_bonus());
+                        }
+
+                        attack_events.send(AttackEvent {
+                            attacker_id: id.id(),
+                            target_id: target_id,
+                            direction: (dir[0], dir[1]),
+                            origin: (pos.0, pos.1),
+                            target_position: target_pos,
+                            knockback: knockback,
+                            damage: damage,
+                            hit: hit,
+                            critical: critical,
+                            damage_type: damage_type,
+                        });
+
+                        // start the cooldown for the next attack
+                        actions.action_timer = Some(Timer::from_seconds(cooldown, false));
+
+                        // attacks limited to a number of shots, e.g. reaction fire, are done once they're used up
+                        let shots = action.params.as_ref().and_then(|params| params.get("shots").cloned());
+                        if let Some(shots) = shots {
+                            if shots <= 1.0 {
+                                // pop actions queue and ready next action
+                                actions.complete_action();
+                            }else if let Some(params) = &mut actions.current_action.params {
+                                params.insert("shots".to_string(), shots - 1.0);
+                            }
+                        }
+                    }
+                }
+
+            },
+            // wait actions do nothing for a specified amount of time
+            ActionType::Wait => {
+                if let Some(timer) = &mut actions.action_timer {
+                    timer.tick(time.delta_seconds);
+                    if timer.finished {
+                        // pop actions queue and ready next action
+                        actions.complete_action();
+                    }
+                }else{
+                    // default duration is None
+                    // if a duration parameter is not passed with the action
+                    // default behaviour is to wait for one second
+                    let mut duration = None;
+                    // attempt to retrieve parameters
+                    if let Some(params) = &action.params {
+                        // attempt to retrieve duration parameter
+                        duration = params.get("duration");
+                    }
+                    
+                    match duration {
+                        // if duration was specified
+                        Some(&length) => {
+                            // create a non-repeating timer that waits for <length> number of seconds
+                            actions.action_timer = Some(Timer::from_seconds(length, false));
+                        },
+                        // if duration was not specified
+                        None => {
+                            // default behaviour is to wait for one second
+                            // create a non-repeating timer to wait for one second
+                            actions.action_timer = Some(Timer::from_seconds(1.0, false));
+                        }
+                    }
+                }
+            }
+            // interact actions stand still at the object until the duration is up, then operate it
+            ActionType::Interact => {
+                sprite.animation_type = AnimationType::Idle;
+                vel.0 = 0.0;
+                vel.1 = 0.0;
+
+                // the interaction is called off if the object is gone
+                let target_id = action.target.1.clone().unwrap_or_default();
+                if let Err(reason) = resolve_target(&(None, action.target.1.clone()), &positions) {
+                    actions.fail_action(reason);
+                    continue;
+                }
+
+                let finished = match &mut actions.action_timer {
+                    Some(timer) => {
+                        timer.tick(time.delta_seconds);
+                        timer.finished
+                    },
+                    None => {
+                        // default behaviour is to take one second
+                        let duration = action.params.as_ref().and_then(|params| params.get("duration").cloned()).unwrap_or(1.0);
+                        actions.action_timer = Some(Timer::from_seconds(duration, false));
+                        false
+                    },
+                };
+
+                if finished {
+                    interact_events.send(InteractEvent {
+                        actor_id: id.id(),
+                        target_id: target_id,
+                    });
+                    // pop actions queue and ready next action
+                    actions.complete_action();
+                }
+            },
+            // distract actions throw something at the target point straight away
+            ActionType::Distract => {
+                sprite.animation_type = AnimationType::Attack;
+                vel.0 = 0.0;
+                vel.1 = 0.0;
+                if let Some(target) = action.target.0 {
+                    throw_events.send(ThrowEvent {
+                        thrower_id: id.id(),
+                        from: (pos.0, pos.1),
+                        to: target,
+                        fuse: None,
+                    });
+                }
+                // pop actions queue and ready next action
+                actions.complete_action();
+            },
+            // grenade actions throw a grenade at the target point straight away
+            ActionType::Grenade => {
+                sprite.animation_type = AnimationType::Attack;
+                vel.0 = 0.0;
+                vel.1 = 0.0;
+                if let Some(target) = action.target.0 {
+                    throw_events.send(ThrowEvent {
+                        thrower_id: id.id(),
+                        from: (pos.0, pos.1),
+                        to: target,
+                        fuse: Some(GRENADE_FUSE),
+                    });
+                }
+                // pop actions queue and ready next action
+                actions.complete_action();
+            },
+            // switch actions stand still while the switch weapon system swaps weapons over
+            ActionType::Switch => {
+                sprite.animation_type = AnimationType::Idle;
+                vel.0 = 0.0;
+                vel.1 = 0.0;
+            },
+            // heal actions stand still while the heal system patches up the target
+            ActionType::Heal => {
+                sprite.animation_type = AnimationType::Idle;
+                vel.0 = 0.0;
+                vel.1 = 0.0;
+            },
+            // brace actions stand still behind a shield, the shield system turns it to face the target point
+            ActionType::Brace => {
+                sprite.animation_type = AnimationType::Idle;
+                vel.0 = 0.0;
+                vel.1 = 0.0;
+            },
+            // overwatch actions stand still, watching, until the overwatch system spots an enemy
+            ActionType::Overwatch => {
+                sprite.animation_type = AnimationType::Idle;
+                vel.0 = 0.0;
+                vel.1 = 0.0;
+            },
+            // patrol actions walk to the next waypoint, then patrol again from the one after
+            ActionType::Patrol => {
+                // find the waypoint being walked to
+                let mut waypoint = None;
+                let mut count = 0;
+                let mut index = 0;
+                if let Some(params) = &action.params {
+                    count = params.get("count").map_or(0, |&c| c as usize);
+                    index = params.get("index").map_or(0, |&i| i as usize);
+                    if let (Some(&x), Some(&y)) = (params.get(&format!("x{}", index)), params.get(&format!("y{}", index))) {
+                        waypoint = Some((x, y));
+                    }
+                }
+
+                match (waypoint, action.params) {
+                    (Some(waypoint), Some(mut params)) => {
+                        // carry on from the next waypoint once this one is reached,
+                        // looping back to the start at the end of the route
+                        params.insert("index".to_string(), ((index + 1) % count.max(1)) as f32);
+                        actions.push_front(Action {
+                            action_type: ActionType::Patrol,
+                            target: (None, None),
+                            params: Some(params),
+                            group: None,
+                        });
+
+                        let mut move_params = HashMap::new();
+                        // range refers to the maximum range acceptable
+                        // set at zero to force entity to move onto the waypoint
+                        move_params.insert("range".to_string(), 0.0);
+                        actions.current_action = Action {
+                            action_type: ActionType::Move,
+                            target: (Some(waypoint), None),
+                            params: Some(move_params),
+                            group: None,
+                        };
+                    },
+                    _ => {
+                        // a route with no waypoints is skipped
+                        actions.advance();
+                    },
+                }
+            },
+            // empty actions do nothing and are immediately popped
+            ActionType::Empty => {
+                // set to use idle animation, unless the entity has surrendered
+                if sprite.animation_type != AnimationType::Surrender {
+                    sprite.animation_type = AnimationType::Idle;
+                }
+                // empty actions do nothing, reset all moving parts and move on to the next
+                vel.0 = 0.0;
+                vel.1 = 0.0;
+                // pop actions queue and ready next action
+                actions.advance();
+            },
+            _ => {
+                // this should never be reached
+                panic!("unimplemented action");
+            }
+        }
+    }
+
+    // record how long this system took
+    diagnostics.add_measurement(RUN_ACTION_SYSTEM_TIME, timer.elapsed().as_secs_f64() * 1000.0);
+}
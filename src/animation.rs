@@ -0,0 +1,366 @@
+// animation module
+// moves sprites between positions and plays the animations attached to people
+use bevy::prelude::*;
+
+use crate::palette::Palette;
+use crate::person::SimpleRect;
+use crate::tick::FrameTime;
+
+// animation plugin
+// responsible for running the appropriate animation
+pub struct AnimationPlugin;
+
+// boilerplate code for plugin implementation
+impl Plugin for AnimationPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        // add frame rate regulator
+        app.add_resource(AnimationFrameRate::new())
+        // add animate system    
+        .add_system(animate_system.system());
+    }
+}
+
+// animation type enum
+// should correspond to the different animation types we want
+#[derive(PartialEq)]
+pub enum AnimationType {
+    Attack,
+    Move,
+    Idle,
+    Surrender,
+}
+
+// sprite data component
+// this allows for storage of frames and animation type
+// and should be spawned along with any sprite that has animation
+pub struct SpriteData {
+    // animation type informs what animation should be run
+    pub animation_type: AnimationType,
+
+    // move frames contain the frames used when the sprite is moving
+    pub move_frames: Vec<SpriteComponents>,
+    // move frame index holds where the move animation currently is
+    pub move_frame_index: usize,
+
+    // idle frames contain the frames used when idling/the default
+    // animation used
+    pub idle_frames: Vec<SpriteComponents>,
+    // idle frame index holds where the idle animation currently is
+    pub idle_frame_index: usize,
+
+    // attack frames contain the frames used when attacking
+    pub attack_frames: Vec<SpriteComponents>,
+    // attack frame index holds where the attack animation currently is
+    pub attack_frame_index: usize,
+
+    // surrender frames contain the frames used once surrendered
+    // sprites without any fall back on the idle frames
+    pub surrender_frames: Vec<SpriteComponents>,
+    // surrender frame index holds where the surrender animation currently is
+    pub surrender_frame_index: usize,
+}
+
+// implementation for sprite data component
+impl SpriteData {
+    // new function provides an empty sprite data
+    // set automatically to idle
+    // note that frames need to be added for this component to work
+    pub fn new() -> Self {
+        SpriteData {
+            animation_type: AnimationType::Idle,
+
+            move_frames: Vec::new(),
+            move_frame_index: 0,
+            
+            idle_frames: Vec::new(),
+            idle_frame_index: 0,
+
+            attack_frames: Vec::new(),
+            attack_frame_index: 0,
+
+            surrender_frames: Vec::new(),
+            surrender_frame_index: 0,
+        }
+    }
+
+    // add move frame
+    // this function adds a frame (sprite) to the sprite data's move animation
+    pub fn add_move_frame(&mut self, sprite: SpriteComponents) {
+        self.move_frames.push(sprite);
+    }
+    // reset move animation
+    // this function resets the move animation
+    pub fn reset_move_animation(&mut self) {
+        self.move_frame_index = 0;
+    }
+    // get move frame
+    // this function gets the next frame for the move animation
+    // it will also automatically reset the other animations
+    pub fn get_move_frame(&mut self) -> SpriteComponents {
+        // reset frame index for other animations
+        self.reset_idle_animation();
+        self.reset_attack_animation();
+
+        // get frame for move animation
+        let copyover = &self.move_frames[self.move_frame_index];
+        // increment frame index
+        self.move_frame_index = (self.move_frame_index + 1) % self.move_frames.len();
+        
+        // manually copy over sprite components because copy/clone aren't implemented for them
+        SpriteComponents {
+            material: copyover.material,
+            translation: copyover.translation,
+            sprite: Sprite {
+                size: copyover.sprite.size,
+            },
+            ..Default::default()
+        }
+    }
+
+    // add idle frame
+    // this function adds a frame (sprite) to the sprite data's idle animation 
+    pub fn add_idle_frame(&mut self, sprite: SpriteComponents) {
+        self.idle_frames.push(sprite);
+    }
+    // reset idle animation
+    // this function resets the idle animation
+    pub fn reset_idle_animation(&mut self) {
+        self.idle_frame_index = 0;
+    }
+    // get idle frame
+    // this function gets the next frame for the idle animation
+    // it will also automatically reset the other animations
+    pub fn get_idle_frame(&mut self) -> SpriteComponents {
+        // reset frame index for other animations
+        self.reset_attack_animation();
+        self.reset_move_animation();
+
+        // get frame for idle animation
+        let copyover = &self.idle_frames[self.idle_frame_index];
+        // increment frame index
+        self.idle_frame_index = (self.idle_frame_index + 1) % self.idle_frames.len();
+
+        // manually copy over sprite components because copy/clone aren't implemented for them
+        SpriteComponents {
+            material: copyover.material,
+            translation: copyover.translation,
+            sprite: Sprite {
+                size: copyover.sprite.size,
+            },
+            ..Default::default()
+        }
+    }
+
+    // add attack frame
+    // this function adds a frame (sprite) to the sprite data's attack animation 
+    pub fn add_attack_frame(&mut self, sprite: SpriteComponents) {
+        self.attack_frames.push(sprite);
+    }
+    // reset attack animation
+    // this function resets the attack animation
+    pub fn reset_attack_animation(&mut self) {
+        self.attack_frame_index = 0;
+    }
+    // add surrender frame
+    // this function adds a frame (sprite) to the sprite data's surrender animation
+    pub fn add_surrender_frame(&mut self, sprite: SpriteComponents) {
+        self.surrender_frames.push(sprite);
+    }
+    // get surrender frame
+    // this function gets the next frame for the surrender animation
+    // sprites without surrender frames use their idle animation instead
+    pub fn get_surrender_frame(&mut self) -> SpriteComponents {
+        if self.surrender_frames.is_empty() {
+            return self.get_idle_frame();
+        }
+        // reset frame index for other animations
+        self.reset_idle_animation();
+        self.reset_move_animation();
+        self.reset_attack_animation();
+
+        // get frame for surrender animation
+        let copyover = &self.surrender_frames[self.surrender_frame_index];
+        // increment frame index
+        self.surrender_frame_index = (self.surrender_frame_index + 1) % self.surrender_frames.len();
+
+        // manually copy over sprite components because copy/clone aren't implemented for them
+        SpriteComponents {
+            material: copyover.material,
+            translation: copyover.translation,
+            sprite: Sprite {
+                size: copyover.sprite.size,
+            },
+            ..Default::default()
+        }
+    }
+
+    // get attack frame
+    // this function gets the next frame for the attack animation
+    // it will also automatically reset the other animations
+    pub fn get_attack_frame(&mut self) -> SpriteComponents {
+        // reset frame index for other animations
+        self.reset_idle_animation();
+        self.reset_move_animation();
+        
+        // get frame for attack animation
+        let copyover = &self.attack_frames[self.attack_frame_index];
+        // increment frame index
+        self.attack_frame_index = (self.attack_frame_index + 1) % self.attack_frames.len();
+        
+        // manually copy over sprite components because copy/clone aren't implemented for them
+        SpriteComponents {
+            material: copyover.material,
+            translation: copyover.translation,
+            sprite: Sprite {
+                size: copyover.sprite.size,
+            },
+            ..Default::default()
+        }
+    }
+}
+
+// animation frame rate struct
+// this struct contains a timer that is
+// used to regulate the framerate of animations
+// this means that the framerate of animations is potentially separate from
+// the overall framerate of the game! (potential issue)
+pub struct AnimationFrameRate(pub Timer);
+
+// implementation for the animation frame rate struct
+impl AnimationFrameRate {
+    // gives a new animation frame rate struct, automatically set to a
+    // default frame rate
+    pub fn new() -> Self {
+        // 6fps per second animation frame rate
+        // create a repeating timer for animation frame rate
+        AnimationFrameRate(Timer::from_seconds(4.0 / 24.0, true))
+    }
+    // generates a new animation frame rate struct from a given fps
+    // fps refers to the desired number of frames per second
+    pub fn from_frame_rate(fps: f32) -> Self {
+        // create a repeating timer for animation frame rate
+        AnimationFrameRate(Timer::from_seconds(1.0 / fps, true))
+    }
+}
+
+// animate system
+// responsible for playing the appropriate animations for each sprite
+pub fn animate_system(time: Res<FrameTime>, mut timer: ResMut<AnimationFrameRate>, mut query: Query<(&mut Handle<ColorMaterial>, &mut Sprite, &mut SpriteData)>) {
+    // tick up on animation frame rate timer
+    timer.0.tick(time.delta_seconds);
+        
+    // check if it's time for a new animation frame
+    if timer.0.finished {
+        // go through all sprites and get then assign new frames
+        for (mut material, mut sprite, mut frames) in &mut query.iter() {    
+            // sprite frame is defaulted to None
+            let mut sprite_frame: Option<SpriteComponents> = None;
+
+            // check animation type for current sprite
+            match frames.animation_type {
+                // if attack animation
+                AnimationType::Attack => {
+                    // get the next attack frame
+                    sprite_frame = Some(frames.get_attack_frame());
+                },
+                // if move animation
+                AnimationType::Move => {
+                    // get the next move frame
+                    sprite_frame = Some(frames.get_move_frame());
+                },
+                // if idle animation
+                AnimationType::Idle => {
+                    // get the next idle frame
+                    sprite_frame = Some(frames.get_idle_frame());
+                },
+                // if surrender animation
+                AnimationType::Surrender => {
+                    // get the next surrender frame
+                    sprite_frame = Some(frames.get_surrender_frame());
+                }
+            }
+
+            // if the frame exists
+            if let Some(frame) = sprite_frame {
+                *material = frame.material;
+                *sprite = frame.sprite;
+            }
+        }
+    }
+}
+
+// get player sprite template
+// gives the template sprite for the player
+// right now mostly just used for testing animation system
+// actual method of getting player sprite may vary
+pub fn get_player_sprite_template(materials: &mut ResMut<Assets<ColorMaterial>>) -> SpriteData {
+    let palette = Palette::default();
+    get_person_sprite_template(materials, palette.player, palette.attack)
+}
+
+// get squadmate sprite template
+// gives the template sprite for squadmates
+// right now only used to test animation system
+// actual method of getting squadmate sprites may vary
+pub fn get_squadmate_sprite_template(materials: &mut ResMut<Assets<ColorMaterial>>) -> SpriteData {
+    let palette = Palette::default();
+    get_person_sprite_template(materials, palette.squad, palette.attack)
+}
+
+// get hostile sprite template
+// gives the template sprite for hostiles
+// right now only used to test animation system
+// actual method of getting hostile sprites may vary
+pub fn get_hostile_sprite_template(materials: &mut ResMut<Assets<ColorMaterial>>, base: Color) -> SpriteData {
+    let mut template = get_person_sprite_template(materials, base, Palette::default().attack);
+
+    // surrendered hostiles flash white, like a white flag
+    let surrender_one_handle = materials.add(Color::WHITE.into());
+    let surrender_two_handle = materials.add(Color::rgb((base.r + 0.6).min(1.0), (base.g + 0.6).min(1.0), (base.b + 0.6).min(1.0)).into());
+    template.add_surrender_frame(SimpleRect::new(surrender_one_handle, Vec2::new(10.0, 10.0)));
+    template.add_surrender_frame(SimpleRect::new(surrender_two_handle, Vec2::new(10.0, 10.0)));
+
+    template
+}
+
+// get person sprite template
+// gives four idle, attack and move frames, each with its own material so they can be tinted again later
+pub fn get_person_sprite_template(materials: &mut ResMut<Assets<ColorMaterial>>, base: Color, attack: Color) -> SpriteData {
+    let mut template = SpriteData::new();
+    for _ in 0..4 {
+        template.add_idle_frame(SimpleRect::new(materials.add(base.into()), Vec2::new(10.0, 10.0)));
+        template.add_attack_frame(SimpleRect::new(materials.add(base.into()), Vec2::new(10.0, 10.0)));
+        template.add_move_frame(SimpleRect::new(materials.add(base.into()), Vec2::new(10.0, 10.0)));
+    }
+    tint_sprite_data(materials, &template, base, attack);
+    template
+}
+
+// tint sprite data
+// recolours the idle, attack and move frames of a sprite around a base colour
+// idle frames glow a little, attack frames fade from the attack colour back to the base, and move frames pulse
+// surrender frames are left as they are, so a white flag stays white
+pub fn tint_sprite_data(materials: &mut Assets<ColorMaterial>, sprite_data: &SpriteData, base: Color, attack: Color) {
+    // lightens the base colour by the given amount
+    let lighten = |amount: f32| Color::rgb((base.r + amount).min(1.0), (base.g + amount).min(1.0), (base.b + amount).min(1.0));
+    // moves the base colour towards the given colour
+    let mix = |other: Color, amount: f32| Color::rgb(
+        base.r + (other.r - base.r) * amount,
+        base.g + (other.g - base.g) * amount,
+        base.b + (other.b - base.b) * amount,
+    );
+    // dark colours pulse lighter and light colours pulse darker, so the pulse shows on either
+    let pulse_towards = if base.r + base.g + base.b > 1.5 { Color::BLACK } else { Color::WHITE };
+
+    let idle = [base, lighten(0.1), lighten(0.25), lighten(0.1)];
+    let attack = [attack, mix(attack, 0.75), mix(attack, 0.5), base];
+    let moving = [base, mix(pulse_towards, 0.25), mix(pulse_towards, 0.5), mix(pulse_towards, 0.25)];
+
+    for (frames, colors) in [(&sprite_data.idle_frames, &idle), (&sprite_data.attack_frames, &attack), (&sprite_data.move_frames, &moving)].iter() {
+        for (frame, color) in frames.iter().zip(colors.iter().cycle()) {
+            if let Some(material) = materials.get_mut(&frame.material) {
+                material.color = *color;
+            }
+        }
+    }
+}
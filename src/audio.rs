@@ -0,0 +1,515 @@
+// audio module
+// sound effects and music
+use bevy::prelude::*;
+use std::collections::HashMap;
+use std::io::Cursor;
+use rodio::{Sink, Source};
+use rodio::source::ChannelVolume;
+
+use crate::{
+    HEARING_FALLOFF, MUSIC_COMBAT_COOLDOWN, MUSIC_FADE_DURATION, PERCEPTION_RADIUS, Position,
+    Velocity,
+};
+use crate::behaviour::{Behaviour, BehaviourSet};
+use crate::combat::AttackEvent;
+use crate::config::GameConfig;
+use crate::console::{Console, ConsoleCommand, ConsoleRegistry};
+use crate::draw::get_position_from_translate;
+use crate::mods::ModAssets;
+use crate::nerve::{ActionType, Nerve};
+use crate::person::{AttitudeType, Controlled, Person};
+use crate::tick::FrameTime;
+
+// audio plugin
+// responsible for playing sound effects for combat, movement and commands
+// sounds are loaded through the asset server from assets/sounds, and
+// played through their own output so each category can have its own volume
+// the volumes start out as the game config says, and can be changed from the console when there is one
+pub struct AudioPlugin;
+
+// implementation of the plugin trait,
+// required for this to be used as a plugin
+impl Plugin for AudioPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        // the listener starts in the middle of the screen, until it finds the camera
+        let config = app.resources().get::<GameConfig>().map(|config| config.clone()).unwrap_or_default();
+        // add in the console command for changing the volumes, if the console plugin was added first
+        let console = match app.resources_mut().get_mut::<ConsoleRegistry>() {
+            Some(mut registry) => {
+                registry.register("volume", "volume <master|combat|movement|command|music> <0.0 to 1.0>", 2);
+                true
+            },
+            None => false,
+        };
+        if console {
+            app.init_resource::<VolumeCommandState>()
+            .add_system(volume_command_system.system());
+        }
+        // add in the sound event
+        app.add_event::<SoundEvent>()
+        // add in the audio settings resource, with the volumes from the config
+        .add_resource(AudioSettings::from_config(&config))
+        // initialise the sound handles and output device
+        .init_resource::<SoundHandles>()
+        .init_resource::<SoundDevice>()
+        // initialise the sound event states
+        .init_resource::<SoundEventState>()
+        .init_resource::<CombatSoundState>()
+        // add in the footstep timer
+        .add_resource(FootstepTimer(Timer::from_seconds(0.35, true)))
+        // load in the sound effects
+        .add_startup_system(load_sounds_system.system())
+        // turn attacks into sound events
+        .add_system(combat_sound_system.system())
+        // turn movement into footstep sound events
+        .add_system(footstep_sound_system.system())
+        // add in the listener, which follows the camera
+        .add_resource(Listener((config.map_width / 2.0, config.map_height / 2.0)))
+        .add_system(update_listener_system.system())
+        // add in the play sound system
+        .add_system(play_sound_system.system())
+        // add in the music resource
+        .init_resource::<MusicPlayer>()
+        // add in the music state system
+        .add_system(music_state_system.system())
+        // add in the music crossfade system
+        .add_system(music_crossfade_system.system());
+    }
+}
+
+// sound category enum
+// every sound belongs to a category, and every category has its own volume
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SoundCategory {
+    Combat,
+    Movement,
+    Command,
+    Music,
+}
+
+impl SoundCategory {
+    // gives the category with the given name, as used in the console
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "combat" => Some(SoundCategory::Combat),
+            "movement" => Some(SoundCategory::Movement),
+            "command" => Some(SoundCategory::Command),
+            "music" => Some(SoundCategory::Music),
+            _ => None,
+        }
+    }
+}
+
+// sound type enum
+// the different sound effects that can be played
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SoundType {
+    Attack,
+    Hit,
+    // played on top of the hit sound for critical hits
+    Critical,
+    Death,
+    Footstep,
+    Acknowledge,
+    // played when an order can't be carried out
+    Error,
+}
+
+impl SoundType {
+    // gives the category this sound belongs to
+    pub fn category(&self) -> SoundCategory {
+        match self {
+            SoundType::Attack | SoundType::Hit | SoundType::Critical | SoundType::Death => SoundCategory::Combat,
+            SoundType::Footstep => SoundCategory::Movement,
+            SoundType::Acknowledge | SoundType::Error => SoundCategory::Command,
+        }
+    }
+    // gives the path of the sound file for this sound
+    pub fn path(&self) -> &'static str {
+        match self {
+            SoundType::Attack => "assets/sounds/attack.mp3",
+            SoundType::Hit => "assets/sounds/hit.mp3",
+            SoundType::Critical => "assets/sounds/critical.mp3",
+            SoundType::Death => "assets/sounds/death.mp3",
+            SoundType::Footstep => "assets/sounds/footstep.mp3",
+            SoundType::Acknowledge => "assets/sounds/acknowledge.mp3",
+            SoundType::Error => "assets/sounds/error.mp3",
+        }
+    }
+}
+
+// sound event
+// send this event to play a sound effect
+pub struct SoundEvent {
+    pub sound: SoundType,
+    // where the sound came from, if anywhere in particular
+    pub position: Option<(f32, f32)>,
+}
+
+// audio settings resource
+// volumes go from 0.0 (muted) to 1.0 (full volume)
+pub struct AudioSettings {
+    pub master_volume: f32,
+    pub volumes: HashMap<SoundCategory, f32>,
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        AudioSettings::from_config(&GameConfig::default())
+    }
+}
+
+impl AudioSettings {
+    // gives the volumes set in the game config
+    pub fn from_config(config: &GameConfig) -> Self {
+        let mut settings = AudioSettings {
+            master_volume: 1.0,
+            volumes: HashMap::new(),
+        };
+        settings.set_master_volume(config.master_volume);
+        settings.set_volume(SoundCategory::Combat, config.combat_volume);
+        settings.set_volume(SoundCategory::Movement, config.movement_volume);
+        settings.set_volume(SoundCategory::Command, config.command_volume);
+        settings.set_volume(SoundCategory::Music, config.music_volume);
+        settings
+    }
+    // gives the final volume for a category, taking the master volume into account
+    pub fn get_volume(&self, category: SoundCategory) -> f32 {
+        self.master_volume * self.volumes.get(&category).cloned().unwrap_or(1.0)
+    }
+    // sets the volume for a category
+    pub fn set_volume(&mut self, category: SoundCategory, volume: f32) {
+        self.volumes.insert(category, volume.max(0.0).min(1.0));
+    }
+    // sets the volume every category is scaled by
+    pub fn set_master_volume(&mut self, volume: f32) {
+        self.master_volume = volume.max(0.0).min(1.0);
+    }
+}
+
+// volume command state holds an event reader for console commands changing the volumes
+#[derive(Default)]
+pub struct VolumeCommandState {
+    pub event_reader: EventReader<ConsoleCommand>,
+}
+
+// volume command system
+// changes the volume of a category, or the master volume, from the console
+// the music picks up the new volume straight away, sound effects from the next one played
+pub fn volume_command_system(mut state: ResMut<VolumeCommandState>, events: Res<Events<ConsoleCommand>>, mut settings: ResMut<AudioSettings>,
+    mut console: ResMut<Console>) {
+    let received: Vec<ConsoleCommand> = state.event_reader.iter(&events).cloned().collect();
+    for command in received {
+        if command.name != "volume" {
+            continue;
+        }
+        let reply = match (command.args[0].as_str(), command.args[1].parse::<f32>()) {
+            (_, Err(_)) => format!("bad volume: {}", command.args[1]),
+            ("master", Ok(volume)) => {
+                settings.set_master_volume(volume);
+                format!("master volume set to {}", settings.master_volume)
+            },
+            (name, Ok(volume)) => match SoundCategory::from_name(name) {
+                Some(category) => {
+                    settings.set_volume(category, volume);
+                    format!("{} volume set to {}", name, settings.volumes[&category])
+                },
+                None => format!("unknown sound category: {}", name),
+            },
+        };
+        console.print(reply);
+    }
+}
+
+// sound handles resource
+// holds the handles to every loaded sound effect
+#[derive(Default)]
+pub struct SoundHandles(pub HashMap<SoundType, Handle<AudioSource>>);
+
+// sound device resource
+// holds the output device sounds are played through, if there is one
+pub struct SoundDevice(pub Option<rodio::Device>);
+
+impl Default for SoundDevice {
+    fn default() -> Self {
+        SoundDevice(rodio::default_output_device())
+    }
+}
+
+// sound event state holds an event reader for sound events
+#[derive(Default)]
+pub struct SoundEventState {
+    pub event_reader: EventReader<SoundEvent>,
+}
+
+// combat sound state holds an event reader for attack events
+#[derive(Default)]
+pub struct CombatSoundState {
+    pub event_reader: EventReader<AttackEvent>,
+}
+
+// footstep timer
+// regulates how often footsteps are heard
+pub struct FootstepTimer(pub Timer);
+
+// load sounds system
+// loads every sound effect through the asset server
+// missing sound files are skipped, so the game still runs without them
+// the sounds in assets/sounds are short silent placeholders until the real ones are recorded, mods can replace them
+pub fn load_sounds_system(mut handles: ResMut<SoundHandles>, asset_server: Res<AssetServer>, mods: Res<ModAssets>) {
+    for &sound in [SoundType::Attack, SoundType::Hit, SoundType::Critical, SoundType::Death, SoundType::Footstep, SoundType::Acknowledge, SoundType::Error].iter() {
+        if let Ok(handle) = asset_server.load(mods.resolve(sound.path())) {
+            handles.0.insert(sound, handle);
+        }
+    }
+}
+
+// combat sound system
+// plays an attack sound at the attacker and a hit sound at the target for every attack
+pub fn combat_sound_system(mut state: ResMut<CombatSoundState>, attacks: Res<Events<AttackEvent>>, mut sounds: ResMut<Events<SoundEvent>>) {
+    for attack in state.event_reader.iter(&attacks) {
+        sounds.send(SoundEvent {
+            sound: SoundType::Attack,
+            position: Some(attack.origin),
+        });
+        if attack.hit {
+            sounds.send(SoundEvent {
+                sound: SoundType::Hit,
+                position: Some(attack.target_position),
+            });
+            if attack.critical {
+                sounds.send(SoundEvent {
+                    sound: SoundType::Critical,
+                    position: Some(attack.target_position),
+                });
+            }
+        }
+    }
+}
+
+// footstep sound system
+// plays footsteps for squad members carrying out a move action
+pub fn footstep_sound_system(time: Res<Time>, mut timer: ResMut<FootstepTimer>, mut sounds: ResMut<Events<SoundEvent>>, mut query: Query<(&Controlled, &Nerve, &Position, &Velocity)>) {
+    timer.0.tick(time.delta_seconds);
+    if !timer.0.finished {
+        return;
+    }
+    for (_controlled, actions, pos, vel) in &mut query.iter() {
+        let moving = vel.0.abs() >= 1.0 || vel.1.abs() >= 1.0;
+        if let ActionType::Move = actions.current_action.action_type {
+            if moving {
+                sounds.send(SoundEvent {
+                    sound: SoundType::Footstep,
+                    position: Some((pos.0, pos.1)),
+                });
+            }
+        }
+    }
+}
+
+// play sound system
+// plays every requested sound effect at the volume of its category
+pub fn play_sound_system(config: Res<GameConfig>, mut state: ResMut<SoundEventState>, events: Res<Events<SoundEvent>>, settings: Res<AudioSettings>, listener: Res<Listener>,
+    device: Res<SoundDevice>, handles: Res<SoundHandles>, sources: Res<Assets<AudioSource>>) {
+    // nothing can be played without an output device
+    let device = match &device.0 {
+        Some(device) => device,
+        None => return,
+    };
+
+    for event in state.event_reader.iter(&events) {
+        let volume = settings.get_volume(event.sound.category());
+        if volume <= 0.0 {
+            continue;
+        }
+        // the sound may not have been loaded, or may still be loading
+        let source = match handles.0.get(&event.sound).and_then(|handle| sources.get(handle)) {
+            Some(source) => source,
+            None => continue,
+        };
+        if let Ok(decoder) = rodio::Decoder::new(Cursor::new(source.clone())) {
+            let sink = Sink::new(device);
+            match event.position {
+                // sounds with a position are panned and attenuated around the listener
+                Some(position) => {
+                    let (left, right) = get_spatial_volumes(&config, position, listener.0);
+                    sink.append(ChannelVolume::new(decoder.amplify(volume), vec![left, right]));
+                },
+                // sounds without a position are heard the same in both ears
+                None => {
+                    sink.append(decoder.amplify(volume));
+                },
+            }
+            // let the sound play out on its own
+            sink.detach();
+        }
+    }
+}
+
+// listener resource
+// the position sounds are heard from, kept at the center of the camera
+pub struct Listener(pub (f32, f32));
+
+// update listener system
+// moves the listener to wherever the camera is looking
+pub fn update_listener_system(config: Res<GameConfig>, mut listener: ResMut<Listener>, mut query: Query<(&Camera, &Translation)>) {
+    for (camera, transl) in &mut query.iter() {
+        // ignore the ui camera
+        if camera.name.as_ref().map_or(false, |name| name == bevy::render::render_graph::base::camera::CAMERA2D) {
+            listener.0 = get_position_from_translate(&config, transl.0[0], transl.0[1]);
+        }
+    }
+}
+
+// gives the left and right volumes for a sound made at the emitter
+// as heard by the listener. sounds to the side are panned towards that ear,
+// and sounds further away are quieter, but never silent
+pub fn get_spatial_volumes(config: &GameConfig, emitter: (f32, f32), listener: (f32, f32)) -> (f32, f32) {
+    let dx = emitter.0 - listener.0;
+    let dy = emitter.1 - listener.1;
+    let dist = (dx * dx + dy * dy).sqrt();
+
+    // attenuation, halves at the falloff distance
+    let ratio = dist / HEARING_FALLOFF;
+    let gain = 1.0 / (1.0 + ratio * ratio);
+
+    // pan goes from -1.0 (all left) to 1.0 (all right), full at the edge of the screen
+    let pan = (dx / (config.window_width / 2.0)).max(-1.0).min(1.0);
+    // equal power panning so sounds don't get quieter in the middle
+    let angle = (pan + 1.0) * std::f32::consts::FRAC_PI_4;
+    (gain * angle.cos(), gain * angle.sin())
+}
+
+// music track enum
+// the background tracks that can be played
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MusicTrack {
+    // played while exploring or resting
+    Ambient,
+    // played while the squad is fighting
+    Combat,
+}
+
+// music player resource
+// holds one looping sink per track, and crossfades between them
+// both tracks keep playing the whole time, only their volumes change
+pub struct MusicPlayer {
+    // handles to the music files
+    pub ambient_handle: Option<Handle<AudioSource>>,
+    pub combat_handle: Option<Handle<AudioSource>>,
+    // sinks playing each track, started once the music has loaded
+    pub ambient_sink: Option<Sink>,
+    pub combat_sink: Option<Sink>,
+    // the track that should be playing
+    pub target: MusicTrack,
+    // how far the crossfade is, 0.0 is all ambient and 1.0 is all combat
+    pub fade: f32,
+    // counts down once the fighting stops, the music only calms down once it runs out
+    pub cooldown: Timer,
+}
+
+impl Default for MusicPlayer {
+    fn default() -> Self {
+        MusicPlayer {
+            ambient_handle: None,
+            combat_handle: None,
+            ambient_sink: None,
+            combat_sink: None,
+            target: MusicTrack::Ambient,
+            fade: 0.0,
+            cooldown: Timer::from_seconds(MUSIC_COMBAT_COOLDOWN, false),
+        }
+    }
+}
+
+// gives a sink looping the given music track, if it has loaded
+pub fn get_music_sink(device: &rodio::Device, handle: &Option<Handle<AudioSource>>, sources: &Assets<AudioSource>) -> Option<Sink> {
+    let source = sources.get(handle.as_ref()?)?;
+    let decoder = rodio::Decoder::new(Cursor::new(source.clone())).ok()?;
+    let sink = Sink::new(device);
+    // start silent, the crossfade system sets the volume
+    sink.set_volume(0.0);
+    sink.append(decoder.repeat_infinite());
+    Some(sink)
+}
+
+// music state system
+// picks the combat track while any squad member is in combat, and goes back to
+// the ambient track after a cooldown with no hostiles nearby
+pub fn music_state_system(time: Res<Time>, mut music: ResMut<MusicPlayer>,
+    mut squad: Query<(&Person, &Behaviour, &Position)>, mut persons: Query<(&Person, &Position)>) {
+    let mut fighting = false;
+    let mut hostiles_near = false;
+    for (pers, behav, pos) in &mut squad.iter() {
+        if pers.attitude != AttitudeType::Squad {
+            continue;
+        }
+        if behav.current_behaviour_set == BehaviourSet::Combat {
+            fighting = true;
+        }
+        // check if there are any hostiles this squad member would notice
+        for (other, other_pos) in &mut persons.iter() {
+            if other.attitude == AttitudeType::Hostile
+                && Vec2::new(other_pos.0 - pos.0, other_pos.1 - pos.1).length() < PERCEPTION_RADIUS {
+                hostiles_near = true;
+            }
+        }
+    }
+
+    if fighting {
+        music.target = MusicTrack::Combat;
+        music.cooldown.reset();
+    }else if music.target == MusicTrack::Combat {
+        // hold off on calming down while hostiles are still around
+        if hostiles_near {
+            music.cooldown.reset();
+        }else{
+            music.cooldown.tick(time.delta_seconds);
+            if music.cooldown.finished {
+                music.target = MusicTrack::Ambient;
+            }
+        }
+    }
+}
+
+// music crossfade system
+// starts the music once it has loaded, and fades between the tracks
+pub fn music_crossfade_system(time: Res<FrameTime>, mut music: ResMut<MusicPlayer>, settings: Res<AudioSettings>,
+    device: Res<SoundDevice>, asset_server: Res<AssetServer>, mods: Res<ModAssets>, sources: Res<Assets<AudioSource>>) {
+    let device = match &device.0 {
+        Some(device) => device,
+        None => return,
+    };
+
+    // load in the music if it hasn't been already
+    if music.ambient_handle.is_none() {
+        music.ambient_handle = asset_server.load(mods.resolve("assets/music/ambient.mp3")).ok();
+    }
+    if music.combat_handle.is_none() {
+        music.combat_handle = asset_server.load(mods.resolve("assets/music/combat.mp3")).ok();
+    }
+    // start the sinks once the music has finished loading
+    if music.ambient_sink.is_none() {
+        music.ambient_sink = get_music_sink(device, &music.ambient_handle, &sources);
+    }
+    if music.combat_sink.is_none() {
+        music.combat_sink = get_music_sink(device, &music.combat_handle, &sources);
+    }
+
+    // move the crossfade towards the target track
+    let step = time.delta_seconds / MUSIC_FADE_DURATION;
+    music.fade = match music.target {
+        MusicTrack::Combat => (music.fade + step).min(1.0),
+        MusicTrack::Ambient => (music.fade - step).max(0.0),
+    };
+
+    // equal power crossfade so the volume doesn't dip halfway through
+    let volume = settings.get_volume(SoundCategory::Music);
+    let angle = music.fade * std::f32::consts::FRAC_PI_2;
+    if let Some(sink) = &music.ambient_sink {
+        sink.set_volume(volume * angle.cos());
+    }
+    if let Some(sink) = &music.combat_sink {
+        sink.set_volume(volume * angle.sin());
+    }
+}
@@ -0,0 +1,411 @@
+// behaviour module
+// how people decide what to do when they are not being told, and how they find paths
+use bevy::prelude::*;
+use std::collections::HashMap;
+use rand::Rng;
+
+use crate::{BEHAVIOUR_STAGE, EXHAUSTED_BIAS, GameRng, MARCH_DISTANCE, Position, SUPPRESSION_PINNED};
+use crate::combat::Suppression;
+use crate::config::GameConfig;
+use crate::nerve::{ActionType, Nerve};
+use crate::patrol::get_patrol_action;
+use crate::routine::Resting;
+use crate::stamina::Stamina;
+use crate::surrender::Morale;
+use crate::tick::AddTickSystem;
+
+// Behaviour plugin
+// responsible for independent action generation
+pub struct BehaviourPlugin;
+
+// boilerplate code for Behaviour plugin
+impl Plugin for BehaviourPlugin {
+    fn build(&self, app: &mut AppBuilder){
+        // initialise the patrol routes, filled in from the tiled map
+        app.init_resource::<MapPatrolRoutes>()
+        // add in the system walking hostiles along their patrol routes
+        .add_tick_system_to_stage(BEHAVIOUR_STAGE, route_patrol_system.system())
+        // add in simple idle system
+        .add_tick_system_to_stage(BEHAVIOUR_STAGE, simple_idle_system.system())
+        // add in the behaviour set selection system
+        .add_tick_system_to_stage(BEHAVIOUR_STAGE, select_behaviour_set_system.system())
+        // add in the behaviour selection system
+        .add_tick_system_to_stage(BEHAVIOUR_STAGE, select_behaviour_system.system());
+    }
+}
+
+// map patrol routes resource
+// holds the waypoints of every patrol route authored in the tiled map, by name
+#[derive(Default)]
+pub struct MapPatrolRoutes(pub HashMap<String, Vec<(f32, f32)>>);
+
+// patrol route component
+// spawn this component along with hostiles that should walk a route from the tiled map while at rest
+// hostiles whose route isn't in the map wander about instead
+pub struct PatrolRoute {
+    pub name: String,
+}
+
+// route patrol system
+// starts hostiles at rest walking their patrol route, whenever they have nothing else to do
+pub fn route_patrol_system(routes: Res<MapPatrolRoutes>, mut query: Query<(&PatrolRoute, &Behaviour, &mut Nerve)>) {
+    for (route, behav, mut actions) in &mut query.iter() {
+        let waypoints = match routes.0.get(&route.name) {
+            Some(waypoints) if !waypoints.is_empty() => waypoints,
+            _ => continue,
+        };
+        let at_rest = behav.current_behaviour_set == BehaviourSet::AtRest || behav.current_behaviour_set == BehaviourSet::Empty;
+        let busy = behav.current_behaviour.is_reaction();
+        if at_rest && !busy && actions.is_curr_action_empty() && actions.action_queue.is_empty() {
+            actions.action_queue.push_back(get_patrol_action(waypoints));
+        }
+    }
+}
+
+// simple idle system
+// allows AI actors to wander around aimlessly
+// will probably be replaced, reworked or at least renamed
+pub fn simple_idle_system(config: Res<GameConfig>, mut rng: ResMut<GameRng>, routes: Res<MapPatrolRoutes>,
+    mut query: Query<(&Behaviour, &Nerve, &mut Pathfinder, &Position, Option<&PatrolRoute>, Option<&Resting>)>) {
+    // use the shared random number generator, so seeded runs play out the same way
+    let rng = &mut rng.0;
+
+    // iterate through every entity with a brain, nervous system, and a physical position
+    for (control, actions, mut pf, pos, route, resting) in &mut query.iter() {
+        // entities with a patrol route walk that instead
+        if route.map_or(false, |route| routes.0.contains_key(&route.name)) {
+            continue;
+        }
+        // entities resting as part of their routine stay where they are
+        if resting.is_some() {
+            continue;
+        }
+        // entities reacting to something have somewhere to be, or are staying put, so don't wander off
+        if control.current_behaviour.is_reaction() {
+            continue;
+        }
+        // check both current action as well as action queue
+        match (actions.current_action.action_type, actions.peek()) {
+            // if there is no current action and the action queue is empty
+            (ActionType::Empty, None) => {
+                // generate a random coordinate within 200 units of the current position
+                // horizontal deviation
+                let rand_x = rng.gen::<f32>() * 200.0 - rng.gen::<f32>() * 200.0;
+                // vertical deviation
+                let rand_y = rng.gen::<f32>() * 200.0 - rng.gen::<f32>() * 200.0;
+                
+                // get random coordinate and make sure it remains in bounds
+                let loiter_x = (rand_x + pos.0).max(10.0).min(config.map_width - 10.0);
+                let loiter_y = (rand_y + pos.1).max(10.0).min(config.map_height - 10.0);
+
+                pf.needs_pathfinding = true;
+                pf.path_goal = TilePos::from_coords(&config, loiter_x, loiter_y);
+                pf.real_goal = (loiter_x, loiter_y);
+
+                /*let mut params = HashMap::new();
+                // range refers to the maximum range at which an attack can be launched
+                params.insert("range".to_string(), 0.0);
+                // add a move action to the randomly generated coordinate
+                actions.action_queue.push_back(Action {
+                    action_type: ActionType::Move,
+                    target: (Some((loiter_x, loiter_y)), None),
+                    params: Some(params),
+                    group: None,
+                });
+
+                // create parameters hashmap
+                let mut params = HashMap::new();
+                // add duration parameter with value 3.0 (seconds)
+                params.insert("duration".to_string(), 3.0);
+
+                // add Wait action
+                actions.action_queue.push_back(Action {
+                    action_type: ActionType::Wait,
+                    target: (None, None),
+                    params: Some(params),
+                    group: None,
+                })*/
+            }
+            _ => {
+
+            }     
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BehaviourSet {
+    AtRest,
+    OnMarch,
+    PreCombat,
+    Combat,
+    Retreat,
+    Empty,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BehaviourType {
+    Rest,
+    Loiter,
+    Alert,
+    Hide,
+    Preparation,
+    AlertMove,
+    LoiterMove,
+    Scout,
+    Stalk,
+    Vantage,
+    Charge,
+    Flank,
+    Defend,
+    Kite,
+    Flee,
+    // move towards something that was heard, to find out what it was
+    Investigate,
+    // run away from something frightening, then cower until calmed down
+    Panic,
+    // lie in wait, hidden, until the squad gets close
+    Ambush,
+    // give up, waiting to be captured
+    Surrender,
+    // look around where a lost target was last seen
+    Search,
+    // run to an alarm and raise it
+    RaiseAlarm,
+    Empty,
+}
+
+impl BehaviourSet {
+    // gives the behaviours that can be picked while in this set
+    pub fn behaviours(&self) -> &'static [BehaviourType] {
+        match self {
+            BehaviourSet::AtRest => &[BehaviourType::Rest, BehaviourType::Loiter],
+            BehaviourSet::OnMarch => &[BehaviourType::LoiterMove, BehaviourType::AlertMove, BehaviourType::Scout],
+            BehaviourSet::PreCombat => &[BehaviourType::Alert, BehaviourType::Hide, BehaviourType::Preparation, BehaviourType::Stalk, BehaviourType::Vantage],
+            // taking the vantage climbs up to the high ground over whoever is being attacked
+            BehaviourSet::Combat => &[BehaviourType::Charge, BehaviourType::Flank, BehaviourType::Defend, BehaviourType::Kite, BehaviourType::Vantage],
+            BehaviourSet::Retreat => &[BehaviourType::Flee, BehaviourType::Hide],
+            BehaviourSet::Empty => &[],
+        }
+    }
+}
+
+impl BehaviourType {
+    // gives whether the behaviour comes from something that happened, rather than being picked out of the behaviour set
+    // entities with one of these have their own plans, so they aren't given new behaviours or sent wandering
+    pub fn is_reaction(&self) -> bool {
+        match self {
+            BehaviourType::Investigate | BehaviourType::Panic | BehaviourType::Ambush
+                | BehaviourType::Surrender | BehaviourType::Search | BehaviourType::RaiseAlarm => true,
+            _ => false,
+        }
+    }
+    // gives the behaviour type with the given name, as used in data files
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "rest" => Some(BehaviourType::Rest),
+            "loiter" => Some(BehaviourType::Loiter),
+            "alert" => Some(BehaviourType::Alert),
+            "hide" => Some(BehaviourType::Hide),
+            "preparation" => Some(BehaviourType::Preparation),
+            "alert_move" => Some(BehaviourType::AlertMove),
+            "loiter_move" => Some(BehaviourType::LoiterMove),
+            "scout" => Some(BehaviourType::Scout),
+            "stalk" => Some(BehaviourType::Stalk),
+            "vantage" => Some(BehaviourType::Vantage),
+            "charge" => Some(BehaviourType::Charge),
+            "flank" => Some(BehaviourType::Flank),
+            "defend" => Some(BehaviourType::Defend),
+            "kite" => Some(BehaviourType::Kite),
+            "flee" => Some(BehaviourType::Flee),
+            "investigate" => Some(BehaviourType::Investigate),
+            "panic" => Some(BehaviourType::Panic),
+            "ambush" => Some(BehaviourType::Ambush),
+            "surrender" => Some(BehaviourType::Surrender),
+            "search" => Some(BehaviourType::Search),
+            "raise_alarm" => Some(BehaviourType::RaiseAlarm),
+            _ => None,
+        }
+    }
+}
+
+pub struct Behaviour {
+    pub current_behaviour_set: BehaviourSet,
+    pub current_behaviour: BehaviourType,
+    // how likely each behaviour is to be picked, behaviours not listed have a weight of one
+    pub preferences: Vec<(BehaviourType, f32)>,
+    // whether something has happened that calls for a rethink, e.g. being interrupted by damage
+    pub reevaluate: bool,
+}
+
+impl Default for Behaviour {
+    fn default() -> Self {
+        Behaviour {
+            current_behaviour_set: BehaviourSet::Empty,
+            current_behaviour: BehaviourType::Empty,
+            preferences: Vec::new(),
+            reevaluate: false,
+        }
+    }
+}
+
+impl Behaviour {
+    // gives how likely a behaviour is to be picked
+    pub fn get_weight(&self, behaviour: BehaviourType) -> f32 {
+        self.preferences.iter()
+            .find(|(b, _)| *b == behaviour)
+            .map_or(1.0, |(_, weight)| *weight)
+    }
+}
+
+// select behaviour set system
+// decides which set of behaviours an entity should be choosing from
+// currently an entity is in combat while it has an attack lined up, on the march while headed somewhere far off,
+// and at rest otherwise
+pub fn select_behaviour_set_system(mut query: Query<(&Position, &mut Behaviour, &mut Nerve, Option<&Morale>, Option<&Pathfinder>)>) {
+    for (pos, mut behav, actions, morale, pf) in &mut query.iter() {
+        // broken hostiles only want to get away
+        if morale.map_or(false, |morale| morale.is_broken()) {
+            behav.current_behaviour_set = BehaviourSet::Retreat;
+            continue;
+        }
+        // check the current action as well as the action queue for attacks
+        let attacking = match actions.current_action.action_type {
+            ActionType::Attack => true,
+            _ => actions.action_queue.iter().any(|action| match action.action_type {
+                ActionType::Attack => true,
+                _ => false,
+            }),
+        };
+
+        // check whether the path being followed, or about to be found, goes far
+        let marching = pf.map_or(false, |pf| {
+            let travelling = pf.needs_pathfinding || pf.path_index < pf.tile_path.len();
+            let dist = ((pf.real_goal.0 - pos.0).powi(2) + (pf.real_goal.1 - pos.1).powi(2)).sqrt();
+            travelling && dist > MARCH_DISTANCE
+        });
+
+        behav.current_behaviour_set = if attacking {
+            BehaviourSet::Combat
+        }else if marching {
+            BehaviourSet::OnMarch
+        }else{
+            BehaviourSet::AtRest
+        };
+    }
+}
+
+// select behaviour system
+// picks a behaviour out of the current behaviour set, whenever the current behaviour isn't part of it
+// behaviours are picked at random, weighted by the entity's preferences
+// suppressed entities lean towards hiding and defending, and think again once they're pinned down
+// exhausted entities are in no state to charge or flee
+pub fn select_behaviour_system(mut rng: ResMut<GameRng>, mut query: Query<(&Position, &mut Behaviour, &mut Nerve, Option<&mut Suppression>, Option<&Stamina>)>) {
+    let rng = &mut rng.0;
+
+    for (_pos, mut behav, _actions, suppression, stamina) in &mut query.iter() {
+        // reacting to something takes priority over the behaviour set
+        if behav.current_behaviour.is_reaction() {
+            continue;
+        }
+        // being interrupted or pinned down calls for a rethink, once
+        let mut rethink = behav.reevaluate;
+        behav.reevaluate = false;
+        let mut bias = 1.0;
+        if let Some(mut suppression) = suppression {
+            if suppression.value >= SUPPRESSION_PINNED && !suppression.pinned {
+                suppression.pinned = true;
+                rethink = true;
+            }
+            bias = suppression.cover_bias();
+        }
+        let choices = behav.current_behaviour_set.behaviours();
+        if choices.contains(&behav.current_behaviour) && !rethink {
+            continue;
+        }
+        if choices.is_empty() {
+            behav.current_behaviour = BehaviourType::Empty;
+            continue;
+        }
+
+        // pick a behaviour, weighted by preference
+        let exhausted = stamina.map_or(false, |stamina| stamina.exhausted);
+        let weight = |choice: BehaviourType| match choice {
+            BehaviourType::Hide | BehaviourType::Defend => behav.get_weight(choice) * bias,
+            BehaviourType::Charge | BehaviourType::Flee if exhausted => behav.get_weight(choice) * EXHAUSTED_BIAS,
+            _ => behav.get_weight(choice),
+        };
+        let total: f32 = choices.iter().map(|&choice| weight(choice)).sum();
+        let mut roll = rng.gen::<f32>() * total;
+        let mut picked = choices[choices.len() - 1];
+        for &choice in choices.iter() {
+            let weight = weight(choice);
+            if roll < weight {
+                picked = choice;
+                break;
+            }
+            roll -= weight;
+        }
+        behav.current_behaviour = picked;
+    }
+}
+
+pub fn run_behaviour_system(mut query: Query<(&Position, &mut Behaviour, &mut Nerve)>) {
+    for (pos, mut behav, mut nerv) in &mut query.iter() {
+        match &behav.current_behaviour {
+            
+            Empty => {
+
+            },
+            _ => {
+
+            },
+        }    
+    }
+}
+
+pub struct MapCoords(pub f32, pub f32);
+
+pub struct PathfindersQueue(pub usize);
+
+#[derive(Default, PartialEq, Eq, Clone, Copy, Hash)]
+pub struct TilePos(pub usize, pub usize);
+ 
+impl TilePos {
+    pub fn from_coords(config: &GameConfig, x: f32, y: f32) -> Self {
+        if x < 0.0 || y < 0.0 {
+            panic!("bad coordinates: negative, non-existent tile");
+        }
+        TilePos((x / config.tile_size) as usize, (y / config.tile_size) as usize)
+    }
+    pub fn to_coords(&self, config: &GameConfig) -> (f32, f32) {
+        (config.tile_size / 2.0 + self.0 as f32 * config.tile_size, config.tile_size / 2.0 + self.1 as f32 * config.tile_size)
+    }
+}
+
+
+pub struct Pathfinder {
+    pub needs_pathfinding: bool,
+    pub path_ready: bool,
+    pub path_start: TilePos,
+    pub path_goal: TilePos,
+    pub real_goal: (f32, f32),
+    pub tile_path: Vec<TilePos>,
+    pub path: Vec<(f32, f32)>,
+    pub path_index: usize,
+}
+
+impl Default for Pathfinder {
+    fn default() -> Self {
+        Pathfinder {
+            needs_pathfinding: false,
+            path_ready: false,
+            path_start: TilePos::default(),
+            path_goal: TilePos::default(),
+            real_goal: (0.0, 0.0),
+            path: Vec::new(),
+            tile_path: Vec::new(),
+            path_index: 0,
+        }
+    }
+}
@@ -0,0 +1,153 @@
+// bench module
+// the stress-test mode, for measuring performance regressions
+use bevy::prelude::*;
+use bevy::diagnostic::{Diagnostic, DiagnosticId, Diagnostics};
+use rand::Rng;
+
+use crate::GameRng;
+use crate::animation::get_squadmate_sprite_template;
+use crate::behaviour::Behaviour;
+use crate::config::GameConfig;
+use crate::encounter::{Archetypes, spawn_hostile};
+use crate::mods::ModAssets;
+use crate::person::{SimpleRect, spawn_squad_member};
+use crate::pool::POOL_REUSE_RATE;
+
+// bench plugin
+// responsible for the stress-test mode, used to measure performance regressions
+// run with `cargo run -- --bench <squadmates> [hostiles]` to spawn in
+// extra people with full ai and pathfinding, along with a timing overlay
+pub struct BenchPlugin;
+
+// implementation of the plugin trait,
+// required for this to be used as a plugin
+impl Plugin for BenchPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        // register the system timing diagnostics
+        app.add_startup_system(setup_bench_diagnostics_system.system())
+        // spawn in the extra people and the timing overlay
+        .add_startup_system(add_bench_people.system())
+        // add in the timing overlay system
+        .add_system(bench_overlay_system.system());
+    }
+}
+
+// diagnostic ids for the timed systems
+pub const PATHFIND_SYSTEM_TIME: DiagnosticId = DiagnosticId::from_u128(0x6d65_7263_6265_6e63_6870_6174_6801);
+pub const RUN_ACTION_SYSTEM_TIME: DiagnosticId = DiagnosticId::from_u128(0x6d65_7263_6265_6e63_6861_6374_6901);
+
+// bench settings resource
+// read in from the command line arguments at startup
+#[derive(Default, Debug)]
+pub struct BenchSettings {
+    // whether or not the bench mode is on
+    pub enabled: bool,
+    // number of extra squadmates to spawn
+    pub squadmates: usize,
+    // number of extra hostiles to spawn
+    pub hostiles: usize,
+}
+
+impl BenchSettings {
+    // reads the bench settings from the command line arguments
+    // expects `--bench <squadmates> [hostiles]`, hostiles defaults to squadmates
+    pub fn from_args(args: Vec<String>) -> Self {
+        let mut settings = BenchSettings::default();
+        if let Some(index) = args.iter().position(|arg| arg == "--bench") {
+            settings.enabled = true;
+            // default to 100 of each if no number was given
+            settings.squadmates = args.get(index + 1).and_then(|n| n.parse().ok()).unwrap_or(100);
+            settings.hostiles = args.get(index + 2).and_then(|n| n.parse().ok()).unwrap_or(settings.squadmates);
+        }
+        settings
+    }
+}
+
+// bench meter component
+// spawn this component along any text components that will display system timings
+pub struct BenchMeter;
+
+// setup bench diagnostics system
+// registers the diagnostics for every timed system
+pub fn setup_bench_diagnostics_system(mut diagnostics: ResMut<Diagnostics>) {
+    diagnostics.add(Diagnostic::new(PATHFIND_SYSTEM_TIME, "pathfind_system_ms", 20));
+    diagnostics.add(Diagnostic::new(RUN_ACTION_SYSTEM_TIME, "run_action_system_ms", 20));
+}
+
+// add bench people startup system
+// spawns in the extra squadmates and hostiles when bench mode is on
+pub fn add_bench_people(mut commands: Commands, config: Res<GameConfig>, settings: Res<BenchSettings>, mut materials: ResMut<Assets<ColorMaterial>>, asset_server: Res<AssetServer>, mods: Res<ModAssets>,
+    archetypes: Res<Archetypes>, mut rng: ResMut<GameRng>) {
+    if !settings.enabled {
+        return;
+    }
+
+    // people are placed using the game rng, so a benchmark with the same seed is the same every time
+    let rng = &mut rng.0;
+
+    let blue_handle = materials.add(Color::BLUE.into());
+
+    for i in 0..settings.squadmates {
+        // random coordinate that stays in bounds
+        let x = rng.gen::<f32>() * (config.map_width - 20.0) + 10.0;
+        let y = rng.gen::<f32>() * (config.map_height - 20.0) + 10.0;
+        // squad indices past the hotkeys so these are never ordered around
+        spawn_squad_member(&config, &mut commands, 10 + i as i32, (x, y));
+        commands
+            .with_bundle(SimpleRect::new(blue_handle, Vec2::new(10.0, 10.0)))
+            .with(Behaviour::default())
+            .with(get_squadmate_sprite_template(&mut materials));
+    }
+
+    for _ in 0..settings.hostiles {
+        // random coordinate that stays in bounds
+        let x = rng.gen::<f32>() * (config.map_width - 20.0) + 10.0;
+        let y = rng.gen::<f32>() * (config.map_height - 20.0) + 10.0;
+        spawn_hostile(&config, &mut commands, &mut materials, &archetypes.get("grunt"), (x, y));
+    }
+
+    let font_handle = asset_server.load(mods.resolve("assets/fonts/LiberationMono-Regular.ttf")).unwrap();
+
+    commands
+        // text for the timing overlay
+        .spawn(TextComponents {
+            style: Style {
+                // place the overlay below the fps counter
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    top: Val::Px(24.0),
+                    left: Val::Px(0.0),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            text: Text {
+                value: "".to_string(),
+                font: font_handle,
+                style: TextStyle {
+                    font_size: 16.0,
+                    color: Color::BLACK,
+                },
+            },
+            ..Default::default()
+        })
+        // make sure to spawn bench meter component so it displays timings
+        .with(BenchMeter);
+}
+
+// bench overlay system
+// writes the average time taken by each timed system to the overlay, along with how often the entity pools reuse entities
+pub fn bench_overlay_system(diagnostics: Res<Diagnostics>, mut query: Query<(&BenchMeter, &mut Text)>) {
+    for (_meter, mut text) in &mut query.iter() {
+        let mut lines = Vec::new();
+        for &(id, name) in [(PATHFIND_SYSTEM_TIME, "pathfind"), (RUN_ACTION_SYSTEM_TIME, "run_action")].iter() {
+            if let Some(average) = diagnostics.get(id).and_then(|d| d.average()) {
+                lines.push(format!("{}: {:.3}ms", name, average));
+            }
+        }
+        if let Some(average) = diagnostics.get(POOL_REUSE_RATE).and_then(|d| d.average()) {
+            lines.push(format!("pool reuse: {:.0}%", average * 100.0));
+        }
+        text.value = lines.join(" | ");
+    }
+}
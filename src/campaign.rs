@@ -0,0 +1,1339 @@
+// campaign module
+// the strategic layer between missions
+use bevy::prelude::*;
+use bevy::render::pass::ClearColor;
+use std::collections::HashMap;
+use rand::rngs::StdRng;
+use std::{fs, env, process};
+use std::process::Child;
+use rand::{SeedableRng, Rng};
+
+use crate::{
+    BASE_KEY, BREAK_CAMP_KEY, CALENDAR_MONTHS, CAMPAIGN_PATH, CAMPAIGN_SAVE_PATH,
+    CONTRACT_REPUTATION, DAYS_PER_MONTH, DEADLINE_WARNING_DAYS, DESERTION_MORALE,
+    EXPIRED_CONTRACT_REPUTATION, FACILITY_COSTS, HEAL_PER_DAY, HIRE_COST, HIRE_KEY,
+    INFIRMARY_HEAL_BONUS, INJURY_DAYS, INJURY_HEALTH, KILL_EXPERIENCE, MERCENARY_HEALTH,
+    MERCENARY_NAMES, MISSION_EXPERIENCE, MISSION_RESULTS_PATH, MODS_PATH, Position, REST_KEY,
+    REST_MORALE, roll_seed, SHOP_STOCK, SKIRMISH_ENCOUNTER_PATH, SKIRMISH_MAP_PATH, SQUAD_SIZE,
+    STARTING_FUNDS, STARTING_MORALE, STARTING_ROSTER, TRAINING_EXPERIENCE, TRAVEL_COST,
+    TRAVEL_EVENT_CHANCE, TRAVEL_EVENTS_PATH, UNPAID_MORALE, UPKEEP_PER_MERCENARY,
+};
+use crate::combat::Perk;
+use crate::config::{GameConfig, LaunchOptions};
+use crate::control::{InputState, keyboard_input_system, KeyBindings, KeyboardState, Weapon};
+use crate::draw::{DrawMovingPlugin, WorldCamera};
+use crate::encounter::{Archetypes, parse_field};
+use crate::locale::{Locale, LocalePlugin};
+use crate::mods::ModAssets;
+use crate::patrol::spawn_patrol_link;
+use crate::person::SimpleRect;
+
+// campaign
+// the strategic layer between missions, a map of locations joined by routes, with contracts on offer at them
+// taking a contract travels the squad there and runs its mission as a game of its own,
+// then reads the mission results back in to pay the squad, count its losses and adjust its reputation
+// run with --campaign, the campaign is saved to the campaign save file after every change
+pub fn run_campaign(config: GameConfig) {
+    // find any installed mods
+    let mods = ModAssets::load(MODS_PATH);
+    let map = CampaignMap::load(&mods.resolve(CAMPAIGN_PATH));
+    // new campaigns start at the first location on the map
+    let start = match map.locations.first() {
+        Some(location) => location.name.clone(),
+        None => {
+            eprintln!("campaign map has no locations, so there's nowhere to start");
+            return;
+        },
+    };
+    // a new campaign can be given its seed with --seed, to play through the same campaign again
+    let seed = LaunchOptions::from_args(env::args().collect(), &config).seed.unwrap_or_else(roll_seed);
+    let state = CampaignState::load(CAMPAIGN_SAVE_PATH, &start, seed);
+    let events = TravelEvents::load(&mods.resolve(TRAVEL_EVENTS_PATH));
+
+    App::build()
+    .add_resource(WindowDescriptor {
+        title: "Mercenaries v0.0.1 - Campaign".to_string(),
+        width: config.window_width as u32,
+        height: config.window_height as u32,
+        vsync: false,
+        ..Default::default()
+    })
+    // the campaign map is drawn on parchment
+    .add_resource(ClearColor(Color::rgb(0.8, 0.75, 0.6)))
+    .add_default_plugins()
+    // the markers and labels are drawn using the screen size in the config
+    .add_resource(config)
+    .add_resource(mods)
+    .add_resource(map)
+    .add_resource(state)
+    .add_resource(events)
+    // the markers and labels are placed by position, like everything else
+    .add_plugin(DrawMovingPlugin)
+    .add_plugin(CampaignPlugin)
+    // every string on the campaign screen comes from the language files
+    .add_plugin(LocalePlugin)
+    .run();
+}
+
+// campaign plugin
+// responsible for the campaign map screen, picking contracts, and running their missions
+pub struct CampaignPlugin;
+
+// implementation of the plugin trait,
+// required for this to be used as a plugin
+impl Plugin for CampaignPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        // contracts are picked from the keyboard
+        app.init_resource::<InputState>()
+        .init_resource::<KeyboardState>()
+        // the language key is read from the same bindings as in a mission
+        .init_resource::<KeyBindings>()
+        .add_system(keyboard_input_system.system())
+        // initialise the mission being run, and the last thing that happened
+        .init_resource::<CampaignMission>()
+        .init_resource::<CampaignMessage>()
+        // initialise the travel event waiting on the player's choice
+        .init_resource::<PendingTravelEvent>()
+        // initialise the camp, set up after every mission
+        .init_resource::<Camp>()
+        .add_system(camp_system.system())
+        // initialise the base screen, opened at the home base
+        .init_resource::<BaseScreen>()
+        .add_system(base_system.system())
+        // add in the campaign map
+        .add_startup_system(setup_campaign_system.system())
+        // add in the system taking contracts and hiring mercenaries
+        .add_system(campaign_input_system.system())
+        // add in the system reading back the results of missions once they're over
+        .add_system(campaign_mission_system.system())
+        // add in the systems showing where the squad is and what's on offer
+        .add_system(campaign_marker_system.system())
+        .add_system(campaign_label_system.system())
+        .add_system(campaign_text_system.system());
+    }
+}
+
+// campaign location struct
+// a place on the campaign map
+#[derive(Debug, Clone)]
+pub struct CampaignLocation {
+    pub name: String,
+    pub position: (f32, f32),
+}
+
+// contract struct
+// a job on offer at a location, played out as a mission on the given map and encounter
+#[derive(Debug, Clone)]
+pub struct Contract {
+    pub name: String,
+    // the name of the location it's on offer at
+    pub location: String,
+    // paid out if the squad makes it out
+    pub reward: i32,
+    pub map_path: String,
+    pub encounter_path: Option<String>,
+    // the last day the contract can be taken on, if it has one
+    pub deadline: Option<u32>,
+    // the faction the contract is for, if any, which takes the location once it's seen through
+    pub employer: Option<String>,
+    // the encounters played instead of the usual one, while the location is held by each faction
+    pub encounters: HashMap<String, String>,
+}
+
+impl Contract {
+    // gives the encounter the contract's mission is played out with, which depends on who holds the location
+    pub fn encounter_for(&self, controller: Option<&str>) -> Option<&str> {
+        controller.and_then(|faction| self.encounters.get(faction))
+            .or(self.encounter_path.as_ref())
+            .map(|path| path.as_str())
+    }
+}
+
+// campaign map resource
+// the locations, the routes between them, and the contracts on offer, read in from assets/campaign.txt
+#[derive(Debug, Clone, Default)]
+pub struct CampaignMap {
+    pub locations: Vec<CampaignLocation>,
+    pub routes: Vec<(String, String)>,
+    pub contracts: Vec<Contract>,
+    // the faction holding each location at the start of the campaign
+    pub control: HashMap<String, String>,
+    // kit sold at the home base's armory by each faction, with prices
+    pub stock: Vec<(String, String, i32)>,
+}
+
+impl CampaignMap {
+    // reads in the campaign map from a file with one entry per line, in the forms
+    //   location <name> <x> <y>
+    //   route <from> <to>
+    //   contract <name> <location> <reward> <map> [encounter]
+    //   deadline <contract> <day>, the last day it can be taken on, counting from the start of the campaign
+    //   control <location> <faction>
+    //   employer <contract> <faction>
+    //   encounter <contract> <faction> <encounter>
+    //   stock <faction> <item> <price>
+    // a contract with an employer is only on offer while its employer doesn't hold the location,
+    // and hands the location over to them once it's seen through. a contract's encounter lines
+    // give the encounter played while the location is held by that faction, instead of the usual one.
+    // a faction's stock is sold at the home base while it holds a location next to it
+    // routes go both ways, and underscores in names are shown as spaces
+    // lines starting with # are ignored
+    pub fn load(path: &str) -> Self {
+        let mut map = CampaignMap::default();
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(err) => {
+                eprintln!("could not read campaign map from {}: {}", path, err);
+                return map;
+            },
+        };
+        // lines that can't be read are printed out and skipped
+        for line in text.lines().map(|line| line.trim()) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Err(err) = map.apply_entry(line) {
+                eprintln!("bad campaign map line: {} ({})", line, err);
+            }
+        }
+        map
+    }
+
+    // adds a single entry from the campaign map file to the map
+    fn apply_entry(&mut self, line: &str) -> Result<(), String> {
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        match parts.as_slice() {
+            ["location", name, x, y] => self.locations.push(CampaignLocation {
+                name: name.to_string(),
+                position: (parse_field(x, "x")?, parse_field(y, "y")?),
+            }),
+            ["route", from, to] => self.routes.push((from.to_string(), to.to_string())),
+            ["contract", name, location, reward, map_path] => self.contracts.push(Contract {
+                name: name.to_string(),
+                location: location.to_string(),
+                reward: parse_field(reward, "reward")?,
+                map_path: map_path.to_string(),
+                encounter_path: None,
+                deadline: None,
+                employer: None,
+                encounters: HashMap::new(),
+            }),
+            ["contract", name, location, reward, map_path, encounter_path] => self.contracts.push(Contract {
+                name: name.to_string(),
+                location: location.to_string(),
+                reward: parse_field(reward, "reward")?,
+                map_path: map_path.to_string(),
+                encounter_path: Some(encounter_path.to_string()),
+                deadline: None,
+                employer: None,
+                encounters: HashMap::new(),
+            }),
+            ["deadline", name, day] => {
+                let day = parse_field(day, "deadline")?;
+                self.contract_mut(name)?.deadline = Some(day);
+            },
+            ["control", location, faction] => {
+                self.control.insert(location.to_string(), faction.to_string());
+            },
+            ["employer", name, faction] => self.contract_mut(name)?.employer = Some(faction.to_string()),
+            ["encounter", name, faction, encounter_path] => {
+                self.contract_mut(name)?.encounters.insert(faction.to_string(), encounter_path.to_string());
+            },
+            ["stock", faction, item, price] => {
+                let price = parse_field(price, "price")?;
+                self.stock.push((faction.to_string(), item.to_string(), price));
+            },
+            _ => return Err("unknown entry".to_string()),
+        }
+        Ok(())
+    }
+
+    // gives the contract with the given name, for the entries that add to a contract listed earlier
+    fn contract_mut(&mut self, name: &str) -> Result<&mut Contract, String> {
+        self.contracts.iter_mut().find(|contract| contract.name == name).ok_or(format!("unknown contract: {}", name))
+    }
+
+    // gives the location with the given name
+    pub fn location(&self, name: &str) -> Option<&CampaignLocation> {
+        self.locations.iter().find(|location| location.name == name)
+    }
+
+    // gives whether a route runs between two locations
+    pub fn connected(&self, a: &str, b: &str) -> bool {
+        self.routes.iter().any(|(from, to)| (from == a && to == b) || (from == b && to == a))
+    }
+}
+
+// mercenary struct
+// one of the mercenaries on the roster, carried over from mission to mission
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mercenary {
+    pub name: String,
+    pub health: f32,
+    pub experience: u32,
+    // the days left before they're fit to fight again, 0 if they're fit now
+    pub injury_days: u32,
+    // the kit they carry, either lockpicks, medkits, shields, armour, perks or the name of an archetype whose weapon they use
+    pub equipment: Vec<String>,
+}
+
+impl Mercenary {
+    pub fn new(name: String) -> Self {
+        Mercenary {
+            name,
+            health: MERCENARY_HEALTH,
+            experience: 0,
+            injury_days: 0,
+            equipment: Vec::new(),
+        }
+    }
+
+    // reads a mercenary from a line of the campaign save, in the form
+    //   mercenary <name> <health> <experience> <injury days> [equipment,...]
+    pub fn from_parts(parts: &[&str]) -> Option<Self> {
+        match parts {
+            ["mercenary", name, health, experience, injury_days, equipment @ ..] => Some(Mercenary {
+                name: name.to_string(),
+                health: health.parse().ok()?,
+                experience: experience.parse().ok()?,
+                injury_days: injury_days.parse().ok()?,
+                equipment: equipment.first().map_or(Vec::new(), |items| items.split(',').map(|item| item.to_string()).collect()),
+            }),
+            _ => None,
+        }
+    }
+
+    // gives the line the mercenary is written to the campaign save as
+    pub fn to_line(&self) -> String {
+        format!("mercenary {} {} {} {} {}", self.name, self.health, self.experience, self.injury_days, self.equipment.join(",")).trim_end().to_string()
+    }
+
+    // gives whether the mercenary is fit to go out on a contract
+    pub fn is_fit(&self) -> bool {
+        self.injury_days == 0 && self.health > 0.0
+    }
+
+    // gives the weapon the mercenary fights with, from the first archetype in their kit, or the default
+    pub fn weapon(&self, archetypes: &Archetypes) -> Weapon {
+        self.equipment.iter()
+            .find(|item| archetypes.0.contains_key(*item))
+            .map_or(Weapon::default(), |item| archetypes.get(item).weapon)
+    }
+
+    // gives the weapon the mercenary carries holstered, from the second archetype in their kit, if there is one
+    pub fn secondary_weapon(&self, archetypes: &Archetypes) -> Option<Weapon> {
+        self.equipment.iter()
+            .filter(|item| archetypes.0.contains_key(*item))
+            .nth(1)
+            .map(|item| archetypes.get(item).weapon)
+    }
+}
+
+// roster resource
+// the mercenaries on the books, saved along with the campaign
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Roster {
+    pub members: Vec<Mercenary>,
+}
+
+impl Roster {
+    // hires the given number of mercenaries for a new campaign, one of them bringing lockpicks
+    pub fn new(size: usize) -> Self {
+        let mut roster = Roster::default();
+        for _ in 0..size {
+            roster.hire();
+        }
+        if let Some(mercenary) = roster.members.get_mut(3) {
+            mercenary.equipment.push("lockpicks".to_string());
+        }
+        roster
+    }
+
+    // reads the roster out of a campaign save
+    pub fn load(path: &str) -> Self {
+        let text = fs::read_to_string(path).unwrap_or_default();
+        Roster {
+            members: text.lines()
+                .filter_map(|line| Mercenary::from_parts(&line.split_whitespace().collect::<Vec<&str>>()))
+                .collect(),
+        }
+    }
+
+    // the number of mercenaries on the books
+    pub fn len(&self) -> usize {
+        self.members.len()
+    }
+
+    // adds a new mercenary, named after the first name not already taken
+    pub fn hire(&mut self) {
+        let taken = |name: &str| self.members.iter().any(|mercenary| mercenary.name == name);
+        let mut round = 1;
+        let name = loop {
+            let free = MERCENARY_NAMES.iter()
+                .map(|name| if round == 1 { name.to_string() } else { format!("{}_{}", name, round) })
+                .find(|name| !taken(name));
+            match free {
+                Some(name) => break name,
+                None => round += 1,
+            }
+        };
+        self.members.push(Mercenary::new(name));
+    }
+
+    // gives the mercenaries that go out on the next mission, the first fit ones on the books
+    pub fn deployed(&self) -> Vec<&Mercenary> {
+        self.members.iter().filter(|mercenary| mercenary.is_fit()).take(SQUAD_SIZE as usize).collect()
+    }
+}
+
+// campaign state resource
+// everything about the squad that carries over from one mission to the next
+#[derive(Debug, Clone, PartialEq)]
+pub struct CampaignState {
+    pub funds: i32,
+    pub reputation: i32,
+    // the mercenaries on the books
+    pub roster: Roster,
+    // how the mercenaries feel about working for the squad, from 0 to 100
+    pub morale: i32,
+    // the number of days since the campaign started
+    pub day: u32,
+    // the level of each facility at the home base, 0 if it hasn't been built
+    pub facilities: HashMap<Facility, u32>,
+    // the name of the location the squad is at
+    pub location: String,
+    // the names of the contracts already seen through
+    pub completed: Vec<String>,
+    // the names of the contracts whose deadlines passed before they were seen through
+    pub expired: Vec<String>,
+    // the faction holding each location that's changed hands since the start of the campaign
+    pub control: HashMap<String, String>,
+    // the seed the campaign was started with, which everything random in it comes from
+    pub seed: u64,
+}
+
+impl CampaignState {
+    // starts a new campaign at the given location, from the given seed
+    pub fn new(location: &str, seed: u64) -> Self {
+        CampaignState {
+            funds: STARTING_FUNDS,
+            reputation: 0,
+            roster: Roster::new(STARTING_ROSTER),
+            morale: STARTING_MORALE,
+            day: 0,
+            facilities: HashMap::new(),
+            location: location.to_string(),
+            completed: Vec::new(),
+            expired: Vec::new(),
+            control: HashMap::new(),
+            seed: seed,
+        }
+    }
+
+    // reads in a saved campaign, starting a new one at the given location from the given seed if there isn't one
+    // the save has one value per line, a name followed by its value,
+    // with a completed line per contract done, an expired line per contract let go,
+    // a control line per location that's changed hands, a mercenary line per mercenary on the roster,
+    // and a facility line per facility built at the home base
+    pub fn load(path: &str, start: &str, seed: u64) -> Self {
+        let mut state = CampaignState::new(start, seed);
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(_) => return state,
+        };
+        state.roster = Roster::load(path);
+        // lines that can't be read are printed out and skipped, keeping the rest of the save
+        for line in text.lines() {
+            if let Err(err) = state.apply_save_line(line) {
+                eprintln!("bad campaign save line: {} ({})", line, err);
+            }
+        }
+        state
+    }
+
+    // reads a single line of the save into the campaign
+    // lines it doesn't know, like the mercenary lines read in by the roster, are left alone
+    fn apply_save_line(&mut self, line: &str) -> Result<(), String> {
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        match parts.as_slice() {
+            ["funds", value] => self.funds = parse_field(value, "funds")?,
+            ["reputation", value] => self.reputation = parse_field(value, "reputation")?,
+            ["morale", value] => self.morale = parse_field(value, "morale")?,
+            ["day", value] => self.day = parse_field(value, "day")?,
+            ["seed", value] => self.seed = parse_field(value, "seed")?,
+            ["facility", name, level] => {
+                let facility = Facility::from_name(name).ok_or(format!("bad facility: {}", name))?;
+                self.facilities.insert(facility, parse_field(level, "facility level")?);
+            },
+            ["location", name] => self.location = name.to_string(),
+            ["completed", name] => self.completed.push(name.to_string()),
+            ["expired", name] => self.expired.push(name.to_string()),
+            ["control", location, faction] => {
+                self.control.insert(location.to_string(), faction.to_string());
+            },
+            _ => {},
+        }
+        Ok(())
+    }
+
+    // writes the campaign out to the save file
+    pub fn save(&self, path: &str) {
+        let mut text = format!("funds {}\nreputation {}\nmorale {}\nday {}\nlocation {}\nseed {}\n",
+            self.funds, self.reputation, self.morale, self.day, self.location, self.seed);
+        for name in self.completed.iter() {
+            text.push_str(&format!("completed {}\n", name));
+        }
+        for name in self.expired.iter() {
+            text.push_str(&format!("expired {}\n", name));
+        }
+        let mut control: Vec<_> = self.control.iter().collect();
+        control.sort();
+        for (location, faction) in control {
+            text.push_str(&format!("control {} {}\n", location, faction));
+        }
+        for mercenary in self.roster.members.iter() {
+            text.push_str(&format!("{}\n", mercenary.to_line()));
+        }
+        for facility in Facility::all().iter() {
+            let level = self.facility(*facility);
+            if level > 0 {
+                text.push_str(&format!("facility {} {}\n", facility.name(), level));
+            }
+        }
+        if let Err(err) = fs::write(path, text) {
+            eprintln!("could not save campaign to {}: {}", path, err);
+        }
+    }
+
+    // gives a random number generator for whatever happens next in the campaign
+    // the same for the same seed, day and contracts done, so a campaign can be played through again
+    pub fn rng(&self) -> StdRng {
+        StdRng::seed_from_u64(self.seed.wrapping_add(self.day as u64).wrapping_add((self.completed.len() as u64) << 32))
+    }
+
+    // gives the level of a facility at the home base
+    pub fn facility(&self, facility: Facility) -> u32 {
+        self.facilities.get(&facility).cloned().unwrap_or(0)
+    }
+
+    // gives the faction holding a location, if anyone does
+    pub fn controller<'a>(&'a self, map: &'a CampaignMap, location: &str) -> Option<&'a str> {
+        self.control.get(location).or_else(|| map.control.get(location)).map(|faction| faction.as_str())
+    }
+
+    // gives what the armory has in stock at its level, with prices,
+    // along with whatever's sold by the factions holding locations next to the home base
+    pub fn shop_stock(&self, map: &CampaignMap) -> Vec<(String, i32)> {
+        let armory = self.facility(Facility::Armory);
+        let mut stock: Vec<(String, i32)> = SHOP_STOCK.iter()
+            .filter(|(_, _, level)| *level <= armory)
+            .map(|(item, price, _)| (item.to_string(), *price))
+            .collect();
+        if let Some(home) = map.locations.first() {
+            let neighbours: Vec<&CampaignLocation> = map.locations.iter().filter(|location| map.connected(&home.name, &location.name)).collect();
+            for (faction, item, price) in map.stock.iter() {
+                if neighbours.iter().any(|location| self.controller(map, &location.name) == Some(faction.as_str())) {
+                    stock.push((item.clone(), *price));
+                }
+            }
+        }
+        stock
+    }
+
+    // gives the number of mercenaries fit to go out on a contract
+    pub fn fit(&self) -> u32 {
+        self.roster.members.iter().filter(|mercenary| mercenary.is_fit()).count() as u32
+    }
+
+    // moves the campaign on by a day, healing the injured, paying the mercenaries,
+    // and letting go of any contracts whose deadlines have passed
+    // gives back a summary of the day
+    pub fn advance_day(&mut self, locale: &Locale, map: &CampaignMap) -> String {
+        self.day += 1;
+        // the infirmary speeds up healing, and the training yard keeps everyone sharp
+        let infirmary = self.facility(Facility::Infirmary);
+        let heal = HEAL_PER_DAY * (1.0 + infirmary as f32 * INFIRMARY_HEAL_BONUS);
+        let recovery = 1 + infirmary / 2;
+        let training = self.facility(Facility::TrainingYard) * TRAINING_EXPERIENCE;
+        let mut healed = 0;
+        for mercenary in self.roster.members.iter_mut() {
+            mercenary.health = (mercenary.health + heal).min(MERCENARY_HEALTH);
+            mercenary.experience += training;
+            if mercenary.injury_days > 0 {
+                mercenary.injury_days = mercenary.injury_days.saturating_sub(recovery);
+                if mercenary.injury_days == 0 {
+                    healed += 1;
+                }
+            }
+        }
+        let mut notes = vec![calendar_date(locale, self.day)];
+        if healed > 0 {
+            notes.push(locale.format("healed", &[&healed]));
+        }
+
+        // whoever put up an ignored contract won't be putting in a good word for the squad
+        for contract in map.contracts.iter() {
+            let passed = contract.deadline.map_or(false, |deadline| self.day > deadline);
+            if passed && !self.completed.contains(&contract.name) && !self.expired.contains(&contract.name) {
+                self.expired.push(contract.name.clone());
+                self.reputation -= EXPIRED_CONTRACT_REPUTATION;
+                notes.push(locale.format("contract_expired", &[&display_name(&contract.name)]));
+            }
+        }
+
+        let upkeep = self.roster.len() as i32 * UPKEEP_PER_MERCENARY;
+        if self.funds >= upkeep {
+            self.funds -= upkeep;
+            notes.push(locale.format("upkeep_paid", &[&upkeep]));
+        }else{
+            // the mercenaries get what there is, and aren't happy about it
+            self.funds = 0;
+            notes.push(locale.get("upkeep_unpaid"));
+            notes.extend(self.adjust_morale(locale, UNPAID_MORALE));
+        }
+        notes.join(", ")
+    }
+
+    // gives the contracts on offer, those not yet done or past their deadline, at the squad's location or one route away
+    pub fn available<'a>(&self, map: &'a CampaignMap) -> Vec<&'a Contract> {
+        map.contracts.iter()
+            .filter(|contract| !self.completed.contains(&contract.name))
+            .filter(|contract| contract.deadline.map_or(true, |deadline| self.day <= deadline))
+            .filter(|contract| contract.employer.as_ref().map_or(true, |employer| self.controller(map, &contract.location) != Some(employer.as_str())))
+            .filter(|contract| contract.location == self.location || map.connected(&self.location, &contract.location))
+            .collect()
+    }
+
+    // raises or lowers morale, losing a mercenary to desertion if it bottoms out
+    // gives back a note of the desertion, if there was one
+    pub fn adjust_morale(&mut self, locale: &Locale, change: i32) -> Option<String> {
+        self.morale = (self.morale + change).max(0).min(100);
+        if self.morale == 0 && self.roster.members.pop().is_some() {
+            self.morale = DESERTION_MORALE;
+            return Some(locale.get("deserted"));
+        }
+        None
+    }
+
+    // applies the effects of a choice made on the road
+    // gives back a summary of what changed, and whether the choice means fighting a skirmish
+    pub fn apply_effects(&mut self, locale: &Locale, effects: &[TravelEffect]) -> (String, bool) {
+        let mut changes = Vec::new();
+        let mut skirmish = false;
+        for effect in effects.iter() {
+            match effect {
+                TravelEffect::Funds(change) => {
+                    self.funds = (self.funds + change).max(0);
+                    changes.push(locale.format("effect_funds", &[&format!("{:+}", change)]));
+                },
+                TravelEffect::Roster(change) => {
+                    // new recruits join at the back of the roster, and those leaving go from the back
+                    for _ in 0..(*change).max(0) {
+                        self.roster.hire();
+                    }
+                    for _ in 0..(-*change).max(0) {
+                        self.roster.members.pop();
+                    }
+                    changes.push(locale.format("effect_roster", &[&format!("{:+}", change)]));
+                },
+                TravelEffect::Morale(change) => {
+                    changes.push(locale.format("effect_morale", &[&format!("{:+}", change)]));
+                    changes.extend(self.adjust_morale(locale, *change));
+                },
+                TravelEffect::Skirmish => skirmish = true,
+                TravelEffect::Control(faction) => {
+                    self.control.insert(self.location.clone(), faction.clone());
+                    changes.push(locale.format("location_taken", &[&display_name(&self.location), &display_name(faction)]));
+                },
+            }
+        }
+        (changes.join(", "), skirmish)
+    }
+
+    // takes in the results of a mission, as saved to the mission results file
+    // the contract is the one the mission was for, none for a skirmish on the road
+    // gives back a summary of how it went
+    pub fn apply_results(&mut self, locale: &Locale, contract: Option<&Contract>, text: &str) -> String {
+        let results: HashMap<&str, &str> = text.lines()
+            .filter_map(|line| {
+                let mut parts = line.split_whitespace();
+                Some((parts.next()?, parts.next()?))
+            })
+            .collect();
+        let get = |name: &str| -> i32 { results.get(name).and_then(|value| value.parse().ok()).unwrap_or(0) };
+
+        // every squad member that went out comes back with their health and experience,
+        // those worn down are out of action for a while, and the dead are struck off the roster
+        let mut losses = 0;
+        for line in text.lines() {
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            if let ["member", name, health, kills, alive] = parts.as_slice() {
+                let index = match self.roster.members.iter().position(|mercenary| mercenary.name == *name) {
+                    Some(index) => index,
+                    None => continue,
+                };
+                if *alive != "true" {
+                    self.roster.members.remove(index);
+                    losses += 1;
+                    continue;
+                }
+                let mercenary = &mut self.roster.members[index];
+                mercenary.health = health.parse().unwrap_or(mercenary.health);
+                mercenary.experience += kills.parse::<u32>().unwrap_or(0) * KILL_EXPERIENCE + MISSION_EXPERIENCE;
+                if mercenary.health < INJURY_HEALTH {
+                    mercenary.injury_days = INJURY_DAYS;
+                }
+            }
+        }
+        self.reputation += get("reputation");
+
+        let extracted = results.get("extracted") == Some(&"true");
+        match contract {
+            Some(contract) if extracted => {
+                let pay = (contract.reward + get("reward_bonus") - get("reward_penalty")).max(0);
+                self.funds += pay;
+                self.reputation += CONTRACT_REPUTATION;
+                self.completed.push(contract.name.clone());
+                // the location falls to whoever the contract was for
+                let done = locale.format("contract_done", &[&display_name(&contract.name), &pay, &losses]);
+                match &contract.employer {
+                    Some(employer) => {
+                        self.control.insert(contract.location.clone(), employer.clone());
+                        format!("{}, {}", done, locale.format("location_taken", &[&display_name(&contract.location), &display_name(employer)]))
+                    },
+                    None => done,
+                }
+            },
+            Some(contract) => locale.format("contract_failed", &[&display_name(&contract.name), &losses]),
+            None if extracted => locale.format("ambush_fought", &[&losses]),
+            None => locale.format("ambush_fled", &[&losses]),
+        }
+    }
+}
+
+// gives the date of a day of the campaign, e.g. 3 thaw, year 1
+// the months are named in the language files as month_ followed by their name
+pub fn calendar_date(locale: &Locale, day: u32) -> String {
+    let days_per_year = DAYS_PER_MONTH * CALENDAR_MONTHS.len() as u32;
+    let month = (day % days_per_year) / DAYS_PER_MONTH;
+    let month = locale.get(&format!("month_{}", CALENDAR_MONTHS[month as usize]));
+    locale.format("calendar_date", &[&(day % DAYS_PER_MONTH + 1), &month, &(day / days_per_year + 1)])
+}
+
+// checks if an item is kit carried alongside a weapon, rather than the name of an archetype whose weapon is used
+pub fn is_kit(item: &str) -> bool {
+    item == "lockpicks" || item == "medkit" || item == "shield" || item == "armor" || Perk::from_name(item).is_some()
+}
+
+// gives a name from the campaign map as it should be shown, with spaces for underscores
+pub fn display_name(name: &str) -> String {
+    name.replace('_', " ")
+}
+
+// starts a mission on the given map and encounter, as a game of its own, with the squad brought in from the campaign save
+// clearing out the last mission's results first, so they can't be mistaken for this one's
+// the campaign should be saved first, so the squad goes out as it is now
+// the mission is seeded from the campaign, so the same campaign plays out the same missions
+pub fn launch_mission(locale: &Locale, map_path: &str, encounter_path: Option<&str>, seed: u64) -> Result<Child, String> {
+    let _ = fs::remove_file(MISSION_RESULTS_PATH);
+    let exe = env::current_exe().map_err(|err| locale.format("launch_failed", &[&err]))?;
+    let mut command = process::Command::new(exe);
+    command.arg("--map").arg(map_path).arg("--roster").arg(CAMPAIGN_SAVE_PATH).arg("--seed").arg(seed.to_string());
+    if let Some(encounter_path) = encounter_path {
+        command.arg("--encounter").arg(encounter_path);
+    }
+    command.spawn().map_err(|err| locale.format("launch_failed", &[&err]))
+}
+
+// campaign mission resource
+// the mission being played, along with the contract it's for, none for a skirmish on the road
+#[derive(Default)]
+pub struct CampaignMission {
+    pub running: Option<(Child, Option<Contract>)>,
+}
+
+// travel effect enum
+// something a choice made on the road does to the squad
+#[derive(Debug, Clone, PartialEq)]
+pub enum TravelEffect {
+    Funds(i32),
+    Roster(i32),
+    Morale(i32),
+    // the squad has to fight a skirmish on the road
+    Skirmish,
+    // the location the squad is headed for falls to the given faction
+    Control(String),
+}
+
+impl TravelEffect {
+    // reads an effect written as funds=<change>, roster=<change>, morale=<change>, control=<faction> or skirmish
+    pub fn parse(text: &str) -> Option<Self> {
+        if text == "skirmish" {
+            return Some(TravelEffect::Skirmish);
+        }
+        let mut parts = text.splitn(2, '=');
+        let name = parts.next()?;
+        let value = parts.next()?;
+        if name == "control" {
+            return Some(TravelEffect::Control(value.to_string()));
+        }
+        let change = value.parse().ok()?;
+        match name {
+            "funds" => Some(TravelEffect::Funds(change)),
+            "roster" => Some(TravelEffect::Roster(change)),
+            "morale" => Some(TravelEffect::Morale(change)),
+            _ => None,
+        }
+    }
+}
+
+// travel choice struct
+// one of the ways the squad can deal with something that happens on the road
+#[derive(Debug, Clone)]
+pub struct TravelChoice {
+    pub label: String,
+    pub effects: Vec<TravelEffect>,
+}
+
+// travel event struct
+// something that can happen on the road, with the choices the squad has
+#[derive(Debug, Clone)]
+pub struct TravelEvent {
+    pub name: String,
+    // how likely the event is to be the one that happens, against the weights of the others
+    pub weight: u32,
+    pub description: String,
+    pub choices: Vec<TravelChoice>,
+}
+
+// travel events resource
+// the table of everything that can happen on the road, read in from assets/travel_events.txt
+#[derive(Debug, Clone, Default)]
+pub struct TravelEvents(pub Vec<TravelEvent>);
+
+impl TravelEvents {
+    // reads in the travel events from a file
+    // see assets/travel_events.txt for the layout
+    pub fn load(path: &str) -> Self {
+        let mut events: Vec<TravelEvent> = Vec::new();
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(err) => {
+                eprintln!("could not read travel events from {}: {}", path, err);
+                return TravelEvents(events);
+            },
+        };
+        for line in text.lines().map(|line| line.trim()) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            match parts.as_slice() {
+                ["event", name, weight, description @ ..] => match parse_field(weight, "weight") {
+                    Ok(weight) => events.push(TravelEvent {
+                        name: name.to_string(),
+                        weight: weight,
+                        description: description.join(" "),
+                        choices: Vec::new(),
+                    }),
+                    Err(err) => eprintln!("bad travel event entry: {} ({})", line, err),
+                },
+                ["choice", event, label, effects @ ..] => {
+                    let effects: Option<Vec<TravelEffect>> = effects.iter().map(|effect| TravelEffect::parse(effect)).collect();
+                    match (effects, events.iter_mut().find(|e| e.name == *event)) {
+                        (Some(effects), Some(e)) => e.choices.push(TravelChoice { label: label.to_string(), effects }),
+                        (None, _) => eprintln!("bad travel event entry: {} (bad effect)", line),
+                        (_, None) => eprintln!("travel event choice for unknown event: {}", line),
+                    }
+                },
+                _ => eprintln!("bad travel event entry: {}", line),
+            }
+        }
+        // events without a way out can't be resolved, so they never happen
+        events.retain(|event| !event.choices.is_empty());
+        TravelEvents(events)
+    }
+
+    // picks an event at random, weighted by how likely each is
+    pub fn pick(&self, rng: &mut StdRng) -> Option<usize> {
+        let total: u32 = self.0.iter().map(|event| event.weight).sum();
+        if total == 0 {
+            return None;
+        }
+        let mut roll = rng.gen_range(0, total);
+        for (index, event) in self.0.iter().enumerate() {
+            if roll < event.weight {
+                return Some(index);
+            }
+            roll -= event.weight;
+        }
+        None
+    }
+}
+
+// facility enum
+// the buildings at the home base that can be upgraded
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Facility {
+    // speeds up healing
+    Infirmary,
+    // stocks better kit in the shop
+    Armory,
+    // gives everyone experience every day
+    TrainingYard,
+}
+
+impl Facility {
+    // gives every facility, in the order they're listed on the base screen
+    pub fn all() -> [Facility; 3] {
+        [Facility::Infirmary, Facility::Armory, Facility::TrainingYard]
+    }
+    // gives the name of the facility, as used in the campaign save
+    pub fn name(&self) -> &'static str {
+        match self {
+            Facility::Infirmary => "infirmary",
+            Facility::Armory => "armory",
+            Facility::TrainingYard => "training_yard",
+        }
+    }
+    // gives the facility with the given name
+    pub fn from_name(name: &str) -> Option<Self> {
+        Facility::all().iter().cloned().find(|facility| facility.name() == name)
+    }
+}
+
+// base screen resource
+// whether the base screen is open, which it can only be at the home base
+#[derive(Default)]
+pub struct BaseScreen {
+    pub open: bool,
+}
+
+// base system
+// opens and closes the base screen while the squad is at the home base,
+// and upgrades facilities or buys kit while it's open
+// facilities are listed first, followed by whatever the armory has in stock
+pub fn base_system(inputs: Res<InputState>, locale: Res<Locale>, map: Res<CampaignMap>, mut state: ResMut<CampaignState>, mut base: ResMut<BaseScreen>,
+    camp: Res<Camp>, pending: Res<PendingTravelEvent>, mission: Res<CampaignMission>, mut message: ResMut<CampaignMessage>) {
+    if inputs.key_just_presses.contains(&BASE_KEY) {
+        let at_home = map.locations.first().map_or(false, |home| home.name == state.location);
+        if base.open {
+            base.open = false;
+        }else if !at_home {
+            message.0 = "The base is back home".to_string();
+        }else if mission.running.is_none() && pending.0.is_none() && !camp.open {
+            base.open = true;
+        }
+        return;
+    }
+    if !base.open {
+        return;
+    }
+
+    let keys = [KeyCode::Key1, KeyCode::Key2, KeyCode::Key3, KeyCode::Key4, KeyCode::Key5, KeyCode::Key6, KeyCode::Key7, KeyCode::Key8, KeyCode::Key9];
+    let index = match keys.iter().position(|key| inputs.key_just_presses.contains(key)) {
+        Some(index) => index,
+        None => return,
+    };
+
+    let facilities = Facility::all();
+    if let Some(facility) = facilities.get(index) {
+        let level = state.facility(*facility);
+        let cost = match FACILITY_COSTS.get(level as usize) {
+            Some(cost) => *cost,
+            None => {
+                message.0 = locale.format("facility_maxed", &[&display_name(facility.name())]);
+                return;
+            },
+        };
+        if state.funds < cost {
+            message.0 = locale.format("facility_unaffordable", &[&display_name(facility.name())]);
+            return;
+        }
+        state.funds -= cost;
+        state.facilities.insert(*facility, level + 1);
+        message.0 = locale.format("facility_upgraded", &[&display_name(facility.name()), &(level + 1)]);
+        state.save(CAMPAIGN_SAVE_PATH);
+        return;
+    }
+
+    let stock = state.shop_stock(&map);
+    let (item, price) = match stock.get(index - facilities.len()) {
+        Some((item, price)) => (item.as_str(), *price),
+        None => return,
+    };
+    if state.funds < price {
+        message.0 = locale.format("item_unaffordable", &[&display_name(item)]);
+        return;
+    }
+    // kit goes to whoever's carrying the least, and a new weapon replaces an old one
+    let is_weapon = !is_kit(item);
+    let mercenary = state.roster.members.iter_mut()
+        .filter(|mercenary| !mercenary.equipment.iter().any(|owned| owned == item))
+        .min_by_key(|mercenary| mercenary.equipment.len());
+    match mercenary {
+        Some(mercenary) => {
+            if is_weapon {
+                mercenary.equipment.retain(|owned| is_kit(owned));
+            }
+            mercenary.equipment.push(item.to_string());
+            message.0 = locale.format("item_bought", &[&display_name(item), &display_name(&mercenary.name)]);
+        },
+        None => {
+            message.0 = locale.format("item_owned", &[&display_name(item)]);
+            return;
+        },
+    }
+    state.funds -= price;
+    state.save(CAMPAIGN_SAVE_PATH);
+}
+
+// camp resource
+// whether the squad is camped, which it does after every mission until it breaks camp
+#[derive(Default)]
+pub struct Camp {
+    pub open: bool,
+}
+
+// pending travel event resource
+// the event that happened on the road, waiting on the player to choose what to do about it
+#[derive(Default)]
+pub struct PendingTravelEvent(pub Option<usize>);
+
+// campaign message resource
+// the last thing that happened on the campaign map, shown under the contracts
+#[derive(Default)]
+pub struct CampaignMessage(pub String);
+
+// campaign marker component
+// spawn this component along with the marker of a location on the campaign map
+// holds the colours of the marker, with the squad's location picked out
+pub struct CampaignMarker {
+    pub location: String,
+    pub material: Handle<ColorMaterial>,
+    pub current_material: Handle<ColorMaterial>,
+}
+
+// campaign label component
+// spawn this component along with the text naming a location on the campaign map
+pub struct CampaignLabel {
+    pub location: String,
+}
+
+// campaign text component
+// spawn this component along with the text showing the squad's state and the contracts on offer
+pub struct CampaignText;
+
+// setup campaign system
+// draws the campaign map, with a marker and label for every location and a line of dots along every route
+pub fn setup_campaign_system(mut commands: Commands, mut materials: ResMut<Assets<ColorMaterial>>, asset_server: Res<AssetServer>,
+    mods: Res<ModAssets>, map: Res<CampaignMap>) {
+    let font_handle = asset_server.load(mods.resolve("assets/fonts/LiberationMono-Regular.ttf")).unwrap();
+
+    commands
+        .spawn(Camera2dComponents::default())
+        .with(WorldCamera)
+        .spawn(UiCameraComponents::default());
+
+    let route_material = materials.add(Color::rgb(0.4, 0.3, 0.2).into());
+    for (from, to) in map.routes.iter() {
+        if let (Some(from), Some(to)) = (map.location(from), map.location(to)) {
+            spawn_patrol_link(&mut commands, route_material, from.position, to.position, None);
+        }
+    }
+
+    let location_material = materials.add(Color::rgb(0.5, 0.1, 0.1).into());
+    let current_material = materials.add(Color::GREEN.into());
+    for location in map.locations.iter() {
+        commands
+            .spawn(SimpleRect::new(location_material, Vec2::new(12.0, 12.0)))
+            .with(Position(location.position.0, location.position.1))
+            .with(CampaignMarker {
+                location: location.name.clone(),
+                material: location_material,
+                current_material: current_material,
+            })
+            // label the location just off to the side of its marker
+            .spawn(TextComponents {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    ..Default::default()
+                },
+                text: Text {
+                    value: display_name(&location.name),
+                    font: font_handle,
+                    style: TextStyle {
+                        font_size: 14.0,
+                        color: Color::BLACK,
+                    },
+                },
+                ..Default::default()
+            })
+            .with(Position(location.position.0 + 10.0, location.position.1 - 20.0))
+            .with(CampaignLabel { location: location.name.clone() });
+    }
+    commands
+        .spawn(TextComponents {
+            style: Style {
+                // keep the text in the top left corner
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    left: Val::Px(5.0),
+                    top: Val::Px(5.0),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            text: Text {
+                value: "".to_string(),
+                font: font_handle,
+                style: TextStyle {
+                    font_size: 16.0,
+                    color: Color::BLACK,
+                },
+            },
+            ..Default::default()
+        })
+        .with(CampaignText);
+}
+
+// campaign input system
+// takes the contract with the number pressed, travelling to it first if it's a route away,
+// or hires a mercenary when the hire key is pressed
+pub fn campaign_input_system(inputs: Res<InputState>, locale: Res<Locale>, map: Res<CampaignMap>, events: Res<TravelEvents>,
+    mut state: ResMut<CampaignState>, mut pending: ResMut<PendingTravelEvent>, mut mission: ResMut<CampaignMission>, mut message: ResMut<CampaignMessage>,
+    camp: Res<Camp>, base: Res<BaseScreen>) {
+    // nothing can be done while the squad is out on a mission, camped, or looking around the base
+    if mission.running.is_some() || camp.open || base.open {
+        return;
+    }
+
+    let keys = [KeyCode::Key1, KeyCode::Key2, KeyCode::Key3, KeyCode::Key4, KeyCode::Key5, KeyCode::Key6, KeyCode::Key7, KeyCode::Key8, KeyCode::Key9];
+    let pressed = keys.iter().position(|key| inputs.key_just_presses.contains(key));
+
+    // something that happened on the road has to be dealt with before anything else
+    if let Some(event_index) = pending.0 {
+        let event = &events.0[event_index];
+        let choice = match pressed.and_then(|index| event.choices.get(index)) {
+            Some(choice) => choice,
+            None => return,
+        };
+        pending.0 = None;
+        let (changes, skirmish) = state.apply_effects(&locale, &choice.effects);
+        message.0 = if changes.is_empty() {
+            display_name(&choice.label)
+        }else{
+            locale.format("choice_made", &[&display_name(&choice.label), &changes])
+        };
+        state.save(CAMPAIGN_SAVE_PATH);
+        if skirmish {
+            match launch_mission(&locale, SKIRMISH_MAP_PATH, Some(SKIRMISH_ENCOUNTER_PATH), state.rng().gen()) {
+                Ok(child) => {
+                    message.0 = locale.get("ambushed");
+                    mission.running = Some((child, None));
+                },
+                Err(err) => message.0 = err,
+            }
+        }
+        return;
+    }
+
+    if inputs.key_just_presses.contains(&HIRE_KEY) {
+        if state.funds >= HIRE_COST {
+            state.funds -= HIRE_COST;
+            state.roster.hire();
+            message.0 = locale.get("hired");
+            state.save(CAMPAIGN_SAVE_PATH);
+        }else{
+            message.0 = locale.get("hire_unaffordable");
+        }
+    }
+
+    let index = match pressed {
+        Some(index) => index,
+        None => return,
+    };
+    let contract = match state.available(&map).get(index) {
+        Some(contract) => (*contract).clone(),
+        None => return,
+    };
+
+    if state.fit() < SQUAD_SIZE {
+        message.0 = locale.get("not_enough_fit");
+        return;
+    }
+    // contracts elsewhere mean travelling there first
+    if contract.location != state.location {
+        if state.funds < TRAVEL_COST {
+            message.0 = locale.get("travel_unaffordable");
+            return;
+        }
+        state.funds -= TRAVEL_COST;
+        state.location = contract.location.clone();
+        // travelling takes a day, which might be one day too many
+        message.0 = state.advance_day(&locale, &map);
+        state.save(CAMPAIGN_SAVE_PATH);
+        if contract.deadline.map_or(false, |deadline| state.day > deadline) {
+            message.0 = locale.format("arrived_late", &[&display_name(&contract.location)]);
+            return;
+        }
+
+        // something might happen on the road, which stops the squad taking the contract straight away
+        // the roll comes from the campaign's seed, so the same campaign meets the same trouble on the road
+        let mut rng = state.rng();
+        if rng.gen::<f32>() < TRAVEL_EVENT_CHANCE {
+            if let Some(event_index) = events.pick(&mut rng) {
+                pending.0 = Some(event_index);
+                message.0 = locale.format("on_the_road", &[&display_name(&contract.location)]);
+                return;
+            }
+        }
+    }
+
+    state.save(CAMPAIGN_SAVE_PATH);
+    // who holds the location decides who the squad runs into there
+    let encounter_path = contract.encounter_for(state.controller(&map, &contract.location)).map(|path| path.to_string());
+    match launch_mission(&locale, &contract.map_path, encounter_path.as_deref(), state.rng().gen()) {
+        Ok(child) => {
+            message.0 = locale.format("out_on", &[&display_name(&contract.name)]);
+            mission.running = Some((child, Some(contract)));
+        },
+        Err(err) => message.0 = err,
+    }
+}
+
+// campaign mission system
+// waits for the mission being played to end, then reads its results back into the campaign
+pub fn campaign_mission_system(locale: Res<Locale>, map: Res<CampaignMap>, mut state: ResMut<CampaignState>, mut mission: ResMut<CampaignMission>,
+    mut message: ResMut<CampaignMessage>, mut camp: ResMut<Camp>) {
+    let finished = match &mut mission.running {
+        Some((child, _contract)) => match child.try_wait() {
+            Ok(Some(_status)) => true,
+            Ok(None) => false,
+            Err(err) => {
+                eprintln!("lost track of the mission: {}", err);
+                true
+            },
+        },
+        None => false,
+    };
+    if !finished {
+        return;
+    }
+
+    if let Some((_child, contract)) = mission.running.take() {
+        // a mission closed before anything happened has no results, and counts as a failure
+        let results = fs::read_to_string(MISSION_RESULTS_PATH).unwrap_or_default();
+        message.0 = state.apply_results(&locale, contract.as_ref(), &results);
+        // the mission takes up the day, then the squad makes camp
+        let day = state.advance_day(&locale, &map);
+        message.0 = format!("{}\n{}", message.0, day);
+        camp.open = true;
+        state.save(CAMPAIGN_SAVE_PATH);
+    }
+}
+
+// camp system
+// lets the squad rest at camp for as many days as it likes, healing and getting its spirits back up,
+// then break camp to get back on the road
+pub fn camp_system(inputs: Res<InputState>, locale: Res<Locale>, map: Res<CampaignMap>, mut state: ResMut<CampaignState>, mut camp: ResMut<Camp>, mut message: ResMut<CampaignMessage>) {
+    if !camp.open {
+        return;
+    }
+    if inputs.key_just_presses.contains(&REST_KEY) {
+        let day = state.advance_day(&locale, &map);
+        let morale = state.adjust_morale(&locale, REST_MORALE);
+        message.0 = match morale {
+            Some(desertion) => format!("{}, {}", day, desertion),
+            None => day,
+        };
+        state.save(CAMPAIGN_SAVE_PATH);
+    }else if inputs.key_just_presses.contains(&BREAK_CAMP_KEY) {
+        camp.open = false;
+        message.0 = locale.get("broke_camp");
+    }
+}
+
+// campaign marker system
+// picks out the location the squad is at
+pub fn campaign_marker_system(state: Res<CampaignState>, mut query: Query<(&CampaignMarker, &mut Handle<ColorMaterial>)>) {
+    for (marker, mut material) in &mut query.iter() {
+        *material = if marker.location == state.location {
+            marker.current_material
+        }else{
+            marker.material
+        };
+    }
+}
+
+// campaign label system
+// names each location on the campaign map, along with the faction holding it
+pub fn campaign_label_system(locale: Res<Locale>, map: Res<CampaignMap>, state: Res<CampaignState>, mut query: Query<(&CampaignLabel, &mut Text)>) {
+    for (label, mut text) in &mut query.iter() {
+        text.value = match state.controller(&map, &label.location) {
+            Some(faction) => locale.format("location_held", &[&display_name(&label.location), &display_name(faction)]),
+            None => display_name(&label.location),
+        };
+    }
+}
+
+// campaign text system
+// shows how the squad is doing, and the contracts on offer
+pub fn campaign_text_system(locale: Res<Locale>, map: Res<CampaignMap>, events: Res<TravelEvents>, state: Res<CampaignState>, pending: Res<PendingTravelEvent>,
+    mission: Res<CampaignMission>, message: Res<CampaignMessage>, camp: Res<Camp>, base: Res<BaseScreen>, mut query: Query<(&CampaignText, &mut Text)>) {
+    let mut value = locale.format("campaign_status", &[&calendar_date(&locale, state.day), &state.funds, &state.reputation, &state.roster.len(), &state.fit(),
+        &state.morale, &display_name(&state.location), &state.seed]);
+    value.push_str("\n\n");
+    if mission.running.is_some() {
+        value.push_str(&format!("{}\n", locale.get("mission_in_progress")));
+    }else if camp.open {
+        value.push_str(&format!("{}\n", locale.format("camped", &[&(state.roster.len() as i32 * UPKEEP_PER_MERCENARY)])));
+        for mercenary in state.roster.members.iter() {
+            let injury = if mercenary.injury_days > 0 { locale.format("injured", &[&mercenary.injury_days]) } else { "".to_string() };
+            value.push_str(&format!("{}\n", locale.format("mercenary_status", &[&display_name(&mercenary.name), &mercenary.health, &mercenary.experience, &injury])));
+        }
+        value.push_str(&format!("{}\n{}\n", locale.format("rest", &[&format!("{:?}", REST_KEY)]), locale.format("break_camp", &[&format!("{:?}", BREAK_CAMP_KEY)])));
+    }else if base.open {
+        value.push_str(&format!("{}\n", locale.get("home_base")));
+        let facilities = Facility::all();
+        for (index, facility) in facilities.iter().enumerate() {
+            let level = state.facility(*facility);
+            let upgrade = FACILITY_COSTS.get(level as usize).map_or(locale.get("fully_upgraded"), |cost| locale.format("upgrade_costs", &[cost]));
+            value.push_str(&format!("{}\n", locale.format("facility_line", &[&(index + 1), &display_name(facility.name()), &level, &upgrade])));
+        }
+        value.push_str(&format!("{}\n", locale.get("armory_stock")));
+        for (index, (item, price)) in state.shop_stock(&map).iter().enumerate() {
+            value.push_str(&format!("{}\n", locale.format("item_line", &[&(facilities.len() + index + 1), &display_name(item), price])));
+        }
+        value.push_str(&format!("{}\n", locale.format("leave_base", &[&format!("{:?}", BASE_KEY)])));
+    }else if let Some(event_index) = pending.0 {
+        let event = &events.0[event_index];
+        value.push_str(&format!("{}\n", event.description));
+        for (index, choice) in event.choices.iter().enumerate().take(9) {
+            value.push_str(&format!("{}. {}\n", index + 1, display_name(&choice.label)));
+        }
+    }else{
+        value.push_str(&format!("{}\n", locale.get("contracts")));
+        for (index, contract) in state.available(&map).iter().enumerate().take(9) {
+            let travel = if contract.location == state.location { "".to_string() } else { locale.format("contract_travel", &[&TRAVEL_COST]) };
+            // contracts running out of time are flagged
+            let deadline = contract.deadline.map_or("".to_string(), |deadline| {
+                let left = deadline - state.day;
+                let warning = if left <= DEADLINE_WARNING_DAYS { "!" } else { "" };
+                locale.format("contract_deadline", &[&calendar_date(&locale, deadline), &left, &warning])
+            });
+            let employer = contract.employer.as_ref().map_or("".to_string(), |employer| locale.format("contract_employer", &[&display_name(employer)]));
+            value.push_str(&format!("{}\n", locale.format("contract_line", &[&(index + 1), &display_name(&contract.name), &employer, &display_name(&contract.location),
+                &contract.reward, &travel, &deadline])));
+        }
+        value.push_str(&format!("{}\n", locale.format("hire", &[&format!("{:?}", HIRE_KEY), &HIRE_COST])));
+        if map.locations.first().map_or(false, |home| home.name == state.location) {
+            value.push_str(&format!("{}\n", locale.format("visit_base", &[&format!("{:?}", BASE_KEY)])));
+        }
+    }
+    value.push_str(&format!("\n{}", message.0));
+
+    for (_text, mut text) in &mut query.iter() {
+        text.value = value.clone();
+    }
+}
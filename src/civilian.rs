@@ -0,0 +1,182 @@
+// civilian module
+// bystanders, and the mission record counting them
+use bevy::prelude::*;
+use bevy_rapier2d::rapier::dynamics::RigidBodySet;
+use bevy_rapier2d::rapier::geometry::ColliderSet;
+use bevy_rapier2d::rapier::pipeline::QueryPipeline;
+
+use crate::{
+    CIVILIAN_DEATH_REPUTATION_PENALTY, CIVILIAN_DEATH_REWARD_PENALTY, combat_query, Id,
+    PANIC_DURATION, PANIC_FLEE_DISTANCE, PERCEPTION_RADIUS, Position,
+};
+use crate::behaviour::{Behaviour, BehaviourType, Pathfinder, TilePos};
+use crate::combat::{AttackEvent, DeathEvent};
+use crate::config::GameConfig;
+use crate::map::MapData;
+use crate::nerve::Nerve;
+use crate::perception::{NoiseReaction, Perception};
+use crate::person::AttitudeType;
+use crate::tick::AddTickSystem;
+
+// civilian plugin
+// responsible for bystanders, who wander about until they hear gunfire or see fighting
+// then run away from it and cower until they calm down
+// civilians dying counts against the squad's reward and reputation
+pub struct CivilianPlugin;
+
+// implementation of the plugin trait,
+// required for this to be used as a plugin
+impl Plugin for CivilianPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        // initialise the event states
+        app.init_resource::<WitnessEventState>()
+        .init_resource::<CivilianDeathState>()
+        // civilians notice fighting they can see
+        .add_tick_system(witness_system.system())
+        // add in the panic system
+        .add_tick_system(panic_system.system())
+        // add in the system counting civilian deaths
+        .add_tick_system(civilian_death_system.system());
+    }
+}
+
+// civilian component
+// spawn this component along with any neutral person who should panic when fighting breaks out
+#[derive(Default)]
+pub struct Civilian {
+    // runs while the civilian is panicking, they calm down once it's done
+    pub panic_timer: Option<Timer>,
+}
+
+// mission record resource
+// keeps track of what the squad has done over the mission, for working out rewards and reputation
+// saved to the mission results file whenever it changes
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct MissionRecord {
+    // the number of civilians killed
+    pub civilian_deaths: u32,
+    // taken off the mission's reward
+    pub reward_penalty: u32,
+    // reputation gained or lost over the mission
+    pub reputation: i32,
+    // the number of hostiles that surrendered
+    pub surrendered: u32,
+    // the number of surrendered hostiles the squad captured
+    pub captured: u32,
+    // added on to the mission's reward
+    pub reward_bonus: u32,
+    // the number of squad members killed
+    pub squad_deaths: u32,
+    // the number of squad members that took a hit and lived
+    pub squad_injured: u32,
+    // how each squad member brought in from the roster did
+    pub members: Vec<MemberRecord>,
+    // whether the squad made it out through an extraction zone
+    pub extracted: bool,
+}
+
+// member record struct
+// how a squad member brought in from the roster did over the mission
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct MemberRecord {
+    // the name they're on the roster under
+    pub name: String,
+    pub health: f32,
+    pub kills: u32,
+    pub alive: bool,
+}
+
+// witness event state holds an event reader for attack events
+#[derive(Default)]
+pub struct WitnessEventState {
+    pub event_reader: EventReader<AttackEvent>,
+}
+
+// civilian death state holds an event reader for death events
+#[derive(Default)]
+pub struct CivilianDeathState {
+    pub event_reader: EventReader<DeathEvent>,
+}
+
+// witness system
+// civilians who can see an attack being launched are frightened by it, the same as hearing it
+pub fn witness_system(config: Res<GameConfig>, mut state: ResMut<WitnessEventState>, attacks: Res<Events<AttackEvent>>,
+    pipeline: Res<QueryPipeline>, bodies: Res<RigidBodySet>, colliders: Res<ColliderSet>,
+    mut people: Query<(&Id, &Position)>, mut query: Query<(&Civilian, &mut Perception, &Position)>) {
+    for attack in state.event_reader.iter(&attacks) {
+        let mut origin = None;
+        for (id, pos) in &mut people.iter() {
+            if id.id() == attack.attacker_id {
+                origin = Some((pos.0, pos.1));
+                break;
+            }
+        }
+        let origin = match origin {
+            Some(origin) => origin,
+            None => continue,
+        };
+
+        for (_civilian, mut perception, pos) in &mut query.iter() {
+            let dist = Vec2::new(origin.0 - pos.0, origin.1 - pos.1).length();
+            if dist <= PERCEPTION_RADIUS && combat_query::line_of_sight(&config, &pipeline, &bodies, &colliders, (pos.0, pos.1), origin) {
+                perception.heard = Some(origin);
+            }
+        }
+    }
+}
+
+// panic system
+// sends civilians who were frightened running away from whatever frightened them
+// once they've got away they cower where they are until they calm down
+pub fn panic_system(config: Res<GameConfig>, time: Res<Time>, map: Res<MapData>,
+    mut query: Query<(&mut Civilian, &mut Perception, &mut Behaviour, &mut Nerve, &mut Pathfinder, &Position)>) {
+    for (mut civilian, mut perception, mut behav, mut actions, mut pf, pos) in &mut query.iter() {
+        if perception.reaction != NoiseReaction::Panic {
+            continue;
+        }
+        if let Some(origin) = perception.heard.take() {
+            // drop whatever the civilian was doing
+            actions.clear();
+
+            // run directly away from the noise
+            let away = Vec2::new(pos.0 - origin.0, pos.1 - origin.1);
+            let away = if away.length() > 0.0 { away.normalize() } else { Vec2::new(1.0, 0.0) };
+            let goal = (
+                (pos.0 + away[0] * PANIC_FLEE_DISTANCE).max(10.0).min(config.map_width - 10.0),
+                (pos.1 + away[1] * PANIC_FLEE_DISTANCE).max(10.0).min(config.map_height - 10.0),
+            );
+            let tile = map.get_nearest_free_tile(&TilePos::from_coords(&config, goal.0, goal.1));
+            pf.needs_pathfinding = true;
+            pf.path_goal = tile;
+            pf.real_goal = tile.to_coords(&config);
+
+            behav.current_behaviour = BehaviourType::Panic;
+            // every new fright starts the calming down over again
+            civilian.panic_timer = Some(Timer::from_seconds(PANIC_DURATION, false));
+        }else if behav.current_behaviour == BehaviourType::Panic {
+            let calmed = match &mut civilian.panic_timer {
+                Some(timer) => {
+                    timer.tick(time.delta_seconds);
+                    timer.finished
+                },
+                None => true,
+            };
+            if calmed {
+                civilian.panic_timer = None;
+                behav.current_behaviour = BehaviourType::Empty;
+            }
+        }
+    }
+}
+
+// civilian death system
+// counts every civilian killed against the squad's reward and reputation
+pub fn civilian_death_system(mut state: ResMut<CivilianDeathState>, deaths: Res<Events<DeathEvent>>, mut record: ResMut<MissionRecord>) {
+    for death in state.event_reader.iter(&deaths) {
+        if death.attitude == Some(AttitudeType::Neutral) {
+            record.civilian_deaths += 1;
+            record.reward_penalty += CIVILIAN_DEATH_REWARD_PENALTY;
+            record.reputation -= CIVILIAN_DEATH_REPUTATION_PENALTY;
+        }
+    }
+}
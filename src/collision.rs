@@ -0,0 +1,265 @@
+// collision module
+// bodies and colliders for people and walls in the physics world
+use bevy::prelude::*;
+use std::collections::HashMap;
+use bevy_rapier2d::physics::{
+    create_body_and_collider_system, EntityToBody, EventQueue, Gravity, RapierPhysicsScale,
+    RigidBodyHandleComponent, step_world_system,
+};
+use bevy_rapier2d::rapier::dynamics::{IntegrationParameters, JointSet, RigidBodyBuilder, RigidBodySet};
+use bevy_rapier2d::rapier::geometry::{BroadPhase, ColliderBuilder, ColliderSet, InteractionGroups, NarrowPhase};
+use bevy_rapier2d::rapier::pipeline::{PhysicsPipeline, QueryPipeline};
+use bevy_rapier2d::rapier::math::{Isometry, Vector};
+
+use crate::{MOVEMENT_STAGE, PHYSICS_STAGE, Position, SIM_TICK, Size, Velocity};
+use crate::behaviour::TilePos;
+use crate::combat::{BlastEvent, StatusEffects, Suppression};
+use crate::config::GameConfig;
+use crate::draw::get_translate_from_position;
+use crate::map::MapData;
+use crate::person::AttitudeType;
+use crate::tick::AddTickSystem;
+
+// collision plugin
+// responsible for giving people and walls bodies in the rapier physics world
+// people get kinematic bodies sized from their Size component, and blocked map tiles
+// get static colliders. the position component stays in charge of where everyone is:
+// people are moved here, kept out of blocked tiles, and their bodies are sent after them
+// the physics world is stepped once every tick, after everything has moved, so it keeps pace with the simulation
+pub struct CollisionPlugin;
+
+// implementation of the plugin trait,
+// required for this to be used as a plugin
+impl Plugin for CollisionPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        // add in the physics world, taking a tick's worth of time every step
+        // this is a top down game, so there is no gravity
+        let mut integration = IntegrationParameters::default();
+        integration.set_dt(SIM_TICK);
+        app.add_resource(PhysicsPipeline::new())
+        .add_resource(integration)
+        .add_resource(Gravity(Vector::new(0.0, 0.0)))
+        .add_resource(BroadPhase::new())
+        .add_resource(NarrowPhase::new())
+        .add_resource(RigidBodySet::new())
+        .add_resource(ColliderSet::new())
+        .add_resource(JointSet::new())
+        .add_resource(RapierPhysicsScale(1.0))
+        .add_resource(EventQueue::new(true))
+        .add_resource(EntityToBody::new())
+        // add in the wall colliders resource
+        .init_resource::<WallColliders>()
+        // add in the query pipeline used by the combat queries
+        .add_resource(QueryPipeline::new())
+        // move everyone with a body, and send their bodies after them
+        .add_tick_system_to_stage(MOVEMENT_STAGE, move_body_system.system())
+        // give bodies to anything spawned since the last tick, step the physics world,
+        // and keep the query pipeline up to date with it, in that order
+        .add_tick_system_to_stage(PHYSICS_STAGE, create_body_and_collider_system.system())
+        .add_tick_system_to_stage(PHYSICS_STAGE, step_world_system.system())
+        .add_tick_system_to_stage(PHYSICS_STAGE, update_query_pipeline_system.system())
+        // add in the wall collider system
+        .add_system(build_wall_colliders_system.system())
+        // add in the system blowing open walls caught in blasts
+        .init_resource::<WallBlastState>()
+        .add_tick_system(blast_wall_system.system());
+    }
+}
+
+// wall colliders resource
+// keeps track of whether the static colliders for the map have been built,
+// and which static body blocks off each tile
+#[derive(Default)]
+pub struct WallColliders {
+    pub built: bool,
+    pub walls: HashMap<TilePos, Entity>,
+}
+
+// wall blast state holds an event reader for blast events
+#[derive(Default)]
+pub struct WallBlastState {
+    pub event_reader: EventReader<BlastEvent>,
+}
+
+// gives the physics components for a person at a given position with a given size
+// spawn these along with the person using with_bundle
+// the attitude decides which collision groups the person belongs to
+pub fn get_person_body_components(config: &GameConfig, position: (f32, f32), size: (f32, f32), attitude: &AttitudeType) -> (RigidBodyBuilder, ColliderBuilder) {
+    // the physics world uses the same coordinates as translation
+    let translation = get_translate_from_position(config, position.0, position.1);
+    // the body is kinematic, it goes wherever the position component says, see the move body system
+    (
+        RigidBodyBuilder::new_kinematic().translation(translation.0, translation.1),
+        ColliderBuilder::cuboid(size.0 / 2.0, size.1 / 2.0)
+            .collision_groups(get_person_collision_groups(attitude)),
+    )
+}
+
+// collision group bits
+// each attitude has its own group, so collisions can be filtered per faction
+pub static GROUP_SQUAD: u16 = 0b0000_0001;
+pub static GROUP_HOSTILE: u16 = 0b0000_0010;
+pub static GROUP_NEUTRAL: u16 = 0b0000_0100;
+pub static GROUP_ALLY: u16 = 0b0000_1000;
+pub static GROUP_WALL: u16 = 0b0001_0000;
+pub static GROUP_PROP: u16 = 0b0010_0000;
+pub static GROUP_PROJECTILE: u16 = 0b0100_0000;
+pub static GROUP_ZONE: u16 = 0b1000_0000;
+
+// gives the collision group bit for an attitude
+pub fn get_attitude_group(attitude: &AttitudeType) -> u16 {
+    match attitude {
+        AttitudeType::Squad => GROUP_SQUAD,
+        AttitudeType::Hostile => GROUP_HOSTILE,
+        AttitudeType::Neutral | AttitudeType::Surrendered => GROUP_NEUTRAL,
+        AttitudeType::Ally => GROUP_ALLY,
+    }
+}
+
+// gives the collision groups for a person with a given attitude
+// every person collides with walls, props, projectiles and every faction
+pub fn get_person_collision_groups(attitude: &AttitudeType) -> InteractionGroups {
+    InteractionGroups::new(get_attitude_group(attitude), u16::MAX)
+}
+
+// gives the collision groups for a projectile fired by someone with a given attitude
+// projectiles pass through the shooter's own faction, and through each other
+// squad and ally projectiles also pass through each other's factions
+pub fn get_projectile_collision_groups(shooter: &AttitudeType) -> InteractionGroups {
+    let friendly = match shooter {
+        AttitudeType::Squad | AttitudeType::Ally => GROUP_SQUAD | GROUP_ALLY,
+        _ => get_attitude_group(shooter),
+    };
+    InteractionGroups::new(GROUP_PROJECTILE, u16::MAX & !friendly & !GROUP_PROJECTILE)
+}
+
+// gives the collision groups for walls
+pub fn get_wall_collision_groups() -> InteractionGroups {
+    InteractionGroups::new(GROUP_WALL, u16::MAX & !GROUP_WALL)
+}
+
+// gives the collision groups for neutral props (crates, barrels and the like)
+// props interact with everything except walls, which they can't move into anyway
+pub fn get_prop_collision_groups() -> InteractionGroups {
+    InteractionGroups::new(GROUP_PROP, u16::MAX & !GROUP_WALL)
+}
+
+// move body system
+// moves everything with a physics body by its velocity, as the move system does for everything else,
+// then hands the new position on to its kinematic body in the physics world
+// slowed and suppressed entities move slower, and nobody can move into a blocked tile
+// each axis is checked on its own, so people slide along walls instead of sticking to them
+pub fn move_body_system(time: Res<Time>, config: Res<GameConfig>, map: Res<MapData>, mut bodies: ResMut<RigidBodySet>,
+    mut query: Query<(&mut Position, &Velocity, &Size, &RigidBodyHandleComponent, Option<&StatusEffects>, Option<&Suppression>)>) {
+    for (mut pos, vel, size, handle, effects, suppression) in &mut query.iter() {
+        let slow = effects.map_or(1.0, |effects| effects.speed_multiplier())
+            * suppression.map_or(1.0, |suppression| suppression.speed_multiplier());
+        let step = (vel.0 * slow * time.delta_seconds, vel.1 * slow * time.delta_seconds);
+        // only the middle half of someone has to stay clear of blocked tiles,
+        // so people as wide as a tile can still get through gaps a tile wide without lining up exactly
+        let size = (size.0 / 2.0, size.1 / 2.0);
+
+        // anyone already caught in a blocked tile, e.g. by a door shutting on them, can still walk out of it
+        let stuck = is_box_blocked(&config, &map, (pos.0, pos.1), size);
+        if stuck || !is_box_blocked(&config, &map, (pos.0 + step.0, pos.1), size) {
+            pos.0 += step.0;
+        }
+        if stuck || !is_box_blocked(&config, &map, (pos.0, pos.1 + step.1), size) {
+            pos.1 += step.1;
+        }
+
+        if let Some(body) = bodies.get_mut(handle.handle()) {
+            let translation = get_translate_from_position(&config, pos.0, pos.1);
+            body.set_next_kinematic_position(Isometry::translation(translation.0, translation.1));
+        }
+    }
+}
+
+// gives whether a box of a given size centred on a position overlaps any blocked tile, or the edge of the map
+pub fn is_box_blocked(config: &GameConfig, map: &MapData, center: (f32, f32), size: (f32, f32)) -> bool {
+    let corners = [
+        (center.0 - size.0 / 2.0, center.1 - size.1 / 2.0),
+        (center.0 + size.0 / 2.0, center.1 - size.1 / 2.0),
+        (center.0 - size.0 / 2.0, center.1 + size.1 / 2.0),
+        (center.0 + size.0 / 2.0, center.1 + size.1 / 2.0),
+    ];
+    corners.iter().any(|&(x, y)| !config.is_on_map(x, y) || map.is_tile_blocked(&TilePos::from_coords(config, x, y)))
+}
+
+// update query pipeline system
+// refreshes the query pipeline so combat queries see where everything is now
+pub fn update_query_pipeline_system(mut pipeline: ResMut<QueryPipeline>, bodies: Res<RigidBodySet>, colliders: Res<ColliderSet>) {
+    pipeline.update(&bodies, &colliders);
+}
+
+// build wall colliders system
+// spawns a static collider for every blocked tile on the map
+// runs once, after the map data has been generated
+pub fn build_wall_colliders_system(mut commands: Commands, config: Res<GameConfig>, mut walls: ResMut<WallColliders>, map: Res<MapData>) {
+    if walls.built || !map.generated {
+        return;
+    }
+    for j in 0..map.size.1 {
+        for i in 0..map.size.0 {
+            let tile = TilePos(i, j);
+            if map.is_tile_blocked(&tile) {
+                if let Some(wall) = spawn_wall_collider(&mut commands, &config, &tile) {
+                    walls.walls.insert(tile, wall);
+                }
+            }
+        }
+    }
+    walls.built = true;
+}
+
+// spawns a static collider blocking off a tile, giving back the entity it was spawned as
+pub fn spawn_wall_collider(commands: &mut Commands, config: &GameConfig, tile: &TilePos) -> Option<Entity> {
+    let (x, y) = tile.to_coords(config);
+    let translation = get_translate_from_position(config, x, y);
+    commands.spawn((
+        RigidBodyBuilder::new_static().translation(translation.0, translation.1),
+        ColliderBuilder::cuboid(config.tile_size / 2.0, config.tile_size / 2.0)
+            .collision_groups(get_wall_collision_groups()),
+    ));
+    commands.current_entity()
+}
+
+// gives every tile whose centre lies within a given distance of a position
+pub fn get_tiles_within(config: &GameConfig, map: &MapData, center: (f32, f32), radius: f32) -> Vec<TilePos> {
+    let mut tiles = Vec::new();
+    let start = TilePos::from_coords(config, (center.0 - radius).max(0.0), (center.1 - radius).max(0.0));
+    let end = TilePos::from_coords(config, (center.0 + radius).max(0.0), (center.1 + radius).max(0.0));
+    for y in start.1..=end.1.min(map.size.1 - 1) {
+        for x in start.0..=end.0.min(map.size.0 - 1) {
+            let tile = TilePos(x, y);
+            let (tx, ty) = tile.to_coords(config);
+            if (tx - center.0).powi(2) + (ty - center.1).powi(2) <= radius * radius {
+                tiles.push(tile);
+            }
+        }
+    }
+    tiles
+}
+
+// blast wall system
+// blows open every wall caught in a blast, taking its static body out of the physics world
+// the tile is open ground from then on
+pub fn blast_wall_system(mut commands: Commands, config: Res<GameConfig>, mut state: ResMut<WallBlastState>, events: Res<Events<BlastEvent>>,
+    mut map: ResMut<MapData>, mut walls: ResMut<WallColliders>, mut bodies: ResMut<RigidBodySet>, mut colliders: ResMut<ColliderSet>, mut joints: ResMut<JointSet>,
+    mut query: Query<&RigidBodyHandleComponent>) {
+    for event in state.event_reader.iter(&events) {
+        for tile in get_tiles_within(&config, &map, event.origin, event.radius) {
+            // there's no blowing a way through deep water
+            if map.is_deep_water(&tile) {
+                continue;
+            }
+            if let Some(wall) = walls.walls.remove(&tile) {
+                if let Ok(body) = query.get::<RigidBodyHandleComponent>(wall) {
+                    bodies.remove(body.handle(), &mut colliders, &mut joints);
+                }
+                commands.despawn(wall);
+                map.destroy_tile(&tile);
+            }
+        }
+    }
+}
@@ -0,0 +1,1412 @@
+// combat module
+// resolving the attacks launched by attack actions
+use bevy::prelude::*;
+use std::collections::{VecDeque, HashMap};
+use bevy_rapier2d::physics::RigidBodyHandleComponent;
+use bevy_rapier2d::rapier::dynamics::{JointSet, RigidBodySet};
+use bevy_rapier2d::rapier::geometry::ColliderSet;
+use bevy_rapier2d::rapier::pipeline::QueryPipeline;
+use ordered_float::OrderedFloat;
+
+use crate::{
+    ACTION_STAGE, ARMOR_RESISTANCES, ASSASSIN_FLANK_BONUS, BRUTE_CRIT_MULTIPLIER_BONUS,
+    COMBAT_LOG_BLEEDING_OUT, COMBAT_LOG_FONT_SIZE, COMBAT_LOG_LENGTH, COMBAT_LOG_LINES,
+    combat_query,
+    CRITICAL_NUMBER_SIZE, DAMAGE_NUMBER_DURATION, DAMAGE_NUMBER_RISE, DAMAGE_NUMBER_SIZE,
+    GRENADE_NOISE_RADIUS, HEAL_AMOUNT, HEAL_DURATION, HEAL_RANGE, Id, KNOCKBACK_DURATION,
+    MARKSMAN_CRIT_BONUS, Position, RUNNER_SPEED_BONUS, SHIELD_ARC, SHIELD_BRACED_ARC,
+    SHIELD_REDUCTION, STATUS_MAX_STACKS, STATUS_TICK, SUPPRESSION_ACCURACY, SUPPRESSION_BIAS,
+    SUPPRESSION_DECAY, SUPPRESSION_PER_ATTACK, SUPPRESSION_PINNED, SUPPRESSION_RADIUS,
+    SUPPRESSION_SPEED, SWITCH_DURATION, Velocity,
+};
+use crate::actions::ActionFailure;
+use crate::behaviour::TilePos;
+use crate::config::{GameConfig, Settings};
+use crate::control::{get_weapon_params, Holstered, InputState, KeyBindings, start_switch, Weapon};
+use crate::locale::Locale;
+use crate::map::MapData;
+use crate::mods::ModAssets;
+use crate::nerve::{Action, ActionType, Nerve};
+use crate::perception::{is_enemy, NoiseEvent, SpottedEvent};
+use crate::person::{AttitudeType, Controlled, Person};
+use crate::pool::{Pooled, PoolKind};
+use crate::tick::{AddTickSystem, FrameTime};
+
+// combat plugin
+// responsible for resolving the attacks launched by attack actions
+pub struct CombatPlugin;
+
+// implementation of the plugin trait,
+// required for this to be used as a plugin
+impl Plugin for CombatPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        // add in the attack and death events
+        app.add_tick_event::<AttackEvent>()
+        .add_tick_event::<DamageEvent>()
+        .add_tick_event::<DeathEvent>()
+        // initialise the attack event state resources
+        .init_resource::<AttackEventState>()
+        .init_resource::<DamageEventState>()
+        // add in the damage system
+        .add_tick_system(damage_system.system())
+        // add in the projectile hit system
+        .add_tick_system(projectile_hit_system.system())
+        // add in the knockback system
+        .add_tick_system(apply_knockback_system.system())
+        // add in the knockback recovery system
+        .add_tick_system(knockback_recovery_system.system())
+        // add in the status effect events
+        .add_tick_event::<ApplyStatusEvent>()
+        .add_tick_event::<StatusAppliedEvent>()
+        .add_tick_event::<StatusExpiredEvent>()
+        // initialise the status effect event state resources
+        .init_resource::<WeaponStatusState>()
+        .init_resource::<ApplyStatusState>()
+        // add in the weapon status system
+        .add_tick_system(weapon_status_system.system())
+        // add in the apply status system
+        .add_tick_system(apply_status_system.system())
+        // add in the status tick system
+        .add_tick_system(status_tick_system.system())
+        // add in the blast event, sent whenever something explodes
+        .add_tick_event::<BlastEvent>()
+        .init_resource::<BlastEventState>()
+        // add in the blast system
+        .add_tick_system(blast_system.system())
+        // add in the weapon switching systems
+        .init_resource::<AmmoAttackState>()
+        .add_tick_system(ammo_system.system())
+        .add_tick_system(sidearm_system.system())
+        .add_tick_system_to_stage(ACTION_STAGE, switch_weapon_system.system())
+        // add in the shield system
+        .init_resource::<ShieldAttackState>()
+        .add_tick_system(shield_system.system())
+        // add in the heal system
+        .add_tick_system_to_stage(ACTION_STAGE, heal_system.system())
+        // add in the suppression systems
+        .init_resource::<SuppressionAttackState>()
+        .add_tick_system(suppression_system.system())
+        .add_tick_system(suppression_decay_system.system());
+    }
+}
+
+// attack event
+// sent whenever an attack lands
+pub struct AttackEvent {
+    // id of the attacking entity
+    pub attacker_id: String,
+    // id of the entity being attacked
+    pub target_id: String,
+    // normalized direction from the attacker to the target
+    pub direction: (f32, f32),
+    // where the attack came from, and where the target was when it was launched
+    pub origin: (f32, f32),
+    pub target_position: (f32, f32),
+    // speed that the target is knocked away at
+    pub knockback: f32,
+    // health taken away from the target
+    pub damage: f32,
+    // whether the attack hit, missed attacks are still seen and heard
+    pub hit: bool,
+    // whether the attack was a critical hit
+    pub critical: bool,
+    // the kind of damage the attack does
+    pub damage_type: DamageType,
+}
+
+// damage event
+// sent whenever an attack takes health away from someone, after shields and resistances
+// react to this rather than attack events when only the damage actually done matters
+pub struct DamageEvent {
+    // id of whoever landed the attack
+    pub attacker_id: String,
+    // id and attitude of whoever was hurt, and where they were
+    pub target_id: String,
+    pub attitude: Option<AttitudeType>,
+    pub position: (f32, f32),
+    // health taken away
+    pub damage: f32,
+    pub damage_type: DamageType,
+    // whether the attack was a critical hit
+    pub critical: bool,
+}
+
+// attack event state holds an event reader for attack events
+#[derive(Default)]
+pub struct AttackEventState {
+    pub event_reader: EventReader<AttackEvent>,
+}
+
+// damage event state holds an event reader for attack events
+#[derive(Default)]
+pub struct DamageEventState {
+    pub event_reader: EventReader<AttackEvent>,
+}
+
+// health component
+// spawn this component along with anything that can be worn down by attacks
+// entities without it shrug attacks off
+pub struct Health {
+    pub current: f32,
+    pub max: f32,
+}
+
+impl Health {
+    pub fn new(max: f32) -> Self {
+        Health {
+            current: max,
+            max: max,
+        }
+    }
+    // gives how much health is left, from 0.0 to 1.0
+    pub fn fraction(&self) -> f32 {
+        if self.max > 0.0 { (self.current / self.max).max(0.0) } else { 0.0 }
+    }
+}
+
+// death event
+// sent whenever something runs out of health
+pub struct DeathEvent {
+    // id of whatever died
+    pub id: String,
+    // attitude of whoever died, if it was a person
+    pub attitude: Option<AttitudeType>,
+    // id of whoever landed the killing blow
+    pub killer_id: String,
+    // where it died
+    pub position: (f32, f32),
+}
+
+// damage type enum
+// the kinds of damage attacks can do, each of which is resisted separately
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DamageType {
+    Ballistic,
+    Blade,
+    Fire,
+    Explosive,
+}
+
+impl Default for DamageType {
+    fn default() -> Self {
+        DamageType::Ballistic
+    }
+}
+
+impl DamageType {
+    // every damage type, in the order of their indices
+    pub fn all() -> [DamageType; 4] {
+        [DamageType::Ballistic, DamageType::Blade, DamageType::Fire, DamageType::Explosive]
+    }
+    // gives the damage type with the given name, as used in data files
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "ballistic" => Some(DamageType::Ballistic),
+            "blade" => Some(DamageType::Blade),
+            "fire" => Some(DamageType::Fire),
+            "explosive" => Some(DamageType::Explosive),
+            _ => None,
+        }
+    }
+    // gives the index of the damage type, as passed along in action params
+    pub fn index(&self) -> usize {
+        DamageType::all().iter().position(|damage_type| damage_type == self).unwrap_or(0)
+    }
+    // gives the damage type with the given index, ballistic if there isn't one
+    pub fn from_index(index: usize) -> Self {
+        DamageType::all().get(index).cloned().unwrap_or_default()
+    }
+}
+
+// resistances component
+// spawn this component along with anyone who shrugs off some kinds of damage, e.g. from armour
+// holds how much of each kind of damage is taken off, as a fraction
+#[derive(Debug, Clone, Default)]
+pub struct Resistances(pub HashMap<DamageType, f32>);
+
+impl Resistances {
+    // gives the resistances worn armour gives
+    pub fn armor() -> Self {
+        Resistances(ARMOR_RESISTANCES.iter().cloned().collect())
+    }
+    // gives the multiplier on damage of the given type
+    pub fn multiplier(&self, damage_type: DamageType) -> f32 {
+        1.0 - self.0.get(&damage_type).cloned().unwrap_or(0.0).max(0.0).min(1.0)
+    }
+}
+
+// gives the health an attack actually takes away from someone,
+// after their shield takes some off attacks from in front and their resistances take off their share
+pub fn damage_taken(event: &AttackEvent, shield: Option<&Shield>, resistances: Option<&Resistances>) -> f32 {
+    event.damage
+        * shield.map_or(1.0, |shield| shield.damage_multiplier(event.direction))
+        * resistances.map_or(1.0, |resistances| resistances.multiplier(event.damage_type))
+}
+
+// removes a person from the world, taking their body out of the physics world along with the entity
+pub fn remove_person(commands: &mut Commands, entity: Entity, body: Option<&RigidBodyHandleComponent>,
+    bodies: &mut RigidBodySet, colliders: &mut ColliderSet, joints: &mut JointSet) {
+    if let Some(body) = body {
+        bodies.remove(body.handle(), colliders, joints);
+    }
+    commands.despawn(entity);
+}
+
+// damage system
+// takes health away from everything attacked, removing anything that runs out
+// anyone standing in cover has some of the damage blocked
+pub fn damage_system(mut commands: Commands, config: Res<GameConfig>, map: Res<MapData>, mut state: ResMut<DamageEventState>, events: Res<Events<AttackEvent>>,
+    mut damages: ResMut<Events<DamageEvent>>, mut deaths: ResMut<Events<DeathEvent>>,
+    mut bodies: ResMut<RigidBodySet>, mut colliders: ResMut<ColliderSet>, mut joints: ResMut<JointSet>,
+    mut query: Query<(Entity, &Id, &mut Health, &Position, Option<&Person>, Option<&RigidBodyHandleComponent>, Option<&Shield>, Option<&Resistances>)>) {
+    for event in state.event_reader.iter(&events) {
+        if !event.hit || event.damage <= 0.0 {
+            continue;
+        }
+        for (entity, id, mut health, pos, pers, body, shield, resistances) in &mut query.iter() {
+            if id.id() != event.target_id || health.current <= 0.0 {
+                continue;
+            }
+            let mut damage = damage_taken(event, shield, resistances);
+            if pos.0 >= 0.0 && pos.1 >= 0.0 {
+                damage *= 1.0 - map.get_cover(&TilePos::from_coords(&config, pos.0, pos.1));
+            }
+            health.current -= damage;
+            damages.send(DamageEvent {
+                attacker_id: event.attacker_id.clone(),
+                target_id: id.id(),
+                attitude: pers.map(|pers| pers.attitude),
+                position: (pos.0, pos.1),
+                damage: damage,
+                damage_type: event.damage_type,
+                critical: event.critical,
+            });
+            if health.current <= 0.0 {
+                deaths.send(DeathEvent {
+                    id: id.id(),
+                    attitude: pers.map(|pers| pers.attitude),
+                    killer_id: event.attacker_id.clone(),
+                    position: (pos.0, pos.1),
+                });
+                remove_person(&mut commands, entity, body, &mut bodies, &mut colliders, &mut joints);
+            }
+            break;
+        }
+    }
+}
+
+// blast event
+// sent whenever something explodes, e.g. a grenade once its fuse is out
+pub struct BlastEvent {
+    // id of whoever set off the blast
+    pub attacker_id: String,
+    // where the blast went off
+    pub origin: (f32, f32),
+    // how far the blast reaches
+    pub radius: f32,
+    // health taken away and speed knocked away at, at the centre of the blast
+    pub damage: f32,
+    pub knockback: f32,
+}
+
+// blast event state holds an event reader for blast events
+#[derive(Default)]
+pub struct BlastEventState {
+    pub event_reader: EventReader<BlastEvent>,
+}
+
+// blast system
+// lands an attack on everyone the blast reaches, weaker the further they are from its centre,
+// and makes a loud noise that everyone can hear
+pub fn blast_system(config: Res<GameConfig>, mut state: ResMut<BlastEventState>, events: Res<Events<BlastEvent>>, pipeline: Res<QueryPipeline>,
+    bodies: Res<RigidBodySet>, colliders: Res<ColliderSet>, mut attacks: ResMut<Events<AttackEvent>>, mut noises: ResMut<Events<NoiseEvent>>,
+    mut query: Query<(&Id, &Person, &Position)>) {
+    for event in state.event_reader.iter(&events) {
+        let mut attitude = None;
+        for (id, pers, pos) in &mut query.iter() {
+            if id.id() == event.attacker_id {
+                attitude = Some(pers.attitude);
+            }
+            let strength = combat_query::blast_strength(&config, &pipeline, &bodies, &colliders, event.origin, event.radius, (pos.0, pos.1));
+            if strength <= 0.0 {
+                continue;
+            }
+            // knocked straight away from the centre of the blast
+            let dir = Vec2::new(pos.0 - event.origin.0, pos.1 - event.origin.1);
+            let dir = if dir.length() > 0.0 { dir.normalize() } else { dir };
+            attacks.send(AttackEvent {
+                attacker_id: event.attacker_id.clone(),
+                target_id: id.id(),
+                direction: (dir[0], dir[1]),
+                origin: event.origin,
+                target_position: (pos.0, pos.1),
+                knockback: event.knockback * strength,
+                damage: event.damage * strength,
+                hit: true,
+                critical: false,
+                damage_type: DamageType::Explosive,
+            });
+        }
+        noises.send(NoiseEvent {
+            origin: event.origin,
+            radius: GRENADE_NOISE_RADIUS,
+            attitude: attitude,
+        });
+    }
+}
+
+// knockback component
+// inserted onto an entity that has been knocked back
+// overrides the entity's velocity, fading back to normal control over its duration
+pub struct Knockback {
+    // velocity of the knockback at its strongest
+    pub velocity: (f32, f32),
+    // timer running for the duration of the knockback
+    pub timer: Timer,
+}
+
+// apply knockback system
+// gives every attacked entity a knockback away from its attacker
+pub fn apply_knockback_system(mut commands: Commands, mut state: ResMut<AttackEventState>, events: Res<Events<AttackEvent>>, mut query: Query<(Entity, &Id)>) {
+    for event in state.event_reader.iter(&events) {
+        // missed attacks and attacks with no knockback do nothing here
+        if !event.hit || event.knockback <= 0.0 {
+            continue;
+        }
+        for (entity, id) in &mut query.iter() {
+            if id.id() == event.target_id {
+                commands.insert_one(entity, Knockback {
+                    velocity: (event.direction.0 * event.knockback, event.direction.1 * event.knockback),
+                    timer: Timer::from_seconds(KNOCKBACK_DURATION, false),
+                });
+                break;
+            }
+        }
+    }
+}
+
+// knockback recovery system
+// blends the knockback velocity back into the entity's own velocity
+// the knockback starts at full strength and eases out over its duration
+pub fn knockback_recovery_system(mut commands: Commands, time: Res<Time>, mut query: Query<(Entity, &mut Knockback, &mut Velocity)>) {
+    for (entity, mut knockback, mut vel) in &mut query.iter() {
+        knockback.timer.tick(time.delta_seconds);
+        if knockback.timer.finished {
+            // control is fully returned
+            commands.remove_one::<Knockback>(entity);
+            continue;
+        }
+        // strength goes from 1.0 down to 0.0 over the duration
+        let strength = 1.0 - ezing::quad_out(knockback.timer.elapsed / knockback.timer.duration);
+        vel.0 = vel.0 * (1.0 - strength) + knockback.velocity.0 * strength;
+        vel.1 = vel.1 * (1.0 - strength) + knockback.velocity.1 * strength;
+    }
+}
+
+// projectile hit system
+// ends the lifetime of any projectile that would hit something this tick
+// this sends the projectile back to its pool
+pub fn projectile_hit_system(config: Res<GameConfig>, time: Res<Time>, pipeline: Res<QueryPipeline>, colliders: Res<ColliderSet>, mut query: Query<(&mut Pooled, &Position, &Velocity)>) {
+    for (mut pooled, pos, vel) in &mut query.iter() {
+        if !pooled.active || pooled.kind != PoolKind::Projectile {
+            continue;
+        }
+        // where the projectile will be by the end of this tick
+        let next = (pos.0 + vel.0 * time.delta_seconds, pos.1 + vel.1 * time.delta_seconds);
+        if combat_query::first_hit(&config, &pipeline, &colliders, (pos.0, pos.1), next, pooled.groups, &[]).is_some() {
+            // run the lifetime out so the projectile is recycled
+            let duration = pooled.lifetime.duration;
+            pooled.lifetime.tick(duration);
+        }
+    }
+}
+
+// status kind
+// the different kinds of status effect that can be put on a person
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StatusKind {
+    // wears health down over time
+    Poison,
+    // stops the person carrying out any actions
+    Stun,
+    // scales the person's movement speed down
+    Slow,
+    // wears health down over time, like poison
+    Burn,
+}
+
+impl StatusKind {
+    // gives the status kind with the given name, as used in data files
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "poison" => Some(StatusKind::Poison),
+            "stun" => Some(StatusKind::Stun),
+            "slow" => Some(StatusKind::Slow),
+            "burn" => Some(StatusKind::Burn),
+            _ => None,
+        }
+    }
+    // whether this kind of status effect wears health down
+    pub fn damages(&self) -> bool {
+        match self {
+            StatusKind::Poison | StatusKind::Burn => true,
+            StatusKind::Stun | StatusKind::Slow => false,
+        }
+    }
+    // gives the kind of damage this kind of status effect does, if it's one that can be resisted
+    pub fn damage_type(&self) -> Option<DamageType> {
+        match self {
+            StatusKind::Burn => Some(DamageType::Fire),
+            _ => None,
+        }
+    }
+}
+
+// status effect
+// a single timed effect on a person
+#[derive(Debug, Clone)]
+pub struct StatusEffect {
+    pub kind: StatusKind,
+    // damage a second for poison and burn, the multiplier on speed for slow, unused for stun
+    pub strength: f32,
+    // timer running for the duration of the effect
+    pub timer: Timer,
+    // id of whoever put the effect on, credited with any kill it causes
+    pub source_id: String,
+}
+
+impl StatusEffect {
+    pub fn new(kind: StatusKind, strength: f32, duration: f32) -> Self {
+        StatusEffect {
+            kind: kind,
+            strength: strength,
+            timer: Timer::from_seconds(duration, false),
+            source_id: String::new(),
+        }
+    }
+}
+
+// status effects component
+// inserted onto a person as soon as a status effect is put on them
+// effects of the same kind stack, up to a limit
+#[derive(Default)]
+pub struct StatusEffects {
+    pub effects: Vec<StatusEffect>,
+    // timer until poison and burn next wear health down
+    pub tick: f32,
+}
+
+impl StatusEffects {
+    // puts another effect on
+    // once the limit of stacks is reached, the stack closest to wearing off is replaced instead
+    pub fn apply(&mut self, effect: StatusEffect) {
+        let stacks = self.effects.iter().filter(|other| other.kind == effect.kind).count();
+        if stacks < STATUS_MAX_STACKS {
+            self.effects.push(effect);
+            return;
+        }
+        let oldest = self.effects.iter().enumerate()
+            .filter(|(_, other)| other.kind == effect.kind)
+            .min_by_key(|(_, other)| OrderedFloat(other.timer.duration - other.timer.elapsed))
+            .map(|(index, _)| index);
+        if let Some(index) = oldest {
+            self.effects[index] = effect;
+        }
+    }
+    // whether any stun is on
+    pub fn stunned(&self) -> bool {
+        self.effects.iter().any(|effect| effect.kind == StatusKind::Stun)
+    }
+    // gives the multiplier on speed from every slow that's on
+    pub fn speed_multiplier(&self) -> f32 {
+        self.effects.iter()
+            .filter(|effect| effect.kind == StatusKind::Slow)
+            .fold(1.0, |multiplier, effect| multiplier * effect.strength.max(0.0).min(1.0))
+    }
+}
+
+// apply status event
+// send this to put a status effect on a person, e.g. from an ability or a script
+pub struct ApplyStatusEvent {
+    // id of the person the effect is put on
+    pub target_id: String,
+    pub effect: StatusEffect,
+}
+
+// status applied event
+// sent whenever a status effect is put on a person
+pub struct StatusAppliedEvent {
+    pub target_id: String,
+    pub kind: StatusKind,
+}
+
+// status expired event
+// sent whenever a status effect wears off a person
+pub struct StatusExpiredEvent {
+    pub target_id: String,
+    pub kind: StatusKind,
+}
+
+// weapon status state holds an event reader for attack events
+#[derive(Default)]
+pub struct WeaponStatusState {
+    pub event_reader: EventReader<AttackEvent>,
+}
+
+// apply status state holds an event reader for apply status events
+#[derive(Default)]
+pub struct ApplyStatusState {
+    pub event_reader: EventReader<ApplyStatusEvent>,
+}
+
+// weapon status system
+// puts the status effects of the attacker's weapon on everyone hit
+pub fn weapon_status_system(mut state: ResMut<WeaponStatusState>, events: Res<Events<AttackEvent>>, mut apply_events: ResMut<Events<ApplyStatusEvent>>,
+    mut query: Query<(&Id, &Weapon)>) {
+    for event in state.event_reader.iter(&events) {
+        if !event.hit {
+            continue;
+        }
+        for (id, weapon) in &mut query.iter() {
+            if id.id() != event.attacker_id {
+                continue;
+            }
+            for effect in weapon.effects.iter() {
+                apply_events.send(ApplyStatusEvent {
+                    target_id: event.target_id.clone(),
+                    effect: StatusEffect {
+                        source_id: event.attacker_id.clone(),
+                        ..effect.clone()
+                    },
+                });
+            }
+            break;
+        }
+    }
+}
+
+// apply status system
+// puts status effects on people, giving them the status effects component if they don't have it yet
+pub fn apply_status_system(mut commands: Commands, mut state: ResMut<ApplyStatusState>, events: Res<Events<ApplyStatusEvent>>,
+    mut applied_events: ResMut<Events<StatusAppliedEvent>>, mut query: Query<(Entity, &Person, &Id, Option<&mut StatusEffects>)>) {
+    // people given their first effects this tick
+    // these are gathered up so that several effects in the same tick all get inserted together
+    let mut inserted: HashMap<Entity, StatusEffects> = HashMap::new();
+    for event in state.event_reader.iter(&events) {
+        for (entity, _, id, effects) in &mut query.iter() {
+            if id.id() != event.target_id {
+                continue;
+            }
+            match effects {
+                Some(mut effects) => effects.apply(event.effect.clone()),
+                None => inserted.entry(entity).or_default().apply(event.effect.clone()),
+            }
+            applied_events.send(StatusAppliedEvent {
+                target_id: event.target_id.clone(),
+                kind: event.effect.kind,
+            });
+            break;
+        }
+    }
+    for (entity, effects) in inserted {
+        commands.insert_one(entity, effects);
+    }
+}
+
+// status tick system
+// runs down every status effect, wearing health down for poison and burn
+// and taking effects off once they wear off
+pub fn status_tick_system(mut commands: Commands, time: Res<Time>, mut expired_events: ResMut<Events<StatusExpiredEvent>>, mut deaths: ResMut<Events<DeathEvent>>,
+    mut bodies: ResMut<RigidBodySet>, mut colliders: ResMut<ColliderSet>, mut joints: ResMut<JointSet>,
+    mut query: Query<(Entity, &Id, &mut StatusEffects, &Position, Option<&mut Health>, Option<&Person>, Option<&RigidBodyHandleComponent>, Option<&Resistances>)>) {
+    for (entity, id, mut effects, pos, health, pers, body, resistances) in &mut query.iter() {
+        // wear health down once every tick
+        effects.tick += time.delta_seconds;
+        if effects.tick >= STATUS_TICK {
+            effects.tick -= STATUS_TICK;
+            if let Some(mut health) = health {
+                // whoever put the strongest damaging effect on is credited with the kill
+                let strongest = effects.effects.iter()
+                    .filter(|effect| effect.kind.damages())
+                    .max_by_key(|effect| OrderedFloat(effect.strength));
+                let killer_id = strongest.map_or(String::new(), |effect| effect.source_id.clone());
+                let damage: f32 = effects.effects.iter()
+                    .filter(|effect| effect.kind.damages())
+                    .map(|effect| effect.strength * STATUS_TICK * effect.kind.damage_type().map_or(1.0, |damage_type| resistances.map_or(1.0, |resistances| resistances.multiplier(damage_type))))
+                    .sum();
+                if damage > 0.0 && health.current > 0.0 {
+                    health.current -= damage;
+                    if health.current <= 0.0 {
+                        deaths.send(DeathEvent {
+                            id: id.id(),
+                            attitude: pers.map(|pers| pers.attitude),
+                            killer_id: killer_id,
+                            position: (pos.0, pos.1),
+                        });
+                        remove_person(&mut commands, entity, body, &mut bodies, &mut colliders, &mut joints);
+                        continue;
+                    }
+                }
+            }
+        }
+
+        // run the effects down, taking off any that wear off
+        for effect in effects.effects.iter_mut() {
+            effect.timer.tick(time.delta_seconds);
+        }
+        for effect in effects.effects.iter().filter(|effect| effect.timer.finished) {
+            expired_events.send(StatusExpiredEvent {
+                target_id: id.id(),
+                kind: effect.kind,
+            });
+        }
+        effects.effects.retain(|effect| !effect.timer.finished);
+        if effects.effects.is_empty() {
+            commands.remove_one::<StatusEffects>(entity);
+        }
+    }
+}
+
+// suppression component
+// inserted onto a person under fire, whether or not it's hitting them
+// suppressed people aim worse, move slower and would rather take cover
+#[derive(Default)]
+pub struct Suppression {
+    // from 0 up to 100
+    pub value: f32,
+    // whether the person has been pinned down, and already thought again about what they're doing
+    pub pinned: bool,
+}
+
+impl Suppression {
+    // gives how suppressed the person is, from 0.0 to 1.0
+    pub fn fraction(&self) -> f32 {
+        (self.value / 100.0).max(0.0).min(1.0)
+    }
+    // gives the multiplier on the accuracy of the person's attacks
+    pub fn accuracy_multiplier(&self) -> f32 {
+        1.0 - SUPPRESSION_ACCURACY * self.fraction()
+    }
+    // gives the multiplier on the person's movement speed
+    pub fn speed_multiplier(&self) -> f32 {
+        1.0 - SUPPRESSION_SPEED * self.fraction()
+    }
+    // gives how many times more likely the person is to hide or defend
+    pub fn cover_bias(&self) -> f32 {
+        1.0 + (SUPPRESSION_BIAS - 1.0) * self.fraction()
+    }
+}
+
+// suppression attack state holds an event reader for attack events
+#[derive(Default)]
+pub struct SuppressionAttackState {
+    pub event_reader: EventReader<AttackEvent>,
+}
+
+// suppression system
+// suppresses the attacker's enemies close to the target of every attack, hit or miss
+pub fn suppression_system(mut commands: Commands, mut state: ResMut<SuppressionAttackState>, attacks: Res<Events<AttackEvent>>,
+    mut query: Query<(Entity, &Id, &Person, &Position, Option<&mut Suppression>)>) {
+    // people suppressed for the first time this tick
+    // these are gathered up so that several attacks in the same tick all count
+    let mut inserted: HashMap<Entity, Suppression> = HashMap::new();
+    for attack in state.event_reader.iter(&attacks) {
+        let mut attitude = None;
+        let mut target_pos = None;
+        for (_entity, id, pers, pos, _suppression) in &mut query.iter() {
+            if id.id() == attack.attacker_id {
+                attitude = Some(pers.attitude);
+            }
+            if id.id() == attack.target_id {
+                target_pos = Some((pos.0, pos.1));
+            }
+        }
+        let (attitude, target_pos) = match (attitude, target_pos) {
+            (Some(attitude), Some(target_pos)) => (attitude, target_pos),
+            _ => continue,
+        };
+        for (entity, _id, pers, pos, suppression) in &mut query.iter() {
+            let dist = Vec2::new(pos.0 - target_pos.0, pos.1 - target_pos.1).length();
+            if dist > SUPPRESSION_RADIUS || !is_enemy(attitude, pers.attitude) {
+                continue;
+            }
+            match suppression {
+                Some(mut suppression) => suppression.value = (suppression.value + SUPPRESSION_PER_ATTACK).min(100.0),
+                None => {
+                    let suppression = inserted.entry(entity).or_default();
+                    suppression.value = (suppression.value + SUPPRESSION_PER_ATTACK).min(100.0);
+                },
+            }
+        }
+    }
+    for (entity, suppression) in inserted {
+        commands.insert_one(entity, suppression);
+    }
+}
+
+// suppression decay system
+// wears suppression off over time, taking it away altogether once it's gone
+pub fn suppression_decay_system(mut commands: Commands, time: Res<Time>, mut query: Query<(Entity, &mut Suppression)>) {
+    for (entity, mut suppression) in &mut query.iter() {
+        suppression.value -= SUPPRESSION_DECAY * time.delta_seconds;
+        // back below the point of being pinned down, ready to be pinned down again
+        if suppression.value < SUPPRESSION_PINNED {
+            suppression.pinned = false;
+        }
+        if suppression.value <= 0.0 {
+            commands.remove_one::<Suppression>(entity);
+        }
+    }
+}
+
+// ammo attack state holds an event reader for attack events
+#[derive(Default)]
+pub struct AmmoAttackState {
+    pub event_reader: EventReader<AttackEvent>,
+}
+
+// ammo system
+// uses up ammo with every attack, hit or miss
+// anyone whose weapon runs dry switches to their holstered weapon, or stops attacking if they have nothing else
+pub fn ammo_system(mut state: ResMut<AmmoAttackState>, attacks: Res<Events<AttackEvent>>, mut query: Query<(&Id, &mut Weapon, &mut Nerve, Option<&Holstered>)>) {
+    for attack in state.event_reader.iter(&attacks) {
+        for (id, mut weapon, mut actions, holstered) in &mut query.iter() {
+            if id.id() != attack.attacker_id {
+                continue;
+            }
+            if let Some(ammo) = weapon.ammo {
+                weapon.ammo = Some(ammo.saturating_sub(1));
+                if ammo <= 1 {
+                    match holstered {
+                        Some(_) => start_switch(&mut actions),
+                        None => {
+                            // nothing left to attack with
+                            let attacking = |action: &Action| match action.action_type {
+                                ActionType::Attack => true,
+                                _ => false,
+                            };
+                            if attacking(&actions.current_action) {
+                                actions.current_action = Action::default();
+                                actions.action_timer = None;
+                            }
+                            actions.action_queue.retain(|action| !attacking(action));
+                        },
+                    }
+                }
+            }
+            break;
+        }
+    }
+}
+
+// sidearm system
+// has hostiles fighting someone too close for their weapon switch to their holstered one, if it can reach
+pub fn sidearm_system(mut targets: Query<(&Id, &Position)>, mut query: Query<Without<Controlled, (&Position, &Weapon, &Holstered, &mut Nerve)>>) {
+    let mut positions = HashMap::new();
+    for (id, pos) in &mut targets.iter() {
+        positions.insert(id.id(), (pos.0, pos.1));
+    }
+    for (pos, weapon, holstered, mut actions) in &mut query.iter() {
+        let target = match (actions.current_action.action_type, &actions.current_action.target.1) {
+            (ActionType::Attack, Some(target)) => target.clone(),
+            _ => continue,
+        };
+        if let Some(target_pos) = positions.get(&target) {
+            let dist = Vec2::new(target_pos.0 - pos.0, target_pos.1 - pos.1).length();
+            if dist < weapon.min_range && dist >= holstered.0.min_range && holstered.0.ammo != Some(0) {
+                start_switch(&mut actions);
+            }
+        }
+    }
+}
+
+// switch weapon system
+// carries out switch actions, swapping the weapon in hand with the holstered one once the switch is done
+// any attacks lined up are carried on with the new weapon
+pub fn switch_weapon_system(mut commands: Commands, time: Res<Time>, mut query: Query<(Entity, &mut Nerve, Option<&mut Weapon>, Option<&mut Holstered>)>) {
+    for (entity, mut actions, weapon, holstered) in &mut query.iter() {
+        match actions.current_action.action_type {
+            ActionType::Switch => {},
+            _ => continue,
+        }
+        let finished = match &mut actions.action_timer {
+            Some(timer) => {
+                timer.tick(time.delta_seconds);
+                timer.finished
+            },
+            None => {
+                let duration = actions.current_action.params.as_ref().and_then(|params| params.get("duration").cloned()).unwrap_or(SWITCH_DURATION);
+                actions.action_timer = Some(Timer::from_seconds(duration, false));
+                false
+            },
+        };
+        if !finished {
+            continue;
+        }
+
+        // swap the weapons over, anyone without a weapon of their own has the default one in hand
+        let drawn = match (weapon, holstered) {
+            (Some(mut weapon), Some(mut holstered)) => {
+                std::mem::swap(&mut *weapon, &mut holstered.0);
+                Some(weapon.clone())
+            },
+            (None, Some(mut holstered)) => {
+                let drawn = std::mem::replace(&mut holstered.0, Weapon::default());
+                commands.insert_one(entity, drawn.clone());
+                Some(drawn)
+            },
+            _ => None,
+        };
+        if let Some(drawn) = drawn {
+            let params = get_weapon_params(&drawn);
+            for action in actions.action_queue.iter_mut() {
+                if let (ActionType::Attack, Some(action_params)) = (action.action_type, &mut action.params) {
+                    action_params.extend(params.clone());
+                }
+            }
+        }
+
+        // pop actions queue and ready next action
+        actions.complete_action();
+    }
+}
+
+// medkits component
+// holds how many medkits someone has left to patch people up with
+pub struct Medkits(pub u32);
+
+// heal system
+// carries out heal actions, using up a medkit to patch up the target once the heal is done
+// anyone hurt part way through has to stop, keeping the medkit, see the damage interrupt system
+pub fn heal_system(time: Res<Time>, mut query: Query<(&Id, &Position, &mut Nerve, Option<&mut Medkits>)>, mut targets: Query<(&Id, &Position, &mut Health)>) {
+    for (id, pos, mut actions, medkits) in &mut query.iter() {
+        let target_id = match (actions.current_action.action_type, &actions.current_action.target.1) {
+            (ActionType::Heal, Some(target_id)) => target_id.clone(),
+            (ActionType::Heal, None) => id.id(),
+            _ => continue,
+        };
+        // the heal is called off if whoever was being patched up is gone
+        let mut exists = false;
+        for (tid, _tpos, _health) in &mut targets.iter() {
+            if tid.id() == target_id {
+                exists = true;
+                break;
+            }
+        }
+        if !exists {
+            actions.fail_action(ActionFailure::TargetMissing);
+            continue;
+        }
+        let finished = match &mut actions.action_timer {
+            Some(timer) => {
+                timer.tick(time.delta_seconds);
+                timer.finished
+            },
+            None => {
+                let duration = actions.current_action.params.as_ref().and_then(|params| params.get("duration").cloned()).unwrap_or(HEAL_DURATION);
+                actions.action_timer = Some(Timer::from_seconds(duration, false));
+                false
+            },
+        };
+        if !finished {
+            continue;
+        }
+
+        // patch the target up, as long as there's a medkit left and they haven't wandered off
+        if let Some(mut medkits) = medkits {
+            if medkits.0 > 0 {
+                for (tid, tpos, mut health) in &mut targets.iter() {
+                    if tid.id() != target_id {
+                        continue;
+                    }
+                    if Vec2::new(tpos.0 - pos.0, tpos.1 - pos.1).length() <= HEAL_RANGE {
+                        health.current = (health.current + HEAL_AMOUNT).min(health.max);
+                        medkits.0 -= 1;
+                    }
+                }
+            }
+        }
+        // pop actions queue and ready next action
+        actions.complete_action();
+    }
+}
+
+// shield component
+// spawn this component along with anyone carrying a shield
+// attacks coming from within the arc in front of them do less damage
+pub struct Shield {
+    // how much of the damage of blocked attacks is taken off, as a fraction
+    pub reduction: f32,
+    // the normalized direction the shield is facing in
+    pub facing: (f32, f32),
+    // whether the shield is braced, which widens its arc
+    pub braced: bool,
+}
+
+impl Default for Shield {
+    fn default() -> Self {
+        Shield {
+            reduction: SHIELD_REDUCTION,
+            facing: (1.0, 0.0),
+            braced: false,
+        }
+    }
+}
+
+impl Shield {
+    // gives how wide the arc in front of the shield is, in degrees
+    pub fn arc(&self) -> f32 {
+        if self.braced { SHIELD_BRACED_ARC } else { SHIELD_ARC }
+    }
+    // checks if an attack travelling in the given direction is coming from within the arc in front of the shield
+    pub fn blocks(&self, direction: (f32, f32)) -> bool {
+        let incoming = Vec2::new(-direction.0, -direction.1);
+        if incoming.length() == 0.0 {
+            return false;
+        }
+        let facing = Vec2::new(self.facing.0, self.facing.1);
+        facing.dot(incoming.normalize()) >= (self.arc() / 2.0).to_radians().cos()
+    }
+    // gives the multiplier on the damage of an attack travelling in the given direction
+    pub fn damage_multiplier(&self, direction: (f32, f32)) -> f32 {
+        if self.blocks(direction) { 1.0 - self.reduction } else { 1.0 }
+    }
+}
+
+// shield attack state holds an event reader for attack events
+#[derive(Default)]
+pub struct ShieldAttackState {
+    pub event_reader: EventReader<AttackEvent>,
+}
+
+// shield system
+// turns every shield to face the way its carrier is moving, or the way they last attacked,
+// or towards the point being braced towards, which also braces the shield
+pub fn shield_system(mut state: ResMut<ShieldAttackState>, attacks: Res<Events<AttackEvent>>, mut query: Query<(&Id, &Position, &Velocity, &Nerve, &mut Shield)>) {
+    for attack in state.event_reader.iter(&attacks) {
+        for (id, _pos, _vel, _actions, mut shield) in &mut query.iter() {
+            if id.id() == attack.attacker_id {
+                if attack.direction.0 != 0.0 || attack.direction.1 != 0.0 {
+                    shield.facing = attack.direction;
+                }
+                break;
+            }
+        }
+    }
+    for (_id, pos, vel, actions, mut shield) in &mut query.iter() {
+        shield.braced = false;
+        if let (ActionType::Brace, Some(target)) = (actions.current_action.action_type, actions.current_action.target.0) {
+            let facing = Vec2::new(target.0 - pos.0, target.1 - pos.1);
+            if facing.length() > 0.0 {
+                let facing = facing.normalize();
+                shield.facing = (facing[0], facing[1]);
+            }
+            shield.braced = true;
+            continue;
+        }
+        let velocity = Vec2::new(vel.0, vel.1);
+        if velocity.length() >= 1.0 {
+            let facing = velocity.normalize();
+            shield.facing = (facing[0], facing[1]);
+        }
+    }
+}
+
+// perk enum
+// the perks a mercenary can bring with them in their kit, changing how their attacks land
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Perk {
+    // more likely to land critical hits
+    Marksman,
+    // much more likely to land critical hits when flanking
+    Assassin,
+    // critical hits do more damage
+    Brute,
+    // moves faster
+    Runner,
+}
+
+impl Perk {
+    // gives the perk with the given name, as used in mercenaries' kit
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "marksman" => Some(Perk::Marksman),
+            "assassin" => Some(Perk::Assassin),
+            "brute" => Some(Perk::Brute),
+            "runner" => Some(Perk::Runner),
+            _ => None,
+        }
+    }
+}
+
+// perks component
+// the perks someone has
+#[derive(Debug, Clone, Default)]
+pub struct Perks(pub Vec<Perk>);
+
+impl Perks {
+    // gives the perks named in the given kit
+    pub fn from_equipment(equipment: &[String]) -> Self {
+        Perks(equipment.iter().filter_map(|item| Perk::from_name(item)).collect())
+    }
+    // gives how much the perks add to the chance of an attack being critical
+    pub fn crit_chance_bonus(&self, flanking: bool) -> f32 {
+        self.0.iter().map(|perk| match perk {
+            Perk::Marksman => MARKSMAN_CRIT_BONUS,
+            Perk::Assassin if flanking => ASSASSIN_FLANK_BONUS,
+            _ => 0.0,
+        }).sum()
+    }
+    // gives how much the perks add to the damage multiplier of critical hits
+    pub fn crit_multiplier_bonus(&self) -> f32 {
+        self.0.iter().filter(|&&perk| perk == Perk::Brute).count() as f32 * BRUTE_CRIT_MULTIPLIER_BONUS
+    }
+    // gives the multiplier the perks put on movement speed
+    pub fn speed_multiplier(&self) -> f32 {
+        1.0 + self.0.iter().filter(|&&perk| perk == Perk::Runner).count() as f32 * RUNNER_SPEED_BONUS
+    }
+}
+
+// damage number plugin
+// responsible for the numbers floating up off anyone hit, bigger and brighter for critical hits
+pub struct DamageNumberPlugin;
+
+// implementation of the plugin trait,
+// required for this to be used as a plugin
+impl Plugin for DamageNumberPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        // initialise the event reader for attack events
+        app.init_resource::<DamageNumberState>()
+        // add in the system spawning the numbers
+        .add_system(spawn_damage_number_system.system())
+        // add in the system floating the numbers up and removing them
+        .add_system(float_damage_number_system.system());
+    }
+}
+
+// damage number state holds an event reader for damage events, and the font the numbers are shown in
+#[derive(Default)]
+pub struct DamageNumberState {
+    pub event_reader: EventReader<DamageEvent>,
+    pub font: Option<Handle<Font>>,
+}
+
+// damage number component
+// spawn this component along with the text of a damage number
+pub struct DamageNumber {
+    // timer running for how long the number floats for
+    pub timer: Timer,
+}
+
+// spawn damage number system
+// spawns a number over everyone hurt, showing the damage done
+pub fn spawn_damage_number_system(mut commands: Commands, mut state: ResMut<DamageNumberState>, damages: Res<Events<DamageEvent>>,
+    asset_server: Res<AssetServer>, mods: Res<ModAssets>) {
+    if state.font.is_none() {
+        state.font = asset_server.load(mods.resolve("assets/fonts/LiberationMono-Regular.ttf")).ok();
+    }
+    let font = match state.font {
+        Some(font) => font,
+        None => return,
+    };
+    for damage in state.event_reader.iter(&damages) {
+        let (size, color) = if damage.critical {
+            (CRITICAL_NUMBER_SIZE, Color::rgb(1.0, 0.8, 0.0))
+        }else{
+            (DAMAGE_NUMBER_SIZE, Color::WHITE)
+        };
+        commands
+            .spawn(TextComponents {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    ..Default::default()
+                },
+                text: Text {
+                    // critical hits are marked out with an exclamation mark as well
+                    value: if damage.critical { format!("{}!", damage.damage.round()) } else { damage.damage.round().to_string() },
+                    font: font,
+                    style: TextStyle {
+                        font_size: size,
+                        color: color,
+                    },
+                },
+                ..Default::default()
+            })
+            // start the number just above the target
+            .with(Position(damage.position.0, damage.position.1 - 14.0))
+            .with(DamageNumber {
+                timer: Timer::from_seconds(DAMAGE_NUMBER_DURATION, false),
+            });
+    }
+}
+
+// float damage number system
+// floats damage numbers up, fading them out, and removes them once they're done
+pub fn float_damage_number_system(mut commands: Commands, time: Res<FrameTime>, mut query: Query<(Entity, &mut DamageNumber, &mut Position, &mut Text)>) {
+    for (entity, mut number, mut pos, mut text) in &mut query.iter() {
+        number.timer.tick(time.delta_seconds);
+        if number.timer.finished {
+            commands.despawn(entity);
+            continue;
+        }
+        pos.1 -= DAMAGE_NUMBER_RISE * time.delta_seconds;
+        text.style.color.a = 1.0 - number.timer.elapsed / number.timer.duration;
+    }
+}
+
+// combat log plugin
+// responsible for the combat log, a panel listing who hit, spotted and downed who, and when
+// each entry is coloured by the side of whoever it's about, in the colours of the chosen palette
+pub struct CombatLogPlugin;
+
+// implementation of the plugin trait,
+// required for this to be used as a plugin
+impl Plugin for CombatLogPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        // initialise the log, hidden, and the event readers feeding it
+        app.init_resource::<CombatLog>()
+        .init_resource::<CombatLogState>()
+        // add in the log text
+        .add_startup_system(setup_combat_log_system.system())
+        // add in the system writing events into the log
+        .add_system(combat_log_system.system())
+        // add in the system showing, hiding and scrolling the log
+        .add_system(combat_log_key_system.system())
+        // add in the system showing the log
+        .add_system(combat_log_text_system.system());
+    }
+}
+
+// combat log entry struct
+// one line of the combat log, kept as the key of its text in the language files and what goes into it,
+// so it's shown in whatever language is picked
+#[derive(Debug, Clone)]
+pub struct CombatLogEntry {
+    // seconds since the mission started
+    pub time: f32,
+    pub key: &'static str,
+    pub args: Vec<String>,
+    // the side of whoever the entry is about, which it's coloured by
+    pub attitude: AttitudeType,
+    pub squad_pos: Option<i32>,
+    // whether the entry is about a squad member going down, which is coloured like health instead
+    pub alarm: bool,
+}
+
+// combat log resource
+// every entry logged so far, oldest first, up to the length of the log
+// along with the labels people are logged by, e.g. P1 for squad members and H0 for hostiles
+#[derive(Default)]
+pub struct CombatLog {
+    pub open: bool,
+    pub entries: VecDeque<CombatLogEntry>,
+    // how many entries back from the latest the log is scrolled
+    pub scroll: usize,
+    pub elapsed: f32,
+    pub labels: HashMap<String, String>,
+    // how many hostiles and neutrals have been given labels, to number the next ones
+    pub hostiles: usize,
+    pub neutrals: usize,
+}
+
+impl CombatLog {
+    // adds an entry to the log, dropping the oldest once the log is full
+    pub fn push(&mut self, key: &'static str, args: Vec<String>, attitude: AttitudeType, squad_pos: Option<i32>, alarm: bool) {
+        if self.entries.len() >= COMBAT_LOG_LENGTH {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(CombatLogEntry {
+            time: self.elapsed,
+            key: key,
+            args: args,
+            attitude: attitude,
+            squad_pos: squad_pos,
+            alarm: alarm,
+        });
+        // keep the same entries in view when scrolled back
+        if self.scroll > 0 {
+            self.scroll = (self.scroll + 1).min(self.entries.len().saturating_sub(COMBAT_LOG_LINES));
+        }
+    }
+    // gives the label a person is logged by, giving them one the first time they're logged
+    // squad members go by their place in the squad, everyone else is numbered in the order they turn up
+    pub fn label(&mut self, id: &str, attitude: AttitudeType, squad_pos: Option<i32>) -> String {
+        if let Some(label) = self.labels.get(id) {
+            return label.clone();
+        }
+        let label = match attitude {
+            AttitudeType::Squad => format!("P{}", squad_pos.map_or("?".to_string(), |squad_pos| squad_pos.to_string())),
+            // surrendered hostiles were hostile when they turned up
+            AttitudeType::Hostile | AttitudeType::Surrendered => {
+                self.hostiles += 1;
+                format!("H{}", self.hostiles - 1)
+            },
+            AttitudeType::Neutral => {
+                self.neutrals += 1;
+                format!("N{}", self.neutrals - 1)
+            },
+        };
+        self.labels.insert(id.to_string(), label.clone());
+        label
+    }
+}
+
+// combat log state holds event readers for everything the combat log shows
+#[derive(Default)]
+pub struct CombatLogState {
+    pub damage_reader: EventReader<DamageEvent>,
+    pub death_reader: EventReader<DeathEvent>,
+    pub spotted_reader: EventReader<SpottedEvent>,
+    pub status_reader: EventReader<StatusAppliedEvent>,
+}
+
+// combat log text component
+// spawn this component along with each line of the combat log, with the index of the line from the top
+pub struct CombatLogText(pub usize);
+
+// setup combat log system
+// spawns in the lines of the combat log up the bottom right of the screen, empty until the log is shown
+pub fn setup_combat_log_system(mut commands: Commands, asset_server: Res<AssetServer>, mods: Res<ModAssets>) {
+    let font_handle = asset_server.load(mods.resolve("assets/fonts/LiberationMono-Regular.ttf")).unwrap();
+    for line in 0..COMBAT_LOG_LINES {
+        commands
+            .spawn(TextComponents {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    position: Rect {
+                        right: Val::Px(5.0),
+                        bottom: Val::Px(5.0 + (COMBAT_LOG_LINES - 1 - line) as f32 * (COMBAT_LOG_FONT_SIZE + 2.0)),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                text: Text {
+                    value: "".to_string(),
+                    font: font_handle,
+                    style: TextStyle {
+                        font_size: COMBAT_LOG_FONT_SIZE,
+                        color: Color::BLACK,
+                    },
+                },
+                ..Default::default()
+            })
+            .with(CombatLogText(line));
+    }
+}
+
+// combat log system
+// writes hits, deaths, spotting and status effects into the combat log as they happen,
+// along with squad members left bleeding out by a hit
+pub fn combat_log_system(time: Res<Time>, mut log: ResMut<CombatLog>, mut state: ResMut<CombatLogState>,
+    damages: Res<Events<DamageEvent>>, deaths: Res<Events<DeathEvent>>, spotted: Res<Events<SpottedEvent>>, statuses: Res<Events<StatusAppliedEvent>>,
+    mut people: Query<(&Id, &Person, Option<&Controlled>, Option<&Health>)>) {
+    log.elapsed += time.delta_seconds;
+
+    // everyone still around, by id, for working out who's who
+    let mut sides: HashMap<String, (AttitudeType, Option<i32>, Option<(f32, f32)>)> = HashMap::new();
+    for (id, pers, controlled, health) in &mut people.iter() {
+        sides.insert(id.id(), (pers.attitude, controlled.map(|controlled| controlled.squad_pos), health.map(|health| (health.current, health.max))));
+    }
+    // gives the label and side of whoever has the given id, if they're a person
+    // anyone killed before being logged is only known by the attitude in their death event
+    let who = |log: &mut CombatLog, id: &str, attitude: Option<AttitudeType>| -> Option<(String, AttitudeType, Option<i32>)> {
+        let (attitude, squad_pos) = match sides.get(id) {
+            Some((attitude, squad_pos, _)) => (*attitude, *squad_pos),
+            None => (attitude?, None),
+        };
+        Some((log.label(id, attitude, squad_pos), attitude, squad_pos))
+    };
+
+    for damage in state.damage_reader.iter(&damages) {
+        let (target, attitude, squad_pos) = match who(&mut log, &damage.target_id, damage.attitude) {
+            Some(target) => target,
+            None => continue,
+        };
+        let amount = damage.damage.round().to_string();
+        match who(&mut log, &damage.attacker_id, None) {
+            Some((attacker, attacker_attitude, attacker_pos)) => {
+                let key = if damage.critical { "log_critical" } else { "log_hit" };
+                log.push(key, vec![attacker, target.clone(), amount], attacker_attitude, attacker_pos, false);
+            },
+            None => log.push("log_hurt", vec![target.clone(), amount], attitude, squad_pos, false),
+        }
+        // only the hit that takes them under is logged, not every hit after it
+        if let Some((AttitudeType::Squad, _, Some((current, max)))) = sides.get(&damage.target_id) {
+            let under = |health: f32| health > 0.0 && health < max * COMBAT_LOG_BLEEDING_OUT;
+            if under(*current) && !under(current + damage.damage) {
+                log.push("log_bleeding_out", vec![target], attitude, squad_pos, true);
+            }
+        }
+    }
+    for death in state.death_reader.iter(&deaths) {
+        let (dead, attitude, squad_pos) = match who(&mut log, &death.id, death.attitude) {
+            Some(dead) => dead,
+            None => continue,
+        };
+        let alarm = attitude == AttitudeType::Squad;
+        match who(&mut log, &death.killer_id, None) {
+            Some((killer, _, _)) => log.push("log_killed", vec![killer, dead], attitude, squad_pos, alarm),
+            None => log.push("log_died", vec![dead], attitude, squad_pos, alarm),
+        }
+    }
+    for spot in state.spotted_reader.iter(&spotted) {
+        if let (Some((spotter, attitude, squad_pos)), Some((target, _, _))) = (who(&mut log, &spot.spotter_id, None), who(&mut log, &spot.target_id, None)) {
+            log.push("log_spotted", vec![spotter, target], attitude, squad_pos, false);
+        }
+    }
+    for status in state.status_reader.iter(&statuses) {
+        if let Some((target, attitude, squad_pos)) = who(&mut log, &status.target_id, None) {
+            let key = match status.kind {
+                StatusKind::Poison => "log_poisoned",
+                StatusKind::Stun => "log_stunned",
+                StatusKind::Slow => "log_slowed",
+                StatusKind::Burn => "log_burning",
+            };
+            log.push(key, vec![target], attitude, squad_pos, false);
+        }
+    }
+}
+
+// combat log key system
+// shows or hides the combat log when the log key is pressed, and scrolls it back and forth while it's shown
+pub fn combat_log_key_system(inputs: Res<InputState>, bindings: Res<KeyBindings>, mut log: ResMut<CombatLog>) {
+    if inputs.key_just_presses.contains(&bindings.combat_log) {
+        log.open = !log.open;
+        log.scroll = 0;
+    }
+    if !log.open {
+        return;
+    }
+    if inputs.key_just_presses.contains(&bindings.combat_log_older) {
+        log.scroll = (log.scroll + 1).min(log.entries.len().saturating_sub(COMBAT_LOG_LINES));
+    }
+    if inputs.key_just_presses.contains(&bindings.combat_log_newer) {
+        log.scroll = log.scroll.saturating_sub(1);
+    }
+}
+
+// combat log text system
+// writes out the latest entries of the combat log while it's shown, or the ones it's scrolled back to,
+// each with the minutes and seconds into the mission it happened
+pub fn combat_log_text_system(log: Res<CombatLog>, locale: Res<Locale>, settings: Res<Settings>, mut query: Query<(&CombatLogText, &mut Text)>) {
+    let end = log.entries.len() - log.scroll.min(log.entries.len());
+    let start = end.saturating_sub(COMBAT_LOG_LINES);
+    // the lines are filled from the bottom, so the latest entry is always at the bottom
+    let offset = COMBAT_LOG_LINES - (end - start);
+    for (line, mut text) in &mut query.iter() {
+        let entry = if log.open && line.0 >= offset { log.entries.get(start + line.0 - offset) } else { None };
+        match entry {
+            Some(entry) => {
+                let args: Vec<&dyn std::fmt::Display> = entry.args.iter().map(|arg| arg as &dyn std::fmt::Display).collect();
+                let seconds = entry.time as u32;
+                text.value = format!("[{:02}:{:02}] {}", seconds / 60, seconds % 60, locale.format(entry.key, &args));
+                text.style.color = if entry.alarm {
+                    settings.palette.health
+                }else{
+                    settings.palette.person_color(&entry.attitude, entry.squad_pos, None)
+                };
+            },
+            None => text.value = "".to_string(),
+        }
+    }
+}
@@ -0,0 +1,81 @@
+// combat query module
+// shared helpers for asking the physics world questions about combat
+// both ai perception and the attack system should go through these so that
+// "can i see it" and "can i hit it" always agree with each other
+use bevy_rapier2d::rapier::dynamics::RigidBodySet;
+use bevy_rapier2d::rapier::geometry::{ColliderHandle, ColliderSet, InteractionGroups, Ray};
+use bevy_rapier2d::rapier::math::{Point, Vector};
+use bevy_rapier2d::rapier::pipeline::QueryPipeline;
+
+use crate::config::GameConfig;
+use crate::draw::get_translate_from_position;
+
+// gives a ray from one position to another, along with the distance between them
+// positions are in position coordinates, the ray is in physics coordinates
+fn get_ray_between(config: &GameConfig, from: (f32, f32), to: (f32, f32)) -> Option<(Ray, f32)> {
+    let start = get_translate_from_position(config, from.0, from.1);
+    let end = get_translate_from_position(config, to.0, to.1);
+    let dir = Vector::new(end.0 - start.0, end.1 - start.1);
+    let dist = dir.norm();
+    // no ray can be cast between two points in the same place
+    if dist <= 0.0 {
+        return None;
+    }
+    Some((Ray::new(Point::new(start.0, start.1), dir / dist), dist))
+}
+
+// line of sight
+// checks if anything static (i.e. a wall) lies between two positions
+// people do not block line of sight
+pub fn line_of_sight(config: &GameConfig, pipeline: &QueryPipeline, bodies: &RigidBodySet, colliders: &ColliderSet, from: (f32, f32), to: (f32, f32)) -> bool {
+    let (ray, dist) = match get_ray_between(config, from, to) {
+        Some(ray) => ray,
+        // two points in the same place can always see each other
+        None => return true,
+    };
+
+    let mut clear = true;
+    pipeline.interferences_with_ray(colliders, &ray, dist, InteractionGroups::all(), |_handle, collider, _hit| {
+        // only static bodies block line of sight
+        if bodies.get(collider.parent()).map_or(false, |body| body.is_static()) {
+            clear = false;
+            // stop looking once something is in the way
+            return false;
+        }
+        true
+    });
+    clear
+}
+
+// first hit
+// gives the first collider hit when travelling from one position to another,
+// along with the distance travelled before hitting it
+// only colliders matching the given groups are hit, and
+// colliders in the ignore list are passed through, e.g. the shooter's own collider
+pub fn first_hit(config: &GameConfig, pipeline: &QueryPipeline, colliders: &ColliderSet, from: (f32, f32), to: (f32, f32), groups: InteractionGroups, ignore: &[ColliderHandle]) -> Option<(ColliderHandle, f32)> {
+    let (ray, dist) = get_ray_between(config, from, to)?;
+
+    let mut nearest: Option<(ColliderHandle, f32)> = None;
+    pipeline.interferences_with_ray(colliders, &ray, dist, groups, |handle, _collider, hit| {
+        if !ignore.contains(&handle) {
+            // keep the closest hit along the ray
+            match nearest {
+                Some((_, toi)) if toi <= hit.toi => {},
+                _ => nearest = Some((handle, hit.toi)),
+            }
+        }
+        true
+    });
+    nearest
+}
+
+// blast strength
+// gives how strongly a blast reaches a position, from 1.0 at its centre down to 0.0 at its edge
+// walls shelter whatever is behind them, so anything with a wall in the way isn't reached at all
+pub fn blast_strength(config: &GameConfig, pipeline: &QueryPipeline, bodies: &RigidBodySet, colliders: &ColliderSet, origin: (f32, f32), radius: f32, to: (f32, f32)) -> f32 {
+    let dist = ((to.0 - origin.0).powi(2) + (to.1 - origin.1).powi(2)).sqrt();
+    if radius <= 0.0 || dist > radius || !line_of_sight(config, pipeline, bodies, colliders, origin, to) {
+        return 0.0;
+    }
+    1.0 - dist / radius
+}
@@ -0,0 +1,303 @@
+// config module
+// the game config, launch options and the player's own settings
+use bevy::prelude::*;
+use std::{fs, env};
+
+use crate::{
+    CONFIG_PATH, DEFAULT_COMBAT_VOLUME, DEFAULT_COMMAND_VOLUME, DEFAULT_LANGUAGE, DEFAULT_MAP_HEIGHT,
+    DEFAULT_MAP_PATH, DEFAULT_MAP_WIDTH, DEFAULT_MASTER_VOLUME, DEFAULT_MAX_PATHFINDERS,
+    DEFAULT_MOVEMENT_VOLUME, DEFAULT_MUSIC_VOLUME, DEFAULT_PARTICLE_POOL_SIZE, DEFAULT_PROJECTILE_POOL_SIZE,
+    DEFAULT_TILE_SIZE, DEFAULT_WINDOW_HEIGHT, DEFAULT_WINDOW_WIDTH,
+};
+use crate::display::DisplayMode;
+use crate::encounter::DifficultyLevel;
+use crate::palette::Palette;
+
+// game config resource
+// the size of the screen, the map and the tiles, the map to load, how many pathfinders run at once, how loud each category of sound is,
+// and how many projectiles and particles are pooled
+// read at startup from the config file, see assets/config.txt for the layout,
+// then overridden by the command line arguments or environment variables, the arguments win if both are given
+//   --config <path>           MERCENARIES_CONFIG           the config file to read
+//   --window-width <pixels>   MERCENARIES_WINDOW_WIDTH     the width of the window
+//   --window-height <pixels>  MERCENARIES_WINDOW_HEIGHT    the height of the window
+//   --map-width <pixels>      MERCENARIES_MAP_WIDTH        the width of the map
+//   --map-height <pixels>     MERCENARIES_MAP_HEIGHT       the height of the map
+//   --tile-size <pixels>      MERCENARIES_TILE_SIZE        the size of each map tile
+//   --max-pathfinders <n>     MERCENARIES_MAX_PATHFINDERS  how many people can pathfind in one frame
+// the map path is given with --map, as part of the launch options
+#[derive(Debug, Clone)]
+pub struct GameConfig {
+    pub window_width: f32,
+    pub window_height: f32,
+    pub map_width: f32,
+    pub map_height: f32,
+    pub tile_size: f32,
+    pub map_path: String,
+    pub max_pathfinders: usize,
+    // volumes go from 0.0 (muted) to 1.0 (full volume), and every category is scaled by the master volume
+    pub master_volume: f32,
+    pub combat_volume: f32,
+    pub movement_volume: f32,
+    pub command_volume: f32,
+    pub music_volume: f32,
+    // how many of each kind of pooled entity are spawned up front, pools grow past this if they run dry
+    pub projectile_pool_size: usize,
+    pub particle_pool_size: usize,
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        GameConfig {
+            window_width: DEFAULT_WINDOW_WIDTH,
+            window_height: DEFAULT_WINDOW_HEIGHT,
+            map_width: DEFAULT_MAP_WIDTH,
+            map_height: DEFAULT_MAP_HEIGHT,
+            tile_size: DEFAULT_TILE_SIZE,
+            map_path: DEFAULT_MAP_PATH.to_string(),
+            max_pathfinders: DEFAULT_MAX_PATHFINDERS,
+            master_volume: DEFAULT_MASTER_VOLUME,
+            combat_volume: DEFAULT_COMBAT_VOLUME,
+            movement_volume: DEFAULT_MOVEMENT_VOLUME,
+            command_volume: DEFAULT_COMMAND_VOLUME,
+            music_volume: DEFAULT_MUSIC_VOLUME,
+            projectile_pool_size: DEFAULT_PROJECTILE_POOL_SIZE,
+            particle_pool_size: DEFAULT_PARTICLE_POOL_SIZE,
+        }
+    }
+}
+
+impl GameConfig {
+    // reads the config from a file
+    // anything the file leaves out keeps its default
+    pub fn load(path: &str) -> Self {
+        let mut config = GameConfig::default();
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(err) => {
+                eprintln!("could not read config from {}: {}", path, err);
+                return config;
+            },
+        };
+        for line in text.lines().map(|line| line.trim()) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            match parts.as_slice() {
+                [key, value] => config.set(key, value),
+                _ => panic!("bad config line: {}", line),
+            }
+        }
+        config
+    }
+
+    // reads the config file given by the command line arguments, or the default one,
+    // then applies any settings given on the command line or as environment variables
+    pub fn from_args(args: Vec<String>) -> Self {
+        // gives the value following a flag, or the environment variable if there isn't one
+        let get = |flag: &str, var: &str| -> Option<String> {
+            args.iter().position(|arg| arg == flag)
+                .and_then(|index| args.get(index + 1).cloned())
+                .or_else(|| env::var(var).ok())
+        };
+
+        let mut config = GameConfig::load(&get("--config", "MERCENARIES_CONFIG").unwrap_or(CONFIG_PATH.to_string()));
+        let overrides = [
+            ("window_width", "--window-width", "MERCENARIES_WINDOW_WIDTH"),
+            ("window_height", "--window-height", "MERCENARIES_WINDOW_HEIGHT"),
+            ("map_width", "--map-width", "MERCENARIES_MAP_WIDTH"),
+            ("map_height", "--map-height", "MERCENARIES_MAP_HEIGHT"),
+            ("tile_size", "--tile-size", "MERCENARIES_TILE_SIZE"),
+            ("max_pathfinders", "--max-pathfinders", "MERCENARIES_MAX_PATHFINDERS"),
+        ];
+        for (key, flag, var) in overrides.iter() {
+            if let Some(value) = get(flag, var) {
+                config.set(key, &value);
+            }
+        }
+        config
+    }
+
+    // sets a single setting by name
+    pub fn set(&mut self, key: &str, value: &str) {
+        match key {
+            "window_width" => self.window_width = value.parse().expect("window width must be a number"),
+            "window_height" => self.window_height = value.parse().expect("window height must be a number"),
+            "map_width" => self.map_width = value.parse().expect("map width must be a number"),
+            "map_height" => self.map_height = value.parse().expect("map height must be a number"),
+            "tile_size" => self.tile_size = value.parse().expect("tile size must be a number"),
+            "map" => self.map_path = value.to_string(),
+            "max_pathfinders" => self.max_pathfinders = value.parse().expect("max pathfinders must be a number"),
+            "master_volume" => self.master_volume = value.parse().expect("master volume must be a number"),
+            "combat_volume" => self.combat_volume = value.parse().expect("combat volume must be a number"),
+            "movement_volume" => self.movement_volume = value.parse().expect("movement volume must be a number"),
+            "command_volume" => self.command_volume = value.parse().expect("command volume must be a number"),
+            "music_volume" => self.music_volume = value.parse().expect("music volume must be a number"),
+            "projectile_pool_size" => self.projectile_pool_size = value.parse().expect("projectile pool size must be a number"),
+            "particle_pool_size" => self.particle_pool_size = value.parse().expect("particle pool size must be a number"),
+            _ => panic!("unknown config setting: {}", key),
+        }
+    }
+
+    // gives the size of the map in tiles
+    pub fn map_size(&self) -> (usize, usize) {
+        ((self.map_width / self.tile_size) as usize, (self.map_height / self.tile_size) as usize)
+    }
+
+    // gives whether a position is on the map
+    pub fn is_on_map(&self, x: f32, y: f32) -> bool {
+        x >= 0.0 && y >= 0.0 && x < self.map_width && y < self.map_height
+    }
+}
+
+// launch options resource
+// the options the game was launched with, read from the command line arguments
+// each option can also be given as an environment variable, the arguments win if both are given
+//   --map <path>        MERCENARIES_MAP        the tiled map to load, instead of the one in the game config
+//   --seed <number>     MERCENARIES_SEED       the seed everything random in the run comes from, rolled if not given
+//                                              with --campaign, the seed of a new campaign
+//   --encounter <path>  MERCENARIES_ENCOUNTER  the encounter file to spawn hostiles from
+//   --debug-physics     MERCENARIES_DEBUG_PHYSICS  draw the outlines of every collider
+//   --difficulty <easy|normal|hard>  MERCENARIES_DIFFICULTY  how tough the hostiles are
+//   --adaptive-difficulty  MERCENARIES_ADAPTIVE_DIFFICULTY  ease off when the squad is struggling
+//   --night             MERCENARIES_NIGHT  start the mission at night, lit only by the map's lights
+//   --roster <path>     MERCENARIES_ROSTER  the campaign save to bring the squad in from
+//   --tiles <path>      MERCENARIES_TILES  tiles painted in the tile editor, laid over the map's own
+#[derive(Debug, Clone)]
+pub struct LaunchOptions {
+    pub map_path: String,
+    pub seed: Option<u64>,
+    pub encounter_path: Option<String>,
+    pub debug_physics: bool,
+    pub difficulty: DifficultyLevel,
+    pub adaptive_difficulty: bool,
+    pub night: bool,
+    pub roster_path: Option<String>,
+    pub tiles_path: Option<String>,
+}
+
+impl Default for LaunchOptions {
+    fn default() -> Self {
+        LaunchOptions {
+            map_path: DEFAULT_MAP_PATH.to_string(),
+            seed: None,
+            encounter_path: None,
+            debug_physics: false,
+            difficulty: DifficultyLevel::Normal,
+            adaptive_difficulty: false,
+            night: false,
+            roster_path: None,
+            tiles_path: None,
+        }
+    }
+}
+
+impl LaunchOptions {
+    // reads the launch options from the command line arguments and environment variables
+    // anything the game config also covers starts out as the config says
+    pub fn from_args(args: Vec<String>, config: &GameConfig) -> Self {
+        // gives the value following a flag, or the environment variable if there isn't one
+        let get = |flag: &str, var: &str| -> Option<String> {
+            args.iter().position(|arg| arg == flag)
+                .and_then(|index| args.get(index + 1).cloned())
+                .or_else(|| env::var(var).ok())
+        };
+
+        let mut options = LaunchOptions::default();
+        options.map_path = config.map_path.clone();
+        if let Some(path) = get("--map", "MERCENARIES_MAP") {
+            options.map_path = path;
+        }
+        options.seed = get("--seed", "MERCENARIES_SEED").map(|seed| seed.parse().expect("seed must be a number"));
+        options.encounter_path = get("--encounter", "MERCENARIES_ENCOUNTER");
+        options.debug_physics = args.iter().any(|arg| arg == "--debug-physics") || env::var("MERCENARIES_DEBUG_PHYSICS").is_ok();
+        if let Some(level) = get("--difficulty", "MERCENARIES_DIFFICULTY") {
+            options.difficulty = DifficultyLevel::from_name(&level).expect("difficulty must be easy, normal or hard");
+        }
+        options.adaptive_difficulty = args.iter().any(|arg| arg == "--adaptive-difficulty") || env::var("MERCENARIES_ADAPTIVE_DIFFICULTY").is_ok();
+        options.night = args.iter().any(|arg| arg == "--night") || env::var("MERCENARIES_NIGHT").is_ok();
+        options.roster_path = get("--roster", "MERCENARIES_ROSTER");
+        options.tiles_path = get("--tiles", "MERCENARIES_TILES");
+        options
+    }
+}
+
+// settings resource
+// the player's own settings, kept in the settings file between runs
+// one setting per line, in the form
+//   display_mode <windowed|borderless|fullscreen>
+//   ui_scale <scale>, how much bigger to draw the ui on top of scaling it to the window
+//   palette <default|deuteranopia|protanopia|tritanopia>
+//   color <player|squad|neutral|hostile|attack|health|health_empty> <r> <g> <b>, changing one colour of the palette
+//   language <name>, the name of a language file in the language folder
+#[derive(Debug, Clone)]
+pub struct Settings {
+    pub display_mode: DisplayMode,
+    pub ui_scale: f32,
+    pub palette: Palette,
+    pub language: String,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            display_mode: DisplayMode::Windowed,
+            ui_scale: 1.0,
+            palette: Palette::default(),
+            language: DEFAULT_LANGUAGE.to_string(),
+        }
+    }
+}
+
+impl Settings {
+    // reads the settings from a file
+    // there is no settings file until a setting is first changed, so anything missing keeps its default
+    pub fn load(path: &str) -> Self {
+        let mut settings = Settings::default();
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(_) => return settings,
+        };
+        for line in text.lines().map(|line| line.trim()) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            match parts.as_slice() {
+                ["display_mode", mode] => match DisplayMode::from_name(mode) {
+                    Some(mode) => settings.display_mode = mode,
+                    None => eprintln!("unknown display mode in settings: {}", mode),
+                },
+                ["ui_scale", scale] => match scale.parse::<f32>() {
+                    Ok(scale) if scale > 0.0 => settings.ui_scale = scale,
+                    _ => eprintln!("bad ui scale in settings: {}", scale),
+                },
+                // colours set before the palette line are replaced by the preset
+                ["palette", name] => match Palette::preset(name) {
+                    Some(palette) => settings.palette = palette,
+                    None => eprintln!("unknown palette in settings: {}", name),
+                },
+                ["color", key, r, g, b] => match (r.parse::<f32>(), g.parse::<f32>(), b.parse::<f32>()) {
+                    (Ok(r), Ok(g), Ok(b)) => if !settings.palette.set(key, Color::rgb(r, g, b)) {
+                        eprintln!("unknown palette colour in settings: {}", key);
+                    },
+                    _ => eprintln!("bad colour in settings: {}", line),
+                },
+                ["language", language] => settings.language = language.to_string(),
+                _ => eprintln!("bad settings line: {}", line),
+            }
+        }
+        settings
+    }
+    // writes the settings to a file
+    pub fn save(&self, path: &str) {
+        let mut text = format!("display_mode {}\nui_scale {}\npalette {}\nlanguage {}\n", self.display_mode.name(), self.ui_scale, self.palette.name, self.language);
+        for (key, color) in self.palette.custom_colors() {
+            text.push_str(&format!("color {} {} {} {}\n", key, color.r, color.g, color.b));
+        }
+        if let Err(err) = fs::write(path, text) {
+            eprintln!("could not save settings to {}: {}", path, err);
+        }
+    }
+}
@@ -0,0 +1,400 @@
+// console module
+// the developer console
+use bevy::prelude::*;
+use bevy::input::keyboard::{ElementState, KeyboardInput};
+use bevy_rapier2d::physics::RigidBodyHandleComponent;
+use bevy_rapier2d::rapier::dynamics::{JointSet, RigidBodySet};
+use bevy_rapier2d::rapier::geometry::ColliderSet;
+use bevy_rapier2d::rapier::math::Isometry;
+
+use crate::{CONSOLE_FONT_SIZE, CONSOLE_LINES, GameRng, Id, Position, RunSeed};
+use crate::combat::{DeathEvent, Medkits, Perk, Perks, remove_person, Resistances, Shield};
+use crate::config::GameConfig;
+use crate::control::{InputState, KeyBindings};
+use crate::door::{Keyring, Lockpicker};
+use crate::draw::get_translate_from_position;
+use crate::encounter::{Archetypes, spawn_civilian, spawn_hostile};
+use crate::mods::ModAssets;
+use crate::person::{AttitudeType, Controlled, Person};
+use crate::weather::{Weather, WeatherKind};
+
+// console plugin
+// responsible for the developer console, opened with the console key, for testing encounters and behaviours
+// commands are looked up in the console registry, then sent out as console command events
+// for whichever system handles them, so other plugins can register commands of their own
+pub struct ConsolePlugin;
+
+// implementation of the plugin trait,
+// required for this to be used as a plugin
+impl Plugin for ConsolePlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        // add in the built in commands
+        let mut registry = ConsoleRegistry::default();
+        registry.register("help", "help", 0);
+        registry.register("spawn", "spawn <hostile|civilian> <x> <y> [archetype]", 3);
+        registry.register("give_item", "give_item <squad_pos> <lockpicks|medkit|shield|armor|weapon archetype|perk|key name>", 2);
+        registry.register("teleport", "teleport <squad_pos> <x> <y>", 3);
+        registry.register("set_seed", "set_seed <seed>", 1);
+        registry.register("toggle_fog", "toggle_fog", 0);
+        registry.register("kill_all", "kill_all", 0);
+
+        app.add_resource(registry)
+        .init_resource::<Console>()
+        .init_resource::<ConsoleInputState>()
+        .init_resource::<ConsoleWorldState>()
+        .init_resource::<ConsoleSettingsState>()
+        // add in the event sent for every command entered
+        .add_event::<ConsoleCommand>()
+        // add in the console text
+        .add_startup_system(setup_console_system.system())
+        // add in the system reading what's typed into the console
+        .add_system(console_input_system.system())
+        // add in the systems carrying out the built in commands
+        .add_system(console_world_system.system())
+        .add_system(console_settings_system.system())
+        // add in the system showing the console
+        .add_system(console_text_system.system());
+    }
+}
+
+// console command event
+// sent whenever a registered command is entered into the console
+#[derive(Debug, Clone)]
+pub struct ConsoleCommand {
+    pub name: String,
+    pub args: Vec<String>,
+}
+
+// console registry resource
+// every command the console knows, in the order they were registered
+#[derive(Default)]
+pub struct ConsoleRegistry {
+    // name of the command, how to use it, and how many arguments it needs at least
+    pub commands: Vec<(String, String, usize)>,
+}
+
+impl ConsoleRegistry {
+    // adds a command to the registry, replacing any other with the same name
+    pub fn register(&mut self, name: &str, usage: &str, args: usize) {
+        self.commands.retain(|(other, _, _)| other != name);
+        self.commands.push((name.to_string(), usage.to_string(), args));
+    }
+    // reads a line entered into the console
+    // gives back the command to send out, or what was wrong with the line
+    pub fn parse(&self, line: &str) -> Result<ConsoleCommand, String> {
+        let mut parts = line.split_whitespace();
+        let name = parts.next().ok_or_else(|| "".to_string())?;
+        let args: Vec<String> = parts.map(|part| part.to_string()).collect();
+        match self.commands.iter().find(|(other, _, _)| other == name) {
+            Some((_, usage, needed)) if args.len() < *needed => Err(format!("usage: {}", usage)),
+            Some(_) => Ok(ConsoleCommand { name: name.to_string(), args }),
+            None => Err(format!("unknown command: {}, try help", name)),
+        }
+    }
+}
+
+// console resource
+// whether the console is open, what's being typed into it, and what it has said back
+#[derive(Default)]
+pub struct Console {
+    pub open: bool,
+    pub input: String,
+    pub log: Vec<String>,
+}
+
+impl Console {
+    // adds a line to the console's output, dropping the oldest once there are too many to show
+    pub fn print(&mut self, line: String) {
+        self.log.push(line);
+        if self.log.len() > CONSOLE_LINES {
+            self.log.remove(0);
+        }
+    }
+}
+
+// console text component
+// spawn this component along with the text showing the console
+pub struct ConsoleText;
+
+// console input state
+// holds an event reader for key presses, read straight from the keyboard so typing isn't taken as orders
+#[derive(Default)]
+pub struct ConsoleInputState {
+    pub event_reader: EventReader<KeyboardInput>,
+    // whether either shift key is held down
+    pub shift: bool,
+}
+
+// console world state holds an event reader for console commands that change the mission
+#[derive(Default)]
+pub struct ConsoleWorldState {
+    pub event_reader: EventReader<ConsoleCommand>,
+}
+
+// console settings state holds an event reader for console commands that change how the mission runs
+#[derive(Default)]
+pub struct ConsoleSettingsState {
+    pub event_reader: EventReader<ConsoleCommand>,
+}
+
+// gives the character typed by a key, if it types one
+pub fn console_char(key: KeyCode, shift: bool) -> Option<char> {
+    let c = match key {
+        KeyCode::A => 'a', KeyCode::B => 'b', KeyCode::C => 'c', KeyCode::D => 'd', KeyCode::E => 'e', KeyCode::F => 'f',
+        KeyCode::G => 'g', KeyCode::H => 'h', KeyCode::I => 'i', KeyCode::J => 'j', KeyCode::K => 'k', KeyCode::L => 'l',
+        KeyCode::M => 'm', KeyCode::N => 'n', KeyCode::O => 'o', KeyCode::P => 'p', KeyCode::Q => 'q', KeyCode::R => 'r',
+        KeyCode::S => 's', KeyCode::T => 't', KeyCode::U => 'u', KeyCode::V => 'v', KeyCode::W => 'w', KeyCode::X => 'x',
+        KeyCode::Y => 'y', KeyCode::Z => 'z',
+        KeyCode::Key0 | KeyCode::Numpad0 => '0', KeyCode::Key1 | KeyCode::Numpad1 => '1', KeyCode::Key2 | KeyCode::Numpad2 => '2',
+        KeyCode::Key3 | KeyCode::Numpad3 => '3', KeyCode::Key4 | KeyCode::Numpad4 => '4', KeyCode::Key5 | KeyCode::Numpad5 => '5',
+        KeyCode::Key6 | KeyCode::Numpad6 => '6', KeyCode::Key7 | KeyCode::Numpad7 => '7', KeyCode::Key8 | KeyCode::Numpad8 => '8',
+        KeyCode::Key9 | KeyCode::Numpad9 => '9',
+        KeyCode::Minus if shift => '_',
+        KeyCode::Minus | KeyCode::Subtract => '-',
+        KeyCode::Underline => '_',
+        KeyCode::Period | KeyCode::Decimal => '.',
+        KeyCode::Comma => ',',
+        KeyCode::Space => ' ',
+        _ => return None,
+    };
+    Some(if shift { c.to_ascii_uppercase() } else { c })
+}
+
+// setup console system
+// spawns in the console text along the bottom of the screen, empty until the console is opened
+pub fn setup_console_system(mut commands: Commands, asset_server: Res<AssetServer>, mods: Res<ModAssets>) {
+    let font_handle = asset_server.load(mods.resolve("assets/fonts/LiberationMono-Regular.ttf")).unwrap();
+    commands
+        .spawn(TextComponents {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    bottom: Val::Px(5.0),
+                    left: Val::Px(5.0),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            text: Text {
+                value: "".to_string(),
+                font: font_handle,
+                style: TextStyle {
+                    font_size: CONSOLE_FONT_SIZE,
+                    color: Color::BLACK,
+                },
+            },
+            ..Default::default()
+        })
+        .with(ConsoleText);
+}
+
+// console input system
+// opens and closes the console, and types into it while it's open
+// the keyboard is taken from the rest of the game while the console is open
+pub fn console_input_system(mut inputs: ResMut<InputState>, bindings: Res<KeyBindings>, mut state: ResMut<ConsoleInputState>, keyboard_input_events: Res<Events<KeyboardInput>>,
+    registry: Res<ConsoleRegistry>, mut console: ResMut<Console>, mut console_commands: ResMut<Events<ConsoleCommand>>) {
+    for event in state.event_reader.iter(&keyboard_input_events) {
+        let key = match event.key_code {
+            Some(key) => key,
+            None => continue,
+        };
+        if key == KeyCode::LShift || key == KeyCode::RShift {
+            state.shift = event.state == ElementState::Pressed;
+            continue;
+        }
+        if event.state != ElementState::Pressed {
+            continue;
+        }
+
+        if key == bindings.console {
+            console.open = !console.open;
+            console.input.clear();
+            inputs.captured = console.open;
+            continue;
+        }
+        if !console.open {
+            continue;
+        }
+        match key {
+            KeyCode::Return | KeyCode::NumpadEnter => {
+                let line = console.input.trim().to_string();
+                console.input.clear();
+                if line.is_empty() {
+                    continue;
+                }
+                console.print(format!("> {}", line));
+                match registry.parse(&line) {
+                    Ok(command) if command.name == "help" => {
+                        for (_name, usage, _args) in registry.commands.iter() {
+                            console.print(usage.clone());
+                        }
+                    },
+                    Ok(command) => console_commands.send(command),
+                    Err(err) => console.print(err),
+                }
+            },
+            KeyCode::Back => {
+                console.input.pop();
+            },
+            _ => if let Some(c) = console_char(key, state.shift) {
+                console.input.push(c);
+            },
+        }
+    }
+}
+
+// console world system
+// carries out the commands that change what's going on in the mission: spawning, giving items, teleporting and killing
+pub fn console_world_system(mut commands: Commands, config: Res<GameConfig>, mut materials: ResMut<Assets<ColorMaterial>>, archetypes: Res<Archetypes>,
+    mut state: ResMut<ConsoleWorldState>, events: Res<Events<ConsoleCommand>>, mut console: ResMut<Console>, mut deaths: ResMut<Events<DeathEvent>>,
+    mut bodies: ResMut<RigidBodySet>, mut colliders: ResMut<ColliderSet>, mut joints: ResMut<JointSet>,
+    mut squad: Query<(Entity, &Controlled, &mut Position, Option<&RigidBodyHandleComponent>, Option<&mut Keyring>, Option<&mut Perks>, Option<&mut Medkits>)>,
+    mut people: Query<(Entity, &Id, &Person, &Position, Option<&RigidBodyHandleComponent>)>) {
+    let received: Vec<ConsoleCommand> = state.event_reader.iter(&events).cloned().collect();
+    for command in received {
+        let args: Vec<&str> = command.args.iter().map(|arg| arg.as_str()).collect();
+        match (command.name.as_str(), args.as_slice()) {
+            ("spawn", [kind, x, y, rest @ ..]) => {
+                let position = match (x.parse::<f32>(), y.parse::<f32>()) {
+                    (Ok(x), Ok(y)) => (x, y),
+                    _ => {
+                        console.print(format!("bad position: {} {}", x, y));
+                        continue;
+                    },
+                };
+                match *kind {
+                    "hostile" => {
+                        let name = rest.first().copied().unwrap_or("grunt");
+                        spawn_hostile(&config, &mut commands, &mut materials, &archetypes.get(name), position);
+                        console.print(format!("spawned a {} at {} {}", name, position.0, position.1));
+                    },
+                    "civilian" => {
+                        spawn_civilian(&config, &mut commands, &mut materials, position);
+                        console.print(format!("spawned a civilian at {} {}", position.0, position.1));
+                    },
+                    _ => console.print(format!("can't spawn a {}", kind)),
+                }
+            },
+            ("give_item", [squad_pos, item, ..]) => {
+                let squad_pos = squad_pos.parse::<i32>().unwrap_or(-1);
+                let mut given = false;
+                for (entity, controlled, _pos, _body, keyring, perks, medkits) in &mut squad.iter() {
+                    if controlled.squad_pos != squad_pos {
+                        continue;
+                    }
+                    // lockpicks, medkits, shields, armour, weapons and perks are kit, anything else is taken as the name of a key
+                    if *item == "lockpicks" {
+                        commands.insert_one(entity, Lockpicker);
+                    }else if *item == "shield" {
+                        commands.insert_one(entity, Shield::default());
+                    }else if *item == "armor" {
+                        commands.insert_one(entity, Resistances::armor());
+                    }else if *item == "medkit" {
+                        match medkits {
+                            Some(mut medkits) => medkits.0 += 1,
+                            None => commands.insert_one(entity, Medkits(1)),
+                        }
+                    }else if archetypes.0.contains_key(*item) {
+                        commands.insert_one(entity, archetypes.get(item).weapon);
+                    }else if let Some(perk) = Perk::from_name(item) {
+                        match perks {
+                            Some(mut perks) => perks.0.push(perk),
+                            None => commands.insert_one(entity, Perks(vec![perk])),
+                        }
+                    }else{
+                        match keyring {
+                            Some(mut keyring) => keyring.0.push(item.to_string()),
+                            None => commands.insert_one(entity, Keyring(vec![item.to_string()])),
+                        }
+                    }
+                    given = true;
+                }
+                console.print(if given { format!("gave {} to {}", item, squad_pos) } else { format!("no squad member {}", squad_pos) });
+            },
+            ("teleport", [squad_pos, x, y, ..]) => {
+                let (squad_pos, x, y) = match (squad_pos.parse::<i32>(), x.parse::<f32>(), y.parse::<f32>()) {
+                    (Ok(squad_pos), Ok(x), Ok(y)) => (squad_pos, x, y),
+                    _ => {
+                        console.print("usage: teleport <squad_pos> <x> <y>".to_string());
+                        continue;
+                    },
+                };
+                let mut moved = false;
+                for (_entity, controlled, mut pos, body, _keyring, _perks, _medkits) in &mut squad.iter() {
+                    if controlled.squad_pos != squad_pos {
+                        continue;
+                    }
+                    pos.0 = x;
+                    pos.1 = y;
+                    // move the physics body too, otherwise it would move the squad member back
+                    if let Some(body) = body.and_then(|body| bodies.get_mut(body.handle())) {
+                        let translation = get_translate_from_position(&config, x, y);
+                        body.set_position(Isometry::translation(translation.0, translation.1));
+                    }
+                    moved = true;
+                }
+                console.print(if moved { format!("teleported {} to {} {}", squad_pos, x, y) } else { format!("no squad member {}", squad_pos) });
+            },
+            ("kill_all", _) => {
+                let mut killed = 0;
+                for (entity, id, pers, pos, body) in &mut people.iter() {
+                    if pers.attitude != AttitudeType::Hostile {
+                        continue;
+                    }
+                    // killed as if in a fight, so everything counting deaths hears about it
+                    deaths.send(DeathEvent {
+                        id: id.id(),
+                        attitude: Some(pers.attitude),
+                        killer_id: "".to_string(),
+                        position: (pos.0, pos.1),
+                    });
+                    remove_person(&mut commands, entity, body, &mut bodies, &mut colliders, &mut joints);
+                    killed += 1;
+                }
+                console.print(format!("killed {} hostiles", killed));
+            },
+            _ => {},
+        }
+    }
+}
+
+// console settings system
+// carries out the commands that change how the mission runs: the random seed and the fog
+pub fn console_settings_system(mut state: ResMut<ConsoleSettingsState>, events: Res<Events<ConsoleCommand>>, mut console: ResMut<Console>,
+    mut rng: ResMut<GameRng>, mut seed: ResMut<RunSeed>, mut weather: ResMut<Weather>) {
+    let received: Vec<ConsoleCommand> = state.event_reader.iter(&events).cloned().collect();
+    for command in received {
+        match (command.name.as_str(), command.args.first()) {
+            ("set_seed", Some(value)) => match value.parse::<u64>() {
+                Ok(value) => {
+                    *rng = GameRng::new(value);
+                    seed.0 = value;
+                    console.print(format!("seed set to {}", value));
+                },
+                Err(_) => console.print(format!("bad seed: {}", value)),
+            },
+            ("toggle_fog", _) => {
+                // the weather system keeps the fog overlay and everyone's sight in line with the weather
+                weather.kind = if weather.kind == WeatherKind::Fog { WeatherKind::Clear } else { WeatherKind::Fog };
+                weather.rolled = true;
+                console.print(format!("weather is now {:?}", weather.kind).to_lowercase());
+            },
+            _ => {},
+        }
+    }
+}
+
+// console text system
+// shows the console's output and input line while it's open
+pub fn console_text_system(console: Res<Console>, mut query: Query<(&ConsoleText, &mut Text)>) {
+    let value = if console.open {
+        let mut lines = console.log.clone();
+        lines.push(format!("] {}_", console.input));
+        lines.join("\n")
+    }else{
+        "".to_string()
+    };
+    for (_console, mut text) in &mut query.iter() {
+        text.value = value.clone();
+    }
+}
@@ -0,0 +1,2077 @@
+// control module
+// player input, selection, orders and the key bindings every control reads its keys from
+use bevy::prelude::*;
+use bevy::window::CursorMoved;
+use bevy::input::mouse::MouseButtonInput;
+use bevy::input::keyboard::{ElementState, KeyboardInput};
+use std::collections::{HashMap, HashSet};
+use bevy_rapier2d::rapier::dynamics::RigidBodySet;
+use bevy_rapier2d::rapier::geometry::ColliderSet;
+use bevy_rapier2d::rapier::pipeline::QueryPipeline;
+
+use crate::{
+    ATTACK_MOVE_KEY, BASE_MOVE_SPEED, BEHAVIOUR_STAGE, BOX_SELECT_THRESHOLD, BRACE_KEY,
+    CAMERA_SCROLL_KEYS, CAPTURE_DURATION, CAPTURE_RADIUS, CLEAR_QUEUE_KEY, COMBAT_LOG_KEY,
+    COMBAT_LOG_NEWER_KEY, COMBAT_LOG_OLDER_KEY, combat_query, COMMAND_STAGE, CONSOLE_KEY,
+    CONTROL_GROUP_KEYS, DISPLAY_MODE_KEY, DISPLAY_MODE_MODIFIERS, DISTRACT_KEY,
+    DISTRACTION_NOISE_RADIUS, DOOR_INTERACT_DURATION, DOOR_REACH, EXHAUSTED_SPEED, FLEE_MODIFIER,
+    FOLLOW_MODIFIER, FOLLOW_START_DISTANCE, FOLLOW_STOP_DISTANCE, FORMATION_KEY, FORMATION_SPACING,
+    GRENADE_DAMAGE, GRENADE_KEY, GRENADE_KNOCKBACK, GRENADE_RADIUS, GROUP_SAVE_MODIFIER,
+    HEAL_DURATION, HEAL_KEY, HEAL_RANGE, Id, INPUT_STAGE, INSPECTOR_KEY, INSPECTOR_NEXT_KEY,
+    INSPECTOR_PREV_KEY, LANGUAGE_KEY, OCCUPANCY_OVERLAY_KEY, OVERWATCH_KEY, PALETTE_KEY, Position,
+    RALLY_KEY, SELECTION_ADD_MODIFIER, Size, SNEAK_KEY, SPRINT_KEY, SPRINT_SPEED, STOP_KEY,
+    SWITCH_KEY, THROW_ARC_HEIGHT, THROW_RANGE, THROW_SPEED, TILE_EDITOR_KEY, UNDO_ORDER_KEY,
+    Velocity, VISION_CONE_KEY, WEAPON_RANGE, WEIGHT_OVERLAY_KEY,
+};
+use crate::actions::ThrowEvent;
+use crate::audio::{SoundEvent, SoundType};
+use crate::behaviour::{Behaviour, BehaviourType, Pathfinder, TilePos};
+use crate::combat::{BlastEvent, DamageType, Medkits, Shield, StatusEffect};
+use crate::config::GameConfig;
+use crate::door::{can_unlock, Door, Keyring, Lockpicker};
+use crate::draw::{camera_scroll_system, Screen};
+use crate::encounter::Ambusher;
+use crate::help::HELP_KEY;
+use crate::locale::Locale;
+use crate::map::MapData;
+use crate::nerve::{Action, ActionType, Nerve, TargetGroup};
+use crate::order_macro::MACRO_KEYS;
+use crate::patrol::{PATROL_ASSIGN_KEY, PATROL_MODIFIER};
+use crate::perception::NoiseEvent;
+use crate::person::{AttitudeType, Command, Controlled, Person, SimpleRect};
+use crate::stamina::Stamina;
+use crate::stance::{FOCUS_KEY, FocusTarget, pick_target, STANCE_KEY};
+use crate::tick::AddTickSystem;
+
+// control plugin
+// responsible for reading player inputs from the mouse and keyboard
+pub struct ControlPlugin;
+
+// implementation of the plugin trait,
+// required for this to be used as a plugin
+impl Plugin for ControlPlugin {
+    fn build(&self, app: &mut AppBuilder){
+        // initialise the inputstate resource
+        app.init_resource::<InputState>()
+        // initialise the key bindings, which every control reads its keys from
+        .init_resource::<KeyBindings>()
+        // add in the event sent whenever an order is given
+        .add_event::<CommandEvent>()
+        // initialise the mousestate resource
+        .init_resource::<MouseState>()
+        // initialise the keyboardstate resource
+        .init_resource::<KeyboardState>()
+        // add in the mouse input system
+        .add_system_to_stage(INPUT_STAGE, mouse_input_system.system())
+        // add in the keyboard input system
+        .add_system_to_stage(INPUT_STAGE, keyboard_input_system.system())
+        // add in the camera scroll system, moving the view around maps bigger than the window
+        .add_system_to_stage(INPUT_STAGE, camera_scroll_system.system())
+        // initialise the selection resource
+        .init_resource::<Selection>()
+        // add in the selection box
+        .add_startup_system(setup_selection_box_system.system())
+        // add in the selection system, before anything that reads the selection
+        .add_system_to_stage(INPUT_STAGE, selection_system.system())
+        // add in the system showing who is selected
+        .add_system(selection_highlight_system.system())
+        // add in the move player system, which turns orders into actions alongside the behaviours of everyone else
+        .add_tick_system_to_stage(BEHAVIOUR_STAGE, move_controlled_system.system())
+        // work out what the mouse is over, before the control player system acts on clicks
+        .init_resource::<Hover>()
+        .add_system_to_stage(INPUT_STAGE, hover_system.system())
+        // add in the control player system
+        .add_system_to_stage(COMMAND_STAGE, player_control_system.system())
+        // initialise the formation resource
+        .init_resource::<Formation>()
+        // add in the formation hotkey system
+        .add_system_to_stage(COMMAND_STAGE, formation_hotkey_system.system())
+        // add in the system that has idle squadmates follow the player
+        .add_tick_system_to_stage(BEHAVIOUR_STAGE, idle_follow_system.system())
+        // add in the system that has squad members fight along an attack move
+        .add_tick_system_to_stage(BEHAVIOUR_STAGE, attack_move_system.system())
+        // add in the stop hotkey system
+        .add_system_to_stage(COMMAND_STAGE, stop_hotkey_system.system())
+        // add in the system for taking back queued orders
+        .add_system_to_stage(COMMAND_STAGE, order_queue_hotkey_system.system())
+        // add in the systems throwing distractions, and making a noise where they land
+        .init_resource::<ThrowEventState>()
+        .add_tick_system(throw_system.system())
+        .add_tick_system(thrown_system.system())
+        // add in the system burning down grenade fuses, setting off a blast once they're out
+        .add_tick_system(grenade_fuse_system.system())
+        // add in the system switching weapons at the press of a key
+        .add_system_to_stage(COMMAND_STAGE, switch_hotkey_system.system())
+        // initialise the rally point resource
+        .init_resource::<RallyPoint>()
+        // add in the rally command system
+        .add_system_to_stage(COMMAND_STAGE, rally_command_system.system())
+        // add in the system that sends new squad members to the rally point
+        .add_tick_system(rally_recruit_system.system());
+    }
+}
+// the inputstate struct is what we will read in the rest
+// of the program to determine if a player is pressing a certain input or not
+#[derive(Default, Debug)]
+pub struct InputState{
+    // mouse_position holds the location of the cursor
+    pub mouse_position: (f32, f32),
+    // mouse_just_presses holds which mouse buttons were JUST pressed
+    pub mouse_just_presses: Vec<MouseButton>,
+    // mouse_presses holds which mouse buttons are currently pressed
+    pub mouse_presses: Vec<MouseButton>,
+    // mouse_just_releases holds which mouse buttons were JUST released
+    pub mouse_just_releases: Vec<MouseButton>,
+    // key_presses holds which keys are currently pressed
+    pub key_presses: Vec<KeyCode>,
+    // key_just_presses holds which keys were JUST pressed
+    pub key_just_presses: Vec<KeyCode>,
+    // captured is set while something else is taking the keyboard, like the console
+    // no key presses are recorded while it is, so typing isn't taken as orders
+    pub captured: bool,
+    // mouse_captured is the same for the mouse, set while something else is taking it, like the tile editor
+    // no clicks are recorded while it is, so painting tiles isn't taken as orders
+    pub mouse_captured: bool,
+}
+// the mousestate struct holds event readers for the mousebutton events and cursormoved events
+// along with where the cursor last was in the window
+#[derive(Default)]
+pub struct MouseState {
+    pub mouse_button_event_reader: EventReader<MouseButtonInput>,
+    pub cursor_moved_event_reader: EventReader<CursorMoved>,
+    pub cursor_position: (f32, f32),
+}
+
+// mouse input system
+// this function reads the input coming from the mouse and stores it in InputState for use in other parts
+// of the program
+pub fn mouse_input_system(mut inputs: ResMut<InputState>, 
+    mut state: ResMut<MouseState>, screen: Res<Screen>,
+    mouse_button_input_events: Res<Events<MouseButtonInput>>, 
+    cursor_moved_events: Res<Events<CursorMoved>>) {
+    
+    // clear the mouse_just_presses vector so that we only capture the most recent button inputs
+    inputs.mouse_just_presses.clear();
+    // same for the mouse_just_releases vector
+    inputs.mouse_just_releases.clear();
+
+    for event in state
+    .mouse_button_event_reader
+    .iter(&mouse_button_input_events)   {
+        // if a mouse button is pressed
+        if event.state == ElementState::Pressed {
+            if inputs.mouse_captured {
+                continue;
+            }
+            // add this to the mouse_presses and mouse_just_presses vectors
+            // no need to check if they're already present because mouse buttons must be released before they can be pressed again
+            inputs.mouse_presses.push(event.button);
+            inputs.mouse_just_presses.push(event.button);
+
+        // if a mouse button is released 
+        } else if event.state == ElementState::Released {
+            // remove it from the mouse_presses vector
+            if let Some(index) = inputs.mouse_presses.iter().position(|x| *x == event.button) {
+                inputs.mouse_presses.remove(index);
+            }
+            // and add it to the mouse_just_releases vector
+            inputs.mouse_just_releases.push(event.button);
+        }
+    }
+
+    // reads CursorMoved events to get the position of the cursor relative to the window
+    let mut cursor_position = state.cursor_position;
+    for event in state
+    .cursor_moved_event_reader
+    .iter(&cursor_moved_events) {
+        cursor_position = (event.position[0], event.position[1]);
+    }
+    state.cursor_position = cursor_position;
+    // convert the cursor position to mouse position coordinates we can use
+    // this is redone every frame, so the mouse position stays right when the window is resized
+    inputs.mouse_position = screen.position_from_cursor(cursor_position);
+}
+// keyboardstate holds an event reader for key presses from the keyboard
+#[derive(Default)]
+pub struct KeyboardState {
+    pub event_reader: EventReader<KeyboardInput>,
+}
+
+// keyboard input system
+// this system captures input from the keyboard and stores it in inputstate
+pub fn keyboard_input_system(mut inputs: ResMut<InputState>, mut state: ResMut<KeyboardState>, keyboard_input_events: Res<Events<KeyboardInput>>) {
+    // clear the key_just_presses vector so that we only capture the most recent key presses
+    inputs.key_just_presses.clear();
+
+    for event in state.event_reader.iter(&keyboard_input_events) {
+        // if a key is pressed
+        if event.state == ElementState::Pressed {
+            if inputs.captured {
+                continue;
+            }
+            // check if its keycode exists
+            if let Some(key) = event.key_code {
+                // check if it's not already in the key_presses vector
+                // note that holding down a key will send multiple keypressed events in succession
+                if inputs.key_presses.iter().position(|x| *x == key) == None {
+                    // add it into the key_presses and key_just_presses vectors
+                    inputs.key_presses.push(key);
+                    inputs.key_just_presses.push(key);
+                }
+            }
+        // if a key is released
+        }else if event.state == ElementState::Released {
+            // check if its keycode exists
+            if let Some(key) = event.key_code {
+                // check if it's in the key_presses vector
+                if let Some(index) = inputs.key_presses.iter().position(|x| *x == key) {
+                    // reomve it from the key_presses vector
+                    inputs.key_presses.remove(index);
+                }
+            }
+        }
+    }
+}
+
+// selection resource
+// holds the squad members currently selected, which is who orders are given to
+// the selection stays the same across frames until changed by
+// the number keys, clicking or dragging a box with the right mouse button, or control groups
+#[derive(Default)]
+pub struct Selection {
+    // the selected squad members
+    pub selected: Vec<Entity>,
+    // the saved control groups, by index into the control group keys
+    pub groups: HashMap<usize, Vec<Entity>>,
+    // where the right mouse button was pressed down, if a box is being dragged
+    pub drag_start: Option<(f32, f32)>,
+}
+
+impl Selection {
+    // checks if a squad member is selected
+    // if no one is selected, the player is
+    pub fn is_selected(&self, entity: Entity, squad_pos: i32) -> bool {
+        if self.selected.is_empty() {
+            squad_pos == 0
+        }else{
+            self.selected.contains(&entity)
+        }
+    }
+    // selects the given squad members, adding to the selection if add is set
+    pub fn select(&mut self, entities: Vec<Entity>, add: bool) {
+        if !add {
+            self.selected.clear();
+        }
+        for entity in entities {
+            if !self.selected.contains(&entity) {
+                self.selected.push(entity);
+            }
+        }
+    }
+}
+
+// selection box component
+// spawn this component along with the box drawn while dragging out a selection
+pub struct SelectionBox;
+
+// selection marker component
+// spawn this component along with the marker drawn over a selected squad member
+pub struct SelectionMarker {
+    // the squad member being marked
+    pub target: Entity,
+}
+
+// setup selection box system
+// spawns in the box drawn while dragging, hidden until needed
+pub fn setup_selection_box_system(mut commands: Commands, mut materials: ResMut<Assets<ColorMaterial>>) {
+    let box_handle = materials.add(Color::rgba(1.0, 1.0, 1.0, 0.2).into());
+    commands
+        .spawn(SimpleRect::new(box_handle, Vec2::new(0.0, 0.0)))
+        // keep it out of sight
+        .with(Position(-1000.0, -1000.0))
+        .with(SelectionBox);
+}
+
+// selection system
+// updates the selection from the number keys, control groups and the right mouse button
+// holding shift adds to the selection instead of replacing it
+pub fn selection_system(inputs: Res<InputState>, bindings: Res<KeyBindings>, mut selection: ResMut<Selection>,
+    mut squad: Query<(Entity, &Controlled, &Position, &Size)>, mut boxes: Query<(&SelectionBox, &mut Sprite, &mut Position)>) {
+    let add = inputs.key_presses.contains(&bindings.add_selection);
+
+    // forget about squad members that are gone
+    let mut existing = Vec::new();
+    for (entity, _state, _pos, _size) in &mut squad.iter() {
+        existing.push(entity);
+    }
+    selection.selected.retain(|entity| existing.contains(entity));
+
+    // number keys select the squad member with that index
+    // holding down several number keys selects all of them
+    for key in inputs.key_just_presses.iter() {
+        let squad_pos = convert_keycode_to_squad_pos(*key);
+        if squad_pos < 0 {
+            continue;
+        }
+        let others_held = inputs.key_presses.iter().any(|k| k != key && convert_keycode_to_squad_pos(*k) >= 0);
+        for (entity, state, _pos, _size) in &mut squad.iter() {
+            if state.squad_pos == squad_pos {
+                selection.select(vec![entity], add || others_held);
+            }
+        }
+    }
+
+    // control groups are saved while holding control, and selected otherwise
+    for (index, key) in bindings.control_groups.iter().enumerate() {
+        if !inputs.key_just_presses.contains(key) {
+            continue;
+        }
+        if inputs.key_presses.contains(&bindings.save_group) {
+            let selected = selection.selected.clone();
+            selection.groups.insert(index, selected);
+        }else if let Some(group) = selection.groups.get(&index).cloned() {
+            let group = group.into_iter().filter(|entity| existing.contains(entity)).collect();
+            selection.select(group, add);
+        }
+    }
+
+    // the right mouse button clicks on or drags a box around squad members
+    // clicks while holding the rally key are for the rally point instead
+    if inputs.mouse_just_presses.contains(&MouseButton::Right) && !inputs.key_presses.contains(&bindings.rally) {
+        selection.drag_start = Some(inputs.mouse_position);
+    }
+    let drag_start = selection.drag_start;
+    if let Some(start) = drag_start {
+        let end = inputs.mouse_position;
+        let min = (start.0.min(end.0), start.1.min(end.1));
+        let max = (start.0.max(end.0), start.1.max(end.1));
+        let is_box = max.0 - min.0 > BOX_SELECT_THRESHOLD || max.1 - min.1 > BOX_SELECT_THRESHOLD;
+
+        if inputs.mouse_just_releases.contains(&MouseButton::Right) {
+            let mut picked = Vec::new();
+            for (entity, _state, pos, size) in &mut squad.iter() {
+                let hit = if is_box {
+                    // anyone inside the box
+                    pos.0 >= min.0 && pos.0 <= max.0 && pos.1 >= min.1 && pos.1 <= max.1
+                }else{
+                    // whoever was clicked on
+                    check_point_collision(end, (pos.0, pos.1), (size.0, size.1))
+                };
+                if hit {
+                    picked.push(entity);
+                }
+            }
+            // clicking on nothing clears the selection, back to just the player
+            selection.select(picked, add);
+            selection.drag_start = None;
+        }
+
+        // draw the box while it's being dragged
+        for (_box, mut sprite, mut pos) in &mut boxes.iter() {
+            if is_box && selection.drag_start.is_some() {
+                sprite.size = Vec2::new(max.0 - min.0, max.1 - min.1);
+                pos.0 = (min.0 + max.0) / 2.0;
+                pos.1 = (min.1 + max.1) / 2.0;
+            }else{
+                pos.0 = -1000.0;
+                pos.1 = -1000.0;
+            }
+        }
+    }
+}
+
+// selection highlight system
+// draws a marker above every selected squad member
+pub fn selection_highlight_system(mut commands: Commands, selection: Res<Selection>, mut materials: ResMut<Assets<ColorMaterial>>,
+    mut squad: Query<(Entity, &Controlled, &Position)>, mut markers: Query<(Entity, &SelectionMarker, &mut Position)>) {
+    // find the squad members that need marking
+    let mut selected = Vec::new();
+    for (entity, state, pos) in &mut squad.iter() {
+        if selection.is_selected(entity, state.squad_pos) {
+            selected.push((entity, (pos.0, pos.1)));
+        }
+    }
+
+    // move the existing markers, and remove the ones no longer needed
+    let mut marked = Vec::new();
+    for (marker_entity, marker, mut pos) in &mut markers.iter() {
+        match selected.iter().find(|(entity, _)| *entity == marker.target) {
+            Some((_, target_pos)) => {
+                pos.0 = target_pos.0;
+                pos.1 = target_pos.1 - 9.0;
+                marked.push(marker.target);
+            },
+            None => commands.despawn(marker_entity),
+        }
+    }
+
+    // add markers for the newly selected
+    for (entity, pos) in selected {
+        if marked.contains(&entity) {
+            continue;
+        }
+        let marker_handle = materials.add(Color::WHITE.into());
+        commands
+            .spawn(SimpleRect::new(marker_handle, Vec2::new(10.0, 2.0)))
+            .with(Position(pos.0, pos.1 - 9.0))
+            .with(SelectionMarker { target: entity });
+    }
+}
+
+// move controlled system
+// responsible for calculating the velocity vector of the player to get to
+// the desired move point and setting the player character's velocity
+pub fn move_controlled_system(config: Res<GameConfig>, rally: Res<RallyPoint>, mut query: Query<(&mut Controlled, &mut Nerve, &mut Velocity, Option<&mut Pathfinder>, Option<&Weapon>)>) {
+    for (mut state, mut actions, mut vel, mut pf, weapon) in &mut query.iter() {
+        // squad members without a weapon of their own use the default one
+        let weapon = weapon.cloned().unwrap_or_default();
+        let command = &state.current_command;
+
+        // a new command calls off any path being followed, e.g. to the rally point
+        // as well as any attack move
+        if command.command_type != CommandType::Empty {
+            if let Some(pf) = &mut pf {
+                pf.needs_pathfinding = false;
+                pf.tile_path.clear();
+                pf.path.clear();
+                pf.path_index = 0;
+            }
+            state.attack_move = None;
+        }
+        let command = &state.current_command;
+        
+        match command.command_type {
+            CommandType::Move => {
+                // clear current actions to replace with new actions
+                actions.clear();
+
+                let mut params = HashMap::new();
+                // range refers to the maximum range acceptable
+                // set at zero to force entity to move to the target location
+                params.insert("range".to_string(), 0.0);
+
+                // add move action to the target location
+                actions.action_queue.push_back(Action {
+                    action_type: ActionType::Move,
+                    target: (command.target_point, None),
+                    params: Some(params.clone()),
+                    group: None,
+                });
+            },
+            CommandType::Attack => {
+                // replace current actions with the actions to get within range of the target and attack it
+                actions.replace_all(get_attack_actions(command.target_id.clone(), &weapon, HashMap::new()));
+            },
+            CommandType::AttackMove => {
+                // clear current actions to replace with new actions
+                actions.clear();
+
+                // path to the target point, the attack move system takes care of the fighting
+                let target_point = command.target_point;
+                if let (Some(point), Some(pf)) = (target_point, &mut pf) {
+                    pf.needs_pathfinding = true;
+                    pf.path_goal = TilePos::from_coords(&config, point.0, point.1);
+                    pf.real_goal = point;
+                }
+                state.attack_move = target_point;
+            },
+            CommandType::Interact => {
+                // clear current actions to replace with new actions
+                actions.clear();
+
+                // walk up to the object, then operate it
+                let mut params = HashMap::new();
+                params.insert("range".to_string(), DOOR_REACH);
+                actions.action_queue.push_back(Action {
+                    action_type: ActionType::Move,
+                    target: (None, command.target_id.clone()),
+                    params: Some(params),
+                    group: None,
+                });
+                let mut params = HashMap::new();
+                params.insert("duration".to_string(), DOOR_INTERACT_DURATION);
+                actions.action_queue.push_back(Action {
+                    action_type: ActionType::Interact,
+                    target: (command.target_point, command.target_id.clone()),
+                    params: Some(params),
+                    group: None,
+                });
+            },
+            CommandType::Capture => {
+                // clear current actions to replace with new actions
+                actions.clear();
+
+                // walk up to the surrendered hostile, then take them prisoner
+                let mut params = HashMap::new();
+                params.insert("range".to_string(), CAPTURE_RADIUS);
+                actions.action_queue.push_back(Action {
+                    action_type: ActionType::Move,
+                    target: (None, command.target_id.clone()),
+                    params: Some(params),
+                    group: None,
+                });
+                let mut params = HashMap::new();
+                params.insert("duration".to_string(), CAPTURE_DURATION);
+                actions.action_queue.push_back(Action {
+                    action_type: ActionType::Interact,
+                    target: (None, command.target_id.clone()),
+                    params: Some(params),
+                    group: None,
+                });
+            },
+            CommandType::Distract => {
+                // clear current actions to replace with new actions
+                actions.clear();
+
+                // get close enough to the target point to throw, then throw
+                let mut params = HashMap::new();
+                params.insert("range".to_string(), THROW_RANGE);
+                actions.action_queue.push_back(Action {
+                    action_type: ActionType::Move,
+                    target: (command.target_point, None),
+                    params: Some(params),
+                    group: None,
+                });
+                actions.action_queue.push_back(Action {
+                    action_type: ActionType::Distract,
+                    target: (command.target_point, None),
+                    params: None,
+                    group: None,
+                });
+            },
+            CommandType::Grenade => {
+                // clear current actions to replace with new actions
+                actions.clear();
+
+                // get close enough to the target point to throw, then throw
+                let mut params = HashMap::new();
+                params.insert("range".to_string(), THROW_RANGE);
+                actions.action_queue.push_back(Action {
+                    action_type: ActionType::Move,
+                    target: (command.target_point, None),
+                    params: Some(params),
+                    group: None,
+                });
+                actions.action_queue.push_back(Action {
+                    action_type: ActionType::Grenade,
+                    target: (command.target_point, None),
+                    params: None,
+                    group: None,
+                });
+            },
+            CommandType::Heal => {
+                // clear current actions to replace with new actions
+                actions.clear();
+
+                // walk up to whoever is being patched up, then patch them up
+                let mut params = HashMap::new();
+                params.insert("range".to_string(), HEAL_RANGE);
+                actions.action_queue.push_back(Action {
+                    action_type: ActionType::Move,
+                    target: (None, command.target_id.clone()),
+                    params: Some(params),
+                    group: None,
+                });
+                let mut params = HashMap::new();
+                params.insert("duration".to_string(), HEAL_DURATION);
+                actions.action_queue.push_back(Action {
+                    action_type: ActionType::Heal,
+                    target: (None, command.target_id.clone()),
+                    params: Some(params),
+                    group: None,
+                });
+            },
+            CommandType::Brace => {
+                // clear current actions to replace with new actions
+                actions.clear();
+
+                // hold position behind the shield, facing the target point
+                actions.action_queue.push_back(Action {
+                    action_type: ActionType::Brace,
+                    target: (command.target_point, None),
+                    params: None,
+                    group: None,
+                });
+            },
+            CommandType::Overwatch => {
+                // clear current actions to replace with new actions
+                actions.clear();
+
+                // hold position watching towards the target point, ready to fire with this weapon
+                actions.action_queue.push_back(Action {
+                    action_type: ActionType::Overwatch,
+                    target: (command.target_point, None),
+                    params: Some(get_weapon_params(&weapon)),
+                    group: None,
+                });
+            },
+            CommandType::Stop => {
+                // drop everything, including any orders still waiting
+                actions.clear();
+                state.clear_queue();
+                // and stand still right away
+                vel.0 = 0.0;
+                vel.1 = 0.0;
+            },
+            // fleeing squad members head for the rally point, if there is one
+            CommandType::Flee if rally.position.is_some() && pf.is_some() => {
+                if let (Some(point), Some(pf)) = (rally.position, &mut pf) {
+                    send_to_rally(&config, point, &mut actions, pf);
+                }
+            },
+            CommandType::Flee => {
+                // clear current actions to replace with new actions
+                actions.clear();
+
+                let mut params = HashMap::new();
+                // min_range refers to the minimum distance that we want to put between
+                // us and the point/entity
+                params.insert("min_range".to_string(), 200.0);
+                // add move action to the target entity
+                // get away from a certain distance of the target
+                actions.action_queue.push_back(Action {
+                    action_type: ActionType::Move,
+                    target: (command.target_point, command.target_id.clone()),
+                    params: Some(params.clone()),
+                    group: None,
+                });
+            },
+            CommandType::Follow => {
+                // clear current actions to replace with new actions
+                actions.clear();
+
+                let mut params = HashMap::new();
+                // range refers to the maximum range acceptable
+                params.insert("range".to_string(), 40.0);
+                params.insert("no_skip".to_string(), 1.0);
+                // if following in formation, keep to the formation slot instead
+                if let Some(offset) = command.formation_offset {
+                    params.insert("offset_x".to_string(), offset.0);
+                    params.insert("offset_y".to_string(), offset.1);
+                    params.insert("range".to_string(), config.tile_size);
+                }
+
+                // add move action to the target entity
+                actions.action_queue.push_back(Action {
+                    action_type: ActionType::Move,
+                    target: (None, command.target_id.clone()),
+                    params: Some(params.clone()),
+                    group: None,
+                });
+            },
+            _ => {
+
+            },
+        }
+
+        // the command has been turned into actions, so it's done with
+        // the next command in the queue is only readied once everything the last one started is finished,
+        // until then the empty command leaves whatever is going on alone
+        let idle = actions.is_curr_action_empty() && actions.action_queue.is_empty() && state.attack_move.is_none()
+            && pf.map_or(true, |pf| !pf.needs_pathfinding && pf.path_index >= pf.tile_path.len());
+        let next = if idle { state.command_queue.pop_front() } else { None };
+        state.current_command = next.unwrap_or_default();
+    }
+}
+
+// weapon component
+// spawn this component along with anything that attacks differently from the default
+#[derive(Debug, Clone)]
+pub struct Weapon {
+    // the furthest away an attack can be launched from
+    pub range: f32,
+    // the closest an attack can be launched from
+    pub min_range: f32,
+    // the number of seconds between attacks
+    pub cooldown: f32,
+    // the speed the victim is knocked away at
+    pub knockback: f32,
+    // the health taken away by each attack
+    pub damage: f32,
+    // the chance of each attack hitting, from 0.0 to 1.0
+    pub accuracy: f32,
+    // status effects put on the victim by each attack that hits
+    pub effects: Vec<StatusEffect>,
+    // the attacks left before it runs dry, unlimited if it doesn't need ammo
+    pub ammo: Option<u32>,
+    // how far either way the damage of each attack varies, as a fraction of the damage
+    pub spread: f32,
+    // the chance of each attack that hits being critical, from 0.0 to 1.0, and the multiplier on its damage if it is
+    pub crit_chance: f32,
+    pub crit_multiplier: f32,
+    // the kind of damage each attack does
+    pub damage_type: DamageType,
+}
+
+impl Default for Weapon {
+    fn default() -> Self {
+        Weapon {
+            range: WEAPON_RANGE,
+            min_range: 20.0,
+            cooldown: 1.0,
+            knockback: 150.0,
+            damage: 10.0,
+            accuracy: 1.0,
+            effects: Vec::new(),
+            ammo: None,
+            spread: 0.2,
+            crit_chance: 0.05,
+            crit_multiplier: 2.0,
+            damage_type: DamageType::Ballistic,
+        }
+    }
+}
+
+// holstered component
+// spawn this component along with anything carrying a second weapon, which it can switch to
+// switching swaps this with the weapon in hand
+pub struct Holstered(pub Weapon);
+
+// starts a switch to the holstered weapon straight away, picking up whatever was being done afterwards
+pub fn start_switch(actions: &mut Nerve) {
+    if let ActionType::Switch = actions.current_action.action_type {
+        return;
+    }
+    let current = std::mem::replace(&mut actions.current_action, Action {
+        action_type: ActionType::Switch,
+        target: (None, None),
+        params: None,
+        group: None,
+    });
+    match current.action_type {
+        ActionType::Empty => {},
+        _ => actions.push_front(current),
+    }
+    actions.action_timer = None;
+}
+
+// move speed component
+// spawn this component along with anything that moves under its own power
+// perks, status effects and the like can change any of these while it's on
+#[derive(Debug, Clone, Copy)]
+pub struct MoveSpeed {
+    // how fast it walks, in pixels a second
+    pub base: f32,
+    // multiplier on speed while sprinting
+    pub sprint: f32,
+    // multiplier on speed from the ground underfoot, set by the terrain speed system
+    pub terrain: f32,
+}
+
+impl Default for MoveSpeed {
+    fn default() -> Self {
+        MoveSpeed::new(BASE_MOVE_SPEED)
+    }
+}
+
+impl MoveSpeed {
+    // initialize a new MoveSpeed struct, walking at the given speed
+    pub fn new(base: f32) -> Self {
+        MoveSpeed {
+            base,
+            sprint: SPRINT_SPEED,
+            terrain: 1.0,
+        }
+    }
+    // gives how fast it moves right now, in pixels a second
+    pub fn current(&self, stamina: Option<&Stamina>) -> f32 {
+        self.base * self.terrain * self.stride(stamina, stamina.map_or(false, |stamina| stamina.sprinting))
+    }
+    // gives the multiplier on speed from the pace it's moving at
+    // sprinting speeds it up, and being exhausted slows it down
+    pub fn stride(&self, stamina: Option<&Stamina>, sprinting: bool) -> f32 {
+        match stamina {
+            Some(stamina) if stamina.exhausted => EXHAUSTED_SPEED,
+            Some(_) if sprinting => self.sprint,
+            _ => 1.0,
+        }
+    }
+}
+
+// gives the actions needed to attack a target entity with a weapon
+// first move within range of the target, then attack it
+// extra params are added on top of the weapon's params
+pub fn get_attack_actions(target_id: Option<String>, weapon: &Weapon, extra: HashMap<String, f32>) -> Vec<Action> {
+    let mut params = get_weapon_params(weapon);
+    params.extend(extra);
+
+    vec![
+        // add move action to the target entity
+        // get within a certain distance of the target
+        Action {
+            action_type: ActionType::Move,
+            target: (None, target_id.clone()),
+            params: Some(params.clone()),
+            group: None,
+        },
+        // add attack action
+        // attack the target
+        Action {
+            action_type: ActionType::Attack,
+            target: (None, target_id),
+            params: Some(params),
+            group: None,
+        },
+    ]
+}
+
+// gives the actions needed to attack any of a group of target entities with a weapon
+// like get_attack_actions, but going for whichever is closest, and moving on to the next once it's gone
+pub fn get_group_attack_actions(target_ids: Vec<String>, weapon: &Weapon, extra: HashMap<String, f32>) -> Vec<Action> {
+    let mut params = get_weapon_params(weapon);
+    params.extend(extra);
+
+    vec![
+        // add move action to the closest of the targets
+        Action {
+            action_type: ActionType::Move,
+            target: (None, None),
+            params: Some(params.clone()),
+            group: Some(TargetGroup::Any(target_ids.clone())),
+        },
+        // add attack action
+        // attack the closest of the targets, then the next
+        Action {
+            action_type: ActionType::Attack,
+            target: (None, None),
+            params: Some(params),
+            group: Some(TargetGroup::Any(target_ids)),
+        },
+    ]
+}
+
+// gives the params attack actions take for a weapon
+pub fn get_weapon_params(weapon: &Weapon) -> HashMap<String, f32> {
+    let mut params = HashMap::new();
+    // range refers to the maximum range at which an attack can be launched
+    params.insert("range".to_string(), weapon.range);
+    // min_range refers to the minimum range at which an attack can be launched
+    params.insert("min_range".to_string(), weapon.min_range);
+    // knockback refers to the speed the victim is knocked away at
+    params.insert("knockback".to_string(), weapon.knockback);
+    // cooldown refers to the number of seconds between attacks
+    params.insert("cooldown".to_string(), weapon.cooldown);
+    // damage refers to the health taken away by each attack
+    params.insert("damage".to_string(), weapon.damage);
+    // accuracy refers to the chance of each attack hitting
+    params.insert("accuracy".to_string(), weapon.accuracy);
+    // spread refers to how far either way the damage of each attack varies
+    params.insert("spread".to_string(), weapon.spread);
+    // crit_chance and crit_multiplier refer to the chance of a critical hit, and the multiplier on its damage
+    params.insert("crit_chance".to_string(), weapon.crit_chance);
+    params.insert("crit_multiplier".to_string(), weapon.crit_multiplier);
+    // damage_type refers to the index of the kind of damage each attack does
+    params.insert("damage_type".to_string(), weapon.damage_type.index() as f32);
+    params
+}
+
+// function to convert the keys pressed to the squad indices they're mapped to
+pub fn convert_keycode_to_squad_pos(key: KeyCode) -> i32 {
+    match key {
+        KeyCode::Key0 => 0,
+        KeyCode::Key1 => 1,
+        KeyCode::Key2 => 2,
+        KeyCode::Key3 => 3,
+        KeyCode::Key4 => 4,
+        KeyCode::Key5 => 5,
+        KeyCode::Key6 => 6,
+        KeyCode::Key7 => 7,
+        KeyCode::Key8 => 8,
+        KeyCode::Key9 => 9,
+        // if this is not a valid mapping, return -1
+        _ => -1
+    }
+}
+
+// this function checks if a point is in the a box at a certain position with a certain 'radius'
+// point is the coordinate being checked
+// box_position is the coordinate where the box is
+// box_size is the size of the box
+pub fn check_point_collision(point: (f32, f32), box_position: (f32, f32), box_size: (f32, f32)) -> bool {
+    let box_radius = (box_size.0 / 2.0, box_size.1 / 2.0);
+    if (point.0 < box_position.0 + box_radius.0) && (point.0 > box_position.0 - box_radius.0)
+        && (point.1 < box_position.1 + box_radius.1) && (point.1 > box_position.1 - box_radius.1) {
+        true
+    }else{
+        false
+    }
+}
+
+// enum for the command type
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum CommandType {
+    // move command orders a pawn to move to a certain spot
+    Move,
+    // attack command orders a pawn to attack a certain entity
+    Attack,
+    // flee command orders a pawn to move a certain distance away from a certain entity/spot
+    Flee,  
+    // follow command orders a pawn to follow another  
+    Follow,
+    // attack move command orders a pawn to move to a certain spot,
+    // fighting any hostiles that come within range along the way
+    AttackMove,
+    // stop command orders a pawn to drop everything and stand still
+    Stop,
+    // distract command orders a pawn to throw something at a certain spot, making a noise there
+    Distract,
+    // grenade command orders a pawn to throw a grenade at a certain spot
+    Grenade,
+    // overwatch command orders a pawn to hold its ground watching towards a certain spot,
+    // firing at the first hostile that moves into view
+    Overwatch,
+    // interact command orders a pawn to operate an object, e.g. opening or closing a door
+    Interact,
+    // heal command orders a pawn to use a medkit on a certain squad member, which can be itself
+    Heal,
+    // brace command orders a pawn to hold its ground behind its shield, facing a certain spot
+    Brace,
+    // capture command orders a pawn to walk up to a surrendered hostile and take them prisoner
+    Capture,
+    // empty command does nothing
+    Empty,
+}
+
+impl CommandType {
+    // gives the command type with the given name, as used in the macros file
+    // only orders aimed at a point can be given by name, since macros aren't aimed at anyone in particular
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "move" => Some(CommandType::Move),
+            "attack_move" => Some(CommandType::AttackMove),
+            "overwatch" => Some(CommandType::Overwatch),
+            "distract" => Some(CommandType::Distract),
+            "grenade" => Some(CommandType::Grenade),
+            "brace" => Some(CommandType::Brace),
+            _ => None,
+        }
+    }
+}
+
+// implementation for the command type enum
+impl Default for CommandType {
+    // default function for the command type
+    // returns the empty command type
+    fn default() -> Self {
+        CommandType::Empty
+    }
+}
+
+
+// hover target
+// the kind of thing the mouse is over
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HoverTarget {
+    Hostile,
+    Neutral,
+    Squad,
+    Ally,
+    // hostiles that have surrendered, who can be captured
+    Surrendered,
+    Door,
+    // open ground that can be walked to
+    Ground,
+    // ground that can't be walked on, or anywhere off the map
+    Blocked,
+}
+
+impl HoverTarget {
+    // gives the order a left click over this gives, before any hotkeys change it
+    pub fn command_type(&self) -> CommandType {
+        match self {
+            HoverTarget::Hostile | HoverTarget::Neutral => CommandType::Attack,
+            HoverTarget::Squad => CommandType::Follow,
+            HoverTarget::Door => CommandType::Interact,
+            HoverTarget::Surrendered => CommandType::Capture,
+            HoverTarget::Ally | HoverTarget::Ground | HoverTarget::Blocked => CommandType::Move,
+        }
+    }
+}
+
+// hover resource
+// what the mouse is over, worked out once a frame by the hover system
+// clicks and the cursor both go by this, so what the cursor shows is what a click does
+pub struct Hover {
+    pub target: HoverTarget,
+    // the id and position of the person or door under the mouse, if there is one
+    pub target_id: Option<String>,
+    pub target_position: Option<(f32, f32)>,
+    // the key the door under the mouse needs, if there's a door under the mouse
+    pub door_key: Option<Option<String>>,
+}
+
+impl Default for Hover {
+    fn default() -> Self {
+        Hover {
+            target: HoverTarget::Ground,
+            target_id: None,
+            target_position: None,
+            door_key: None,
+        }
+    }
+}
+
+// hover system
+// works out what the mouse is over: people first, then doors, then the ground itself
+pub fn hover_system(config: Res<GameConfig>, inputs: Res<InputState>, map: Res<MapData>, mut hover: ResMut<Hover>,
+    mut persons: Query<(&Id, &Person, &Position, &Size, Option<&Ambusher>)>, mut doors: Query<(&Id, &Door, &Position)>) {
+    *hover = Hover::default();
+
+    // check if the mouse is over someone
+    for (id, pers, pos, size, ambusher) in &mut persons.iter() {
+        // hidden ambushers can't be picked out
+        if ambusher.map_or(false, |ambusher| ambusher.hidden) {
+            continue;
+        }
+        if check_point_collision(inputs.mouse_position, (pos.0, pos.1), (size.0, size.1)) {
+            let target = match &pers.attitude {
+                AttitudeType::Hostile => HoverTarget::Hostile,
+                AttitudeType::Neutral => HoverTarget::Neutral,
+                AttitudeType::Squad => HoverTarget::Squad,
+                AttitudeType::Ally => HoverTarget::Ally,
+                AttitudeType::Surrendered => HoverTarget::Surrendered,
+            };
+            hover.target = target;
+            hover.target_id = Some(id.id());
+            hover.target_position = Some((pos.0, pos.1));
+            return;
+        }
+    }
+
+    // check if the mouse is over a door, which is opened or closed
+    for (id, door, pos) in &mut doors.iter() {
+        if check_point_collision(inputs.mouse_position, (pos.0, pos.1), door.size) {
+            hover.target = HoverTarget::Door;
+            hover.target_id = Some(id.id());
+            hover.target_position = Some((pos.0, pos.1));
+            hover.door_key = Some(door.key.clone());
+            return;
+        }
+    }
+
+    // otherwise it's over the ground, which might not be walkable
+    let (x, y) = inputs.mouse_position;
+    if !config.is_on_map(x, y) || map.is_tile_blocked(&TilePos::from_coords(&config, x, y)) {
+        hover.target = HoverTarget::Blocked;
+    }
+}
+
+// player control system
+// responsible for translating all inputs into the respective actions in-game
+pub fn player_control_system(config: Res<GameConfig>, inputs: Res<InputState>, bindings: Res<KeyBindings>, hover: Res<Hover>, map: Res<MapData>, formation: Res<Formation>, selection: Res<Selection>, mut command_events: ResMut<Events<CommandEvent>>, mut controlstate: Query<(Entity, &Id, &mut Controlled, &Position, Option<&Keyring>, Option<&Lockpicker>, Option<&mut Stamina>, Option<&Medkits>, Option<&Shield>)>) {
+    // if the left mouse button was just pressed
+    // clicks made while holding the patrol modifier lay down waypoints instead of giving orders,
+    // as do clicks made while holding the rally key, which set the rally point,
+    // and clicks made while holding the focus key, which mark the priority target
+    if inputs.mouse_just_presses.contains(&MouseButton::Left) && !inputs.key_presses.contains(&bindings.waypoint)
+        && !inputs.key_presses.contains(&bindings.rally) && !inputs.key_presses.contains(&bindings.focus) {
+        
+        // what was clicked on is whatever the mouse is over, see the hover system
+        // clicking on nothing in particular defaults to a move command
+        let mut command_type = hover.target.command_type();
+        // a move command by default has no target entity
+        let target_entity = hover.target_id.clone();
+        // position of the target entity, if there is one
+        let target_position = hover.target_position;
+        // holds the key the door needs, if a door was clicked and it's locked
+        let target_door = hover.door_key.clone();
+
+        // check hotkeys pressed
+        // the flee modifier, left shift, switches move/follow/attack -> flee
+        if inputs.key_presses.contains(&bindings.flee) {
+            command_type = CommandType::Flee;
+        // the follow modifier, left control, switches move/attack -> follow
+        } else if inputs.key_presses.contains(&bindings.follow) && target_entity.is_some() && target_door.is_none() {
+            command_type = CommandType::Follow;
+        // the attack move key switches move -> attack move
+        } else if inputs.key_presses.contains(&bindings.attack_move) && target_entity.is_none() {
+            command_type = CommandType::AttackMove;
+        // the distract key switches move -> distract
+        } else if inputs.key_presses.contains(&bindings.distract) && target_entity.is_none() {
+            command_type = CommandType::Distract;
+        // the grenade key switches move -> grenade
+        } else if inputs.key_presses.contains(&bindings.grenade) && target_entity.is_none() {
+            command_type = CommandType::Grenade;
+        // the overwatch key switches anything -> overwatch
+        } else if inputs.key_presses.contains(&bindings.overwatch) {
+            command_type = CommandType::Overwatch;
+        // the heal key switches anything -> heal
+        } else if inputs.key_presses.contains(&bindings.heal) {
+            command_type = CommandType::Heal;
+        // the brace key switches anything -> brace
+        } else if inputs.key_presses.contains(&bindings.brace) {
+            command_type = CommandType::Brace;
+        }
+        
+
+        // find the squad indices that actually exist and are being ordered
+        // sorted so that the same squad member always gets the same spot
+        let mut ordered = Vec::new();
+        // also find the middle of the group being ordered
+        let mut centroid = Vec2::new(0.0, 0.0);
+        for (entity, _id, state, pos, _keyring, _lockpicker, _stamina, _medkits, _shield) in &mut controlstate.iter() {
+            if selection.is_selected(entity, state.squad_pos) {
+                ordered.push(state.squad_pos);
+                centroid += Vec2::new(pos.0, pos.1);
+            }
+        }
+        ordered.sort();
+        if !ordered.is_empty() {
+            centroid /= ordered.len() as f32;
+        }
+
+        // when several squad members are ordered together, they take up the selected formation
+        // the formation faces the way the group is travelling, towards the point clicked
+        // or towards the leader being followed
+        let heading = match (command_type, target_position) {
+            (CommandType::Follow, Some(leader)) => Vec2::new(leader.0, leader.1) - centroid,
+            _ => Vec2::new(inputs.mouse_position.0, inputs.mouse_position.1) - centroid,
+        };
+        // followers take the slots behind the leader, who is always the first slot
+        let slots = match command_type {
+            CommandType::Follow => ordered.len() + 1,
+            _ => ordered.len(),
+        };
+        let offsets = get_formation_offsets(formation.kind, slots, heading);
+
+        // go through all the controlled components
+        for (entity, id, mut state, _pos, keyring, lockpicker, stamina, medkits, shield) in &mut controlstate.iter() {
+            // if this controlled component is one of the ones being commanded
+            if selection.is_selected(entity, state.squad_pos) {
+                
+                // new orders replace the current command, along with any queued up behind it
+                // check the command type
+                // orders that can't be carried out are rejected, leaving the current command alone
+                let mut rejection = None;
+                match command_type {
+                    // if the command type is move or attack move
+                    CommandType::Move | CommandType::AttackMove => {
+                        // default to the cursor position
+                        let mut target_point = inputs.mouse_position.clone();
+                        // use this squad member's slot in the formation, if there is more than one
+                        // squad member being ordered
+                        if let Some(index) = ordered.iter().position(|&p| p == state.squad_pos) {
+                            if index > 0 {
+                                if let Some(offset) = offsets.get(index) {
+                                    target_point = get_formation_destination(&config, &map, target_point, *offset);
+                                }
+                            }
+                        }
+                        // squad members can't be sent somewhere they can't get to
+                        let tile = TilePos::from_coords(&config, target_point.0.max(0.0).min(config.map_width - 1.0), target_point.1.max(0.0).min(config.map_height - 1.0));
+                        if map.is_tile_blocked(&tile) {
+                            rejection = Some("order_unreachable".to_string());
+                        }else{
+                            // set the current command to a move or attack move type command
+                            // towards the target point
+                            state.current_command = Command {
+                                command_type: command_type,
+                                target_point: Some(target_point),
+                                target_id: None,
+                                formation_offset: None,
+                            };
+                        }
+                    },
+                    // if the command type is attack
+                    CommandType::Attack => {
+                        // set the current command to an attack type command
+                        // at the entity clicked
+                        state.current_command = Command {
+                            command_type: command_type,
+                            target_point: None,
+                            target_id: target_entity.clone(),
+                            formation_offset: None,
+                        };
+                    },
+                    // if the command type is overwatch
+                    CommandType::Overwatch => {
+                        // every squad member ordered watches towards the cursor from where they stand
+                        state.current_command = Command {
+                            command_type: command_type,
+                            target_point: Some(inputs.mouse_position.clone()),
+                            target_id: None,
+                            formation_offset: None,
+                        };
+                    },
+                    // if the command type is flee
+                    CommandType::Flee => {
+                        // set the current command to a flee type command
+                        // at what is clicked
+                        state.current_command = Command {
+                            command_type: command_type,
+                            target_point: Some(inputs.mouse_position.clone()),
+                            target_id: target_entity.clone(),
+                            formation_offset: None,
+                        };
+                    },
+                    // if the command type is follow
+                    CommandType::Follow => {
+                        // set the current command to a follow type command
+                        // at what is clicked
+                        // take up this squad member's slot behind the leader
+                        let offset = ordered.iter()
+                            .position(|&p| p == state.squad_pos)
+                            .and_then(|index| offsets.get(index + 1))
+                            .map(|offset| (offset[0], offset[1]));
+                        // squad members can't follow themselves
+                        if target_entity == Some(id.id()) {
+                            rejection = Some("order_follow_self".to_string());
+                        }else{
+                            state.current_command = Command {
+                                command_type: command_type,
+                                target_point: None,
+                                target_id: target_entity.clone(),
+                                formation_offset: offset,
+                            }
+                        }
+                    }
+                    // if the command type is interact
+                    CommandType::Interact => {
+                        // only one squad member needs to go, the first of those ordered
+                        if ordered.first() != Some(&state.squad_pos) {
+                            continue;
+                        }
+                        // locked doors need the key, or someone who can pick the lock
+                        let key = target_door.clone().flatten();
+                        if key.map_or(false, |key| !can_unlock(keyring, lockpicker, &key)) {
+                            rejection = Some("order_locked".to_string());
+                        }else{
+                            state.current_command = Command {
+                                command_type: command_type,
+                                target_point: target_position,
+                                target_id: target_entity.clone(),
+                                formation_offset: None,
+                            };
+                        }
+                    },
+                    // if the command type is capture
+                    CommandType::Capture => {
+                        // only one squad member needs to go, the first of those ordered
+                        if ordered.first() != Some(&state.squad_pos) {
+                            continue;
+                        }
+                        state.current_command = Command {
+                            command_type: command_type,
+                            target_point: target_position,
+                            target_id: target_entity.clone(),
+                            formation_offset: None,
+                        };
+                    },
+                    // if the command type is brace
+                    CommandType::Brace => {
+                        // every squad member ordered braces towards the cursor from where they stand,
+                        // as long as they have a shield to brace behind
+                        if shield.is_none() {
+                            rejection = Some("order_no_shield".to_string());
+                        }else{
+                            state.current_command = Command {
+                                command_type: command_type,
+                                target_point: Some(inputs.mouse_position.clone()),
+                                target_id: None,
+                                formation_offset: None,
+                            };
+                        }
+                    },
+                    // if the command type is heal
+                    CommandType::Heal => {
+                        // only one squad member needs to go, the first of those ordered
+                        if ordered.first() != Some(&state.squad_pos) {
+                            continue;
+                        }
+                        // clicking on another squad member patches them up, clicking anywhere else patches up whoever was ordered
+                        let target_id = match hover.target {
+                            HoverTarget::Squad => target_entity.clone(),
+                            _ => Some(id.id()),
+                        };
+                        if medkits.map_or(true, |medkits| medkits.0 == 0) {
+                            rejection = Some("order_no_medkits".to_string());
+                        }else{
+                            state.current_command = Command {
+                                command_type: command_type,
+                                target_point: None,
+                                target_id: target_id,
+                                formation_offset: None,
+                            };
+                        }
+                    },
+                    // if the command type is distract or grenade
+                    CommandType::Distract | CommandType::Grenade => {
+                        // only one squad member needs to throw, the first of those ordered
+                        if ordered.first() != Some(&state.squad_pos) {
+                            continue;
+                        }
+                        let target_point = inputs.mouse_position.clone();
+                        // distractions and grenades can't be thrown into walls
+                        let tile = TilePos::from_coords(&config, target_point.0.max(0.0).min(config.map_width - 1.0), target_point.1.max(0.0).min(config.map_height - 1.0));
+                        if map.is_tile_blocked(&tile) {
+                            rejection = Some("order_throw_blocked".to_string());
+                        }else{
+                            state.current_command = Command {
+                                command_type: command_type,
+                                target_point: Some(target_point),
+                                target_id: None,
+                                formation_offset: None,
+                            };
+                        }
+                    },
+                    // if the command type is empty
+                    CommandType::Empty => {
+                        // set the current command to an empty type command
+                        state.current_command = Command {
+                            command_type: command_type,
+                            target_point: None,
+                            target_id: None,
+                            formation_offset: None,
+                        }
+                    },
+                    _ => {
+
+                    },
+                }
+
+                if rejection.is_none() {
+                    state.clear_queue();
+                }
+
+                // orders given while holding the sprint key are carried out at a sprint
+                if let (None, Some(mut stamina)) = (&rejection, stamina) {
+                    stamina.sprinting = inputs.key_presses.contains(&bindings.sprint);
+                }
+
+                // let everything else know how the order went
+                command_events.send(CommandEvent {
+                    unit: entity,
+                    command_type: command_type,
+                    destination: state.current_command.target_point.or(target_position),
+                    rejection: rejection,
+                });
+            }
+        }
+    }
+}
+
+// command event
+// sent whenever a squad member is given an order
+#[derive(Debug)]
+pub struct CommandEvent {
+    // the squad member the order was given to
+    pub unit: Entity,
+    // the type of order given
+    pub command_type: CommandType,
+    // where the order sends the squad member, if anywhere
+    pub destination: Option<(f32, f32)>,
+    // why the order was rejected, if it was, as the key of the reason in the language files
+    pub rejection: Option<String>,
+}
+
+// formation kind enum
+// the shapes a group of squad members can take up
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FormationKind {
+    // side by side, facing the direction of travel
+    Line,
+    // a v shape with the leader at the point
+    Wedge,
+    // one behind the other
+    Column,
+}
+
+// formation resource
+// the formation used for group orders, cycled with the F key
+pub struct Formation {
+    pub kind: FormationKind,
+}
+
+impl Default for Formation {
+    fn default() -> Self {
+        Formation {
+            kind: FormationKind::Wedge,
+        }
+    }
+}
+
+// gives the offset of every slot in a formation from the first slot
+// the formation is oriented so that it faces along the heading
+pub fn get_formation_offsets(kind: FormationKind, count: usize, heading: Vec2) -> Vec<Vec2> {
+    // forward is the direction of travel, right is perpendicular to it
+    let forward = if heading.length() > 0.0 { heading.normalize() } else { Vec2::new(0.0, -1.0) };
+    let right = Vec2::new(-forward[1], forward[0]);
+
+    (0..count).map(|i| {
+        // slots alternate between the right and left side, moving further out each pair
+        let rank = ((i + 1) / 2) as f32;
+        let side = if i % 2 == 1 { 1.0 } else { -1.0 };
+        match kind {
+            FormationKind::Line => right * side * rank * FORMATION_SPACING,
+            FormationKind::Wedge => (right * side - forward) * rank * FORMATION_SPACING,
+            FormationKind::Column => -forward * i as f32 * FORMATION_SPACING,
+        }
+    }).collect()
+}
+
+// gives where a squad member ordered to the given point goes to take up the slot at the given offset
+// slots that land on a blocked or occupied tile are moved to the closest free tile, so no one stacks up on one spot
+pub fn get_formation_destination(config: &GameConfig, map: &MapData, point: (f32, f32), offset: Vec2) -> (f32, f32) {
+    let slot = (
+        (point.0 + offset[0]).max(0.0).min(config.map_width - 1.0),
+        (point.1 + offset[1]).max(0.0).min(config.map_height - 1.0),
+    );
+    map.get_nearest_free_tile(&TilePos::from_coords(config, slot.0, slot.1)).to_coords(config)
+}
+
+// formation hotkey system
+// cycles through the formations when F is pressed
+pub fn formation_hotkey_system(inputs: Res<InputState>, bindings: Res<KeyBindings>, mut formation: ResMut<Formation>) {
+    if inputs.key_just_presses.contains(&bindings.formation) {
+        formation.kind = match formation.kind {
+            FormationKind::Line => FormationKind::Wedge,
+            FormationKind::Wedge => FormationKind::Column,
+            FormationKind::Column => FormationKind::Line,
+        };
+    }
+}
+
+// idle follow system
+// squadmates with nothing to do fall in behind the player, in the current formation
+// they only set off once they've fallen far enough behind their spot, so small
+// moves by the player don't have them constantly shuffling around
+pub fn idle_follow_system(formation: Res<Formation>, mut query: Query<(&Controlled, &Id, &Position, &mut Nerve, Option<&Behaviour>, Option<&Pathfinder>)>) {
+    // find the player, along with all the squadmates
+    let mut leader = None;
+    let mut squadmates = Vec::new();
+    let mut centroid = Vec2::new(0.0, 0.0);
+    for (state, id, pos, _actions, _behaviour, _pf) in &mut query.iter() {
+        if state.squad_pos == 0 {
+            leader = Some((id.id(), (pos.0, pos.1)));
+        }else{
+            squadmates.push(state.squad_pos);
+            centroid += Vec2::new(pos.0, pos.1);
+        }
+    }
+    let (leader_id, leader_pos) = match leader {
+        Some(leader) => leader,
+        None => return,
+    };
+    if squadmates.is_empty() {
+        return;
+    }
+    // sorted so that the same squadmate always gets the same spot
+    squadmates.sort();
+    centroid /= squadmates.len() as f32;
+
+    // the formation faces away from the squad, towards the player
+    let heading = Vec2::new(leader_pos.0, leader_pos.1) - centroid;
+    let offsets = get_formation_offsets(formation.kind, squadmates.len() + 1, heading);
+
+    for (state, _id, pos, mut actions, behaviour, pf) in &mut query.iter() {
+        // only squadmates with no orders and nothing else to do follow
+        if state.squad_pos == 0 || state.attack_move.is_some() || !actions.is_curr_action_empty() || !actions.action_queue.is_empty()
+            || state.current_command.command_type != CommandType::Empty || !state.command_queue.is_empty() {
+            continue;
+        }
+        // squadmates on their way somewhere, e.g. the rally point, carry on
+        if let Some(pf) = pf {
+            if pf.needs_pathfinding || pf.path_index < pf.tile_path.len() {
+                continue;
+            }
+        }
+        if let Some(behaviour) = behaviour {
+            if behaviour.current_behaviour == BehaviourType::Investigate {
+                continue;
+            }
+        }
+
+        // find this squadmate's spot, the player takes the first one
+        let offset = match squadmates.iter().position(|&p| p == state.squad_pos).and_then(|index| offsets.get(index + 1)) {
+            Some(offset) => *offset,
+            None => continue,
+        };
+        let slot = (leader_pos.0 + offset[0], leader_pos.1 + offset[1]);
+
+        // stay put until far enough from the spot
+        if Vec2::new(slot.0 - pos.0, slot.1 - pos.1).length() < FOLLOW_START_DISTANCE {
+            continue;
+        }
+
+        let mut params = HashMap::new();
+        // range refers to the maximum range acceptable
+        params.insert("range".to_string(), FOLLOW_STOP_DISTANCE);
+        // keep to the spot in the formation, wherever the player moves to
+        params.insert("offset_x".to_string(), offset[0]);
+        params.insert("offset_y".to_string(), offset[1]);
+        actions.action_queue.push_back(Action {
+            action_type: ActionType::Move,
+            target: (None, Some(leader_id.clone())),
+            params: Some(params),
+            group: None,
+        });
+    }
+}
+
+// rally point resource
+// the point squad members gather at when fleeing or when newly recruited
+#[derive(Default)]
+pub struct RallyPoint {
+    // where the rally point is, if one has been set
+    pub position: Option<(f32, f32)>,
+    // the flag marking the rally point
+    pub markers: Vec<Entity>,
+    // ids of the squad members already known about, None until the squad is first seen
+    pub known: Option<HashSet<String>>,
+}
+
+// sends a squad member off to the rally point, pathing around walls on the way
+pub fn send_to_rally(config: &GameConfig, point: (f32, f32), actions: &mut Nerve, pf: &mut Pathfinder) {
+    // drop whatever the squad member was doing
+    actions.clear();
+    // and path to the rally point instead
+    pf.needs_pathfinding = true;
+    pf.path_goal = TilePos::from_coords(config, point.0, point.1);
+    pf.real_goal = point;
+}
+
+// rally command system
+// sets the rally point when the player clicks while holding the rally key, and clears it on a right click
+// a flag is drawn at the rally point for as long as it's set
+pub fn rally_command_system(mut commands: Commands, config: Res<GameConfig>, inputs: Res<InputState>, bindings: Res<KeyBindings>, map: Res<MapData>,
+    mut rally: ResMut<RallyPoint>, mut materials: ResMut<Assets<ColorMaterial>>, mut sounds: ResMut<Events<SoundEvent>>) {
+    if !inputs.key_presses.contains(&bindings.rally) {
+        return;
+    }
+
+    let set = inputs.mouse_just_presses.contains(&MouseButton::Left);
+    let clear = inputs.mouse_just_presses.contains(&MouseButton::Right);
+    if !set && !clear {
+        return;
+    }
+
+    // take down the old flag
+    for entity in rally.markers.drain(..) {
+        commands.despawn(entity);
+    }
+    rally.position = None;
+
+    if set {
+        // the rally point has to be somewhere squad members can actually get to
+        let (mx, my) = inputs.mouse_position;
+        let tile = map.get_nearest_free_tile(&TilePos::from_coords(&config, mx.max(0.0).min(config.map_width - 1.0), my.max(0.0).min(config.map_height - 1.0)));
+        let point = tile.to_coords(&config);
+        rally.position = Some(point);
+
+        // put up a flag, a pole with a pennant hanging off the top of it
+        let pole = materials.add(Color::rgb(0.3, 0.2, 0.1).into());
+        let pennant = materials.add(Color::RED.into());
+        commands
+            .spawn(SimpleRect::new(pole, Vec2::new(2.0, 16.0)))
+            .with(Position(point.0, point.1 - 8.0));
+        if let Some(entity) = commands.current_entity() {
+            rally.markers.push(entity);
+        }
+        commands
+            .spawn(SimpleRect::new(pennant, Vec2::new(8.0, 6.0)))
+            .with(Position(point.0 + 5.0, point.1 - 13.0));
+        if let Some(entity) = commands.current_entity() {
+            rally.markers.push(entity);
+        }
+
+        // acknowledge the new rally point
+        sounds.send(SoundEvent {
+            sound: SoundType::Acknowledge,
+            position: None,
+        });
+    }
+}
+
+// rally recruit system
+// sends squad members that have just joined the squad to the rally point
+// the squad the game starts with is not sent anywhere
+pub fn rally_recruit_system(config: Res<GameConfig>, mut rally: ResMut<RallyPoint>, mut query: Query<(&Controlled, &Id, &mut Nerve, &mut Pathfinder)>) {
+    let first_look = rally.known.is_none();
+    let position = rally.position;
+    let known = rally.known.get_or_insert_with(HashSet::new);
+
+    for (_state, id, mut actions, mut pf) in &mut query.iter() {
+        // check if this squad member has been seen before
+        if !known.insert(id.id()) || first_look {
+            continue;
+        }
+        if let Some(point) = position {
+            send_to_rally(&config, point, &mut actions, &mut pf);
+        }
+    }
+}
+
+// throw event state holds an event reader for throw events
+#[derive(Default)]
+pub struct ThrowEventState {
+    pub event_reader: EventReader<ThrowEvent>,
+}
+
+// thrown component
+// spawn this component along with something thrown, flying in an arc from where it was thrown to where it lands
+pub struct Thrown {
+    pub thrower_id: String,
+    pub from: (f32, f32),
+    pub to: (f32, f32),
+    pub timer: Timer,
+    // seconds the fuse burns for once it lands, if it's a grenade
+    pub fuse: Option<f32>,
+}
+
+// grenade component
+// inserted onto a grenade once it lands, burning down its fuse
+pub struct Grenade {
+    // id of whoever threw the grenade, credited with whatever the blast hits
+    pub thrower_id: String,
+    pub fuse: Timer,
+}
+
+// throw system
+// spawns in the objects thrown by distract and grenade actions
+pub fn throw_system(mut commands: Commands, mut state: ResMut<ThrowEventState>, events: Res<Events<ThrowEvent>>, mut materials: ResMut<Assets<ColorMaterial>>) {
+    for event in state.event_reader.iter(&events) {
+        let dist = Vec2::new(event.to.0 - event.from.0, event.to.1 - event.from.1).length();
+        // grenades are darker than distractions
+        let color = if event.fuse.is_some() { Color::rgb(0.2, 0.25, 0.15) } else { Color::rgb(0.5, 0.35, 0.2) };
+        let material = materials.add(color.into());
+        commands
+            .spawn(SimpleRect::new(material, Vec2::new(3.0, 3.0)))
+            .with(Position(event.from.0, event.from.1))
+            .with(Thrown {
+                thrower_id: event.thrower_id.clone(),
+                from: event.from,
+                to: event.to,
+                timer: Timer::from_seconds((dist / THROW_SPEED).max(0.1), false),
+                fuse: event.fuse,
+            });
+    }
+}
+
+// thrown system
+// flies thrown objects along their arc, and makes a noise once they land
+// the noise is made by the squad, so only everyone else hears it
+// grenades land quietly, and are left lying there with their fuse burning
+pub fn thrown_system(mut commands: Commands, time: Res<Time>, mut noises: ResMut<Events<NoiseEvent>>, mut query: Query<(Entity, &mut Thrown, &mut Position)>) {
+    for (entity, mut thrown, mut pos) in &mut query.iter() {
+        thrown.timer.tick(time.delta_seconds);
+        if thrown.timer.finished {
+            if let Some(fuse) = thrown.fuse {
+                pos.0 = thrown.to.0;
+                pos.1 = thrown.to.1;
+                commands.remove_one::<Thrown>(entity);
+                commands.insert_one(entity, Grenade {
+                    thrower_id: thrown.thrower_id.clone(),
+                    fuse: Timer::from_seconds(fuse, false),
+                });
+                continue;
+            }
+            commands.despawn(entity);
+            noises.send(NoiseEvent {
+                origin: thrown.to,
+                radius: DISTRACTION_NOISE_RADIUS,
+                attitude: Some(AttitudeType::Squad),
+            });
+            continue;
+        }
+        // travel along the ground, lifted up by the arc, which peaks halfway
+        let progress = thrown.timer.elapsed / thrown.timer.duration;
+        let height = THROW_ARC_HEIGHT * 4.0 * progress * (1.0 - progress);
+        pos.0 = thrown.from.0 + (thrown.to.0 - thrown.from.0) * progress;
+        pos.1 = thrown.from.1 + (thrown.to.1 - thrown.from.1) * progress - height;
+    }
+}
+
+// grenade fuse system
+// burns down the fuses of grenades lying on the ground, setting off a blast where each one lies once its fuse is out
+pub fn grenade_fuse_system(mut commands: Commands, time: Res<Time>, mut blasts: ResMut<Events<BlastEvent>>, mut query: Query<(Entity, &mut Grenade, &Position)>) {
+    for (entity, mut grenade, pos) in &mut query.iter() {
+        grenade.fuse.tick(time.delta_seconds);
+        if grenade.fuse.finished {
+            commands.despawn(entity);
+            blasts.send(BlastEvent {
+                attacker_id: grenade.thrower_id.clone(),
+                origin: (pos.0, pos.1),
+                radius: GRENADE_RADIUS,
+                damage: GRENADE_DAMAGE,
+                knockback: GRENADE_KNOCKBACK,
+            });
+        }
+    }
+}
+
+// stop hotkey system
+// orders the selected squad members to stop when the stop key is pressed
+pub fn stop_hotkey_system(inputs: Res<InputState>, bindings: Res<KeyBindings>, selection: Res<Selection>, mut command_events: ResMut<Events<CommandEvent>>,
+    mut query: Query<(Entity, &mut Controlled)>) {
+    if !inputs.key_just_presses.contains(&bindings.stop) {
+        return;
+    }
+
+    for (entity, mut state) in &mut query.iter() {
+        if selection.is_selected(entity, state.squad_pos) {
+            state.current_command = Command {
+                command_type: CommandType::Stop,
+                target_point: None,
+                target_id: None,
+                formation_offset: None,
+            };
+            command_events.send(CommandEvent {
+                unit: entity,
+                command_type: CommandType::Stop,
+                destination: None,
+                rejection: None,
+            });
+        }
+    }
+}
+
+// order queue hotkey system
+// takes back the last queued order, or clears every queued order, of the selected squad members
+// only orders that haven't started yet are touched, so what they're doing now carries on
+pub fn order_queue_hotkey_system(inputs: Res<InputState>, bindings: Res<KeyBindings>, selection: Res<Selection>, mut query: Query<(Entity, &mut Controlled)>) {
+    let undo = inputs.key_just_presses.contains(&bindings.undo);
+    let clear = inputs.key_just_presses.contains(&bindings.clear_queue);
+    if !undo && !clear {
+        return;
+    }
+
+    for (entity, mut state) in &mut query.iter() {
+        if !selection.is_selected(entity, state.squad_pos) {
+            continue;
+        }
+        if clear {
+            state.clear_queue();
+        }else{
+            state.undo_command();
+        }
+    }
+}
+
+// attack move system
+// squad members on an attack move stop to fight any hostile that comes within weapon range,
+// then carry on to where they were going once it's dealt with
+pub fn attack_move_system(config: Res<GameConfig>, focus: Res<FocusTarget>, pipeline: Res<QueryPipeline>, bodies: Res<RigidBodySet>, colliders: Res<ColliderSet>,
+    mut squad: Query<(&mut Controlled, &Position, &mut Nerve, &mut Pathfinder, Option<&Weapon>)>, mut people: Query<(&Id, &Person, &Position, Option<&Ambusher>)>) {
+    // gather up the hostiles, apart from hidden ambushers
+    let mut hostiles = Vec::new();
+    for (id, person, pos, ambusher) in &mut people.iter() {
+        if person.attitude == AttitudeType::Hostile && !ambusher.map_or(false, |ambusher| ambusher.hidden) {
+            hostiles.push((id.id(), (pos.0, pos.1)));
+        }
+    }
+
+    for (mut state, pos, mut actions, mut pf, weapon) in &mut squad.iter() {
+        let weapon = weapon.cloned().unwrap_or_default();
+        let destination = match state.attack_move {
+            Some(destination) => destination,
+            None => continue,
+        };
+
+        // already fighting
+        let attacking = match actions.current_action.action_type {
+            ActionType::Attack => true,
+            _ => actions.action_queue.iter().any(|action| match action.action_type {
+                ActionType::Attack => true,
+                _ => false,
+            }),
+        };
+        if attacking {
+            continue;
+        }
+
+        // look for hostiles in range and in sight
+        let mut candidates = Vec::new();
+        for (hid, hpos) in hostiles.iter() {
+            let dist = Vec2::new(hpos.0 - pos.0, hpos.1 - pos.1).length();
+            if dist > weapon.range || !combat_query::line_of_sight(&config, &pipeline, &bodies, &colliders, (pos.0, pos.1), *hpos) {
+                continue;
+            }
+            candidates.push((hid.clone(), dist));
+        }
+
+        if let Some(tid) = pick_target(&candidates, &focus.target) {
+            // stop where we are and fight
+            actions.clear();
+            pf.needs_pathfinding = false;
+            pf.tile_path.clear();
+            pf.path.clear();
+            pf.path_index = 0;
+
+            let mut extra = HashMap::new();
+            // don't get dragged too far off course
+            extra.insert("max_chase".to_string(), weapon.range * 2.0);
+            actions.action_queue.extend(get_attack_actions(Some(tid), &weapon, extra));
+            continue;
+        }
+
+        // nothing to fight, check on how the move is going
+        if !actions.is_curr_action_empty() || !actions.action_queue.is_empty() || pf.needs_pathfinding {
+            continue;
+        }
+        if Vec2::new(destination.0 - pos.0, destination.1 - pos.1).length() <= config.tile_size * 2.0 {
+            // arrived, the attack move is done
+            state.attack_move = None;
+        }else if pf.path_index >= pf.tile_path.len() {
+            // the path was called off to fight, so find a new one from here
+            pf.needs_pathfinding = true;
+            pf.path_goal = TilePos::from_coords(&config, destination.0, destination.1);
+            pf.real_goal = destination;
+        }
+    }
+}
+
+// switch hotkey system
+// has the selected squad members with a holstered weapon switch to it when the switch key is pressed
+pub fn switch_hotkey_system(inputs: Res<InputState>, bindings: Res<KeyBindings>, selection: Res<Selection>, mut query: Query<(Entity, &Controlled, &Holstered, &mut Nerve)>) {
+    if !inputs.key_just_presses.contains(&bindings.switch) {
+        return;
+    }
+
+    for (entity, state, _holstered, mut actions) in &mut query.iter() {
+        if selection.is_selected(entity, state.squad_pos) {
+            start_switch(&mut actions);
+        }
+    }
+}
+
+// binding input enum
+// one part of what's pressed for a key binding, held down along with the other parts
+#[derive(Debug, Clone)]
+pub enum BindingInput {
+    Key(KeyCode),
+    // any one of the keys, e.g. either alt key, or the key of each control group
+    AnyKey(Vec<KeyCode>),
+    // any key from the first to the last, e.g. the number keys
+    KeyRange(KeyCode, KeyCode),
+    // a mouse button, by the key of its name in the language files
+    Mouse(&'static str),
+}
+
+impl BindingInput {
+    // gives what's shown for the input in the help
+    pub fn name(&self, locale: &Locale) -> String {
+        match self {
+            BindingInput::Key(key) => get_key_name(*key),
+            BindingInput::AnyKey(keys) => keys.iter().map(|key| get_key_name(*key)).collect::<Vec<String>>().join("/"),
+            BindingInput::KeyRange(first, last) => format!("{}-{}", get_key_name(*first), get_key_name(*last)),
+            BindingInput::Mouse(name) => locale.get(name),
+        }
+    }
+}
+
+// gives the name a key is shown by, with the number keys shown by their number
+pub fn get_key_name(key: KeyCode) -> String {
+    let name = format!("{:?}", key);
+    if name.len() == 4 && name.starts_with("Key") {
+        name[3..].to_string()
+    }else{
+        name
+    }
+}
+
+// key binding struct
+// what's pressed for something the player can do, along with the key of its description in the language files
+#[derive(Debug, Clone)]
+pub struct KeyBinding {
+    pub inputs: Vec<BindingInput>,
+    pub action: &'static str,
+}
+
+impl KeyBinding {
+    pub fn new(inputs: Vec<BindingInput>, action: &'static str) -> Self {
+        KeyBinding {
+            inputs: inputs,
+            action: action,
+        }
+    }
+}
+
+// key bindings resource
+// the keys for everything the player can do, which the controls read, and the help lists
+// every key starts out as its setting
+#[derive(Debug, Clone)]
+pub struct KeyBindings {
+    // orders
+    pub flee: KeyCode,
+    pub follow: KeyCode,
+    pub attack_move: KeyCode,
+    pub distract: KeyCode,
+    pub grenade: KeyCode,
+    pub overwatch: KeyCode,
+    pub heal: KeyCode,
+    pub sprint: KeyCode,
+    pub focus: KeyCode,
+    pub rally: KeyCode,
+    pub waypoint: KeyCode,
+    pub assign_patrol: KeyCode,
+    pub stop: KeyCode,
+    pub undo: KeyCode,
+    pub clear_queue: KeyCode,
+    pub macros: [KeyCode; 4],
+    // squad
+    pub add_selection: KeyCode,
+    pub control_groups: [KeyCode; 4],
+    pub save_group: KeyCode,
+    pub formation: KeyCode,
+    pub stance: KeyCode,
+    pub sneak: KeyCode,
+    pub switch: KeyCode,
+    pub brace: KeyCode,
+    // view, the camera scroll keys go up, down, left then right
+    pub camera_scroll: [KeyCode; 4],
+    pub vision_cones: KeyCode,
+    pub display_modifiers: [KeyCode; 2],
+    pub display_mode: KeyCode,
+    pub palette: KeyCode,
+    pub language: KeyCode,
+    pub help: KeyCode,
+    pub combat_log: KeyCode,
+    pub combat_log_older: KeyCode,
+    pub combat_log_newer: KeyCode,
+    // debug
+    pub console: KeyCode,
+    pub inspector: KeyCode,
+    pub inspector_prev: KeyCode,
+    pub inspector_next: KeyCode,
+    pub weight_overlay: KeyCode,
+    pub occupancy_overlay: KeyCode,
+    pub tile_editor: KeyCode,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        KeyBindings {
+            flee: FLEE_MODIFIER,
+            follow: FOLLOW_MODIFIER,
+            attack_move: ATTACK_MOVE_KEY,
+            distract: DISTRACT_KEY,
+            grenade: GRENADE_KEY,
+            overwatch: OVERWATCH_KEY,
+            heal: HEAL_KEY,
+            sprint: SPRINT_KEY,
+            focus: FOCUS_KEY,
+            rally: RALLY_KEY,
+            waypoint: PATROL_MODIFIER,
+            assign_patrol: PATROL_ASSIGN_KEY,
+            stop: STOP_KEY,
+            undo: UNDO_ORDER_KEY,
+            clear_queue: CLEAR_QUEUE_KEY,
+            macros: MACRO_KEYS,
+            add_selection: SELECTION_ADD_MODIFIER,
+            control_groups: CONTROL_GROUP_KEYS,
+            save_group: GROUP_SAVE_MODIFIER,
+            formation: FORMATION_KEY,
+            stance: STANCE_KEY,
+            sneak: SNEAK_KEY,
+            switch: SWITCH_KEY,
+            brace: BRACE_KEY,
+            camera_scroll: CAMERA_SCROLL_KEYS,
+            vision_cones: VISION_CONE_KEY,
+            display_modifiers: DISPLAY_MODE_MODIFIERS,
+            display_mode: DISPLAY_MODE_KEY,
+            palette: PALETTE_KEY,
+            language: LANGUAGE_KEY,
+            help: HELP_KEY,
+            combat_log: COMBAT_LOG_KEY,
+            combat_log_older: COMBAT_LOG_OLDER_KEY,
+            combat_log_newer: COMBAT_LOG_NEWER_KEY,
+            console: CONSOLE_KEY,
+            inspector: INSPECTOR_KEY,
+            inspector_prev: INSPECTOR_PREV_KEY,
+            inspector_next: INSPECTOR_NEXT_KEY,
+            weight_overlay: WEIGHT_OVERLAY_KEY,
+            occupancy_overlay: OCCUPANCY_OVERLAY_KEY,
+            tile_editor: TILE_EDITOR_KEY,
+        }
+    }
+}
+
+impl KeyBindings {
+    // gives every key that's bound to more than one thing, which should never happen
+    // the modifiers are left out, since they're only ever held with a click or another key,
+    // e.g. left shift flees with a left click but adds to the selection with a right click
+    pub fn clashes(&self) -> Vec<KeyCode> {
+        let mut keys = vec![
+            self.attack_move, self.distract, self.grenade, self.overwatch, self.heal, self.sprint, self.focus, self.rally,
+            self.assign_patrol, self.stop, self.undo, self.clear_queue, self.formation, self.stance, self.sneak, self.switch,
+            self.brace, self.vision_cones, self.display_mode, self.palette, self.language, self.help, self.combat_log,
+            self.combat_log_older, self.combat_log_newer, self.console, self.inspector, self.inspector_prev, self.inspector_next,
+            self.weight_overlay, self.occupancy_overlay, self.tile_editor,
+        ];
+        keys.extend_from_slice(&self.macros);
+        keys.extend_from_slice(&self.control_groups);
+        keys.extend_from_slice(&self.camera_scroll);
+        // the number keys select squad members
+        keys.extend_from_slice(&[
+            KeyCode::Key0, KeyCode::Key1, KeyCode::Key2, KeyCode::Key3, KeyCode::Key4,
+            KeyCode::Key5, KeyCode::Key6, KeyCode::Key7, KeyCode::Key8, KeyCode::Key9,
+        ]);
+
+        let mut clashes = Vec::new();
+        for (index, key) in keys.iter().enumerate() {
+            if keys[..index].contains(key) && !clashes.contains(key) {
+                clashes.push(*key);
+            }
+        }
+        clashes
+    }
+    // gives every key binding, in groups under the key of each group's name in the language files
+    pub fn groups(&self) -> Vec<(&'static str, Vec<KeyBinding>)> {
+        let key = BindingInput::Key;
+        let keys = |keys: &[KeyCode]| BindingInput::AnyKey(keys.to_vec());
+        let left = || BindingInput::Mouse("mouse_left");
+        let right = || BindingInput::Mouse("mouse_right");
+        vec![
+            ("help_orders", vec![
+                KeyBinding::new(vec![left()], "help_order"),
+                KeyBinding::new(vec![key(self.flee), left()], "help_flee"),
+                KeyBinding::new(vec![key(self.follow), left()], "help_follow"),
+                KeyBinding::new(vec![key(self.attack_move), left()], "help_attack_move"),
+                KeyBinding::new(vec![key(self.distract), left()], "help_distract"),
+                KeyBinding::new(vec![key(self.grenade), left()], "help_grenade"),
+                KeyBinding::new(vec![key(self.overwatch), left()], "help_overwatch"),
+                KeyBinding::new(vec![key(self.heal), left()], "help_heal"),
+                KeyBinding::new(vec![key(self.sprint), left()], "help_sprint"),
+                KeyBinding::new(vec![key(self.focus), left()], "help_focus"),
+                KeyBinding::new(vec![key(self.rally), left()], "help_rally"),
+                KeyBinding::new(vec![key(self.rally), right()], "help_clear_rally"),
+                KeyBinding::new(vec![key(self.waypoint), left()], "help_waypoint"),
+                KeyBinding::new(vec![key(self.assign_patrol)], "help_assign_patrol"),
+                KeyBinding::new(vec![key(self.stop)], "help_stop"),
+                KeyBinding::new(vec![key(self.undo)], "help_undo"),
+                KeyBinding::new(vec![key(self.clear_queue)], "help_clear_queue"),
+                KeyBinding::new(vec![keys(&self.macros)], "help_macro"),
+            ]),
+            ("help_squad", vec![
+                KeyBinding::new(vec![BindingInput::KeyRange(KeyCode::Key0, KeyCode::Key9)], "help_select"),
+                KeyBinding::new(vec![right()], "help_box_select"),
+                KeyBinding::new(vec![key(self.add_selection)], "help_add_selection"),
+                KeyBinding::new(vec![keys(&self.control_groups)], "help_select_group"),
+                KeyBinding::new(vec![key(self.save_group), keys(&self.control_groups)], "help_save_group"),
+                KeyBinding::new(vec![key(self.formation)], "help_formation"),
+                KeyBinding::new(vec![key(self.stance)], "help_stance"),
+                KeyBinding::new(vec![key(self.sneak)], "help_sneak"),
+                KeyBinding::new(vec![key(self.switch)], "help_switch"),
+                KeyBinding::new(vec![key(self.brace)], "help_brace"),
+            ]),
+            ("help_view", vec![
+                KeyBinding::new(vec![keys(&self.camera_scroll)], "help_scroll"),
+                KeyBinding::new(vec![key(self.vision_cones)], "help_vision_cones"),
+                KeyBinding::new(vec![keys(&self.display_modifiers), key(self.display_mode)], "help_display_mode"),
+                KeyBinding::new(vec![keys(&self.display_modifiers), key(self.palette)], "help_palette"),
+                KeyBinding::new(vec![keys(&self.display_modifiers), key(self.language)], "help_language"),
+                KeyBinding::new(vec![key(self.help)], "help_help"),
+                KeyBinding::new(vec![key(self.combat_log)], "help_combat_log"),
+                KeyBinding::new(vec![key(self.combat_log_older), key(self.combat_log_newer)], "help_scroll_log"),
+            ]),
+            ("help_debug", vec![
+                KeyBinding::new(vec![key(self.console)], "help_console"),
+                KeyBinding::new(vec![key(self.inspector)], "help_inspector"),
+                KeyBinding::new(vec![key(self.inspector_prev), key(self.inspector_next)], "help_inspect"),
+                KeyBinding::new(vec![key(self.weight_overlay)], "help_weight_overlay"),
+                KeyBinding::new(vec![key(self.occupancy_overlay)], "help_occupancy_overlay"),
+                KeyBinding::new(vec![key(self.tile_editor)], "help_tile_editor"),
+            ]),
+        ]
+    }
+}
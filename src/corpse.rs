@@ -0,0 +1,115 @@
+// corpse module
+// the bodies left behind by the dead
+use bevy::prelude::*;
+use std::collections::HashSet;
+use bevy_rapier2d::rapier::dynamics::RigidBodySet;
+use bevy_rapier2d::rapier::geometry::ColliderSet;
+use bevy_rapier2d::rapier::pipeline::QueryPipeline;
+
+use crate::{combat_query, PLAYER_Z_LEVEL, Position};
+use crate::behaviour::{Behaviour, BehaviourType, Pathfinder};
+use crate::combat::DeathEvent;
+use crate::config::GameConfig;
+use crate::lighting::LightMap;
+use crate::map::MapData;
+use crate::nerve::{ActionType, Nerve};
+use crate::perception::{Perception, SearchPattern};
+use crate::person::{AttitudeType, Person, SimpleRect};
+use crate::tick::AddTickSystem;
+
+// corpse plugin
+// responsible for the bodies left behind by the dead
+// hostiles that come across the body of one of their own go on their guard, and search the area around it
+pub struct CorpsePlugin;
+
+// implementation of the plugin trait,
+// required for this to be used as a plugin
+impl Plugin for CorpsePlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        // initialise the death event state resource
+        app.init_resource::<CorpseDeathState>()
+        // add in the system leaving bodies behind
+        .add_tick_system(corpse_system.system())
+        // add in the system letting hostiles find bodies
+        .add_tick_system(corpse_discovery_system.system());
+    }
+}
+
+// corpse component
+// spawn this component along with the body of someone who's died
+pub struct Corpse {
+    // attitude of whoever died, if it was a person
+    pub attitude: Option<AttitudeType>,
+    // the hostiles that have already found the body
+    pub found_by: HashSet<Entity>,
+}
+
+// corpse death state holds an event reader for death events
+#[derive(Default)]
+pub struct CorpseDeathState {
+    pub event_reader: EventReader<DeathEvent>,
+}
+
+// corpse system
+// leaves a body behind wherever someone dies
+pub fn corpse_system(mut commands: Commands, mut state: ResMut<CorpseDeathState>, events: Res<Events<DeathEvent>>, mut materials: ResMut<Assets<ColorMaterial>>) {
+    for event in state.event_reader.iter(&events) {
+        let material = materials.add(Color::rgb(0.3, 0.05, 0.05).into());
+        // bodies lie on their side, under everyone still standing
+        let mut body = SimpleRect::new(material, Vec2::new(10.0, 5.0));
+        body.translation = Translation(Vec3::new(-1000.0, -1000.0, PLAYER_Z_LEVEL - 1.0));
+        commands
+            .spawn(body)
+            .with(Position(event.position.0, event.position.1))
+            .with(Corpse {
+                attitude: event.attitude,
+                found_by: HashSet::new(),
+            });
+    }
+}
+
+// corpse discovery system
+// hostiles that see the body of another hostile go on their guard and search the area around it
+// each hostile only reacts to each body once, so bodies left lying around keep catching out whoever passes by
+pub fn corpse_discovery_system(config: Res<GameConfig>, map: Res<MapData>, light: Res<LightMap>, pipeline: Res<QueryPipeline>, bodies: Res<RigidBodySet>, colliders: Res<ColliderSet>,
+    mut corpses: Query<(&mut Corpse, &Position)>,
+    mut query: Query<(Entity, &Person, &mut Perception, &Position, &mut Behaviour, &mut Nerve, &mut Pathfinder)>) {
+    for (mut corpse, corpse_pos) in &mut corpses.iter() {
+        if corpse.attitude != Some(AttitudeType::Hostile) {
+            continue;
+        }
+        let corpse_pos = (corpse_pos.0, corpse_pos.1);
+        for (entity, pers, mut perception, pos, mut behav, mut actions, mut pf) in &mut query.iter() {
+            if pers.attitude != AttitudeType::Hostile || corpse.found_by.contains(&entity) {
+                continue;
+            }
+            // hostiles in the middle of something else have no time to notice
+            let busy = match behav.current_behaviour {
+                BehaviourType::Ambush | BehaviourType::Surrender | BehaviourType::Panic | BehaviourType::RaiseAlarm => true,
+                _ => false,
+            };
+            let fighting = match actions.current_action.action_type {
+                ActionType::Attack => true,
+                _ => actions.action_queue.iter().any(|action| match action.action_type {
+                    ActionType::Attack => true,
+                    _ => false,
+                }),
+            };
+            if busy || fighting {
+                continue;
+            }
+            if !perception.in_vision_cone((pos.0, pos.1), corpse_pos, light.visibility_at(corpse_pos))
+                || !combat_query::line_of_sight(&config, &pipeline, &bodies, &colliders, (pos.0, pos.1), corpse_pos) {
+                continue;
+            }
+
+            corpse.found_by.insert(entity);
+            perception.wary = true;
+            // drop everything and search around the body
+            actions.clear();
+            pf.tile_path.clear();
+            perception.search = Some(SearchPattern::new(&config, &map, corpse_pos));
+            behav.current_behaviour = BehaviourType::Search;
+        }
+    }
+}
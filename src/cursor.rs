@@ -0,0 +1,116 @@
+// cursor module
+// the in-game cursor, showing what a left click would do
+use bevy::prelude::*;
+use bevy::window::Windows;
+use bevy::winit::WinitWindows;
+
+use crate::Position;
+use crate::control::{Hover, HoverTarget, InputState};
+use crate::mods::ModAssets;
+
+// cursor plugin
+// responsible for the in-game cursor, which replaces the system cursor
+// and changes to show what a left click would do, going by what the mouse is over
+pub struct CursorPlugin;
+
+// implementation of the plugin trait,
+// required for this to be used as a plugin
+impl Plugin for CursorPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<CursorState>()
+            .add_startup_system(setup_cursor_system.system())
+            .add_system(hide_system_cursor_system.system())
+            .add_system(cursor_system.system());
+    }
+}
+
+// settings for the in-game cursor
+// the glyph shown for each order a left click would give
+pub static CURSOR_ATTACK: &str = "+";
+pub static CURSOR_FOLLOW: &str = "»";
+pub static CURSOR_INTERACT: &str = "o";
+pub static CURSOR_MOVE: &str = "v";
+pub static CURSOR_BLOCKED: &str = "x";
+pub static CURSOR_FONT_SIZE: f32 = 16.0;
+// shifts the glyph from its top left corner so it sits centred on the mouse
+pub static CURSOR_OFFSET: (f32, f32) = (-5.0, -9.0);
+
+// cursor component
+// marks the text drawing the in-game cursor
+pub struct Cursor;
+
+// cursor state resource
+// whether the system cursor has been hidden yet, which can only happen once the window is open
+#[derive(Default)]
+pub struct CursorState {
+    pub system_cursor_hidden: bool,
+}
+
+// gives the glyph and colour the cursor takes over each kind of thing
+pub fn get_cursor_style(target: HoverTarget) -> (&'static str, Color) {
+    match target {
+        // a crosshair, to attack
+        HoverTarget::Hostile | HoverTarget::Neutral => (CURSOR_ATTACK, Color::rgb(0.9, 0.1, 0.1)),
+        // arrows, to follow
+        HoverTarget::Squad => (CURSOR_FOLLOW, Color::rgb(0.1, 0.8, 0.1)),
+        HoverTarget::Door | HoverTarget::Surrendered => (CURSOR_INTERACT, Color::YELLOW),
+        // a boot, to move
+        HoverTarget::Ally | HoverTarget::Ground => (CURSOR_MOVE, Color::WHITE),
+        // nowhere to go
+        HoverTarget::Blocked => (CURSOR_BLOCKED, Color::rgb(0.5, 0.5, 0.5)),
+    }
+}
+
+// setup cursor system
+// spawns in the in-game cursor
+pub fn setup_cursor_system(mut commands: Commands, asset_server: Res<AssetServer>, mods: Res<ModAssets>) {
+    let font_handle = asset_server.load(mods.resolve("assets/fonts/LiberationMono-Regular.ttf")).unwrap();
+    let (glyph, color) = get_cursor_style(HoverTarget::Ground);
+    commands
+        .spawn(TextComponents {
+            style: Style {
+                position_type: PositionType::Absolute,
+                ..Default::default()
+            },
+            text: Text {
+                value: glyph.to_string(),
+                font: font_handle,
+                style: TextStyle {
+                    font_size: CURSOR_FONT_SIZE,
+                    color: color,
+                },
+            },
+            ..Default::default()
+        })
+        .with(Position(0.0, 0.0))
+        .with(Cursor);
+}
+
+// hide system cursor system
+// hides the system cursor over the window, once the window has been opened
+pub fn hide_system_cursor_system(mut state: ResMut<CursorState>, windows: Res<Windows>, winit_windows: Res<WinitWindows>) {
+    if state.system_cursor_hidden {
+        return;
+    }
+    if let Some(window) = windows.get_primary().and_then(|window| winit_windows.get_window(window.id)) {
+        window.set_cursor_visible(false);
+        state.system_cursor_hidden = true;
+    }
+}
+
+// cursor system
+// moves the in-game cursor to the mouse, and changes it to match what the mouse is over
+pub fn cursor_system(inputs: Res<InputState>, hover: Res<Hover>, mut query: Query<(&Cursor, &mut Text, &mut Position)>) {
+    let (glyph, color) = get_cursor_style(hover.target);
+    for (_cursor, mut text, mut pos) in &mut query.iter() {
+        // the glyph is drawn from its top left corner, so shift it to sit centred on the mouse
+        pos.0 = inputs.mouse_position.0 + CURSOR_OFFSET.0;
+        pos.1 = inputs.mouse_position.1 + CURSOR_OFFSET.1;
+        if text.value != glyph {
+            text.value = glyph.to_string();
+        }
+        if text.style.color != color {
+            text.style.color = color;
+        }
+    }
+}
@@ -0,0 +1,21 @@
+// debug render module
+// drawing collider outlines for debugging
+use bevy::prelude::*;
+use bevy_rapier2d::render::RapierRenderPlugin;
+
+use crate::config::LaunchOptions;
+
+// debug render plugin
+// draws the outlines of every collider, when the launch options ask for it
+pub struct DebugRenderPlugin;
+
+// implementation of the plugin trait,
+// required for this to be used as a plugin
+impl Plugin for DebugRenderPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        let enabled = app.resources().get::<LaunchOptions>().map_or(false, |launch| launch.debug_physics);
+        if enabled {
+            app.add_plugin(RapierRenderPlugin);
+        }
+    }
+}
@@ -0,0 +1,89 @@
+// detection module
+// the detection meters shown above hostiles
+use bevy::prelude::*;
+use std::collections::HashMap;
+
+use crate::{PLAYER_Z_LEVEL, Position};
+use crate::perception::Perception;
+use crate::person::{AttitudeType, Person, SimpleRect};
+
+// detection meter plugin
+// responsible for the eyes shown above hostiles that are starting to notice a sneaking squad member
+// each eye fills up as the hostile gets closer to spotting them, and disappears once it's sure or has forgotten
+pub struct DetectionMeterPlugin;
+
+// implementation of the plugin trait,
+// required for this to be used as a plugin
+impl Plugin for DetectionMeterPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        // initialise the detection meters resource
+        app.init_resource::<DetectionMeters>()
+        // add in the system drawing the detection meters
+        .add_system(detection_meter_system.system());
+    }
+}
+
+// detection meters resource
+// the outline and fill of the eye shown above each hostile that's noticing someone
+#[derive(Default)]
+pub struct DetectionMeters {
+    pub meters: HashMap<Entity, (Entity, Entity)>,
+}
+
+// detection meter system
+// keeps an eye above every hostile partway to spotting someone, filled up as far as the hostile has got
+pub fn detection_meter_system(mut commands: Commands, mut meters: ResMut<DetectionMeters>, mut materials: ResMut<Assets<ColorMaterial>>,
+    mut query: Query<(Entity, &Person, &Perception, &Position)>, mut parts: Query<(&mut Position, &mut Sprite)>) {
+    let mut noticing = Vec::new();
+    for (entity, pers, perception, pos) in &mut query.iter() {
+        if pers.attitude == AttitudeType::Hostile && perception.detection > 0.0 && perception.detection < 1.0 {
+            noticing.push((entity, (pos.0, pos.1 - 12.0), perception.detection));
+        }
+    }
+
+    // clear away the eyes of hostiles no longer noticing anyone
+    let gone: Vec<Entity> = meters.meters.keys().filter(|owner| !noticing.iter().any(|(entity, _, _)| *entity == **owner)).cloned().collect();
+    for owner in gone {
+        if let Some((outline, fill)) = meters.meters.remove(&owner) {
+            commands.despawn(outline);
+            commands.despawn(fill);
+        }
+    }
+
+    for (owner, position, detection) in noticing {
+        // the eye fills up from the left
+        let width = 10.0 * detection;
+        let fill_position = (position.0 - 5.0 + width / 2.0, position.1);
+        if !meters.meters.contains_key(&owner) {
+            let outline_material = materials.add(Color::rgb(0.15, 0.15, 0.15).into());
+            let fill_material = materials.add(Color::rgb(1.0, 0.85, 0.0).into());
+            commands
+                .spawn(SimpleRect::new(outline_material, Vec2::new(12.0, 5.0)))
+                .with(Position(position.0, position.1));
+            let outline = commands.current_entity();
+            // the fill is drawn over the outline
+            let mut fill_sprite = SimpleRect::new(fill_material, Vec2::new(width, 3.0));
+            fill_sprite.translation = Translation(Vec3::new(-1000.0, -1000.0, PLAYER_Z_LEVEL + 1.0));
+            commands
+                .spawn(fill_sprite)
+                .with(Position(fill_position.0, fill_position.1));
+            let fill = commands.current_entity();
+            if let (Some(outline), Some(fill)) = (outline, fill) {
+                meters.meters.insert(owner, (outline, fill));
+            }
+            continue;
+        }
+        let (outline, fill) = meters.meters[&owner];
+        if let Ok(mut pos) = parts.get_mut::<Position>(outline) {
+            pos.0 = position.0;
+            pos.1 = position.1;
+        }
+        if let Ok(mut pos) = parts.get_mut::<Position>(fill) {
+            pos.0 = fill_position.0;
+            pos.1 = fill_position.1;
+        }
+        if let Ok(mut sprite) = parts.get_mut::<Sprite>(fill) {
+            sprite.size = Vec2::new(width, 3.0);
+        }
+    }
+}
@@ -0,0 +1,104 @@
+// display module
+// the display mode of the game window
+use bevy::prelude::*;
+use bevy::window::{WindowMode, Windows};
+use bevy::winit::WinitWindows;
+use winit::window::Fullscreen;
+
+use crate::SETTINGS_PATH;
+use crate::config::Settings;
+use crate::control::{InputState, KeyBindings};
+
+// display mode
+// how the game window is shown
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DisplayMode {
+    // a normal window with a border
+    Windowed,
+    // a window without a border, covering the whole monitor
+    Borderless,
+    // exclusive fullscreen, at the monitor's largest video mode
+    Fullscreen,
+}
+
+impl DisplayMode {
+    // gives the display mode with the given name, as written in the settings file
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "windowed" => Some(DisplayMode::Windowed),
+            "borderless" => Some(DisplayMode::Borderless),
+            "fullscreen" => Some(DisplayMode::Fullscreen),
+            _ => None,
+        }
+    }
+    // gives the name of the display mode, as written in the settings file
+    pub fn name(&self) -> &'static str {
+        match self {
+            DisplayMode::Windowed => "windowed",
+            DisplayMode::Borderless => "borderless",
+            DisplayMode::Fullscreen => "fullscreen",
+        }
+    }
+    // gives the display mode the toggle switches to next
+    pub fn next(&self) -> Self {
+        match self {
+            DisplayMode::Windowed => DisplayMode::Borderless,
+            DisplayMode::Borderless => DisplayMode::Fullscreen,
+            DisplayMode::Fullscreen => DisplayMode::Windowed,
+        }
+    }
+    // gives the bevy window mode to start the window in
+    pub fn window_mode(&self) -> WindowMode {
+        match self {
+            DisplayMode::Windowed => WindowMode::Windowed,
+            DisplayMode::Borderless => WindowMode::BorderlessFullscreen,
+            DisplayMode::Fullscreen => WindowMode::Fullscreen { use_size: false },
+        }
+    }
+}
+
+// display plugin
+// responsible for switching between windowed, borderless and exclusive fullscreen while the game runs
+// the screen resource follows the window's new size, so clicks and sprites keep lining up
+pub struct DisplayPlugin;
+
+// implementation of the plugin trait,
+// required for this to be used as a plugin
+impl Plugin for DisplayPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        // the settings are read in by main, to start the window in the right mode
+        if app.resources().get::<Settings>().is_none() {
+            app.add_resource(Settings::load(SETTINGS_PATH));
+        }
+        app.add_system(display_mode_system.system());
+    }
+}
+
+// display mode system
+// switches to the next display mode when alt and the display mode key are pressed together, and saves the choice
+pub fn display_mode_system(inputs: Res<InputState>, bindings: Res<KeyBindings>, mut settings: ResMut<Settings>, windows: Res<Windows>, winit_windows: Res<WinitWindows>) {
+    if !inputs.key_just_presses.contains(&bindings.display_mode)
+        || !bindings.display_modifiers.iter().any(|key| inputs.key_presses.contains(key)) {
+        return;
+    }
+    let window = match windows.get_primary().and_then(|window| winit_windows.get_window(window.id)) {
+        Some(window) => window,
+        None => return,
+    };
+
+    settings.display_mode = settings.display_mode.next();
+    let monitor = window.current_monitor();
+    match settings.display_mode {
+        DisplayMode::Windowed => window.set_fullscreen(None),
+        DisplayMode::Borderless => window.set_fullscreen(Some(Fullscreen::Borderless(monitor))),
+        DisplayMode::Fullscreen => {
+            // use the biggest video mode the monitor has, falling back to borderless if it has none
+            let mode = monitor.video_modes().max_by_key(|mode| (mode.size().width * mode.size().height, mode.refresh_rate()));
+            match mode {
+                Some(mode) => window.set_fullscreen(Some(Fullscreen::Exclusive(mode))),
+                None => window.set_fullscreen(Some(Fullscreen::Borderless(monitor))),
+            }
+        },
+    }
+    settings.save(SETTINGS_PATH);
+}
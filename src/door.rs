@@ -0,0 +1,268 @@
+// door module
+// doors, keys and lockpicking
+use bevy::prelude::*;
+use bevy_rapier2d::physics::RigidBodyHandleComponent;
+use bevy_rapier2d::rapier::dynamics::{JointSet, RigidBodyBuilder, RigidBodySet};
+use bevy_rapier2d::rapier::geometry::{ColliderBuilder, ColliderSet};
+
+use crate::{Id, Position};
+use crate::actions::InteractEvent;
+use crate::behaviour::{Pathfinder, TilePos};
+use crate::collision::get_wall_collision_groups;
+use crate::combat::BlastEvent;
+use crate::config::GameConfig;
+use crate::draw::get_translate_from_position;
+use crate::map::MapData;
+use crate::person::{Controlled, SimpleRect};
+use crate::tick::AddTickSystem;
+
+// door plugin
+// responsible for doors and gates, which block the way while closed
+// squad members open and close them by interacting with them, and locked doors need a key or a lockpicker
+pub struct DoorPlugin;
+
+// implementation of the plugin trait,
+// required for this to be used as a plugin
+impl Plugin for DoorPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        // initialise the interact event state resource
+        app.init_resource::<DoorInteractState>()
+        // add in the system opening and closing doors
+        .add_tick_system(door_interact_system.system())
+        // add in the system keeping the map and physics world up to date with the doors
+        .add_tick_system(door_state_system.system())
+        // add in the system breaking doors caught in blasts
+        .init_resource::<DoorBlastState>()
+        .add_tick_system(blast_door_system.system())
+        // add in the system picking up keys
+        .add_tick_system(key_pickup_system.system());
+    }
+}
+
+// door component
+// spawn this component along with a door or gate, which covers every tile under it
+pub struct Door {
+    pub open: bool,
+    // the key needed to open the door, if it's locked
+    pub key: Option<String>,
+    pub size: (f32, f32),
+    // the tiles the door covers
+    pub tiles: Vec<TilePos>,
+    // the static bodies blocking the door while it's closed
+    pub walls: Vec<Entity>,
+    // whether the door was open the last time the map and physics world were updated, if they have been
+    pub applied: Option<bool>,
+    // whether the door has been blown off its hinges, after which it's gone for good
+    pub broken: bool,
+}
+
+// keyring component
+// spawn this component along with a squad member carrying keys, holding the names of the keys
+#[derive(Default)]
+pub struct Keyring(pub Vec<String>);
+
+// lockpicker component
+// spawn this component along with a squad member who can open locked doors without the key
+pub struct Lockpicker;
+
+// key component
+// spawn this component along with a key lying on the ground, waiting to be picked up
+pub struct Key {
+    pub name: String,
+}
+
+// gives whether a squad member can open a door locked with the given key
+pub fn can_unlock(keyring: Option<&Keyring>, lockpicker: Option<&Lockpicker>, key: &str) -> bool {
+    lockpicker.is_some() || keyring.map_or(false, |keyring| keyring.0.iter().any(|k| k == key))
+}
+
+// spawns a closed door covering the given area
+pub fn spawn_door(config: &GameConfig, commands: &mut Commands, materials: &mut ResMut<Assets<ColorMaterial>>, center: (f32, f32), size: (f32, f32), key: Option<String>) {
+    // the door covers every tile under it
+    let start = TilePos::from_coords(config, (center.0 - size.0 / 2.0 + 1.0).max(0.0), (center.1 - size.1 / 2.0 + 1.0).max(0.0));
+    let end = TilePos::from_coords(config, (center.0 + size.0 / 2.0 - 1.0).max(0.0), (center.1 + size.1 / 2.0 - 1.0).max(0.0));
+    let mut tiles = Vec::new();
+    for y in start.1..=end.1 {
+        for x in start.0..=end.0 {
+            tiles.push(TilePos(x, y));
+        }
+    }
+    // locked doors are a darker colour
+    let color = if key.is_some() { Color::rgb(0.35, 0.2, 0.1) } else { Color::rgb(0.6, 0.4, 0.2) };
+    let material = materials.add(color.into());
+    commands
+        .spawn(SimpleRect::new(material, Vec2::new(size.0, size.1)))
+        .with(Id::new())
+        .with(Position(center.0, center.1))
+        .with(Door {
+            open: false,
+            key: key,
+            size: size,
+            tiles: tiles,
+            walls: Vec::new(),
+            applied: None,
+            broken: false,
+        });
+}
+
+// spawns a key on the ground
+pub fn spawn_key(commands: &mut Commands, materials: &mut ResMut<Assets<ColorMaterial>>, name: String, position: (f32, f32)) {
+    let material = materials.add(Color::rgb(1.0, 0.85, 0.0).into());
+    commands
+        .spawn(SimpleRect::new(material, Vec2::new(4.0, 4.0)))
+        .with(Position(position.0, position.1))
+        .with(Key { name: name });
+}
+
+// door interact state holds an event reader for interact events
+#[derive(Default)]
+pub struct DoorInteractState {
+    pub event_reader: EventReader<InteractEvent>,
+}
+
+// door interact system
+// opens or closes a door once a squad member has finished interacting with it
+// locked doors only open for squad members with the key, or who can pick the lock
+pub fn door_interact_system(mut state: ResMut<DoorInteractState>, events: Res<Events<InteractEvent>>,
+    mut squad: Query<(&Id, &Controlled, Option<&Keyring>, Option<&Lockpicker>)>, mut doors: Query<(&Id, &mut Door)>) {
+    for event in state.event_reader.iter(&events) {
+        let mut unlocks = None;
+        for (id, _controlled, keyring, lockpicker) in &mut squad.iter() {
+            if id.id() == event.actor_id {
+                unlocks = Some((keyring.map(|keyring| keyring.0.clone()).unwrap_or_default(), lockpicker.is_some()));
+                break;
+            }
+        }
+        let (keys, lockpicker) = match unlocks {
+            Some(unlocks) => unlocks,
+            None => continue,
+        };
+        for (id, mut door) in &mut doors.iter() {
+            if id.id() != event.target_id {
+                continue;
+            }
+            let unlocked = match &door.key {
+                Some(key) => lockpicker || keys.contains(key),
+                None => true,
+            };
+            if unlocked {
+                door.open = !door.open;
+            }
+        }
+    }
+}
+
+// door blast state holds an event reader for blast events
+#[derive(Default)]
+pub struct DoorBlastState {
+    pub event_reader: EventReader<BlastEvent>,
+}
+
+// blast door system
+// breaks every door caught in a blast, leaving the way through open for good
+pub fn blast_door_system(config: Res<GameConfig>, mut state: ResMut<DoorBlastState>, events: Res<Events<BlastEvent>>, mut doors: Query<&mut Door>) {
+    for event in state.event_reader.iter(&events) {
+        for mut door in &mut doors.iter() {
+            let reached = door.tiles.iter().any(|tile| {
+                let (x, y) = tile.to_coords(&config);
+                (x - event.origin.0).powi(2) + (y - event.origin.1).powi(2) <= event.radius * event.radius
+            });
+            if reached {
+                door.open = true;
+                door.broken = true;
+            }
+        }
+    }
+}
+
+// door state system
+// blocks off the tiles and puts up static bodies under closed doors, and clears them away under open ones,
+// as soon as a door changes
+// a door can't close on anyone standing in it, so stays open until they're out of the way
+// anyone whose path ran through a door that's just closed finds a new way round
+// broken doors are taken away once the way through is clear
+pub fn door_state_system(mut commands: Commands, config: Res<GameConfig>, mut map: ResMut<MapData>, mut materials: ResMut<Assets<ColorMaterial>>,
+    mut bodies: ResMut<RigidBodySet>, mut colliders: ResMut<ColliderSet>, mut joints: ResMut<JointSet>,
+    mut doors: Query<(Entity, &mut Door, &Handle<ColorMaterial>)>, mut walls: Query<&RigidBodyHandleComponent>, mut pathfinders: Query<&mut Pathfinder>) {
+    // the map has to be generated before the door can be added to it
+    if !map.generated {
+        return;
+    }
+    for (entity, mut door, handle) in &mut doors.iter() {
+        if door.applied == Some(door.open) && !door.broken {
+            continue;
+        }
+        if door.open {
+            // clear the way
+            for wall in door.walls.drain(..) {
+                if let Ok(body) = walls.get::<RigidBodyHandleComponent>(wall) {
+                    bodies.remove(body.handle(), &mut colliders, &mut joints);
+                }
+                commands.despawn(wall);
+            }
+            for tile in door.tiles.iter() {
+                map.set_tile_closed(tile, false);
+            }
+            if door.broken {
+                commands.despawn(entity);
+                continue;
+            }
+        }else{
+            if door.tiles.iter().any(|tile| map.is_tile_occupied(tile)) {
+                door.open = true;
+                continue;
+            }
+            // block the way
+            let mut new_walls = Vec::new();
+            for tile in door.tiles.iter() {
+                map.set_tile_closed(tile, true);
+                let (x, y) = tile.to_coords(&config);
+                let translation = get_translate_from_position(&config, x, y);
+                commands.spawn((
+                    RigidBodyBuilder::new_static().translation(translation.0, translation.1),
+                    ColliderBuilder::cuboid(config.tile_size / 2.0, config.tile_size / 2.0)
+                        .collision_groups(get_wall_collision_groups()),
+                ));
+                if let Some(wall) = commands.current_entity() {
+                    new_walls.push(wall);
+                }
+            }
+            door.walls = new_walls;
+            // find new paths for anyone who was going to walk through
+            for mut pf in &mut pathfinders.iter() {
+                let remaining = pf.tile_path.iter().skip(pf.path_index).any(|tile| door.tiles.contains(tile));
+                if remaining {
+                    pf.needs_pathfinding = true;
+                }
+            }
+        }
+        // open doors fade out so the way through can be seen
+        if let Some(material) = materials.get_mut(&handle) {
+            material.color.a = if door.open { 0.3 } else { 1.0 };
+        }
+        door.applied = Some(door.open);
+    }
+}
+
+// key pickup system
+// squad members pick up any key they walk over
+pub fn key_pickup_system(mut commands: Commands, config: Res<GameConfig>, mut keys: Query<(Entity, &Key, &Position)>,
+    mut squad: Query<(Entity, &Controlled, &Position, Option<&mut Keyring>)>) {
+    for (key_entity, key, key_pos) in &mut keys.iter() {
+        for (entity, _controlled, pos, keyring) in &mut squad.iter() {
+            if Vec2::new(key_pos.0 - pos.0, key_pos.1 - pos.1).length() > config.tile_size {
+                continue;
+            }
+            match keyring {
+                Some(mut keyring) => {
+                    keyring.0.push(key.name.clone());
+                },
+                None => {
+                    commands.insert_one(entity, Keyring(vec![key.name.clone()]));
+                },
+            }
+            commands.despawn(key_entity);
+            break;
+        }
+    }
+}
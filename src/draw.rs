@@ -0,0 +1,276 @@
+// draw module
+// the screen, camera and ui scale, and drawing everything at its position
+use bevy::prelude::*;
+use bevy::window::WindowResized;
+
+use crate::{CAMERA_SCROLL_SPEED, Position};
+use crate::config::{GameConfig, Settings};
+use crate::control::{InputState, KeyBindings};
+use crate::tick::{get_interpolated_position, PreviousPosition, SimulationClock};
+
+// draw moving plugin
+// this plugin updates everything drawable to their correct positions
+// drawing itself happens within the bevy engine
+pub struct DrawMovingPlugin;
+
+// implementation of the plugin trait,
+// required for this to be used as a plugin
+impl Plugin for DrawMovingPlugin {
+    fn build(&self, app: &mut AppBuilder){
+        // the window starts out at the size given in the config, with the ui scaled as the settings say
+        let config = app.resources().get::<GameConfig>().map(|config| config.clone()).unwrap_or_default();
+        let ui_setting = app.resources().get::<Settings>().map_or(1.0, |settings| settings.ui_scale);
+        // add in the screen resource, kept up to date with the size of the window
+        app.add_resource(Screen::new(&config, ui_setting))
+            .init_resource::<ScreenState>()
+            .init_resource::<UiScale>()
+            // add in the window resize system, before anything that draws or reads the mouse
+            .add_system(window_resize_system.system())
+            // zoom the camera so the world fills the window
+            .add_system(camera_scale_system.system())
+            // scale the ui to the window, picking up new ui nodes as they're spawned
+            .add_system(ui_base_system.system())
+            .add_system(ui_scale_system.system())
+            // things are drawn between ticks of the simulation clock, if there is anything driving it
+            .init_resource::<SimulationClock>()
+            // add in the draw text system
+            .add_system(draw_text_system.system())
+            .add_system(draw_sprite_system.system());
+    }
+}
+
+// screen resource
+// the current size of the window, which can differ from the size of the view of the world once the window is resized
+// the view is zoomed to fit the window, keeping its shape, and sits in the middle of it
+// maps bigger than the view are scrolled around, with the camera kept over the map
+pub struct Screen {
+    pub width: f32,
+    pub height: f32,
+    // the size of the view of the world, from the window size in the config
+    pub world_width: f32,
+    pub world_height: f32,
+    // the size of the whole map, from the config
+    pub map_width: f32,
+    pub map_height: f32,
+    // the position in the middle of the view
+    pub camera: (f32, f32),
+    // the player's own ui scale, from the settings, on top of the scale from the window size
+    pub ui_setting: f32,
+}
+
+impl Screen {
+    pub fn new(config: &GameConfig, ui_setting: f32) -> Self {
+        let mut screen = Screen {
+            width: config.window_width,
+            height: config.window_height,
+            world_width: config.window_width,
+            world_height: config.window_height,
+            map_width: config.map_width,
+            map_height: config.map_height,
+            camera: (0.0, 0.0),
+            ui_setting: ui_setting,
+        };
+        // the camera starts out over the middle of the map
+        screen.scroll_to((config.map_width / 2.0, config.map_height / 2.0));
+        screen
+    }
+    // moves the view so it's centred on the given position, as far as it can without leaving the map
+    // maps smaller than the view stay in the middle of it
+    pub fn scroll_to(&mut self, position: (f32, f32)) {
+        let clamp = |center: f32, view: f32, map: f32| if map <= view { map / 2.0 } else { center.max(view / 2.0).min(map - view / 2.0) };
+        self.camera = (clamp(position.0, self.world_width, self.map_width), clamp(position.1, self.world_height, self.map_height));
+    }
+    // gives how many pixels on the screen each unit of the world takes up
+    pub fn world_scale(&self) -> f32 {
+        (self.width / self.world_width).min(self.height / self.world_height)
+    }
+    // gives how much bigger the ui is drawn than it was laid out
+    pub fn ui_scale(&self) -> f32 {
+        self.world_scale() * self.ui_setting
+    }
+    // gives the position coordinates under the cursor
+    // the cursor is given from the bottom left of the window, and the camera is centered
+    // on the middle of the view
+    pub fn position_from_cursor(&self, cursor: (f32, f32)) -> (f32, f32) {
+        let scale = self.world_scale();
+        ((cursor.0 - self.width / 2.0) / scale + self.camera.0, self.camera.1 - (cursor.1 - self.height / 2.0) / scale)
+    }
+    // gives where on the screen a position is, from the top left of the window, for placing ui
+    pub fn position_to_screen(&self, position: (f32, f32)) -> (f32, f32) {
+        let scale = self.world_scale();
+        ((position.0 - self.camera.0) * scale + self.width / 2.0, (position.1 - self.camera.1) * scale + self.height / 2.0)
+    }
+}
+
+// screenstate holds an event reader for window resize events
+#[derive(Default)]
+pub struct ScreenState {
+    pub event_reader: EventReader<WindowResized>,
+}
+
+// window resize system
+// keeps the screen resource up to date with the size of the window
+pub fn window_resize_system(mut screen: ResMut<Screen>, mut state: ResMut<ScreenState>, resize_events: Res<Events<WindowResized>>) {
+    for event in state.event_reader.iter(&resize_events) {
+        screen.width = event.width as f32;
+        screen.height = event.height as f32;
+    }
+}
+
+// world camera component
+// marks the camera looking at the world, as opposed to the ui camera
+pub struct WorldCamera;
+
+// camera scale system
+// zooms the world camera so the whole view fits in the window, and moves it to where the view has been scrolled
+pub fn camera_scale_system(config: Res<GameConfig>, screen: Res<Screen>, mut query: Query<(&WorldCamera, &mut Scale, &mut Translation)>) {
+    let zoom = 1.0 / screen.world_scale();
+    let (x, y) = get_translate_from_position(&config, screen.camera.0, screen.camera.1);
+    for (_camera, mut scale, mut translation) in &mut query.iter() {
+        if scale.0 != zoom {
+            scale.0 = zoom;
+        }
+        if translation.0[0] != x || translation.0[1] != y {
+            translation.0 = Vec3::new(x, y, translation.0[2]);
+        }
+    }
+}
+
+// camera scroll system
+// scrolls the view around the map while the camera scroll keys are held
+pub fn camera_scroll_system(time: Res<Time>, inputs: Res<InputState>, bindings: Res<KeyBindings>, mut screen: ResMut<Screen>) {
+    let mut direction = (0.0, 0.0);
+    for (key, step) in bindings.camera_scroll.iter().zip([(0.0, -1.0), (0.0, 1.0), (-1.0, 0.0), (1.0, 0.0)].iter()) {
+        if inputs.key_presses.contains(key) {
+            direction.0 += step.0;
+            direction.1 += step.1;
+        }
+    }
+    if direction != (0.0, 0.0) {
+        let camera = screen.camera;
+        screen.scroll_to((camera.0 + direction.0 * CAMERA_SCROLL_SPEED * time.delta_seconds, camera.1 + direction.1 * CAMERA_SCROLL_SPEED * time.delta_seconds));
+    }
+}
+
+// ui base component
+// the layout a ui node was spawned with, before it was scaled to the window
+pub struct UiBase {
+    pub style: Style,
+    pub font_size: Option<f32>,
+}
+
+// ui scale resource
+// the scale the ui was last laid out at
+pub struct UiScale(pub f32);
+
+impl Default for UiScale {
+    fn default() -> Self {
+        UiScale(1.0)
+    }
+}
+
+// scales the pixel sizes in a layout, leaving percentages and automatic sizes alone
+pub fn scale_val(val: Val, scale: f32) -> Val {
+    match val {
+        Val::Px(px) => Val::Px(px * scale),
+        other => other,
+    }
+}
+
+pub fn scale_rect(rect: &Rect<Val>, scale: f32) -> Rect<Val> {
+    Rect {
+        left: scale_val(rect.left, scale),
+        right: scale_val(rect.right, scale),
+        top: scale_val(rect.top, scale),
+        bottom: scale_val(rect.bottom, scale),
+    }
+}
+
+// lays out a ui node at the given scale, from the layout it was spawned with
+// nodes placed by position are moved by the draw text system instead
+pub fn apply_ui_scale(base: &UiBase, style: &mut Style, text: Option<&mut Text>, positioned: bool, scale: f32) {
+    if !positioned {
+        style.position = scale_rect(&base.style.position, scale);
+    }
+    style.margin = scale_rect(&base.style.margin, scale);
+    style.padding = scale_rect(&base.style.padding, scale);
+    style.size = bevy::math::Size::new(scale_val(base.style.size.width, scale), scale_val(base.style.size.height, scale));
+    if let (Some(text), Some(font_size)) = (text, base.font_size) {
+        text.style.font_size = font_size * scale;
+    }
+}
+
+// ui base system
+// remembers the layout of newly spawned ui nodes, then scales them to the window straight away
+pub fn ui_base_system(mut commands: Commands, screen: Res<Screen>,
+    mut query: Query<Without<UiBase, (Entity, &mut Style, Option<&mut Text>, Option<&Position>)>>) {
+    let scale = screen.ui_scale();
+    for (entity, mut style, mut text, position) in &mut query.iter() {
+        let base = UiBase {
+            style: style.clone(),
+            font_size: text.as_ref().map(|text| text.style.font_size),
+        };
+        apply_ui_scale(&base, &mut style, text.as_mut().map(|text| &mut **text), position.is_some(), scale);
+        commands.insert_one(entity, base);
+    }
+}
+
+// ui scale system
+// lays out every ui node again whenever the window changes size
+pub fn ui_scale_system(screen: Res<Screen>, mut last: ResMut<UiScale>,
+    mut query: Query<(&UiBase, &mut Style, Option<&mut Text>, Option<&Position>)>) {
+    let scale = screen.ui_scale();
+    if scale == last.0 {
+        return;
+    }
+    last.0 = scale;
+    for (base, mut style, mut text, position) in &mut query.iter() {
+        apply_ui_scale(base, &mut style, text.as_mut().map(|text| &mut **text), position.is_some(), scale);
+    }
+}
+
+// draw text system
+// this function goes through all entities with text, style, and position components
+// and updates the style component to reflect the correct position of the entity
+// note that this only happens when any of those position components are changed
+pub fn draw_text_system(screen: Res<Screen>, clock: Res<SimulationClock>, mut query: Query<(&Text, &mut Style, &Position, Option<&PreviousPosition>)>){
+    for (_text, mut style, pos, previous) in &mut query.iter() {
+        // update the style component to have the correct position on the screen
+        let (left, top) = screen.position_to_screen(get_interpolated_position(&pos, previous, clock.alpha));
+        style.position.left = Val::Px(left);
+        style.position.top = Val::Px(top);
+    }
+}
+
+// function to get the correct translation coordinates from a given position
+pub fn get_translate_from_position(config: &GameConfig, x: f32, y: f32) -> (f32, f32) {
+    // translation has (0, 0) at the center of the screen
+    // it also has the y-coordinates increase from bottom to top
+    // we must invert the y-coordinates to use the right scale, then
+    // we must shift the position coordinates towards the 
+    // upper left corner of the screen by half the 
+    // screen dimensions
+    (x - config.map_width / 2.0, (config.map_height - y) - config.map_height / 2.0)
+}
+
+// function to get the correct position coordinates from a given translation
+// this is the inverse of get_translate_from_position
+pub fn get_position_from_translate(config: &GameConfig, x: f32, y: f32) -> (f32, f32) {
+    (x + config.map_width / 2.0, config.map_height / 2.0 - y)
+}
+
+// draw sprite system
+// responsible for moving sprites to their proper positions for 
+// display
+// things are drawn part of the way between ticks, so movement stays smooth at any framerate
+pub fn draw_sprite_system(config: Res<GameConfig>, clock: Res<SimulationClock>, mut query: Query<(&Sprite, &mut Translation, &Position, Option<&PreviousPosition>)>){
+    for (_sprite, mut transl, pos, previous) in &mut query.iter() {
+        
+        // get the proper coordinates for translation
+        let drawn = get_interpolated_position(&pos, previous, clock.alpha);
+        let adj_pos = get_translate_from_position(&config, drawn.0, drawn.1);
+
+        // assign coordinates
+        transl.0 = Vec3::new(adj_pos.0, adj_pos.1, transl.0[2]);
+    }
+}
@@ -0,0 +1,141 @@
+// elevation module
+// the high ground
+use bevy::prelude::*;
+use std::collections::HashMap;
+use bevy_rapier2d::rapier::dynamics::RigidBodySet;
+use bevy_rapier2d::rapier::geometry::ColliderSet;
+use bevy_rapier2d::rapier::pipeline::QueryPipeline;
+use pathfinding::prelude::astar;
+
+use crate::{
+    BEHAVIOUR_STAGE, combat_query, HIGH_GROUND_HEIGHT, HIGH_GROUND_RANGE, Id, Position,
+    VANTAGE_PATHS, VANTAGE_SEARCH_RADIUS, WEAPON_RANGE,
+};
+use crate::behaviour::{Behaviour, BehaviourType, TilePos};
+use crate::config::GameConfig;
+use crate::map::MapData;
+use crate::nerve::{Action, ActionType, Nerve};
+use crate::person::{Controlled, Person};
+use crate::tick::AddTickSystem;
+
+// elevation plugin
+// responsible for the high ground, which attacks reach further and hit more often from,
+// see also the map data, where cliffs cut off tiles too far apart in height from each other
+pub struct ElevationPlugin;
+
+// implementation of the plugin trait,
+// required for this to be used as a plugin
+impl Plugin for ElevationPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        // add in the system keeping track of how high up everyone is
+        app.add_tick_system_to_stage(BEHAVIOUR_STAGE, elevation_system.system())
+        // add in the system sending anyone taking the vantage up to the high ground
+        .add_tick_system_to_stage(BEHAVIOUR_STAGE, vantage_system.system());
+    }
+}
+
+// elevation component
+// how high up someone is standing, kept up to date with the height of the tile they're on
+pub struct Elevation(pub f32);
+
+// gives whether an attacker at the one height has the high ground on a target at the other
+pub fn has_high_ground(attacker: f32, target: f32) -> bool {
+    attacker - target >= HIGH_GROUND_HEIGHT
+}
+
+// elevation system
+// gives everyone the height of the tile they're standing on
+pub fn elevation_system(mut commands: Commands, config: Res<GameConfig>, map: Res<MapData>, mut query: Query<(Entity, &Person, &Position, Option<&mut Elevation>)>) {
+    for (entity, _pers, pos, elevation) in &mut query.iter() {
+        let height = if pos.0 >= 0.0 && pos.1 >= 0.0 { map.get_height(&TilePos::from_coords(&config, pos.0, pos.1)) } else { 0.0 };
+        match elevation {
+            Some(mut elevation) => elevation.0 = height,
+            None => commands.insert_one(entity, Elevation(height)),
+        }
+    }
+}
+
+// vantage system
+// sends anyone taking the vantage up to the highest tile nearby overlooking who they're attacking,
+// which the attack can be launched from with nothing in the way, picking the attack back up once they get there
+// only one search is made for each attack, so anyone with nowhere better to go just keeps on attacking
+// the squad only go where they're told
+pub fn vantage_system(config: Res<GameConfig>, map: Res<MapData>, pipeline: Res<QueryPipeline>, bodies: Res<RigidBodySet>, colliders: Res<ColliderSet>,
+    mut targets: Query<(&Id, &Position)>, mut query: Query<(&Behaviour, &mut Nerve, &Position, Option<&Controlled>)>) {
+    let mut positions = HashMap::new();
+    for (id, pos) in &mut targets.iter() {
+        positions.insert(id.id(), (pos.0, pos.1));
+    }
+
+    for (behav, mut actions, pos, controlled) in &mut query.iter() {
+        if controlled.is_some() || behav.current_behaviour != BehaviourType::Vantage {
+            continue;
+        }
+        let attack = actions.current_action.clone();
+        let target_pos = match (attack.action_type, &attack.target.1) {
+            (ActionType::Attack, Some(target_id)) => match positions.get(target_id) {
+                Some(&target_pos) => target_pos,
+                None => continue,
+            },
+            _ => continue,
+        };
+        let mut params = attack.params.clone().unwrap_or_default();
+        if params.contains_key("vantage") || pos.0 < 0.0 || pos.1 < 0.0 || target_pos.0 < 0.0 || target_pos.1 < 0.0 {
+            continue;
+        }
+        params.insert("vantage".to_string(), 1.0);
+        actions.current_action.params = Some(params.clone());
+
+        // the attack reaches further from up there
+        let range = params.get("range").cloned().unwrap_or(WEAPON_RANGE) * HIGH_GROUND_RANGE;
+        let min_range = params.get("min_range").cloned().unwrap_or(0.0);
+        let start = TilePos::from_coords(&config, pos.0, pos.1);
+        let target_height = map.get_height(&TilePos::from_coords(&config, target_pos.0, target_pos.1));
+
+        // only free tiles higher up than here, overlooking the target and in range of it, are worth the climb
+        let mut candidates = Vec::new();
+        let reach = (VANTAGE_SEARCH_RADIUS / config.tile_size) as i32;
+        for j in -reach..reach + 1 {
+            for i in -reach..reach + 1 {
+                let x = start.0 as i32 + i;
+                let y = start.1 as i32 + j;
+                if x < 0 || y < 0 || !map.contains(&TilePos(x as usize, y as usize)) {
+                    continue;
+                }
+                let tile = TilePos(x as usize, y as usize);
+                let height = map.get_height(&tile);
+                if height <= map.get_height(&start) || !has_high_ground(height, target_height)
+                    || map.is_tile_blocked(&tile) || map.is_tile_occupied(&tile) {
+                    continue;
+                }
+                let coords = tile.to_coords(&config);
+                let climb = Vec2::new(coords.0 - pos.0, coords.1 - pos.1).length();
+                let dist = Vec2::new(target_pos.0 - coords.0, target_pos.1 - coords.1).length();
+                if climb <= VANTAGE_SEARCH_RADIUS && dist <= range && dist >= min_range {
+                    candidates.push((tile, height, climb));
+                }
+            }
+        }
+
+        // the highest first, then the closest, taking the first that can be seen from and walked up to
+        candidates.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap().then(a.2.partial_cmp(&b.2).unwrap()));
+        let path = candidates.iter()
+            .filter(|(tile, _, _)| combat_query::line_of_sight(&config, &pipeline, &bodies, &colliders, tile.to_coords(&config), target_pos))
+            .take(VANTAGE_PATHS)
+            .find_map(|(tile, _, _)| astar(&start, |p| map.successors(p, false), |p| map.get_diag_dist(*p, *tile), |p| p == tile));
+        if let Some((path, _cost)) = path {
+            let attack = actions.current_action.clone();
+            let moves = path.into_iter().skip(1).map(|tile| {
+                let mut params = HashMap::new();
+                params.insert("range".to_string(), config.tile_size);
+                Action {
+                    action_type: ActionType::Move,
+                    target: (Some(tile.to_coords(&config)), None),
+                    params: Some(params),
+                    group: None,
+                }
+            });
+            actions.replace_all(moves.chain(std::iter::once(attack)));
+        }
+    }
+}
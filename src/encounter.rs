@@ -0,0 +1,1503 @@
+// encounter module
+// encounters, waves, reinforcements, alarms, archetypes and bosses
+use bevy::prelude::*;
+use std::collections::{VecDeque, HashMap, HashSet};
+use std::fs;
+use std::str::FromStr;
+use bevy_rapier2d::rapier::dynamics::RigidBodySet;
+use bevy_rapier2d::rapier::geometry::ColliderSet;
+use bevy_rapier2d::rapier::pipeline::QueryPipeline;
+
+use crate::{
+    ALARM_RAISE_DURATION, ALARM_RUN_DISTANCE, AMBUSH_RADIUS, AMBUSH_SPOT_RADIUS, ARCHETYPES_PATH,
+    BASE_MOVE_SPEED, CIVILIAN_HEALTH, combat_query, DIFFICULTY_LOSS_EASING,
+    DIFFICULTY_OVERTIME_EASING, DIFFICULTY_PAR_TIME, HOSTILE_GROUP_PREFIX, Id, Position, Size,
+    SPAWN_TELEGRAPH_DURATION, Velocity,
+};
+use crate::actions::InteractEvent;
+use crate::animation::get_hostile_sprite_template;
+use crate::behaviour::{Behaviour, BehaviourType, Pathfinder, PatrolRoute, TilePos};
+use crate::civilian::Civilian;
+use crate::collision::get_person_body_components;
+use crate::combat::{DamageType, DeathEvent, Health, Resistances, StatusEffect, StatusKind};
+use crate::config::{GameConfig, LaunchOptions};
+use crate::control::{get_attack_actions, Holstered, MoveSpeed, Weapon};
+use crate::door::{spawn_door, spawn_key};
+use crate::lighting::WorldClock;
+use crate::locale::Locale;
+use crate::map::MapData;
+use crate::mods::ModAssets;
+use crate::nerve::{Action, ActionType, Nerve};
+use crate::palette::OwnColor;
+use crate::perception::{Alert, NoiseReaction, Perception};
+use crate::person::{AttitudeType, Controlled, Person, SimpleRect};
+use crate::routine::{Routine, RoutineActivity};
+use crate::stamina::Stamina;
+use crate::surrender::Morale;
+use crate::tick::AddTickSystem;
+use crate::weather::{Weather, WeatherKind};
+use crate::zone::{spawn_zone, StartPoint, Zone, ZoneEvent, ZoneKind};
+
+// encounter plugin
+// responsible for generating encounters for the player
+pub struct EncounterPlugin;
+
+// implementation of the plugin trait,
+// required for this to be used as a plugin
+impl Plugin for EncounterPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        // load in the hostile archetypes, allowing mods to replace them
+        let path = app.resources().get::<ModAssets>().map_or(ARCHETYPES_PATH.to_string(), |mods| mods.resolve(ARCHETYPES_PATH));
+        app.add_resource(Archetypes::load(&path));
+        // set the difficulty up from the launch options
+        let difficulty = app.resources().get::<LaunchOptions>().map_or(Difficulty::default(), |launch| Difficulty::new(launch.difficulty, launch.adaptive_difficulty));
+        app.add_resource(difficulty)
+        .init_resource::<DifficultyDeathState>()
+        // add in the system keeping track of how the squad is doing, for adaptive difficulty
+        .add_tick_system(difficulty_system.system())
+        // initialise the wave scheduler, filled in from the encounter file
+        .init_resource::<WaveScheduler>()
+        // initialise the reinforcements brought in by alarms, also filled in from the encounter file
+        .init_resource::<Reinforcements>()
+        .init_resource::<AlarmZoneEventState>()
+        // add in add hostiles start up system
+        .add_startup_system(add_hostiles.system())
+        // add in the wave counter
+        .add_startup_system(setup_wave_counter_system.system())
+        // add in the system spawning the waves
+        .add_tick_system(wave_system.system())
+        // add in the system calling reinforcements when an alarm is raised
+        .add_tick_system(alarm_system.system())
+        // add in the systems sending hostiles to raise alarms, and raising them once they get there
+        .init_resource::<InteractEventState>()
+        .add_tick_system(alarm_runner_system.system())
+        .add_tick_system(raise_alarm_system.system())
+        // add in the system springing ambushes
+        .add_tick_system(ambush_system.system())
+        // add in the system waking up stages of the encounter
+        .init_resource::<EncounterStages>()
+        .init_resource::<EncounterZoneEventState>()
+        .add_tick_system(encounter_stage_system.system())
+        // add in the system bringing reinforcements in once their spawn point has been marked
+        .add_tick_system(spawn_telegraph_system.system())
+        // add in the boss health bar
+        .add_startup_system(setup_boss_health_bar_system.system())
+        // add in the system moving bosses through their phases
+        .add_tick_system(boss_phase_system.system())
+        // add in the system showing the boss's health
+        .add_system(boss_health_bar_system.system())
+        // add in the system showing the wave counter
+        .add_system(wave_counter_system.system());
+    }
+}
+
+// add hostiles start up system
+// this function adds in some hostiles
+pub fn add_hostiles(mut commands: Commands, config: Res<GameConfig>, mut materials: ResMut<Assets<ColorMaterial>>, launch: Res<LaunchOptions>,
+    archetypes: Res<Archetypes>, difficulty: Res<Difficulty>, mut scheduler: ResMut<WaveScheduler>, mut reinforcements: ResMut<Reinforcements>,
+    mut stages: ResMut<EncounterStages>, mut clock: ResMut<WorldClock>, mut weather: ResMut<Weather>) {
+    // use the encounter file instead, if one was given
+    if let Some(path) = &launch.encounter_path {
+        scheduler.waves = load_encounter(&config, &mut commands, &mut materials, &archetypes, &difficulty, &mut reinforcements, &mut stages, &mut clock, &mut weather, path);
+        scheduler.total = scheduler.waves.len();
+        return;
+    }
+
+    // a couple of grunts come running if the camp's alarm is raised
+    reinforcements.composition = vec![("grunt".to_string(), 2)];
+
+    // a grunt and a sniper watching over the camp, from the map's first hostile group if it has one
+    let group = format!("{}1", HOSTILE_GROUP_PREFIX);
+    spawn_hostile(&config, &mut commands, &mut materials, &difficulty.adjust(archetypes.get("grunt")), (200.0, 400.0));
+    // the grunt walks the camp's patrol route at night, if the map has one, and stands guard during the day
+    commands.with(Routine::new(RoutineActivity::Rest((200.0, 400.0)), RoutineActivity::Patrol("camp".to_string())))
+        .with(StartPoint::new(&group));
+    spawn_hostile(&config, &mut commands, &mut materials, &difficulty.adjust(archetypes.get("sniper")), (400.0, 200.0));
+    commands.with(StartPoint::new(&group));
+
+    // a few locals going about their day nearby
+    spawn_civilian(&config, &mut commands, &mut materials, (600.0, 300.0));
+    spawn_civilian(&config, &mut commands, &mut materials, (650.0, 350.0));
+    spawn_civilian(&config, &mut commands, &mut materials, (550.0, 380.0));
+
+    // ambush zone around the hostiles, springs when the squad walks in
+    spawn_zone(&config, &mut commands, ZoneKind::Ambush, "hostile camp".to_string(), (300.0, 300.0), (300.0, 250.0));
+    // the camp's alarm, which the hostiles will run for once they spot the squad
+    spawn_alarm(&mut commands, &mut materials, (320.0, 420.0));
+}
+// load encounter
+// spawns in the hostiles and zones listed in an encounter file, and gives back the waves listed in it
+// the encounter file has one entry per line, in the forms
+//   hostile <x> <y> [archetype] [patrol route]
+//   civilian <x> <y>
+//   ambush <x> <y> <archetype> <group> [radius]
+//   zone <ambush|extraction|objective|exit> <name> <x> <y> <width> <height>
+//   wave <after|cleared> <delay> <tile x> <tile y> <composition>
+//   wave <after|cleared> <delay> spawn <composition>
+//   spawn <name> <x> <y>
+//   alarm <x> <y>
+//   door <x> <y> <width> <height> [key]
+//   key <name> <x> <y>
+//   reinforcements <composition>
+//   stage <name>
+//   trigger <name> <x> <y> <width> <height>
+//   trigger <name> tiles <tile x> <tile y> <width in tiles> <height in tiles>
+//   time <hour>
+//   weather <clear|rain|fog>
+// hostiles without an archetype are grunts, and hostiles given a patrol route walk that route
+// from the tiled map while at rest. a wave's composition is a list of archetypes
+// with how many of each, e.g. grunt:3,sniper:1, or just a number of grunts
+// waves given "spawn" instead of a tile come in through the spawn points, which can also be
+// placed in the tiled map. reinforcements come in through the spawn points when an alarm is raised,
+// either by the squad walking into an ambush zone or by a hostile getting to an alarm and setting it off
+// doors start closed, and doors given a key need that key, picked up from where the key line puts it, to be opened
+// ambushers lie hidden until a squad member comes within the radius of any of their group,
+// then the whole group opens fire at once
+// entries after a stage line stay dormant until a squad member walks into that stage's trigger zone,
+// which is placed with a trigger line or as an object of type "encounter" in the tiled map.
+// entries before the first stage line are put in straight away
+// waves come in the order they're listed. an "after" wave comes <delay> seconds after the wave
+// before it arrived, a "cleared" wave comes <delay> seconds after the wave before it was wiped out
+// a time line starts the mission at that hour of the day, e.g. 22.5 for half ten at night,
+// and a weather line sets the weather, which is otherwise rolled at random
+// exit zones take the squad on to the stage of the mission they're named after, once the whole squad is in one,
+// loading that stage's map and encounter in place of this one's
+// lines starting with # are ignored, and lines that can't be read are printed out and skipped
+pub fn load_encounter(config: &GameConfig, commands: &mut Commands, materials: &mut ResMut<Assets<ColorMaterial>>, archetypes: &Archetypes, difficulty: &Difficulty,
+    reinforcements: &mut Reinforcements, stages: &mut EncounterStages, clock: &mut WorldClock, weather: &mut Weather, path: &str) -> VecDeque<Wave> {
+    let mut waves = VecDeque::new();
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(err) => {
+            eprintln!("could not read encounter from {}: {}", path, err);
+            return waves;
+        },
+    };
+    // the stage the entries being read belong to, none until the first stage line
+    let mut stage: Option<String> = None;
+    for line in text.lines().map(|line| line.trim()) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        match parts.as_slice() {
+            // the mission starts at the given time of day
+            ["time", hour] => match parse_field::<f32>(hour, "time") {
+                Ok(hour) => clock.hour = hour % 24.0,
+                Err(err) => eprintln!("bad encounter line: {} ({})", line, err),
+            },
+            // the mission has the given weather, rather than rolling for it
+            ["weather", kind] => match WeatherKind::from_name(kind) {
+                Some(kind) => {
+                    weather.kind = kind;
+                    weather.rolled = true;
+                },
+                None => eprintln!("bad encounter line: {} (weather must be clear, rain or fog)", line),
+            },
+            ["stage", name] => {
+                stage = Some(name.to_string());
+                stages.dormant.entry(name.to_string()).or_insert_with(Vec::new);
+            },
+            // triggers are always put in straight away, otherwise nothing could wake their stage up
+            // the rectangle is given in tiles, from its top left tile
+            ["trigger", name, "tiles", x, y, w, h] => match (parse_field(x, "tile x"), parse_field(y, "tile y"), parse_size(w, h)) {
+                (Ok(tile_x), Ok(tile_y), Ok(size)) => {
+                    let corner = TilePos(tile_x, tile_y);
+                    let size = (size.0 * config.tile_size, size.1 * config.tile_size);
+                    let center = (corner.0 as f32 * config.tile_size + size.0 / 2.0, corner.1 as f32 * config.tile_size + size.1 / 2.0);
+                    spawn_zone(config, commands, ZoneKind::Encounter, name.to_string(), center, size);
+                },
+                (Err(err), _, _) | (_, Err(err), _) | (_, _, Err(err)) => eprintln!("bad encounter line: {} ({})", line, err),
+            },
+            ["trigger", name, x, y, w, h] => match (parse_point(x, y), parse_size(w, h)) {
+                (Ok(center), Ok(size)) => spawn_zone(config, commands, ZoneKind::Encounter, name.to_string(), center, size),
+                (Err(err), _) | (_, Err(err)) => eprintln!("bad encounter line: {} ({})", line, err),
+            },
+            _ => match &stage {
+                // entries in a stage wait until it's triggered
+                Some(stage) => stages.dormant.entry(stage.clone()).or_insert_with(Vec::new).push(line.to_string()),
+                None => match apply_encounter_entry(config, commands, materials, archetypes, difficulty, reinforcements, line) {
+                    Ok(wave) => waves.extend(wave),
+                    Err(err) => eprintln!("bad encounter line: {} ({})", line, err),
+                },
+            },
+        }
+    }
+    waves
+}
+
+// apply encounter entry
+// puts a single entry from an encounter file into the world, giving back the wave if the entry was one
+// everything in the entry is read before anything is put in, so an entry that can't be read puts nothing in
+pub fn apply_encounter_entry(config: &GameConfig, commands: &mut Commands, materials: &mut ResMut<Assets<ColorMaterial>>, archetypes: &Archetypes, difficulty: &Difficulty,
+    reinforcements: &mut Reinforcements, line: &str) -> Result<Option<Wave>, String> {
+    let parts: Vec<&str> = line.split_whitespace().collect();
+    match parts.as_slice() {
+        ["hostile", x, y] => {
+            let position = parse_point(x, y)?;
+            spawn_hostile(config, commands, materials, &difficulty.adjust(archetypes.get("grunt")), position);
+        },
+        ["hostile", x, y, archetype] => {
+            let position = parse_point(x, y)?;
+            spawn_hostile(config, commands, materials, &difficulty.adjust(archetypes.get(archetype)), position);
+        },
+        ["hostile", x, y, archetype, route] => {
+            let position = parse_point(x, y)?;
+            spawn_hostile(config, commands, materials, &difficulty.adjust(archetypes.get(archetype)), position);
+            commands.with(PatrolRoute { name: route.to_string() });
+        },
+        ["ambush", x, y, archetype, group] => {
+            let position = parse_point(x, y)?;
+            spawn_hostile(config, commands, materials, &difficulty.adjust(archetypes.get(archetype)), position);
+            commands.with(Ambusher::new(group.to_string(), AMBUSH_RADIUS));
+        },
+        ["ambush", x, y, archetype, group, radius] => {
+            let position = parse_point(x, y)?;
+            let radius = parse_field(radius, "ambush radius")?;
+            spawn_hostile(config, commands, materials, &difficulty.adjust(archetypes.get(archetype)), position);
+            commands.with(Ambusher::new(group.to_string(), radius));
+        },
+        ["civilian", x, y] => {
+            let position = parse_point(x, y)?;
+            spawn_civilian(config, commands, materials, position);
+        },
+        ["civilian", x, y, home_x, home_y] => {
+            let position = parse_point(x, y)?;
+            let home = (parse_field(home_x, "home x")?, parse_field(home_y, "home y")?);
+            spawn_civilian(config, commands, materials, position);
+            // civilians go about their day, and go home for the night
+            commands.with(Routine::new(RoutineActivity::Wander, RoutineActivity::Rest(home)));
+        },
+        ["garrison", x, y, archetype, route, camp_x, camp_y] => {
+            let position = parse_point(x, y)?;
+            let camp = (parse_field(camp_x, "camp x")?, parse_field(camp_y, "camp y")?);
+            spawn_hostile(config, commands, materials, &difficulty.adjust(archetypes.get(archetype)), position);
+            // the garrison rests at camp during the day, and walks its patrol route at night
+            commands.with(Routine::new(RoutineActivity::Rest(camp), RoutineActivity::Patrol(route.to_string())));
+        },
+        ["zone", kind, name, x, y, w, h] => {
+            let kind = ZoneKind::from_name(kind).ok_or(format!("bad zone kind: {}", kind))?;
+            let center = parse_point(x, y)?;
+            let size = parse_size(w, h)?;
+            spawn_zone(config, commands, kind, name.to_string(), center, size);
+        },
+        ["wave", trigger, delay, x, y, composition] => {
+            return Ok(Some(Wave {
+                trigger: parse_wave_trigger(trigger)?,
+                delay: parse_field(delay, "delay")?,
+                tile: Some(TilePos(parse_field(x, "tile x")?, parse_field(y, "tile y")?)),
+                composition: parse_wave_composition(composition)?,
+            }));
+        },
+        ["wave", trigger, delay, "spawn", composition] => {
+            return Ok(Some(Wave {
+                trigger: parse_wave_trigger(trigger)?,
+                delay: parse_field(delay, "delay")?,
+                tile: None,
+                composition: parse_wave_composition(composition)?,
+            }));
+        },
+        ["spawn", name, x, y] => {
+            let position = parse_point(x, y)?;
+            spawn_spawn_point(commands, name.to_string(), position);
+        },
+        ["door", x, y, w, h] => {
+            let center = parse_point(x, y)?;
+            let size = parse_size(w, h)?;
+            spawn_door(config, commands, materials, center, size, None);
+        },
+        ["door", x, y, w, h, key] => {
+            let center = parse_point(x, y)?;
+            let size = parse_size(w, h)?;
+            spawn_door(config, commands, materials, center, size, Some(key.to_string()));
+        },
+        ["key", name, x, y] => {
+            let position = parse_point(x, y)?;
+            spawn_key(commands, materials, name.to_string(), position);
+        },
+        ["alarm", x, y] => {
+            let position = parse_point(x, y)?;
+            spawn_alarm(commands, materials, position);
+        },
+        ["reinforcements", composition] => {
+            reinforcements.composition = parse_wave_composition(composition)?;
+        },
+        _ => return Err("unknown entry".to_string()),
+    }
+    Ok(None)
+}
+
+// reads a value from a data file, naming what it was meant to be if it can't be read
+pub fn parse_field<T: FromStr>(text: &str, name: &str) -> Result<T, String> {
+    text.parse().map_err(|_| format!("bad {}: {}", name, text))
+}
+
+// reads a position from an encounter entry
+pub fn parse_point(x: &str, y: &str) -> Result<(f32, f32), String> {
+    Ok((parse_field(x, "x")?, parse_field(y, "y")?))
+}
+
+// reads a width and height from an encounter entry
+pub fn parse_size(w: &str, h: &str) -> Result<(f32, f32), String> {
+    Ok((parse_field(w, "width")?, parse_field(h, "height")?))
+}
+
+// encounter stages resource
+// holds the entries of every stage of the encounter that hasn't been triggered yet, by stage name
+// a stage is triggered by a squad member walking into an encounter zone with the same name
+#[derive(Default)]
+pub struct EncounterStages {
+    pub dormant: HashMap<String, Vec<String>>,
+}
+
+// encounter zone event state holds an event reader for zone events
+#[derive(Default)]
+pub struct EncounterZoneEventState {
+    pub event_reader: EventReader<ZoneEvent>,
+}
+
+// encounter stage system
+// wakes up a stage of the encounter when the squad walks into its trigger zone
+// bringing in everything listed in the stage, and adding its waves on to the end of the waves to come
+pub fn encounter_stage_system(mut commands: Commands, config: Res<GameConfig>, mut state: ResMut<EncounterZoneEventState>, events: Res<Events<ZoneEvent>>,
+    archetypes: Res<Archetypes>, difficulty: Res<Difficulty>, mut stages: ResMut<EncounterStages>, mut scheduler: ResMut<WaveScheduler>,
+    mut reinforcements: ResMut<Reinforcements>, mut materials: ResMut<Assets<ColorMaterial>>, zones: Query<&Zone>) {
+    for event in state.event_reader.iter(&events) {
+        if event.kind != ZoneKind::Encounter || !event.entered {
+            continue;
+        }
+        let name = match zones.get::<Zone>(event.zone) {
+            Ok(zone) => zone.name.clone(),
+            Err(_) => continue,
+        };
+        // each stage only wakes up once
+        let entries = match stages.dormant.remove(&name) {
+            Some(entries) => entries,
+            None => continue,
+        };
+        for line in entries.iter() {
+            match apply_encounter_entry(&config, &mut commands, &mut materials, &archetypes, &difficulty, &mut reinforcements, line) {
+                Ok(Some(wave)) => {
+                    scheduler.waves.push_back(wave);
+                    scheduler.total += 1;
+                },
+                Ok(None) => (),
+                Err(err) => eprintln!("bad encounter line: {} ({})", line, err),
+            }
+        }
+    }
+}
+
+// wave trigger enum
+// what a wave waits on before its delay starts counting down
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WaveTrigger {
+    // the wave before it arriving
+    After,
+    // the wave before it being wiped out
+    Cleared,
+}
+
+// wave struct
+// a group of hostiles brought in together partway through an encounter
+#[derive(Debug, Clone)]
+pub struct Wave {
+    pub trigger: WaveTrigger,
+    // seconds to wait once triggered
+    pub delay: f32,
+    // tile the wave spawns around, or none to come in through the spawn points
+    pub tile: Option<TilePos>,
+    // the archetypes in the wave, along with how many of each
+    pub composition: Vec<(String, usize)>,
+}
+
+// reads a wave trigger, either after or cleared
+pub fn parse_wave_trigger(text: &str) -> Result<WaveTrigger, String> {
+    match text {
+        "after" => Ok(WaveTrigger::After),
+        "cleared" => Ok(WaveTrigger::Cleared),
+        _ => Err(format!("bad wave trigger: {}", text)),
+    }
+}
+
+// reads a wave composition, e.g. grunt:3,sniper:1
+// a plain number is that many grunts
+pub fn parse_wave_composition(text: &str) -> Result<Vec<(String, usize)>, String> {
+    if let Ok(count) = text.parse() {
+        return Ok(vec![("grunt".to_string(), count)]);
+    }
+    text.split(',').map(|part| {
+        let mut pieces = part.splitn(2, ':');
+        let name = pieces.next().unwrap_or("grunt").to_string();
+        let count = match pieces.next() {
+            Some(count) => parse_field(count, "count in wave composition")?,
+            None => 1,
+        };
+        Ok((name, count))
+    }).collect()
+}
+
+// wave scheduler resource
+// holds the waves still to come, and counts down to the next one
+#[derive(Default)]
+pub struct WaveScheduler {
+    // the waves still to come, in order
+    pub waves: VecDeque<Wave>,
+    // the number of waves in the encounter
+    pub total: usize,
+    // the number of waves that have arrived so far
+    pub arrived: usize,
+    // counts down to the next wave, once it's been triggered
+    pub timer: Option<Timer>,
+}
+
+// wave member component
+// spawn this component along with every hostile brought in by a wave
+// holds the number of the wave it came in with
+pub struct WaveMember(pub usize);
+
+// wave counter component
+// spawn this component along with the text showing which wave the encounter is on
+pub struct WaveCounter;
+
+// wave system
+// triggers the next wave, counts down its delay, and brings it in
+pub fn wave_system(mut commands: Commands, config: Res<GameConfig>, time: Res<Time>, map: Res<MapData>, archetypes: Res<Archetypes>, difficulty: Res<Difficulty>, mut scheduler: ResMut<WaveScheduler>,
+    mut materials: ResMut<Assets<ColorMaterial>>, mut members: Query<&WaveMember>, mut telegraphs: Query<&SpawnTelegraph>,
+    mut spawn_points: Query<(&SpawnPoint, &Position)>) {
+    let next = match scheduler.waves.front() {
+        Some(wave) => wave.clone(),
+        None => return,
+    };
+
+    // start counting down once the wave has been triggered
+    if scheduler.timer.is_none() {
+        let triggered = match next.trigger {
+            // the wave before has always arrived by the time this one is next
+            WaveTrigger::After => true,
+            WaveTrigger::Cleared => {
+                // check if anyone from the last wave is left
+                let mut remaining = false;
+                for member in &mut members.iter() {
+                    if member.0 == scheduler.arrived {
+                        remaining = true;
+                    }
+                }
+                // including anyone from it still on their way in
+                for telegraph in &mut telegraphs.iter() {
+                    if telegraph.wave == Some(scheduler.arrived) {
+                        remaining = true;
+                    }
+                }
+                !remaining
+            },
+        };
+        if triggered {
+            scheduler.timer = Some(Timer::from_seconds(next.delay, false));
+        }
+    }
+
+    let ready = match &mut scheduler.timer {
+        Some(timer) => {
+            timer.tick(time.delta_seconds);
+            timer.finished
+        },
+        None => false,
+    };
+    if !ready {
+        return;
+    }
+
+    scheduler.waves.pop_front();
+    scheduler.timer = None;
+    scheduler.arrived += 1;
+    let number = scheduler.arrived;
+    let names = difficulty.expand_composition(&next.composition);
+    match next.tile {
+        // bring the wave in, spread out around its tile
+        Some(tile) => {
+            let tiles = map.get_spread_destinations(&tile, names.len());
+            for (name, tile) in names.iter().zip(tiles.iter()) {
+                spawn_hostile(&config, &mut commands, &mut materials, &difficulty.adjust(archetypes.get(name)), tile.to_coords(&config));
+                commands.with(WaveMember(number));
+            }
+        },
+        // mark the spawn points, the wave comes in through them shortly after
+        None => {
+            let points = get_spawn_point_positions(&mut spawn_points);
+            if points.is_empty() {
+                eprintln!("wave {} comes in through spawn points, but there are none", number);
+            }
+            let positions = get_reinforcement_positions(&config, &map, &points, names.len());
+            for (name, position) in names.iter().zip(positions.iter()) {
+                spawn_telegraph(&mut commands, &mut materials, name.to_string(), Some(number), *position);
+            }
+        },
+    }
+}
+
+// ambusher component
+// spawn this component along with hostiles that lie in wait, hidden, for the squad to come close
+// ambushers in the same group spring the ambush together
+pub struct Ambusher {
+    pub group: String,
+    // how close a squad member gets before the ambush is sprung
+    pub radius: f32,
+    // hidden ambushers aren't drawn, and can't be targeted by the squad
+    pub hidden: bool,
+    // whether the ambush has been sprung
+    pub sprung: bool,
+}
+
+impl Ambusher {
+    pub fn new(group: String, radius: f32) -> Self {
+        Ambusher {
+            group: group,
+            radius: radius,
+            hidden: true,
+            sprung: false,
+        }
+    }
+}
+
+// ambush system
+// keeps ambushers still and hidden until they're spotted, and springs the ambush
+// when a squad member comes within range of any ambusher in the group
+// every ambusher in a sprung group opens fire on the nearest squad member at the same time
+pub fn ambush_system(config: Res<GameConfig>, pipeline: Res<QueryPipeline>, bodies: Res<RigidBodySet>, colliders: Res<ColliderSet>,
+    mut squad: Query<(&Controlled, &Id, &Position)>,
+    mut query: Query<(&mut Ambusher, &Position, &mut Behaviour, &mut Nerve, &mut Draw, Option<&Weapon>)>) {
+    let mut members = Vec::new();
+    for (_controlled, id, pos) in &mut squad.iter() {
+        members.push((id.id(), (pos.0, pos.1)));
+    }
+
+    // find the groups being sprung, and reveal any ambushers that have been spotted
+    let mut sprung_groups = HashSet::new();
+    for (mut ambusher, pos, mut behav, mut actions, mut draw, _weapon) in &mut query.iter() {
+        if !ambusher.sprung {
+            // lie in wait
+            behav.current_behaviour = BehaviourType::Ambush;
+            actions.clear();
+        }
+        for (_, member_pos) in members.iter() {
+            let dist = Vec2::new(member_pos.0 - pos.0, member_pos.1 - pos.1).length();
+            let in_sight = || combat_query::line_of_sight(&config, &pipeline, &bodies, &colliders, (pos.0, pos.1), *member_pos);
+            if !ambusher.sprung && dist <= ambusher.radius && in_sight() {
+                sprung_groups.insert(ambusher.group.clone());
+            }
+            if ambusher.hidden && dist <= AMBUSH_SPOT_RADIUS && in_sight() {
+                ambusher.hidden = false;
+            }
+        }
+        draw.is_visible = !ambusher.hidden;
+    }
+    if sprung_groups.is_empty() {
+        return;
+    }
+
+    for (mut ambusher, pos, mut behav, mut actions, mut draw, weapon) in &mut query.iter() {
+        if ambusher.sprung || !sprung_groups.contains(&ambusher.group) {
+            continue;
+        }
+        ambusher.sprung = true;
+        ambusher.hidden = false;
+        draw.is_visible = true;
+        behav.current_behaviour = BehaviourType::Empty;
+
+        // open fire on the nearest squad member, with no cooldown so the whole group fires together
+        let nearest = members.iter()
+            .map(|(id, member_pos)| (id, Vec2::new(member_pos.0 - pos.0, member_pos.1 - pos.1).length()))
+            .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal));
+        if let Some((target, _)) = nearest {
+            actions.replace_all(get_attack_actions(Some(target.clone()), &weapon.cloned().unwrap_or_default(), HashMap::new()));
+        }
+    }
+}
+
+// difficulty level enum
+// the difficulty picked when launching the game
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DifficultyLevel {
+    Easy,
+    Normal,
+    Hard,
+}
+
+impl DifficultyLevel {
+    // reads a difficulty level from its name
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "easy" => Some(DifficultyLevel::Easy),
+            "normal" => Some(DifficultyLevel::Normal),
+            "hard" => Some(DifficultyLevel::Hard),
+            _ => None,
+        }
+    }
+    // gives the multipliers on hostile counts, accuracy and perception for this level
+    pub fn multipliers(&self) -> (f32, f32, f32) {
+        match self {
+            DifficultyLevel::Easy => (0.75, 0.7, 0.8),
+            DifficultyLevel::Normal => (1.0, 1.0, 1.0),
+            DifficultyLevel::Hard => (1.3, 1.2, 1.25),
+        }
+    }
+}
+
+// difficulty resource
+// scales the hostiles brought in by encounters, according to the difficulty level picked
+// with adaptive difficulty on, the scaling eases off as the squad loses members or the mission drags on
+// the difficulty is applied whenever hostiles are spawned, so hostiles already in play aren't changed
+pub struct Difficulty {
+    pub level: DifficultyLevel,
+    pub adaptive: bool,
+    // squad members lost so far
+    pub squad_losses: u32,
+    // seconds since the mission started
+    pub elapsed: f32,
+}
+
+impl Default for Difficulty {
+    fn default() -> Self {
+        Difficulty::new(DifficultyLevel::Normal, false)
+    }
+}
+
+impl Difficulty {
+    pub fn new(level: DifficultyLevel, adaptive: bool) -> Self {
+        Difficulty {
+            level: level,
+            adaptive: adaptive,
+            squad_losses: 0,
+            elapsed: 0.0,
+        }
+    }
+    // gives how much the difficulty has eased off from how the squad is doing
+    // 1.0 is no easing, and the difficulty never eases off below half
+    pub fn easing(&self) -> f32 {
+        if !self.adaptive {
+            return 1.0;
+        }
+        let overtime = (self.elapsed / DIFFICULTY_PAR_TIME - 1.0).max(0.0);
+        (1.0 - self.squad_losses as f32 * DIFFICULTY_LOSS_EASING - overtime * DIFFICULTY_OVERTIME_EASING).max(0.5)
+    }
+    // gives how many hostiles come in, in place of the given number
+    // there's always at least one, as long as any were asked for
+    pub fn scale_count(&self, count: usize) -> usize {
+        if count == 0 {
+            return 0;
+        }
+        let (hostiles, _, _) = self.level.multipliers();
+        ((count as f32 * hostiles * self.easing()).round() as usize).max(1)
+    }
+    // gives the names of every hostile in a composition, scaled by the difficulty
+    pub fn expand_composition(&self, composition: &[(String, usize)]) -> Vec<String> {
+        composition.iter()
+            .flat_map(|(name, count)| std::iter::repeat(name.clone()).take(self.scale_count(*count)))
+            .collect()
+    }
+    // gives the archetype with its accuracy and perception scaled by the difficulty
+    pub fn adjust(&self, mut archetype: Archetype) -> Archetype {
+        let (_, accuracy, perception) = self.level.multipliers();
+        let easing = self.easing();
+        archetype.weapon.accuracy = (archetype.weapon.accuracy * accuracy * easing).min(1.0);
+        if let Some(sidearm) = &mut archetype.sidearm {
+            sidearm.accuracy = (sidearm.accuracy * accuracy * easing).min(1.0);
+        }
+        archetype.hearing *= perception * easing;
+        archetype
+    }
+}
+
+// difficulty death state holds an event reader for death events
+#[derive(Default)]
+pub struct DifficultyDeathState {
+    pub event_reader: EventReader<DeathEvent>,
+}
+
+// difficulty system
+// keeps track of how long the mission has run and how many squad members have been lost
+pub fn difficulty_system(time: Res<Time>, mut state: ResMut<DifficultyDeathState>, deaths: Res<Events<DeathEvent>>, mut difficulty: ResMut<Difficulty>) {
+    difficulty.elapsed += time.delta_seconds;
+    for death in state.event_reader.iter(&deaths) {
+        if death.attitude == Some(AttitudeType::Squad) {
+            difficulty.squad_losses += 1;
+        }
+    }
+}
+
+// spawn point component
+// spawn this component along with a position to mark where reinforcements come in from
+// spawn points are listed in the encounter file, or placed as objects of type "spawn" in the tiled map
+pub struct SpawnPoint {
+    pub name: String,
+}
+
+// spawns a spawn point at the given position
+pub fn spawn_spawn_point(commands: &mut Commands, name: String, position: (f32, f32)) {
+    commands.spawn((
+        SpawnPoint { name: name },
+        Position(position.0, position.1),
+    ));
+}
+
+// gives the positions of every spawn point
+pub fn get_spawn_point_positions(spawn_points: &mut Query<(&SpawnPoint, &Position)>) -> Vec<(f32, f32)> {
+    let mut points = Vec::new();
+    for (_point, pos) in &mut spawn_points.iter() {
+        points.push((pos.0, pos.1));
+    }
+    points
+}
+
+// gives where each of a number of reinforcements comes in
+// reinforcements are shared out between the spawn points, and spread out around each one
+pub fn get_reinforcement_positions(config: &GameConfig, map: &MapData, points: &[(f32, f32)], count: usize) -> Vec<(f32, f32)> {
+    if points.is_empty() {
+        return Vec::new();
+    }
+    let mut positions = Vec::new();
+    for (i, point) in points.iter().enumerate() {
+        // how many come in through this spawn point
+        let share = count / points.len() + if i < count % points.len() { 1 } else { 0 };
+        if share == 0 {
+            continue;
+        }
+        let tile = map.get_nearest_free_tile(&TilePos::from_coords(config, point.0.max(0.0), point.1.max(0.0)));
+        for tile in map.get_spread_destinations(&tile, share) {
+            positions.push(tile.to_coords(config));
+        }
+    }
+    positions
+}
+
+// spawn telegraph component
+// spawn this component along with the marker shown on a spawn point before a reinforcement arrives
+// the marker grows until the timer runs out, then the hostile is brought in where it stood
+pub struct SpawnTelegraph {
+    // the archetype of the hostile coming in
+    pub archetype: String,
+    // the wave the hostile is part of, if any
+    pub wave: Option<usize>,
+    pub timer: Timer,
+}
+
+// spawns the marker telegraphing a reinforcement arriving
+pub fn spawn_telegraph(commands: &mut Commands, materials: &mut ResMut<Assets<ColorMaterial>>, archetype: String, wave: Option<usize>, position: (f32, f32)) {
+    let material = materials.add(Color::rgb(0.8, 0.1, 0.1).into());
+    commands
+        .spawn(SimpleRect::new(material, Vec2::new(2.0, 2.0)))
+        .with(Position(position.0, position.1))
+        .with(SpawnTelegraph {
+            archetype: archetype,
+            wave: wave,
+            timer: Timer::from_seconds(SPAWN_TELEGRAPH_DURATION, false),
+        });
+}
+
+// spawn telegraph system
+// grows the markers on spawn points, and brings the reinforcements in once they're done
+pub fn spawn_telegraph_system(mut commands: Commands, config: Res<GameConfig>, time: Res<Time>, archetypes: Res<Archetypes>, difficulty: Res<Difficulty>, mut materials: ResMut<Assets<ColorMaterial>>,
+    mut query: Query<(Entity, &mut SpawnTelegraph, &Position, &mut Sprite)>) {
+    for (entity, mut telegraph, pos, mut sprite) in &mut query.iter() {
+        telegraph.timer.tick(time.delta_seconds);
+        if !telegraph.timer.finished {
+            // grow up to the size of a person
+            let progress = telegraph.timer.elapsed / telegraph.timer.duration;
+            sprite.size = Vec2::new(2.0, 2.0) + Vec2::new(8.0, 8.0) * progress;
+            continue;
+        }
+
+        commands.despawn(entity);
+        spawn_hostile(&config, &mut commands, &mut materials, &difficulty.adjust(archetypes.get(&telegraph.archetype)), (pos.0, pos.1));
+        if let Some(wave) = telegraph.wave {
+            commands.with(WaveMember(wave));
+        }
+    }
+}
+
+// reinforcements resource
+// the hostiles brought in through the spawn points whenever an alarm is raised
+#[derive(Default)]
+pub struct Reinforcements {
+    // the archetypes in the reinforcements, along with how many of each
+    pub composition: Vec<(String, usize)>,
+    // ambush zones that have already raised the alarm
+    pub raised: HashSet<Entity>,
+}
+
+// alarm zone event state holds an event reader for zone events
+#[derive(Default)]
+pub struct AlarmZoneEventState {
+    pub event_reader: EventReader<ZoneEvent>,
+}
+
+// calls the reinforcements in through the spawn points
+pub fn call_reinforcements(config: &GameConfig, commands: &mut Commands, materials: &mut ResMut<Assets<ColorMaterial>>, map: &MapData, difficulty: &Difficulty,
+    reinforcements: &Reinforcements, spawn_points: &mut Query<(&SpawnPoint, &Position)>) {
+    let points = get_spawn_point_positions(spawn_points);
+    let names = difficulty.expand_composition(&reinforcements.composition);
+    let positions = get_reinforcement_positions(config, map, &points, names.len());
+    for (name, position) in names.into_iter().zip(positions.into_iter()) {
+        spawn_telegraph(commands, materials, name, None, position);
+    }
+}
+
+// alarm system
+// the squad walking into an ambush zone raises the alarm, calling reinforcements in through the spawn points
+// and putting the encounter on alert
+// each ambush zone only raises the alarm once
+pub fn alarm_system(mut commands: Commands, config: Res<GameConfig>, map: Res<MapData>, difficulty: Res<Difficulty>, mut state: ResMut<AlarmZoneEventState>, events: Res<Events<ZoneEvent>>,
+    mut reinforcements: ResMut<Reinforcements>, mut alert: ResMut<Alert>, mut materials: ResMut<Assets<ColorMaterial>>, mut spawn_points: Query<(&SpawnPoint, &Position)>) {
+    for event in state.event_reader.iter(&events) {
+        if event.kind != ZoneKind::Ambush || !event.entered || reinforcements.raised.contains(&event.zone) {
+            continue;
+        }
+        reinforcements.raised.insert(event.zone);
+        alert.raised = true;
+        call_reinforcements(&config, &mut commands, &mut materials, &map, &difficulty, &reinforcements, &mut spawn_points);
+    }
+}
+
+// alarm component
+// spawn this component along with the alarm hostiles run to once they've spotted the squad
+// alarms are listed in the encounter file, or placed as objects of type "alarm" in the tiled map
+pub struct Alarm {
+    // whether the alarm has been raised
+    pub activated: bool,
+    // the hostile running to raise the alarm, if any
+    pub runner: Option<Entity>,
+    // whether the runner has got to the alarm and is raising it
+    pub raising: bool,
+}
+
+// spawns an alarm at the given position
+pub fn spawn_alarm(commands: &mut Commands, materials: &mut ResMut<Assets<ColorMaterial>>, position: (f32, f32)) {
+    let material = materials.add(Color::rgb(0.9, 0.6, 0.0).into());
+    commands
+        .spawn(SimpleRect::new(material, Vec2::new(8.0, 8.0)))
+        .with(Id::new())
+        .with(Position(position.0, position.1))
+        .with(Alarm {
+            activated: false,
+            runner: None,
+            raising: false,
+        });
+}
+
+// alarm runner system
+// sends the closest hostile fighting the squad running to each alarm that hasn't been raised,
+// then has it raise the alarm once it gets there
+// only one hostile runs for an alarm at a time, another is sent if the runner is stopped on the way
+pub fn alarm_runner_system(config: Res<GameConfig>, map: Res<MapData>, mut alarms: Query<(&mut Alarm, &Id, &Position)>,
+    mut hostiles: Query<(Entity, &Person, &Position, &mut Behaviour, &mut Nerve, &mut Pathfinder)>) {
+    for (mut alarm, alarm_id, alarm_pos) in &mut alarms.iter() {
+        if alarm.activated {
+            continue;
+        }
+
+        if let Some(runner) = alarm.runner {
+            // runners are stopped by being killed, or by being made to do something else, e.g. surrendering
+            let running = hostiles.get::<Behaviour>(runner).map_or(false, |behav| behav.current_behaviour == BehaviourType::RaiseAlarm);
+            if !running {
+                alarm.runner = None;
+                alarm.raising = false;
+                continue;
+            }
+            if alarm.raising {
+                continue;
+            }
+            let arrived = match (hostiles.get::<Pathfinder>(runner), hostiles.get::<Nerve>(runner)) {
+                (Ok(pf), Ok(actions)) => !pf.needs_pathfinding && pf.path_index >= pf.tile_path.len()
+                    && actions.is_curr_action_empty() && actions.action_queue.is_empty(),
+                _ => false,
+            };
+            if arrived {
+                if let Ok(mut actions) = hostiles.get_mut::<Nerve>(runner) {
+                    let mut params = HashMap::new();
+                    params.insert("duration".to_string(), ALARM_RAISE_DURATION);
+                    actions.action_queue.push_back(Action {
+                        action_type: ActionType::Interact,
+                        target: (Some((alarm_pos.0, alarm_pos.1)), Some(alarm_id.id())),
+                        params: Some(params),
+                        group: None,
+                    });
+                }
+                alarm.raising = true;
+            }
+            continue;
+        }
+
+        // find the closest hostile fighting the squad, that isn't already busy
+        let mut closest: Option<(Entity, f32)> = None;
+        for (entity, pers, pos, behav, actions, _pf) in &mut hostiles.iter() {
+            if pers.attitude != AttitudeType::Hostile || behav.current_behaviour.is_reaction() {
+                continue;
+            }
+            let fighting = match actions.current_action.action_type {
+                ActionType::Attack => true,
+                _ => actions.action_queue.iter().any(|action| match action.action_type {
+                    ActionType::Attack => true,
+                    _ => false,
+                }),
+            };
+            if !fighting {
+                continue;
+            }
+            let dist = Vec2::new(alarm_pos.0 - pos.0, alarm_pos.1 - pos.1).length();
+            if dist <= ALARM_RUN_DISTANCE && closest.map_or(true, |(_, closest)| dist < closest) {
+                closest = Some((entity, dist));
+            }
+        }
+
+        // and send it running
+        if let Some((runner, _)) = closest {
+            if let Ok(mut behav) = hostiles.get_mut::<Behaviour>(runner) {
+                behav.current_behaviour = BehaviourType::RaiseAlarm;
+            }
+            if let Ok(mut actions) = hostiles.get_mut::<Nerve>(runner) {
+                actions.clear();
+            }
+            if let Ok(mut pf) = hostiles.get_mut::<Pathfinder>(runner) {
+                let goal = map.get_nearest_free_tile(&TilePos::from_coords(&config, alarm_pos.0.max(0.0), alarm_pos.1.max(0.0)));
+                pf.tile_path.clear();
+                pf.needs_pathfinding = true;
+                pf.path_goal = goal;
+                pf.real_goal = goal.to_coords(&config);
+            }
+            alarm.runner = Some(runner);
+        }
+    }
+}
+
+// interact event state holds an event reader for interact events
+#[derive(Default)]
+pub struct InteractEventState {
+    pub event_reader: EventReader<InteractEvent>,
+}
+
+// raise alarm system
+// raises an alarm once a hostile has finished interacting with it, calling reinforcements in through the spawn points
+// and putting the encounter on alert, the runner then goes back to fighting
+pub fn raise_alarm_system(mut commands: Commands, config: Res<GameConfig>, map: Res<MapData>, difficulty: Res<Difficulty>, mut state: ResMut<InteractEventState>, events: Res<Events<InteractEvent>>,
+    reinforcements: Res<Reinforcements>, mut alert: ResMut<Alert>, mut materials: ResMut<Assets<ColorMaterial>>, mut spawn_points: Query<(&SpawnPoint, &Position)>,
+    mut alarms: Query<(&mut Alarm, &Id, &mut Handle<ColorMaterial>)>, mut runners: Query<(&Id, &mut Behaviour)>) {
+    for event in state.event_reader.iter(&events) {
+        for (mut alarm, id, mut material) in &mut alarms.iter() {
+            if id.id() != event.target_id || alarm.activated {
+                continue;
+            }
+            alarm.activated = true;
+            alarm.runner = None;
+            alarm.raising = false;
+            // raised alarms turn red
+            *material = materials.add(Color::rgb(0.9, 0.1, 0.1).into());
+            alert.raised = true;
+            call_reinforcements(&config, &mut commands, &mut materials, &map, &difficulty, &reinforcements, &mut spawn_points);
+        }
+        for (id, mut behav) in &mut runners.iter() {
+            if id.id() == event.actor_id && behav.current_behaviour == BehaviourType::RaiseAlarm {
+                behav.current_behaviour = BehaviourType::Empty;
+            }
+        }
+    }
+}
+
+// setup wave counter system
+// spawns in the text showing which wave the encounter is on
+pub fn setup_wave_counter_system(mut commands: Commands, asset_server: Res<AssetServer>, mods: Res<ModAssets>) {
+    let font_handle = asset_server.load(mods.resolve("assets/fonts/LiberationMono-Regular.ttf")).unwrap();
+
+    commands
+        .spawn(TextComponents {
+            style: Style {
+                // keep the counter in the top right corner
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    right: Val::Px(5.0),
+                    top: Val::Px(5.0),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            text: Text {
+                value: "".to_string(),
+                font: font_handle,
+                style: TextStyle {
+                    font_size: 20.0,
+                    color: Color::BLACK,
+                },
+            },
+            ..Default::default()
+        })
+        .with(WaveCounter);
+}
+
+// wave counter system
+// shows the wave the encounter is on, if the encounter has waves
+pub fn wave_counter_system(scheduler: Res<WaveScheduler>, alert: Res<Alert>, locale: Res<Locale>, mut query: Query<(&WaveCounter, &mut Text)>) {
+    for (_counter, mut text) in &mut query.iter() {
+        text.value = if scheduler.total > 0 {
+            locale.format("wave", &[&scheduler.arrived, &scheduler.total])
+        }else{
+            "".to_string()
+        };
+        // let the player know once the alarm's been raised
+        if alert.raised {
+            text.value = format!("{} {}", text.value, locale.get("alert")).trim_start().to_string();
+        }
+    }
+}
+
+// boss phase system
+// moves bosses on to their next phase once they've been worn down past its threshold
+// a new phase changes which behaviours the boss prefers, and can summon in reinforcements
+pub fn boss_phase_system(mut commands: Commands, config: Res<GameConfig>, map: Res<MapData>, difficulty: Res<Difficulty>, mut materials: ResMut<Assets<ColorMaterial>>,
+    mut spawn_points: Query<(&SpawnPoint, &Position)>, mut query: Query<(&mut Boss, &Health, &Position, &mut Behaviour)>) {
+    for (mut boss, health, pos, mut behav) in &mut query.iter() {
+        while boss.phase < boss.phases.len() && health.fraction() <= boss.phases[boss.phase].threshold {
+            let phase = boss.phases[boss.phase].clone();
+            boss.phase += 1;
+
+            if !phase.preferences.is_empty() {
+                behav.preferences = phase.preferences;
+                // pick again with the new preferences
+                if behav.current_behaviour != BehaviourType::Investigate {
+                    behav.current_behaviour = BehaviourType::Empty;
+                }
+            }
+
+            // summons come in through the spawn points, or around the boss if there aren't any
+            let names = difficulty.expand_composition(&phase.summon);
+            if names.is_empty() {
+                continue;
+            }
+            let mut points = get_spawn_point_positions(&mut spawn_points);
+            if points.is_empty() {
+                points.push((pos.0, pos.1));
+            }
+            let positions = get_reinforcement_positions(&config, &map, &points, names.len());
+            for (name, position) in names.into_iter().zip(positions.into_iter()) {
+                spawn_telegraph(&mut commands, &mut materials, name, None, position);
+            }
+        }
+    }
+}
+
+// setup boss health bar system
+// spawns in the bar showing the boss's health across the top of the screen, hidden until a boss turns up
+pub fn setup_boss_health_bar_system(mut commands: Commands, mut materials: ResMut<Assets<ColorMaterial>>, asset_server: Res<AssetServer>, mods: Res<ModAssets>) {
+    let font_handle = asset_server.load(mods.resolve("assets/fonts/LiberationMono-Regular.ttf")).unwrap();
+
+    commands
+        .spawn(TextComponents {
+            style: Style {
+                // sit just above the bar
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    left: Val::Percent(20.0),
+                    top: Val::Px(5.0),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            text: Text {
+                value: "".to_string(),
+                font: font_handle,
+                style: TextStyle {
+                    font_size: 16.0,
+                    color: Color::BLACK,
+                },
+            },
+            ..Default::default()
+        })
+        .with(BossName)
+        .spawn(NodeComponents {
+            style: Style {
+                // stretch across the middle of the top of the screen
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    left: Val::Percent(20.0),
+                    top: Val::Px(25.0),
+                    ..Default::default()
+                },
+                // the game's own size component shadows bevy's
+                size: bevy::math::Size::new(Val::Percent(0.0), Val::Px(10.0)),
+                ..Default::default()
+            },
+            material: materials.add(Color::rgb(0.2, 0.2, 0.2).into()),
+            ..Default::default()
+        })
+        .with(BossHealthBar)
+        .with_children(|parent| {
+            parent
+                .spawn(NodeComponents {
+                    style: Style {
+                        size: bevy::math::Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                        ..Default::default()
+                    },
+                    material: materials.add(Color::rgb(0.8, 0.1, 0.1).into()),
+                    ..Default::default()
+                })
+                .with(BossHealthFill);
+        });
+}
+
+// boss health bar system
+// shows the health of the first boss still standing, and hides the bar when there isn't one
+pub fn boss_health_bar_system(mut bosses: Query<(&Boss, &Health)>, mut names: Query<(&BossName, &mut Text)>,
+    mut bars: Query<(Option<&BossHealthBar>, Option<&BossHealthFill>, &mut Style)>) {
+    let mut shown = None;
+    for (boss, health) in &mut bosses.iter() {
+        shown = Some((boss.name.clone(), health.fraction()));
+        break;
+    }
+
+    for (_name, mut text) in &mut names.iter() {
+        text.value = shown.as_ref().map_or("".to_string(), |(name, _)| name.to_uppercase());
+    }
+    for (bar, fill, mut style) in &mut bars.iter() {
+        if bar.is_some() {
+            style.size.width = Val::Percent(if shown.is_some() { 60.0 } else { 0.0 });
+        }else if fill.is_some() {
+            style.size.width = Val::Percent(shown.as_ref().map_or(0.0, |(_, fraction)| fraction * 100.0));
+        }
+    }
+}
+
+// spawn hostile
+// spawns in a single hostile of the given archetype, with everything it needs to wander, fight and perceive
+pub fn spawn_hostile(config: &GameConfig, commands: &mut Commands, materials: &mut ResMut<Assets<ColorMaterial>>, archetype: &Archetype, position: (f32, f32)) {
+    let handle = materials.add(archetype.color.into());
+    commands
+        .spawn(SimpleRect::new(handle, Vec2::new(10.0, 10.0)))
+        .with(Id::new())
+        .with(Person::new(AttitudeType::Hostile))
+        .with(Position(position.0, position.1))
+        .with(Velocity(0.0, 0.0))
+        .with(MoveSpeed::new(BASE_MOVE_SPEED * archetype.speed))
+        .with(Stamina::default())
+        .with(archetype.weapon.clone())
+        .with(Nerve::new())
+        .with(Size(10.0, 10.0))
+        .with_bundle(get_person_body_components(config, position, (10.0, 10.0), &AttitudeType::Hostile))
+        .with(Behaviour {
+            preferences: archetype.preferences.clone(),
+            ..Default::default()
+        })
+        .with(get_hostile_sprite_template(materials, archetype.color))
+        .with(OwnColor(archetype.color))
+        .with(Perception {
+            hearing: archetype.hearing,
+            ..Default::default()
+        })
+        .with(Pathfinder::default());
+
+    // hostiles that can be worn down
+    if let Some(health) = archetype.health {
+        commands.with(Health::new(health));
+    }
+    // hostiles with a sidearm carry it holstered
+    if let Some(sidearm) = &archetype.sidearm {
+        commands.with(Holstered(sidearm.clone()));
+    }
+    // hostiles that shrug off some kinds of damage
+    if !archetype.resistances.0.is_empty() {
+        commands.with(archetype.resistances.clone());
+    }
+    // bosses fight to the end, everyone else can lose their nerve
+    if archetype.phases.is_empty() {
+        commands.with(Morale::default());
+    }else{
+        commands.with(Boss {
+            name: archetype.name.clone(),
+            phases: archetype.phases.clone(),
+            phase: 0,
+        });
+    }
+}
+
+// spawn civilian
+// spawns in a single civilian, who wanders about until fighting breaks out nearby
+pub fn spawn_civilian(config: &GameConfig, commands: &mut Commands, materials: &mut ResMut<Assets<ColorMaterial>>, position: (f32, f32)) {
+    let color = Color::rgb(0.3, 0.5, 0.8);
+    let handle = materials.add(color.into());
+    commands
+        .spawn(SimpleRect::new(handle, Vec2::new(10.0, 10.0)))
+        .with(Id::new())
+        .with(Person::new(AttitudeType::Neutral))
+        .with(Position(position.0, position.1))
+        .with(Velocity(0.0, 0.0))
+        .with(Nerve::new())
+        .with(Size(10.0, 10.0))
+        .with_bundle(get_person_body_components(config, position, (10.0, 10.0), &AttitudeType::Neutral))
+        .with(Behaviour::default())
+        .with(get_hostile_sprite_template(materials, color))
+        .with(Perception {
+            reaction: NoiseReaction::Panic,
+            ..Default::default()
+        })
+        .with(Civilian::default())
+        .with(Health::new(CIVILIAN_HEALTH))
+        .with(Pathfinder::default());
+}
+
+// archetype struct
+// a template for a kind of hostile, read in from assets/archetypes.txt
+#[derive(Debug, Clone)]
+pub struct Archetype {
+    // the name of the archetype, as listed in the file
+    pub name: String,
+    // multiplier on how fast the hostile moves
+    pub speed: f32,
+    // the weapon the hostile attacks with
+    pub weapon: Weapon,
+    // the weapon the hostile carries holstered, to switch to once the first runs dry or the fight gets too close
+    pub sidearm: Option<Weapon>,
+    // multiplier on how far away the hostile hears noises from
+    pub hearing: f32,
+    // the base colour of the hostile
+    pub color: Color,
+    // how likely the hostile is to pick each behaviour
+    pub preferences: Vec<(BehaviourType, f32)>,
+    // how much health the hostile has, if it can be worn down
+    pub health: Option<f32>,
+    // how much of each kind of damage the hostile shrugs off
+    pub resistances: Resistances,
+    // the phases the hostile goes through as it's worn down, only bosses have any
+    pub phases: Vec<BossPhase>,
+}
+
+impl Default for Archetype {
+    // the default archetype is a plain grunt
+    fn default() -> Self {
+        Archetype {
+            name: "grunt".to_string(),
+            speed: 1.0,
+            weapon: Weapon::default(),
+            sidearm: None,
+            hearing: 1.0,
+            color: Color::BLACK,
+            preferences: Vec::new(),
+            health: None,
+            resistances: Resistances::default(),
+            phases: Vec::new(),
+        }
+    }
+}
+
+// boss phase struct
+// one stage of a boss fight, starting once the boss is worn down to its threshold
+#[derive(Debug, Clone)]
+pub struct BossPhase {
+    // the fraction of health left when the phase starts, e.g. 0.5 for half health
+    pub threshold: f32,
+    // how likely the boss is to pick each behaviour during the phase
+    pub preferences: Vec<(BehaviourType, f32)>,
+    // the hostiles summoned in through the spawn points when the phase starts
+    pub summon: Vec<(String, usize)>,
+}
+
+// boss component
+// spawn this component along with hostiles that go through phases as they're worn down
+pub struct Boss {
+    pub name: String,
+    pub phases: Vec<BossPhase>,
+    // the phase the boss is in
+    pub phase: usize,
+}
+
+// boss health bar component
+// spawn this component along with the bar showing the boss's health across the top of the screen
+pub struct BossHealthBar;
+
+// boss health fill component
+// spawn this component along with the part of the boss health bar that shrinks as the boss is worn down
+pub struct BossHealthFill;
+
+// boss name component
+// spawn this component along with the text naming the boss above the health bar
+pub struct BossName;
+
+// reads a list of behaviour preferences, e.g. charge:3,defend:1
+pub fn parse_preferences(text: &str) -> Result<Vec<(BehaviourType, f32)>, String> {
+    text.split(',').map(|pref| {
+        let mut pieces = pref.splitn(2, ':');
+        let behaviour = pieces.next().and_then(BehaviourType::from_name).ok_or(format!("bad behaviour: {}", pref))?;
+        let weight = match pieces.next() {
+            Some(weight) => parse_field(weight, "weight")?,
+            None => 1.0,
+        };
+        Ok((behaviour, weight))
+    }).collect()
+}
+
+// parses a list of status effects, as used in data files
+// e.g. poison:4:3,slow:0.5:2 gives poison doing 4 damage a second for 3 seconds and halving speed for 2 seconds
+pub fn parse_status_effects(text: &str) -> Result<Vec<StatusEffect>, String> {
+    text.split(',').map(|effect| {
+        let pieces: Vec<&str> = effect.split(':').collect();
+        let kind = pieces.get(0).and_then(|name| StatusKind::from_name(name)).ok_or(format!("bad status effect: {}", effect))?;
+        let strength = match pieces.get(1) {
+            Some(strength) => parse_field(strength, "status strength")?,
+            None => 1.0,
+        };
+        let duration = match pieces.get(2) {
+            Some(duration) => parse_field(duration, "status duration")?,
+            None => 1.0,
+        };
+        Ok(StatusEffect::new(kind, strength, duration))
+    }).collect()
+}
+
+// reads in resistances, written as <damage type>:<fraction taken off>,...
+pub fn parse_resistances(text: &str) -> Result<Resistances, String> {
+    let resistances = text.split(',').map(|resistance| {
+        let pieces: Vec<&str> = resistance.split(':').collect();
+        let damage_type = pieces.get(0).and_then(|name| DamageType::from_name(name)).ok_or(format!("bad damage type: {}", resistance))?;
+        let fraction = match pieces.get(1) {
+            Some(fraction) => parse_field(fraction, "resistance")?,
+            None => 0.0,
+        };
+        Ok((damage_type, fraction))
+    }).collect::<Result<_, String>>()?;
+    Ok(Resistances(resistances))
+}
+
+// reads a boss phase line, giving back the archetype it belongs to along with the phase
+// e.g. phase warlord 0.5 prefers=charge:3 summon=grunt:2
+pub fn parse_boss_phase(line: &str) -> Result<(String, BossPhase), String> {
+    let mut parts = line.split_whitespace().skip(1);
+    let (owner, threshold) = match (parts.next(), parts.next()) {
+        (Some(owner), Some(threshold)) => (owner.to_string(), parse_field(threshold, "threshold")?),
+        _ => return Err("boss phase needs an archetype and a threshold".to_string()),
+    };
+    let mut phase = BossPhase {
+        threshold: threshold,
+        preferences: Vec::new(),
+        summon: Vec::new(),
+    };
+    for part in parts {
+        let mut pieces = part.splitn(2, '=');
+        match (pieces.next(), pieces.next()) {
+            (Some("prefers"), Some(value)) => phase.preferences = parse_preferences(value)?,
+            (Some("summon"), Some(value)) => phase.summon = parse_wave_composition(value)?,
+            _ => return Err(format!("bad boss phase setting: {}", part)),
+        }
+    }
+    Ok((owner, phase))
+}
+
+// reads an archetype line, giving back the archetype along with the name of its sidearm if it has one
+pub fn parse_archetype(line: &str) -> Result<(Archetype, Option<String>), String> {
+    let mut parts = line.split_whitespace();
+    let name = parts.next().ok_or("archetype has no name".to_string())?;
+    let mut archetype = Archetype {
+        name: name.to_string(),
+        ..Default::default()
+    };
+    let mut sidearm = None;
+    for part in parts {
+        let mut pieces = part.splitn(2, '=');
+        let (key, value) = match (pieces.next(), pieces.next()) {
+            (Some(key), Some(value)) => (key, value),
+            _ => return Err(format!("bad archetype setting: {}", part)),
+        };
+        let number = || parse_field::<f32>(value, key);
+        match key {
+            "speed" => archetype.speed = number()?,
+            "range" => archetype.weapon.range = number()?,
+            "min_range" => archetype.weapon.min_range = number()?,
+            "cooldown" => archetype.weapon.cooldown = number()?,
+            "knockback" => archetype.weapon.knockback = number()?,
+            "damage" => archetype.weapon.damage = number()?,
+            "accuracy" => archetype.weapon.accuracy = number()?,
+            "ammo" => archetype.weapon.ammo = Some(parse_field(value, "ammo")?),
+            "spread" => archetype.weapon.spread = number()?,
+            "crit_chance" => archetype.weapon.crit_chance = number()?,
+            "crit_multiplier" => archetype.weapon.crit_multiplier = number()?,
+            "sidearm" => sidearm = Some(value.to_string()),
+            "effects" => archetype.weapon.effects = parse_status_effects(value)?,
+            "damage_type" => archetype.weapon.damage_type = DamageType::from_name(value).ok_or(format!("bad damage type: {}", value))?,
+            "resist" => archetype.resistances = parse_resistances(value)?,
+            "health" => archetype.health = Some(number()?),
+            "hearing" => archetype.hearing = number()?,
+            "color" => {
+                let rgb = value.split(',').map(|c| parse_field(c, "colour")).collect::<Result<Vec<f32>, String>>()?;
+                if rgb.len() != 3 {
+                    return Err(format!("colour needs three parts: {}", value));
+                }
+                archetype.color = Color::rgb(rgb[0], rgb[1], rgb[2]);
+            },
+            "prefers" => archetype.preferences = parse_preferences(value)?,
+            _ => return Err(format!("unknown archetype setting: {}", key)),
+        }
+    }
+    Ok((archetype, sidearm))
+}
+
+// archetypes resource
+// holds every hostile archetype, by name
+#[derive(Default)]
+pub struct Archetypes(pub HashMap<String, Archetype>);
+
+impl Archetypes {
+    // reads in the archetypes from a file
+    // see assets/archetypes.txt for the layout
+    pub fn load(path: &str) -> Self {
+        let mut archetypes = HashMap::new();
+        // sidearms are the weapons of other archetypes, which might not be read in yet
+        let mut sidearms = Vec::new();
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(err) => {
+                eprintln!("could not read archetypes from {}: {}", path, err);
+                return Archetypes(archetypes);
+            },
+        };
+        // lines that can't be read are printed out and skipped, rather than stopping the game
+        for line in text.lines().map(|line| line.trim()) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            // phases belong to the archetype listed before them
+            if line.split_whitespace().next() == Some("phase") {
+                match parse_boss_phase(line) {
+                    Ok((owner, phase)) => match archetypes.get_mut(&owner) {
+                        Some(archetype) => archetype.phases.push(phase),
+                        None => eprintln!("bad archetypes line: {} (boss phase listed before its archetype)", line),
+                    },
+                    Err(err) => eprintln!("bad archetypes line: {} ({})", line, err),
+                }
+                continue;
+            }
+            match parse_archetype(line) {
+                Ok((archetype, sidearm)) => {
+                    if let Some(sidearm) = sidearm {
+                        sidearms.push((archetype.name.clone(), sidearm));
+                    }
+                    archetypes.insert(archetype.name.clone(), archetype);
+                },
+                Err(err) => eprintln!("bad archetypes line: {} ({})", line, err),
+            }
+        }
+        for (name, sidearm) in sidearms {
+            let weapon = match archetypes.get(&sidearm) {
+                Some(archetype) => archetype.weapon.clone(),
+                None => {
+                    eprintln!("unknown sidearm for {}: {}", name, sidearm);
+                    continue;
+                },
+            };
+            if let Some(archetype) = archetypes.get_mut(&name) {
+                archetype.sidearm = Some(weapon);
+            }
+        }
+        Archetypes(archetypes)
+    }
+    // gives the archetype with the given name
+    // unknown names fall back to the grunt, or the default archetype if there's no grunt either
+    pub fn get(&self, name: &str) -> Archetype {
+        self.0.get(name).or_else(|| self.0.get("grunt")).cloned().unwrap_or_default()
+    }
+}
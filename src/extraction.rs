@@ -0,0 +1,173 @@
+// extraction module
+// ending the mission, and counting the squad's losses
+use bevy::prelude::*;
+use bevy::app::AppExit;
+use std::collections::{HashMap, HashSet};
+
+use crate::{Id};
+use crate::civilian::{MemberRecord, MissionRecord};
+use crate::combat::{DamageEvent, DeathEvent, Health};
+use crate::person::{AttitudeType, Controlled, RosterMember};
+use crate::surrender::SavedMissionRecord;
+use crate::zone::{ZoneEvent, ZoneKind};
+
+// extraction plugin
+// responsible for ending the mission, once the whole squad has made it into an extraction zone
+// or the whole squad is dead, and for counting the squad's losses in the mission record
+// the mission ends only once the mission record has been saved, so the campaign can read it
+pub struct ExtractionPlugin;
+
+// implementation of the plugin trait,
+// required for this to be used as a plugin
+impl Plugin for ExtractionPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        // initialise the squad members in extraction zones, and the event states
+        app.init_resource::<Extraction>()
+        .init_resource::<ExtractionZoneEventState>()
+        .init_resource::<SquadDeathState>()
+        .init_resource::<SquadInjuryState>()
+        // add in the systems counting squad deaths and injuries
+        .add_system(squad_death_system.system())
+        .add_system(squad_injury_system.system())
+        // add in the system keeping track of how each squad member from the roster is doing
+        .init_resource::<RosterRecordState>()
+        .add_system(roster_record_system.system())
+        // add in the extraction system
+        .add_system(extraction_system.system());
+    }
+}
+
+// extraction resource
+// keeps track of which squad members are standing in an extraction zone
+#[derive(Default)]
+pub struct Extraction {
+    pub inside: HashSet<Entity>,
+}
+
+// extraction zone event state holds an event reader for zone events
+#[derive(Default)]
+pub struct ExtractionZoneEventState {
+    pub event_reader: EventReader<ZoneEvent>,
+}
+
+// squad death state holds an event reader for death events
+#[derive(Default)]
+pub struct SquadDeathState {
+    pub event_reader: EventReader<DeathEvent>,
+}
+
+// squad injury state holds an event reader for damage events,
+// along with the ids of every squad member hurt so far
+#[derive(Default)]
+pub struct SquadInjuryState {
+    pub event_reader: EventReader<DamageEvent>,
+    pub injured: HashSet<String>,
+}
+
+// roster record state holds an event reader for death events,
+// along with the roster name of every squad member by id, so they can be found after they're gone
+#[derive(Default)]
+pub struct RosterRecordState {
+    pub event_reader: EventReader<DeathEvent>,
+    pub names: HashMap<String, String>,
+}
+
+// roster record system
+// keeps the mission record up to date with the health, kills and deaths of the squad members from the roster
+pub fn roster_record_system(mut state: ResMut<RosterRecordState>, deaths: Res<Events<DeathEvent>>, mut record: ResMut<MissionRecord>,
+    mut squad: Query<(&RosterMember, &Id, &Health)>) {
+    for (member, id, health) in &mut squad.iter() {
+        state.names.insert(id.id(), member.name.clone());
+        let health = health.current.max(0.0);
+        match record.members.iter_mut().find(|entry| entry.name == member.name) {
+            Some(entry) => if entry.alive && entry.health != health {
+                entry.health = health;
+            },
+            None => record.members.push(MemberRecord {
+                name: member.name.clone(),
+                health: health,
+                kills: 0,
+                alive: true,
+            }),
+        }
+    }
+
+    for death in state.event_reader.iter(&deaths) {
+        if let Some(name) = state.names.get(&death.killer_id) {
+            if let Some(entry) = record.members.iter_mut().find(|entry| entry.name == *name) {
+                entry.kills += 1;
+            }
+        }
+        if let Some(name) = state.names.get(&death.id) {
+            if let Some(entry) = record.members.iter_mut().find(|entry| entry.name == *name) {
+                entry.alive = false;
+                entry.health = 0.0;
+            }
+        }
+    }
+}
+
+// squad death system
+// counts the squad members lost over the mission
+pub fn squad_death_system(mut state: ResMut<SquadDeathState>, deaths: Res<Events<DeathEvent>>, mut record: ResMut<MissionRecord>,
+    injuries: Res<SquadInjuryState>) {
+    for death in state.event_reader.iter(&deaths) {
+        if death.attitude == Some(AttitudeType::Squad) {
+            record.squad_deaths += 1;
+            // the dead aren't counted as injured as well
+            if injuries.injured.contains(&death.id) {
+                record.squad_injured = record.squad_injured.saturating_sub(1);
+            }
+        }
+    }
+}
+
+// squad injury system
+// counts the squad members that have taken a hit over the mission
+pub fn squad_injury_system(mut state: ResMut<SquadInjuryState>, damages: Res<Events<DamageEvent>>, mut record: ResMut<MissionRecord>) {
+    let mut hurt = Vec::new();
+    for damage in state.event_reader.iter(&damages) {
+        if damage.attitude == Some(AttitudeType::Squad) {
+            hurt.push(damage.target_id.clone());
+        }
+    }
+    for id in hurt {
+        if state.injured.insert(id) {
+            record.squad_injured += 1;
+        }
+    }
+}
+
+// extraction system
+// marks the squad as extracted once every squad member still standing is in an extraction zone,
+// and ends the mission once that, or the squad being wiped out, has been saved to the mission results
+pub fn extraction_system(mut state: ResMut<ExtractionZoneEventState>, events: Res<Events<ZoneEvent>>, mut extraction: ResMut<Extraction>,
+    mut record: ResMut<MissionRecord>, saved: Res<SavedMissionRecord>, mut exit: ResMut<Events<AppExit>>, mut squad: Query<(Entity, &Controlled)>) {
+    for event in state.event_reader.iter(&events) {
+        if event.kind != ZoneKind::Extraction {
+            continue;
+        }
+        if event.entered {
+            extraction.inside.insert(event.person);
+        }else{
+            extraction.inside.remove(&event.person);
+        }
+    }
+
+    let mut standing = 0;
+    let mut inside = 0;
+    for (entity, _controlled) in &mut squad.iter() {
+        standing += 1;
+        if extraction.inside.contains(&entity) {
+            inside += 1;
+        }
+    }
+    if !record.extracted && standing > 0 && inside == standing {
+        record.extracted = true;
+    }
+
+    let wiped_out = standing == 0 && record.squad_deaths > 0;
+    if (record.extracted || wiped_out) && saved.0.as_ref() == Some(&*record) {
+        exit.send(AppExit);
+    }
+}
@@ -0,0 +1,242 @@
+// feedback module
+// letting the player know whether their orders went through, and what the squad has run into
+use bevy::prelude::*;
+
+use crate::Position;
+use crate::audio::{SoundEvent, SoundType};
+use crate::config::GameConfig;
+use crate::control::CommandEvent;
+use crate::locale::Locale;
+use crate::mods::ModAssets;
+use crate::perception::SpottedEvent;
+use crate::person::{Controlled, SimpleRect};
+use crate::tick::FrameTime;
+
+// feedback plugin
+// responsible for letting the player know whether their orders were accepted
+// accepted orders get a bark, a flash on the squad member and a pulse where they're headed
+// rejected orders get an error cue and a notification saying why
+pub struct FeedbackPlugin;
+
+// implementation of the plugin trait,
+// required for this to be used as a plugin
+impl Plugin for FeedbackPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        // initialise the event reader for command events
+        app.init_resource::<CommandEventState>()
+        // add in the notification text
+        .add_startup_system(setup_notification_system.system())
+        // add in the system reacting to orders
+        .add_system(command_feedback_system.system())
+        // add in the system running the flashes and pulses
+        .add_system(command_effect_system.system())
+        // add in the system warning the player when the squad is spotted
+        .init_resource::<SpottedEventState>()
+        .add_system(spotted_feedback_system.system())
+        // add in the system hiding old notifications
+        .add_system(notification_system.system());
+    }
+}
+
+// settings for command feedback, in seconds
+pub static COMMAND_FLASH_DURATION: f32 = 0.2;
+pub static COMMAND_PULSE_DURATION: f32 = 0.5;
+pub static NOTIFICATION_DURATION: f32 = 2.0;
+
+// command event state holds an event reader for command events
+#[derive(Default)]
+pub struct CommandEventState {
+    pub event_reader: EventReader<CommandEvent>,
+}
+
+// command effect component
+// spawn this component along with a short lived sprite, e.g. a flash or a pulse
+// the sprite grows over its lifetime and is removed once it's done
+pub struct CommandEffect {
+    // the entity the effect sits on, if it follows one
+    pub target: Option<Entity>,
+    // the size the sprite starts at
+    pub size: Vec2,
+    // how much bigger the sprite gets by the end, e.g. 1.0 doubles in size
+    pub grow: f32,
+    // timer running for the lifetime of the effect
+    pub timer: Timer,
+}
+
+// notification component
+// spawn this component along with the text used for notifications
+pub struct Notification {
+    // timer running for how long the current notification is shown
+    pub timer: Timer,
+}
+
+// setup notification system
+// spawns in the text notifications are shown with, empty until needed
+pub fn setup_notification_system(mut commands: Commands, config: Res<GameConfig>, asset_server: Res<AssetServer>, mods: Res<ModAssets>) {
+    let font_handle = asset_server.load(mods.resolve("assets/fonts/LiberationMono-Regular.ttf")).unwrap();
+
+    commands
+        .spawn(TextComponents {
+            style: Style {
+                // keep notifications along the top of the screen
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    left: Val::Px(config.window_width / 2.0 - 100.0),
+                    top: Val::Px(5.0),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            text: Text {
+                value: "".to_string(),
+                font: font_handle,
+                style: TextStyle {
+                    font_size: 20.0,
+                    color: Color::RED,
+                },
+            },
+            ..Default::default()
+        })
+        .with(Notification {
+            timer: Timer::from_seconds(NOTIFICATION_DURATION, false),
+        });
+}
+
+// command feedback system
+// plays and shows the response to every order given
+pub fn command_feedback_system(mut commands: Commands, mut state: ResMut<CommandEventState>, events: Res<Events<CommandEvent>>,
+    mut materials: ResMut<Assets<ColorMaterial>>, mut sounds: ResMut<Events<SoundEvent>>, locale: Res<Locale>,
+    mut units: Query<(Entity, &Controlled, &Position)>, mut notifications: Query<(&mut Notification, &mut Text)>) {
+    let mut accepted = false;
+    let mut rejection = None;
+    let mut destinations = Vec::new();
+
+    for event in state.event_reader.iter(&events) {
+        match &event.rejection {
+            None => {
+                accepted = true;
+
+                // flash the squad member that took the order
+                let mut position = (-1000.0, -1000.0);
+                for (entity, _state, pos) in &mut units.iter() {
+                    if entity == event.unit {
+                        position = (pos.0, pos.1);
+                    }
+                }
+                let flash_handle = materials.add(Color::rgba(1.0, 1.0, 1.0, 0.6).into());
+                commands
+                    .spawn(SimpleRect::new(flash_handle, Vec2::new(14.0, 14.0)))
+                    .with(Position(position.0, position.1))
+                    .with(CommandEffect {
+                        target: Some(event.unit),
+                        size: Vec2::new(14.0, 14.0),
+                        grow: 0.0,
+                        timer: Timer::from_seconds(COMMAND_FLASH_DURATION, false),
+                    });
+
+                // only one pulse for each destination, even when ordering a group
+                if let Some(destination) = event.destination {
+                    if !destinations.contains(&destination) {
+                        destinations.push(destination);
+                    }
+                }
+            },
+            Some(reason) => {
+                rejection = Some(reason.clone());
+            },
+        }
+    }
+
+    // pulse where the squad is headed
+    for destination in destinations {
+        let pulse_handle = materials.add(Color::rgba(1.0, 1.0, 0.0, 0.5).into());
+        commands
+            .spawn(SimpleRect::new(pulse_handle, Vec2::new(6.0, 6.0)))
+            .with(Position(destination.0, destination.1))
+            .with(CommandEffect {
+                target: None,
+                size: Vec2::new(6.0, 6.0),
+                grow: 3.0,
+                timer: Timer::from_seconds(COMMAND_PULSE_DURATION, false),
+            });
+    }
+
+    // bark once for the whole order, rather than once per squad member
+    if accepted {
+        sounds.send(SoundEvent {
+            sound: SoundType::Acknowledge,
+            position: None,
+        });
+    }
+
+    // let the player know why the order couldn't be carried out
+    if let Some(reason) = rejection {
+        sounds.send(SoundEvent {
+            sound: SoundType::Error,
+            position: None,
+        });
+        for (mut notification, mut text) in &mut notifications.iter() {
+            text.value = locale.get(&reason);
+            notification.timer.reset();
+        }
+    }
+}
+
+// command effect system
+// grows the flashes and pulses, keeps flashes on their squad member, and removes them once done
+pub fn command_effect_system(mut commands: Commands, time: Res<FrameTime>, mut units: Query<(Entity, &Controlled, &Position)>,
+    mut query: Query<(Entity, &mut CommandEffect, &mut Sprite, &mut Position)>) {
+    // find where all the squad members are, for the effects following them
+    let mut positions = Vec::new();
+    for (entity, _state, pos) in &mut units.iter() {
+        positions.push((entity, (pos.0, pos.1)));
+    }
+
+    for (entity, mut effect, mut sprite, mut pos) in &mut query.iter() {
+        effect.timer.tick(time.delta_seconds);
+        if effect.timer.finished {
+            commands.despawn(entity);
+            continue;
+        }
+
+        let progress = effect.timer.elapsed / effect.timer.duration;
+        sprite.size = effect.size * (1.0 + effect.grow * progress);
+
+        if let Some(target) = effect.target {
+            if let Some((_, target_pos)) = positions.iter().find(|(e, _)| *e == target) {
+                pos.0 = target_pos.0;
+                pos.1 = target_pos.1;
+            }
+        }
+    }
+}
+
+// spotted event state holds an event reader for spotted events
+#[derive(Default)]
+pub struct SpottedEventState {
+    pub event_reader: EventReader<SpottedEvent>,
+}
+
+// spotted feedback system
+// lets the player know whenever a hostile spots one of the squad
+pub fn spotted_feedback_system(mut state: ResMut<SpottedEventState>, events: Res<Events<SpottedEvent>>, locale: Res<Locale>,
+    mut notifications: Query<(&mut Notification, &mut Text)>) {
+    if state.event_reader.iter(&events).count() == 0 {
+        return;
+    }
+    for (mut notification, mut text) in &mut notifications.iter() {
+        text.value = locale.get("squad_spotted");
+        notification.timer.reset();
+    }
+}
+
+// notification system
+// clears the notification once it's been shown for long enough
+pub fn notification_system(time: Res<FrameTime>, mut query: Query<(&mut Notification, &mut Text)>) {
+    for (mut notification, mut text) in &mut query.iter() {
+        notification.timer.tick(time.delta_seconds);
+        if notification.timer.finished && !text.value.is_empty() {
+            text.value = "".to_string();
+        }
+    }
+}
@@ -0,0 +1,281 @@
+// headless module
+// the headless simulation, for running encounters without a window
+use bevy::prelude::*;
+use bevy::diagnostic::Diagnostics;
+use std::collections::VecDeque;
+use std::fs;
+use std::sync::{Arc, Mutex};
+
+use crate::{
+    BEHAVIOUR_STAGE, GameRng, HEADLESS_HOSTILE_HEALTH, Id, Position, RunSeed, Size, SQUAD_POSITIONS, STARTING_MEDKITS,
+    Velocity,
+};
+use crate::actions::ActionsPlugin;
+use crate::animation::SpriteData;
+use crate::behaviour::{Behaviour, BehaviourPlugin, Pathfinder, TilePos};
+use crate::collision::{CollisionPlugin, get_person_body_components};
+use crate::combat::{CombatPlugin, Health, Medkits};
+use crate::config::GameConfig;
+use crate::control::{CommandType, move_controlled_system, RallyPoint};
+use crate::map::MapPlugin;
+use crate::moving::MovingPlugin;
+use crate::nerve::Nerve;
+use crate::perception::{Perception, PerceptionPlugin};
+use crate::person::{AttitudeType, Command, Controlled, Person, spawn_squad_member};
+use crate::stamina::StaminaPlugin;
+use crate::tick::{AddTickSystem, PipelinePlugin};
+
+// headless simulation
+// runs the simulation without a window, rendering or audio, for a fixed number of ticks
+// every tick advances time by the same amount, and the random number generator is seeded,
+// so the same scenario always plays out the same way. this is meant for automated testing
+// run with `cargo run -- --headless <ticks> [commands file]`
+// the commands file has one command per line, in the form
+//   <tick> <squad index> move <x> <y>
+//   <tick> <squad index> attack <x> <y>
+// where attack goes after whoever is nearest the point
+// lines starting with # are ignored
+
+// headless command
+// a command given to a squad member at a certain tick
+pub struct HeadlessCommand {
+    pub tick: usize,
+    pub squad_pos: i32,
+    // either move or attack
+    pub command_type: CommandType,
+    pub target_point: (f32, f32),
+}
+
+// headless scenario resource
+// everything needed to run a headless simulation
+pub struct HeadlessScenario {
+    // number of ticks to run for
+    pub ticks: usize,
+    // commands to give out, in order of tick
+    pub commands: VecDeque<HeadlessCommand>,
+    // seed for the random number generator
+    pub seed: u64,
+    // the current tick
+    pub tick: usize,
+    // the config to run the simulation with
+    pub config: GameConfig,
+}
+
+impl HeadlessScenario {
+    // reads the headless scenario from the command line arguments
+    // gives None if headless mode wasn't asked for
+    pub fn from_args(args: Vec<String>) -> Option<Self> {
+        let index = args.iter().position(|arg| arg == "--headless")?;
+        let ticks = args.get(index + 1).and_then(|n| n.parse().ok()).unwrap_or(600);
+        let commands = match args.get(index + 2).map(|path| (path, fs::read_to_string(path))) {
+            Some((_path, Ok(text))) => HeadlessScenario::parse_commands(&text),
+            Some((path, Err(err))) => {
+                eprintln!("could not read headless commands from {}: {}", path, err);
+                VecDeque::new()
+            },
+            None => VecDeque::new(),
+        };
+        Some(HeadlessScenario::new(ticks, commands))
+    }
+
+    // creates a new headless scenario
+    pub fn new(ticks: usize, commands: VecDeque<HeadlessCommand>) -> Self {
+        HeadlessScenario {
+            ticks: ticks,
+            commands: commands,
+            seed: 0,
+            tick: 0,
+            config: GameConfig::default(),
+        }
+    }
+
+    // reads commands from the text of a commands file
+    // bad lines are reported and skipped
+    pub fn parse_commands(text: &str) -> VecDeque<HeadlessCommand> {
+        let mut commands: Vec<HeadlessCommand> = Vec::new();
+        for line in text.lines().map(|line| line.trim()) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            match HeadlessScenario::parse_command(line) {
+                Ok(command) => commands.push(command),
+                Err(err) => eprintln!("bad headless command: {} ({})", line, err),
+            }
+        }
+        // commands are given out in order of tick
+        commands.sort_by_key(|command| command.tick);
+        commands.into_iter().collect()
+    }
+
+    // reads a single line of a commands file
+    fn parse_command(line: &str) -> Result<HeadlessCommand, String> {
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        let (tick, squad_pos, command_type, x, y) = match parts.as_slice() {
+            [tick, squad_pos, "move", x, y] => (tick, squad_pos, CommandType::Move, x, y),
+            [tick, squad_pos, "attack", x, y] => (tick, squad_pos, CommandType::Attack, x, y),
+            _ => return Err("expected <tick> <squad index> move|attack <x> <y>".to_string()),
+        };
+        Ok(HeadlessCommand {
+            tick: tick.parse().map_err(|_| format!("bad tick: {}", tick))?,
+            squad_pos: squad_pos.parse().map_err(|_| format!("bad squad index: {}", squad_pos))?,
+            command_type: command_type,
+            target_point: (x.parse().map_err(|_| format!("bad x: {}", x))?, y.parse().map_err(|_| format!("bad y: {}", y))?),
+        })
+    }
+}
+
+// headless report resource
+// what the world looked like at the end of a headless simulation
+#[derive(Default, Debug, Clone)]
+pub struct HeadlessReport {
+    // final position of each squad member, by squad index
+    pub squad: Vec<(i32, (f32, f32))>,
+    // final position of each hostile
+    pub hostiles: Vec<(f32, f32)>,
+    // the config the simulation ran with, needed to find the tiles people ended up on
+    pub config: GameConfig,
+}
+
+impl HeadlessReport {
+    // gives the tile a squad member ended up on
+    pub fn squad_tile(&self, squad_pos: i32) -> Option<TilePos> {
+        self.squad.iter()
+            .find(|(p, _)| *p == squad_pos)
+            .map(|(_, pos)| TilePos::from_coords(&self.config, pos.0, pos.1))
+    }
+    // prints the report
+    pub fn print(&self) {
+        for (squad_pos, pos) in self.squad.iter() {
+            let tile = TilePos::from_coords(&self.config, pos.0, pos.1);
+            println!("squad {}: ({:.1}, {:.1}) tile ({}, {})", squad_pos, pos.0, pos.1, tile.0, tile.1);
+        }
+        println!("hostiles remaining: {}", self.hostiles.len());
+    }
+}
+
+// runs a headless simulation of the given scenario and reports how it ended up
+pub fn run_headless(scenario: HeadlessScenario) -> HeadlessReport {
+    let report = Arc::new(Mutex::new(HeadlessReport {
+        config: scenario.config.clone(),
+        ..Default::default()
+    }));
+    let output = report.clone();
+    let seed = scenario.seed;
+    let config = scenario.config.clone();
+
+    App::build()
+        // time is advanced by a fixed step each tick instead of by the clock
+        .add_resource(Time::default())
+        .add_resource(Diagnostics::default())
+        .add_resource(RunSeed(seed))
+        .add_resource(GameRng::new(seed))
+        .add_resource(config)
+        .add_resource(scenario)
+        // the stages each tick goes through
+        .add_plugin(PipelinePlugin)
+        // physics
+        .add_plugin(CollisionPlugin)
+        // the simulation itself
+        .add_plugin(MapPlugin)
+        .add_plugin(MovingPlugin)
+        .add_plugin(ActionsPlugin)
+        .add_plugin(CombatPlugin)
+        .add_plugin(StaminaPlugin)
+        .add_plugin(BehaviourPlugin)
+        .add_plugin(PerceptionPlugin)
+        // turn commands into actions
+        // there's no rally point in a headless run, but commands look for one
+        .init_resource::<RallyPoint>()
+        .add_tick_system_to_stage(BEHAVIOUR_STAGE, move_controlled_system.system())
+        // spawn in the people, without any sprites
+        .add_startup_system(add_headless_people.system())
+        // give out commands, the simulation runs one tick every update
+        .add_system_to_stage(stage::FIRST, headless_tick_system.system())
+        // run the simulation for the number of ticks in the scenario
+        .set_runner(move |mut app: App| {
+            let ticks = app.resources.get::<HeadlessScenario>().unwrap().ticks;
+            for _ in 0..ticks {
+                app.update();
+            }
+            // write up how the world ended up
+            let mut report = output.lock().unwrap();
+            for (controlled, pos) in &mut app.world.query::<(&Controlled, &Position)>().iter() {
+                report.squad.push((controlled.squad_pos, (pos.0, pos.1)));
+            }
+            for (pers, pos) in &mut app.world.query::<(&Person, &Position)>().iter() {
+                if pers.attitude == AttitudeType::Hostile {
+                    report.hostiles.push((pos.0, pos.1));
+                }
+            }
+            report.squad.sort_by_key(|(squad_pos, _)| *squad_pos);
+        })
+        .run();
+
+    let report = report.lock().unwrap().clone();
+    report
+}
+
+// headless tick system
+// gives out the commands due this tick
+// every update is a tick of the simulation, the same length as a tick of the game, so they play out the same
+pub fn headless_tick_system(mut scenario: ResMut<HeadlessScenario>, mut query: Query<&mut Controlled>, mut people: Query<(&Id, &Person, &Position)>) {
+    while scenario.commands.front().map_or(false, |command| command.tick <= scenario.tick) {
+        let command = scenario.commands.pop_front().unwrap();
+        // attacks go after the hostile nearest the point
+        let target_id = match command.command_type {
+            CommandType::Attack => {
+                let mut nearest: Option<(String, f32)> = None;
+                for (id, pers, pos) in &mut people.iter() {
+                    let dist = Vec2::new(pos.0 - command.target_point.0, pos.1 - command.target_point.1).length();
+                    if pers.attitude == AttitudeType::Hostile && nearest.as_ref().map_or(true, |(_, nearest)| dist < *nearest) {
+                        nearest = Some((id.id(), dist));
+                    }
+                }
+                nearest.map(|(id, _)| id)
+            },
+            _ => None,
+        };
+        for mut state in &mut query.iter() {
+            if state.squad_pos == command.squad_pos {
+                state.current_command = Command {
+                    command_type: command.command_type,
+                    target_point: Some(command.target_point),
+                    target_id: target_id.clone(),
+                    formation_offset: None,
+                };
+            }
+        }
+    }
+    scenario.tick += 1;
+}
+
+// add headless people startup system
+// spawns in the same people as the game, minus anything that needs rendering
+pub fn add_headless_people(mut commands: Commands, config: Res<GameConfig>) {
+    for (i, &position) in SQUAD_POSITIONS.iter().enumerate() {
+        spawn_squad_member(&config, &mut commands, i as i32, position);
+        commands
+            .with(Medkits(STARTING_MEDKITS))
+            // animation is never run, but actions still set the animation type
+            .with(SpriteData::new());
+        // the player doesn't wander off on their own
+        if i > 0 {
+            commands.with(Behaviour::default());
+        }
+    }
+
+    // kept clear of where the squad starts
+    for &position in [(700.0, 400.0), (400.0, 200.0)].iter() {
+        commands
+            .spawn((Id::new(), Person::new(AttitudeType::Hostile), Position(position.0, position.1), Velocity(0.0, 0.0)))
+            // hostiles can be worn down, so a run can be judged by how many are left
+            .with(Health::new(HEADLESS_HOSTILE_HEALTH))
+            .with(Nerve::new())
+            .with(Size(10.0, 10.0))
+            .with_bundle(get_person_body_components(&config, position, (10.0, 10.0), &AttitudeType::Hostile))
+            .with(SpriteData::new())
+            .with(Behaviour::default())
+            .with(Perception::default())
+            .with(Pathfinder::default());
+    }
+}
@@ -0,0 +1,126 @@
+// help module
+// the list of every key binding, shown with the help key
+use bevy::prelude::*;
+
+use crate::control::{get_key_name, InputState, KeyBindings};
+use crate::locale::Locale;
+use crate::mods::ModAssets;
+
+// help plugin
+// responsible for the key binding help, listing every key binding grouped by what they're for
+// the list is made from the key bindings resource the controls read their keys from, so it always shows the keys as they are
+// the key bindings are added by the control plugin, so this goes after it
+pub struct HelpPlugin;
+
+// implementation of the plugin trait,
+// required for this to be used as a plugin
+impl Plugin for HelpPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        // initialise the help, hidden
+        app.init_resource::<Help>()
+        // add in the help text
+        .add_startup_system(setup_help_system.system())
+        // add in the system showing and hiding the help
+        .add_system(help_key_system.system())
+        // add in the system writing out the help
+        .add_system(help_text_system.system());
+    }
+}
+
+// settings for the key binding help
+// the help key shows and hides the list of every key binding, grouped by what they're for
+pub static HELP_KEY: KeyCode = KeyCode::F1;
+// the help is split into columns this far apart, so it fits on the screen
+pub static HELP_COLUMNS: usize = 2;
+pub static HELP_COLUMN_WIDTH: f32 = 400.0;
+pub static HELP_FONT_SIZE: f32 = 12.0;
+
+// help resource
+// whether the key binding help is being shown
+#[derive(Default)]
+pub struct Help {
+    pub open: bool,
+}
+
+// help text component
+// spawn this component along with each column of the help text, with the index of the column
+pub struct HelpText(pub usize);
+
+// setup help system
+// spawns in the columns of the help text across the top of the screen, empty until the help is shown
+pub fn setup_help_system(mut commands: Commands, asset_server: Res<AssetServer>, mods: Res<ModAssets>) {
+    let font_handle = asset_server.load(mods.resolve("assets/fonts/LiberationMono-Regular.ttf")).unwrap();
+    for column in 0..HELP_COLUMNS {
+        commands
+            .spawn(TextComponents {
+                style: Style {
+                    // below the fps counter
+                    position_type: PositionType::Absolute,
+                    position: Rect {
+                        left: Val::Px(5.0 + column as f32 * HELP_COLUMN_WIDTH),
+                        top: Val::Px(30.0),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                text: Text {
+                    value: "".to_string(),
+                    font: font_handle,
+                    style: TextStyle {
+                        font_size: HELP_FONT_SIZE,
+                        color: Color::BLACK,
+                    },
+                },
+                ..Default::default()
+            })
+            .with(HelpText(column));
+    }
+}
+
+// help key system
+// shows or hides the help when the help key is pressed
+pub fn help_key_system(inputs: Res<InputState>, bindings: Res<KeyBindings>, mut help: ResMut<Help>) {
+    if inputs.key_just_presses.contains(&bindings.help) {
+        help.open = !help.open;
+    }
+}
+
+// help text system
+// writes out every key binding while the help is shown, a group at a time,
+// moving on to the next column once a group would take a column past its share of the lines
+pub fn help_text_system(help: Res<Help>, bindings: Res<KeyBindings>, locale: Res<Locale>, mut query: Query<(&HelpText, &mut Text)>) {
+    let mut columns = vec![String::new(); HELP_COLUMNS];
+    if help.open {
+        let mut groups = Vec::new();
+        for (group, group_bindings) in bindings.groups().iter() {
+            let mut lines = vec![locale.get(group)];
+            for binding in group_bindings.iter() {
+                let inputs: Vec<String> = binding.inputs.iter().map(|input| input.name(&locale)).collect();
+                lines.push(format!("  {}: {}", inputs.join(" + "), locale.get(binding.action)));
+            }
+            lines.push("".to_string());
+            groups.push(lines);
+        }
+        let total: usize = groups.iter().map(|lines| lines.len()).sum();
+        let share = (total + HELP_COLUMNS - 1) / HELP_COLUMNS;
+        let mut column = 0;
+        let mut filled = 0;
+        columns[0] = locale.format("help_title", &[&get_key_name(bindings.help)]) + "\n\n";
+        for lines in groups {
+            if filled > 0 && filled + lines.len() > share && column + 1 < HELP_COLUMNS {
+                column += 1;
+                filled = 0;
+            }
+            filled += lines.len();
+            columns[column].push_str(&lines.join("\n"));
+            columns[column].push('\n');
+        }
+    }
+    for (help_text, mut text) in &mut query.iter() {
+        if let Some(value) = columns.get(help_text.0) {
+            if text.value != *value {
+                text.value = value.clone();
+            }
+        }
+    }
+}
@@ -0,0 +1,271 @@
+// inspector module
+// the entity inspector
+use bevy::prelude::*;
+use bevy_rapier2d::physics::RigidBodyHandleComponent;
+use bevy_rapier2d::rapier::dynamics::RigidBodySet;
+use bevy_rapier2d::rapier::math::Isometry;
+
+use crate::{CONSOLE_FONT_SIZE, Id, INSPECTOR_LINES, Position, Velocity};
+use crate::behaviour::{Behaviour, BehaviourType, Pathfinder};
+use crate::combat::Health;
+use crate::config::GameConfig;
+use crate::console::{Console, ConsoleCommand, ConsoleRegistry};
+use crate::control::{InputState, KeyBindings};
+use crate::draw::get_translate_from_position;
+use crate::mods::ModAssets;
+use crate::nerve::{Action, Nerve};
+use crate::person::Person;
+
+// inspector plugin
+// responsible for the entity inspector, a panel listing the people in the mission
+// and showing what one of them is doing, for tuning the ai without printing everything out
+// the inspected person is edited from the console, so this goes after the console plugin
+pub struct InspectorPlugin;
+
+// implementation of the plugin trait,
+// required for this to be used as a plugin
+impl Plugin for InspectorPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        // add in the console commands for picking and editing who's inspected
+        {
+            let mut registry = app.resources_mut().get_mut::<ConsoleRegistry>().expect("the inspector needs the console plugin added first");
+            registry.register("inspect", "inspect <id>, picks who's inspected by the start of their id", 1);
+            registry.register("edit", "edit <position x y|velocity x y|health amount|behaviour name|action clear>", 2);
+        }
+        app.init_resource::<Inspector>()
+        .init_resource::<InspectorEditState>()
+        // add in the inspector text
+        .add_startup_system(setup_inspector_system.system())
+        // add in the system opening the inspector and picking who's inspected
+        .add_system(inspector_key_system.system())
+        // add in the system carrying out edits made from the console
+        .add_system(inspector_edit_system.system())
+        // add in the system showing the inspector
+        .add_system(inspector_text_system.system());
+    }
+}
+
+// inspector resource
+// whether the inspector is open, and the id of whoever is being inspected
+#[derive(Default)]
+pub struct Inspector {
+    pub open: bool,
+    pub selected: Option<String>,
+}
+
+// inspector text component
+// spawn this component along with the text showing the inspector
+pub struct InspectorText;
+
+// inspector edit state holds an event reader for console commands editing whoever is inspected
+#[derive(Default)]
+pub struct InspectorEditState {
+    pub event_reader: EventReader<ConsoleCommand>,
+}
+
+// setup inspector system
+// spawns in the inspector text down the right of the screen, empty until the inspector is opened
+pub fn setup_inspector_system(mut commands: Commands, asset_server: Res<AssetServer>, mods: Res<ModAssets>) {
+    let font_handle = asset_server.load(mods.resolve("assets/fonts/LiberationMono-Regular.ttf")).unwrap();
+    commands
+        .spawn(TextComponents {
+            style: Style {
+                // below the wave counter
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    right: Val::Px(5.0),
+                    top: Val::Px(30.0),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            text: Text {
+                value: "".to_string(),
+                font: font_handle,
+                style: TextStyle {
+                    font_size: CONSOLE_FONT_SIZE,
+                    color: Color::BLACK,
+                },
+            },
+            ..Default::default()
+        })
+        .with(InspectorText);
+}
+
+// inspector key system
+// opens and closes the inspector, and goes through the people in it, in order of id
+pub fn inspector_key_system(inputs: Res<InputState>, bindings: Res<KeyBindings>, mut inspector: ResMut<Inspector>, mut people: Query<(&Id, &Person)>) {
+    if inputs.key_just_presses.contains(&bindings.inspector) {
+        inspector.open = !inspector.open;
+    }
+    if !inspector.open {
+        return;
+    }
+    let step = if inputs.key_just_presses.contains(&bindings.inspector_next) {
+        1
+    }else if inputs.key_just_presses.contains(&bindings.inspector_prev) {
+        -1
+    }else{
+        return;
+    };
+
+    let mut ids: Vec<String> = Vec::new();
+    for (id, _pers) in &mut people.iter() {
+        ids.push(id.id());
+    }
+    if ids.is_empty() {
+        return;
+    }
+    ids.sort();
+    let index = inspector.selected.as_ref()
+        .and_then(|selected| ids.iter().position(|id| id == selected))
+        .map_or(0, |index| (index as i32 + step).rem_euclid(ids.len() as i32) as usize);
+    inspector.selected = Some(ids[index].clone());
+}
+
+// inspector edit system
+// carries out the inspect and edit console commands
+pub fn inspector_edit_system(config: Res<GameConfig>, mut state: ResMut<InspectorEditState>, events: Res<Events<ConsoleCommand>>,
+    mut console: ResMut<Console>, mut inspector: ResMut<Inspector>, mut bodies: ResMut<RigidBodySet>,
+    mut people: Query<(&Id, &Person, &mut Position, Option<&mut Velocity>, Option<&mut Health>, Option<&mut Behaviour>, Option<&mut Nerve>, Option<&RigidBodyHandleComponent>)>) {
+    let received: Vec<ConsoleCommand> = state.event_reader.iter(&events).cloned().collect();
+    for command in received {
+        let args: Vec<&str> = command.args.iter().map(|arg| arg.as_str()).collect();
+        match (command.name.as_str(), args.as_slice()) {
+            ("inspect", [prefix, ..]) => {
+                let mut found = None;
+                for (id, _pers, _pos, _vel, _health, _behaviour, _nerve, _body) in &mut people.iter() {
+                    if id.0.starts_with(prefix) {
+                        found = Some(id.id());
+                        break;
+                    }
+                }
+                match found {
+                    Some(id) => {
+                        console.print(format!("inspecting {}", id));
+                        inspector.selected = Some(id);
+                        inspector.open = true;
+                    },
+                    None => console.print(format!("no one with an id starting {}", prefix)),
+                }
+            },
+            ("edit", [field, values @ ..]) => {
+                let selected = match &inspector.selected {
+                    Some(selected) => selected.clone(),
+                    None => {
+                        console.print("no one is being inspected".to_string());
+                        continue;
+                    },
+                };
+                let numbers: Vec<f32> = values.iter().filter_map(|value| value.parse().ok()).collect();
+                let mut result = Err(format!("can't edit {}", field));
+                for (id, _pers, mut pos, vel, health, behaviour, nerve, body) in &mut people.iter() {
+                    if id.0 != selected {
+                        continue;
+                    }
+                    result = match (*field, numbers.as_slice(), values.first()) {
+                        ("position", [x, y], _) => {
+                            pos.0 = *x;
+                            pos.1 = *y;
+                            // move the physics body too, otherwise it would move them back
+                            if let Some(body) = body.and_then(|body| bodies.get_mut(body.handle())) {
+                                let translation = get_translate_from_position(&config, *x, *y);
+                                body.set_position(Isometry::translation(translation.0, translation.1));
+                            }
+                            Ok(())
+                        },
+                        ("velocity", [x, y], _) => match vel {
+                            Some(mut vel) => {
+                                vel.0 = *x;
+                                vel.1 = *y;
+                                Ok(())
+                            },
+                            None => Err("they have no velocity".to_string()),
+                        },
+                        ("health", [amount], _) => match health {
+                            Some(mut health) => {
+                                health.current = amount.min(health.max);
+                                Ok(())
+                            },
+                            None => Err("they have no health".to_string()),
+                        },
+                        ("behaviour", _, Some(name)) => match (behaviour, BehaviourType::from_name(name)) {
+                            (Some(mut behaviour), Some(kind)) => {
+                                behaviour.current_behaviour = kind;
+                                Ok(())
+                            },
+                            (None, _) => Err("they have no behaviour".to_string()),
+                            (_, None) => Err(format!("unknown behaviour {}", name)),
+                        },
+                        ("action", _, Some(&"clear")) => match nerve {
+                            Some(mut nerve) => {
+                                nerve.current_action = Action::default();
+                                nerve.action_queue.clear();
+                                Ok(())
+                            },
+                            None => Err("they have no actions".to_string()),
+                        },
+                        _ => Err(format!("usage: edit <position x y|velocity x y|health amount|behaviour name|action clear>, not {}", args.join(" "))),
+                    };
+                    break;
+                }
+                match result {
+                    Ok(()) => console.print(format!("set {} to {}", field, values.join(" "))),
+                    Err(err) => console.print(err),
+                }
+            },
+            _ => {},
+        }
+    }
+}
+
+// inspector text system
+// lists the people around whoever is inspected, then shows what the inspected person is doing
+pub fn inspector_text_system(inspector: Res<Inspector>, bindings: Res<KeyBindings>, mut people: Query<(&Id, &Person, &Position, Option<&Velocity>, Option<&Health>, Option<&Nerve>, Option<&Behaviour>, Option<&Pathfinder>)>,
+    mut query: Query<(&InspectorText, &mut Text)>) {
+    let mut value = String::new();
+    if inspector.open {
+        let mut entries = Vec::new();
+        let mut details = Vec::new();
+        for (id, pers, pos, vel, health, nerve, behaviour, pathfinder) in &mut people.iter() {
+            let selected = inspector.selected.as_ref() == Some(&id.0);
+            // ids are long, the start of one is enough to tell people apart
+            entries.push((id.id(), format!("{} {} {:?} ({:.0}, {:.0})", if selected { ">" } else { " " }, &id.0[..8.min(id.0.len())], pers.attitude, pos.0, pos.1)));
+            if !selected {
+                continue;
+            }
+            details.push(format!("id {}", id.0));
+            details.push(format!("position {:.1} {:.1}", pos.0, pos.1));
+            if let Some(vel) = vel {
+                details.push(format!("velocity {:.1} {:.1}", vel.0, vel.1));
+            }
+            if let Some(health) = health {
+                details.push(format!("health {:.1}/{:.1}", health.current, health.max));
+            }
+            if let Some(nerve) = nerve {
+                let action = &nerve.current_action;
+                details.push(format!("action {:?} point {:?} target {:?}, {} queued", action.action_type, action.target.0, action.target.1, nerve.action_queue.len()));
+            }
+            if let Some(behaviour) = behaviour {
+                details.push(format!("behaviour {:?} {:?}", behaviour.current_behaviour_set, behaviour.current_behaviour));
+            }
+            if let Some(pathfinder) = pathfinder {
+                let status = if pathfinder.needs_pathfinding { "waiting" } else if pathfinder.path_ready { "following" } else { "idle" };
+                details.push(format!("pathfinder {}, waypoint {}/{}, goal ({:.0}, {:.0})", status, pathfinder.path_index, pathfinder.path.len(),
+                    pathfinder.real_goal.0, pathfinder.real_goal.1));
+            }
+        }
+        // only the people around whoever is inspected are listed
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+        let index = entries.iter().position(|(id, _)| inspector.selected.as_ref() == Some(id)).unwrap_or(0);
+        let start = index.saturating_sub(INSPECTOR_LINES / 2).min(entries.len().saturating_sub(INSPECTOR_LINES));
+        let mut lines = vec![format!("{} people, {:?}/{:?} to pick", entries.len(), bindings.inspector_prev, bindings.inspector_next)];
+        lines.extend(entries.into_iter().skip(start).take(INSPECTOR_LINES).map(|(_, line)| line));
+        lines.push("".to_string());
+        lines.extend(details);
+        value = lines.join("\n");
+    }
+    for (_inspector, mut text) in &mut query.iter() {
+        text.value = value.clone();
+    }
+}
@@ -0,0 +1,774 @@
+// mercenaries library
+// the game is split into plugins, each public module holding a plugin along with the components,
+// resources and settings it works on, so they can be reused and tested on their own
+// the plugins are exported from here, everything else is used from the module it lives in
+// imports from bevy engine
+use bevy::{
+    diagnostic::{Diagnostics, FrameTimeDiagnosticsPlugin},
+    prelude::*,
+};
+// imports for id generation
+use uuid::Uuid;
+// imports for random number generator
+use rand::{Rng, SeedableRng, rngs::StdRng};
+
+use crate::combat::DamageType;
+use crate::draw::WorldCamera;
+use crate::locale::Locale;
+// default settings for window width/height, tile size, map and pathfinding
+// these are only used when the config file and command line don't say otherwise
+pub static CONFIG_PATH: &str = "assets/config.txt";
+pub static DEFAULT_WINDOW_WIDTH: f32 = 800.0;
+pub static DEFAULT_WINDOW_HEIGHT: f32 = 450.0;
+pub static DEFAULT_TILE_SIZE: f32 = 10.0;
+pub static DEFAULT_MAP_PATH: &str = "assets/maps/ortho-map.tmx";
+// the size of the map, in pixels, which can be bigger than the window
+pub static DEFAULT_MAP_WIDTH: f32 = 800.0;
+pub static DEFAULT_MAP_HEIGHT: f32 = 450.0;
+pub static DEFAULT_MAX_PATHFINDERS: usize = 10;
+// how many of each kind of pooled entity are spawned up front
+pub static DEFAULT_PROJECTILE_POOL_SIZE: usize = 64;
+pub static DEFAULT_PARTICLE_POOL_SIZE: usize = 256;
+// the volume of each category of sound, from 0.0 (muted) to 1.0 (full volume)
+pub static DEFAULT_MASTER_VOLUME: f32 = 1.0;
+pub static DEFAULT_COMBAT_VOLUME: f32 = 1.0;
+pub static DEFAULT_MOVEMENT_VOLUME: f32 = 0.4;
+pub static DEFAULT_COMMAND_VOLUME: f32 = 0.8;
+pub static DEFAULT_MUSIC_VOLUME: f32 = 0.5;
+pub static PLAYER_Z_LEVEL: f32 = 10.0;
+// the stages every frame goes through, in this order, after events are updated
+// input is read and turned into orders, then the simulation runs once for every tick due this frame
+pub static INPUT_STAGE: &str = "input";
+pub static COMMAND_STAGE: &str = "command";
+pub static SIMULATION_STAGE: &str = "simulation";
+// the stages every tick of the simulation goes through, in this order
+// orders and behaviours are turned into actions, and actions carried out, paths are found for whoever needs one,
+// everything moves and the physics world steps, then the rest of the simulation (combat, perception, encounters) runs
+pub static BEHAVIOUR_STAGE: &str = "behaviour";
+pub static ACTION_STAGE: &str = "action";
+pub static PATHFINDING_STAGE: &str = "pathfinding";
+pub static MOVEMENT_STAGE: &str = "movement";
+pub static PHYSICS_STAGE: &str = "physics";
+pub static TICK_UPDATE_STAGE: &str = "tick_update";
+// how much time passes each tick of the simulation, in seconds
+// this is also the step the physics world takes each tick
+pub static SIM_TICK: f32 = 1.0 / 60.0;
+// the most ticks run in one frame to catch up when frames fall behind
+// anything further behind than this is dropped, and the simulation slows down instead of piling up ticks
+pub static SIM_MAX_CATCH_UP_TICKS: u32 = 4;
+// how much health hostiles have in a headless run
+pub static HEADLESS_HOSTILE_HEALTH: f32 = 30.0;
+// anything moved further than this in a single tick is drawn straight where it ended up instead of sliding there
+pub static INTERPOLATION_SNAP_DISTANCE: f32 = 64.0;
+// settings for the display mode
+// holding alt and pressing the display mode key goes from windowed, to borderless, to exclusive fullscreen
+// the choice is kept in the settings file, so the game starts in it next time
+pub static SETTINGS_PATH: &str = "settings.txt";
+pub static DISPLAY_MODE_MODIFIERS: [KeyCode; 2] = [KeyCode::LAlt, KeyCode::RAlt];
+pub static DISPLAY_MODE_KEY: KeyCode = KeyCode::Return;
+// settings for the camera
+// holding the scroll keys, up, down, left and right, scrolls the view around maps bigger than the window
+pub static CAMERA_SCROLL_KEYS: [KeyCode; 4] = [KeyCode::Up, KeyCode::Down, KeyCode::Left, KeyCode::Right];
+// how far the view scrolls every second
+pub static CAMERA_SCROLL_SPEED: f32 = 400.0;
+// settings for the colour palette
+// holding alt and pressing the palette key goes through the palettes, including the colourblind-safe ones
+pub static PALETTE_KEY: KeyCode = KeyCode::K;
+pub static PALETTE_NAMES: [&str; 4] = ["default", "deuteranopia", "protanopia", "tritanopia"];
+// settings for the language
+// every string the player sees is read from the language files, one file per language, named after it
+// holding alt and pressing the language key goes through the languages there are files for
+// strings missing from a language fall back on the default language's
+pub static LANG_PATH: &str = "assets/lang";
+pub static DEFAULT_LANGUAGE: &str = "en";
+pub static LANGUAGE_KEY: KeyCode = KeyCode::L;
+pub static SCRIPTS_PATH: &str = "assets/scripts";
+// settings for the limits put on scripts, so a broken or runaway script can't hang the game or eat its memory
+// a script call that goes over any of these is stopped, and the error printed
+pub static SCRIPT_MAX_OPERATIONS: u64 = 100_000;
+pub static SCRIPT_MAX_CALL_LEVELS: usize = 32;
+pub static SCRIPT_MAX_EXPR_DEPTH: usize = 64;
+pub static SCRIPT_MAX_FUNCTION_EXPR_DEPTH: usize = 32;
+pub static SCRIPT_MAX_STRING_SIZE: usize = 1024;
+pub static SCRIPT_MAX_ARRAY_SIZE: usize = 1024;
+pub static SCRIPT_MAX_MAP_SIZE: usize = 256;
+// how many actions and spawns all scripts together can queue up each tick, anything past this is dropped
+pub static SCRIPT_MAX_COMMANDS: usize = 256;
+pub static ARCHETYPES_PATH: &str = "assets/archetypes.txt";
+pub static MODS_PATH: &str = "mods";
+// settings for pooled projectiles
+// how fast the projectile fired by each ranged attack flies, and how far off course, in radians, a miss goes
+pub static PROJECTILE_SPEED: f32 = 600.0;
+pub static PROJECTILE_MISS_ANGLE: f32 = 0.15;
+// settings for how long knockback takes to wear off, in seconds
+pub static KNOCKBACK_DURATION: f32 = 0.3;
+// settings for music crossfading, in seconds
+pub static MUSIC_FADE_DURATION: f32 = 2.0;
+pub static MUSIC_COMBAT_COOLDOWN: f32 = 8.0;
+// settings for how quickly sounds get quieter with distance from the camera
+// at this distance a sound is heard at half volume
+pub static HEARING_FALLOFF: f32 = 400.0;
+// settings for how far apart squad members stand in formation, and the key going through the formations
+pub static FORMATION_SPACING: f32 = 20.0;
+pub static FORMATION_KEY: KeyCode = KeyCode::F;
+// settings for idle squadmates following the player
+// they only set off once they're this far from their spot in the formation,
+// and stop once they're back within the stop distance
+pub static FOLLOW_START_DISTANCE: f32 = 40.0;
+pub static FOLLOW_STOP_DISTANCE: f32 = 10.0;
+// settings for the rally point
+// hold the rally key and left click to set the rally point, or right click to clear it
+pub static RALLY_KEY: KeyCode = KeyCode::R;
+// settings for selection
+// hold control and press one of these keys to save the selection as a control group,
+// or press it on its own to select the group again
+// they're on the numpad, leaving the number keys for squad members and F1 for the help
+// holding shift while selecting adds to the selection instead of replacing it
+pub static CONTROL_GROUP_KEYS: [KeyCode; 4] = [KeyCode::Numpad1, KeyCode::Numpad2, KeyCode::Numpad3, KeyCode::Numpad4];
+pub static GROUP_SAVE_MODIFIER: KeyCode = KeyCode::LControl;
+pub static SELECTION_ADD_MODIFIER: KeyCode = KeyCode::LShift;
+// right mouse drags shorter than this are treated as clicks rather than box selects
+pub static BOX_SELECT_THRESHOLD: f32 = 4.0;
+// settings for order modifiers
+// hold the flee modifier and click to flee there, or the follow modifier and click on someone to follow them
+pub static FLEE_MODIFIER: KeyCode = KeyCode::LShift;
+pub static FOLLOW_MODIFIER: KeyCode = KeyCode::LControl;
+// settings for attack moves
+// hold the attack move key and click to attack move
+pub static ATTACK_MOVE_KEY: KeyCode = KeyCode::A;
+// the key that stops the selected squad members in their tracks
+pub static STOP_KEY: KeyCode = KeyCode::S;
+// settings for queued orders
+// how many orders a squad member can have queued up behind their current one
+pub static ORDER_QUEUE_LENGTH: usize = 8;
+// the key that takes back the last order queued for the selected squad members
+pub static UNDO_ORDER_KEY: KeyCode = KeyCode::U;
+// the key that clears the selected squad members' queued orders, as shown in the squad panel
+pub static CLEAR_QUEUE_KEY: KeyCode = KeyCode::Y;
+// settings for distractions
+// hold the distract key and click to have a selected squad member throw something there,
+// making a noise that draws hostiles over to investigate
+pub static DISTRACT_KEY: KeyCode = KeyCode::D;
+// how far a distraction can be thrown, how fast it flies and how high its arc goes
+pub static THROW_RANGE: f32 = 150.0;
+pub static THROW_SPEED: f32 = 200.0;
+pub static THROW_ARC_HEIGHT: f32 = 20.0;
+// how far away the distraction can be heard when it lands
+pub static DISTRACTION_NOISE_RADIUS: f32 = 120.0;
+// settings for grenades
+// hold the grenade key and click to have a selected squad member throw a grenade there,
+// which goes off once its fuse burns down, thrown as far and as fast as distractions
+pub static GRENADE_KEY: KeyCode = KeyCode::G;
+pub static GRENADE_FUSE: f32 = 2.0;
+// how far the blast reaches, and the damage and knockback at its centre, which fall off towards its edge
+pub static GRENADE_RADIUS: f32 = 40.0;
+pub static GRENADE_DAMAGE: f32 = 60.0;
+pub static GRENADE_KNOCKBACK: f32 = 300.0;
+// how far away the blast can be heard
+pub static GRENADE_NOISE_RADIUS: f32 = 400.0;
+// settings for overwatch
+// hold the overwatch key and click to have the selected squad members hold their ground watching that way,
+// firing at the first hostile that moves through a cone this wide, in degrees, within their weapon's range
+pub static OVERWATCH_KEY: KeyCode = KeyCode::O;
+pub static OVERWATCH_ANGLE: f32 = 60.0;
+// settings for doors
+// how close a squad member has to get to a door to open or close it, and how long that takes
+pub static DOOR_REACH: f32 = 15.0;
+pub static DOOR_INTERACT_DURATION: f32 = 0.5;
+// the furthest away an attack can be launched from
+pub static WEAPON_RANGE: f32 = 40.0;
+// settings for status effects
+// the most stacks of one kind of status effect a person can carry at once,
+// and how many seconds apart poison and burn wear health down
+pub static STATUS_MAX_STACKS: usize = 3;
+pub static STATUS_TICK: f32 = 0.5;
+// settings for sneaking
+// the key toggling sneaking for the selected squad members, and how much sneaking
+// scales their speed, the noise they make and how far away they can be seen from
+pub static SNEAK_KEY: KeyCode = KeyCode::Z;
+pub static SNEAK_SPEED: f32 = 0.5;
+pub static SNEAK_NOISE: f32 = 0.5;
+pub static SNEAK_VISIBILITY: f32 = 0.5;
+// how many seconds a hostile has to watch a sneaking squad member before spotting them,
+// and how quickly that wears off, as a fraction of the way to spotting them per second, once they're out of sight
+pub static SNEAK_DETECTION_TIME: f32 = 1.5;
+pub static DETECTION_DECAY: f32 = 0.5;
+// how long a spawn point is marked before reinforcements arrive through it
+pub static SPAWN_TELEGRAPH_DURATION: f32 = 1.5;
+// settings for alarms
+// how far alerted hostiles will run to raise an alarm, how long raising it takes,
+// and how much further hostiles can see once the encounter is on alert
+pub static ALARM_RUN_DISTANCE: f32 = 250.0;
+pub static ALARM_RAISE_DURATION: f32 = 2.0;
+pub static ALERT_VISIBILITY: f32 = 1.5;
+// how much further hostiles that have found a body can see
+pub static WARY_VISIBILITY: f32 = 1.25;
+// settings for light and darkness
+// how bright it is away from any lights at night, the dimmest anyone can be seen in,
+// and the size of the squares the darkness overlay is drawn in
+pub static NIGHT_AMBIENT_LIGHT: f32 = 0.2;
+pub static MIN_LIGHT_VISIBILITY: f32 = 0.25;
+pub static DARKNESS_CELL_SIZE: f32 = 25.0;
+pub static DARKNESS_Z_LEVEL: f32 = 20.0;
+// how far a muzzle flash lights up around the shooter, and for how long
+pub static MUZZLE_FLASH_RADIUS: f32 = 80.0;
+pub static MUZZLE_FLASH_DURATION: f32 = 0.15;
+// settings for the day/night cycle, in hours from midnight
+// when dawn starts, when it's fully day, when dusk starts and when it's fully night
+pub static DAWN_HOUR: f32 = 5.0;
+pub static DAY_HOUR: f32 = 7.0;
+pub static DUSK_HOUR: f32 = 18.0;
+pub static NIGHT_HOUR: f32 = 20.0;
+// the time missions start at, during the day or at night, unless the encounter says otherwise
+pub static DAY_START_HOUR: f32 = 12.0;
+pub static NIGHT_START_HOUR: f32 = 23.0;
+// how many hours pass every second
+pub static WORLD_CLOCK_SPEED: f32 = 0.05;
+// the colour of the sky in full daylight, darkening as the light goes
+pub static SKY_COLOR: [f32; 3] = [0.2, 0.2, 0.8];
+// settings for the weather
+// the chances of a mission being rainy or foggy when the encounter doesn't say, otherwise it's clear
+pub static RAIN_CHANCE: f32 = 0.25;
+pub static FOG_CHANCE: f32 = 0.15;
+// how far away anyone can be seen in the rain and fog, as a fraction of how far they can be seen when it's clear
+pub static RAIN_VISIBILITY: f32 = 0.75;
+pub static FOG_VISIBILITY: f32 = 0.5;
+// how much harder it is to walk through mud, which low ground turns to in the rain
+pub static MUD_WEIGHT: f32 = 3.0;
+// and how much slower it is to walk through
+pub static MUD_SPEED: f32 = 0.7;
+// settings for water
+// generated maps have shallow water where the noise is below the shallow mark, and deep water below the deep mark,
+// water can also be painted on in tiled, see TileProperties
+pub static SHALLOW_WATER_NOISE: f64 = -0.4;
+pub static DEEP_WATER_NOISE: f64 = -0.7;
+// how much harder shallow water is to wade through than open ground, and how much slower
+pub static WADING_WEIGHT: f32 = 4.0;
+pub static WADING_SPEED: f32 = 0.4;
+// how much worse anyone wading aims
+pub static WADING_ACCURACY: f32 = 0.6;
+// health lost every second by anyone who ends up in deep water
+pub static DROWNING_DAMAGE: f32 = 15.0;
+// settings for elevation
+// tiles this much higher or lower than the tile next to them are cut off from it by a cliff,
+// heights are painted on in tiled, see TileProperties
+pub static CLIFF_HEIGHT: f32 = 2.0;
+// how much higher up than their target an attacker has to be to have the high ground
+pub static HIGH_GROUND_HEIGHT: f32 = 0.5;
+// how much further attacks from the high ground reach, and how much more often they hit
+pub static HIGH_GROUND_RANGE: f32 = 1.25;
+pub static HIGH_GROUND_ACCURACY: f32 = 1.2;
+// how far away anyone taking the vantage looks for high ground overlooking their target
+pub static VANTAGE_SEARCH_RADIUS: f32 = 150.0;
+// how many of the best spots are tried before giving up on finding a way up to any of them
+pub static VANTAGE_PATHS: usize = 5;
+// how many raindrops fall every second, and how thick the fog is
+pub static RAINDROPS_PER_SECOND: f32 = 120.0;
+pub static FOG_OPACITY: f32 = 0.35;
+// how close someone on a routine needs to be to where they rest before they settle down there
+pub static REST_DISTANCE: f32 = 15.0;
+// settings for how far noises carry
+pub static GUNSHOT_NOISE_RADIUS: f32 = 300.0;
+// how many times more of a noise's range is used up getting through a wall than crossing open ground
+pub static NOISE_WALL_MUFFLING: f32 = 5.0;
+// settings for how far away the squad notices hostiles
+pub static PERCEPTION_RADIUS: f32 = 150.0;
+// settings for how wide hostiles see, in degrees, and the key showing what they can see
+pub static VISION_CONE_ANGLE: f32 = 120.0;
+pub static VISION_CONE_KEY: KeyCode = KeyCode::C;
+// the number of dots drawn along each edge of a vision cone, and around its arc
+pub static VISION_CONE_DOTS: usize = 8;
+// settings for searching for a target that's been lost sight of
+// how long a target can be out of sight before it's lost, how far apart the tiles searched are,
+// and how long is spent looking around at each one
+pub static LOST_SIGHT_GRACE: f32 = 1.0;
+pub static SEARCH_STEP: f32 = 30.0;
+pub static SEARCH_LOOK_DURATION: f32 = 1.5;
+// settings for ambushing hostiles
+// how close the squad gets before the ambush is sprung, unless the encounter says otherwise,
+// and how close the squad needs to be to spot a hidden ambusher before then
+pub static AMBUSH_RADIUS: f32 = 80.0;
+pub static AMBUSH_SPOT_RADIUS: f32 = 40.0;
+// settings for hostile morale
+// morale starts at 100 and is lost by being hit or seeing allies die nearby
+// hostiles whose morale drops to the break point run, or surrender if they're cornered
+pub static MORALE_HIT_LOSS: f32 = 10.0;
+pub static MORALE_ALLY_DEATH_LOSS: f32 = 25.0;
+pub static MORALE_ALLY_DEATH_RADIUS: f32 = 150.0;
+pub static MORALE_BREAK: f32 = 30.0;
+// settings for suppression
+// every attack, hit or miss, suppresses the attacker's enemies close to its target,
+// building up to 100 and wearing off by so much a second
+pub static SUPPRESSION_RADIUS: f32 = 30.0;
+pub static SUPPRESSION_PER_ATTACK: f32 = 15.0;
+pub static SUPPRESSION_DECAY: f32 = 10.0;
+// how much the fully suppressed have their accuracy and speed scaled down by,
+// and how many times more likely they are to hide or defend
+pub static SUPPRESSION_ACCURACY: f32 = 0.5;
+pub static SUPPRESSION_SPEED: f32 = 0.5;
+pub static SUPPRESSION_BIAS: f32 = 4.0;
+// how suppressed someone has to be to be pinned down, and think again about what they're doing
+pub static SUPPRESSION_PINNED: f32 = 60.0;
+// settings for start points
+// spawn points in the tiled map with these names mark where the squad, and each group of hostiles, start out
+pub static PLAYER_START: &str = "player_start";
+// where each squad member starts out when the map has no start point for them, by squad index
+pub static SQUAD_POSITIONS: [(f32, f32); 4] = [(100.0, 100.0), (200.0, 400.0), (600.0, 100.0), (500.0, 100.0)];
+pub static HOSTILE_GROUP_PREFIX: &str = "hostile_group_";
+// settings for map transitions
+// exit zones are named after the stage of the mission they lead to, whose map,
+// and encounter if it has one, are the files named after the stage in these directories
+pub static STAGE_MAP_DIR: &str = "assets/maps";
+pub static STAGE_ENCOUNTER_DIR: &str = "assets/encounters";
+// settings for map tile properties
+// how much of the damage of attacks cover tiles block, for tiles marked as cover without saying how much
+pub static TILE_COVER: f32 = 0.5;
+// settings for roads and bridges
+// how much cheaper they are to cross than open ground, and how much faster they are to walk along
+pub static ROAD_COST: f32 = 0.5;
+pub static ROAD_SPEED: f32 = 1.25;
+// how far someone has to be going to be on the march, and how much harder those on the march find it
+// to leave the road, so they keep to roads where they can
+pub static MARCH_DISTANCE: f32 = 120.0;
+pub static OFF_ROAD_MARCH_WEIGHT: f32 = 2.0;
+// settings for movement
+// how fast people move by default, in pixels a second, and how far from where they're going they start slowing down
+pub static BASE_MOVE_SPEED: f32 = 137.5;
+pub static MOVE_EASE_DISTANCE: f32 = 137.5;
+// settings for stamina
+// orders given while holding the sprint key are carried out at a sprint, which wears stamina down,
+// as do melee attacks, which are those launched from up close
+// stamina comes back while standing still
+pub static SPRINT_KEY: KeyCode = KeyCode::X;
+pub static SPRINT_SPEED: f32 = 1.6;
+pub static STAMINA_MAX: f32 = 100.0;
+pub static STAMINA_SPRINT_DRAIN: f32 = 20.0;
+pub static STAMINA_MELEE_COST: f32 = 10.0;
+pub static MELEE_RANGE: f32 = 20.0;
+pub static STAMINA_REGEN: f32 = 15.0;
+// running out of stamina leaves people exhausted until this much of it has come back, as a fraction,
+// and the exhausted move slower, aim worse and are less likely to charge or flee
+pub static STAMINA_RECOVERED: f32 = 0.3;
+pub static EXHAUSTED_SPEED: f32 = 0.6;
+pub static EXHAUSTED_ACCURACY: f32 = 0.6;
+pub static EXHAUSTED_BIAS: f32 = 0.25;
+// settings for weapon switching
+// the key that has the selected squad members switch to their other weapon, and how many seconds switching takes
+pub static SWITCH_KEY: KeyCode = KeyCode::Q;
+pub static SWITCH_DURATION: f32 = 0.75;
+// settings for critical hits
+// how much more likely an attack is to be critical when made from outside the target's vision cone,
+// and how much more likely, and harder hitting, critical hits are for those with the perks for them
+pub static FLANK_CRIT_BONUS: f32 = 0.25;
+pub static MARKSMAN_CRIT_BONUS: f32 = 0.1;
+pub static ASSASSIN_FLANK_BONUS: f32 = 0.25;
+pub static BRUTE_CRIT_MULTIPLIER_BONUS: f32 = 1.0;
+// how much faster those with the runner perk move
+pub static RUNNER_SPEED_BONUS: f32 = 0.15;
+// settings for damage numbers
+// how big the numbers floating up off anyone hit are, and critical hits', how long they float for and how fast they rise
+pub static DAMAGE_NUMBER_SIZE: f32 = 12.0;
+pub static CRITICAL_NUMBER_SIZE: f32 = 20.0;
+pub static DAMAGE_NUMBER_DURATION: f32 = 0.8;
+pub static DAMAGE_NUMBER_RISE: f32 = 25.0;
+// settings for medkits
+// the key that has a selected squad member use a medkit on whoever is clicked, or themselves,
+// how many medkits each squad member starts with on top of any in their kit,
+// how close they have to be to patch someone up, how many seconds it takes and how much health it gives back
+pub static HEAL_KEY: KeyCode = KeyCode::E;
+pub static STARTING_MEDKITS: u32 = 1;
+pub static HEAL_RANGE: f32 = 20.0;
+pub static HEAL_DURATION: f32 = 3.0;
+pub static HEAL_AMOUNT: f32 = 40.0;
+// settings for shields
+// the key that has the selected squad members with shields brace towards the cursor,
+// how much of the damage of attacks coming from in front a shield takes off, as a fraction,
+// and how wide in front is, in degrees, normally and while braced
+pub static BRACE_KEY: KeyCode = KeyCode::W;
+pub static SHIELD_REDUCTION: f32 = 0.6;
+pub static SHIELD_ARC: f32 = 90.0;
+pub static SHIELD_BRACED_ARC: f32 = 180.0;
+// settings for armour
+// how much of each kind of damage worn armour takes off, as a fraction
+pub static ARMOR_RESISTANCES: [(DamageType, f32); 3] = [(DamageType::Ballistic, 0.4), (DamageType::Blade, 0.25), (DamageType::Explosive, 0.15)];
+// how close the squad has to be for a broken hostile to be cornered,
+// how close a squad member ordered to capture a surrendered hostile has to get to them,
+// and how long it takes to take them prisoner once there
+pub static SURRENDER_RADIUS: f32 = 60.0;
+pub static CAPTURE_RADIUS: f32 = 15.0;
+pub static CAPTURE_DURATION: f32 = 1.0;
+pub static CAPTURE_REWARD_BONUS: u32 = 100;
+// where the results of the mission are saved
+pub static MISSION_RESULTS_PATH: &str = "mission_results.txt";
+// settings for panicking civilians
+// how far they run from what scared them, and how long they stay scared for
+pub static PANIC_FLEE_DISTANCE: f32 = 150.0;
+pub static PANIC_DURATION: f32 = 8.0;
+pub static CIVILIAN_HEALTH: f32 = 20.0;
+// settings for adaptive difficulty
+// each squad member lost eases the difficulty, as does the mission running past its par time
+pub static DIFFICULTY_LOSS_EASING: f32 = 0.1;
+pub static DIFFICULTY_PAR_TIME: f32 = 300.0;
+pub static DIFFICULTY_OVERTIME_EASING: f32 = 0.1;
+// settings for what a civilian's death costs the squad
+pub static CIVILIAN_DEATH_REWARD_PENALTY: u32 = 50;
+pub static CIVILIAN_DEATH_REPUTATION_PENALTY: i32 = 5;
+// settings for the campaign
+// where the campaign map is read from, and where the campaign is saved between missions
+pub static CAMPAIGN_PATH: &str = "assets/campaign.txt";
+pub static CAMPAIGN_SAVE_PATH: &str = "campaign_save.txt";
+// what a new campaign starts with
+pub static STARTING_FUNDS: i32 = 1000;
+pub static STARTING_ROSTER: usize = 6;
+// how much health a mercenary has, how much they get back each day,
+// and how low it has to have dropped by the end of a mission for them to count as injured
+pub static MERCENARY_HEALTH: f32 = 100.0;
+pub static HEAL_PER_DAY: f32 = 20.0;
+pub static INJURY_HEALTH: f32 = 50.0;
+// experience for each kill and for each mission survived, and how much each point of it
+// cuts the time between attacks, up to a limit
+pub static KILL_EXPERIENCE: u32 = 2;
+pub static MISSION_EXPERIENCE: u32 = 1;
+pub static EXPERIENCE_COOLDOWN_CUT: f32 = 0.01;
+pub static MAX_EXPERIENCE_COOLDOWN_CUT: f32 = 0.3;
+// names given to newly hired mercenaries, numbered once they've all been used
+pub static MERCENARY_NAMES: [&str; 8] = ["alder", "brandt", "corvin", "dagny", "esker", "falk", "greer", "holt"];
+// how many mercenaries go out on each contract, what it costs to travel along a route,
+// and what it costs to hire a new mercenary
+pub static SQUAD_SIZE: u32 = 4;
+pub static TRAVEL_COST: i32 = 50;
+pub static HIRE_COST: i32 = 200;
+pub static HIRE_KEY: KeyCode = KeyCode::H;
+// reputation gained for seeing a contract through
+pub static CONTRACT_REPUTATION: i32 = 10;
+// the squad's morale runs from 0 to 100, a mercenary deserts whenever it hits 0,
+// after which the rest pull themselves back up to the desertion morale
+pub static STARTING_MORALE: i32 = 60;
+pub static DESERTION_MORALE: i32 = 30;
+// settings for camping between missions
+// how many days an injured mercenary takes to heal, how much morale a day's rest brings back,
+// what each mercenary is paid every day, and how much morale is lost when the squad can't pay them
+pub static INJURY_DAYS: u32 = 3;
+pub static REST_MORALE: i32 = 5;
+pub static UPKEEP_PER_MERCENARY: i32 = 10;
+pub static UNPAID_MORALE: i32 = -15;
+// the keys that rest a day at camp, and that break camp to get back on the road
+pub static REST_KEY: KeyCode = KeyCode::R;
+pub static BREAK_CAMP_KEY: KeyCode = KeyCode::Space;
+// settings for the calendar, the campaign starts on the first day of the first month of year 1
+pub static CALENDAR_MONTHS: [&str; 12] = ["thaw", "seedtime", "rains", "bloom", "highsun", "haymaking", "harvest", "vintage", "leaffall", "frost", "deepwinter", "longnight"];
+pub static DAYS_PER_MONTH: u32 = 30;
+// reputation lost for letting a contract's deadline pass without seeing it through,
+// and how many days before a deadline a contract is flagged as running out of time
+pub static EXPIRED_CONTRACT_REPUTATION: i32 = 10;
+pub static DEADLINE_WARNING_DAYS: u32 = 2;
+// settings for the home base, found at the first location on the campaign map
+// the key opening and closing the base screen, and what each level of a facility costs, up to the top level
+pub static BASE_KEY: KeyCode = KeyCode::B;
+pub static FACILITY_COSTS: [i32; 3] = [300, 600, 1000];
+// how much faster each infirmary level heals, as a multiplier on health back each day,
+// with every other level also taking an extra day off injuries
+pub static INFIRMARY_HEAL_BONUS: f32 = 0.5;
+// experience every mercenary picks up each day, for each training yard level
+pub static TRAINING_EXPERIENCE: u32 = 1;
+// what the armory sells: lockpicks, shields, armour, or the weapon of an archetype, with its price and the armory level needed to stock it
+pub static SHOP_STOCK: [(&str, i32, u32); 6] = [("lockpicks", 150, 0), ("shield", 200, 1), ("brawler", 250, 1), ("armor", 300, 2), ("sniper", 350, 2), ("leader", 500, 3)];
+// settings for things that happen on the road
+// where the travel events are read from, and the chance of one happening each time the squad travels
+pub static TRAVEL_EVENTS_PATH: &str = "assets/travel_events.txt";
+pub static TRAVEL_EVENT_CHANCE: f32 = 0.4;
+// the map and encounter skirmishes on the road are fought out on
+pub static SKIRMISH_MAP_PATH: &str = "assets/maps/ortho-map.tmx";
+pub static SKIRMISH_ENCOUNTER_PATH: &str = "assets/encounters/road_ambush.txt";
+// settings for the developer console
+// the console key opens and closes it, and it shows the input line under the last few lines of output
+pub static CONSOLE_KEY: KeyCode = KeyCode::Grave;
+pub static CONSOLE_LINES: usize = 8;
+pub static CONSOLE_FONT_SIZE: f32 = 16.0;
+// settings for the entity inspector
+// the inspector key opens and closes it, and the next and previous keys go through the people it lists
+// it lists this many people at a time, around the one being inspected
+pub static INSPECTOR_KEY: KeyCode = KeyCode::F9;
+pub static INSPECTOR_NEXT_KEY: KeyCode = KeyCode::PageDown;
+pub static INSPECTOR_PREV_KEY: KeyCode = KeyCode::PageUp;
+pub static INSPECTOR_LINES: usize = 8;
+// settings for the map overlays
+// the weight key shows and hides the heatmap of how hard each tile is to cross, and the occupancy key the occupied tiles
+// the heatmap goes from green for open ground to red for tiles this many times harder to cross, or more
+pub static WEIGHT_OVERLAY_KEY: KeyCode = KeyCode::F10;
+pub static OCCUPANCY_OVERLAY_KEY: KeyCode = KeyCode::F11;
+pub static HEATMAP_MAX_WEIGHT: f32 = 5.0;
+pub static MAP_OVERLAY_OPACITY: f32 = 0.5;
+pub static MAP_OVERLAY_Z_LEVEL: f32 = 25.0;
+// settings for the tile editor
+// the editor key opens and closes it, and the painted tiles are exported to the export path unless another is given
+pub static TILE_EDITOR_KEY: KeyCode = KeyCode::F12;
+pub static TILE_EDITOR_EXPORT_PATH: &str = "assets/maps/painted_tiles.txt";
+pub static TILE_EDITOR_OPACITY: f32 = 0.6;
+pub static TILE_EDITOR_Z_LEVEL: f32 = 24.0;
+// settings for the combat log
+// the log key shows and hides it, and the older and newer keys scroll back through it
+pub static COMBAT_LOG_KEY: KeyCode = KeyCode::N;
+pub static COMBAT_LOG_OLDER_KEY: KeyCode = KeyCode::LBracket;
+pub static COMBAT_LOG_NEWER_KEY: KeyCode = KeyCode::RBracket;
+// only this many entries are kept, the oldest are dropped to make room, and this many are shown at a time
+pub static COMBAT_LOG_LENGTH: usize = 200;
+pub static COMBAT_LOG_LINES: usize = 10;
+pub static COMBAT_LOG_FONT_SIZE: f32 = 12.0;
+// squad members are logged as bleeding out once a hit leaves them with less than this much of their health
+pub static COMBAT_LOG_BLEEDING_OUT: f32 = 0.25;
+
+// combat queries against the physics world
+pub mod combat_query;
+// the plugin modules, each holding its plugin along with the components, resources and settings it works on
+pub mod control;
+pub mod feedback;
+pub mod order_macro;
+pub mod patrol;
+pub mod stance;
+pub mod cursor;
+pub mod help;
+pub mod path_preview;
+pub mod actions;
+pub mod animation;
+pub mod behaviour;
+pub mod map;
+pub mod combat;
+pub mod tick;
+pub mod moving;
+pub mod draw;
+pub mod person;
+pub mod nerve;
+pub mod encounter;
+pub mod pool;
+pub mod bench;
+pub mod collision;
+pub mod door;
+pub mod zone;
+pub mod audio;
+pub mod perception;
+pub mod weather;
+pub mod water;
+pub mod elevation;
+pub mod routine;
+pub mod corpse;
+pub mod detection;
+pub mod lighting;
+pub mod vision_cone;
+pub mod civilian;
+pub mod stamina;
+pub mod surrender;
+pub mod extraction;
+pub mod scripting;
+pub mod mods;
+pub mod headless;
+pub mod network;
+pub mod campaign;
+pub mod config;
+pub mod debug_render;
+pub mod display;
+pub mod palette;
+pub mod locale;
+pub mod console;
+pub mod inspector;
+pub mod tile_editor;
+// the plugins, and whatever else the game binary needs to put them together
+pub use control::{ControlPlugin, KeyBindings};
+pub use feedback::FeedbackPlugin;
+pub use order_macro::OrderMacroPlugin;
+pub use patrol::PatrolPlugin;
+pub use stance::StancePlugin;
+pub use cursor::CursorPlugin;
+pub use help::HelpPlugin;
+pub use path_preview::PathPreviewPlugin;
+pub use actions::ActionsPlugin;
+pub use animation::AnimationPlugin;
+pub use behaviour::BehaviourPlugin;
+pub use map::{MapOverlayPlugin, MapPlugin, MapPropertiesPlugin, MapTransitionPlugin};
+pub use combat::{CombatLogPlugin, CombatPlugin, DamageNumberPlugin};
+pub use tick::{FixedTickPlugin, PipelinePlugin};
+pub use moving::MovingPlugin;
+pub use draw::DrawMovingPlugin;
+pub use person::PersonPlugin;
+pub use encounter::EncounterPlugin;
+pub use pool::PoolPlugin;
+pub use bench::{BenchPlugin, BenchSettings};
+pub use collision::CollisionPlugin;
+pub use door::DoorPlugin;
+pub use zone::ZonePlugin;
+pub use audio::AudioPlugin;
+pub use perception::PerceptionPlugin;
+pub use weather::WeatherPlugin;
+pub use water::WaterPlugin;
+pub use elevation::ElevationPlugin;
+pub use routine::RoutinePlugin;
+pub use corpse::CorpsePlugin;
+pub use detection::DetectionMeterPlugin;
+pub use lighting::LightingPlugin;
+pub use vision_cone::VisionConePlugin;
+pub use civilian::CivilianPlugin;
+pub use stamina::StaminaPlugin;
+pub use surrender::SurrenderPlugin;
+pub use extraction::ExtractionPlugin;
+pub use scripting::ScriptingPlugin;
+pub use mods::ModAssets;
+pub use headless::{HeadlessReport, HeadlessScenario, run_headless};
+pub use network::{NetworkPlugin, NetworkState};
+pub use campaign::run_campaign;
+pub use config::{GameConfig, LaunchOptions, Settings};
+pub use debug_render::DebugRenderPlugin;
+pub use display::DisplayPlugin;
+pub use palette::PalettePlugin;
+pub use locale::LocalePlugin;
+pub use console::ConsolePlugin;
+pub use inspector::InspectorPlugin;
+pub use tile_editor::TileEditorPlugin;
+// game rng resource
+// the random number generator used by the simulation
+// everything random that affects gameplay should go through this, so that
+// a run can be repeated exactly by seeding it the same way
+pub struct GameRng(pub StdRng);
+
+impl GameRng {
+    // gives a random number generator that always rolls the same way for the same seed
+    pub fn new(seed: u64) -> Self {
+        GameRng(StdRng::seed_from_u64(seed))
+    }
+}
+
+// run seed resource
+// the one seed everything random in a run comes from: the map, the game rng, and through that
+// the weather, loot and hostiles' choices. shown on screen, so a run can be shared or reported
+pub struct RunSeed(pub u64);
+
+// rolls a seed for a run that wasn't given one
+// this is the only place randomness is drawn from outside of a seed
+pub fn roll_seed() -> u64 {
+    StdRng::from_entropy().gen()
+}
+
+// id component
+// this should be spawned along side every entity
+// it is responsible for keeping the unique id of each entity
+// it is essentially impossible to keep track of an entity otherwise
+// note that bevy has its own id system for entities but it uses a u32 id
+// which carries the risk of collision
+pub struct Id(pub String);
+
+// id component implementation
+impl Id {
+    // new function generates new uuid
+    pub fn new() -> Self {
+        Id(
+            // uuid removes all hyphens 
+            Uuid::new_v4().to_simple().to_string()
+        )
+    }
+    // id function returns id as a string
+    pub fn id(&self) -> String {
+        self.0.clone()
+    }
+}
+// position component
+// spawn this component along with any entity that has a physical position on the screen
+pub struct Position(pub f32, pub f32);
+
+// size component
+// spawn this component along with any entity that should have collision turned on
+pub struct Size(pub f32, pub f32);
+
+// velocity component
+// spawn this component along with any entity that has a physical velocity
+pub struct Velocity(pub f32, pub f32);
+
+// fps counter component
+// spawn this component along any text components that will be used as fps counters 
+pub struct FPSMeter;
+
+// seed label component
+// spawn this component along any text components that should show the run's seed
+pub struct SeedLabel;
+
+// initial setup function, 
+// spawn in necessary entities (cameras)
+// along with fps counter
+pub fn setup(mut commands: Commands, mut materials: ResMut<Assets<ColorMaterial>>, asset_server: Res<AssetServer>, mods: Res<ModAssets>, launch: Res<LaunchOptions>){
+    let font_handle = asset_server.load(mods.resolve("assets/fonts/LiberationMono-Regular.ttf")).unwrap();
+
+    // add in tile map
+    commands
+        .spawn(bevy_tiled::TiledMapComponents {
+            map_asset: asset_server.load(mods.resolve(&launch.map_path)).unwrap(),
+            center: true,
+            ..Default::default()
+        });
+
+    commands
+        // cameras
+        .spawn(Camera2dComponents::default())
+        .with(WorldCamera)
+        .spawn(UiCameraComponents::default())
+        // text for fps counter
+        .spawn(TextComponents {
+            style: Style {
+                // for alignment of the text
+                align_self: AlignSelf::FlexEnd,
+                ..Default::default()
+            },
+            text: Text {
+                // the content of the text
+                value: "".to_string(),
+                // the font used to display it
+                font: font_handle,
+                // styling for the text, including font size and color
+                style: TextStyle {
+                    font_size: 20.0,
+                    color: Color::BLACK,
+                },
+            },
+            ..Default::default()
+        })
+        // make sure to spawn fps meter component so it displays fps
+        .with(FPSMeter)
+        // text for the run's seed, in the bottom left corner
+        .spawn(TextComponents {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    left: Val::Px(5.0),
+                    bottom: Val::Px(5.0),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            text: Text {
+                value: "".to_string(),
+                font: font_handle,
+                style: TextStyle {
+                    font_size: 16.0,
+                    color: Color::BLACK,
+                },
+            },
+            ..Default::default()
+        })
+        .with(SeedLabel);
+}
+
+// seed label system
+// keeps the seed shown on screen up to date, as it can be changed from the console
+pub fn seed_label_system(seed: Res<RunSeed>, locale: Res<Locale>, mut query: Query<(&SeedLabel, &mut Text)>) {
+    for (_label, mut text) in &mut query.iter() {
+        text.value = locale.format("seed", &[&seed.0]);
+    }
+}
+
+// fps counter system
+pub fn fps_monitor_system(diagnostics: Res<Diagnostics>, locale: Res<Locale>, mut query: Query<(&FPSMeter, &mut Text)>){
+    for (_fpsmeter, mut text) in &mut query.iter() {
+        if let Some(fps) = diagnostics.get(FrameTimeDiagnosticsPlugin::FPS) {
+            if let Some(average) = fps.average() {
+                text.value = locale.format("fps", &[&format!("{:.2}", average)]);
+            }
+        }
+    }
+}
@@ -0,0 +1,211 @@
+// lighting module
+// light, darkness and the time of day
+use bevy::prelude::*;
+use bevy::render::pass::ClearColor;
+
+use crate::{
+    DARKNESS_CELL_SIZE, DARKNESS_Z_LEVEL, DAWN_HOUR, DAY_HOUR, DAY_START_HOUR, DUSK_HOUR,
+    MIN_LIGHT_VISIBILITY, MUZZLE_FLASH_DURATION, MUZZLE_FLASH_RADIUS, NIGHT_AMBIENT_LIGHT,
+    NIGHT_HOUR, NIGHT_START_HOUR, Position, SKY_COLOR, WORLD_CLOCK_SPEED,
+};
+use crate::combat::AttackEvent;
+use crate::config::{GameConfig, LaunchOptions};
+use crate::person::SimpleRect;
+use crate::tick::AddTickSystem;
+
+// lighting plugin
+// responsible for light and darkness, which makes squad members harder to see
+// the light comes and goes with the time of day, and at night the map is only lit by its lights and muzzle flashes,
+// with everywhere else covered in darkness
+pub struct LightingPlugin;
+
+// implementation of the plugin trait,
+// required for this to be used as a plugin
+impl Plugin for LightingPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        // start the clock during the day, or at night if the launch options ask for it
+        let night = app.resources().get::<LaunchOptions>().map_or(false, |launch| launch.night);
+        app.add_resource(WorldClock {
+            hour: if night { NIGHT_START_HOUR } else { DAY_START_HOUR },
+        })
+        // add in the system moving the clock on, and lighting the world to match
+        .add_tick_system(world_clock_system.system())
+        // initialise the attack event state resource
+        .init_resource::<MuzzleFlashState>()
+        // add in the darkness overlay
+        .add_startup_system(setup_darkness_system.system())
+        // add in the muzzle flash system
+        .add_tick_system(muzzle_flash_system.system())
+        // add in the system shading the darkness overlay
+        .add_system(darkness_system.system());
+    }
+}
+
+// light struct
+// a source of light, fading out towards its edge
+#[derive(Debug, Clone)]
+pub struct Light {
+    pub position: (f32, f32),
+    pub radius: f32,
+}
+
+impl Light {
+    // gives how brightly the light lights up a position, from 0 to 1
+    pub fn level_at(&self, position: (f32, f32)) -> f32 {
+        let dist = Vec2::new(position.0 - self.position.0, position.1 - self.position.1).length();
+        (1.0 - dist / self.radius.max(1.0)).max(0.0)
+    }
+}
+
+// light map resource
+// how brightly lit everywhere is, from the light everywhere gets, the map's lights, and short lived muzzle flashes
+pub struct LightMap {
+    pub ambient: f32,
+    pub lights: Vec<Light>,
+    // muzzle flashes, along with the timer until they go out
+    pub flashes: Vec<(Light, Timer)>,
+}
+
+impl Default for LightMap {
+    fn default() -> Self {
+        LightMap {
+            // full daylight
+            ambient: 1.0,
+            lights: Vec::new(),
+            flashes: Vec::new(),
+        }
+    }
+}
+
+impl LightMap {
+    // gives how brightly lit a position is, from 0 to 1
+    pub fn level_at(&self, position: (f32, f32)) -> f32 {
+        let mut level = self.ambient;
+        for light in self.lights.iter().chain(self.flashes.iter().map(|(light, _timer)| light)) {
+            level = level.max(light.level_at(position));
+        }
+        level.min(1.0)
+    }
+    // gives how far something standing at a position can be seen from, as a fraction of how far it could be seen in daylight
+    pub fn visibility_at(&self, position: (f32, f32)) -> f32 {
+        self.level_at(position).max(MIN_LIGHT_VISIBILITY)
+    }
+}
+
+// day phase enum
+// the parts of the day, as far as the light goes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DayPhase {
+    Dawn,
+    Day,
+    Dusk,
+    Night,
+}
+
+// world clock resource
+// the time of day, in hours from midnight, moving on as the mission goes
+// missions start during the day, at night, or at the time given in the encounter file
+pub struct WorldClock {
+    pub hour: f32,
+}
+
+impl WorldClock {
+    // gives the part of the day it is
+    pub fn phase(&self) -> DayPhase {
+        if self.hour >= DAWN_HOUR && self.hour < DAY_HOUR {
+            DayPhase::Dawn
+        }else if self.hour >= DAY_HOUR && self.hour < DUSK_HOUR {
+            DayPhase::Day
+        }else if self.hour >= DUSK_HOUR && self.hour < NIGHT_HOUR {
+            DayPhase::Dusk
+        }else{
+            DayPhase::Night
+        }
+    }
+    // gives whether it's light enough out for people to be keeping to their daytime routines
+    pub fn is_daytime(&self) -> bool {
+        match self.phase() {
+            DayPhase::Dawn | DayPhase::Day => true,
+            DayPhase::Dusk | DayPhase::Night => false,
+        }
+    }
+    // gives how much light there is everywhere, brightening through dawn and dimming through dusk
+    pub fn ambient(&self) -> f32 {
+        match self.phase() {
+            DayPhase::Day => 1.0,
+            DayPhase::Night => NIGHT_AMBIENT_LIGHT,
+            DayPhase::Dawn => {
+                let progress = (self.hour - DAWN_HOUR) / (DAY_HOUR - DAWN_HOUR);
+                NIGHT_AMBIENT_LIGHT + (1.0 - NIGHT_AMBIENT_LIGHT) * progress
+            },
+            DayPhase::Dusk => {
+                let progress = (self.hour - DUSK_HOUR) / (NIGHT_HOUR - DUSK_HOUR);
+                1.0 - (1.0 - NIGHT_AMBIENT_LIGHT) * progress
+            },
+        }
+    }
+}
+
+// world clock system
+// moves the time of day on, and sets the light and the colour of the sky to match
+pub fn world_clock_system(time: Res<Time>, mut clock: ResMut<WorldClock>, mut light: ResMut<LightMap>, mut sky: ResMut<ClearColor>) {
+    clock.hour = (clock.hour + time.delta_seconds * WORLD_CLOCK_SPEED) % 24.0;
+    light.ambient = clock.ambient();
+    sky.0 = Color::rgb(SKY_COLOR[0] * light.ambient, SKY_COLOR[1] * light.ambient, SKY_COLOR[2] * light.ambient);
+}
+
+// darkness cell component
+// spawn this component along with a square of the darkness overlay
+pub struct DarknessCell;
+
+// setup darkness system
+// covers the map in the darkness overlay, which is see-through during the day
+pub fn setup_darkness_system(mut commands: Commands, config: Res<GameConfig>, mut materials: ResMut<Assets<ColorMaterial>>) {
+    let columns = (config.map_width / DARKNESS_CELL_SIZE).ceil() as usize;
+    let rows = (config.map_height / DARKNESS_CELL_SIZE).ceil() as usize;
+    for x in 0..columns {
+        for y in 0..rows {
+            // each cell is shaded on its own, so needs its own material
+            let material = materials.add(Color::rgba(0.0, 0.0, 0.05, 0.0).into());
+            let mut cell = SimpleRect::new(material, Vec2::new(DARKNESS_CELL_SIZE, DARKNESS_CELL_SIZE));
+            // drawn over everything else
+            cell.translation = Translation(Vec3::new(-1000.0, -1000.0, DARKNESS_Z_LEVEL));
+            commands
+                .spawn(cell)
+                .with(Position((x as f32 + 0.5) * DARKNESS_CELL_SIZE, (y as f32 + 0.5) * DARKNESS_CELL_SIZE))
+                .with(DarknessCell);
+        }
+    }
+}
+
+// muzzle flash state holds an event reader for attack events
+#[derive(Default)]
+pub struct MuzzleFlashState {
+    pub event_reader: EventReader<AttackEvent>,
+}
+
+// muzzle flash system
+// lights up the area around anyone attacking for a moment, and puts out flashes once they're done
+pub fn muzzle_flash_system(time: Res<Time>, mut state: ResMut<MuzzleFlashState>, attacks: Res<Events<AttackEvent>>, mut light: ResMut<LightMap>) {
+    for flash in light.flashes.iter_mut() {
+        flash.1.tick(time.delta_seconds);
+    }
+    light.flashes.retain(|(_light, timer)| !timer.finished);
+
+    for attack in state.event_reader.iter(&attacks) {
+        light.flashes.push((Light {
+            position: attack.origin,
+            radius: MUZZLE_FLASH_RADIUS,
+        }, Timer::from_seconds(MUZZLE_FLASH_DURATION, false)));
+    }
+}
+
+// darkness system
+// shades each square of the darkness overlay by how dark it is in the middle of the square
+pub fn darkness_system(light: Res<LightMap>, mut materials: ResMut<Assets<ColorMaterial>>, mut query: Query<(&DarknessCell, &Position, &Handle<ColorMaterial>)>) {
+    for (_cell, pos, handle) in &mut query.iter() {
+        if let Some(material) = materials.get_mut(&handle) {
+            material.color.a = 1.0 - light.level_at((pos.0, pos.1));
+        }
+    }
+}
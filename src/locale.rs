@@ -0,0 +1,144 @@
+// locale module
+// the strings the player sees, in the language they chose
+use bevy::prelude::*;
+use std::collections::HashMap;
+use std::{fs, fmt};
+use std::path::Path;
+
+use crate::{DEFAULT_LANGUAGE, LANG_PATH, SETTINGS_PATH};
+use crate::config::Settings;
+use crate::control::{InputState, KeyBindings};
+
+// locale plugin
+// responsible for the strings the player sees, in the language they chose
+// the language can be switched while the game runs, text that is redrawn every frame switches straight away
+pub struct LocalePlugin;
+
+// implementation of the plugin trait,
+// required for this to be used as a plugin
+impl Plugin for LocalePlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        // the language is one of the player's settings
+        if app.resources().get::<Settings>().is_none() {
+            app.add_resource(Settings::load(SETTINGS_PATH));
+        }
+        let language = app.resources().get::<Settings>().unwrap().language.clone();
+        app.add_resource(Locale::load(&language))
+        // add in the language key system
+        .add_system(language_key_system.system());
+    }
+}
+
+// locale resource
+// the strings of a language, each under a key
+// see assets/lang/en.txt for the layout of a language file
+#[derive(Debug, Clone)]
+pub struct Locale {
+    // name of the language, the same as its file's
+    pub language: String,
+    // the strings, by key
+    pub strings: HashMap<String, String>,
+}
+
+impl Locale {
+    // reads in the language with the given name, on top of the default language
+    pub fn load(language: &str) -> Self {
+        let mut locale = Locale {
+            language: language.to_string(),
+            strings: HashMap::new(),
+        };
+        locale.read(&Path::new(LANG_PATH).join(format!("{}.txt", DEFAULT_LANGUAGE)));
+        if language != DEFAULT_LANGUAGE {
+            locale.read(&Path::new(LANG_PATH).join(format!("{}.txt", language)));
+        }
+        locale
+    }
+    // reads the strings from a language file, replacing any already read under the same key
+    // one string per line, the key then the string, which is everything after the first space
+    // \n in a string starts a new line
+    pub fn read(&mut self, path: &Path) {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(err) => {
+                eprintln!("could not read language file {}: {}", path.display(), err);
+                return;
+            },
+        };
+        for line in text.lines() {
+            if line.trim().is_empty() || line.trim_start().starts_with('#') {
+                continue;
+            }
+            let mut parts = line.splitn(2, ' ');
+            let key = parts.next().unwrap_or("");
+            let value = parts.next().unwrap_or("").replace("\\n", "\n");
+            self.strings.insert(key.to_string(), value);
+        }
+    }
+    // gives the names of the languages there are files for, in order
+    pub fn languages() -> Vec<String> {
+        let mut languages: Vec<String> = fs::read_dir(LANG_PATH)
+            .map(|entries| entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+                .filter(|path| path.extension().map_or(false, |extension| extension == "txt"))
+                .filter_map(|path| path.file_stem().map(|stem| stem.to_string_lossy().to_string()))
+                .collect())
+            .unwrap_or_default();
+        languages.sort();
+        languages
+    }
+    // gives the string with the given key
+    // missing strings show up as their key, so they're easy to spot
+    pub fn get(&self, key: &str) -> String {
+        self.strings.get(key).cloned().unwrap_or_else(|| key.to_string())
+    }
+    // gives the string with the given key, with the arguments filled in
+    // {} is filled in by the next argument, and {0}, {1} and so on by the argument at that index,
+    // so a language can put the arguments in whatever order reads best
+    pub fn format(&self, key: &str, args: &[&dyn fmt::Display]) -> String {
+        let template = self.get(key);
+        let mut value = String::new();
+        let mut next = 0;
+        let mut rest = template.as_str();
+        while let Some(start) = rest.find('{') {
+            let end = match rest[start..].find('}') {
+                Some(end) => start + end,
+                None => break,
+            };
+            value.push_str(&rest[..start]);
+            let index = match rest[start + 1..end].parse::<usize>() {
+                Ok(index) => index,
+                Err(_) => {
+                    let index = next;
+                    next += 1;
+                    index
+                },
+            };
+            match args.get(index) {
+                Some(arg) => value.push_str(&arg.to_string()),
+                None => value.push_str(&rest[start..=end]),
+            }
+            rest = &rest[end + 1..];
+        }
+        value.push_str(rest);
+        value
+    }
+}
+
+// language key system
+// switches to the next language there is a file for when alt and the language key are pressed together, and saves the choice
+pub fn language_key_system(inputs: Res<InputState>, bindings: Res<KeyBindings>, mut settings: ResMut<Settings>, mut locale: ResMut<Locale>) {
+    if !inputs.key_just_presses.contains(&bindings.language)
+        || !bindings.display_modifiers.iter().any(|key| inputs.key_presses.contains(key)) {
+        return;
+    }
+    let languages = Locale::languages();
+    let index = languages.iter().position(|language| *language == locale.language).map_or(0, |index| index + 1);
+    let language = match languages.get(index % languages.len().max(1)) {
+        Some(language) => language.clone(),
+        None => return,
+    };
+    *locale = Locale::load(&language);
+    settings.language = language;
+    settings.save(SETTINGS_PATH);
+}
@@ -0,0 +1,1180 @@
+// map module
+// loading the tiled map into the world, and the walkability data used by pathfinding
+use bevy::prelude::*;
+use bevy::diagnostic::Diagnostics;
+use std::collections::{HashMap, HashSet, BinaryHeap};
+use std::cmp::Reverse;
+use noise::{Perlin, Seedable, NoiseFn};
+use std::fs;
+use std::path::Path;
+use std::time::Instant;
+use bevy_rapier2d::physics::RigidBodyHandleComponent;
+use bevy_rapier2d::rapier::dynamics::{JointSet, RigidBodySet};
+use bevy_rapier2d::rapier::geometry::ColliderSet;
+use pathfinding::prelude::astar;
+use bevy_tiled::tiled::PropertyValue;
+use ordered_float::OrderedFloat;
+
+use crate::{
+    CLIFF_HEIGHT, DEEP_WATER_NOISE, HEATMAP_MAX_WEIGHT, MAP_OVERLAY_OPACITY, MAP_OVERLAY_Z_LEVEL,
+    MUD_SPEED, MUD_WEIGHT, NOISE_WALL_MUFFLING, OFF_ROAD_MARCH_WEIGHT, PATHFINDING_STAGE,
+    PLAYER_START, Position, ROAD_COST, ROAD_SPEED, RunSeed, SHALLOW_WATER_NOISE, STAGE_ENCOUNTER_DIR,
+    STAGE_MAP_DIR, TILE_COVER, WADING_SPEED, WADING_WEIGHT,
+};
+use crate::behaviour::{
+    Behaviour, BehaviourSet, MapCoords, MapPatrolRoutes, Pathfinder, PathfindersQueue, TilePos,
+};
+use crate::bench::PATHFIND_SYSTEM_TIME;
+use crate::collision::{spawn_wall_collider, WallColliders};
+use crate::combat::remove_person;
+use crate::config::{GameConfig, LaunchOptions};
+use crate::control::{InputState, KeyBindings};
+use crate::corpse::Corpse;
+use crate::door::{Door, Key};
+use crate::encounter::{
+    Alarm, Archetypes, Difficulty, EncounterStages, load_encounter, Reinforcements,
+    spawn_spawn_point, SpawnPoint, WaveScheduler,
+};
+use crate::extraction::Extraction;
+use crate::lighting::{LightMap, WorldClock};
+use crate::mods::ModAssets;
+use crate::nerve::{Action, ActionType, Nerve};
+use crate::person::{AttitudeType, Command, Controlled, Person, SimpleRect};
+use crate::tick::AddTickSystem;
+use crate::weather::Weather;
+use crate::zone::{
+    MapStartPoints, MapZonesLoaded, spawn_zone, StartPoint, Zone, ZoneEvent, ZoneKind,
+};
+
+pub struct MapPlugin;
+
+impl Plugin for MapPlugin {
+    fn build (&self, app: &mut AppBuilder){
+        // the map is generated from the run's seed, if there is one
+        let seed = app.resources().get::<RunSeed>().map_or(0, |seed| seed.0);
+        // the map is sized to the config, and the default config is used if none was given
+        let config = app.resources().get::<GameConfig>().map(|config| config.clone());
+        let config = match config {
+            Some(config) => config,
+            None => {
+                app.add_resource(GameConfig::default());
+                GameConfig::default()
+            },
+        };
+        app.add_resource(MapCoords(0.0, 0.0))
+            .add_resource(MapData::new(seed as u32, &config))
+            .add_resource(PathfindersQueue(0))
+            .add_tick_system(update_map_system.system())
+            .add_tick_system_to_stage(PATHFINDING_STAGE, pathfind_system.system())
+            .add_tick_system_to_stage(PATHFINDING_STAGE, follow_path_system.system());
+    }
+}
+
+
+pub fn update_map_system(config: Res<GameConfig>, coords: Res<MapCoords>, mut map: ResMut<MapData>, mut query: Query<(&Person, &Position)>) {
+    map.update_map(coords.0 as i32, coords.1 as i32);
+    // occupancy is rebuilt from scratch every tick
+    map.clear_occupied();
+    for (_person, pos) in &mut query.iter() {
+        map.set_tile_occupied(&TilePos::from_coords(&config, pos.0, pos.1));
+    }
+}
+
+pub fn follow_path_system(config: Res<GameConfig>, mut query: Query<(&mut Pathfinder, &mut Nerve, &Position)>) {
+    for (mut pf, mut actions, pos) in &mut query.iter() {
+        if pf.tile_path.len() == 0 {
+            continue;
+        }
+
+        if pf.path_index < pf.tile_path.len() {
+
+            let path_tile = pf.tile_path[pf.path_index];
+            
+            if TilePos::from_coords(&config, pos.0, pos.1) == path_tile {
+                pf.path_index += 1;
+            }
+
+        }
+
+        if pf.path_index < pf.tile_path.len() && actions.is_curr_action_empty() && actions.action_queue.is_empty() {
+            for i in pf.path_index..pf.tile_path.len() {
+                let mut params = HashMap::new();
+
+                params.insert("range".to_string(), config.tile_size);
+
+                actions.action_queue.push_back(Action {
+                    action_type: ActionType::Move,
+                    target: (Some(pf.path[i]), None),
+                    params: Some(params),
+                    group: None,
+                });
+            }
+            pf.path_ready = true;
+        }
+    }
+}
+
+pub fn pathfind_system(config: Res<GameConfig>, mut waiting: ResMut<PathfindersQueue>, mut diagnostics: ResMut<Diagnostics>, map: Res<MapData>, mut query: Query<(&mut Pathfinder, &Position, Option<&Behaviour>)>) {
+    // start timing this system
+    let timer = Instant::now();
+
+    for (mut pf, pos, behaviour) in &mut query.iter() {
+        if !pf.needs_pathfinding {
+            continue;
+        }
+        if waiting.0 < config.max_pathfinders {
+            waiting.0 += 1;
+            // update path index
+            pf.path_index = 0;
+            // update start coordinates
+            pf.path_start = TilePos::from_coords(&config, pos.0, pos.1);
+            
+            if map.is_tile_occupied(&pf.path_goal) {
+                //panic!("tile destination is occupied");
+            }
+
+            // pathfind here
+            // those on the march keep to the roads where they can
+            let prefer_roads = behaviour.map_or(false, |behaviour| behaviour.current_behaviour_set == BehaviourSet::OnMarch);
+            let path = astar(&pf.path_start, |p| map.successors(p, prefer_roads), |p| map.get_diag_dist(*p, pf.path_goal), |p| *p == pf.path_goal);
+            //let path = None;
+            match path {
+                Some((path, cost)) => {
+                    let real_goal = pf.real_goal;
+
+                    pf.tile_path = path.clone();
+                    pf.path = path.iter().map( |t| t.to_coords(&config)).collect();
+
+                    pf.tile_path.push(TilePos::from_coords(&config, real_goal.0, real_goal.1));
+                    pf.path.push((real_goal.0, real_goal.1));
+                },
+                None => {
+                    // the goal can't be reached, e.g. being up a cliff, so there's nowhere to go
+                    pf.tile_path.clear();
+                    pf.path.clear();
+                }
+            }
+
+            pf.needs_pathfinding = false;
+        }else{
+            // max pathfinders this tick reached
+            // break loop
+            break;
+        }
+    }
+    // reset pathfinders queue
+    waiting.0 = 0;
+
+    // record how long this system took
+    diagnostics.add_measurement(PATHFIND_SYSTEM_TIME, timer.elapsed().as_secs_f64() * 1000.0);
+}
+
+pub enum TileType {
+    Grass,
+    Water,
+    ShallowWater,
+    DeepWater,
+    Empty,
+}
+
+// water depth enum
+// how deep the water on a tile is. shallow water can be waded through, slowly and out in the open,
+// deep water can't be crossed at all, other than by a bridge, and anyone who ends up in it drowns
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WaterDepth {
+    Dry,
+    Shallow,
+    Deep,
+}
+
+impl Default for WaterDepth {
+    fn default() -> Self {
+        WaterDepth::Dry
+    }
+}
+
+impl WaterDepth {
+    // reads a water depth from the value given to a tile's water property in tiled
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "dry" | "none" => Some(WaterDepth::Dry),
+            "shallow" => Some(WaterDepth::Shallow),
+            "deep" => Some(WaterDepth::Deep),
+            _ => None,
+        }
+    }
+    // gives the name the water depth is read from
+    pub fn name(&self) -> &'static str {
+        match self {
+            WaterDepth::Dry => "dry",
+            WaterDepth::Shallow => "shallow",
+            WaterDepth::Deep => "deep",
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct MapData {
+    pub generator: noise::Perlin,
+    pub size: (usize, usize),
+    pub data: Vec::<f32>,
+    pub occupied: Vec::<bool>,
+    // tiles blocked off by closed doors
+    pub closed: Vec::<bool>,
+    // low ground, which turns to mud while the map is muddy
+    pub low: Vec::<bool>,
+    pub muddy: bool,
+    // tiles blown open, e.g. by grenades, which can be walked through from then on
+    pub destroyed: Vec::<bool>,
+    // annotations read from the custom properties of the tiled map's tiles, see TileProperties
+    // whether the tile can be walked through, how many times harder it is to cross than open ground,
+    // and how much of the damage of attacks on anyone standing on it the tile blocks, from 0.0 to 1.0
+    pub walkable: Vec::<bool>,
+    pub cost: Vec::<f32>,
+    pub cover: Vec::<f32>,
+    // roads and bridges, which are quicker going and never turn to mud
+    pub road: Vec::<bool>,
+    // how deep any water on the tile is, from the generated map or the tiled map's tiles
+    pub water: Vec::<WaterDepth>,
+    // how high up the tile is, from the tiled map's tiles, with open ground at 0.0
+    // tiles much higher or lower than the tile next to them are cut off from it by a cliff
+    pub height: Vec::<f32>,
+    // whether or not the map data has been generated yet
+    pub generated: bool,
+}
+
+pub fn get_map_weight_from_tile_type(tile: TileType) -> f32 {
+    match tile {
+        TileType::Grass => {
+            1.0
+        },
+        _ => {
+            1.0
+            //f32::INFINITY
+        },
+    }
+}
+
+impl Default for MapData {
+    fn default() -> Self {
+        MapData::new(0, &GameConfig::default())
+    }
+}
+
+
+impl MapData {
+    pub fn new(seed: u32, config: &GameConfig) -> Self {
+        let gen = Perlin::new();
+        gen.set_seed(seed);
+        let size = config.map_size();
+        MapData {
+            generator: gen,
+            size: size,
+            data: vec![0.0; size.0 * size.1],
+            occupied: vec![false; size.0 * size.1],
+            closed: vec![false; size.0 * size.1],
+            low: vec![false; size.0 * size.1],
+            muddy: false,
+            destroyed: vec![false; size.0 * size.1],
+            walkable: vec![true; size.0 * size.1],
+            cost: vec![1.0; size.0 * size.1],
+            cover: vec![0.0; size.0 * size.1],
+            road: vec![false; size.0 * size.1],
+            water: vec![WaterDepth::Dry; size.0 * size.1],
+            height: vec![0.0; size.0 * size.1],
+            generated: false,
+        }
+    }
+    pub fn convert_f64_to_tiletype(float: f64) -> TileType {
+        match float {
+            0.0..=0.5 => {
+                TileType::Water
+            },
+            0.5..=1.0 => {
+                TileType::Grass
+            },
+            depth if depth < DEEP_WATER_NOISE => {
+                TileType::DeepWater
+            },
+            depth if depth < SHALLOW_WATER_NOISE => {
+                TileType::ShallowWater
+            },
+            _ => {
+                TileType::Empty
+            }
+        }
+    }
+    // gives the tiles next to the given tile, along with the cost of moving on from it
+    // those preferring roads, e.g. on the march, find leaving the road harder
+    pub fn successors(&self, tile: &TilePos, prefer_roads: bool) -> Vec<(TilePos, OrderedFloat<f32>)> {
+        let &TilePos(x, y) = tile;
+        let mut output = Vec::new();
+        let mut weight = self.get_weight(tile);
+        if prefer_roads && !self.is_road(tile) {
+            weight = OrderedFloat(weight.0 * OFF_ROAD_MARCH_WEIGHT);
+        }
+
+        for i in -1..2 {
+            for j in -1..2 {
+                if i == 0 && j == 0 {
+                    continue
+                }
+                let mx = x as i32 + i;
+                let my = y as i32 + j;
+                // there's no walking up or down a cliff
+                if mx >= 0 && my >= 0 && self.contains(&TilePos(mx as usize, my as usize))
+                    && !self.is_cliff(tile, &TilePos(mx as usize, my as usize)) {
+                    output.push((TilePos(mx as usize, my as usize), weight))
+                }
+            }
+        }
+        
+        output
+    }
+    // gives whether a tile is on the map, which can be any size, not just the size of the window
+    pub fn contains(&self, tile: &TilePos) -> bool {
+        tile.0 < self.size.0 && tile.1 < self.size.1
+    }
+    pub fn is_tile_blocked(&self, tile: &TilePos) -> bool {
+        self.get_weight(tile).0.is_infinite()
+    }
+    // gives up to count free tiles around a target tile, closest first
+    // the target tile itself is always first, then the rings around it are searched outwards,
+    // skipping any tile that is blocked or already occupied
+    pub fn get_spread_destinations(&self, target: &TilePos, count: usize) -> Vec<TilePos> {
+        let mut output = vec![*target];
+        let mut ring = 1;
+        // stop searching once the rings are bigger than the map
+        while output.len() < count && ring < self.size.0.max(self.size.1) as i32 {
+            for j in -ring..ring + 1 {
+                for i in -ring..ring + 1 {
+                    // only look at the edge of the ring, the inside has already been searched
+                    if i.abs() != ring && j.abs() != ring {
+                        continue;
+                    }
+                    let x = target.0 as i32 + i;
+                    let y = target.1 as i32 + j;
+                    if x < 0 || y < 0 || !self.contains(&TilePos(x as usize, y as usize)) {
+                        continue;
+                    }
+                    let tile = TilePos(x as usize, y as usize);
+                    if !self.is_tile_blocked(&tile) && !self.is_tile_occupied(&tile) && output.len() < count {
+                        output.push(tile);
+                    }
+                }
+            }
+            ring += 1;
+        }
+        output
+    }
+    // gives the given tile if it's free, otherwise the closest free tile to it
+    pub fn get_nearest_free_tile(&self, tile: &TilePos) -> TilePos {
+        if !self.is_tile_blocked(tile) && !self.is_tile_occupied(tile) {
+            return *tile;
+        }
+        // the first destination is always the tile itself, the second is the closest free tile
+        self.get_spread_destinations(tile, 2).get(1).cloned().unwrap_or(*tile)
+    }
+    pub fn clear_occupied(&mut self) {
+        for tile in self.occupied.iter_mut() {
+            *tile = false;
+        }
+    }
+    pub fn is_tile_occupied(&self, tile: &TilePos) -> bool {
+        if !self.contains(tile) {
+            return false;
+        }
+        let &TilePos(x, y) = tile;
+        self.occupied[x + y * self.size.0]
+    }
+    pub fn set_tile_occupied(&mut self, tile: &TilePos) {
+        if !self.contains(tile) {
+            return;
+        }
+        let &TilePos(x, y) = tile;
+        self.occupied[x + y * self.size.0] = true;
+    }
+    pub fn get_weight(&self, tile: &TilePos) -> OrderedFloat<f32> {
+        // there's no walking off the edge of the map
+        if !self.contains(tile) {
+            return OrderedFloat(f32::INFINITY);
+        }
+        let &TilePos(x, y) = tile;
+        // closed doors can't be walked through, and neither can tiles marked as unwalkable, or deep water
+        if self.closed[x + y * self.size.0] || self.is_impassable(tile) {
+            return OrderedFloat(f32::INFINITY);
+        }
+        let weight = self.data[x + y * self.size.0] * self.cost[x + y * self.size.0];
+        // roads and bridges are quick going, and never turn to mud
+        if self.road[x + y * self.size.0] {
+            return OrderedFloat(weight * ROAD_COST);
+        }
+        // wading is slower going still
+        if self.water[x + y * self.size.0] == WaterDepth::Shallow {
+            return OrderedFloat(weight * WADING_WEIGHT);
+        }
+        // mud is slow going
+        if self.muddy && self.low[x + y * self.size.0] {
+            return OrderedFloat(weight * MUD_WEIGHT);
+        }
+        OrderedFloat(weight)
+    }
+    // whether the tile can never be walked through, being unwalkable or deep water without a bridge over it
+    // unlike closed doors, which can be opened
+    pub fn is_impassable(&self, tile: &TilePos) -> bool {
+        let &TilePos(x, y) = tile;
+        !self.contains(tile) || !self.walkable[x + y * self.size.0] || self.is_deep_water(tile)
+    }
+    // whether anyone on the tile is wading through shallow water
+    pub fn is_wading(&self, tile: &TilePos) -> bool {
+        let &TilePos(x, y) = tile;
+        self.contains(tile) && self.water[x + y * self.size.0] == WaterDepth::Shallow && !self.road[x + y * self.size.0]
+    }
+    // whether the tile is deep water, which anyone in it drowns in, without a bridge over it
+    pub fn is_deep_water(&self, tile: &TilePos) -> bool {
+        let &TilePos(x, y) = tile;
+        self.contains(tile) && self.water[x + y * self.size.0] == WaterDepth::Deep && !self.road[x + y * self.size.0]
+    }
+    // gives how high up the tile is, with anything off the edge of the map at ground level
+    pub fn get_height(&self, tile: &TilePos) -> f32 {
+        if !self.contains(tile) {
+            return 0.0;
+        }
+        let &TilePos(x, y) = tile;
+        self.height[x + y * self.size.0]
+    }
+    // whether there's a cliff between two tiles, making them too far apart in height to walk between
+    pub fn is_cliff(&self, from: &TilePos, to: &TilePos) -> bool {
+        (self.get_height(from) - self.get_height(to)).abs() >= CLIFF_HEIGHT
+    }
+    // gives how much of the damage of attacks on anyone standing on the tile it blocks
+    // tiles off the edge of the map give no cover, and neither does wading out in the water
+    pub fn get_cover(&self, tile: &TilePos) -> f32 {
+        if !self.contains(tile) || self.is_wading(tile) {
+            return 0.0;
+        }
+        let &TilePos(x, y) = tile;
+        self.cover[x + y * self.size.0]
+    }
+    // annotates the tile with the properties given to it in the tiled map
+    // anything the properties leave out keeps what it was
+    pub fn set_tile_properties(&mut self, tile: &TilePos, properties: &TileProperties) {
+        if !self.contains(tile) {
+            return;
+        }
+        let &TilePos(x, y) = tile;
+        if let Some(walkable) = properties.walkable {
+            self.walkable[x + y * self.size.0] = walkable;
+        }
+        if let Some(cost) = properties.movement_cost {
+            self.cost[x + y * self.size.0] = cost;
+        }
+        if let Some(cover) = properties.cover {
+            self.cover[x + y * self.size.0] = cover;
+        }
+        if let Some(water) = properties.water {
+            self.water[x + y * self.size.0] = water;
+        }
+        if let Some(road) = properties.road {
+            self.road[x + y * self.size.0] = road;
+        }
+        if let Some(height) = properties.height {
+            self.height[x + y * self.size.0] = height;
+        }
+    }
+    // whether the tile is mud, which is the case for low ground while the map is muddy
+    // tiles off the edge of the map are never mud, and neither are roads and bridges
+    pub fn is_mud(&self, tile: &TilePos) -> bool {
+        let &TilePos(x, y) = tile;
+        self.muddy && self.contains(tile) && self.low[x + y * self.size.0] && !self.road[x + y * self.size.0]
+    }
+    // whether the tile is a road or a bridge
+    pub fn is_road(&self, tile: &TilePos) -> bool {
+        let &TilePos(x, y) = tile;
+        self.contains(tile) && self.road[x + y * self.size.0]
+    }
+    // gives the multiplier on the speed of anyone walking over the tile
+    // roads speed them up, and wading and mud slow them down
+    pub fn get_terrain_speed(&self, tile: &TilePos) -> f32 {
+        if self.is_road(tile) {
+            ROAD_SPEED
+        }else if self.is_wading(tile) {
+            WADING_SPEED
+        }else if self.is_mud(tile) {
+            MUD_SPEED
+        }else{
+            1.0
+        }
+    }
+    // gives how far a noise has travelled to reach every tile it reaches, spreading out from its origin tile by tile
+    // going through walls and closed doors uses up much more of the noise's range than crossing open ground,
+    // so noises are muffled by whatever lies between, and can't be heard beyond the range
+    pub fn propagate_noise(&self, config: &GameConfig, origin: &TilePos, range: f32) -> HashMap<TilePos, f32> {
+        let mut travelled = HashMap::new();
+        let mut frontier = BinaryHeap::new();
+        travelled.insert(*origin, 0.0);
+        frontier.push((Reverse(OrderedFloat(0.0)), origin.0, origin.1));
+        while let Some((Reverse(OrderedFloat(dist)), x, y)) = frontier.pop() {
+            // skip tiles already reached a shorter way
+            if travelled.get(&TilePos(x, y)).map_or(false, |&best| dist > best) {
+                continue;
+            }
+            for j in -1..2 {
+                for i in -1..2 {
+                    if i == 0 && j == 0 {
+                        continue;
+                    }
+                    let nx = x as i32 + i;
+                    let ny = y as i32 + j;
+                    if nx < 0 || ny < 0 || !self.contains(&TilePos(nx as usize, ny as usize)) {
+                        continue;
+                    }
+                    let next = TilePos(nx as usize, ny as usize);
+                    let step = if i != 0 && j != 0 { config.tile_size * 1.414 } else { config.tile_size };
+                    let muffling = if self.is_tile_blocked(&next) { NOISE_WALL_MUFFLING } else { 1.0 };
+                    let next_dist = dist + step * muffling;
+                    if next_dist > range || travelled.get(&next).map_or(false, |&best| next_dist >= best) {
+                        continue;
+                    }
+                    travelled.insert(next, next_dist);
+                    frontier.push((Reverse(OrderedFloat(next_dist)), next.0, next.1));
+                }
+            }
+        }
+        travelled
+    }
+    pub fn set_tile_closed(&mut self, tile: &TilePos, closed: bool) {
+        let &TilePos(x, y) = tile;
+        self.closed[x + y * self.size.0] = closed;
+    }
+    pub fn destroy_tile(&mut self, tile: &TilePos) {
+        let &TilePos(x, y) = tile;
+        self.destroyed[x + y * self.size.0] = true;
+        self.walkable[x + y * self.size.0] = true;
+        self.cover[x + y * self.size.0] = 0.0;
+        self.water[x + y * self.size.0] = WaterDepth::Dry;
+        self.data[x + y * self.size.0] = get_map_weight_from_tile_type(TileType::Grass);
+    }
+    // reads in the tiles painted in the tile editor and lays them over the map
+    // one tile per line, its x and y followed by its properties, see TileProperties::from_line
+    // gives back the tiles read in, so the spawn points among them can be spawned
+    pub fn load_painted_tiles(&mut self, path: &str) -> Vec<(TilePos, TileProperties)> {
+        let mut tiles = Vec::new();
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(err) => {
+                eprintln!("could not read painted tiles from {}: {}", path, err);
+                return tiles;
+            },
+        };
+        for line in text.lines().map(|line| line.trim()) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut parts = line.splitn(3, ' ');
+            let x = parts.next().and_then(|x| x.parse().ok()).expect("bad x in painted tiles");
+            let y = parts.next().and_then(|y| y.parse().ok()).expect("bad y in painted tiles");
+            let tile = TilePos(x, y);
+            let properties = TileProperties::from_line(parts.next().unwrap_or_default());
+            self.set_tile_properties(&tile, &properties);
+            tiles.push((tile, properties));
+        }
+        tiles
+    }
+    pub fn get_diag_dist(&self, a: TilePos, b: TilePos) -> OrderedFloat<f32> {
+        let TilePos(ax, ay) = a;
+        let TilePos(bx, by) = b;
+        let dx = (ax as f32 - bx as f32).abs();
+        let dy = (ay as f32 - by as f32).abs();
+        let c = self.get_weight(&a).0;
+        OrderedFloat(c * (dx + dy) + (c * 1.414 - 2.0 * c) * dx.min(dy))
+    }
+    pub fn get_tile(&self, x: i32, y: i32) -> TileType{
+        let noise = self.generator.get([x as f64, y as f64]);
+        MapData::convert_f64_to_tiletype(noise)
+    }
+    pub fn update_map(&mut self, x: i32, y: i32) {
+        for j in 0..self.size.1 {
+            for i in 0..self.size.0 {
+                let tile = self.get_tile(i as i32 + x, j as i32 + y);
+                // water collects on the low ground when it rains
+                self.low[i + j * self.size.0] = match tile {
+                    TileType::Water => true,
+                    _ => false,
+                };
+                // the water is only laid out once, so the tiled map's water isn't washed away
+                if !self.generated {
+                    self.water[i + j * self.size.0] = match tile {
+                        TileType::ShallowWater => WaterDepth::Shallow,
+                        TileType::DeepWater => WaterDepth::Deep,
+                        _ => WaterDepth::Dry,
+                    };
+                }
+                // tiles that have been blown open are open ground from then on
+                let tile = if self.destroyed[i + j * self.size.0] { TileType::Grass } else { tile };
+                self.data[i + j * self.size.0] = get_map_weight_from_tile_type(tile);
+            }
+        }
+        self.generated = true;
+    }
+}
+
+// tile properties struct
+// the custom properties designers can give tiles in tiled, all of them optional:
+//   walkable (bool) - false for tiles nobody can walk through, like walls
+//   movement_cost (float) - how many times harder the tile is to cross than open ground
+//   cover (bool or float) - how much of the damage of attacks on anyone standing on the tile it blocks,
+//     true for the default amount
+//   road (bool) - makes the tile a road, which is cheaper to cross, faster to walk along and never turns to mud
+//   bridge (bool) - makes the tile a bridge, a road that can be walked across even over water
+//   water (string) - how deep the water on the tile is: shallow, which can be waded through slowly
+//     without any cover and with worse aim, deep, which can't be crossed, or dry
+//   height (float or int) - how high up the tile is, 0 for open ground. tiles CLIFF_HEIGHT or more
+//     apart can't be walked between, and attacks from higher ground reach further and hit more often
+//   spawn (string) - makes the tile a spawn point for reinforcements, with the given name
+//   trigger (string) - makes the tile part of a trigger zone, given as <kind> or <kind>:<name>,
+//     with the tiles sharing a trigger making up one zone
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TileProperties {
+    pub walkable: Option<bool>,
+    pub movement_cost: Option<f32>,
+    pub cover: Option<f32>,
+    pub road: Option<bool>,
+    pub water: Option<WaterDepth>,
+    pub height: Option<f32>,
+    pub spawn: Option<String>,
+    pub trigger: Option<String>,
+}
+
+impl TileProperties {
+    // reads the properties of a tile in tiled, ignoring any that aren't for the game
+    pub fn from_tiled(properties: &HashMap<String, PropertyValue>) -> Self {
+        let mut tile = TileProperties::default();
+        let mut bridge = false;
+        for (key, value) in properties.iter() {
+            match (key.as_str(), value) {
+                ("walkable", PropertyValue::BoolValue(walkable)) => tile.walkable = Some(*walkable),
+                ("movement_cost", PropertyValue::FloatValue(cost)) => tile.movement_cost = Some(cost.max(0.0)),
+                ("movement_cost", PropertyValue::IntValue(cost)) => tile.movement_cost = Some((*cost as f32).max(0.0)),
+                ("cover", PropertyValue::BoolValue(cover)) => tile.cover = Some(if *cover { TILE_COVER } else { 0.0 }),
+                ("cover", PropertyValue::FloatValue(cover)) => tile.cover = Some(cover.max(0.0).min(1.0)),
+                ("road", PropertyValue::BoolValue(road)) => tile.road = Some(*road),
+                ("bridge", PropertyValue::BoolValue(spans)) => bridge = *spans,
+                ("water", PropertyValue::StringValue(depth)) if WaterDepth::from_name(depth).is_some() => tile.water = WaterDepth::from_name(depth),
+                ("height", PropertyValue::FloatValue(height)) => tile.height = Some(*height),
+                ("height", PropertyValue::IntValue(height)) => tile.height = Some(*height as f32),
+                ("spawn", PropertyValue::StringValue(name)) => tile.spawn = Some(name.clone()),
+                ("trigger", PropertyValue::StringValue(trigger)) => tile.trigger = Some(trigger.clone()),
+                ("walkable", _) | ("movement_cost", _) | ("cover", _) | ("road", _) | ("bridge", _) | ("water", _) | ("height", _) | ("spawn", _) | ("trigger", _) => {
+                    eprintln!("bad value for tile property {} in map", key);
+                },
+                _ => {},
+            }
+        }
+        // bridges are roads that can be walked across, whatever is under them
+        if bridge {
+            tile.road = Some(true);
+            tile.walkable = tile.walkable.or(Some(true));
+        }
+        tile
+    }
+    // whether any of the properties are for the game
+    pub fn is_empty(&self) -> bool {
+        *self == TileProperties::default()
+    }
+    // takes on every property the other gives, keeping the rest
+    pub fn overlay(&mut self, other: &TileProperties) {
+        self.walkable = other.walkable.or(self.walkable);
+        self.movement_cost = other.movement_cost.or(self.movement_cost);
+        self.cover = other.cover.or(self.cover);
+        self.road = other.road.or(self.road);
+        self.water = other.water.or(self.water);
+        self.height = other.height.or(self.height);
+        self.spawn = other.spawn.clone().or_else(|| self.spawn.clone());
+        self.trigger = other.trigger.clone().or_else(|| self.trigger.clone());
+    }
+    // reads properties written out by to_line, as name=value pairs split by spaces
+    pub fn from_line(line: &str) -> Self {
+        let mut tile = TileProperties::default();
+        for pair in line.split_whitespace() {
+            let mut parts = pair.splitn(2, '=');
+            let key = parts.next().unwrap_or_default();
+            let value = parts.next().unwrap_or_default();
+            let read = match key {
+                "walkable" => value.parse().ok().map(|walkable| tile.walkable = Some(walkable)),
+                "movement_cost" => value.parse::<f32>().ok().map(|cost| tile.movement_cost = Some(cost.max(0.0))),
+                "cover" => value.parse::<f32>().ok().map(|cover| tile.cover = Some(cover.max(0.0).min(1.0))),
+                "road" => value.parse().ok().map(|road| tile.road = Some(road)),
+                "water" => WaterDepth::from_name(value).map(|water| tile.water = Some(water)),
+                "height" => value.parse().ok().map(|height| tile.height = Some(height)),
+                "spawn" if !value.is_empty() => Some(tile.spawn = Some(value.to_string())),
+                "trigger" if !value.is_empty() => Some(tile.trigger = Some(value.to_string())),
+                _ => None,
+            };
+            if read.is_none() {
+                eprintln!("bad tile property {} in painted tiles", pair);
+            }
+        }
+        tile
+    }
+    // gives the properties as a line of name=value pairs, read back in by from_line
+    pub fn to_line(&self) -> String {
+        let mut pairs = Vec::new();
+        if let Some(walkable) = self.walkable {
+            pairs.push(format!("walkable={}", walkable));
+        }
+        if let Some(cost) = self.movement_cost {
+            pairs.push(format!("movement_cost={}", cost));
+        }
+        if let Some(cover) = self.cover {
+            pairs.push(format!("cover={}", cover));
+        }
+        if let Some(road) = self.road {
+            pairs.push(format!("road={}", road));
+        }
+        if let Some(water) = self.water {
+            pairs.push(format!("water={}", water.name()));
+        }
+        if let Some(height) = self.height {
+            pairs.push(format!("height={}", height));
+        }
+        if let Some(spawn) = &self.spawn {
+            pairs.push(format!("spawn={}", spawn));
+        }
+        if let Some(trigger) = &self.trigger {
+            pairs.push(format!("trigger={}", trigger));
+        }
+        pairs.join(" ")
+    }
+}
+
+// map properties plugin
+// responsible for honouring the custom properties of the tiled map's tiles, see TileProperties,
+// once the map has finished loading
+pub struct MapPropertiesPlugin;
+
+// implementation of the plugin trait,
+// required for this to be used as a plugin
+impl Plugin for MapPropertiesPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        // initialise the loaded tile properties resource
+        app.init_resource::<TilePropertiesLoaded>()
+        // add in the tile property loading system
+        .add_system(load_tile_properties_system.system());
+    }
+}
+
+// loaded tile properties resource
+// whether the tile properties have been read from the map yet
+#[derive(Default)]
+pub struct TilePropertiesLoaded(pub bool);
+
+// gives the properties of the tile with the given global id in the tiled map, if it has any
+pub fn get_tiled_tile_properties(map: &bevy_tiled::tiled::Map, gid: u32) -> Option<TileProperties> {
+    // a global id of 0 is an empty tile
+    if gid == 0 {
+        return None;
+    }
+    let tileset = map.tilesets.iter()
+        .filter(|tileset| tileset.first_gid <= gid)
+        .max_by_key(|tileset| tileset.first_gid)?;
+    let id = gid - tileset.first_gid;
+    let tile = tileset.tiles.iter().find(|tile| tile.id == id)?;
+    let properties = TileProperties::from_tiled(&tile.properties);
+    if properties.is_empty() { None } else { Some(properties) }
+}
+
+// load tile properties system
+// annotates the map data with the properties of every tile in every layer of the tiled map,
+// spawning spawn points and trigger zones for the tiles marked as them
+// tiles in later layers override the properties of those under them, and any tiles painted in the tile editor
+// that the game was launched with override the lot
+// unwalkable tiles and deep water are walled off, if the walls have already been built by the time the map loads
+pub fn load_tile_properties_system(mut commands: Commands, config: Res<GameConfig>, launch: Res<LaunchOptions>, mut loaded: ResMut<TilePropertiesLoaded>, mut map_data: ResMut<MapData>,
+    mut walls: ResMut<WallColliders>, maps: Res<Assets<bevy_tiled::Map>>, mut query: Query<&Handle<bevy_tiled::Map>>) {
+    if loaded.0 {
+        return;
+    }
+    for handle in &mut query.iter() {
+        if let Some(map) = maps.get(&handle) {
+            // the tiles making up each trigger zone, as the corners of the area they cover
+            let mut triggers: HashMap<String, ((f32, f32), (f32, f32))> = HashMap::new();
+            for layer in map.map.layers.iter() {
+                for (y, row) in layer.tiles.iter().enumerate() {
+                    for (x, layer_tile) in row.iter().enumerate() {
+                        let properties = match get_tiled_tile_properties(&map.map, layer_tile.gid) {
+                            Some(properties) => properties,
+                            None => continue,
+                        };
+                        // each tile in tiled is scaled up to a tile of the game
+                        let min = (x as f32 * config.tile_size, y as f32 * config.tile_size);
+                        let max = (min.0 + config.tile_size, min.1 + config.tile_size);
+                        let center = (min.0 + config.tile_size / 2.0, min.1 + config.tile_size / 2.0);
+                        map_data.set_tile_properties(&TilePos::from_coords(&config, center.0, center.1), &properties);
+                        if let Some(name) = &properties.spawn {
+                            // reinforcements come in through spawn points
+                            spawn_spawn_point(&mut commands, name.clone(), center);
+                        }
+                        if let Some(trigger) = properties.trigger {
+                            let area = triggers.entry(trigger).or_insert((min, max));
+                            area.0 = ((area.0).0.min(min.0), (area.0).1.min(min.1));
+                            area.1 = ((area.1).0.max(max.0), (area.1).1.max(max.1));
+                        }
+                    }
+                }
+            }
+            for (trigger, (min, max)) in triggers.into_iter() {
+                let mut parts = trigger.splitn(2, ':');
+                let kind = parts.next().unwrap_or_default();
+                let name = parts.next().unwrap_or_default().to_string();
+                match ZoneKind::from_name(kind) {
+                    Some(kind) => {
+                        let size = (max.0 - min.0, max.1 - min.1);
+                        let center = (min.0 + size.0 / 2.0, min.1 + size.1 / 2.0);
+                        spawn_zone(&config, &mut commands, kind, name, center, size);
+                    },
+                    None => eprintln!("unknown trigger in map tile properties: {}", trigger),
+                }
+            }
+            if let Some(path) = &launch.tiles_path {
+                for (tile, properties) in map_data.load_painted_tiles(path) {
+                    if let Some(name) = properties.spawn {
+                        spawn_spawn_point(&mut commands, name, tile.to_coords(&config));
+                    }
+                }
+            }
+            if walls.built {
+                for j in 0..map_data.size.1 {
+                    for i in 0..map_data.size.0 {
+                        let tile = TilePos(i, j);
+                        if map_data.is_impassable(&tile) && !walls.walls.contains_key(&tile) {
+                            if let Some(wall) = spawn_wall_collider(&mut commands, &config, &tile) {
+                                walls.walls.insert(tile, wall);
+                            }
+                        }
+                    }
+                }
+            }
+            loaded.0 = true;
+        }
+    }
+}
+
+// map transition plugin
+// responsible for moving the squad on to the next stage of a multi-map mission, once the whole squad
+// is standing in an exit zone. everything left on the old map is cleared away, and the stage's map
+// and encounter, see STAGE_MAP_DIR and STAGE_ENCOUNTER_DIR, are loaded in its place
+// squad members keep everything about them, and start the new map at its player start
+pub struct MapTransitionPlugin;
+
+// implementation of the plugin trait,
+// required for this to be used as a plugin
+impl Plugin for MapTransitionPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        // initialise the map transition resource and the event state
+        app.init_resource::<MapTransition>()
+        .init_resource::<ExitZoneEventState>()
+        // add in the system watching for the squad in exit zones
+        .add_system(exit_zone_system.system())
+        // the move is made after everything else has run, so the old map is gone before the next frame
+        // the systems making it run in this order, and the last one finishes the move
+        .add_system_to_stage(stage::POST_UPDATE, transition_people_system.system())
+        .add_system_to_stage(stage::POST_UPDATE, transition_clear_map_system.system())
+        .add_system_to_stage(stage::POST_UPDATE, transition_load_map_system.system())
+        .add_system_to_stage(stage::POST_UPDATE, transition_load_encounter_system.system());
+    }
+}
+
+// map transition resource
+// keeps track of which squad members are standing in each exit zone,
+// and the stage the squad is moving on to, until the move has been made
+#[derive(Default)]
+pub struct MapTransition {
+    pub inside: HashMap<Entity, HashSet<Entity>>,
+    // the map of the stage, and its encounter if it has one
+    pub pending: Option<(String, Option<String>)>,
+}
+
+// exit zone event state holds an event reader for zone events
+#[derive(Default)]
+pub struct ExitZoneEventState {
+    pub event_reader: EventReader<ZoneEvent>,
+}
+
+// gives the paths of the map, and encounter if there is one, of the stage of a mission with the given name
+pub fn get_stage_paths(mods: &ModAssets, stage: &str) -> (String, Option<String>) {
+    let map = mods.resolve(&format!("{}/{}.tmx", STAGE_MAP_DIR, stage));
+    let encounter = mods.resolve(&format!("{}/{}.txt", STAGE_ENCOUNTER_DIR, stage));
+    (map, if Path::new(&encounter).exists() { Some(encounter) } else { None })
+}
+
+// exit zone system
+// starts the move on to the next stage once every squad member still standing is in the same exit zone
+pub fn exit_zone_system(mut state: ResMut<ExitZoneEventState>, events: Res<Events<ZoneEvent>>, mods: Res<ModAssets>, mut transition: ResMut<MapTransition>,
+    mut zones: Query<(Entity, &Zone)>, mut squad: Query<(Entity, &Controlled)>) {
+    for event in state.event_reader.iter(&events) {
+        if event.kind != ZoneKind::Exit {
+            continue;
+        }
+        let inside = transition.inside.entry(event.zone).or_insert_with(HashSet::new);
+        if event.entered {
+            inside.insert(event.person);
+        }else{
+            inside.remove(&event.person);
+        }
+    }
+    if transition.pending.is_some() {
+        return;
+    }
+
+    let mut standing = Vec::new();
+    for (entity, _controlled) in &mut squad.iter() {
+        standing.push(entity);
+    }
+    if standing.is_empty() {
+        return;
+    }
+    for (entity, zone) in &mut zones.iter() {
+        if zone.kind != ZoneKind::Exit {
+            continue;
+        }
+        let everyone_inside = match transition.inside.get(&entity) {
+            Some(inside) => standing.iter().all(|person| inside.contains(person)),
+            None => false,
+        };
+        if everyone_inside {
+            let paths = get_stage_paths(&mods, &zone.name);
+            if !Path::new(&paths.0).exists() {
+                eprintln!("no map for the next stage of the mission: {}", paths.0);
+                continue;
+            }
+            transition.pending = Some(paths);
+            break;
+        }
+    }
+}
+
+// transition people system
+// removes everyone but the squad from the old map, along with the bodies left lying on it,
+// and has the squad drop whatever they were doing and start out again at the new map's player start
+pub fn transition_people_system(mut commands: Commands, transition: Res<MapTransition>, mut extraction: ResMut<Extraction>,
+    mut bodies: ResMut<RigidBodySet>, mut colliders: ResMut<ColliderSet>, mut joints: ResMut<JointSet>,
+    mut people: Query<(Entity, &Person, Option<&RigidBodyHandleComponent>)>, mut squad: Query<(&mut Controlled, &mut Nerve, &mut Pathfinder)>,
+    mut corpses: Query<(Entity, &Corpse)>) {
+    if transition.pending.is_none() {
+        return;
+    }
+    for (entity, pers, body) in &mut people.iter() {
+        if pers.attitude == AttitudeType::Squad {
+            commands.insert_one(entity, StartPoint::new(PLAYER_START));
+        }else{
+            remove_person(&mut commands, entity, body, &mut bodies, &mut colliders, &mut joints);
+        }
+    }
+    for (mut controlled, mut nerve, mut pathfinder) in &mut squad.iter() {
+        controlled.current_command = Command::default();
+        controlled.clear_queue();
+        controlled.attack_move = None;
+        nerve.clear();
+        *pathfinder = Pathfinder::default();
+    }
+    for (entity, _corpse) in &mut corpses.iter() {
+        commands.despawn(entity);
+    }
+    // the old map's extraction zones are going
+    extraction.inside.clear();
+}
+
+// transition clear map system
+// removes the old map's walls, zones, doors, keys, alarms and spawn points,
+// and starts the map data afresh, ready for the new map's tiles and zones to be read in
+pub fn transition_clear_map_system(mut commands: Commands, config: Res<GameConfig>, seed: Res<RunSeed>, transition: Res<MapTransition>,
+    mut map_data: ResMut<MapData>, mut walls: ResMut<WallColliders>, mut zones_loaded: ResMut<MapZonesLoaded>, mut properties_loaded: ResMut<TilePropertiesLoaded>,
+    mut bodies: ResMut<RigidBodySet>, mut colliders: ResMut<ColliderSet>, mut joints: ResMut<JointSet>,
+    mut zones: Query<(Entity, &Zone)>, mut doors: Query<(Entity, &Door)>, mut keys: Query<(Entity, &Key)>, mut alarms: Query<(Entity, &Alarm)>,
+    mut spawn_points: Query<(Entity, &SpawnPoint)>, mut statics: Query<(Entity, &RigidBodyHandleComponent)>) {
+    if transition.pending.is_none() {
+        return;
+    }
+    // everything with a body in the physics world has it taken out too
+    let mut removed: HashSet<Entity> = walls.walls.values().cloned().collect();
+    for (entity, _zone) in &mut zones.iter() {
+        removed.insert(entity);
+    }
+    for (entity, door) in &mut doors.iter() {
+        removed.extend(door.walls.iter().cloned());
+        commands.despawn(entity);
+    }
+    for (entity, body) in &mut statics.iter() {
+        if removed.remove(&entity) {
+            bodies.remove(body.handle(), &mut colliders, &mut joints);
+            commands.despawn(entity);
+        }
+    }
+    for (entity, _key) in &mut keys.iter() {
+        commands.despawn(entity);
+    }
+    for (entity, _alarm) in &mut alarms.iter() {
+        commands.despawn(entity);
+    }
+    for (entity, _point) in &mut spawn_points.iter() {
+        commands.despawn(entity);
+    }
+
+    // the walls are built again once the new map data has been generated
+    *map_data = MapData::new(seed.0 as u32, &config);
+    *walls = WallColliders::default();
+    zones_loaded.0 = false;
+    properties_loaded.0 = false;
+}
+
+// transition load map system
+// swaps the old tiled map out for the new one, forgetting everything read in from the old one
+pub fn transition_load_map_system(mut commands: Commands, asset_server: Res<AssetServer>, transition: Res<MapTransition>,
+    mut starts: ResMut<MapStartPoints>, mut routes: ResMut<MapPatrolRoutes>, mut light: ResMut<LightMap>,
+    mut maps: Query<(Entity, &Handle<bevy_tiled::Map>)>, mut chunks: Query<(Entity, &bevy_tiled::TileMapChunk)>) {
+    let (map_path, _) = match &transition.pending {
+        Some(paths) => paths,
+        None => return,
+    };
+    for (entity, _handle) in &mut maps.iter() {
+        commands.despawn(entity);
+    }
+    for (entity, _chunk) in &mut chunks.iter() {
+        commands.despawn(entity);
+    }
+    starts.0.clear();
+    routes.0.clear();
+    light.lights.clear();
+
+    commands
+        .spawn(bevy_tiled::TiledMapComponents {
+            map_asset: asset_server.load(map_path.as_str()).unwrap(),
+            center: true,
+            ..Default::default()
+        });
+}
+
+// transition load encounter system
+// brings in the new stage's encounter, if it has one, in place of whatever was left of the old one,
+// and finishes the move
+pub fn transition_load_encounter_system(mut commands: Commands, config: Res<GameConfig>, mut transition: ResMut<MapTransition>, mut materials: ResMut<Assets<ColorMaterial>>,
+    archetypes: Res<Archetypes>, difficulty: Res<Difficulty>, mut scheduler: ResMut<WaveScheduler>, mut reinforcements: ResMut<Reinforcements>,
+    mut stages: ResMut<EncounterStages>, mut clock: ResMut<WorldClock>, mut weather: ResMut<Weather>) {
+    let (_, encounter_path) = match transition.pending.take() {
+        Some(paths) => paths,
+        None => return,
+    };
+    transition.inside.clear();
+
+    scheduler.waves.clear();
+    scheduler.total = 0;
+    scheduler.arrived = 0;
+    scheduler.timer = None;
+    reinforcements.composition.clear();
+    reinforcements.raised.clear();
+    stages.dormant.clear();
+    if let Some(path) = encounter_path {
+        scheduler.waves = load_encounter(&config, &mut commands, &mut materials, &archetypes, &difficulty, &mut reinforcements, &mut stages, &mut clock, &mut weather, &path);
+        scheduler.total = scheduler.waves.len();
+    }
+}
+
+// map overlay plugin
+// debug overlays drawn over the tiles of the map, for checking the pathfinding data against what the tiled map shows
+// the weight heatmap shades each tile by how hard it is to cross, with blocked tiles in black,
+// and the occupancy overlay marks each occupied tile in red
+pub struct MapOverlayPlugin;
+
+// implementation of the plugin trait,
+// required for this to be used as a plugin
+impl Plugin for MapOverlayPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        // initialise the overlays, both hidden
+        app.init_resource::<MapOverlays>()
+        // add in the toggles for the overlays
+        .add_system(map_overlay_toggle_system.system())
+        // add in the system shading the overlays
+        .add_system(map_overlay_system.system());
+    }
+}
+
+// map overlays resource
+// which of the overlays are being shown, and the square drawn over each tile while any of them are
+#[derive(Default)]
+pub struct MapOverlays {
+    pub weights: bool,
+    pub occupancy: bool,
+    pub cells: Vec<Entity>,
+}
+
+// map overlay cell component
+// spawn this component along with the square of the map overlays drawn over a tile
+pub struct MapOverlayCell(pub TilePos);
+
+// gives the colour of a tile in the weight heatmap
+pub fn get_heatmap_color(weight: f32) -> Color {
+    if weight.is_infinite() {
+        return Color::rgba(0.0, 0.0, 0.0, MAP_OVERLAY_OPACITY);
+    }
+    let heat = ((weight - 1.0) / (HEATMAP_MAX_WEIGHT - 1.0)).max(0.0).min(1.0);
+    Color::rgba(heat, 1.0 - heat, 0.0, MAP_OVERLAY_OPACITY)
+}
+
+// map overlay toggle system
+// shows or hides each overlay when its key is pressed
+pub fn map_overlay_toggle_system(inputs: Res<InputState>, bindings: Res<KeyBindings>, mut overlays: ResMut<MapOverlays>) {
+    if inputs.key_just_presses.contains(&bindings.weight_overlay) {
+        overlays.weights = !overlays.weights;
+    }
+    if inputs.key_just_presses.contains(&bindings.occupancy_overlay) {
+        overlays.occupancy = !overlays.occupancy;
+    }
+}
+
+// map overlay system
+// covers the map in squares once an overlay is shown, clearing them away again once none are,
+// and shades each square from the map data, with occupied tiles drawn over the heatmap
+pub fn map_overlay_system(mut commands: Commands, config: Res<GameConfig>, map: Res<MapData>, mut overlays: ResMut<MapOverlays>,
+    mut materials: ResMut<Assets<ColorMaterial>>, mut query: Query<(&MapOverlayCell, &Handle<ColorMaterial>)>) {
+    let shown = overlays.weights || overlays.occupancy;
+    if !shown {
+        for cell in overlays.cells.drain(..) {
+            commands.despawn(cell);
+        }
+        return;
+    }
+    if overlays.cells.is_empty() {
+        for y in 0..map.size.1 {
+            for x in 0..map.size.0 {
+                let tile = TilePos(x, y);
+                let coords = tile.to_coords(&config);
+                // each cell is shaded on its own, so needs its own material
+                let material = materials.add(Color::rgba(0.0, 0.0, 0.0, 0.0).into());
+                let mut cell = SimpleRect::new(material, Vec2::new(config.tile_size, config.tile_size));
+                // drawn over everything else, darkness included
+                cell.translation = Translation(Vec3::new(-1000.0, -1000.0, MAP_OVERLAY_Z_LEVEL));
+                commands
+                    .spawn(cell)
+                    .with(Position(coords.0, coords.1))
+                    .with(MapOverlayCell(tile));
+                if let Some(cell) = commands.current_entity() {
+                    overlays.cells.push(cell);
+                }
+            }
+        }
+        return;
+    }
+
+    for (cell, handle) in &mut query.iter() {
+        if let Some(material) = materials.get_mut(&handle) {
+            material.color = if overlays.occupancy && map.is_tile_occupied(&cell.0) {
+                Color::rgba(1.0, 0.0, 0.0, MAP_OVERLAY_OPACITY)
+            }else if overlays.weights {
+                get_heatmap_color(map.get_weight(&cell.0).0)
+            }else{
+                Color::rgba(0.0, 0.0, 0.0, 0.0)
+            };
+        }
+    }
+}
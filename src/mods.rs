@@ -0,0 +1,88 @@
+// mods module
+// letting mods override the base assets
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// mod assets resource
+// responsible for letting mods override the base assets
+// every directory in mods/ is a mod, laid out the same way as assets/
+// e.g. mods/my_mod/maps/ortho-map.tmx replaces assets/maps/ortho-map.tmx
+// mods are loaded in the order listed in mods/load_order.txt, one name per line,
+// or alphabetically if there is no load order. later mods win over earlier ones
+pub struct ModAssets {
+    // root directory of every mod, in load order
+    pub mods: Vec<PathBuf>,
+}
+
+impl ModAssets {
+    // finds every mod in the given directory
+    pub fn load(path: &str) -> Self {
+        let root = Path::new(path);
+        let mut mods = Vec::new();
+
+        match fs::read_to_string(root.join("load_order.txt")) {
+            // load order given, use it
+            Ok(order) => {
+                for name in order.lines().map(|line| line.trim()) {
+                    // skip blank lines and comments
+                    if name.is_empty() || name.starts_with('#') {
+                        continue;
+                    }
+                    let dir = root.join(name);
+                    if dir.is_dir() {
+                        mods.push(dir);
+                    }else{
+                        eprintln!("mod {} in load order not found", name);
+                    }
+                }
+            },
+            // no load order, use every mod alphabetically
+            Err(_) => {
+                if let Ok(entries) = fs::read_dir(root) {
+                    for entry in entries.filter_map(|e| e.ok()) {
+                        if entry.path().is_dir() {
+                            mods.push(entry.path());
+                        }
+                    }
+                }
+                mods.sort();
+            },
+        }
+
+        ModAssets {
+            mods: mods,
+        }
+    }
+
+    // gives the path of the asset that should actually be loaded
+    // for a path under assets/, checking the mods from last to first
+    pub fn resolve(&self, path: &str) -> String {
+        let relative = match Path::new(path).strip_prefix("assets") {
+            Ok(relative) => relative,
+            // only paths under assets/ can be overridden
+            Err(_) => return path.to_string(),
+        };
+        for dir in self.mods.iter().rev() {
+            let candidate = dir.join(relative);
+            if candidate.exists() {
+                return candidate.to_string_lossy().to_string();
+            }
+        }
+        path.to_string()
+    }
+
+    // gives every directory that matches a directory under assets/
+    // the base directory comes first, followed by the mods in load order
+    pub fn resolve_dirs(&self, path: &str) -> Vec<String> {
+        let mut dirs = vec![path.to_string()];
+        if let Ok(relative) = Path::new(path).strip_prefix("assets") {
+            for dir in self.mods.iter() {
+                let candidate = dir.join(relative);
+                if candidate.is_dir() {
+                    dirs.push(candidate.to_string_lossy().to_string());
+                }
+            }
+        }
+        dirs
+    }
+}
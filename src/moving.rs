@@ -0,0 +1,34 @@
+// moving module
+// moves everything with a velocity
+use bevy::prelude::*;
+use bevy_rapier2d::physics::RigidBodyHandleComponent;
+
+use crate::{MOVEMENT_STAGE, Position, Velocity};
+use crate::tick::AddTickSystem;
+
+// moving plugin 
+// this plugin is in charge of moving everything with both a position component
+// and a velocity component
+pub struct MovingPlugin;
+
+// implementation of the plugin trait,
+// required for this to be used as a plugin
+impl Plugin for MovingPlugin {
+    fn build(&self, app: &mut AppBuilder){
+        // add in the move system
+        app.add_tick_system_to_stage(MOVEMENT_STAGE, move_system.system());
+    }
+}
+
+// move system
+// this function goes through all entities with both position and velocity components
+// and moves them
+// entities with a physics body are moved by the collision plugin instead, which keeps them out of walls
+pub fn move_system(time: Res<Time>, mut query: Query<Without<RigidBodyHandleComponent, (&mut Position, &Velocity)>>){
+    for (mut pos, vel) in &mut query.iter() {
+        // adjust the amount moved by the time passed since last tick - this keeps
+        // movement consistent despite inconsistent fps
+        pos.0 += vel.0 * time.delta_seconds;
+        pos.1 += vel.1 * time.delta_seconds;
+    }
+}
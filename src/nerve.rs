@@ -0,0 +1,340 @@
+// nerve module
+// the action queue each person works through
+use bevy::prelude::*;
+use std::collections::{VecDeque, HashMap};
+
+use crate::actions::ActionFailure;
+
+// Nerve component
+// holds the current action as well as succeeding actions
+pub struct Nerve {
+    // current action is the action being worked on
+    pub current_action: Action,
+    // action queue holds the next actions, after the current one
+    pub action_queue: VecDeque<Action>,
+    // action timer allows for a sense of realtime
+    // it belongs to the current action, and is stopped whenever the next action comes up
+    pub action_timer: Option<Timer>,
+    // actions carried through to the end since the action completed system last ran
+    pub completed: Vec<Action>,
+    // actions given up on because they couldn't be carried out, along with why, since the action completed system last ran
+    pub failed: Vec<(Action, ActionFailure)>,
+}
+
+impl Nerve {
+    // function to initialise a new Nerve component
+    pub fn new() -> Self {
+        Nerve {
+            // initialise with an empty action
+            current_action: Action::default(),
+            // initialise with an empty action queue
+            action_queue: VecDeque::new(),
+            // initialise with None
+            action_timer: None,
+            // initialise with nothing completed
+            completed: Vec::new(),
+            // initialise with nothing failed
+            failed: Vec::new(),
+        }
+    }
+    // moves on to the next action in the queue, or the empty action if there isn't one,
+    // giving back the action that was current
+    // the action timer is stopped, as it belonged to the action moved on from
+    // use this for actions that are skipped or given up on
+    pub fn advance(&mut self) -> Action {
+        self.action_timer = None;
+        let next = self.action_queue.pop_front().unwrap_or_default();
+        std::mem::replace(&mut self.current_action, next)
+    }
+    // moves on to the next action like advance, counting the current action as completed
+    pub fn complete_action(&mut self) {
+        let finished = self.advance();
+        self.completed.push(finished);
+    }
+    // moves on to the next action like advance, counting the current action as failed for the given reason
+    pub fn fail_action(&mut self, reason: ActionFailure) {
+        let failed = self.advance();
+        self.failed.push((failed, reason));
+    }
+    // gives the action lined up after the current one, if there is one
+    pub fn peek(&self) -> Option<&Action> {
+        self.action_queue.front()
+    }
+    // lines up an action to be done straight after the current one
+    pub fn push_front(&mut self, action: Action) {
+        self.action_queue.push_front(action);
+    }
+    // drops the current action and everything lined up after it, leaving the entity with nothing to do
+    pub fn clear(&mut self) {
+        self.current_action = Action::default();
+        self.action_queue.clear();
+        self.action_timer = None;
+    }
+    // drops everything like clear, and lines up the given actions instead
+    // these are started on from the next tick, once the empty action is moved on from
+    pub fn replace_all<I: IntoIterator<Item = Action>>(&mut self, actions: I) {
+        self.clear();
+        self.action_queue.extend(actions);
+    }
+    // checks whether the entity is only doing something passive, which taking damage should put a stop to
+    // walking a patrol route counts, as the patrol action waits in the queue behind each leg of the route
+    pub fn is_passive(&self) -> bool {
+        self.current_action.action_type.is_passive()
+            || self.peek().map_or(false, |action| action.action_type == ActionType::Patrol)
+    }
+    // puts a stop to anything passive the entity is doing, counting it as failed
+    // a patrol route is dropped along with the leg being walked
+    // gives back whether anything was stopped
+    pub fn interrupt_action(&mut self) -> bool {
+        if !self.is_passive() {
+            return false;
+        }
+        self.fail_action(ActionFailure::Interrupted);
+        if self.current_action.action_type == ActionType::Patrol {
+            self.fail_action(ActionFailure::Interrupted);
+        }
+        true
+    }
+    pub fn is_curr_action_empty(&self) -> bool {
+        match self.current_action.action_type {
+            ActionType::Empty => {
+                true
+            },
+            _ => {
+                false
+            },
+        }
+    }
+}
+
+// enum for the type of action 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ActionType {
+    // move actions will move entities to a stationary point
+    // or a moving entity
+    // additional parameters include:
+    // range: maximum distance from target allowable
+    // min_range: minimum distance from target allowable
+    Move,
+    // attack actions will launch attacks at an entity until it 
+    // ceases to become hostile
+    // additional parameters include:
+    // shots: how many attacks to launch before the action is done, unlimited if not given
+    Attack,
+    // wait actions will do nothing for a specified amount of time
+    // this is one of the passive actions
+    Wait,
+    // interact actions will operate an object, e.g. an alarm, once the entity has been at it for a while
+    // the target is the object's id
+    // additional parameters include:
+    // duration: how long operating the object takes, defaults to one second
+    Interact,
+    // distract actions will throw something at a point, making a noise where it lands
+    Distract,
+    // grenade actions will throw a grenade at a point, which goes off once its fuse burns down
+    Grenade,
+    // switch actions will put away the weapon in hand and draw the holstered one, which takes a moment
+    // additional parameters include:
+    // duration: how long switching takes, defaults to the usual switch duration
+    Switch,
+    // heal actions will use up a medkit on the target entity, once the entity has been at it for a while
+    // the target is the id of whoever is being patched up, which can be the entity itself
+    // taking damage part way through interrupts the heal, without using up the medkit
+    // this is one of the passive actions
+    // additional parameters include:
+    // duration: how long healing takes, defaults to the usual heal duration
+    Heal,
+    // brace actions will hold position behind a shield, facing towards a point, which widens the arc it covers
+    Brace,
+    // overwatch actions will hold position watching towards a point, until an enemy moves into view
+    // the overwatch system then swaps it for an attack on that enemy
+    // additional parameters are the same as for attack actions
+    Overwatch,
+    // patrol actions will walk a looping route of waypoints
+    // additional parameters include:
+    // count: number of waypoints in the route
+    // index: the waypoint being walked to next
+    // x<i>, y<i>: the coordinates of waypoint i
+    // this is one of the passive actions
+    Patrol,
+    // empty actions do nothing and are immediately popped
+    Empty,
+}
+
+impl ActionType {
+    // passive actions are the ones put a stop to by taking damage
+    // so no one stands around waiting out a timer while being shot
+    pub fn is_passive(&self) -> bool {
+        match self {
+            ActionType::Wait | ActionType::Heal | ActionType::Patrol => true,
+            _ => false,
+        }
+    }
+}
+
+// action struct
+#[derive(Debug, Clone)]
+pub struct Action {
+    pub action_type: ActionType,
+    // target is either a coordinate point or an entity id
+    pub target: (Option<(f32, f32)>, Option<String>),
+    // params contains additional parameters
+    // add additional parameters to an action by inserting
+    // a string key with a f32 value
+    pub params: Option<HashMap<String, f32>>,
+    // group targets more than one entity, or a whole area, and takes the place of the target if given
+    // only move and attack actions make use of it
+    pub group: Option<TargetGroup>,
+}
+
+// target group
+// a target made up of a number of entities or an area, for actions taken on groups, e.g. squad level maneuvers
+#[derive(Debug, Clone)]
+pub enum TargetGroup {
+    // any one of these entities will do, whichever is closest
+    // attacks move on to the next closest once one is gone
+    Any(Vec<String>),
+    // all of these entities at once, so moves keep all of them within range
+    // attacks go for whichever is closest
+    All(Vec<String>),
+    // an area, given by its centre and radius, so moves keep all of it within range
+    Area((f32, f32), f32),
+}
+
+impl Default for Action {
+    // default function for action
+    // gives a default Action, which is an empty action
+    fn default() -> Self {
+        Action {
+            action_type: ActionType::Empty,
+            target: (None, None),
+            params: None,
+            group: None,
+        }
+    }
+}
+
+// tests for the action queue each person works through
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // gives an action of the given type with no target
+    fn action(action_type: ActionType) -> Action {
+        Action {
+            action_type: action_type,
+            target: (None, None),
+            params: None,
+            group: None,
+        }
+    }
+
+    // gives the types of the current action and everything queued after it, in order
+    fn action_types(actions: &Nerve) -> Vec<ActionType> {
+        std::iter::once(&actions.current_action)
+            .chain(actions.action_queue.iter())
+            .map(|action| action.action_type)
+            .collect()
+    }
+
+    #[test]
+    fn advance_takes_actions_in_order() {
+        let mut actions = Nerve::new();
+        actions.action_queue.push_back(action(ActionType::Move));
+        actions.action_queue.push_back(action(ActionType::Wait));
+        actions.push_front(action(ActionType::Attack));
+
+        assert_eq!(actions.advance().action_type, ActionType::Empty);
+        assert_eq!(actions.current_action.action_type, ActionType::Attack);
+        assert_eq!(actions.advance().action_type, ActionType::Attack);
+        assert_eq!(actions.advance().action_type, ActionType::Move);
+        assert_eq!(actions.advance().action_type, ActionType::Wait);
+        assert!(actions.is_curr_action_empty());
+    }
+
+    #[test]
+    fn push_front_goes_straight_after_the_current_action() {
+        let mut actions = Nerve::new();
+        actions.current_action = action(ActionType::Move);
+        actions.action_queue.push_back(action(ActionType::Wait));
+        actions.push_front(action(ActionType::Attack));
+
+        assert_eq!(action_types(&actions), vec![ActionType::Move, ActionType::Attack, ActionType::Wait]);
+    }
+
+    #[test]
+    fn advance_on_an_empty_queue_leaves_the_empty_action() {
+        let mut actions = Nerve::new();
+        actions.current_action = action(ActionType::Move);
+
+        assert_eq!(actions.advance().action_type, ActionType::Move);
+        assert!(actions.is_curr_action_empty());
+        assert_eq!(actions.advance().action_type, ActionType::Empty);
+        assert!(actions.is_curr_action_empty());
+        assert!(actions.action_queue.is_empty());
+    }
+
+    #[test]
+    fn peek_gives_the_next_action_without_moving_on() {
+        let mut actions = Nerve::new();
+        assert!(actions.peek().is_none());
+
+        actions.action_queue.push_back(action(ActionType::Wait));
+        assert_eq!(actions.peek().map(|action| action.action_type), Some(ActionType::Wait));
+        assert!(actions.is_curr_action_empty());
+        assert_eq!(actions.action_queue.len(), 1);
+    }
+
+    #[test]
+    fn clear_drops_everything() {
+        let mut actions = Nerve::new();
+        actions.current_action = action(ActionType::Wait);
+        actions.action_queue.push_back(action(ActionType::Move));
+        actions.action_timer = Some(Timer::from_seconds(1.0, false));
+        actions.clear();
+
+        assert!(actions.is_curr_action_empty());
+        assert!(actions.action_queue.is_empty());
+        assert!(actions.action_timer.is_none());
+    }
+
+    #[test]
+    fn replace_all_lines_up_the_new_actions_in_order() {
+        let mut actions = Nerve::new();
+        actions.current_action = action(ActionType::Attack);
+        actions.action_queue.push_back(action(ActionType::Wait));
+        actions.replace_all(vec![action(ActionType::Move), action(ActionType::Interact)]);
+
+        assert_eq!(action_types(&actions), vec![ActionType::Empty, ActionType::Move, ActionType::Interact]);
+    }
+
+    #[test]
+    fn finishing_a_wait_resets_the_timer_for_the_next_one() {
+        let mut actions = Nerve::new();
+        actions.current_action = action(ActionType::Wait);
+        actions.action_queue.push_back(action(ActionType::Wait));
+        let mut timer = Timer::from_seconds(1.0, false);
+        timer.tick(1.0);
+        actions.action_timer = Some(timer);
+        actions.complete_action();
+
+        // the next wait starts its own timer, rather than finishing straight away on the old one
+        assert_eq!(actions.current_action.action_type, ActionType::Wait);
+        assert!(actions.action_timer.is_none());
+        assert_eq!(actions.completed.len(), 1);
+    }
+
+    #[test]
+    fn failing_an_action_records_why() {
+        let mut actions = Nerve::new();
+        actions.current_action = action(ActionType::Wait);
+        actions.action_timer = Some(Timer::from_seconds(1.0, false));
+        actions.fail_action(ActionFailure::Interrupted);
+
+        assert!(actions.is_curr_action_empty());
+        assert!(actions.action_timer.is_none());
+        assert_eq!(actions.failed.len(), 1);
+        assert_eq!(actions.failed[0].1, ActionFailure::Interrupted);
+        assert!(actions.completed.is_empty());
+    }
+}
@@ -0,0 +1,513 @@
+// network module
+// two player co-op over the network
+use bevy::prelude::*;
+use std::collections::HashSet;
+use std::net::{TcpListener, TcpStream};
+use bevy_rapier2d::physics::RigidBodyHandleComponent;
+use bevy_rapier2d::rapier::dynamics::{JointSet, RigidBodySet};
+use bevy_rapier2d::rapier::geometry::ColliderSet;
+use bevy_rapier2d::rapier::math::Isometry;
+use std::io::{Read, Write};
+
+use crate::{COMMAND_STAGE, Id, Position, Size, Velocity};
+use crate::collision::get_person_body_components;
+use crate::combat::remove_person;
+use crate::config::{GameConfig, Settings};
+use crate::control::CommandType;
+use crate::draw::get_translate_from_position;
+use crate::feedback::Notification;
+use crate::locale::Locale;
+use crate::nerve::Nerve;
+use crate::person::{AttitudeType, Command, Controlled, Person, SimpleRect};
+use crate::tick::SimulationClock;
+
+// network plugin
+// responsible for cooperative multiplayer, where a second player controls part of the squad
+// the host runs the real simulation. the client sends its orders to the host, and the host
+// sends back snapshots of where everyone is, which the client copies into its own world
+// the client doesn't run the simulation at all while connected, so nothing happens there that the host didn't say
+// run `cargo run -- --host <port>` on one machine and `cargo run -- --join <address:port>` on the other
+// the client's squad members are picked with `--client-squad <index,index,...>`, which both players should give
+// messages are plain text, one per line:
+//   client to host: CMD <squad index> <move|attack|flee|follow> <x> <y> <target id or ->
+//   host to client: SPAWN <id> <attitude> <squad index or -1> <x> <y>, the first time the client hears of someone
+//                   SNAP <id> <attitude> <squad index or -1> <x> <y> <vx> <vy>
+//                   DESPAWN <id>, once someone the client heard of is gone
+//                   END, after every snapshot
+// a peer that sends more than NETWORK_MAX_BUFFER bytes without ending a line is dropped
+pub struct NetworkPlugin;
+
+// implementation of the plugin trait,
+// required for this to be used as a plugin
+impl Plugin for NetworkPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        // add in the snapshot timer
+        app.add_resource(SnapshotTimer(Timer::from_seconds(1.0 / NETWORK_SNAPSHOT_RATE, true)))
+        // add in the host and client systems, which pass orders across
+        // these go with the other orders, so orders from across the network are carried out in the same frame
+        .add_system_to_stage(COMMAND_STAGE, host_network_system.system())
+        .add_system_to_stage(COMMAND_STAGE, client_network_system.system());
+    }
+}
+
+// settings for networking
+// number of snapshots the host sends per second
+pub static NETWORK_SNAPSHOT_RATE: f32 = 10.0;
+// squad indices controlled by the client unless --client-squad says otherwise, the host controls the rest
+pub static DEFAULT_CLIENT_SQUAD: [i32; 2] = [2, 3];
+// most bytes kept from a peer waiting on the end of a line, past which the peer is dropped
+pub static NETWORK_MAX_BUFFER: usize = 64 * 1024;
+
+// connection struct
+// a tcp stream along with anything read from it that isn't a full line yet,
+// anything sent that the stream hasn't taken yet, and the ids the other end has been told about
+pub struct Connection {
+    pub stream: TcpStream,
+    pub buffer: String,
+    pub outgoing: Vec<u8>,
+    pub known: HashSet<String>,
+}
+
+impl Connection {
+    pub fn new(stream: TcpStream) -> Result<Self, String> {
+        // never block the game waiting on the network
+        stream.set_nonblocking(true).map_err(|err| format!("could not set stream to nonblocking: {}", err))?;
+        stream.set_nodelay(true).ok();
+        Ok(Connection {
+            stream: stream,
+            buffer: String::new(),
+            outgoing: Vec::new(),
+            known: HashSet::new(),
+        })
+    }
+    // gives every full line received since the last read
+    // gives None if the connection was closed, or the other end sent too much without ending a line
+    pub fn read_lines(&mut self) -> Option<Vec<String>> {
+        let mut chunk = [0; 4096];
+        loop {
+            match self.stream.read(&mut chunk) {
+                // closed
+                Ok(0) => return None,
+                Ok(n) => self.buffer.push_str(&String::from_utf8_lossy(&chunk[..n])),
+                Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => break,
+                Err(_) => return None,
+            }
+        }
+        let mut lines = Vec::new();
+        while let Some(index) = self.buffer.find('\n') {
+            let line: String = self.buffer.drain(..index + 1).collect();
+            lines.push(line.trim().to_string());
+        }
+        if self.buffer.len() > NETWORK_MAX_BUFFER {
+            eprintln!("dropping connection, sent more than {} bytes without ending a line", NETWORK_MAX_BUFFER);
+            return None;
+        }
+        Some(lines)
+    }
+    // sends a message, gives false if the connection was closed
+    // whatever the stream can't take straight away is kept, and sent on the next flush
+    pub fn send(&mut self, message: &str) -> bool {
+        self.outgoing.extend_from_slice(message.as_bytes());
+        self.flush()
+    }
+    // sends as much of what's waiting as the stream will take, gives false if the connection was closed
+    pub fn flush(&mut self) -> bool {
+        while !self.outgoing.is_empty() {
+            match self.stream.write(&self.outgoing) {
+                // closed
+                Ok(0) => return false,
+                Ok(n) => {
+                    self.outgoing.drain(..n);
+                },
+                Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => break,
+                Err(ref e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(_) => return false,
+            }
+        }
+        true
+    }
+}
+
+// network state resource
+// what part this game plays in a multiplayer session, if any
+pub enum NetworkState {
+    // single player
+    Offline,
+    // running the simulation, with any connected clients
+    Host {
+        listener: TcpListener,
+        clients: Vec<Connection>,
+        // squad indices controlled by the clients
+        client_squad: Vec<i32>,
+    },
+    // connected to a host
+    Client {
+        connection: Connection,
+        // ids that have been adopted from the host
+        adopted: HashSet<String>,
+        // squad indices controlled by this client
+        client_squad: Vec<i32>,
+    },
+}
+
+impl NetworkState {
+    // reads the network state from the command line arguments
+    // expects `--host <port>` or `--join <address:port>`, along with `--client-squad <index,index,...>` if given
+    // gives an error if the port can't be bound or the host can't be reached
+    pub fn from_args(args: Vec<String>) -> Result<Self, String> {
+        let client_squad = match args.iter().position(|arg| arg == "--client-squad") {
+            Some(index) => parse_client_squad(args.get(index + 1).ok_or("--client-squad needs a list of squad indices")?)?,
+            None => DEFAULT_CLIENT_SQUAD.to_vec(),
+        };
+        if let Some(index) = args.iter().position(|arg| arg == "--host") {
+            let port = args.get(index + 1).map(|p| p.as_str()).unwrap_or("7777");
+            let listener = TcpListener::bind(format!("0.0.0.0:{}", port))
+                .map_err(|err| format!("could not bind host port {}: {}", port, err))?;
+            listener.set_nonblocking(true).map_err(|err| format!("could not set listener to nonblocking: {}", err))?;
+            return Ok(NetworkState::Host {
+                listener: listener,
+                clients: Vec::new(),
+                client_squad: client_squad,
+            });
+        }
+        if let Some(index) = args.iter().position(|arg| arg == "--join") {
+            let address = args.get(index + 1).ok_or("--join needs an address")?;
+            let stream = TcpStream::connect(address).map_err(|err| format!("could not connect to host {}: {}", address, err))?;
+            return Ok(NetworkState::Client {
+                connection: Connection::new(stream)?,
+                adopted: HashSet::new(),
+                client_squad: client_squad,
+            });
+        }
+        Ok(NetworkState::Offline)
+    }
+}
+
+// reads the squad indices controlled by the client, e.g. `2,3`
+pub fn parse_client_squad(list: &str) -> Result<Vec<i32>, String> {
+    list.split(',')
+        .map(|index| index.trim().parse().map_err(|_| format!("bad squad index for --client-squad: {}", index)))
+        .collect()
+}
+
+// snapshot timer
+// regulates how often the host sends snapshots
+pub struct SnapshotTimer(pub Timer);
+
+// gives the name used for an attitude over the network
+pub fn get_attitude_name(attitude: &AttitudeType) -> &'static str {
+    match attitude {
+        AttitudeType::Neutral => "neutral",
+        AttitudeType::Squad => "squad",
+        AttitudeType::Hostile => "hostile",
+        AttitudeType::Ally => "ally",
+        AttitudeType::Surrendered => "surrendered",
+    }
+}
+
+// gives the name used for a command type over the network
+pub fn get_command_name(command_type: CommandType) -> Option<&'static str> {
+    match command_type {
+        CommandType::Move => Some("move"),
+        CommandType::Attack => Some("attack"),
+        CommandType::Flee => Some("flee"),
+        CommandType::Follow => Some("follow"),
+        CommandType::AttackMove => Some("attack_move"),
+        CommandType::Stop => Some("stop"),
+        CommandType::Distract => Some("distract"),
+        CommandType::Grenade => Some("grenade"),
+        CommandType::Overwatch => Some("overwatch"),
+        CommandType::Interact => Some("interact"),
+        CommandType::Heal => Some("heal"),
+        CommandType::Brace => Some("brace"),
+        CommandType::Capture => Some("capture"),
+        _ => None,
+    }
+}
+
+// reads a command sent over the network
+pub fn parse_network_command(line: &str) -> Option<(i32, Command)> {
+    let parts: Vec<&str> = line.split_whitespace().collect();
+    if parts.len() != 6 || parts[0] != "CMD" {
+        return None;
+    }
+    let command_type = match parts[2] {
+        "move" => CommandType::Move,
+        "attack" => CommandType::Attack,
+        "flee" => CommandType::Flee,
+        "follow" => CommandType::Follow,
+        "attack_move" => CommandType::AttackMove,
+        "stop" => CommandType::Stop,
+        "distract" => CommandType::Distract,
+        "grenade" => CommandType::Grenade,
+        "overwatch" => CommandType::Overwatch,
+        "interact" => CommandType::Interact,
+        "heal" => CommandType::Heal,
+        "brace" => CommandType::Brace,
+        "capture" => CommandType::Capture,
+        _ => return None,
+    };
+    let target_id = if parts[5] == "-" { None } else { Some(parts[5].to_string()) };
+    Some((parts[1].parse().ok()?, Command {
+        command_type: command_type,
+        target_point: Some((parts[3].parse().ok()?, parts[4].parse().ok()?)),
+        target_id: target_id,
+        formation_offset: None,
+    }))
+}
+
+// host network system
+// accepts clients, carries out the orders they send, and sends them snapshots
+pub fn host_network_system(time: Res<Time>, mut network: ResMut<NetworkState>, mut timer: ResMut<SnapshotTimer>,
+    mut controlled: Query<&mut Controlled>, mut persons: Query<(&Id, &Person, &Position, &Velocity, Option<&Controlled>)>) {
+    let (listener, clients, client_squad) = match &mut *network {
+        NetworkState::Host { listener, clients, client_squad } => (listener, clients, client_squad),
+        _ => return,
+    };
+
+    // accept any new clients
+    while let Ok((stream, address)) = listener.accept() {
+        match Connection::new(stream) {
+            Ok(connection) => clients.push(connection),
+            Err(err) => eprintln!("could not accept client {}: {}", address, err),
+        }
+    }
+
+    // the host player can't order the client's squad members around
+    for mut state in &mut controlled.iter() {
+        if client_squad.contains(&state.squad_pos) {
+            state.current_command = Command::default();
+        }
+    }
+
+    // carry out the orders sent by clients, dropping any that disconnected
+    // anything left over from earlier snapshots is sent on first
+    let mut orders = Vec::new();
+    let mut open = Vec::new();
+    for mut client in clients.drain(..) {
+        if !client.flush() {
+            continue;
+        }
+        if let Some(lines) = client.read_lines() {
+            orders.extend(lines.iter().filter_map(|line| parse_network_command(line)));
+            open.push(client);
+        }
+    }
+    *clients = open;
+    for (squad_pos, command) in orders {
+        // clients can only order their own squad members
+        if !client_squad.contains(&squad_pos) {
+            continue;
+        }
+        for mut state in &mut controlled.iter() {
+            if state.squad_pos == squad_pos {
+                state.current_command = Command {
+                    command_type: command.command_type,
+                    target_point: command.target_point,
+                    target_id: command.target_id.clone(),
+                    formation_offset: None,
+                };
+            }
+        }
+    }
+
+    // send out a snapshot of everyone
+    timer.0.tick(time.delta_seconds);
+    if timer.0.finished && !clients.is_empty() {
+        let mut everyone = Vec::new();
+        for (id, pers, pos, vel, control) in &mut persons.iter() {
+            let squad_pos = control.map_or(-1, |c| c.squad_pos);
+            everyone.push((id.id(), get_attitude_name(&pers.attitude), squad_pos, (pos.0, pos.1), (vel.0, vel.1)));
+        }
+        let mut open = Vec::new();
+        for mut client in clients.drain(..) {
+            // each client is told about anyone new to it, and anyone it knew of that has gone
+            let mut snapshot = String::new();
+            let mut present = HashSet::new();
+            for (id, attitude, squad_pos, pos, vel) in everyone.iter() {
+                if !client.known.contains(id) {
+                    snapshot.push_str(&format!("SPAWN {} {} {} {} {}\n", id, attitude, squad_pos, pos.0, pos.1));
+                }
+                snapshot.push_str(&format!("SNAP {} {} {} {} {} {} {}\n", id, attitude, squad_pos, pos.0, pos.1, vel.0, vel.1));
+                present.insert(id.clone());
+            }
+            for id in client.known.difference(&present) {
+                snapshot.push_str(&format!("DESPAWN {}\n", id));
+            }
+            snapshot.push_str("END\n");
+            client.known = present;
+            if client.send(&snapshot) {
+                open.push(client);
+            }
+        }
+        *clients = open;
+    }
+}
+
+// client network system
+// sends orders for the client's squad members to the host, and copies
+// the host's snapshots into the world, spawning and despawning people as the host says
+// the simulation is held still while connected, so only the host decides what happens
+// if the host goes away, the game carries on offline with the squad as it was
+pub fn client_network_system(mut commands: Commands, config: Res<GameConfig>, locale: Res<Locale>, settings: Res<Settings>,
+    mut network: ResMut<NetworkState>, mut clock: ResMut<SimulationClock>, mut materials: ResMut<Assets<ColorMaterial>>,
+    mut bodies: ResMut<RigidBodySet>, mut colliders: ResMut<ColliderSet>, mut joints: ResMut<JointSet>,
+    mut controlled: Query<&mut Controlled>,
+    mut persons: Query<(Entity, &mut Id, &Person, &mut Position, &mut Velocity, &mut Nerve, Option<&Controlled>, Option<&RigidBodyHandleComponent>)>,
+    mut notifications: Query<(&mut Notification, &mut Text)>) {
+    let (connection, adopted, client_squad) = match &mut *network {
+        NetworkState::Client { connection, adopted, client_squad } => (connection, adopted, client_squad),
+        _ => return,
+    };
+
+    // send orders to the host instead of carrying them out
+    let mut open = connection.flush();
+    for mut state in &mut controlled.iter() {
+        if client_squad.contains(&state.squad_pos) {
+            if let Some(name) = get_command_name(state.current_command.command_type) {
+                let command = &state.current_command;
+                let point = command.target_point.unwrap_or((0.0, 0.0));
+                let target = command.target_id.clone().unwrap_or("-".to_string());
+                open = open && connection.send(&format!("CMD {} {} {} {} {}\n", state.squad_pos, name, point.0, point.1, target));
+            }
+        }
+        // only the host carries out orders
+        state.current_command = Command::default();
+    }
+
+    let lines = match connection.read_lines() {
+        Some(lines) if open => lines,
+        _ => {
+            // let the player know, and carry on offline
+            for (mut notification, mut text) in &mut notifications.iter() {
+                text.value = locale.get("host_disconnected");
+                notification.timer.reset();
+            }
+            *network = NetworkState::Offline;
+            return;
+        },
+    };
+
+    // no ticks run this frame, so nothing moves but what the host sends
+    // everyone is drawn where they are rather than part way from where they were last tick
+    clock.ticks = 0;
+    clock.ticked = false;
+    clock.alpha = 1.0;
+
+    for line in lines {
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        match parts.as_slice() {
+            ["SPAWN", host_id, attitude, squad_pos, x, y] => {
+                let squad_pos: i32 = squad_pos.parse().unwrap_or(-1);
+                let position = match (x.parse::<f32>(), y.parse::<f32>()) {
+                    (Ok(x), Ok(y)) => (x, y),
+                    _ => continue,
+                };
+                let attitude = match get_attitude_from_name(attitude) {
+                    Some(attitude) => attitude,
+                    None => continue,
+                };
+                // someone the client already has is adopted, matching squad members by index
+                // and everyone else by the closest person of the same attitude that hasn't been adopted yet
+                let mut best: Option<(f32, Entity)> = None;
+                for (entity, id, pers, pos, _vel, _nerve, control, _body) in &mut persons.iter() {
+                    if pers.attitude != attitude || adopted.contains(&id.id()) {
+                        continue;
+                    }
+                    if squad_pos >= 0 && control.map_or(-1, |c| c.squad_pos) != squad_pos {
+                        continue;
+                    }
+                    let dist = Vec2::new(pos.0 - position.0, pos.1 - position.1).length();
+                    if best.map_or(true, |(d, _)| dist < d) {
+                        best = Some((dist, entity));
+                    }
+                }
+                adopted.insert(host_id.to_string());
+                match best {
+                    Some((_, entity)) => {
+                        if let Ok(mut id) = persons.get_mut::<Id>(entity) {
+                            id.0 = host_id.to_string();
+                        }
+                    },
+                    // anyone else is spawned in
+                    None => spawn_network_person(&config, &mut commands, &mut materials, &settings, host_id, attitude, squad_pos, position),
+                }
+            },
+            ["SNAP", host_id, _attitude, _squad_pos, values @ ..] => {
+                let values: Vec<f32> = values.iter().filter_map(|v| v.parse().ok()).collect();
+                if values.len() != 4 {
+                    continue;
+                }
+                for (_entity, id, _pers, mut pos, mut vel, mut actions, _control, body) in &mut persons.iter() {
+                    if id.id() != *host_id {
+                        continue;
+                    }
+                    pos.0 = values[0];
+                    pos.1 = values[1];
+                    vel.0 = values[2];
+                    vel.1 = values[3];
+                    // the host decides what everyone does
+                    actions.clear();
+                    // move the physics body too, otherwise it would move the entity back
+                    if let Some(handle) = body {
+                        if let Some(body) = bodies.get_mut(handle.handle()) {
+                            let translation = get_translate_from_position(&config, values[0], values[1]);
+                            body.set_position(Isometry::translation(translation.0, translation.1));
+                        }
+                    }
+                    break;
+                }
+            },
+            ["DESPAWN", host_id] => {
+                adopted.remove(*host_id);
+                for (entity, id, _pers, _pos, _vel, _nerve, _control, body) in &mut persons.iter() {
+                    if id.id() == *host_id {
+                        remove_person(&mut commands, entity, body, &mut bodies, &mut colliders, &mut joints);
+                        break;
+                    }
+                }
+            },
+            ["END"] => {
+                // by the end of a snapshot, anyone the host didn't claim only exists on the client
+                for (entity, id, _pers, _pos, _vel, _nerve, _control, body) in &mut persons.iter() {
+                    if !adopted.contains(&id.id()) {
+                        remove_person(&mut commands, entity, body, &mut bodies, &mut colliders, &mut joints);
+                    }
+                }
+            },
+            _ => {},
+        }
+    }
+}
+
+// gives the attitude with the given name, as sent over the network
+pub fn get_attitude_from_name(name: &str) -> Option<AttitudeType> {
+    match name {
+        "neutral" => Some(AttitudeType::Neutral),
+        "squad" => Some(AttitudeType::Squad),
+        "hostile" => Some(AttitudeType::Hostile),
+        "ally" => Some(AttitudeType::Ally),
+        "surrendered" => Some(AttitudeType::Surrendered),
+        _ => None,
+    }
+}
+
+// spawns in someone the host has that the client doesn't, with just what's needed to draw and move them,
+// as the client never runs anything else on them
+pub fn spawn_network_person(config: &GameConfig, commands: &mut Commands, materials: &mut ResMut<Assets<ColorMaterial>>, settings: &Settings,
+    host_id: &str, attitude: AttitudeType, squad_pos: i32, position: (f32, f32)) {
+    let squad = if squad_pos >= 0 { Some(squad_pos) } else { None };
+    let handle = materials.add(settings.palette.person_color(&attitude, squad, None).into());
+    commands
+        .spawn(SimpleRect::new(handle, Vec2::new(10.0, 10.0)))
+        .with(Id(host_id.to_string()))
+        .with(Person::new(attitude))
+        .with(Position(position.0, position.1))
+        .with(Velocity(0.0, 0.0))
+        .with(Nerve::new())
+        .with(Size(10.0, 10.0))
+        .with_bundle(get_person_body_components(config, position, (10.0, 10.0), &attitude));
+    if let Some(squad_pos) = squad {
+        commands.with(Controlled::new(squad_pos));
+    }
+}
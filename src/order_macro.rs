@@ -0,0 +1,251 @@
+// order macro module
+// compound orders saved under a name and given with a single key
+use bevy::prelude::*;
+use std::fs;
+
+use crate::COMMAND_STAGE;
+use crate::behaviour::TilePos;
+use crate::config::GameConfig;
+use crate::control::{CommandEvent, CommandType, InputState, KeyBindings};
+use crate::map::MapData;
+use crate::person::{Command, Controlled};
+
+// order macro plugin
+// responsible for compound orders saved under a name, e.g. a breach, with one squad member on overwatch
+// while two move through the door and another throws a distraction
+// each macro is bound to one of the macro keys, and is given at the cursor when its key is pressed
+pub struct OrderMacroPlugin;
+
+// implementation of the plugin trait,
+// required for this to be used as a plugin
+impl Plugin for OrderMacroPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        // add in the macros, read from the macros file
+        app.add_resource(OrderMacros::load(MACROS_PATH))
+        // add in the event sent whenever a macro is triggered
+        .add_event::<MacroEvent>()
+        // initialise the event reader for macro events
+        .init_resource::<MacroExpansionState>()
+        // add in the system triggering macros from their keys
+        .add_system_to_stage(COMMAND_STAGE, macro_hotkey_system.system())
+        // add in the system expanding macros into orders for each squad member
+        .add_system_to_stage(COMMAND_STAGE, macro_expansion_system.system());
+    }
+}
+
+// settings for order macros
+// the file macros are read from, and the keys they can be bound to
+pub static MACROS_PATH: &str = "macros.txt";
+pub static MACRO_KEYS: [KeyCode; 4] = [KeyCode::F5, KeyCode::F6, KeyCode::F7, KeyCode::F8];
+
+// macro step struct
+// a single order in a macro
+#[derive(Debug, Clone)]
+pub struct MacroStep {
+    // squad index of who the order is for
+    pub squad_pos: i32,
+    // the type of order given
+    pub command_type: CommandType,
+    // where the order is aimed, as an offset from the cursor when the macro is triggered
+    pub offset: (f32, f32),
+}
+
+// order macro struct
+// a named set of orders for the squad
+#[derive(Debug, Clone)]
+pub struct OrderMacro {
+    pub name: String,
+    // index of the macro key it's bound to, if it's bound to one
+    pub key: Option<usize>,
+    // the orders in it, in order
+    // squad members with more than one order carry them out one after another
+    pub steps: Vec<MacroStep>,
+}
+
+// order macros resource
+// every macro the player has defined
+#[derive(Debug, Clone, Default)]
+pub struct OrderMacros {
+    pub macros: Vec<OrderMacro>,
+}
+
+impl OrderMacros {
+    // reads the macros from a file
+    // there are no macros until the player writes the file, so a missing file gives none
+    // each macro starts with a line naming it, along with the number of the macro key it's bound to, if any:
+    //   macro <name> [<1-4>]
+    // followed by a line for each order in it, aimed at an offset in pixels from the cursor:
+    //   <squad index> <move|attack_move|overwatch|distract|grenade|brace> [<x offset> <y offset>]
+    pub fn load(path: &str) -> Self {
+        let mut macros = OrderMacros::default();
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(_) => return macros,
+        };
+        for line in text.lines().map(|line| line.trim()) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            match parts.as_slice() {
+                ["macro", name, rest @ ..] => {
+                    let key = match rest {
+                        [] => None,
+                        [key] => match key.parse::<usize>() {
+                            Ok(key) if key >= 1 && key <= MACRO_KEYS.len() => Some(key - 1),
+                            _ => {
+                                eprintln!("bad macro key in macros: {}", line);
+                                None
+                            },
+                        },
+                        _ => {
+                            eprintln!("bad macros line: {}", line);
+                            None
+                        },
+                    };
+                    macros.macros.push(OrderMacro {
+                        name: name.to_string(),
+                        key: key,
+                        steps: Vec::new(),
+                    });
+                },
+                [squad_pos, command_type, offset @ ..] => {
+                    let squad_pos = match squad_pos.parse::<i32>() {
+                        Ok(squad_pos) => squad_pos,
+                        Err(_) => {
+                            eprintln!("bad squad index in macros: {}", line);
+                            continue;
+                        },
+                    };
+                    let command_type = match CommandType::from_name(command_type) {
+                        Some(command_type) => command_type,
+                        None => {
+                            eprintln!("unknown order in macros: {}", command_type);
+                            continue;
+                        },
+                    };
+                    let offset = match offset {
+                        [] => (0.0, 0.0),
+                        [x, y] => match (x.parse::<f32>(), y.parse::<f32>()) {
+                            (Ok(x), Ok(y)) => (x, y),
+                            _ => {
+                                eprintln!("bad offset in macros: {}", line);
+                                continue;
+                            },
+                        },
+                        _ => {
+                            eprintln!("bad macros line: {}", line);
+                            continue;
+                        },
+                    };
+                    // orders before the first macro line don't belong to anything
+                    match macros.macros.last_mut() {
+                        Some(order_macro) => order_macro.steps.push(MacroStep {
+                            squad_pos: squad_pos,
+                            command_type: command_type,
+                            offset: offset,
+                        }),
+                        None => eprintln!("order outside of a macro in macros: {}", line),
+                    }
+                },
+                _ => eprintln!("bad macros line: {}", line),
+            }
+        }
+        macros
+    }
+    // gives the macro with the given name, if there is one
+    pub fn get(&self, name: &str) -> Option<&OrderMacro> {
+        self.macros.iter().find(|order_macro| order_macro.name == name)
+    }
+    // gives the macro bound to the macro key at the given index, if there is one
+    pub fn bound_to(&self, key: usize) -> Option<&OrderMacro> {
+        self.macros.iter().find(|order_macro| order_macro.key == Some(key))
+    }
+}
+
+// macro event
+// send this to have the squad carry out a macro, aimed at a point
+pub struct MacroEvent {
+    // name of the macro to carry out
+    pub name: String,
+    // the point the macro's orders are aimed around
+    pub point: (f32, f32),
+}
+
+// macro expansion state holds an event reader for macro events
+#[derive(Default)]
+pub struct MacroExpansionState {
+    pub event_reader: EventReader<MacroEvent>,
+}
+
+// macro hotkey system
+// triggers the macro bound to a macro key when it's pressed, aimed at the cursor
+pub fn macro_hotkey_system(inputs: Res<InputState>, bindings: Res<KeyBindings>, macros: Res<OrderMacros>, mut macro_events: ResMut<Events<MacroEvent>>) {
+    for (index, key) in bindings.macros.iter().enumerate() {
+        if !inputs.key_just_presses.contains(key) {
+            continue;
+        }
+        if let Some(order_macro) = macros.bound_to(index) {
+            macro_events.send(MacroEvent {
+                name: order_macro.name.clone(),
+                point: inputs.mouse_position,
+            });
+        }
+    }
+}
+
+// macro expansion system
+// turns each macro carried out into orders for the squad members in it
+// each squad member's first order replaces whatever they were doing, and the rest are queued up behind it
+pub fn macro_expansion_system(config: Res<GameConfig>, map: Res<MapData>, macros: Res<OrderMacros>, mut state: ResMut<MacroExpansionState>, events: Res<Events<MacroEvent>>,
+    mut command_events: ResMut<Events<CommandEvent>>, mut query: Query<(Entity, &mut Controlled)>) {
+    for event in state.event_reader.iter(&events) {
+        let order_macro = match macros.get(&event.name) {
+            Some(order_macro) => order_macro,
+            None => {
+                eprintln!("unknown macro: {}", event.name);
+                continue;
+            },
+        };
+
+        for (entity, mut controlled) in &mut query.iter() {
+            let mut first = true;
+            for step in order_macro.steps.iter().filter(|step| step.squad_pos == controlled.squad_pos) {
+                let point = (
+                    (event.point.0 + step.offset.0).max(0.0).min(config.map_width - 1.0),
+                    (event.point.1 + step.offset.1).max(0.0).min(config.map_height - 1.0),
+                );
+                // orders that can't be carried out are left out, the same as if they'd been given by hand
+                let blocked = map.is_tile_blocked(&TilePos::from_coords(&config, point.0, point.1));
+                let rejection = match step.command_type {
+                    CommandType::Move | CommandType::AttackMove if blocked => Some("order_unreachable".to_string()),
+                    CommandType::Distract | CommandType::Grenade if blocked => Some("order_throw_blocked".to_string()),
+                    _ => None,
+                };
+                if rejection.is_none() {
+                    let command = Command {
+                        command_type: step.command_type,
+                        target_point: Some(point),
+                        target_id: None,
+                        formation_offset: None,
+                    };
+                    if first {
+                        controlled.current_command = command;
+                        controlled.clear_queue();
+                        first = false;
+                    }else if !controlled.queue_command(command) {
+                        eprintln!("too many orders for squad member {} in macro: {}", controlled.squad_pos, order_macro.name);
+                    }
+                }
+
+                // let everything else know how the order went
+                command_events.send(CommandEvent {
+                    unit: entity,
+                    command_type: step.command_type,
+                    destination: Some(point),
+                    rejection: rejection,
+                });
+            }
+        }
+    }
+}
@@ -0,0 +1,237 @@
+// palette module
+// the colours people are drawn in
+use bevy::prelude::*;
+
+use crate::{PALETTE_NAMES, SETTINGS_PATH};
+use crate::animation::{SpriteData, tint_sprite_data};
+use crate::config::Settings;
+use crate::control::{InputState, KeyBindings};
+use crate::encounter::{BossHealthBar, BossHealthFill};
+use crate::person::{AttitudeType, Controlled, Person};
+
+// palette
+// the colours people are drawn in, so that who is on which side doesn't rest on telling green from blue
+#[derive(Debug, Clone, PartialEq)]
+pub struct Palette {
+    // name of the preset the palette started from
+    pub name: String,
+    // the player character
+    pub player: Color,
+    // the rest of the squad
+    pub squad: Color,
+    // civilians and anyone else not taking sides
+    pub neutral: Color,
+    // hostiles, or none to keep each archetype's own colour
+    pub hostile: Option<Color>,
+    // the flash of an attack
+    pub attack: Color,
+    // the filled part of a health bar
+    pub health: Color,
+    // the empty part of a health bar
+    pub health_empty: Color,
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Palette {
+            name: "default".to_string(),
+            player: Color::GREEN,
+            squad: Color::BLUE,
+            neutral: Color::rgb(0.3, 0.5, 0.8),
+            hostile: None,
+            attack: Color::rgb(1.0, 0.0, 0.0),
+            health: Color::rgb(0.8, 0.1, 0.1),
+            health_empty: Color::rgb(0.2, 0.2, 0.2),
+        }
+    }
+}
+
+impl Palette {
+    // gives the preset palette with the given name, as written in the settings file
+    // the colourblind presets keep to colours that stay apart for each kind of colourblindness
+    pub fn preset(name: &str) -> Option<Self> {
+        let palette = match name {
+            "default" => Palette::default(),
+            // red-green, with green weak
+            "deuteranopia" => Palette {
+                name: name.to_string(),
+                player: Color::rgb(0.0, 0.45, 0.7),
+                squad: Color::rgb(0.35, 0.7, 0.9),
+                neutral: Color::rgb(0.95, 0.9, 0.25),
+                hostile: Some(Color::rgb(0.9, 0.6, 0.0)),
+                attack: Color::WHITE,
+                health: Color::rgb(0.9, 0.6, 0.0),
+                health_empty: Color::rgb(0.2, 0.2, 0.2),
+            },
+            // red-green, with red weak, so reds are kept out as they look dark
+            "protanopia" => Palette {
+                name: name.to_string(),
+                player: Color::rgb(0.0, 0.45, 0.7),
+                squad: Color::rgb(0.35, 0.7, 0.9),
+                neutral: Color::rgb(0.95, 0.9, 0.25),
+                hostile: Some(Color::BLACK),
+                attack: Color::WHITE,
+                health: Color::rgb(0.95, 0.9, 0.25),
+                health_empty: Color::rgb(0.2, 0.2, 0.2),
+            },
+            // blue-yellow, so the sides are kept apart on red against teal
+            "tritanopia" => Palette {
+                name: name.to_string(),
+                player: Color::rgb(0.85, 0.1, 0.3),
+                squad: Color::rgb(0.95, 0.55, 0.6),
+                neutral: Color::rgb(0.6, 0.6, 0.6),
+                hostile: Some(Color::rgb(0.0, 0.35, 0.35)),
+                attack: Color::WHITE,
+                health: Color::rgb(0.85, 0.1, 0.3),
+                health_empty: Color::rgb(0.2, 0.2, 0.2),
+            },
+            _ => return None,
+        };
+        Some(palette)
+    }
+    // gives the preset palette after this one, for the palette key to switch to
+    pub fn next(&self) -> Self {
+        let index = PALETTE_NAMES.iter().position(|name| *name == self.name).map_or(0, |index| index + 1);
+        Palette::preset(PALETTE_NAMES[index % PALETTE_NAMES.len()]).unwrap_or_default()
+    }
+    // gives the colour a person is drawn in
+    // the player is the squad member at the front, and hostiles can fall back on their own colour
+    pub fn person_color(&self, attitude: &AttitudeType, squad_pos: Option<i32>, own_color: Option<Color>) -> Color {
+        match attitude {
+            AttitudeType::Squad if squad_pos == Some(0) => self.player,
+            AttitudeType::Squad => self.squad,
+            AttitudeType::Neutral | AttitudeType::Surrendered => self.neutral,
+            AttitudeType::Hostile => self.hostile.or(own_color).unwrap_or(Color::BLACK),
+        }
+    }
+    // sets one of the palette's colours by name, as written in the settings file
+    // gives back whether there is a colour with that name
+    pub fn set(&mut self, key: &str, color: Color) -> bool {
+        match key {
+            "player" => self.player = color,
+            "squad" => self.squad = color,
+            "neutral" => self.neutral = color,
+            "hostile" => self.hostile = Some(color),
+            "attack" => self.attack = color,
+            "health" => self.health = color,
+            "health_empty" => self.health_empty = color,
+            _ => return false,
+        }
+        true
+    }
+    // gives the colours that differ from the preset the palette started from, by name
+    pub fn custom_colors(&self) -> Vec<(&'static str, Color)> {
+        let preset = Palette::preset(&self.name).unwrap_or_default();
+        let mut colors = Vec::new();
+        let pairs = [
+            ("player", Some(self.player), Some(preset.player)),
+            ("squad", Some(self.squad), Some(preset.squad)),
+            ("neutral", Some(self.neutral), Some(preset.neutral)),
+            ("hostile", self.hostile, preset.hostile),
+            ("attack", Some(self.attack), Some(preset.attack)),
+            ("health", Some(self.health), Some(preset.health)),
+            ("health_empty", Some(self.health_empty), Some(preset.health_empty)),
+        ];
+        for (key, color, preset_color) in pairs.iter() {
+            if let Some(color) = color {
+                if Some(*color) != *preset_color {
+                    colors.push((*key, *color));
+                }
+            }
+        }
+        colors
+    }
+}
+
+// palette plugin
+// responsible for drawing people and health bars in the colours of the chosen palette
+// the palette can be switched while the game runs, and everything already drawn is tinted again
+pub struct PalettePlugin;
+
+// implementation of the plugin trait,
+// required for this to be used as a plugin
+impl Plugin for PalettePlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        // the palette is one of the player's settings
+        if app.resources().get::<Settings>().is_none() {
+            app.add_resource(Settings::load(SETTINGS_PATH));
+        }
+        app.init_resource::<PaletteState>()
+        // add in the palette key system
+        .add_system(palette_key_system.system())
+        // add in the palette tint system
+        .add_system(palette_tint_system.system());
+    }
+}
+
+// palette state
+// remembers the palette things were last tinted with, to tell when it changes
+#[derive(Default)]
+pub struct PaletteState {
+    pub applied: Option<Palette>,
+}
+
+// own colour component
+// the colour a person was spawned in, which palettes without a colour for their side fall back on
+pub struct OwnColor(pub Color);
+
+// tinted component
+// marks something as drawn in the current palette
+pub struct Tinted;
+
+// palette key system
+// switches to the next palette when alt and the palette key are pressed together, and saves the choice
+pub fn palette_key_system(inputs: Res<InputState>, bindings: Res<KeyBindings>, mut settings: ResMut<Settings>) {
+    if !inputs.key_just_presses.contains(&bindings.palette)
+        || !bindings.display_modifiers.iter().any(|key| inputs.key_presses.contains(key)) {
+        return;
+    }
+    settings.palette = settings.palette.next();
+    settings.save(SETTINGS_PATH);
+}
+
+// palette tint system
+// tints newly spawned people and health bars in the palette's colours
+// and tints everything again whenever the palette changes
+pub fn palette_tint_system(mut commands: Commands, settings: Res<Settings>, mut state: ResMut<PaletteState>, mut materials: ResMut<Assets<ColorMaterial>>,
+    mut people: Query<(Entity, &Person, Option<&Controlled>, Option<&OwnColor>, Option<&Tinted>, &SpriteData)>,
+    mut bars: Query<(Entity, &BossHealthBar, Option<&Tinted>, &Handle<ColorMaterial>)>,
+    mut fills: Query<(Entity, &BossHealthFill, Option<&Tinted>, &Handle<ColorMaterial>)>) {
+    let palette = &settings.palette;
+    let changed = state.applied.as_ref() != Some(palette);
+
+    for (entity, person, controlled, own_color, tinted, sprite_data) in &mut people.iter() {
+        if tinted.is_some() && !changed {
+            continue;
+        }
+        let color = palette.person_color(&person.attitude, controlled.map(|controlled| controlled.squad_pos), own_color.map(|own_color| own_color.0));
+        tint_sprite_data(&mut materials, &sprite_data, color, palette.attack);
+        if tinted.is_none() {
+            commands.insert_one(entity, Tinted);
+        }
+    }
+
+    // the empty part of a health bar sits behind the filled part
+    let mut health_bars = Vec::new();
+    for (entity, _, tinted, handle) in &mut bars.iter() {
+        health_bars.push((entity, tinted.is_some(), *handle, palette.health_empty));
+    }
+    for (entity, _, tinted, handle) in &mut fills.iter() {
+        health_bars.push((entity, tinted.is_some(), *handle, palette.health));
+    }
+    for (entity, tinted, handle, color) in health_bars {
+        if tinted && !changed {
+            continue;
+        }
+        if let Some(material) = materials.get_mut(&handle) {
+            material.color = color;
+        }
+        if !tinted {
+            commands.insert_one(entity, Tinted);
+        }
+    }
+
+    if changed {
+        state.applied = Some(palette.clone());
+    }
+}
@@ -0,0 +1,262 @@
+// path preview module
+// the paths the squad would take to wherever the mouse rests
+use bevy::prelude::*;
+use pathfinding::prelude::astar;
+
+use crate::{Position, SNEAK_SPEED};
+use crate::behaviour::{PathfindersQueue, TilePos};
+use crate::combat::Perks;
+use crate::config::GameConfig;
+use crate::control::{
+    Formation, get_formation_destination, get_formation_offsets, Hover, HoverTarget, InputState,
+    KeyBindings, MoveSpeed, Selection,
+};
+use crate::locale::Locale;
+use crate::map::MapData;
+use crate::mods::ModAssets;
+use crate::person::{Controlled, SimpleRect};
+use crate::stamina::Stamina;
+use crate::stance::Sneaking;
+use crate::tick::FrameTime;
+
+// path preview plugin
+// responsible for showing the paths the ordered squad members would take to wherever the mouse rests,
+// along with how long it would take them all to get there
+pub struct PathPreviewPlugin;
+
+// implementation of the plugin trait,
+// required for this to be used as a plugin
+impl Plugin for PathPreviewPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        // initialise the path preview resource
+        app.init_resource::<PathPreview>()
+        // add in the font the arrival time is shown in
+        .add_startup_system(setup_path_preview_system.system())
+        // add in the system finding where the mouse rests and who'd be ordered there
+        .add_system(path_preview_hover_system.system())
+        // add in the system working out and drawing the paths
+        .add_system(path_preview_system.system());
+    }
+}
+
+// settings for the path preview
+// the paths are worked out once the cursor has rested on a tile for this many seconds, so they aren't worked out every frame
+pub static PATH_PREVIEW_DELAY: f32 = 0.25;
+// the paths are drawn as dashes this far apart
+pub static PATH_PREVIEW_SPACING: f32 = 8.0;
+pub static PATH_PREVIEW_DASH_SIZE: f32 = 3.0;
+
+// path preview resource
+// the tile the mouse is resting on and the squad members who'd be ordered there,
+// how long the mouse has rested there, the paths worked out so far, and the markers drawn for the paths
+#[derive(Default)]
+pub struct PathPreview {
+    pub hovered: Option<(TilePos, Vec<i32>)>,
+    // where exactly the mouse is, and where each squad member who'd be ordered stands and how fast they'd go, in order
+    pub point: (f32, f32),
+    pub squad: Vec<((f32, f32), f32)>,
+    pub timer: f32,
+    pub shown: bool,
+    // the way each squad member would go and how long it'd take them, in order, none if they can't get there
+    pub paths: Vec<Option<(Vec<(f32, f32)>, f32)>>,
+    pub markers: Vec<Entity>,
+    // font used for the arrival time
+    pub font: Option<Handle<Font>>,
+    // material the dashes are drawn with
+    pub material: Option<Handle<ColorMaterial>>,
+}
+
+// path preview marker component
+// spawn this component along with the dashes and text drawn for a path preview
+pub struct PathPreviewMarker;
+
+// gives how long it takes to walk along a path, starting from the first point, at the given speed
+// each step is walked at the speed of the ground at its end
+pub fn get_path_duration(config: &GameConfig, map: &MapData, path: &[(f32, f32)], speed: f32) -> f32 {
+    path.windows(2).map(|step| {
+        let distance = Vec2::new(step[1].0 - step[0].0, step[1].1 - step[0].1).length();
+        let tile = TilePos::from_coords(config, step[1].0, step[1].1);
+        distance / (speed * map.get_terrain_speed(&tile)).max(1.0)
+    }).sum()
+}
+
+// spawns dashes along a path, so the way it goes can be seen
+// gives back the dashes spawned
+pub fn spawn_path_dashes(commands: &mut Commands, material: Handle<ColorMaterial>, path: &[(f32, f32)]) -> Vec<Entity> {
+    let mut dashes = Vec::new();
+    // carry the distance left over from each step on to the next, so the dashes are evenly spaced around corners
+    let mut carried = 0.0;
+    for step in path.windows(2) {
+        let dir = Vec2::new(step[1].0 - step[0].0, step[1].1 - step[0].1);
+        let length = dir.length();
+        if length <= 0.0 {
+            continue;
+        }
+        let mut along = PATH_PREVIEW_SPACING - carried;
+        while along <= length {
+            let point = Vec2::new(step[0].0, step[0].1) + dir * (along / length);
+            commands
+                .spawn(SimpleRect::new(material, Vec2::new(PATH_PREVIEW_DASH_SIZE, PATH_PREVIEW_DASH_SIZE)))
+                .with(Position(point[0], point[1]))
+                .with(PathPreviewMarker);
+            if let Some(entity) = commands.current_entity() {
+                dashes.push(entity);
+            }
+            along += PATH_PREVIEW_SPACING;
+        }
+        carried = length - (along - PATH_PREVIEW_SPACING);
+    }
+    dashes
+}
+
+// setup path preview system
+// loads the font the arrival time is shown in, and makes the material the dashes are drawn with
+pub fn setup_path_preview_system(asset_server: Res<AssetServer>, mods: Res<ModAssets>, mut materials: ResMut<Assets<ColorMaterial>>, mut preview: ResMut<PathPreview>) {
+    preview.font = asset_server.load(mods.resolve("assets/fonts/LiberationMono-Regular.ttf")).ok();
+    preview.material = Some(materials.add(Color::rgba(1.0, 1.0, 1.0, 0.8).into()));
+}
+
+// path preview hover system
+// finds the tile the mouse rests on and the squad members who'd be ordered there, and how long it's rested there
+// the preview is taken away as soon as the mouse moves on, or the click would give some other order
+pub fn path_preview_hover_system(mut commands: Commands, config: Res<GameConfig>, time: Res<FrameTime>, inputs: Res<InputState>, bindings: Res<KeyBindings>,
+    hover: Res<Hover>, selection: Res<Selection>, mut preview: ResMut<PathPreview>,
+    mut squad: Query<(Entity, &Controlled, &Position, Option<&MoveSpeed>, Option<&Stamina>, Option<&Sneaking>, Option<&Perks>)>) {
+    let point = inputs.mouse_position;
+    let on_map = point.0 >= 0.0 && point.1 >= 0.0 && point.0 < config.map_width && point.1 < config.map_height;
+    // holding any of these keys turns the click into an order that isn't a move
+    let other_order = [bindings.flee, bindings.distract, bindings.grenade, bindings.overwatch, bindings.heal, bindings.brace, bindings.waypoint, bindings.rally, bindings.focus]
+        .iter().any(|key| inputs.key_presses.contains(key));
+    // moves ordered while holding the sprint key are carried out at a sprint
+    let sprinting = inputs.key_presses.contains(&bindings.sprint);
+
+    // find who would be ordered, sorted so that the same squad member always gets the same spot, as with move orders
+    // along with how fast they'd go, leaving the ground underfoot to the path
+    let mut ordered = Vec::new();
+    for (entity, controlled, pos, speed, stamina, sneaking, perks) in &mut squad.iter() {
+        if selection.is_selected(entity, controlled.squad_pos) {
+            let speed = speed.cloned().unwrap_or_default();
+            let speed = speed.base * speed.stride(stamina, sprinting)
+                * perks.map_or(1.0, |perks| perks.speed_multiplier())
+                * if sneaking.is_some() { SNEAK_SPEED } else { 1.0 };
+            ordered.push((controlled.squad_pos, (pos.0, pos.1), speed));
+        }
+    }
+    ordered.sort_by_key(|(squad_pos, _, _)| *squad_pos);
+
+    let hovered = if on_map && !other_order && !inputs.mouse_captured && hover.target == HoverTarget::Ground && !ordered.is_empty() {
+        Some((TilePos::from_coords(&config, point.0, point.1), ordered.iter().map(|(squad_pos, _, _)| *squad_pos).collect()))
+    }else{
+        None
+    };
+    if hovered != preview.hovered {
+        preview.hovered = hovered;
+        preview.timer = 0.0;
+        preview.shown = false;
+        preview.paths.clear();
+        for marker in preview.markers.drain(..) {
+            commands.despawn(marker);
+        }
+    }
+    if preview.hovered.is_none() || preview.shown {
+        return;
+    }
+    preview.timer += time.delta_seconds;
+    preview.point = point;
+    preview.squad = ordered.into_iter().map(|(_, pos, speed)| (pos, speed)).collect();
+}
+
+// path preview system
+// once the mouse has rested on open ground for long enough, works out the paths the squad members
+// who'd be ordered there would take around water and walls, and draws them,
+// with the time the last of them would get there, or that they can't get there, by the mouse
+// paths are worked out a few at a time, out of the same pathfinders allowed each tick as everyone else's
+pub fn path_preview_system(mut commands: Commands, config: Res<GameConfig>, map: Res<MapData>, formation: Res<Formation>, locale: Res<Locale>,
+    mut waiting: ResMut<PathfindersQueue>, mut preview: ResMut<PathPreview>) {
+    if preview.hovered.is_none() || preview.shown || preview.timer < PATH_PREVIEW_DELAY {
+        return;
+    }
+    let point = preview.point;
+    let ordered = preview.squad.clone();
+
+    // the formation faces from the middle of the group towards the mouse, as with move orders
+    let mut centroid = Vec2::new(0.0, 0.0);
+    for (pos, _) in ordered.iter() {
+        centroid += Vec2::new(pos.0, pos.1);
+    }
+    centroid /= ordered.len() as f32;
+    let offsets = get_formation_offsets(formation.kind, ordered.len(), Vec2::new(point.0, point.1) - centroid);
+
+    // work out the paths not worked out yet, for as long as there are pathfinders to spare this tick
+    while preview.paths.len() < ordered.len() && waiting.0 < config.max_pathfinders {
+        waiting.0 += 1;
+        let index = preview.paths.len();
+        let (pos, speed) = ordered[index];
+        let destination = match offsets.get(index) {
+            Some(offset) if index > 0 => get_formation_destination(&config, &map, point, *offset),
+            _ => point,
+        };
+        let start = TilePos::from_coords(&config, pos.0, pos.1);
+        let goal = TilePos::from_coords(&config, destination.0, destination.1);
+        let path = astar(&start, |p| map.successors(p, false), |p| map.get_diag_dist(*p, goal), |p| *p == goal);
+        // blocked in, e.g. up a cliff, so there's no path to show
+        preview.paths.push(path.map(|(tiles, _cost)| {
+            // from where they stand, through the middle of each tile on the way, to the exact spot
+            let mut points = vec![pos];
+            points.extend(tiles.iter().skip(1).map(|tile| tile.to_coords(&config)));
+            points.push(destination);
+            let duration = get_path_duration(&config, &map, &points, speed);
+            (points, duration)
+        }));
+    }
+    // wait for the rest to be worked out before showing any of them
+    if preview.paths.len() < ordered.len() {
+        return;
+    }
+    preview.shown = true;
+
+    let mut longest = Some(0.0f32);
+    let mut dashes = Vec::new();
+    for path in preview.paths.iter() {
+        match path {
+            Some((points, duration)) => {
+                longest = longest.map(|longest| longest.max(*duration));
+                if let Some(material) = preview.material {
+                    dashes.extend(spawn_path_dashes(&mut commands, material, points));
+                }
+            },
+            None => longest = None,
+        }
+    }
+    preview.markers.extend(dashes);
+
+    // show when the last of them would get there by the mouse
+    let value = match longest {
+        Some(seconds) => locale.format("path_eta", &[&seconds.ceil()]),
+        None => locale.get("order_unreachable"),
+    };
+    if let Some(font) = preview.font {
+        commands
+            .spawn(TextComponents {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    ..Default::default()
+                },
+                text: Text {
+                    value: value,
+                    font: font,
+                    style: TextStyle {
+                        font_size: 12.0,
+                        color: Color::WHITE,
+                    },
+                },
+                ..Default::default()
+            })
+            // offset the text so it sits beside the cursor
+            .with(Position(point.0 + 8.0, point.1 - 14.0))
+            .with(PathPreviewMarker);
+        if let Some(entity) = commands.current_entity() {
+            preview.markers.push(entity);
+        }
+    }
+}
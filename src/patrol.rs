@@ -0,0 +1,214 @@
+// patrol module
+// laying down patrol routes and handing them to the squad
+use bevy::prelude::*;
+use std::collections::HashMap;
+
+use crate::{Id, Position};
+use crate::control::{InputState, KeyBindings, Selection};
+use crate::mods::ModAssets;
+use crate::nerve::{Action, ActionType, Nerve};
+use crate::person::{Controlled, SimpleRect};
+
+// patrol plugin
+// responsible for the patrol route editor
+// hold the patrol modifier and click to lay down numbered waypoints,
+// then press the assign key to have the selected squad member patrol them
+pub struct PatrolPlugin;
+
+// implementation of the plugin trait,
+// required for this to be used as a plugin
+impl Plugin for PatrolPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        // initialise the patrol editor resource
+        app.init_resource::<PatrolEditor>()
+        // add in the system for laying down waypoints
+        .add_system(patrol_edit_system.system())
+        // add in the system for handing routes to squad members
+        .add_system(patrol_assign_system.system());
+    }
+}
+
+// settings for the patrol route editor
+// hold the modifier and click to lay down waypoints, then press the assign key to
+// hand the route to a squad member
+pub static PATROL_MODIFIER: KeyCode = KeyCode::LAlt;
+pub static PATROL_ASSIGN_KEY: KeyCode = KeyCode::P;
+// distance between the dots linking one waypoint to the next
+pub static PATROL_LINK_SPACING: f32 = 8.0;
+
+// patrol editor resource
+// holds the route currently being laid down
+#[derive(Default)]
+pub struct PatrolEditor {
+    // the waypoints laid down so far, in order
+    pub waypoints: Vec<(f32, f32)>,
+    // the markers drawn for the route so far
+    pub markers: Vec<Entity>,
+    // font used for the waypoint numbers
+    pub font: Option<Handle<Font>>,
+}
+
+// patrol marker component
+// spawn this component along with the markers drawn for a patrol route
+pub struct PatrolMarker {
+    // id of the squad member the route belongs to, if it's been assigned
+    pub owner: Option<String>,
+}
+
+// gives a patrol action that walks the given waypoints in a loop
+pub fn get_patrol_action(waypoints: &[(f32, f32)]) -> Action {
+    let mut params = HashMap::new();
+    params.insert("count".to_string(), waypoints.len() as f32);
+    params.insert("index".to_string(), 0.0);
+    for (i, waypoint) in waypoints.iter().enumerate() {
+        params.insert(format!("x{}", i), waypoint.0);
+        params.insert(format!("y{}", i), waypoint.1);
+    }
+    Action {
+        action_type: ActionType::Patrol,
+        target: (None, None),
+        params: Some(params),
+        group: None,
+    }
+}
+
+// spawns a line of dots between two waypoints, so the route can be seen
+// gives back the dots spawned
+pub fn spawn_patrol_link(commands: &mut Commands, material: Handle<ColorMaterial>, from: (f32, f32), to: (f32, f32), owner: Option<String>) -> Vec<Entity> {
+    let mut dots = Vec::new();
+    let dir = Vec2::new(to.0 - from.0, to.1 - from.1);
+    let steps = (dir.length() / PATROL_LINK_SPACING) as usize;
+    // skip the ends, the waypoint markers sit there
+    for i in 1..steps {
+        let point = Vec2::new(from.0, from.1) + dir * (i as f32 / steps as f32);
+        commands
+            .spawn(SimpleRect::new(material, Vec2::new(2.0, 2.0)))
+            .with(Position(point[0], point[1]))
+            .with(PatrolMarker { owner: owner.clone() });
+        if let Some(entity) = commands.current_entity() {
+            dots.push(entity);
+        }
+    }
+    dots
+}
+
+// patrol edit system
+// lays down a waypoint wherever the player clicks while holding the patrol modifier
+pub fn patrol_edit_system(mut commands: Commands, inputs: Res<InputState>, bindings: Res<KeyBindings>, mut editor: ResMut<PatrolEditor>,
+    mut materials: ResMut<Assets<ColorMaterial>>, asset_server: Res<AssetServer>, mods: Res<ModAssets>) {
+    if !inputs.mouse_just_presses.contains(&MouseButton::Left) || !inputs.key_presses.contains(&bindings.waypoint) {
+        return;
+    }
+
+    let waypoint = inputs.mouse_position;
+    let material = materials.add(Color::YELLOW.into());
+
+    // link the new waypoint to the last one
+    if let Some(&last) = editor.waypoints.last() {
+        let dots = spawn_patrol_link(&mut commands, material, last, waypoint, None);
+        editor.markers.extend(dots);
+    }
+
+    // draw the waypoint itself
+    commands
+        .spawn(SimpleRect::new(material, Vec2::new(6.0, 6.0)))
+        .with(Position(waypoint.0, waypoint.1))
+        .with(PatrolMarker { owner: None });
+    if let Some(entity) = commands.current_entity() {
+        editor.markers.push(entity);
+    }
+
+    // number the waypoint so the order of the route can be seen
+    if editor.font.is_none() {
+        editor.font = asset_server.load(mods.resolve("assets/fonts/LiberationMono-Regular.ttf")).ok();
+    }
+    if let Some(font) = editor.font {
+        commands
+            .spawn(TextComponents {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    ..Default::default()
+                },
+                text: Text {
+                    value: (editor.waypoints.len() + 1).to_string(),
+                    font: font,
+                    style: TextStyle {
+                        font_size: 12.0,
+                        color: Color::YELLOW,
+                    },
+                },
+                ..Default::default()
+            })
+            // offset the number so it sits beside the marker
+            .with(Position(waypoint.0 + 4.0, waypoint.1 - 14.0))
+            .with(PatrolMarker { owner: None });
+        if let Some(entity) = commands.current_entity() {
+            editor.markers.push(entity);
+        }
+    }
+
+    editor.waypoints.push(waypoint);
+}
+
+// patrol assign system
+// hands the route being laid down to the selected squad member when the assign key is pressed
+// the route replaces whatever the squad member was doing
+pub fn patrol_assign_system(mut commands: Commands, inputs: Res<InputState>, bindings: Res<KeyBindings>, mut editor: ResMut<PatrolEditor>, mut materials: ResMut<Assets<ColorMaterial>>,
+    selection: Res<Selection>, mut squad: Query<(Entity, &Controlled, &Id, &mut Nerve)>, mut markers: Query<(Entity, &mut PatrolMarker)>) {
+    if !inputs.key_just_presses.contains(&bindings.assign_patrol) || editor.waypoints.is_empty() {
+        return;
+    }
+
+    // the route goes to the selected squad member with the lowest squad index
+    let mut squad_pos = None;
+    for (entity, state, _id, _actions) in &mut squad.iter() {
+        if selection.is_selected(entity, state.squad_pos) && squad_pos.map_or(true, |p| state.squad_pos < p) {
+            squad_pos = Some(state.squad_pos);
+        }
+    }
+    let squad_pos = match squad_pos {
+        Some(squad_pos) => squad_pos,
+        None => return,
+    };
+
+    let mut owner = None;
+    for (_entity, state, id, mut actions) in &mut squad.iter() {
+        if state.squad_pos == squad_pos {
+            // replace the current actions with the patrol
+            actions.action_queue.clear();
+            actions.action_timer = None;
+            actions.current_action = get_patrol_action(&editor.waypoints);
+            owner = Some(id.id());
+            break;
+        }
+    }
+
+    // keep the route being edited if no one was there to take it
+    let owner = match owner {
+        Some(owner) => owner,
+        None => return,
+    };
+
+    for (entity, mut marker) in &mut markers.iter() {
+        // remove the markers of the squad member's old route
+        if marker.owner.as_ref() == Some(&owner) {
+            commands.despawn(entity);
+        }
+        // and hand them the markers of the new one
+        if editor.markers.contains(&entity) {
+            marker.owner = Some(owner.clone());
+        }
+    }
+
+    // close the loop, so it's clear the route starts over at the end
+    if editor.waypoints.len() > 2 {
+        let first = editor.waypoints[0];
+        let last = editor.waypoints[editor.waypoints.len() - 1];
+        let material = materials.add(Color::YELLOW.into());
+        spawn_patrol_link(&mut commands, material, last, first, Some(owner.clone()));
+    }
+
+    // start a fresh route
+    editor.waypoints.clear();
+    editor.markers.clear();
+}
@@ -0,0 +1,511 @@
+// perception module
+// what ai actors notice about the world around them
+use bevy::prelude::*;
+use std::collections::HashMap;
+use bevy_rapier2d::rapier::dynamics::RigidBodySet;
+use bevy_rapier2d::rapier::geometry::ColliderSet;
+use bevy_rapier2d::rapier::pipeline::QueryPipeline;
+
+use crate::{
+    ALERT_VISIBILITY, combat_query, DETECTION_DECAY, GUNSHOT_NOISE_RADIUS, Id, LOST_SIGHT_GRACE,
+    OVERWATCH_ANGLE, PERCEPTION_RADIUS, Position, SEARCH_LOOK_DURATION, SEARCH_STEP,
+    SNEAK_DETECTION_TIME, SNEAK_NOISE, SNEAK_VISIBILITY, Velocity, VISION_CONE_ANGLE,
+    WARY_VISIBILITY, WEAPON_RANGE,
+};
+use crate::behaviour::{Behaviour, BehaviourType, Pathfinder, TilePos};
+use crate::combat::AttackEvent;
+use crate::config::GameConfig;
+use crate::control::{get_attack_actions, Weapon};
+use crate::lighting::LightMap;
+use crate::map::MapData;
+use crate::nerve::{Action, ActionType, Nerve};
+use crate::person::{AttitudeType, Controlled, Person};
+use crate::stance::Sneaking;
+use crate::tick::AddTickSystem;
+use crate::vision_cone::{hearing_system, investigate_system};
+use crate::weather::Weather;
+
+// perception plugin
+// responsible for what ai actors notice about the world around them
+pub struct PerceptionPlugin;
+
+// implementation of the plugin trait,
+// required for this to be used as a plugin
+impl Plugin for PerceptionPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        // add in the noise and spotted events
+        app.add_tick_event::<NoiseEvent>()
+        .add_tick_event::<SpottedEvent>()
+        // initialise the event states
+        .init_resource::<NoiseEventState>()
+        .init_resource::<GunshotNoiseState>()
+        // attacks make noise
+        .add_tick_system(gunshot_noise_system.system())
+        .init_resource::<FacingAttackState>()
+        // add in the facing system
+        .add_tick_system(facing_system.system())
+        // initialise the alert, raised by alarms
+        .init_resource::<Alert>()
+        // initialise the light map, full daylight unless the lighting plugin says otherwise
+        .init_resource::<LightMap>()
+        // initialise the weather, clear unless the weather plugin says otherwise
+        .init_resource::<Weather>()
+        // add in the sight system
+        .add_tick_system(sight_system.system())
+        // add in the overwatch system
+        .add_tick_system(overwatch_system.system())
+        // add in the search system
+        .add_tick_system(search_system.system())
+        // add in the hearing system
+        .add_tick_system(hearing_system.system())
+        // add in the investigate system
+        .add_tick_system(investigate_system.system());
+    }
+}
+
+// alert resource
+// whether the encounter has been put on alert by an alarm being raised
+// the alert lasts for the rest of the encounter
+#[derive(Default)]
+pub struct Alert {
+    pub raised: bool,
+}
+
+// noise event
+// sent whenever something loud happens, such as gunshots, explosions, or sprinting
+// noises are heard by anyone within the radius, whether or not they can see the origin
+pub struct NoiseEvent {
+    // where the noise came from
+    pub origin: (f32, f32),
+    // how far away the noise can be heard
+    pub radius: f32,
+    // attitude of whoever made the noise, if anyone
+    pub attitude: Option<AttitudeType>,
+}
+
+// spotted event
+// sent whenever a hostile spots a squad member and goes after them
+pub struct SpottedEvent {
+    // id of the hostile that did the spotting
+    pub spotter_id: String,
+    // id of the squad member spotted, and where they were
+    pub target_id: String,
+    pub position: (f32, f32),
+}
+
+// noise event state holds an event reader for noise events
+#[derive(Default)]
+pub struct NoiseEventState {
+    pub event_reader: EventReader<NoiseEvent>,
+}
+
+// gunshot noise state holds an event reader for attack events
+#[derive(Default)]
+pub struct GunshotNoiseState {
+    pub event_reader: EventReader<AttackEvent>,
+}
+
+// perception component
+// spawn this component along with any ai actor that should notice things
+pub struct Perception {
+    // multiplier on how far away noises can be heard from
+    pub hearing: f32,
+    // how far away the entity can see
+    pub sight: f32,
+    // how wide the entity can see, in degrees
+    pub fov: f32,
+    // the normalized direction the entity is looking in
+    pub facing: (f32, f32),
+    // the most recent noise heard, waiting to be reacted to
+    pub heard: Option<(f32, f32)>,
+    // what the entity does about the noises it hears
+    pub reaction: NoiseReaction,
+    // the target being chased, and where it was last seen
+    pub memory: Option<Memory>,
+    // the search for a lost target, if there is one
+    pub search: Option<SearchPattern>,
+    // how close the entity is to spotting a sneaking squad member, from 0 to 1
+    pub detection: f32,
+    // whether the entity has found a body, which puts it on its guard for good
+    pub wary: bool,
+}
+
+// memory struct
+// what a perceiving entity remembers about the target it's chasing
+#[derive(Debug, Clone)]
+pub struct Memory {
+    // id of the target
+    pub target: String,
+    // where the target was last seen
+    pub last_known: (f32, f32),
+    // seconds since the target was last seen
+    pub unseen: f32,
+}
+
+// search pattern struct
+// the places to look for a lost target, starting where it was last seen and moving on to the tiles around it
+#[derive(Debug, Clone)]
+pub struct SearchPattern {
+    pub points: Vec<(f32, f32)>,
+    // the point being searched
+    pub index: usize,
+    // whether the entity is on its way to the point
+    pub travelling: bool,
+    // runs while the entity is looking around at the point
+    pub look: Option<Timer>,
+}
+
+impl SearchPattern {
+    // gives a search pattern around where a target was last seen
+    pub fn new(config: &GameConfig, map: &MapData, last_known: (f32, f32)) -> Self {
+        let mut points = vec![last_known];
+        for offset in [(0.0, -1.0), (1.0, 0.0), (0.0, 1.0), (-1.0, 0.0)].iter() {
+            let point = (
+                (last_known.0 + offset.0 * SEARCH_STEP).max(10.0).min(config.map_width - 10.0),
+                (last_known.1 + offset.1 * SEARCH_STEP).max(10.0).min(config.map_height - 10.0),
+            );
+            // skip anywhere that can't be stood on
+            if !map.is_tile_blocked(&TilePos::from_coords(config, point.0, point.1)) {
+                points.push(point);
+            }
+        }
+        SearchPattern {
+            points: points,
+            index: 0,
+            travelling: false,
+            look: None,
+        }
+    }
+}
+
+impl Default for Perception {
+    fn default() -> Self {
+        Perception {
+            hearing: 1.0,
+            sight: PERCEPTION_RADIUS,
+            fov: VISION_CONE_ANGLE,
+            facing: (1.0, 0.0),
+            heard: None,
+            reaction: NoiseReaction::Investigate,
+            memory: None,
+            search: None,
+            detection: 0.0,
+            wary: false,
+        }
+    }
+}
+
+impl Perception {
+    // gives whether a position lies within the entity's vision cone
+    // visibility scales how far away the cone reaches, e.g. 0.5 for something only seen from half as far
+    // this doesn't check whether anything is in the way
+    pub fn in_vision_cone(&self, from: (f32, f32), to: (f32, f32), visibility: f32) -> bool {
+        let offset = Vec2::new(to.0 - from.0, to.1 - from.1);
+        let dist = offset.length();
+        if dist > self.sight * visibility {
+            return false;
+        }
+        // anything right on top of the entity can always be seen
+        if dist <= 0.0 {
+            return true;
+        }
+        let facing = Vec2::new(self.facing.0, self.facing.1);
+        let cos = facing.dot(offset / dist);
+        cos >= (self.fov.to_radians() / 2.0).cos()
+    }
+}
+
+// noise reaction enum
+// what an entity does on hearing a noise
+// each reaction is handled by its own system, which takes the heard noise off the perception
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NoiseReaction {
+    // go and find out what made the noise, handled by the investigate system
+    Investigate,
+    // run away from the noise, handled by the panic system
+    Panic,
+}
+
+// gunshot noise system
+// every attack makes a noise at the attacker's position
+pub fn gunshot_noise_system(mut state: ResMut<GunshotNoiseState>, attacks: Res<Events<AttackEvent>>, mut noises: ResMut<Events<NoiseEvent>>, mut query: Query<(&Id, &Person, &Position, Option<&Sneaking>)>) {
+    for attack in state.event_reader.iter(&attacks) {
+        for (id, pers, pos, sneaking) in &mut query.iter() {
+            if id.id() == attack.attacker_id {
+                noises.send(NoiseEvent {
+                    origin: (pos.0, pos.1),
+                    // sneaking squad members make less noise
+                    radius: GUNSHOT_NOISE_RADIUS * if sneaking.is_some() { SNEAK_NOISE } else { 1.0 },
+                    attitude: Some(pers.attitude),
+                });
+                break;
+            }
+        }
+    }
+}
+
+// facing attack state holds an event reader for attack events
+#[derive(Default)]
+pub struct FacingAttackState {
+    pub event_reader: EventReader<AttackEvent>,
+}
+
+// facing system
+// turns every perceiving entity to face the way it's moving
+// or the way it last attacked, when it's standing still
+pub fn facing_system(mut state: ResMut<FacingAttackState>, attacks: Res<Events<AttackEvent>>, mut query: Query<(&Id, &Velocity, &mut Perception)>) {
+    for attack in state.event_reader.iter(&attacks) {
+        for (id, _vel, mut perception) in &mut query.iter() {
+            if id.id() == attack.attacker_id {
+                if attack.direction.0 != 0.0 || attack.direction.1 != 0.0 {
+                    perception.facing = attack.direction;
+                }
+                break;
+            }
+        }
+    }
+    for (_id, vel, mut perception) in &mut query.iter() {
+        let velocity = Vec2::new(vel.0, vel.1);
+        if velocity.length() >= 1.0 {
+            let facing = velocity.normalize();
+            perception.facing = (facing[0], facing[1]);
+        }
+    }
+}
+
+// gives whether people with the given attitudes are on opposite sides of the fight
+pub fn is_enemy(attitude: AttitudeType, other: AttitudeType) -> bool {
+    match (attitude, other) {
+        (AttitudeType::Hostile, AttitudeType::Squad) | (AttitudeType::Hostile, AttitudeType::Ally) => true,
+        (AttitudeType::Squad, AttitudeType::Hostile) | (AttitudeType::Ally, AttitudeType::Hostile) => true,
+        _ => false,
+    }
+}
+
+// overwatch system
+// watches for enemies on the move through the cone of everyone on overwatch,
+// within their weapon's range and with nothing in the way
+// the first one seen is fired at straight away, which uses up the overwatch
+pub fn overwatch_system(config: Res<GameConfig>, pipeline: Res<QueryPipeline>, bodies: Res<RigidBodySet>, colliders: Res<ColliderSet>,
+    mut movers: Query<(&Id, &Person, &Position, &Velocity)>, mut watchers: Query<(&Person, &Position, &mut Nerve)>) {
+    // only enemies on the move set off an overwatch
+    let mut moving = Vec::new();
+    for (id, pers, pos, vel) in &mut movers.iter() {
+        if Vec2::new(vel.0, vel.1).length() >= 1.0 {
+            moving.push((id.id(), pers.attitude, (pos.0, pos.1)));
+        }
+    }
+
+    for (pers, pos, mut actions) in &mut watchers.iter() {
+        let action = actions.current_action.clone();
+        let (target, params) = match (action.action_type, action.target.0, action.params) {
+            (ActionType::Overwatch, Some(target), Some(params)) => (target, params),
+            _ => continue,
+        };
+        let range = params.get("range").cloned().unwrap_or(WEAPON_RANGE);
+        let min_range = params.get("min_range").cloned().unwrap_or(0.0);
+        // the cone reaches as far as the weapon, facing the way being watched
+        let facing = Vec2::new(target.0 - pos.0, target.1 - pos.1);
+        let facing = if facing.length() > 0.0 { facing.normalize() } else { Vec2::new(1.0, 0.0) };
+        let cone = Perception {
+            sight: range,
+            fov: OVERWATCH_ANGLE,
+            facing: (facing[0], facing[1]),
+            ..Default::default()
+        };
+        let spotted = moving.iter().find(|(_, attitude, mover_pos)| {
+            let dist = Vec2::new(mover_pos.0 - pos.0, mover_pos.1 - pos.1).length();
+            is_enemy(pers.attitude, *attitude) && dist >= min_range
+                && cone.in_vision_cone((pos.0, pos.1), *mover_pos, 1.0)
+                && combat_query::line_of_sight(&config, &pipeline, &bodies, &colliders, (pos.0, pos.1), *mover_pos)
+        });
+        if let Some((target_id, _, _)) = spotted {
+            // fire a single shot, then go back to whatever comes next
+            let mut params = params;
+            params.insert("shots".to_string(), 1.0);
+            actions.current_action = Action {
+                action_type: ActionType::Attack,
+                target: (None, Some(target_id.clone())),
+                params: Some(params),
+                group: None,
+            };
+            actions.action_timer = None;
+        }
+    }
+}
+
+// sight system
+// lets hostiles see squad members within their vision cone, with nothing in the way
+// hostiles go after the squad members they see, unless they're already fighting or otherwise busy
+// squad members approaching from behind aren't seen
+// hostiles remember where the target they're chasing was last seen, and go searching there if they lose sight of it
+// hostiles see further once the encounter is on alert, and squad members standing in the dark, rain or fog are harder to see
+pub fn sight_system(config: Res<GameConfig>, time: Res<Time>, map: Res<MapData>, alert: Res<Alert>, light: Res<LightMap>, weather: Res<Weather>, pipeline: Res<QueryPipeline>, bodies: Res<RigidBodySet>, colliders: Res<ColliderSet>,
+    mut spotted: ResMut<Events<SpottedEvent>>,
+    mut squad: Query<(&Controlled, &Id, &Position, Option<&Sneaking>)>,
+    mut query: Query<(&Id, &Person, &mut Perception, &Position, &mut Behaviour, &mut Nerve, &mut Pathfinder, Option<&Weapon>)>) {
+    let mut members = Vec::new();
+    for (_controlled, id, pos, sneaking) in &mut squad.iter() {
+        // sneaking squad members can only be seen from closer up
+        let visibility = if sneaking.is_some() { SNEAK_VISIBILITY } else { 1.0 }
+            * if alert.raised { ALERT_VISIBILITY } else { 1.0 }
+            * light.visibility_at((pos.0, pos.1))
+            * weather.kind.visibility();
+        members.push((id.id(), (pos.0, pos.1), visibility, sneaking.is_some()));
+    }
+
+    for (id, pers, mut perception, pos, mut behav, mut actions, mut pf, weapon) in &mut query.iter() {
+        if pers.attitude != AttitudeType::Hostile {
+            continue;
+        }
+        // ambushers, the surrendered, the panicking and alarm runners have their own plans
+        let busy = behav.current_behaviour == BehaviourType::Ambush || behav.current_behaviour == BehaviourType::Surrender
+            || behav.current_behaviour == BehaviourType::Panic || behav.current_behaviour == BehaviourType::RaiseAlarm;
+        if busy {
+            continue;
+        }
+        let visible = |perception: &Perception, member_pos: (f32, f32), visibility: f32| {
+            // hostiles on their guard see further
+            let visibility = visibility * if perception.wary { WARY_VISIBILITY } else { 1.0 };
+            perception.in_vision_cone((pos.0, pos.1), member_pos, visibility)
+                && combat_query::line_of_sight(&config, &pipeline, &bodies, &colliders, (pos.0, pos.1), member_pos)
+        };
+
+        // find who the hostile is fighting, if anyone
+        let chasing = if let ActionType::Attack = actions.current_action.action_type {
+            actions.current_action.target.1.clone()
+        }else{
+            actions.action_queue.iter().find(|action| match action.action_type {
+                ActionType::Attack => true,
+                _ => false,
+            }).and_then(|action| action.target.1.clone())
+        };
+
+        if let Some(target) = chasing {
+            // keep track of where the target is while it can be seen
+            let member = members.iter().find(|(id, _, _, _)| *id == target);
+            if let Some((_, member_pos, visibility, _)) = member {
+                if visible(&perception, *member_pos, *visibility) {
+                    perception.memory = Some(Memory {
+                        target: target.clone(),
+                        last_known: *member_pos,
+                        unseen: 0.0,
+                    });
+                    continue;
+                }
+            }
+            // and go looking for it once it's been out of sight for long enough
+            let lost = match &mut perception.memory {
+                Some(memory) if memory.target == target => {
+                    memory.unseen += time.delta_seconds;
+                    if memory.unseen >= LOST_SIGHT_GRACE { Some(memory.last_known) } else { None }
+                },
+                _ => None,
+            };
+            if let Some(last_known) = lost {
+                actions.clear();
+                pf.tile_path.clear();
+                perception.memory = None;
+                perception.search = Some(SearchPattern::new(&config, &map, last_known));
+                behav.current_behaviour = BehaviourType::Search;
+            }
+            continue;
+        }
+
+        // go after the closest squad member in sight
+        let mut seen: Option<(String, (f32, f32), f32, bool)> = None;
+        for (id, member_pos, visibility, sneaking) in members.iter() {
+            if !visible(&perception, *member_pos, *visibility) {
+                continue;
+            }
+            let dist = Vec2::new(member_pos.0 - pos.0, member_pos.1 - pos.1).length();
+            if seen.as_ref().map_or(true, |(_, _, closest, _)| dist < *closest) {
+                seen = Some((id.clone(), *member_pos, dist, *sneaking));
+            }
+        }
+        // sneaking squad members take a while to be spotted, anyone else is spotted straight away
+        // the hostile slowly forgets what it half saw once nothing's in sight
+        match &seen {
+            Some((_, _, _, true)) => perception.detection = (perception.detection + time.delta_seconds / SNEAK_DETECTION_TIME).min(1.0),
+            Some((_, _, _, false)) => perception.detection = 1.0,
+            None => perception.detection = (perception.detection - time.delta_seconds * DETECTION_DECAY).max(0.0),
+        }
+        if perception.detection < 1.0 {
+            continue;
+        }
+        if let Some((target, target_pos, _, _)) = seen {
+            actions.replace_all(get_attack_actions(Some(target.clone()), &weapon.cloned().unwrap_or_default(), HashMap::new()));
+            spotted.send(SpottedEvent {
+                spotter_id: id.id(),
+                target_id: target.clone(),
+                position: target_pos,
+            });
+            perception.memory = Some(Memory {
+                target: target,
+                last_known: target_pos,
+                unseen: 0.0,
+            });
+            // whatever the hostile was looking for, it's found something
+            perception.search = None;
+            if behav.current_behaviour == BehaviourType::Investigate || behav.current_behaviour == BehaviourType::Search {
+                behav.current_behaviour = BehaviourType::Empty;
+            }
+        }
+    }
+}
+
+// search system
+// walks searching entities through their search pattern, looking around at each point
+// they give up once every point has been searched
+pub fn search_system(config: Res<GameConfig>, time: Res<Time>, mut query: Query<(&mut Perception, &mut Behaviour, &Nerve, &mut Pathfinder)>) {
+    for (mut perception, mut behav, actions, mut pf) in &mut query.iter() {
+        if behav.current_behaviour != BehaviourType::Search {
+            continue;
+        }
+        let arrived = !pf.needs_pathfinding
+            && pf.path_index >= pf.tile_path.len()
+            && actions.is_curr_action_empty()
+            && actions.action_queue.is_empty();
+        let facing = perception.facing;
+        let search = match &mut perception.search {
+            Some(search) => search,
+            None => {
+                behav.current_behaviour = BehaviourType::Empty;
+                continue;
+            },
+        };
+
+        // look around, turning a full circle over the look duration
+        let mut new_facing = None;
+        if let Some(look) = &mut search.look {
+            look.tick(time.delta_seconds);
+            let turn = std::f32::consts::PI * 2.0 * time.delta_seconds / SEARCH_LOOK_DURATION;
+            let heading = facing.1.atan2(facing.0) + turn;
+            new_facing = Some((heading.cos(), heading.sin()));
+            if look.finished {
+                search.look = None;
+                search.index += 1;
+            }
+        }else if search.travelling {
+            if arrived {
+                search.travelling = false;
+                search.look = Some(Timer::from_seconds(SEARCH_LOOK_DURATION, false));
+            }
+        }else if search.index < search.points.len() {
+            // head to the next point
+            let point = search.points[search.index];
+            pf.needs_pathfinding = true;
+            pf.path_goal = TilePos::from_coords(&config, point.0.max(0.0), point.1.max(0.0));
+            pf.real_goal = point;
+            search.travelling = true;
+        }else{
+            // nothing found, give up
+            perception.search = None;
+            behav.current_behaviour = BehaviourType::Empty;
+            continue;
+        }
+        if let Some(new_facing) = new_facing {
+            perception.facing = new_facing;
+        }
+    }
+}
@@ -0,0 +1,298 @@
+// person module
+// the people of the mission, and the orders given to them
+use bevy::prelude::*;
+use std::collections::VecDeque;
+
+use crate::{
+    EXPERIENCE_COOLDOWN_CUT, Id, MAX_EXPERIENCE_COOLDOWN_CUT, MERCENARY_HEALTH, ORDER_QUEUE_LENGTH,
+    PLAYER_START, PLAYER_Z_LEVEL, Position, Size, SQUAD_POSITIONS, STARTING_MEDKITS, Velocity,
+};
+use crate::animation::{get_player_sprite_template, get_squadmate_sprite_template};
+use crate::behaviour::{Behaviour, Pathfinder};
+use crate::campaign::Roster;
+use crate::collision::get_person_body_components;
+use crate::combat::{Health, Medkits, Perks, Resistances, Shield};
+use crate::config::{GameConfig, LaunchOptions};
+use crate::control::{CommandType, Holstered, MoveSpeed};
+use crate::door::Lockpicker;
+use crate::encounter::Archetypes;
+use crate::nerve::Nerve;
+use crate::stamina::Stamina;
+use crate::stance::Stance;
+use crate::zone::StartPoint;
+
+// person plugin
+// adds in all the people
+pub struct PersonPlugin;
+// person component
+// spawn this component along with any entity that should be considered a person
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AttitudeType {
+    Neutral,
+    Squad,
+    Hostile,
+    Ally,
+    // hostiles that have given up, who are no threat and can be captured
+    Surrendered,
+}
+
+pub struct Person {
+    pub attitude: AttitudeType,
+}
+
+impl Default for Person {
+    fn default() -> Self {
+        Person {
+            attitude: AttitudeType::Neutral,
+        }
+    }
+}
+
+impl Person {
+    pub fn new(att: AttitudeType) -> Self {
+        Person {
+            attitude: att,
+        }
+    }
+}
+
+// controlled component
+// spawn this component along with any entity that should be considered controlled by the player
+#[derive(Default)]
+pub struct Controlled {
+    // current command given to this entity
+    pub current_command: Command,
+    // index in the squad
+    pub squad_pos: i32,
+    // commands queued up behind the current one, oldest first
+    // these haven't started yet, so the latest can be taken back
+    pub command_queue: VecDeque<Command>,
+    // destination of the attack move being carried out, if there is one
+    pub attack_move: Option<(f32, f32)>,
+}
+
+impl Controlled {
+    // initialize a new Controlled struct
+    pub fn new(i: i32) -> Self {
+        Controlled {
+            // initialized with an empty command
+            current_command: Command::default(),
+            // needs to be an assigned a squad index
+            squad_pos: i,
+            // initialized with an empty command queue
+            command_queue: VecDeque::new(),
+            // initialized without an attack move
+            attack_move: None,
+        }
+    }
+
+    // queue up a command to be carried out after the ones already given
+    // returns false if the queue is already full
+    pub fn queue_command(&mut self, command: Command) -> bool {
+        if self.command_queue.len() >= ORDER_QUEUE_LENGTH {
+            return false;
+        }
+        self.command_queue.push_back(command);
+        true
+    }
+
+    // take back the most recently queued command, if there is one
+    pub fn undo_command(&mut self) -> Option<Command> {
+        self.command_queue.pop_back()
+    }
+
+    // throw away every queued command, leaving the current one alone
+    pub fn clear_queue(&mut self) {
+        self.command_queue.clear();
+    }
+}
+
+// struct that represents a command
+#[derive(Default)]
+pub struct Command {
+    // requires a command type
+    pub command_type: CommandType,
+    // target is the id of the target entity, if it exists
+    pub target_id: Option<String>,
+    // point is the target coordinate, if it exists
+    pub target_point: Option<(f32, f32)>,
+    // offset from the target entity to keep to when following, if in formation
+    pub formation_offset: Option<(f32, f32)>,
+}
+
+// labels are currently not under use
+/*
+// label struct
+// this struct is a convenient way of generating the necessary text components to label an entity
+pub struct Label;
+
+//implementation for the label struct
+impl Label {
+    // to generate a label, do Label::new(<name>, <font>, <color>, <font size>).0
+    // the '.0' accesses the inner TextComponents, which is what you really need
+    fn new(name: String, font: Handle<Font>, color: Color, font_size: f32) -> TextComponents {
+        TextComponents {
+            text: Text {
+                // note that the font should be a handle to a font asset loaded in by the asset server
+                font: font,
+                value: name,
+                style: TextStyle {
+                    color: color,
+                    font_size: font_size,
+                },
+            },
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    // hide text initially by sending it way out of bounds until
+                    // its position is updated
+                    top: Val::Px(-1000.0),
+                    left: Val::Px(-1000.0),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+}
+*/
+
+// helper struct for a simple rectangle sprite
+pub struct SimpleRect;
+
+impl SimpleRect {
+    // this function gives the necessary components for a rectanglular sprite
+    // takes a color handle, and a size vector and returns SpriteComponents
+    pub fn new(color_handle: Handle<ColorMaterial>, size: Vec2) -> SpriteComponents {
+        SpriteComponents {
+            material: color_handle,
+            // move sprite off screen
+            translation: Translation(Vec3::new(-1000.0, -1000.0, PLAYER_Z_LEVEL)),
+            sprite: Sprite {
+                size: size,
+            },
+            ..Default::default()
+        }
+    }
+}
+
+// implementation of the plugin trait,
+// required for this to be used as a plugin
+impl Plugin for PersonPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        // add in the add people startup system 
+        app
+        .add_startup_system(add_people.system());
+    }
+}
+
+// add people startup system
+// this function runs once at the initialization of the plugin to add in six people
+// when the mission is part of a campaign, the squad is made up of the campaign's roster instead
+pub fn add_people(mut commands: Commands, config: Res<GameConfig>, mut materials: ResMut<Assets<ColorMaterial>>, asset_server: Res<AssetServer>,
+    launch: Res<LaunchOptions>, archetypes: Res<Archetypes>) {
+    if let Some(path) = &launch.roster_path {
+        spawn_roster_squad(&config, &mut commands, &mut materials, &archetypes, &Roster::load(path));
+        return;
+    }
+
+    let green_handle = materials.add(Color::GREEN.into());
+    let blue_handle = materials.add(Color::BLUE.into());
+
+    for (index, &position) in SQUAD_POSITIONS.iter().enumerate() {
+        spawn_squad_member(&config, &mut commands, index as i32, position);
+        commands
+            .with(StartPoint::new(PLAYER_START))
+            .with(Medkits(STARTING_MEDKITS));
+        // the first squad member is the player character, the rest are squadmates
+        if index == 0 {
+            commands
+                .with_bundle(SimpleRect::new(green_handle, Vec2::new(10.0, 10.0)))
+                .with(get_player_sprite_template(&mut materials));
+        }else{
+            commands
+                .with_bundle(SimpleRect::new(blue_handle, Vec2::new(10.0, 10.0)))
+                .with(Behaviour::default())
+                .with(get_squadmate_sprite_template(&mut materials));
+        }
+        // the last one can get through locked doors without the key
+        if index == SQUAD_POSITIONS.len() - 1 {
+            commands.with(Lockpicker);
+        }
+    }
+}
+
+// spawns in a squad member with everything they need to move, fight and take orders, but nothing to draw them with
+// whatever else they need, e.g. a sprite, is added on after by the caller
+pub fn spawn_squad_member(config: &GameConfig, commands: &mut Commands, squad_pos: i32, position: (f32, f32)) {
+    commands
+        .spawn((Id::new(), Person::new(AttitudeType::Squad), Position(position.0, position.1), Velocity(0.0, 0.0)))
+        .with(Controlled::new(squad_pos))
+        .with(Stance::default())
+        .with(MoveSpeed::default())
+        .with(Stamina::default())
+        .with(Nerve::new())
+        .with(Size(10.0, 10.0))
+        // spawn along the physics body so that this entity collides with walls and others
+        .with_bundle(get_person_body_components(config, position, (10.0, 10.0), &AttitudeType::Squad))
+        .with(Pathfinder::default());
+}
+
+// roster member component
+// spawn this component along with squad members brought in from the campaign's roster
+// holds the name they're on the roster under, so how they did can be written back to it
+pub struct RosterMember {
+    pub name: String,
+}
+
+// spawns in the squad going out from the roster, in the same places as the usual squad, or at the map's player start
+// each brings their health, experience and kit with them
+pub fn spawn_roster_squad(config: &GameConfig, commands: &mut Commands, materials: &mut ResMut<Assets<ColorMaterial>>, archetypes: &Archetypes, roster: &Roster) {
+    let green_handle = materials.add(Color::GREEN.into());
+    let blue_handle = materials.add(Color::BLUE.into());
+
+    for (index, (mercenary, &position)) in roster.deployed().into_iter().zip(SQUAD_POSITIONS.iter()).enumerate() {
+        // experience makes for quicker attacks
+        let mut weapon = mercenary.weapon(archetypes);
+        let cut = (mercenary.experience as f32 * EXPERIENCE_COOLDOWN_CUT).min(MAX_EXPERIENCE_COOLDOWN_CUT);
+        weapon.cooldown *= 1.0 - cut;
+
+        spawn_squad_member(config, commands, index as i32, position);
+        commands
+            .with_bundle(SimpleRect::new(if index == 0 { green_handle } else { blue_handle }, Vec2::new(10.0, 10.0)))
+            .with(StartPoint::new(PLAYER_START))
+            .with(Medkits(STARTING_MEDKITS + mercenary.equipment.iter().filter(|item| *item == "medkit").count() as u32))
+            .with(weapon)
+            .with(Health {
+                current: mercenary.health,
+                max: MERCENARY_HEALTH,
+            })
+            .with(RosterMember { name: mercenary.name.clone() });
+        // the first squad member is the player character, the rest are squadmates
+        if index == 0 {
+            commands.with(get_player_sprite_template(materials));
+        }else{
+            commands
+                .with(Behaviour::default())
+                .with(get_squadmate_sprite_template(materials));
+        }
+        if mercenary.equipment.iter().any(|item| item == "lockpicks") {
+            commands.with(Lockpicker);
+        }
+        if let Some(secondary) = mercenary.secondary_weapon(archetypes) {
+            commands.with(Holstered(secondary));
+        }
+        if mercenary.equipment.iter().any(|item| item == "shield") {
+            commands.with(Shield::default());
+        }
+        if mercenary.equipment.iter().any(|item| item == "armor") {
+            commands.with(Resistances::armor());
+        }
+        let perks = Perks::from_equipment(&mercenary.equipment);
+        if !perks.0.is_empty() {
+            commands.with(perks);
+        }
+    }
+}
@@ -0,0 +1,334 @@
+// pool module
+// recycling short-lived entities such as projectiles and particles
+use bevy::prelude::*;
+use bevy::diagnostic::{Diagnostic, DiagnosticId, Diagnostics};
+use std::collections::HashMap;
+use bevy_rapier2d::physics::ColliderHandleComponent;
+use bevy_rapier2d::rapier::dynamics::RigidBodyBuilder;
+use bevy_rapier2d::rapier::geometry::{ColliderBuilder, ColliderSet, InteractionGroups};
+
+use crate::{Id, MELEE_RANGE, Position, PROJECTILE_MISS_ANGLE, PROJECTILE_SPEED, Size, Velocity};
+use crate::collision::{get_projectile_collision_groups, GROUP_PROJECTILE};
+use crate::combat::AttackEvent;
+use crate::config::GameConfig;
+use crate::person::{AttitudeType, Person, SimpleRect};
+use crate::tick::AddTickSystem;
+
+// pool plugin
+// responsible for recycling short-lived entities such as projectiles and particles
+// spawning and despawning these every frame fragments the ecs, so instead
+// a number set in the game config are spawned up front and handed out/taken back as needed
+// every ranged attack fires a pooled projectile from the attacker towards the target
+pub struct PoolPlugin;
+
+// implementation of the plugin trait,
+// required for this to be used as a plugin
+impl Plugin for PoolPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        // add in the entity pool resource
+        app.add_resource(EntityPool::default())
+        // initialise the pool request state resource
+        .init_resource::<PoolRequestState>()
+        .init_resource::<PoolAttackState>()
+        // add in the pool spawn request event
+        .add_tick_event::<PoolSpawnRequest>()
+        // fill up the pools at startup
+        .add_startup_system(fill_pools_system.system())
+        // register the reuse rate diagnostic
+        .add_startup_system(setup_pool_diagnostic_system.system())
+        // fire projectiles for ranged attacks
+        .add_tick_system(attack_projectile_system.system())
+        // add in the pool spawn system
+        .add_tick_system(pool_spawn_system.system())
+        // add in the pool recycle system
+        .add_tick_system(pool_recycle_system.system())
+        // add in the pool diagnostic system
+        .add_system(pool_diagnostic_system.system());
+    }
+}
+
+// diagnostic id for the pool reuse rate
+pub const POOL_REUSE_RATE: DiagnosticId = DiagnosticId::from_u128(0x6d65_7263_706f_6f6c_7265_7573_6501);
+
+// pool kind enum
+// each kind of pooled entity has its own pool
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PoolKind {
+    Projectile,
+    Particle,
+}
+
+impl PoolKind {
+    // gives the number of entities to spawn up front for this kind
+    pub fn pool_size(&self, config: &GameConfig) -> usize {
+        match self {
+            PoolKind::Projectile => config.projectile_pool_size,
+            PoolKind::Particle => config.particle_pool_size,
+        }
+    }
+    // gives the size of the sprite used for this kind
+    pub fn sprite_size(&self) -> Vec2 {
+        match self {
+            PoolKind::Projectile => Vec2::new(3.0, 3.0),
+            PoolKind::Particle => Vec2::new(2.0, 2.0),
+        }
+    }
+}
+
+// pooled component
+// spawn this component along with any entity that is owned by a pool
+pub struct Pooled {
+    // the pool this entity belongs to
+    pub kind: PoolKind,
+    // whether or not this entity is currently in use
+    pub active: bool,
+    // how long this entity stays in use before it is taken back
+    pub lifetime: Timer,
+    // what this entity is allowed to hit, set from whoever spawned it
+    pub groups: InteractionGroups,
+}
+
+// entity pool resource
+// holds the inactive entities for each pool kind along with
+// counters used to measure how often entities are reused
+#[derive(Default)]
+pub struct EntityPool {
+    // inactive entities, ready to be handed out
+    pub free: HashMap<PoolKind, Vec<Entity>>,
+    // number of requests made to the pool
+    pub acquired: usize,
+    // number of requests that were served by an inactive entity
+    pub reused: usize,
+    // material used for each pool kind, kept around for when a pool needs to grow
+    pub materials: HashMap<PoolKind, Handle<ColorMaterial>>,
+}
+
+impl EntityPool {
+    // takes an inactive entity out of the pool, if there is one
+    pub fn take(&mut self, kind: PoolKind) -> Option<Entity> {
+        self.acquired += 1;
+        let entity = self.free.get_mut(&kind).and_then(|free| free.pop());
+        if entity.is_some() {
+            self.reused += 1;
+        }
+        entity
+    }
+    // puts an entity back into the pool
+    pub fn give(&mut self, kind: PoolKind, entity: Entity) {
+        self.free.entry(kind).or_insert_with(Vec::new).push(entity);
+    }
+    // gives the fraction of requests that were served by reusing an entity
+    pub fn reuse_rate(&self) -> f64 {
+        if self.acquired == 0 {
+            return 1.0;
+        }
+        self.reused as f64 / self.acquired as f64
+    }
+}
+
+// pool spawn request event
+// send this event to get an entity out of a pool
+pub struct PoolSpawnRequest {
+    pub kind: PoolKind,
+    pub position: (f32, f32),
+    pub velocity: (f32, f32),
+    // lifetime in seconds
+    pub lifetime: f32,
+    // attitude of whoever spawned this, projectiles ignore their own faction
+    pub attitude: Option<AttitudeType>,
+}
+
+// gives the collision groups for a pooled entity spawned by someone with a given attitude
+pub fn get_pooled_collision_groups(kind: PoolKind, attitude: &Option<AttitudeType>) -> InteractionGroups {
+    match (kind, attitude) {
+        (PoolKind::Projectile, Some(attitude)) => get_projectile_collision_groups(attitude),
+        (PoolKind::Projectile, None) => InteractionGroups::new(GROUP_PROJECTILE, u16::MAX & !GROUP_PROJECTILE),
+        // particles are purely visual and never hit anything
+        (PoolKind::Particle, _) => InteractionGroups::none(),
+    }
+}
+
+// pool request state holds an event reader for pool spawn requests
+#[derive(Default)]
+pub struct PoolRequestState {
+    pub event_reader: EventReader<PoolSpawnRequest>,
+}
+
+// pool attack state holds an event reader for attack events
+#[derive(Default)]
+pub struct PoolAttackState {
+    pub event_reader: EventReader<AttackEvent>,
+}
+
+// gives the components for an inactive pooled entity, parked off screen
+pub fn get_pooled_components(kind: PoolKind, material: Handle<ColorMaterial>) -> (SpriteComponents, Pooled, Position, Velocity) {
+    (
+        SimpleRect::new(material, kind.sprite_size()),
+        Pooled {
+            kind: kind,
+            active: false,
+            lifetime: Timer::from_seconds(1.0, false),
+            groups: InteractionGroups::all(),
+        },
+        // park off screen until it is handed out
+        Position(-1000.0, -1000.0),
+        Velocity(0.0, 0.0),
+    )
+}
+
+// spawns in a pooled entity from its components
+// projectiles also get a body, with a sensor collider that only touches what the projectile can hit
+pub fn spawn_pooled(commands: &mut Commands, components: (SpriteComponents, Pooled, Position, Velocity)) -> Option<Entity> {
+    let (sprite, pooled, pos, vel) = components;
+    let kind = pooled.kind;
+    let groups = pooled.groups;
+    commands.spawn(sprite).with(pooled).with(pos).with(vel);
+    if kind == PoolKind::Projectile {
+        let size = kind.sprite_size();
+        commands
+            .with(Size(size[0], size[1]))
+            .with_bundle((
+                RigidBodyBuilder::new_kinematic().translation(-1000.0, -1000.0),
+                ColliderBuilder::cuboid(size[0] / 2.0, size[1] / 2.0)
+                    .sensor(true)
+                    .collision_groups(groups),
+            ));
+    }
+    commands.current_entity()
+}
+
+// fill pools system
+// spawns in every pooled entity at startup, as many of each kind as the game config says
+pub fn fill_pools_system(mut commands: Commands, config: Res<GameConfig>, mut pool: ResMut<EntityPool>, mut materials: ResMut<Assets<ColorMaterial>>) {
+    let projectile_handle = materials.add(Color::rgb(1.0, 0.9, 0.2).into());
+    let particle_handle = materials.add(Color::rgb(0.8, 0.8, 0.8).into());
+
+    for &(kind, material) in [(PoolKind::Projectile, projectile_handle), (PoolKind::Particle, particle_handle)].iter() {
+        pool.materials.insert(kind, material);
+        for _ in 0..kind.pool_size(&config) {
+            // every entity starts off inactive and in the pool
+            if let Some(entity) = spawn_pooled(&mut commands, get_pooled_components(kind, material)) {
+                pool.give(kind, entity);
+            }
+        }
+    }
+}
+
+// pool spawn system
+// hands out pooled entities in response to spawn requests
+// if a pool runs dry, a new entity is spawned and the pool grows
+pub fn pool_spawn_system(mut commands: Commands, mut pool: ResMut<EntityPool>,
+    mut state: ResMut<PoolRequestState>, requests: Res<Events<PoolSpawnRequest>>,
+    mut materials: ResMut<Assets<ColorMaterial>>, mut colliders: ResMut<ColliderSet>,
+    mut query: Query<(Entity, &mut Pooled, &mut Position, &mut Velocity)>, mut handles: Query<&ColliderHandleComponent>) {
+    for request in state.event_reader.iter(&requests) {
+        let groups = get_pooled_collision_groups(request.kind, &request.attitude);
+        match pool.take(request.kind) {
+            // an inactive entity was available
+            Some(entity) => {
+                if let Ok(mut pooled) = query.get_mut::<Pooled>(entity) {
+                    pooled.active = true;
+                    pooled.lifetime = Timer::from_seconds(request.lifetime, false);
+                    pooled.groups = groups;
+                }
+                // the collider carries over from whoever had the entity last, so it has to be told what it can hit now
+                if let Ok(handle) = handles.get::<ColliderHandleComponent>(entity) {
+                    if let Some(collider) = colliders.get_mut(handle.handle()) {
+                        collider.set_collision_groups(groups);
+                    }
+                }
+                if let Ok(mut pos) = query.get_mut::<Position>(entity) {
+                    pos.0 = request.position.0;
+                    pos.1 = request.position.1;
+                }
+                if let Ok(mut vel) = query.get_mut::<Velocity>(entity) {
+                    vel.0 = request.velocity.0;
+                    vel.1 = request.velocity.1;
+                }
+            },
+            // the pool ran dry, spawn a new entity instead
+            None => {
+                let material = match pool.materials.get(&request.kind) {
+                    Some(&material) => material,
+                    None => materials.add(Color::WHITE.into()),
+                };
+                let (sprite, mut pooled, _pos, _vel) = get_pooled_components(request.kind, material);
+                pooled.active = true;
+                pooled.lifetime = Timer::from_seconds(request.lifetime, false);
+                pooled.groups = groups;
+                let pos = Position(request.position.0, request.position.1);
+                let vel = Velocity(request.velocity.0, request.velocity.1);
+                spawn_pooled(&mut commands, (sprite, pooled, pos, vel));
+            }
+        }
+    }
+}
+
+// attack projectile system
+// fires a pooled projectile for every ranged attack, from the attacker towards the target
+// hits fly as far as the target, misses go wide and carry on past it
+pub fn attack_projectile_system(mut state: ResMut<PoolAttackState>, attacks: Res<Events<AttackEvent>>, mut requests: ResMut<Events<PoolSpawnRequest>>,
+    mut people: Query<(&Id, &Person)>) {
+    for attack in state.event_reader.iter(&attacks) {
+        let offset = Vec2::new(attack.target_position.0 - attack.origin.0, attack.target_position.1 - attack.origin.1);
+        let dist = offset.length();
+        // melee attacks don't fire anything
+        if dist <= MELEE_RANGE {
+            continue;
+        }
+        let mut attitude = None;
+        for (id, pers) in &mut people.iter() {
+            if id.id() == attack.attacker_id {
+                attitude = Some(pers.attitude);
+                break;
+            }
+        }
+        let mut direction = offset / dist;
+        let mut range = dist;
+        if !attack.hit {
+            let (sin, cos) = PROJECTILE_MISS_ANGLE.sin_cos();
+            direction = Vec2::new(direction[0] * cos - direction[1] * sin, direction[0] * sin + direction[1] * cos);
+            range *= 2.0;
+        }
+        requests.send(PoolSpawnRequest {
+            kind: PoolKind::Projectile,
+            position: attack.origin,
+            velocity: (direction[0] * PROJECTILE_SPEED, direction[1] * PROJECTILE_SPEED),
+            lifetime: range / PROJECTILE_SPEED,
+            attitude: attitude,
+        });
+    }
+}
+
+// pool recycle system
+// takes back pooled entities once their lifetime runs out
+pub fn pool_recycle_system(time: Res<Time>, mut pool: ResMut<EntityPool>, mut query: Query<(Entity, &mut Pooled, &mut Position, &mut Velocity)>) {
+    for (entity, mut pooled, mut pos, mut vel) in &mut query.iter() {
+        if !pooled.active {
+            continue;
+        }
+        pooled.lifetime.tick(time.delta_seconds);
+        if pooled.lifetime.finished {
+            // deactivate and park off screen
+            pooled.active = false;
+            pos.0 = -1000.0;
+            pos.1 = -1000.0;
+            vel.0 = 0.0;
+            vel.1 = 0.0;
+            let kind = pooled.kind;
+            pool.give(kind, entity);
+        }
+    }
+}
+
+// setup pool diagnostic system
+// registers the reuse rate diagnostic so it can be read alongside the fps
+pub fn setup_pool_diagnostic_system(mut diagnostics: ResMut<Diagnostics>) {
+    diagnostics.add(Diagnostic::new(POOL_REUSE_RATE, "pool_reuse_rate", 20));
+}
+
+// pool diagnostic system
+// records how often pool requests are served by reusing an entity
+pub fn pool_diagnostic_system(pool: Res<EntityPool>, mut diagnostics: ResMut<Diagnostics>) {
+    diagnostics.add_measurement(POOL_REUSE_RATE, pool.reuse_rate());
+}
@@ -0,0 +1,119 @@
+// routine module
+// daily routines for hostiles and civilians
+use bevy::prelude::*;
+
+use crate::{Position, REST_DISTANCE};
+use crate::behaviour::{Behaviour, BehaviourSet, Pathfinder, PatrolRoute, TilePos};
+use crate::config::GameConfig;
+use crate::lighting::WorldClock;
+use crate::nerve::Nerve;
+use crate::tick::AddTickSystem;
+
+// routine plugin
+// responsible for daily routines, which have hostiles and civilians doing different things
+// at different times of day, so the layout of a camp depends on when it's scouted
+pub struct RoutinePlugin;
+
+// implementation of the plugin trait,
+// required for this to be used as a plugin
+impl Plugin for RoutinePlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        // add in the routine system
+        app.add_tick_system(routine_system.system());
+    }
+}
+
+// routine activity enum
+// what someone on a routine does for part of the day
+#[derive(Debug, Clone, PartialEq)]
+pub enum RoutineActivity {
+    // wander around aimlessly, as anyone without a routine does
+    Wander,
+    // walk the patrol route with the given name
+    Patrol(String),
+    // go to the given position and stay there
+    Rest((f32, f32)),
+}
+
+// routine component
+// spawn this component along with anyone who should keep to a daily routine
+pub struct Routine {
+    // what to do during the day
+    pub day: RoutineActivity,
+    // what to do at night
+    pub night: RoutineActivity,
+    // whether it was daytime the last time the routine was checked, none until it's first checked
+    pub daytime: Option<bool>,
+}
+
+impl Routine {
+    pub fn new(day: RoutineActivity, night: RoutineActivity) -> Self {
+        Routine {
+            day,
+            night,
+            daytime: None,
+        }
+    }
+}
+
+// resting component
+// spawn this component along with anyone staying put as part of their routine
+pub struct Resting;
+
+// routine system
+// switches people between their daytime and night time activities as the clock moves on,
+// and sends anyone resting back to where they rest once they've nothing else to do
+pub fn routine_system(mut commands: Commands, config: Res<GameConfig>, clock: Res<WorldClock>,
+    mut query: Query<(Entity, &mut Routine, &Behaviour, &mut Nerve, &mut Pathfinder, &Position, Option<&PatrolRoute>, Option<&Resting>)>) {
+    let daytime = clock.is_daytime();
+    for (entity, mut routine, behav, mut actions, mut pf, pos, route, resting) in &mut query.iter() {
+        let at_rest = behav.current_behaviour_set == BehaviourSet::AtRest || behav.current_behaviour_set == BehaviourSet::Empty;
+        // people in the middle of a fight or reacting to something get back to their routine afterwards
+        if !at_rest || behav.current_behaviour.is_reaction() {
+            continue;
+        }
+
+        let activity = if daytime { routine.day.clone() } else { routine.night.clone() };
+        if routine.daytime != Some(daytime) {
+            routine.daytime = Some(daytime);
+            // drop whatever was left of the last activity
+            actions.action_queue.clear();
+            match &activity {
+                RoutineActivity::Patrol(name) => {
+                    commands.insert_one(entity, PatrolRoute { name: name.clone() });
+                    if resting.is_some() {
+                        commands.remove_one::<Resting>(entity);
+                    }
+                },
+                RoutineActivity::Rest(_) => {
+                    commands.insert_one(entity, Resting);
+                    if route.is_some() {
+                        commands.remove_one::<PatrolRoute>(entity);
+                    }
+                },
+                RoutineActivity::Wander => {
+                    if resting.is_some() {
+                        commands.remove_one::<Resting>(entity);
+                    }
+                    if route.is_some() {
+                        commands.remove_one::<PatrolRoute>(entity);
+                    }
+                },
+            }
+        }
+
+        // head back to the resting spot whenever there's nothing else to do
+        if let RoutineActivity::Rest(spot) = activity {
+            let idle = !pf.needs_pathfinding
+                && pf.path_index >= pf.tile_path.len()
+                && actions.is_curr_action_empty()
+                && actions.action_queue.is_empty();
+            let away = ((spot.0 - pos.0).powi(2) + (spot.1 - pos.1).powi(2)).sqrt() > REST_DISTANCE;
+            if idle && away {
+                pf.needs_pathfinding = true;
+                pf.path_goal = TilePos::from_coords(&config, spot.0, spot.1);
+                pf.real_goal = spot;
+            }
+        }
+    }
+}
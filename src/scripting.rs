@@ -0,0 +1,324 @@
+// scripting module
+// rhai scripts for behaviours and encounter triggers
+use bevy::prelude::*;
+use std::collections::HashMap;
+use std::fs;
+use rhai::{Array, Dynamic, Engine, EvalAltResult, Scope, AST};
+use std::sync::{Arc, Mutex};
+
+use crate::{
+    Id, MODS_PATH, Position, SCRIPT_MAX_ARRAY_SIZE, SCRIPT_MAX_CALL_LEVELS, SCRIPT_MAX_COMMANDS,
+    SCRIPT_MAX_EXPR_DEPTH, SCRIPT_MAX_FUNCTION_EXPR_DEPTH, SCRIPT_MAX_MAP_SIZE, SCRIPT_MAX_OPERATIONS,
+    SCRIPT_MAX_STRING_SIZE, SCRIPTS_PATH,
+};
+use crate::config::GameConfig;
+use crate::encounter::{Archetypes, Difficulty, spawn_hostile};
+use crate::mods::ModAssets;
+use crate::nerve::{Action, ActionType, Nerve};
+use crate::person::Person;
+use crate::tick::AddTickSystem;
+use crate::zone::{Zone, ZoneEvent};
+
+// scripting plugin
+// responsible for running rhai scripts loaded from assets/scripts
+// scripts can define hostile behaviours, encounter triggers and mission logic
+// by providing any of these functions:
+//   on_start()                     - called once when the game starts
+//   on_tick(id, x, y)              - called every tick for each entity running the script
+//   on_zone_enter(zone, person_id) - called when a squad member enters a zone
+//   on_zone_exit(zone, person_id)  - called when a squad member leaves a zone
+// scripts can't touch the world directly, instead they have access to:
+//   push_action(id, "move", x, y)  - queue up a move action for an entity
+//   push_attack(id, target_id)     - queue up an attack action for an entity
+//   query_nearby(x, y, radius)     - gives the ids of the people within the radius
+//   spawn_entity("hostile", x, y)  - spawn in a new entity, which runs the calling script
+//                                    an archetype name, e.g. "sniper", can be given instead of "hostile"
+// scripts are limited in how long they can run and how much they can queue up, see the settings for scripts
+// assets/scripts has an example behaviour and an example encounter trigger
+pub struct ScriptingPlugin;
+
+// implementation of the plugin trait,
+// required for this to be used as a plugin
+impl Plugin for ScriptingPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        // add in the script engine, with every script loaded in
+        // mods can add scripts of their own, or replace the base ones
+        app.add_resource(ScriptEngine::load(ModAssets::load(MODS_PATH).resolve_dirs(SCRIPTS_PATH)))
+        // initialise the zone event state for scripts
+        .init_resource::<ScriptZoneEventState>()
+        // run the start hooks
+        .add_startup_system(script_start_system.system())
+        // run the per-entity tick hooks
+        .add_tick_system(script_tick_system.system())
+        // run the zone hooks
+        .add_tick_system(script_zone_system.system())
+        // carry out everything the scripts asked for
+        .add_tick_system(apply_script_commands_system.system());
+    }
+}
+
+// scripted component
+// spawn this component along with any entity whose behaviour comes from a script
+pub struct Scripted {
+    // name of the script, the file name without the extension
+    pub script: String,
+}
+
+// script command enum
+// everything a script can ask the game to do
+// scripts queue these up, and they are carried out after the scripts have run
+pub enum ScriptCommand {
+    PushAction { id: String, action: Action },
+    SpawnEntity { kind: String, position: (f32, f32), script: String },
+}
+
+// a person as seen by scripts
+#[derive(Clone)]
+pub struct ScriptPerson {
+    pub id: String,
+    pub position: (f32, f32),
+}
+
+// script engine resource
+// holds the rhai engine along with every loaded script
+pub struct ScriptEngine {
+    pub engine: Engine,
+    // loaded scripts, by name
+    pub scripts: Vec<(String, AST)>,
+    // commands queued up by scripts, tagged with the name of the script
+    pub commands: Arc<Mutex<Vec<(String, ScriptCommand)>>>,
+    // how many commands have been dropped this tick, for going over the limit
+    pub dropped: Arc<Mutex<usize>>,
+    // name of the script currently running
+    pub current: Arc<Mutex<String>>,
+    // every person in the world, refreshed before scripts run
+    pub people: Arc<Mutex<Vec<ScriptPerson>>>,
+}
+
+impl ScriptEngine {
+    // creates the engine with the script api registered, and loads
+    // every .rhai file in the given directories
+    // scripts in later directories replace scripts of the same name in earlier ones
+    pub fn load(paths: Vec<String>) -> Self {
+        let mut engine = Engine::new();
+        engine.set_max_operations(SCRIPT_MAX_OPERATIONS)
+            .set_max_call_levels(SCRIPT_MAX_CALL_LEVELS)
+            .set_max_expr_depths(SCRIPT_MAX_EXPR_DEPTH, SCRIPT_MAX_FUNCTION_EXPR_DEPTH)
+            .set_max_string_size(SCRIPT_MAX_STRING_SIZE)
+            .set_max_array_size(SCRIPT_MAX_ARRAY_SIZE)
+            .set_max_map_size(SCRIPT_MAX_MAP_SIZE);
+        let commands: Arc<Mutex<Vec<(String, ScriptCommand)>>> = Arc::new(Mutex::new(Vec::new()));
+        let dropped = Arc::new(Mutex::new(0));
+        let current = Arc::new(Mutex::new(String::new()));
+        let people: Arc<Mutex<Vec<ScriptPerson>>> = Arc::new(Mutex::new(Vec::new()));
+
+        // push_action(id, "move", x, y)
+        let (queue, script, over) = (commands.clone(), current.clone(), dropped.clone());
+        engine.register_fn("push_action", move |id: &str, action_type: &str, x: f64, y: f64| {
+            let action_type = match action_type {
+                "move" => ActionType::Move,
+                "wait" => ActionType::Wait,
+                // anything else is ignored
+                _ => return,
+            };
+            let mut params = HashMap::new();
+            params.insert("range".to_string(), 0.0);
+            if let ActionType::Wait = action_type {
+                // for wait actions, x is the duration
+                params.insert("duration".to_string(), x as f32);
+            }
+            let action = Action {
+                action_type: action_type,
+                target: (Some((x as f32, y as f32)), None),
+                params: Some(params),
+                group: None,
+            };
+            queue_command(&queue, &over, script.lock().unwrap().clone(), ScriptCommand::PushAction { id: id.to_string(), action: action });
+        });
+
+        // push_attack(id, target_id)
+        let (queue, script, over) = (commands.clone(), current.clone(), dropped.clone());
+        engine.register_fn("push_attack", move |id: &str, target_id: &str| {
+            let mut params = HashMap::new();
+            params.insert("range".to_string(), 40.0);
+            params.insert("min_range".to_string(), 20.0);
+            let action = Action {
+                action_type: ActionType::Attack,
+                target: (None, Some(target_id.to_string())),
+                params: Some(params),
+                group: None,
+            };
+            queue_command(&queue, &over, script.lock().unwrap().clone(), ScriptCommand::PushAction { id: id.to_string(), action: action });
+        });
+
+        // query_nearby(x, y, radius)
+        let nearby = people.clone();
+        engine.register_fn("query_nearby", move |x: f64, y: f64, radius: f64| -> Array {
+            nearby.lock().unwrap().iter()
+                .filter(|p| Vec2::new(p.position.0 - x as f32, p.position.1 - y as f32).length() <= radius as f32)
+                .map(|p| Dynamic::from(p.id.clone()))
+                .collect()
+        });
+
+        // spawn_entity("hostile", x, y)
+        let (queue, script, over) = (commands.clone(), current.clone(), dropped.clone());
+        engine.register_fn("spawn_entity", move |kind: &str, x: f64, y: f64| {
+            let name = script.lock().unwrap().clone();
+            queue_command(&queue, &over, name.clone(), ScriptCommand::SpawnEntity { kind: kind.to_string(), position: (x as f32, y as f32), script: name });
+        });
+
+        // load in every script
+        let mut scripts: Vec<(String, AST)> = Vec::new();
+        for path in paths.iter() {
+            if let Ok(entries) = fs::read_dir(path) {
+                for entry in entries.filter_map(|e| e.ok()) {
+                    let file = entry.path();
+                    if file.extension().map_or(false, |ext| ext == "rhai") {
+                        let name = file.file_stem().unwrap().to_string_lossy().to_string();
+                        match engine.compile_file(file.clone()) {
+                            Ok(ast) => {
+                                // replace any earlier script with the same name
+                                scripts.retain(|(other, _)| *other != name);
+                                scripts.push((name, ast));
+                            },
+                            // a broken script shouldn't take the game down with it
+                            Err(err) => eprintln!("failed to compile script {:?}: {}", file, err),
+                        }
+                    }
+                }
+            }
+        }
+
+        ScriptEngine {
+            engine: engine,
+            scripts: scripts,
+            commands: commands,
+            dropped: dropped,
+            current: current,
+            people: people,
+        }
+    }
+
+    // calls a function in the named script, if the script defines it
+    pub fn call(&self, script: &str, function: &str, args: impl rhai::FuncArgs) {
+        if let Some((name, ast)) = self.scripts.iter().find(|(name, _)| name == script) {
+            *self.current.lock().unwrap() = name.clone();
+            let result: Result<Dynamic, Box<EvalAltResult>> = self.engine.call_fn(&mut Scope::new(), ast, function, args);
+            if let Err(err) = result {
+                // scripts don't have to define every hook
+                if !matches!(*err, EvalAltResult::ErrorFunctionNotFound(..)) {
+                    eprintln!("error in script {}: {}", name, err);
+                }
+            }
+        }
+    }
+
+    // gives the names of every loaded script
+    pub fn script_names(&self) -> Vec<String> {
+        self.scripts.iter().map(|(name, _)| name.clone()).collect()
+    }
+
+    // refreshes the people visible to scripts
+    pub fn set_people(&self, people: Vec<ScriptPerson>) {
+        *self.people.lock().unwrap() = people;
+    }
+}
+
+// queues up a command from a script, unless the scripts have already queued up as many as they can this tick
+pub fn queue_command(queue: &Mutex<Vec<(String, ScriptCommand)>>, dropped: &Mutex<usize>, script: String, command: ScriptCommand) {
+    let mut queue = queue.lock().unwrap();
+    if queue.len() >= SCRIPT_MAX_COMMANDS {
+        *dropped.lock().unwrap() += 1;
+        return;
+    }
+    queue.push((script, command));
+}
+
+// script zone event state holds an event reader for zone events
+#[derive(Default)]
+pub struct ScriptZoneEventState {
+    pub event_reader: EventReader<ZoneEvent>,
+}
+
+// script start system
+// runs the start hook of every script
+pub fn script_start_system(scripts: Res<ScriptEngine>) {
+    for name in scripts.script_names() {
+        scripts.call(&name, "on_start", ());
+    }
+}
+
+// script tick system
+// runs the tick hook for every scripted entity
+pub fn script_tick_system(scripts: Res<ScriptEngine>, mut people: Query<(&Id, &Person, &Position)>, mut query: Query<(&Scripted, &Id, &Position)>) {
+    if scripts.scripts.is_empty() {
+        return;
+    }
+    // let scripts see where everyone is
+    let mut snapshot = Vec::new();
+    for (id, _pers, pos) in &mut people.iter() {
+        snapshot.push(ScriptPerson { id: id.id(), position: (pos.0, pos.1) });
+    }
+    scripts.set_people(snapshot);
+
+    for (scripted, id, pos) in &mut query.iter() {
+        scripts.call(&scripted.script, "on_tick", (id.id(), pos.0 as f64, pos.1 as f64));
+    }
+}
+
+// script zone system
+// runs the zone hooks of every script whenever a squad member crosses a zone
+pub fn script_zone_system(scripts: Res<ScriptEngine>, mut state: ResMut<ScriptZoneEventState>, events: Res<Events<ZoneEvent>>,
+    zones: Query<&Zone>, ids: Query<&Id>) {
+    for event in state.event_reader.iter(&events) {
+        let zone = match zones.get::<Zone>(event.zone) {
+            Ok(zone) => zone.name.clone(),
+            Err(_) => continue,
+        };
+        let person = match ids.get::<Id>(event.person) {
+            Ok(id) => id.id(),
+            Err(_) => continue,
+        };
+        let hook = if event.entered { "on_zone_enter" } else { "on_zone_exit" };
+        for name in scripts.script_names() {
+            scripts.call(&name, hook, (zone.clone(), person.clone()));
+        }
+    }
+}
+
+// apply script commands system
+// carries out every command queued up by scripts
+pub fn apply_script_commands_system(mut commands: Commands, config: Res<GameConfig>, scripts: Res<ScriptEngine>, archetypes: Res<Archetypes>, difficulty: Res<Difficulty>, mut materials: ResMut<Assets<ColorMaterial>>, mut query: Query<(&Id, &mut Nerve)>) {
+    let queued: Vec<(String, ScriptCommand)> = scripts.commands.lock().unwrap().drain(..).collect();
+    let mut dropped = scripts.dropped.lock().unwrap();
+    if *dropped > 0 {
+        eprintln!("scripts queued up more than {} commands this tick, {} were dropped", SCRIPT_MAX_COMMANDS, *dropped);
+        *dropped = 0;
+    }
+    for (_script, command) in queued {
+        match command {
+            ScriptCommand::PushAction { id, action } => {
+                for (eid, mut actions) in &mut query.iter() {
+                    if eid.id() == id {
+                        actions.action_queue.push_back(action);
+                        break;
+                    }
+                }
+            },
+            ScriptCommand::SpawnEntity { kind, position, script } => {
+                match kind.as_str() {
+                    "hostile" => {
+                        spawn_hostile(&config, &mut commands, &mut materials, &difficulty.adjust(archetypes.get("grunt")), position);
+                        // the new hostile runs the script that spawned it
+                        commands.with(Scripted { script: script });
+                    },
+                    name if archetypes.0.contains_key(name) => {
+                        spawn_hostile(&config, &mut commands, &mut materials, &difficulty.adjust(archetypes.get(name)), position);
+                        commands.with(Scripted { script: script });
+                    },
+                    _ => eprintln!("script {} tried to spawn unknown entity {}", script, kind),
+                }
+            },
+        }
+    }
+}
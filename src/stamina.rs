@@ -0,0 +1,124 @@
+// stamina module
+// stamina, for sprinting and melee attacks
+use bevy::prelude::*;
+
+use crate::{
+    EXHAUSTED_ACCURACY, Id, MELEE_RANGE, STAMINA_MAX, STAMINA_MELEE_COST, STAMINA_RECOVERED,
+    STAMINA_REGEN, STAMINA_SPRINT_DRAIN, Velocity,
+};
+use crate::behaviour::{Behaviour, BehaviourType};
+use crate::combat::AttackEvent;
+use crate::nerve::Nerve;
+use crate::person::Controlled;
+use crate::tick::AddTickSystem;
+
+// stamina plugin
+// responsible for stamina, which sprinting and melee attacks wear down and standing still brings back
+// hostiles sprint while charging or fleeing, as long as they have the stamina for it
+pub struct StaminaPlugin;
+
+// implementation of the plugin trait,
+// required for this to be used as a plugin
+impl Plugin for StaminaPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        // initialise the attack event state resource
+        app.init_resource::<StaminaAttackState>()
+        // add in the stamina system
+        .add_tick_system(stamina_system.system())
+        // add in the melee stamina system
+        .add_tick_system(melee_stamina_system.system())
+        // add in the hostile sprint system
+        .add_tick_system(hostile_sprint_system.system());
+    }
+}
+
+// stamina component
+// spawn this component along with people who can sprint and tire out
+pub struct Stamina {
+    pub current: f32,
+    pub max: f32,
+    // whether moves are carried out at a sprint
+    pub sprinting: bool,
+    // whether stamina has run out, and not yet come back far enough
+    pub exhausted: bool,
+}
+
+impl Default for Stamina {
+    fn default() -> Self {
+        Stamina {
+            current: STAMINA_MAX,
+            max: STAMINA_MAX,
+            sprinting: false,
+            exhausted: false,
+        }
+    }
+}
+
+impl Stamina {
+    // takes stamina away, leaving the person exhausted if it runs out
+    pub fn drain(&mut self, amount: f32) {
+        self.current = (self.current - amount).max(0.0);
+        if self.current <= 0.0 {
+            self.exhausted = true;
+        }
+    }
+    // gives stamina back, ending exhaustion once enough of it has come back
+    pub fn regenerate(&mut self, amount: f32) {
+        self.current = (self.current + amount).min(self.max);
+        if self.current >= self.max * STAMINA_RECOVERED {
+            self.exhausted = false;
+        }
+    }
+    // gives the multiplier on the accuracy of attacks
+    pub fn accuracy_multiplier(&self) -> f32 {
+        if self.exhausted { EXHAUSTED_ACCURACY } else { 1.0 }
+    }
+}
+
+// stamina attack state holds an event reader for attack events
+#[derive(Default)]
+pub struct StaminaAttackState {
+    pub event_reader: EventReader<AttackEvent>,
+}
+
+// stamina system
+// wears stamina down while sprinting, and brings it back while standing still with nothing to do
+pub fn stamina_system(time: Res<Time>, mut query: Query<(&mut Stamina, &Velocity, &Nerve)>) {
+    for (mut stamina, vel, actions) in &mut query.iter() {
+        let moving = Vec2::new(vel.0, vel.1).length() >= 1.0;
+        if moving && stamina.sprinting && !stamina.exhausted {
+            stamina.drain(STAMINA_SPRINT_DRAIN * time.delta_seconds);
+        }else if !moving && actions.is_curr_action_empty() {
+            stamina.regenerate(STAMINA_REGEN * time.delta_seconds);
+        }
+    }
+}
+
+// melee stamina system
+// wears down the stamina of everyone launching an attack from up close
+pub fn melee_stamina_system(mut state: ResMut<StaminaAttackState>, attacks: Res<Events<AttackEvent>>, mut query: Query<(&Id, &mut Stamina)>) {
+    for attack in state.event_reader.iter(&attacks) {
+        let offset = Vec2::new(attack.target_position.0 - attack.origin.0, attack.target_position.1 - attack.origin.1);
+        if offset.length() > MELEE_RANGE {
+            continue;
+        }
+        for (id, mut stamina) in &mut query.iter() {
+            if id.id() != attack.attacker_id {
+                continue;
+            }
+            stamina.drain(STAMINA_MELEE_COST);
+            break;
+        }
+    }
+}
+
+// hostile sprint system
+// has hostiles sprint while charging or fleeing
+pub fn hostile_sprint_system(mut query: Query<Without<Controlled, (&Behaviour, &mut Stamina)>>) {
+    for (behav, mut stamina) in &mut query.iter() {
+        stamina.sprinting = match behav.current_behaviour {
+            BehaviourType::Charge | BehaviourType::Flee => true,
+            _ => false,
+        };
+    }
+}
@@ -0,0 +1,446 @@
+// stance module
+// stances, sneaking and the priority target, which decide when the squad fights without being ordered to
+use bevy::prelude::*;
+use std::collections::HashMap;
+use bevy_rapier2d::rapier::dynamics::RigidBodySet;
+use bevy_rapier2d::rapier::geometry::ColliderSet;
+use bevy_rapier2d::rapier::pipeline::QueryPipeline;
+
+use crate::{BEHAVIOUR_STAGE, combat_query, COMMAND_STAGE, Id, PERCEPTION_RADIUS, Position, Size};
+use crate::civilian::MissionRecord;
+use crate::combat::AttackEvent;
+use crate::config::GameConfig;
+use crate::control::{check_point_collision, get_attack_actions, InputState, KeyBindings, Selection, Weapon};
+use crate::encounter::Ambusher;
+use crate::locale::Locale;
+use crate::mods::ModAssets;
+use crate::nerve::{Action, ActionType, Nerve};
+use crate::person::{AttitudeType, Controlled, Person, SimpleRect};
+use crate::tick::AddTickSystem;
+
+// stance plugin
+// responsible for deciding when squad members fight without being ordered to
+// attacks the player orders are always carried out, stances only filter the attacks
+// squad members would start on their own
+pub struct StancePlugin;
+
+// implementation of the plugin trait,
+// required for this to be used as a plugin
+impl Plugin for StancePlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        // initialise the event reader for attacks on the squad
+        app.init_resource::<StanceAttackState>()
+        // initialise the focus target resource
+        .init_resource::<FocusTarget>()
+        // add in the system for marking the priority target
+        .add_system(focus_mark_system.system())
+        // add in the system keeping the mark on the priority target
+        .add_system(focus_marker_system.system())
+        // add in the squad hud
+        .add_startup_system(setup_squad_hud_system.system())
+        // add in the stance hotkey system
+        .add_system_to_stage(COMMAND_STAGE, stance_hotkey_system.system())
+        // add in the sneak hotkey system
+        .add_system_to_stage(COMMAND_STAGE, sneak_hotkey_system.system())
+        // add in the system for starting fights
+        // attack events are kept for a tick, so none made the tick before are missed
+        .add_tick_system_to_stage(BEHAVIOUR_STAGE, stance_engage_system.system())
+        // add in the system for keeping fights within the stance's limits
+        .add_tick_system_to_stage(BEHAVIOUR_STAGE, stance_leash_system.system())
+        // add in the system for showing stances in the hud
+        .add_system(squad_hud_system.system());
+    }
+}
+
+// settings for stances
+// the key that cycles the stance of the selected squad members
+pub static STANCE_KEY: KeyCode = KeyCode::V;
+// how far defensive squad members will stray from where they were when they started fighting back
+pub static DEFENSIVE_LEASH: f32 = 100.0;
+// hold the focus key and click on a hostile to mark it as the squad's priority target,
+// or click on nothing to clear the mark
+pub static FOCUS_KEY: KeyCode = KeyCode::T;
+
+// stance kind enum
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StanceKind {
+    // attacks any hostile it can see
+    Aggressive,
+    // only fights back against hostiles attacking it, and won't chase them far
+    Defensive,
+    // never attacks unless ordered to
+    HoldFire,
+}
+
+impl StanceKind {
+    // key of the short name shown in the squad hud, in the language files
+    pub fn name(&self) -> &'static str {
+        match self {
+            StanceKind::Aggressive => "stance_aggressive",
+            StanceKind::Defensive => "stance_defensive",
+            StanceKind::HoldFire => "stance_hold_fire",
+        }
+    }
+}
+
+// stance component
+// spawn this component along with any controlled entity
+pub struct Stance {
+    pub kind: StanceKind,
+}
+
+impl Default for Stance {
+    fn default() -> Self {
+        Stance {
+            kind: StanceKind::Defensive,
+        }
+    }
+}
+
+// squad hud component
+// spawn this component along with the text showing the squad's stances
+pub struct SquadHud;
+
+// stance attack state holds an event reader for attack events
+#[derive(Default)]
+pub struct StanceAttackState {
+    pub event_reader: EventReader<AttackEvent>,
+}
+
+// checks if a stance lets a squad member start an attack on its own
+// provoked is whether the target attacked first, dist is how far away the target is
+pub fn stance_allows_engagement(stance: StanceKind, provoked: bool, dist: f32) -> bool {
+    match stance {
+        StanceKind::Aggressive => true,
+        StanceKind::Defensive => provoked && dist <= DEFENSIVE_LEASH,
+        StanceKind::HoldFire => false,
+    }
+}
+
+// checks if an action is part of an attack the squad member started on its own
+pub fn is_auto_attack(action: &Action) -> bool {
+    action.params.as_ref().map_or(false, |params| params.contains_key("auto"))
+}
+
+// setup squad hud system
+// spawns in the text showing the stance of each squad member
+pub fn setup_squad_hud_system(mut commands: Commands, asset_server: Res<AssetServer>, mods: Res<ModAssets>) {
+    let font_handle = asset_server.load(mods.resolve("assets/fonts/LiberationMono-Regular.ttf")).unwrap();
+
+    commands
+        .spawn(TextComponents {
+            style: Style {
+                // keep the hud in the bottom left corner
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    left: Val::Px(5.0),
+                    bottom: Val::Px(5.0),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            text: Text {
+                value: "".to_string(),
+                font: font_handle,
+                style: TextStyle {
+                    font_size: 16.0,
+                    color: Color::BLACK,
+                },
+            },
+            ..Default::default()
+        })
+        .with(SquadHud);
+}
+
+// squad hud system
+// shows each squad member's stance next to its squad index, and who is selected
+pub fn squad_hud_system(selection: Res<Selection>, bindings: Res<KeyBindings>, record: Res<MissionRecord>, locale: Res<Locale>, mut squad: Query<(Entity, &Controlled, &Stance, Option<&Sneaking>)>, mut query: Query<(&SquadHud, &mut Text)>) {
+    let mut entries = Vec::new();
+    for (entity, state, stance, sneaking) in &mut squad.iter() {
+        // sneaking squad members have their stance shown in lowercase
+        let name = locale.get(stance.kind.name());
+        let name = if sneaking.is_some() { name.to_lowercase() } else { name };
+        // queued orders are counted after the stance
+        let queued = state.command_queue.len();
+        let name = if queued > 0 { format!("{}+{}", name, queued) } else { name };
+        entries.push((state.squad_pos, name, selection.is_selected(entity, state.squad_pos), queued));
+    }
+    entries.sort_by_key(|(squad_pos, _, _, _)| *squad_pos);
+
+    // selected squad members are shown in brackets
+    let mut value = entries.iter()
+        .map(|(squad_pos, name, selected, _)| if *selected {
+            format!("[{}:{}]", squad_pos, name)
+        }else{
+            format!("{}:{}", squad_pos, name)
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+    // offer to clear the queued orders of the selected squad members, if they have any
+    if entries.iter().any(|(_, _, selected, queued)| *selected && *queued > 0) {
+        value.push_str(&format!("  {}", locale.format("clear_queue", &[&format!("{:?}", bindings.clear_queue)])));
+    }
+    // remind the player of any civilians they've killed
+    if record.civilian_deaths > 0 {
+        value.push_str(&format!("  {}", locale.format("civilians_lost", &[&record.civilian_deaths])));
+    }
+
+    for (_hud, mut text) in &mut query.iter() {
+        text.value = value.clone();
+    }
+}
+
+// focus target resource
+// the hostile marked as the squad's priority target, if there is one
+#[derive(Default)]
+pub struct FocusTarget {
+    // id of the marked hostile
+    pub target: Option<String>,
+    // the marker drawn over the marked hostile
+    pub marker: Option<Entity>,
+}
+
+// focus marker component
+// spawn this component along with the marker drawn over the priority target
+pub struct FocusMarker;
+
+// picks who to attack out of the hostiles that can be attacked, given with their distance
+// the priority target is always picked if it's there, otherwise the closest one is
+pub fn pick_target(candidates: &[(String, f32)], focus: &Option<String>) -> Option<String> {
+    if let Some(focus) = focus {
+        if candidates.iter().any(|(id, _)| id == focus) {
+            return Some(focus.clone());
+        }
+    }
+    candidates.iter()
+        .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))
+        .map(|(id, _)| id.clone())
+}
+
+// focus mark system
+// marks the hostile clicked on while holding the focus key as the priority target
+// clicking on anything else clears the mark
+pub fn focus_mark_system(mut commands: Commands, inputs: Res<InputState>, bindings: Res<KeyBindings>, mut focus: ResMut<FocusTarget>,
+    mut materials: ResMut<Assets<ColorMaterial>>, mut persons: Query<(&Id, &Person, &Position, &Size)>) {
+    if !inputs.mouse_just_presses.contains(&MouseButton::Left) || !inputs.key_presses.contains(&bindings.focus) {
+        return;
+    }
+
+    // find the hostile clicked on, if any
+    let mut clicked = None;
+    for (id, person, pos, size) in &mut persons.iter() {
+        if person.attitude == AttitudeType::Hostile && check_point_collision(inputs.mouse_position, (pos.0, pos.1), (size.0, size.1)) {
+            clicked = Some((id.id(), (pos.0, pos.1)));
+            break;
+        }
+    }
+
+    // take down the old marker
+    if let Some(marker) = focus.marker.take() {
+        commands.despawn(marker);
+    }
+    focus.target = None;
+
+    if let Some((id, pos)) = clicked {
+        let marker_handle = materials.add(Color::RED.into());
+        commands
+            .spawn(SimpleRect::new(marker_handle, Vec2::new(4.0, 4.0)))
+            .with(Position(pos.0, pos.1 - 10.0))
+            .with(FocusMarker);
+        focus.marker = commands.current_entity();
+        focus.target = Some(id);
+    }
+}
+
+// focus marker system
+// keeps the marker over the priority target, and clears the mark once the target is gone
+pub fn focus_marker_system(mut commands: Commands, mut focus: ResMut<FocusTarget>, mut people: Query<(&Id, &Position)>,
+    mut markers: Query<(&FocusMarker, &mut Position)>) {
+    let target = match &focus.target {
+        Some(target) => target.clone(),
+        None => return,
+    };
+
+    let mut target_pos = None;
+    for (id, pos) in &mut people.iter() {
+        if id.id() == target {
+            target_pos = Some((pos.0, pos.1));
+            break;
+        }
+    }
+
+    match target_pos {
+        Some(target_pos) => {
+            for (_marker, mut pos) in &mut markers.iter() {
+                pos.0 = target_pos.0;
+                pos.1 = target_pos.1 - 10.0;
+            }
+        },
+        None => {
+            // the target is gone, so the mark is too
+            if let Some(marker) = focus.marker.take() {
+                commands.despawn(marker);
+            }
+            focus.target = None;
+        },
+    }
+}
+
+// stance hotkey system
+// cycles the stance of the selected squad members when the stance key is pressed
+pub fn stance_hotkey_system(inputs: Res<InputState>, bindings: Res<KeyBindings>, selection: Res<Selection>, mut query: Query<(Entity, &Controlled, &mut Stance)>) {
+    if !inputs.key_just_presses.contains(&bindings.stance) {
+        return;
+    }
+
+    for (entity, state, mut stance) in &mut query.iter() {
+        if selection.is_selected(entity, state.squad_pos) {
+            stance.kind = match stance.kind {
+                StanceKind::Aggressive => StanceKind::Defensive,
+                StanceKind::Defensive => StanceKind::HoldFire,
+                StanceKind::HoldFire => StanceKind::Aggressive,
+            };
+        }
+    }
+}
+
+// sneaking component
+// inserted onto squad members while they're sneaking
+// sneaking squad members move slower, make less noise and can only be seen from closer up
+pub struct Sneaking;
+
+// sneak hotkey system
+// toggles sneaking for the selected squad members when the sneak key is pressed
+// if any of them aren't sneaking they all start, otherwise they all stop
+pub fn sneak_hotkey_system(mut commands: Commands, inputs: Res<InputState>, bindings: Res<KeyBindings>, selection: Res<Selection>, mut query: Query<(Entity, &Controlled, Option<&Sneaking>)>) {
+    if !inputs.key_just_presses.contains(&bindings.sneak) {
+        return;
+    }
+
+    let mut selected = Vec::new();
+    for (entity, state, sneaking) in &mut query.iter() {
+        if selection.is_selected(entity, state.squad_pos) {
+            selected.push((entity, sneaking.is_some()));
+        }
+    }
+    let sneak = selected.iter().any(|(_, sneaking)| !sneaking);
+    for (entity, sneaking) in selected {
+        if sneak && !sneaking {
+            commands.insert_one(entity, Sneaking);
+        }else if !sneak && sneaking {
+            commands.remove_one::<Sneaking>(entity);
+        }
+    }
+}
+
+// stance engage system
+// finds hostiles for idle squad members to attack, and lets their stance decide if they do
+// hostiles attacking a squad member provoke it, others are only seen
+// the priority target is preferred over any other
+pub fn stance_engage_system(config: Res<GameConfig>, mut state: ResMut<StanceAttackState>, attacks: Res<Events<AttackEvent>>, focus: Res<FocusTarget>,
+    pipeline: Res<QueryPipeline>, bodies: Res<RigidBodySet>, colliders: Res<ColliderSet>,
+    mut squad: Query<(&Stance, &Id, &Position, &mut Nerve, Option<&Weapon>)>, mut people: Query<(&Id, &Person, &Position, Option<&Ambusher>)>) {
+    // find out who has been attacking whom
+    let mut provocations = Vec::new();
+    for event in state.event_reader.iter(&attacks) {
+        provocations.push((event.target_id.clone(), event.attacker_id.clone()));
+    }
+
+    // gather up the hostiles, they're the only ones fought without orders
+    // hidden ambushers can't be fought until they've been spotted
+    let mut hostiles = Vec::new();
+    for (id, person, pos, ambusher) in &mut people.iter() {
+        if person.attitude == AttitudeType::Hostile && !ambusher.map_or(false, |ambusher| ambusher.hidden) {
+            hostiles.push((id.id(), (pos.0, pos.1)));
+        }
+    }
+
+    for (stance, id, pos, mut actions, weapon) in &mut squad.iter() {
+        // only squad members with nothing to do look for a fight
+        // aggressive squad members fighting on their own will also switch to the priority target
+        let idle = actions.is_curr_action_empty() && actions.action_queue.is_empty();
+        let switching = stance.kind == StanceKind::Aggressive && focus.target.is_some()
+            && is_auto_attack(&actions.current_action) && actions.current_action.target.1 != focus.target;
+        if !idle && !switching {
+            continue;
+        }
+
+        // find the hostiles this squad member is allowed to attack
+        let mut candidates = Vec::new();
+        for (hid, hpos) in hostiles.iter() {
+            let dist = Vec2::new(hpos.0 - pos.0, hpos.1 - pos.1).length();
+            let provoked = provocations.iter().any(|(victim, attacker)| *victim == id.id() && attacker == hid);
+            // hostiles that haven't attacked need to be seen first
+            if !provoked && (dist > PERCEPTION_RADIUS || !combat_query::line_of_sight(&config, &pipeline, &bodies, &colliders, (pos.0, pos.1), *hpos)) {
+                continue;
+            }
+            if !stance_allows_engagement(stance.kind, provoked, dist) {
+                continue;
+            }
+            candidates.push((hid.clone(), dist));
+        }
+
+        let target = match pick_target(&candidates, &focus.target) {
+            Some(target) => target,
+            None => continue,
+        };
+        // only drop the current fight for the priority target itself
+        if !idle {
+            if Some(&target) != focus.target.as_ref() {
+                continue;
+            }
+            actions.clear();
+        }
+
+        let mut extra = HashMap::new();
+        // auto marks this as an attack the squad member started on its own
+        extra.insert("auto".to_string(), 1.0);
+        // anchor is where the squad member was when it started, for the defensive leash
+        extra.insert("anchor_x".to_string(), pos.0);
+        extra.insert("anchor_y".to_string(), pos.1);
+        actions.action_queue.extend(get_attack_actions(Some(target), &weapon.cloned().unwrap_or_default(), extra));
+    }
+}
+
+// stance leash system
+// calls off fights the squad member started on its own once its stance no longer allows them
+// hold fire calls them off straight away, defensive squad members return to where
+// they started once they stray past the leash
+pub fn stance_leash_system(mut query: Query<(&Stance, &Position, &mut Nerve)>) {
+    for (stance, pos, mut actions) in &mut query.iter() {
+        if !is_auto_attack(&actions.current_action) {
+            continue;
+        }
+        let params = actions.current_action.params.clone().unwrap_or_default();
+
+        let call_off = match stance.kind {
+            StanceKind::Aggressive => false,
+            StanceKind::Defensive => {
+                let anchor = (params.get("anchor_x").cloned().unwrap_or(pos.0), params.get("anchor_y").cloned().unwrap_or(pos.1));
+                Vec2::new(pos.0 - anchor.0, pos.1 - anchor.1).length() > DEFENSIVE_LEASH
+            },
+            StanceKind::HoldFire => true,
+        };
+
+        if call_off {
+            // drop the fight, leaving anything else queued up
+            actions.action_queue.retain(|action| !is_auto_attack(action));
+            actions.action_timer = None;
+            actions.current_action = Action::default();
+
+            // defensive squad members go back to where they were
+            if stance.kind == StanceKind::Defensive {
+                if let (Some(&x), Some(&y)) = (params.get("anchor_x"), params.get("anchor_y")) {
+                    let mut move_params = HashMap::new();
+                    move_params.insert("range".to_string(), 0.0);
+                    actions.push_front(Action {
+                        action_type: ActionType::Move,
+                        target: (Some((x, y)), None),
+                        params: Some(move_params),
+                        group: None,
+                    });
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,242 @@
+// surrender module
+// hostile morale, surrendering and capturing
+use bevy::prelude::*;
+use std::fs;
+use bevy_rapier2d::physics::RigidBodyHandleComponent;
+use bevy_rapier2d::rapier::dynamics::{JointSet, RigidBodySet};
+use bevy_rapier2d::rapier::geometry::ColliderSet;
+use bevy_rapier2d::rapier::pipeline::QueryPipeline;
+
+use crate::{
+    CAPTURE_REWARD_BONUS, combat_query, Id, MISSION_RESULTS_PATH,
+    MORALE_ALLY_DEATH_LOSS, MORALE_ALLY_DEATH_RADIUS, MORALE_BREAK, MORALE_HIT_LOSS,
+    PANIC_FLEE_DISTANCE, Position, SURRENDER_RADIUS,
+};
+use crate::actions::InteractEvent;
+use crate::animation::{AnimationType, SpriteData};
+use crate::behaviour::{Behaviour, BehaviourType, Pathfinder, TilePos};
+use crate::civilian::MissionRecord;
+use crate::combat::{AttackEvent, DeathEvent, remove_person};
+use crate::config::GameConfig;
+use crate::map::MapData;
+use crate::nerve::Nerve;
+use crate::person::{AttitudeType, Controlled, Person};
+use crate::tick::AddTickSystem;
+
+// surrender plugin
+// responsible for hostile morale, and what happens when it breaks
+// broken hostiles run, unless the squad has them cornered, in which case they surrender
+// surrendered hostiles are no longer hostile, and can be captured by ordering a squad member to take them prisoner
+// the mission record, along with how many were captured, is saved to the mission results file
+pub struct SurrenderPlugin;
+
+// implementation of the plugin trait,
+// required for this to be used as a plugin
+impl Plugin for SurrenderPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        // initialise the mission record, and the last copy of it saved
+        app.init_resource::<MissionRecord>()
+        .init_resource::<SavedMissionRecord>()
+        // initialise the event states
+        .init_resource::<MoraleAttackState>()
+        .init_resource::<MoraleDeathState>()
+        .init_resource::<CaptureInteractState>()
+        // add in the morale system
+        .add_tick_system(morale_system.system())
+        // add in the surrender system
+        .add_tick_system(surrender_system.system())
+        // add in the capture system
+        .add_tick_system(capture_system.system())
+        // save the mission results whenever they change
+        .add_system(save_mission_results_system.system());
+    }
+}
+
+// morale component
+// spawn this component along with hostiles that can lose their nerve
+pub struct Morale {
+    // from 100 down to 0
+    pub value: f32,
+}
+
+impl Default for Morale {
+    fn default() -> Self {
+        Morale {
+            value: 100.0,
+        }
+    }
+}
+
+impl Morale {
+    // gives whether morale has dropped far enough to break
+    pub fn is_broken(&self) -> bool {
+        self.value <= MORALE_BREAK
+    }
+}
+
+// surrendered component
+// inserted onto hostiles that have surrendered
+pub struct Surrendered;
+
+// saved mission record resource
+// the copy of the mission record last saved, so it's only saved again when something changes
+#[derive(Default)]
+pub struct SavedMissionRecord(pub Option<MissionRecord>);
+
+// morale attack state holds an event reader for attack events
+#[derive(Default)]
+pub struct MoraleAttackState {
+    pub event_reader: EventReader<AttackEvent>,
+}
+
+// morale death state holds an event reader for death events
+#[derive(Default)]
+pub struct MoraleDeathState {
+    pub event_reader: EventReader<DeathEvent>,
+}
+
+// capture interact state holds an event reader for interact events
+#[derive(Default)]
+pub struct CaptureInteractState {
+    pub event_reader: EventReader<InteractEvent>,
+}
+
+// morale system
+// knocks the morale of hostiles that are hit, or that see another hostile die nearby
+pub fn morale_system(mut attack_state: ResMut<MoraleAttackState>, attacks: Res<Events<AttackEvent>>,
+    mut death_state: ResMut<MoraleDeathState>, deaths: Res<Events<DeathEvent>>, mut query: Query<(&Id, &Person, &Position, &mut Morale)>) {
+    for attack in attack_state.event_reader.iter(&attacks) {
+        if !attack.hit {
+            continue;
+        }
+        for (id, _pers, _pos, mut morale) in &mut query.iter() {
+            if id.id() == attack.target_id {
+                morale.value = (morale.value - MORALE_HIT_LOSS).max(0.0);
+                break;
+            }
+        }
+    }
+    for death in death_state.event_reader.iter(&deaths) {
+        if death.attitude != Some(AttitudeType::Hostile) {
+            continue;
+        }
+        for (_id, pers, pos, mut morale) in &mut query.iter() {
+            let dist = Vec2::new(death.position.0 - pos.0, death.position.1 - pos.1).length();
+            if pers.attitude == AttitudeType::Hostile && dist <= MORALE_ALLY_DEATH_RADIUS {
+                morale.value = (morale.value - MORALE_ALLY_DEATH_LOSS).max(0.0);
+            }
+        }
+    }
+}
+
+// surrender system
+// sends broken hostiles running away from the squad, or has them surrender if they have nowhere to go
+// a broken hostile has nowhere to go when a squad member is close by and can see them,
+// and there's no open ground to run to away from the squad
+pub fn surrender_system(mut commands: Commands, config: Res<GameConfig>, map: Res<MapData>, mut record: ResMut<MissionRecord>,
+    pipeline: Res<QueryPipeline>, bodies: Res<RigidBodySet>, colliders: Res<ColliderSet>,
+    mut squad: Query<(&Controlled, &Position)>,
+    mut query: Query<Without<Surrendered, (Entity, &mut Person, &Morale, &Position, &mut Behaviour, &mut Nerve, &mut Pathfinder, &mut SpriteData)>>) {
+    let mut members = Vec::new();
+    for (_controlled, pos) in &mut squad.iter() {
+        members.push((pos.0, pos.1));
+    }
+
+    for (entity, mut pers, morale, pos, mut behav, mut actions, mut pf, mut sprite) in &mut query.iter() {
+        if pers.attitude != AttitudeType::Hostile || !morale.is_broken() {
+            continue;
+        }
+        // find the closest squad member
+        let nearest = members.iter()
+            .map(|member| (*member, Vec2::new(member.0 - pos.0, member.1 - pos.1).length()))
+            .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal));
+        let (threat, dist) = match nearest {
+            Some(nearest) => nearest,
+            None => continue,
+        };
+
+        // work out where the hostile would run to, straight away from the squad
+        let away = Vec2::new(pos.0 - threat.0, pos.1 - threat.1);
+        let away = if away.length() > 0.0 { away.normalize() } else { Vec2::new(1.0, 0.0) };
+        let goal = (
+            (pos.0 + away[0] * PANIC_FLEE_DISTANCE).max(10.0).min(config.map_width - 10.0),
+            (pos.1 + away[1] * PANIC_FLEE_DISTANCE).max(10.0).min(config.map_height - 10.0),
+        );
+        let room = Vec2::new(goal.0 - pos.0, goal.1 - pos.1).length();
+        let escape = room >= PANIC_FLEE_DISTANCE / 2.0
+            && !map.is_tile_blocked(&TilePos::from_coords(&config, goal.0, goal.1))
+            && combat_query::line_of_sight(&config, &pipeline, &bodies, &colliders, (pos.0, pos.1), goal);
+        let cornered = dist <= SURRENDER_RADIUS
+            && combat_query::line_of_sight(&config, &pipeline, &bodies, &colliders, (pos.0, pos.1), threat)
+            && !escape;
+
+        if cornered {
+            // give up, and stop being a threat
+            pers.attitude = AttitudeType::Surrendered;
+            behav.current_behaviour = BehaviourType::Surrender;
+            actions.clear();
+            pf.needs_pathfinding = false;
+            pf.tile_path.clear();
+            sprite.animation_type = AnimationType::Surrender;
+            commands.insert_one(entity, Surrendered);
+            record.surrendered += 1;
+        }else if escape && actions.is_curr_action_empty() && actions.action_queue.is_empty() && pf.path_index >= pf.tile_path.len() {
+            // run for it
+            let tile = map.get_nearest_free_tile(&TilePos::from_coords(&config, goal.0, goal.1));
+            pf.needs_pathfinding = true;
+            pf.path_goal = tile;
+            pf.real_goal = tile.to_coords(&config);
+        }
+    }
+}
+
+// capture system
+// captures a surrendered hostile once a squad member ordered to take them prisoner has finished doing so,
+// taking them off the map
+pub fn capture_system(mut commands: Commands, mut state: ResMut<CaptureInteractState>, events: Res<Events<InteractEvent>>, mut record: ResMut<MissionRecord>,
+    mut bodies: ResMut<RigidBodySet>, mut colliders: ResMut<ColliderSet>, mut joints: ResMut<JointSet>,
+    mut squad: Query<(&Id, &Controlled)>, mut query: Query<(Entity, &Id, &Surrendered, Option<&RigidBodyHandleComponent>)>) {
+    for event in state.event_reader.iter(&events) {
+        // only the squad takes prisoners
+        let mut by_squad = false;
+        for (id, _controlled) in &mut squad.iter() {
+            if id.id() == event.actor_id {
+                by_squad = true;
+                break;
+            }
+        }
+        if !by_squad {
+            continue;
+        }
+        for (entity, id, _surrendered, body) in &mut query.iter() {
+            if id.id() == event.target_id {
+                record.captured += 1;
+                record.reward_bonus += CAPTURE_REWARD_BONUS;
+                remove_person(&mut commands, entity, body, &mut bodies, &mut colliders, &mut joints);
+                break;
+            }
+        }
+    }
+}
+
+// save mission results system
+// writes the mission record out to the mission results file whenever it changes
+// the file has one result per line, a name followed by its value
+pub fn save_mission_results_system(record: Res<MissionRecord>, mut saved: ResMut<SavedMissionRecord>) {
+    if saved.0.as_ref() == Some(&*record) {
+        return;
+    }
+    let text = format!(
+        "civilian_deaths {}\nreward_penalty {}\nreputation {}\nsurrendered {}\ncaptured {}\nreward_bonus {}\nsquad_deaths {}\nsquad_injured {}\nextracted {}\n",
+        record.civilian_deaths, record.reward_penalty, record.reputation, record.surrendered, record.captured, record.reward_bonus,
+        record.squad_deaths, record.squad_injured, record.extracted,
+    );
+    // followed by a line for each squad member from the roster
+    let text = record.members.iter().fold(text, |text, member| {
+        format!("{}member {} {} {} {}\n", text, member.name, member.health, member.kills, member.alive)
+    });
+    if let Err(err) = fs::write(MISSION_RESULTS_PATH, text) {
+        eprintln!("could not save mission results to {}: {}", MISSION_RESULTS_PATH, err);
+    }
+    saved.0 = Some(record.clone());
+}
@@ -0,0 +1,207 @@
+// tick module
+// the stages every frame goes through, and the fixed tick the simulation runs on
+use bevy::prelude::*;
+use bevy::ecs::{Schedule, System};
+
+use crate::{
+    ACTION_STAGE, BEHAVIOUR_STAGE, COMMAND_STAGE, INPUT_STAGE, INTERPOLATION_SNAP_DISTANCE,
+    MOVEMENT_STAGE, PATHFINDING_STAGE, PHYSICS_STAGE, Position, SIM_MAX_CATCH_UP_TICKS, SIM_TICK,
+    SIMULATION_STAGE, TICK_UPDATE_STAGE, Velocity,
+};
+
+// pipeline plugin
+// responsible for adding in the stages every frame goes through, and the schedule every tick of the simulation goes through
+// see the settings for them. the simulation stage of the frame runs the tick schedule once for every tick due that frame
+// this has to be added before any other plugin, so the stages are there for them to add systems to
+pub struct PipelinePlugin;
+
+// implementation of the plugin trait,
+// required for this to be used as a plugin
+impl Plugin for PipelinePlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_stage_after(stage::EVENT_UPDATE, INPUT_STAGE)
+        .add_stage_after(INPUT_STAGE, COMMAND_STAGE)
+        .add_stage_after(COMMAND_STAGE, SIMULATION_STAGE)
+        // add in the schedule for the simulation, which plugins add to with add_tick_system
+        .add_resource(TickSchedule::new())
+        .add_system_to_stage(SIMULATION_STAGE, run_ticks_system.thread_local_system())
+        // add in the simulation clock, and the time of the frame for anything drawn
+        .init_resource::<SimulationClock>()
+        .init_resource::<FrameTime>();
+    }
+}
+
+// fixed tick plugin
+// responsible for running the simulation at a fixed rate, no matter how fast frames are drawn
+// the simulation clock gathers up the time passed each frame, and ticks once for every whole tick's worth that has passed
+// the time resource only moves on by whole ticks, so everything reading it sees the time of the simulation
+// the headless simulation ticks every update instead, as the clock ticks once a frame until this is added
+pub struct FixedTickPlugin;
+
+// implementation of the plugin trait,
+// required for this to be used as a plugin
+impl Plugin for FixedTickPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        // this comes after the time system, which also goes first
+        app.add_system_to_stage(stage::FIRST, simulation_clock_system.system())
+        // positions are remembered at the start of every tick, before anything moves
+        .add_tick_system_to_stage(BEHAVIOUR_STAGE, previous_position_system.system());
+    }
+}
+
+// tick schedule resource
+// the stages every tick of the simulation goes through, and the systems in them
+// the schedule is taken out while it runs, as its systems need the resources it lives in
+pub struct TickSchedule {
+    pub schedule: Option<Schedule>,
+}
+
+impl TickSchedule {
+    // gives the tick schedule with every stage in it, in order
+    pub fn new() -> Self {
+        let mut schedule = Schedule::default();
+        for stage in [BEHAVIOUR_STAGE, ACTION_STAGE, PATHFINDING_STAGE, MOVEMENT_STAGE, PHYSICS_STAGE, TICK_UPDATE_STAGE].iter() {
+            schedule.add_stage(*stage);
+        }
+        TickSchedule {
+            schedule: Some(schedule),
+        }
+    }
+}
+
+// lets plugins put systems on the simulation, as in app.add_tick_system(damage_system.system())
+// systems on the simulation run once every tick, in the order they were added within each stage
+// systems reading events sent outside the simulation should stay off it, as they could miss them between ticks
+// events sent by the simulation are added with add_tick_event, so they're kept for a tick rather than a frame,
+// and a tick never misses what was sent the tick before, however many frames go by in between
+pub trait AddTickSystem {
+    fn add_tick_system(&mut self, system: Box<dyn System>) -> &mut Self;
+    fn add_tick_system_to_stage(&mut self, stage: &'static str, system: Box<dyn System>) -> &mut Self;
+    fn add_tick_event<T: Send + Sync + 'static>(&mut self) -> &mut Self;
+}
+
+impl AddTickSystem for AppBuilder {
+    fn add_tick_system(&mut self, system: Box<dyn System>) -> &mut Self {
+        self.add_tick_system_to_stage(TICK_UPDATE_STAGE, system)
+    }
+    fn add_tick_system_to_stage(&mut self, stage: &'static str, system: Box<dyn System>) -> &mut Self {
+        add_to_tick_schedule(self, |schedule| { schedule.add_system_to_stage(stage, system); });
+        self
+    }
+    fn add_tick_event<T: Send + Sync + 'static>(&mut self) -> &mut Self {
+        self.add_resource(Events::<T>::default());
+        // events are moved on at the very start of every tick
+        add_to_tick_schedule(self, |schedule| { schedule.add_system_to_stage_front(BEHAVIOUR_STAGE, Events::<T>::update_system.system()); });
+        self
+    }
+}
+
+// hands the tick schedule over to be added to
+fn add_to_tick_schedule(app: &mut AppBuilder, add: impl FnOnce(&mut Schedule)) {
+    let mut tick_schedule = app.resources_mut()
+        .get_mut::<TickSchedule>()
+        .expect("the pipeline plugin has to be added before anything is put on the simulation");
+    add(tick_schedule.schedule.as_mut().expect("systems can't be put on the simulation while it runs"));
+}
+
+// run ticks system
+// runs the whole tick schedule once for every tick the simulation clock says is due this frame,
+// moving the time resource on by a tick before each one, so every tick sees the same step
+pub fn run_ticks_system(world: &mut World, resources: &mut Resources) {
+    let ticks = resources.get::<SimulationClock>().map_or(1, |clock| clock.ticks);
+    let schedule = resources.get_mut::<TickSchedule>().and_then(|mut tick_schedule| tick_schedule.schedule.take());
+    if let Some(mut schedule) = schedule {
+        schedule.initialize(resources);
+        for _ in 0..ticks {
+            if let Some(mut time) = resources.get_mut::<Time>() {
+                time.delta_seconds = SIM_TICK;
+                time.seconds_since_startup += SIM_TICK as f64;
+            }
+            schedule.run(world, resources);
+        }
+        if let Some(mut tick_schedule) = resources.get_mut::<TickSchedule>() {
+            tick_schedule.schedule = Some(schedule);
+        }
+    }
+}
+
+// simulation clock resource
+// keeps track of the time passed that hasn't been simulated yet, and how many times the simulation ticks this frame
+pub struct SimulationClock {
+    pub accumulator: f32,
+    pub tick: u64,
+    pub ticked: bool,
+    // how many ticks are run this frame, more than one when catching up after a long frame
+    pub ticks: u32,
+    // how far the frame is between the last tick and the next, for drawing things in between
+    pub alpha: f32,
+}
+
+// every frame is a tick until the clock says otherwise, as in the headless simulation
+impl Default for SimulationClock {
+    fn default() -> Self {
+        SimulationClock {
+            accumulator: 0.0,
+            tick: 0,
+            ticked: true,
+            ticks: 1,
+            alpha: 1.0,
+        }
+    }
+}
+
+// frame time resource
+// the time that actually passed since the last frame, for animations and the ui
+#[derive(Default)]
+pub struct FrameTime {
+    pub delta_seconds: f32,
+}
+
+// simulation clock system
+// moves the simulation clock on by the time passed this frame, and works out how many ticks are due
+// the time resource is put back to the start of this frame's ticks, and the tick schedule moves it on a tick at a time
+pub fn simulation_clock_system(mut time: ResMut<Time>, mut frame_time: ResMut<FrameTime>, mut clock: ResMut<SimulationClock>) {
+    frame_time.delta_seconds = time.delta_seconds;
+    clock.accumulator += time.delta_seconds;
+    // run a tick for every whole tick's worth of time, up to the most that can be caught up on in one frame
+    clock.ticks = 0;
+    while clock.accumulator >= SIM_TICK && clock.ticks < SIM_MAX_CATCH_UP_TICKS {
+        clock.accumulator -= SIM_TICK;
+        clock.tick += 1;
+        clock.ticks += 1;
+    }
+    clock.ticked = clock.ticks > 0;
+    // past the catch up limit the simulation slows down instead of piling up ticks,
+    // though up to a tick is kept in hand, to even out short frames followed by long ones
+    clock.accumulator = clock.accumulator.min(SIM_TICK);
+    clock.alpha = clock.accumulator / SIM_TICK;
+    time.delta_seconds = if clock.ticked { SIM_TICK } else { 0.0 };
+    time.seconds_since_startup = (clock.tick - clock.ticks as u64) as f64 * SIM_TICK as f64;
+}
+
+// previous position component
+// where something was at the start of the last tick, so it can be drawn part of the way to where it is now
+pub struct PreviousPosition(pub f32, pub f32);
+
+// previous position system
+// remembers where everything that moves was before each tick
+pub fn previous_position_system(mut commands: Commands,
+    mut new_query: Query<Without<PreviousPosition, (Entity, &Position, &Velocity)>>, mut query: Query<(&mut PreviousPosition, &Position)>) {
+    for (entity, pos, _vel) in &mut new_query.iter() {
+        commands.insert_one(entity, PreviousPosition(pos.0, pos.1));
+    }
+    for (mut previous, pos) in &mut query.iter() {
+        previous.0 = pos.0;
+        previous.1 = pos.1;
+    }
+}
+
+// gives where to draw something between where it was at the start of the last tick and where it is now
+pub fn get_interpolated_position(pos: &Position, previous: Option<&PreviousPosition>, alpha: f32) -> (f32, f32) {
+    match previous {
+        Some(previous) if ((pos.0 - previous.0).powi(2) + (pos.1 - previous.1).powi(2)).sqrt() <= INTERPOLATION_SNAP_DISTANCE => {
+            (previous.0 + (pos.0 - previous.0) * alpha, previous.1 + (pos.1 - previous.1) * alpha)
+        },
+        _ => (pos.0, pos.1),
+    }
+}
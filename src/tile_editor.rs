@@ -0,0 +1,319 @@
+// tile editor module
+// the tile editor
+use bevy::prelude::*;
+use bevy::input::mouse::MouseButtonInput;
+use bevy::input::keyboard::ElementState;
+use std::collections::HashMap;
+use std::fs;
+use bevy_rapier2d::physics::RigidBodyHandleComponent;
+use bevy_rapier2d::rapier::dynamics::{JointSet, RigidBodySet};
+use bevy_rapier2d::rapier::geometry::ColliderSet;
+
+use crate::{
+    CONSOLE_FONT_SIZE, Position, TILE_EDITOR_EXPORT_PATH, TILE_EDITOR_OPACITY,
+    TILE_EDITOR_Z_LEVEL,
+};
+use crate::behaviour::TilePos;
+use crate::collision::{spawn_wall_collider, WallColliders};
+use crate::config::GameConfig;
+use crate::console::{Console, ConsoleCommand, ConsoleRegistry};
+use crate::control::{InputState, KeyBindings};
+use crate::encounter::{spawn_spawn_point, SpawnPoint};
+use crate::map::{MapData, TileProperties, WaterDepth};
+use crate::mods::ModAssets;
+use crate::person::SimpleRect;
+
+// tile editor plugin
+// responsible for the tile editor, for painting tiles straight onto the running map with the mouse
+// and exporting them, to be laid over the map the next time with --tiles
+// brushes are picked and tiles exported from the console, so this goes after the console plugin
+pub struct TileEditorPlugin;
+
+// implementation of the plugin trait,
+// required for this to be used as a plugin
+impl Plugin for TileEditorPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        // add in the console commands for picking brushes and exporting the painted tiles
+        {
+            let mut registry = app.resources_mut().get_mut::<ConsoleRegistry>().expect("the tile editor needs the console plugin added first");
+            registry.register("brush", "brush <wall|floor|road|dry|shallow|deep|cost amount|cover amount|height amount|spawn name>", 1);
+            registry.register("export_tiles", "export_tiles [path], writes out the painted tiles", 0);
+        }
+        app.init_resource::<TileEditor>()
+        .init_resource::<TileEditorCommandState>()
+        // add in the tile editor text
+        .add_startup_system(setup_tile_editor_system.system())
+        // add in the system opening and closing the editor
+        .add_system(tile_editor_key_system.system())
+        // add in the system painting tiles
+        .add_system(tile_editor_paint_system.system())
+        // add in the system carrying out the editor's console commands
+        .add_system(tile_editor_command_system.system())
+        // add in the system showing the editor
+        .add_system(tile_editor_text_system.system());
+    }
+}
+
+// tile editor resource
+// whether the editor is open, the brush being painted with, and everything painted so far,
+// along with the markers drawn over the painted tiles and the spawn points painted in
+pub struct TileEditor {
+    pub open: bool,
+    pub brush_name: String,
+    pub brush: TileProperties,
+    // whether the left mouse button is held down, painting every tile the cursor passes over
+    pub painting: bool,
+    pub painted: HashMap<TilePos, TileProperties>,
+    pub markers: HashMap<TilePos, Entity>,
+    pub spawn_points: HashMap<TilePos, Entity>,
+    // the editor reads the mouse itself, as the clicks are kept from everything else while it's open
+    pub mouse_reader: EventReader<MouseButtonInput>,
+}
+
+impl Default for TileEditor {
+    fn default() -> Self {
+        TileEditor {
+            open: false,
+            brush_name: "wall".to_string(),
+            brush: get_tile_brush("wall", None).unwrap_or_default(),
+            painting: false,
+            painted: HashMap::new(),
+            markers: HashMap::new(),
+            spawn_points: HashMap::new(),
+            mouse_reader: EventReader::default(),
+        }
+    }
+}
+
+// tile editor marker component
+// spawn this component along with the markers drawn over painted tiles
+pub struct TileEditorMarker;
+
+// tile editor text component
+// spawn this component along with the text showing the tile editor
+pub struct TileEditorText;
+
+// tile editor command state holds an event reader for the tile editor's console commands
+#[derive(Default)]
+pub struct TileEditorCommandState {
+    pub event_reader: EventReader<ConsoleCommand>,
+}
+
+// gives the tile properties painted by the brush with the given name, and the amount for those that need one
+// the floor brush puts a tile back to open ground
+pub fn get_tile_brush(name: &str, amount: Option<&str>) -> Result<TileProperties, String> {
+    let mut brush = TileProperties::default();
+    let number = || amount.and_then(|amount| amount.parse::<f32>().ok()).ok_or_else(|| format!("brush {} needs an amount", name));
+    match name {
+        "wall" => brush.walkable = Some(false),
+        "floor" => {
+            brush.walkable = Some(true);
+            brush.movement_cost = Some(1.0);
+            brush.cover = Some(0.0);
+            brush.road = Some(false);
+            brush.water = Some(WaterDepth::Dry);
+            brush.height = Some(0.0);
+        },
+        "road" => brush.road = Some(true),
+        "dry" | "shallow" | "deep" => brush.water = WaterDepth::from_name(name),
+        "cost" => brush.movement_cost = Some(number()?.max(0.0)),
+        "cover" => brush.cover = Some(number()?.max(0.0).min(1.0)),
+        "height" => brush.height = Some(number()?),
+        "spawn" => brush.spawn = Some(amount.ok_or_else(|| "brush spawn needs a name".to_string())?.to_string()),
+        _ => return Err(format!("no brush called {}", name)),
+    }
+    Ok(brush)
+}
+
+// gives the colour of the marker drawn over a painted tile
+pub fn get_painted_tile_color(properties: &TileProperties) -> Color {
+    let color = if properties.spawn.is_some() {
+        Color::rgb(0.0, 1.0, 1.0)
+    }else if properties.walkable == Some(false) {
+        Color::BLACK
+    }else if properties.water == Some(WaterDepth::Deep) {
+        Color::rgb(0.0, 0.0, 0.5)
+    }else if properties.water == Some(WaterDepth::Shallow) {
+        Color::rgb(0.4, 0.6, 1.0)
+    }else if properties.road == Some(true) {
+        Color::rgb(0.5, 0.5, 0.5)
+    }else{
+        Color::WHITE
+    };
+    Color::rgba(color.r, color.g, color.b, TILE_EDITOR_OPACITY)
+}
+
+// setup tile editor system
+// spawns in the tile editor text in the bottom right of the screen, empty until the editor is opened
+pub fn setup_tile_editor_system(mut commands: Commands, asset_server: Res<AssetServer>, mods: Res<ModAssets>) {
+    let font_handle = asset_server.load(mods.resolve("assets/fonts/LiberationMono-Regular.ttf")).unwrap();
+    commands
+        .spawn(TextComponents {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    right: Val::Px(5.0),
+                    bottom: Val::Px(5.0),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            text: Text {
+                value: "".to_string(),
+                font: font_handle,
+                style: TextStyle {
+                    font_size: CONSOLE_FONT_SIZE,
+                    color: Color::BLACK,
+                },
+            },
+            ..Default::default()
+        })
+        .with(TileEditorText);
+}
+
+// tile editor key system
+// opens and closes the tile editor, which takes the mouse while it's open
+// the markers over the painted tiles are only shown while it is
+pub fn tile_editor_key_system(mut inputs: ResMut<InputState>, bindings: Res<KeyBindings>, mut editor: ResMut<TileEditor>, mut markers: Query<(&TileEditorMarker, &mut Draw)>) {
+    if !inputs.key_just_presses.contains(&bindings.tile_editor) {
+        return;
+    }
+    editor.open = !editor.open;
+    editor.painting = false;
+    inputs.mouse_captured = editor.open;
+    for (_marker, mut draw) in &mut markers.iter() {
+        draw.is_visible = editor.open;
+    }
+}
+
+// tile editor paint system
+// paints the tile under the cursor with the brush while the left mouse button is held down,
+// keeping the map data, the walls and the spawn points up to date with it
+// painting over a spawn point with any other brush clears it away
+pub fn tile_editor_paint_system(mut commands: Commands, config: Res<GameConfig>, inputs: Res<InputState>, mouse_events: Res<Events<MouseButtonInput>>,
+    mut editor: ResMut<TileEditor>, mut map: ResMut<MapData>, mut walls: ResMut<WallColliders>, mut materials: ResMut<Assets<ColorMaterial>>,
+    mut bodies: ResMut<RigidBodySet>, mut colliders: ResMut<ColliderSet>, mut joints: ResMut<JointSet>, mut query: Query<&RigidBodyHandleComponent>, mut spawn_points: Query<&SpawnPoint>) {
+    let presses: Vec<(MouseButton, ElementState)> = editor.mouse_reader.iter(&mouse_events).map(|event| (event.button, event.state)).collect();
+    for (button, state) in presses {
+        if button == MouseButton::Left {
+            editor.painting = editor.open && state == ElementState::Pressed;
+        }
+    }
+    let (x, y) = inputs.mouse_position;
+    if !editor.open || !editor.painting || !config.is_on_map(x, y) {
+        return;
+    }
+
+    let tile = TilePos::from_coords(&config, x, y);
+    let brush = editor.brush.clone();
+    let before = editor.painted.get(&tile).cloned().unwrap_or_default();
+    let mut painted = before.clone();
+    if brush.spawn.is_none() {
+        painted.spawn = None;
+    }
+    painted.overlay(&brush);
+    // holding the button down over the same tile doesn't paint it again
+    if painted == before {
+        return;
+    }
+    map.set_tile_properties(&tile, &brush);
+
+    // keep the spawn points in line with what's painted
+    if painted.spawn != before.spawn {
+        // the spawn point might already be gone, cleared away along with the map it was painted on
+        if let Some(point) = editor.spawn_points.remove(&tile) {
+            if spawn_points.get::<SpawnPoint>(point).is_ok() {
+                commands.despawn(point);
+            }
+        }
+        if let Some(name) = &painted.spawn {
+            spawn_spawn_point(&mut commands, name.clone(), tile.to_coords(&config));
+            if let Some(point) = commands.current_entity() {
+                editor.spawn_points.insert(tile, point);
+            }
+        }
+    }
+
+    // and the walls in line with whether the tile can be walked through
+    if walls.built {
+        let impassable = map.is_impassable(&tile);
+        if impassable && !walls.walls.contains_key(&tile) {
+            if let Some(wall) = spawn_wall_collider(&mut commands, &config, &tile) {
+                walls.walls.insert(tile, wall);
+            }
+        }else if !impassable {
+            if let Some(wall) = walls.walls.remove(&tile) {
+                if let Ok(body) = query.get::<RigidBodyHandleComponent>(wall) {
+                    bodies.remove(body.handle(), &mut colliders, &mut joints);
+                }
+                commands.despawn(wall);
+            }
+        }
+    }
+
+    // mark the tile as painted, in a colour showing what it was painted with
+    if let Some(marker) = editor.markers.remove(&tile) {
+        commands.despawn(marker);
+    }
+    let coords = tile.to_coords(&config);
+    let mut marker = SimpleRect::new(materials.add(get_painted_tile_color(&painted).into()), Vec2::new(config.tile_size, config.tile_size));
+    marker.translation = Translation(Vec3::new(-1000.0, -1000.0, TILE_EDITOR_Z_LEVEL));
+    commands
+        .spawn(marker)
+        .with(Position(coords.0, coords.1))
+        .with(TileEditorMarker);
+    if let Some(marker) = commands.current_entity() {
+        editor.markers.insert(tile, marker);
+    }
+    editor.painted.insert(tile, painted);
+}
+
+// tile editor command system
+// carries out the brush and export_tiles console commands
+// painted tiles are exported one to a line, their x and y followed by their properties, as read in by MapData::load_painted_tiles
+pub fn tile_editor_command_system(mut state: ResMut<TileEditorCommandState>, events: Res<Events<ConsoleCommand>>,
+    mut console: ResMut<Console>, mut editor: ResMut<TileEditor>) {
+    let received: Vec<ConsoleCommand> = state.event_reader.iter(&events).cloned().collect();
+    for command in received {
+        let args: Vec<&str> = command.args.iter().map(|arg| arg.as_str()).collect();
+        match (command.name.as_str(), args.as_slice()) {
+            ("brush", [name, rest @ ..]) => {
+                match get_tile_brush(name, rest.first().cloned()) {
+                    Ok(brush) => {
+                        editor.brush_name = args.join(" ");
+                        editor.brush = brush;
+                        console.print(format!("painting with {}", editor.brush_name));
+                    },
+                    Err(err) => console.print(err),
+                }
+            },
+            ("export_tiles", _) => {
+                let path = args.first().cloned().unwrap_or(TILE_EDITOR_EXPORT_PATH);
+                let mut tiles: Vec<(&TilePos, &TileProperties)> = editor.painted.iter().collect();
+                tiles.sort_by_key(|(tile, _)| (tile.1, tile.0));
+                let mut text = "# tiles painted in the tile editor, laid over the map by launching with --tiles\n".to_string();
+                for (tile, properties) in tiles {
+                    text.push_str(&format!("{} {} {}\n", tile.0, tile.1, properties.to_line()));
+                }
+                match fs::write(path, text) {
+                    Ok(()) => console.print(format!("exported {} tiles to {}", editor.painted.len(), path)),
+                    Err(err) => console.print(format!("could not export tiles to {}: {}", path, err)),
+                }
+            },
+            _ => {},
+        }
+    }
+}
+
+// tile editor text system
+// shows the brush being painted with while the editor is open
+pub fn tile_editor_text_system(editor: Res<TileEditor>, mut query: Query<(&TileEditorText, &mut Text)>) {
+    let value = if editor.open {
+        format!("tile editor, painting with {}\n{} tiles painted, brush and export_tiles in the console", editor.brush_name, editor.painted.len())
+    }else{
+        String::new()
+    };
+    for (_editor, mut text) in &mut query.iter() {
+        text.value = value.clone();
+    }
+}
@@ -0,0 +1,175 @@
+// vision cone module
+// vision cones, hearing and investigating noises
+use bevy::prelude::*;
+use std::collections::HashMap;
+
+use crate::{Position, VISION_CONE_DOTS};
+use crate::behaviour::{Behaviour, BehaviourType, Pathfinder, TilePos};
+use crate::config::{GameConfig, LaunchOptions};
+use crate::control::{InputState, KeyBindings};
+use crate::encounter::Ambusher;
+use crate::map::MapData;
+use crate::nerve::Nerve;
+use crate::perception::{NoiseEvent, NoiseEventState, NoiseReaction, Perception};
+use crate::person::{AttitudeType, Person, SimpleRect};
+
+// vision cone plugin
+// draws the vision cones of every hostile, so the squad can sneak around them
+// the cones are toggled with the vision cone key, and start shown in the debug overlay
+pub struct VisionConePlugin;
+
+// implementation of the plugin trait,
+// required for this to be used as a plugin
+impl Plugin for VisionConePlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        let shown = app.resources().get::<LaunchOptions>().map_or(false, |launch| launch.debug_physics);
+        app.add_resource(VisionConeOverlay {
+            shown: shown,
+            dots: HashMap::new(),
+        })
+        // add in the toggle for the vision cones
+        .add_system(vision_cone_toggle_system.system())
+        // add in the system drawing the vision cones
+        .add_system(vision_cone_system.system());
+    }
+}
+
+// vision cone overlay resource
+// whether the vision cones are being shown, and the dots drawing each entity's cone
+pub struct VisionConeOverlay {
+    pub shown: bool,
+    pub dots: HashMap<Entity, Vec<Entity>>,
+}
+
+// vision cone toggle system
+// shows or hides the vision cones when the vision cone key is pressed
+pub fn vision_cone_toggle_system(inputs: Res<InputState>, bindings: Res<KeyBindings>, mut overlay: ResMut<VisionConeOverlay>) {
+    if inputs.key_just_presses.contains(&bindings.vision_cones) {
+        overlay.shown = !overlay.shown;
+    }
+}
+
+// vision cone system
+// keeps the dots outlining every hostile's vision cone in the right place
+// the edges of the cone are lines of dots, joined up by an arc of dots at the far end
+pub fn vision_cone_system(mut commands: Commands, mut overlay: ResMut<VisionConeOverlay>, mut materials: ResMut<Assets<ColorMaterial>>,
+    mut query: Query<(Entity, &Person, &Perception, &Position, Option<&Ambusher>)>, mut dots: Query<&mut Position>) {
+    let mut cones = Vec::new();
+    if overlay.shown {
+        for (entity, pers, perception, pos, ambusher) in &mut query.iter() {
+            // hidden ambushers would be given away by their cones
+            if pers.attitude == AttitudeType::Hostile && !ambusher.map_or(false, |ambusher| ambusher.hidden) {
+                cones.push((entity, (pos.0, pos.1), perception.facing, perception.fov, perception.sight));
+            }
+        }
+    }
+
+    // clear away the dots of cones no longer shown
+    let gone: Vec<Entity> = overlay.dots.keys().filter(|owner| !cones.iter().any(|cone| cone.0 == **owner)).cloned().collect();
+    for owner in gone {
+        for dot in overlay.dots.remove(&owner).unwrap_or_default() {
+            commands.despawn(dot);
+        }
+    }
+
+    for (owner, origin, facing, fov, sight) in cones {
+        // the points along the outline of the cone
+        let heading = facing.1.atan2(facing.0);
+        let half = fov.to_radians() / 2.0;
+        let point = |angle: f32, dist: f32| (origin.0 + angle.cos() * dist, origin.1 + angle.sin() * dist);
+        let mut points = Vec::new();
+        for i in 1..=VISION_CONE_DOTS {
+            let dist = sight * i as f32 / VISION_CONE_DOTS as f32;
+            points.push(point(heading - half, dist));
+            points.push(point(heading + half, dist));
+        }
+        for i in 1..VISION_CONE_DOTS {
+            points.push(point(heading - half + fov.to_radians() * i as f32 / VISION_CONE_DOTS as f32, sight));
+        }
+
+        if !overlay.dots.contains_key(&owner) {
+            let material = materials.add(Color::rgb(1.0, 0.8, 0.0).into());
+            let mut spawned = Vec::new();
+            for point in points.iter() {
+                commands
+                    .spawn(SimpleRect::new(material, Vec2::new(2.0, 2.0)))
+                    .with(Position(point.0, point.1));
+                if let Some(dot) = commands.current_entity() {
+                    spawned.push(dot);
+                }
+            }
+            overlay.dots.insert(owner, spawned);
+            continue;
+        }
+        for (dot, point) in overlay.dots[&owner].iter().zip(points.iter()) {
+            if let Ok(mut pos) = dots.get_mut::<Position>(*dot) {
+                pos.0 = point.0;
+                pos.1 = point.1;
+            }
+        }
+    }
+}
+
+// hearing system
+// lets every perceiving entity hear the noises made within range of it
+// noises travel through the map, so walls between an entity and a noise make it harder to hear
+// entities don't react to noises made by their own faction
+pub fn hearing_system(config: Res<GameConfig>, map: Res<MapData>, mut state: ResMut<NoiseEventState>, noises: Res<Events<NoiseEvent>>, mut query: Query<(&Person, &mut Perception, &Position)>) {
+    for noise in state.event_reader.iter(&noises) {
+        // the noise only needs to be followed as far as the sharpest ears can hear it
+        let mut range: f32 = 0.0;
+        for (_pers, perception, _pos) in &mut query.iter() {
+            range = range.max(noise.radius * perception.hearing);
+        }
+        let to_coords = |x: f32, y: f32| TilePos::from_coords(&config, x.max(0.0).min(config.map_width - 1.0), y.max(0.0).min(config.map_height - 1.0));
+        let travelled = map.propagate_noise(&config, &to_coords(noise.origin.0, noise.origin.1), range);
+
+        for (pers, mut perception, pos) in &mut query.iter() {
+            if noise.attitude == Some(pers.attitude) {
+                continue;
+            }
+            if let Some(&dist) = travelled.get(&to_coords(pos.0, pos.1)) {
+                if dist <= noise.radius * perception.hearing {
+                    perception.heard = Some(noise.origin);
+                }
+            }
+        }
+    }
+}
+
+// investigate system
+// sends entities that heard something over to where the noise came from
+// and lets them go back to what they were doing once they get there
+pub fn investigate_system(config: Res<GameConfig>, mut query: Query<(&mut Perception, &mut Behaviour, &mut Nerve, &mut Pathfinder)>) {
+    for (mut perception, mut behav, mut actions, mut pf) in &mut query.iter() {
+        if perception.reaction != NoiseReaction::Investigate {
+            continue;
+        }
+        // ambushers and the surrendered hold their position no matter what they hear
+        if behav.current_behaviour == BehaviourType::Ambush || behav.current_behaviour == BehaviourType::Surrender {
+            perception.heard = None;
+            continue;
+        }
+        if let Some(origin) = perception.heard.take() {
+            // drop whatever the entity was doing
+            actions.clear();
+
+            // pathfind to the origin of the noise
+            let goal = (origin.0.max(0.0), origin.1.max(0.0));
+            pf.needs_pathfinding = true;
+            pf.path_goal = TilePos::from_coords(&config, goal.0, goal.1);
+            pf.real_goal = goal;
+
+            behav.current_behaviour = BehaviourType::Investigate;
+        }else if behav.current_behaviour == BehaviourType::Investigate {
+            // check if the entity has arrived at the origin of the noise
+            let arrived = !pf.needs_pathfinding
+                && pf.path_index >= pf.tile_path.len()
+                && actions.is_curr_action_empty()
+                && actions.action_queue.is_empty();
+            if arrived {
+                behav.current_behaviour = BehaviourType::Empty;
+            }
+        }
+    }
+}
@@ -0,0 +1,70 @@
+// water module
+// wading and drowning
+use bevy::prelude::*;
+use bevy_rapier2d::physics::RigidBodyHandleComponent;
+use bevy_rapier2d::rapier::dynamics::{JointSet, RigidBodySet};
+use bevy_rapier2d::rapier::geometry::ColliderSet;
+
+use crate::{BEHAVIOUR_STAGE, DROWNING_DAMAGE, Id, Position};
+use crate::behaviour::TilePos;
+use crate::combat::{DeathEvent, Health, remove_person};
+use crate::config::GameConfig;
+use crate::map::MapData;
+use crate::person::Person;
+use crate::tick::AddTickSystem;
+
+// water plugin
+// responsible for anyone in the water. wading through shallow water throws off their aim,
+// see also the map data and terrain speed, and anyone who ends up in deep water drowns
+pub struct WaterPlugin;
+
+// implementation of the plugin trait,
+// required for this to be used as a plugin
+impl Plugin for WaterPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        // add in the system keeping track of who's wading
+        app.add_tick_system_to_stage(BEHAVIOUR_STAGE, wading_system.system())
+        // add in the drowning system
+        .add_tick_system_to_stage(BEHAVIOUR_STAGE, drowning_system.system());
+    }
+}
+
+// wading component
+// inserted onto anyone while they're wading through shallow water
+pub struct Wading;
+
+// wading system
+// marks everyone standing in shallow water as wading, and everyone who's left it as not
+pub fn wading_system(mut commands: Commands, config: Res<GameConfig>, map: Res<MapData>, mut query: Query<(Entity, &Person, &Position, Option<&Wading>)>) {
+    for (entity, _pers, pos, wading) in &mut query.iter() {
+        let in_water = pos.0 >= 0.0 && pos.1 >= 0.0 && map.is_wading(&TilePos::from_coords(&config, pos.0, pos.1));
+        if in_water && wading.is_none() {
+            commands.insert_one(entity, Wading);
+        }else if !in_water && wading.is_some() {
+            commands.remove_one::<Wading>(entity);
+        }
+    }
+}
+
+// drowning system
+// wears down the health of anyone who ends up in deep water, e.g. by being spawned or knocked into it
+pub fn drowning_system(mut commands: Commands, config: Res<GameConfig>, time: Res<Time>, map: Res<MapData>, mut deaths: ResMut<Events<DeathEvent>>,
+    mut bodies: ResMut<RigidBodySet>, mut colliders: ResMut<ColliderSet>, mut joints: ResMut<JointSet>,
+    mut query: Query<(Entity, &Id, &Person, &mut Health, &Position, Option<&RigidBodyHandleComponent>)>) {
+    for (entity, id, pers, mut health, pos, body) in &mut query.iter() {
+        if pos.0 < 0.0 || pos.1 < 0.0 || health.current <= 0.0 || !map.is_deep_water(&TilePos::from_coords(&config, pos.0, pos.1)) {
+            continue;
+        }
+        health.current -= DROWNING_DAMAGE * time.delta_seconds;
+        if health.current <= 0.0 {
+            // nobody gets the credit for a drowning
+            deaths.send(DeathEvent {
+                id: id.id(),
+                attitude: Some(pers.attitude),
+                killer_id: String::new(),
+                position: (pos.0, pos.1),
+            });
+            remove_person(&mut commands, entity, body, &mut bodies, &mut colliders, &mut joints);
+        }
+    }
+}
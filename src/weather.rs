@@ -0,0 +1,151 @@
+// weather module
+// the weather and the fog that comes with it
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::{
+    BEHAVIOUR_STAGE, DARKNESS_Z_LEVEL, FOG_CHANCE, FOG_OPACITY, FOG_VISIBILITY, GameRng, Position,
+    RAIN_CHANCE, RAIN_VISIBILITY, RAINDROPS_PER_SECOND,
+};
+use crate::behaviour::TilePos;
+use crate::config::GameConfig;
+use crate::control::MoveSpeed;
+use crate::draw::Screen;
+use crate::map::MapData;
+use crate::person::SimpleRect;
+use crate::pool::{PoolKind, PoolSpawnRequest};
+use crate::tick::AddTickSystem;
+
+// weather plugin
+// responsible for the weather, which is rolled at the start of each mission unless the encounter sets it
+// rain and fog make everyone harder to see, and rain turns low ground to mud
+pub struct WeatherPlugin;
+
+// implementation of the plugin trait,
+// required for this to be used as a plugin
+impl Plugin for WeatherPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        // add in the fog overlay
+        app.add_startup_system(setup_fog_system.system())
+        // add in the weather system
+        .add_tick_system(weather_system.system())
+        // add in the system slowing people down in the mud
+        .add_tick_system_to_stage(BEHAVIOUR_STAGE, terrain_speed_system.system());
+    }
+}
+
+// weather kind enum
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WeatherKind {
+    Clear,
+    Rain,
+    Fog,
+}
+
+impl WeatherKind {
+    // gives the weather with the given name, as used in encounter files
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "clear" => Some(WeatherKind::Clear),
+            "rain" => Some(WeatherKind::Rain),
+            "fog" => Some(WeatherKind::Fog),
+            _ => None,
+        }
+    }
+    // gives how far away anyone can be seen, as a fraction of how far they can be seen when it's clear
+    pub fn visibility(&self) -> f32 {
+        match self {
+            WeatherKind::Clear => 1.0,
+            WeatherKind::Rain => RAIN_VISIBILITY,
+            WeatherKind::Fog => FOG_VISIBILITY,
+        }
+    }
+}
+
+// weather resource
+// the weather for the mission, and whether it's been decided yet
+pub struct Weather {
+    pub kind: WeatherKind,
+    pub rolled: bool,
+    // raindrops waiting to fall, carried over between frames
+    pub raindrops: f32,
+}
+
+impl Default for Weather {
+    fn default() -> Self {
+        Weather {
+            kind: WeatherKind::Clear,
+            rolled: false,
+            raindrops: 0.0,
+        }
+    }
+}
+
+// fog component
+// spawn this component along with the overlay drawn over everything while it's foggy
+pub struct Fog;
+
+// setup fog system
+// spawns in the fog overlay, hidden until it's foggy
+pub fn setup_fog_system(mut commands: Commands, config: Res<GameConfig>, mut materials: ResMut<Assets<ColorMaterial>>) {
+    let material = materials.add(Color::rgba(0.85, 0.85, 0.9, FOG_OPACITY).into());
+    let mut fog = SimpleRect::new(material, Vec2::new(config.map_width, config.map_height));
+    // drawn over everything but the darkness
+    fog.translation = Translation(Vec3::new(-1000.0, -1000.0, DARKNESS_Z_LEVEL - 1.0));
+    commands
+        .spawn(fog)
+        .with(Position(config.map_width / 2.0, config.map_height / 2.0))
+        .with(Fog);
+}
+
+// weather system
+// rolls the weather once the encounter has had its say, then keeps the map and the overlays in line with it
+// rain falls as particles from the entity pool
+pub fn weather_system(config: Res<GameConfig>, time: Res<Time>, screen: Res<Screen>, mut rng: ResMut<GameRng>, mut weather: ResMut<Weather>, mut map: ResMut<MapData>,
+    mut particles: ResMut<Events<PoolSpawnRequest>>, mut fog: Query<(&Fog, &mut Draw)>) {
+    if !weather.rolled {
+        let roll = rng.0.gen::<f32>();
+        weather.kind = if roll < RAIN_CHANCE {
+            WeatherKind::Rain
+        }else if roll < RAIN_CHANCE + FOG_CHANCE {
+            WeatherKind::Fog
+        }else{
+            WeatherKind::Clear
+        };
+        weather.rolled = true;
+    }
+
+    map.muddy = weather.kind == WeatherKind::Rain;
+    for (_fog, mut draw) in &mut fog.iter() {
+        draw.is_visible = weather.kind == WeatherKind::Fog;
+    }
+
+    if weather.kind == WeatherKind::Rain {
+        weather.raindrops += RAINDROPS_PER_SECOND * time.delta_seconds;
+        while weather.raindrops >= 1.0 {
+            weather.raindrops -= 1.0;
+            // drops fall from anywhere along the top of the screen, blown slightly to the side
+            let top_left = (screen.camera.0 - screen.world_width / 2.0, screen.camera.1 - screen.world_height / 2.0);
+            particles.send(PoolSpawnRequest {
+                kind: PoolKind::Particle,
+                position: (top_left.0 + rng.0.gen::<f32>() * screen.world_width, top_left.1 + rng.0.gen::<f32>() * screen.world_height * 0.5),
+                velocity: (-30.0, 400.0),
+                lifetime: 0.5,
+                attitude: None,
+            });
+        }
+    }
+}
+
+// terrain speed system
+// slows down anyone walking through mud and speeds up anyone walking along a road,
+// and puts them back to their usual speed once they're off it
+pub fn terrain_speed_system(config: Res<GameConfig>, map: Res<MapData>, mut query: Query<(&Position, &mut MoveSpeed)>) {
+    for (pos, mut speed) in &mut query.iter() {
+        if pos.0 < 0.0 || pos.1 < 0.0 {
+            speed.terrain = 1.0;
+            continue;
+        }
+        speed.terrain = map.get_terrain_speed(&TilePos::from_coords(&config, pos.0, pos.1));
+    }
+}
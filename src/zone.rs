@@ -0,0 +1,327 @@
+// zone module
+// trigger zones, and the start points loaded along with them
+use bevy::prelude::*;
+use std::collections::HashMap;
+use bevy_rapier2d::physics::{ColliderHandleComponent, EventQueue, RigidBodyHandleComponent};
+use bevy_rapier2d::rapier::dynamics::{RigidBodyBuilder, RigidBodySet};
+use bevy_rapier2d::rapier::geometry::{ColliderBuilder, InteractionGroups, Proximity};
+use bevy_rapier2d::rapier::math::Isometry;
+use bevy_tiled::tiled::{ObjectShape, PropertyValue};
+
+use crate::{HOSTILE_GROUP_PREFIX, PLAYER_START, Position, Size};
+use crate::behaviour::{MapPatrolRoutes, TilePos};
+use crate::collision::{GROUP_SQUAD, GROUP_ZONE};
+use crate::config::GameConfig;
+use crate::door::{spawn_door, spawn_key};
+use crate::draw::get_translate_from_position;
+use crate::encounter::{spawn_alarm, spawn_spawn_point};
+use crate::lighting::{Light, LightMap};
+use crate::map::MapData;
+use crate::person::{AttitudeType, Controlled, Person};
+use crate::routine::{Routine, RoutineActivity};
+use crate::tick::AddTickSystem;
+
+// zone plugin
+// responsible for trigger zones: areas of the map that notice when
+// squad members walk in or out of them, e.g. ambushes, extractions and objectives
+// zones are sensor colliders in the physics world, and are either spawned
+// by the encounter system or loaded from the object layers of the tiled map
+pub struct ZonePlugin;
+
+// implementation of the plugin trait,
+// required for this to be used as a plugin
+impl Plugin for ZonePlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        // add in the zone event
+        app.add_tick_event::<ZoneEvent>()
+        // initialise the loaded map zones resource
+        .init_resource::<MapZonesLoaded>()
+        // initialise the start points, filled in from the tiled map
+        .init_resource::<MapStartPoints>()
+        // add in the map zone loading system
+        .add_system(load_map_zones_system.system())
+        // add in the system moving everyone to their start points once the map has loaded
+        .add_system(start_point_system.system())
+        // turn physics proximity events into zone events
+        .add_tick_system(zone_event_system.system());
+    }
+}
+
+// zone kind enum
+// what a zone is used for
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ZoneKind {
+    // hostiles spring an ambush when the squad enters
+    Ambush,
+    // the squad can leave the map from here
+    Extraction,
+    // an area the squad needs to reach or hold
+    Objective,
+    // wakes up the stage of the encounter with the same name when the squad enters
+    Encounter,
+    // moves the squad on to the stage of the mission with the same name, once the whole squad is inside
+    Exit,
+}
+
+impl ZoneKind {
+    // reads a zone kind from the type given to an object in tiled
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "ambush" => Some(ZoneKind::Ambush),
+            "extraction" => Some(ZoneKind::Extraction),
+            "objective" => Some(ZoneKind::Objective),
+            "encounter" => Some(ZoneKind::Encounter),
+            "exit" => Some(ZoneKind::Exit),
+            _ => None,
+        }
+    }
+}
+
+// zone component
+// spawn this component along with a sensor collider to make a trigger zone
+pub struct Zone {
+    pub kind: ZoneKind,
+    pub name: String,
+}
+
+// zone event
+// sent whenever a squad member enters or exits a zone
+pub struct ZoneEvent {
+    // the zone entity
+    pub zone: Entity,
+    // what the zone is used for
+    pub kind: ZoneKind,
+    // the squad member that crossed the zone's edge
+    pub person: Entity,
+    // true if the squad member entered, false if they exited
+    pub entered: bool,
+}
+
+// map zones loaded resource
+// keeps track of whether zones have been loaded from the tiled map yet
+#[derive(Default)]
+pub struct MapZonesLoaded(pub bool);
+
+// map start points resource
+// holds where each start point authored in the tiled map is, by name
+#[derive(Default)]
+pub struct MapStartPoints(pub HashMap<String, (f32, f32)>);
+
+// gives whether a spawn point with the given name marks where people start out,
+// rather than where reinforcements come in
+pub fn is_start_point_name(name: &str) -> bool {
+    name == PLAYER_START || name.starts_with(HOSTILE_GROUP_PREFIX)
+}
+
+// start point component
+// spawn this component along with anyone who should start out at one of the tiled map's start points
+// anyone whose start point isn't in the map stays where they were spawned
+pub struct StartPoint {
+    pub name: String,
+}
+
+impl StartPoint {
+    pub fn new(name: &str) -> Self {
+        StartPoint {
+            name: name.to_string(),
+        }
+    }
+}
+
+// start point system
+// moves everyone to their start point once the map has loaded, spreading out those starting at the same one,
+// with the squad in squad order. anyone resting as part of their routine rests the same distance away as before
+pub fn start_point_system(mut commands: Commands, config: Res<GameConfig>, loaded: Res<MapZonesLoaded>, points: Res<MapStartPoints>, map: Res<MapData>, mut bodies: ResMut<RigidBodySet>,
+    mut query: Query<(Entity, &StartPoint, &mut Position, Option<&Controlled>, Option<&RigidBodyHandleComponent>, Option<&mut Routine>)>) {
+    if !loaded.0 {
+        return;
+    }
+
+    // find everyone starting at each point
+    let mut starting: HashMap<String, Vec<(i32, Entity)>> = HashMap::new();
+    for (entity, start, _pos, controlled, _body, _routine) in &mut query.iter() {
+        starting.entry(start.name.clone()).or_insert_with(Vec::new).push((controlled.map_or(i32::MAX, |controlled| controlled.squad_pos), entity));
+    }
+    // and give each of them a free tile around it
+    let mut destinations = HashMap::new();
+    for (name, mut entities) in starting.into_iter() {
+        let point = match points.0.get(&name) {
+            Some(&point) => point,
+            None => continue,
+        };
+        entities.sort_by_key(|&(squad_pos, _)| squad_pos);
+        let tiles = map.get_spread_destinations(&TilePos::from_coords(&config, point.0, point.1), entities.len());
+        for ((_, entity), tile) in entities.into_iter().zip(tiles.into_iter()) {
+            destinations.insert(entity, tile.to_coords(&config));
+        }
+    }
+
+    for (entity, _start, mut pos, _controlled, body, routine) in &mut query.iter() {
+        if let Some(&destination) = destinations.get(&entity) {
+            let offset = (destination.0 - pos.0, destination.1 - pos.1);
+            pos.0 = destination.0;
+            pos.1 = destination.1;
+            // move the physics body too, otherwise it would move them back
+            if let Some(body) = body.and_then(|body| bodies.get_mut(body.handle())) {
+                let translation = get_translate_from_position(&config, destination.0, destination.1);
+                body.set_position(Isometry::translation(translation.0, translation.1));
+            }
+            if let Some(mut routine) = routine {
+                let routine = &mut *routine;
+                for activity in [&mut routine.day, &mut routine.night].iter_mut() {
+                    if let RoutineActivity::Rest(spot) = &mut **activity {
+                        spot.0 += offset.0;
+                        spot.1 += offset.1;
+                    }
+                }
+            }
+        }
+        commands.remove_one::<StartPoint>(entity);
+    }
+}
+
+// spawns a trigger zone
+// center and size are in position coordinates
+pub fn spawn_zone(config: &GameConfig, commands: &mut Commands, kind: ZoneKind, name: String, center: (f32, f32), size: (f32, f32)) {
+    let translation = get_translate_from_position(config, center.0, center.1);
+    commands
+        .spawn((
+            Zone {
+                kind: kind,
+                name: name,
+            },
+            Position(center.0, center.1),
+            Size(size.0, size.1),
+        ))
+        .with_bundle((
+            RigidBodyBuilder::new_static().translation(translation.0, translation.1),
+            // sensors detect overlaps without pushing anything around
+            // zones only care about the squad
+            ColliderBuilder::cuboid(size.0 / 2.0, size.1 / 2.0)
+                .sensor(true)
+                .collision_groups(InteractionGroups::new(GROUP_ZONE, GROUP_SQUAD)),
+        ));
+}
+
+// load map zones system
+// spawns a zone for every object in the tiled map's object layers whose
+// type is one of the zone kinds, once the map has finished loading
+// objects whose type is "spawn" become spawn points for reinforcements, apart from those named after who starts out there,
+// e.g. "player_start" or "hostile_group_1", which become start points. objects whose type is "alarm" become alarms,
+// objects whose type is "light" become lights reaching as far as the object's edge,
+// objects whose type is "door" become doors, locked if given a "key" property, objects whose type is "key" become keys, and
+// lines or points whose type is "patrol" become patrol routes for hostiles, named after the object
+pub fn load_map_zones_system(mut commands: Commands, config: Res<GameConfig>, mut loaded: ResMut<MapZonesLoaded>, mut routes: ResMut<MapPatrolRoutes>, mut starts: ResMut<MapStartPoints>,
+    mut materials: ResMut<Assets<ColorMaterial>>, mut light: ResMut<LightMap>, maps: Res<Assets<bevy_tiled::Map>>, mut query: Query<&Handle<bevy_tiled::Map>>) {
+    if loaded.0 {
+        return;
+    }
+    for handle in &mut query.iter() {
+        if let Some(map) = maps.get(&handle) {
+            // objects are placed in tiled's pixels, convert them into game units
+            let scale = config.tile_size / map.map.tile_width as f32;
+            // points making up patrol routes, along with their object id to put them in order
+            let mut route_points: HashMap<String, Vec<(u32, (f32, f32))>> = HashMap::new();
+            for group in map.map.object_groups.iter() {
+                for object in group.objects.iter() {
+                    // patrol routes are lines or points, rather than areas
+                    if object.obj_type.to_lowercase() == "patrol" {
+                        match &object.shape {
+                            // line points are given relative to the object
+                            ObjectShape::Polyline { points } | ObjectShape::Polygon { points } => {
+                                let waypoints = points.iter().map(|point| ((object.x + point.0) * scale, (object.y + point.1) * scale)).collect();
+                                routes.0.insert(object.name.clone(), waypoints);
+                            },
+                            // points with the same name make up one route
+                            ObjectShape::Point(x, y) => {
+                                route_points.entry(object.name.clone()).or_insert_with(Vec::new).push((object.id, (x * scale, y * scale)));
+                            },
+                            _ => eprintln!("patrol route {} should be a line or points", object.name),
+                        }
+                        continue;
+                    }
+
+                    let size = (object.width * scale, object.height * scale);
+                    // tiled places objects by their top left corner
+                    let center = (object.x * scale + size.0 / 2.0, object.y * scale + size.1 / 2.0);
+                    if let Some(kind) = ZoneKind::from_name(&object.obj_type) {
+                        spawn_zone(&config, &mut commands, kind, object.name.clone(), center, size);
+                    }else if object.obj_type.to_lowercase() == "spawn" && is_start_point_name(&object.name) {
+                        // where the squad or a group of hostiles start out
+                        starts.0.insert(object.name.clone(), center);
+                    }else if object.obj_type.to_lowercase() == "spawn" {
+                        // reinforcements come in through spawn points
+                        spawn_spawn_point(&mut commands, object.name.clone(), center);
+                    }else if object.obj_type.to_lowercase() == "alarm" {
+                        // alarms hostiles run to once they've spotted the squad
+                        spawn_alarm(&mut commands, &mut materials, center);
+                    }else if object.obj_type.to_lowercase() == "door" {
+                        // doors given a key property are locked
+                        let key = match object.properties.get("key") {
+                            Some(PropertyValue::StringValue(key)) => Some(key.clone()),
+                            _ => None,
+                        };
+                        spawn_door(&config, &mut commands, &mut materials, center, size, key);
+                    }else if object.obj_type.to_lowercase() == "key" {
+                        // keys are named after the doors they open
+                        spawn_key(&mut commands, &mut materials, object.name.clone(), center);
+                    }else if object.obj_type.to_lowercase() == "light" {
+                        // lights shine as far as the object reaches
+                        light.lights.push(Light {
+                            position: center,
+                            radius: size.0.max(size.1) / 2.0,
+                        });
+                    }
+                }
+            }
+            for (name, mut points) in route_points.into_iter() {
+                points.sort_by_key(|&(id, _)| id);
+                routes.0.insert(name, points.into_iter().map(|(_, point)| point).collect());
+            }
+            loaded.0 = true;
+        }
+    }
+}
+
+// zone event system
+// reads proximity events from the physics world and sends a zone event
+// whenever a squad member crosses into or out of a zone
+pub fn zone_event_system(physics_events: Res<EventQueue>, mut zone_events: ResMut<Events<ZoneEvent>>,
+    mut zones: Query<(Entity, &Zone, &ColliderHandleComponent)>,
+    mut persons: Query<(Entity, &Person, &ColliderHandleComponent)>) {
+    while let Ok(proximity) = physics_events.proximity_events.pop() {
+        // proximity events don't say which collider is the zone, so check both ways round
+        for &(zone_handle, person_handle) in [(proximity.collider1, proximity.collider2), (proximity.collider2, proximity.collider1)].iter() {
+            let mut zone = None;
+            for (entity, zone_data, handle) in &mut zones.iter() {
+                if handle.handle() == zone_handle {
+                    zone = Some((entity, zone_data.kind));
+                    break;
+                }
+            }
+            let mut person = None;
+            for (entity, pers, handle) in &mut persons.iter() {
+                if handle.handle() == person_handle && pers.attitude == AttitudeType::Squad {
+                    person = Some(entity);
+                    break;
+                }
+            }
+
+            if let (Some((zone, kind)), Some(person)) = (zone, person) {
+                let entered = match (proximity.prev_status, proximity.new_status) {
+                    (Proximity::Disjoint, Proximity::Intersecting) => Some(true),
+                    (Proximity::Intersecting, Proximity::Disjoint) => Some(false),
+                    _ => None,
+                };
+                if let Some(entered) = entered {
+                    zone_events.send(ZoneEvent {
+                        zone: zone,
+                        kind: kind,
+                        person: person,
+                        entered: entered,
+                    });
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,8 @@
+// key binding tests
+// no two things should be bound to the same key
+use mercenaries::KeyBindings;
+
+#[test]
+fn default_key_bindings_do_not_clash() {
+    assert_eq!(KeyBindings::default().clashes(), Vec::new());
+}
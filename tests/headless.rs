@@ -0,0 +1,41 @@
+// headless tests
+// a headless run with a fixed seed should always play out the same way
+use mercenaries::headless::{HeadlessReport, HeadlessScenario, run_headless};
+
+// runs a short encounter, with the player ordered across the map part way through
+// and two squad members sent after the hostile nearest them
+fn run_seeded(seed: u64) -> HeadlessReport {
+    let commands = HeadlessScenario::parse_commands("30 0 move 400 300\n10 2 attack 400 200\n10 3 attack 400 200\n");
+    let mut scenario = HeadlessScenario::new(600, commands);
+    scenario.seed = seed;
+    run_headless(scenario)
+}
+
+#[test]
+fn headless_run_is_deterministic() {
+    let first = run_seeded(7);
+    let second = run_seeded(7);
+
+    assert_eq!(first.squad, second.squad);
+    assert_eq!(first.hostiles, second.hostiles);
+}
+
+#[test]
+fn headless_run_plays_out() {
+    let report = run_seeded(7);
+
+    // the player got to where they were ordered
+    let (_, pos) = report.squad.iter().find(|(squad_pos, _)| *squad_pos == 0).unwrap();
+    let tolerance = report.config.tile_size * 2.0;
+    assert!((pos.0 - 400.0).abs() <= tolerance && (pos.1 - 300.0).abs() <= tolerance, "player ended up at {:?}", pos);
+    // and the hostile that was attacked is gone
+    assert_eq!(report.hostiles.len(), 1);
+}
+
+#[test]
+fn headless_runs_differ_by_seed() {
+    let first = run_seeded(7);
+    let other = run_seeded(8);
+
+    assert_ne!((first.squad, first.hostiles), (other.squad, other.hostiles));
+}
@@ -0,0 +1,26 @@
+// plugin tests
+// every plugin should work in an app of its own, given only the stages from the pipeline plugin
+use bevy::prelude::*;
+
+use mercenaries::{MovingPlugin, PipelinePlugin, Position, SIM_TICK, Velocity};
+
+// moves the time on by one tick every update, as there's no time plugin to do it
+fn tick_time_system(mut time: ResMut<Time>) {
+    time.delta_seconds = SIM_TICK;
+}
+
+#[test]
+fn moving_plugin_moves_by_velocity() {
+    let mut builder = App::build();
+    builder.add_resource(Time::default())
+        .add_plugin(PipelinePlugin)
+        .add_plugin(MovingPlugin)
+        .add_system_to_stage(stage::FIRST, tick_time_system.system());
+    let mut app = builder.app;
+    let entity = app.world.spawn((Position(0.0, 0.0), Velocity(10.0, -5.0)));
+
+    app.update();
+
+    let pos = app.world.get::<Position>(entity).unwrap();
+    assert_eq!((pos.0, pos.1), (10.0 * SIM_TICK, -5.0 * SIM_TICK));
+}